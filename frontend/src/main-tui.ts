@@ -35,6 +35,9 @@ const terminalEl = document.getElementById('terminal')!;
         console.log('[Main] Launching TUI...');
         console.log(`[Main] JSPI support: ${hasJSPI ? 'YES' : 'NO'}`);
 
+        // ?fresh in the URL starts without restoring the previous session
+        const tuiArgs = new URLSearchParams(window.location.search).has('fresh') ? ['--fresh'] : [];
+
         let terminalInstance;
         // Shared reference for relay setup (set in whichever code path runs)
         let sandboxFetchForRelay: ((input: string, init?: RequestInit) => Promise<Response>) | null = null;
@@ -118,7 +121,7 @@ const terminalEl = document.getElementById('terminal')!;
             bridge.handleResize(terminal.cols, terminal.rows);
 
             // Run the TUI module
-            bridge.runModule('tui');
+            bridge.runModule('tui', tuiArgs);
 
         } else {
             console.log('[Main] JSPI supported, launching direct WASM...');
@@ -129,6 +132,7 @@ const terminalEl = document.getElementById('terminal')!;
             const { terminal } = await launchTui({
                 container: terminalEl,
                 fontSize: 14,
+                args: tuiArgs,
                 theme: {
                     background: '#1a1b26',
                     foreground: '#a9b1d6',
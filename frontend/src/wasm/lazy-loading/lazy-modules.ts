@@ -92,6 +92,7 @@ export function registerAllModules(): void {
 export const LAZY_COMMANDS: Record<string, string> = {
     'tsx': 'tsx-engine',
     'tsc': 'tsx-engine',
+    'tsx-test': 'tsx-engine',
     'sqlite3': 'sqlite-module',
     'git': 'git-module',
     // Interactive TUI demos
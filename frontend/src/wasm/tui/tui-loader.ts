@@ -12,7 +12,7 @@ import { init as initGhostty, Terminal } from 'ghostty-web';
 import { run } from '../web-agent-tui/web-agent-tui.js';
 
 // Import the CLI shim to set up the terminal
-import { setTerminal, setTerminalSize } from '@tjfontaine/wasi-shims/ghostty-cli-shim.js';
+import { setArguments, setTerminal, setTerminalSize } from '@tjfontaine/wasi-shims/ghostty-cli-shim.js';
 
 // Import transport handler for routing MCP requests  
 import { setTransportHandler } from '@tjfontaine/wasi-shims/wasi-http-impl.js';
@@ -26,6 +26,8 @@ import { initFilesystem } from '@tjfontaine/wasi-shims/opfs-filesystem-impl.js';
 export interface TuiLoaderOptions {
     container: HTMLElement;
     fontSize?: number;
+    /** Extra command-line arguments for the TUI (e.g. `--fresh`) */
+    args?: string[];
     theme?: {
         background?: string;
         foreground?: string;
@@ -201,6 +203,7 @@ export async function launchTui(options: TuiLoaderOptions): Promise<{
     };
 
     // Run the TUI (async)
+    setArguments(['web-agent-tui', ...(options.args ?? [])]);
     run().then(exitCode => {
         console.log('TUI exited with code:', exitCode);
     }).catch(err => {
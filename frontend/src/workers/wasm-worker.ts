@@ -437,6 +437,8 @@ self.onmessage = async (event: MessageEvent<WorkerMessage>) => {
                     self.postMessage({ type: 'started', module: msg.module });
 
                     // Run the TUI
+                    const { setArguments } = await import('@tjfontaine/wasi-shims/ghostty-cli-shim.js');
+                    setArguments(['web-agent-tui', ...(msg.args ?? [])]);
                     try {
                         const exitCode = tuiModule.run();
                         console.log('[WasmWorker] TUI exited with code:', exitCode);
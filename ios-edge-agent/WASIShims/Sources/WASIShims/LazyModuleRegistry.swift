@@ -18,6 +18,7 @@ public final class LazyModuleRegistry: @unchecked Sendable {
     private static let commandModules: [String: String] = [
         "tsx": "tsx-engine",
         "tsc": "tsx-engine",
+        "tsx-test": "tsx-engine",
         "sqlite3": "sqlite-module",
         "ratatui-demo": "ratatui-demo",
         "tui-demo": "ratatui-demo",
@@ -59,6 +59,7 @@ const loadingPromises: Map<string, Promise<CommandModule>> = new Map();
 export const LAZY_COMMANDS: Record<string, string> = {
     'tsx': 'tsx-engine',
     'tsc': 'tsx-engine',
+    'tsx-test': 'tsx-engine',
     'sqlite3': 'sqlite-module',
     'git': 'git-module',
     // Interactive TUI demos
@@ -717,7 +717,7 @@ class OpfsDescriptor {
         _newPath: string
     ): void {
         console.warn('[opfs-fs] linkAt: hard links not supported');
-        throw 'not-supported';
+        throw 'unsupported';
     }
 
     /**
@@ -2,7 +2,7 @@
  * @tjfontaine/wasm-tsx
  * 
  * TSX/TypeScript engine module for the WASM shell.
- * Provides 'tsx' and 'tsc' commands.
+ * Provides 'tsx', 'tsc', and 'tsx-test' commands.
  * 
  * NOTE: This package exports only metadata. The loader is provided by
  * the consuming application (e.g., frontend/lazy-modules.ts) to avoid
@@ -19,6 +19,7 @@ export const metadata: ModuleMetadata = {
     commands: [
         { name: 'tsx', mode: 'buffered' },
         { name: 'tsc', mode: 'buffered' },
+        { name: 'tsx-test', mode: 'buffered' },
     ],
 };
 
@@ -47,8 +47,12 @@ interface WasmModule {
     sendMessage(handle: AgentHandle, message: string): void;
     poll(handle: AgentHandle): WasmAgentEvent | undefined;
     cancel(handle: AgentHandle): void;
+    approveTool(handle: AgentHandle): void;
+    denyTool(handle: AgentHandle, reason: string): void;
     getHistory(handle: AgentHandle): WasmMessage[];
     clearHistory(handle: AgentHandle): void;
+    exportHistory(handle: AgentHandle): string;
+    importHistory(handle: AgentHandle, json: string): void;
 }
 
 /**
@@ -81,12 +85,18 @@ function mapEvent(event: WasmAgentEvent): AgentEvent {
             return { type: 'chunk', text: event.val };
         case 'stream-complete':
             return { type: 'complete', text: event.val };
+        case 'usage':
+            return { type: 'usage', usage: event.val };
         case 'stream-error':
             return { type: 'error', error: event.val };
+        case 'retrying':
+            return { type: 'retrying', retry: event.val };
         case 'tool-call':
             return { type: 'tool-call', toolName: event.val };
         case 'tool-result':
             return { type: 'tool-result', data: event.val };
+        case 'tool-approval-request':
+            return { type: 'tool-approval-request', request: event.val };
         case 'plan-generated':
             return { type: 'plan-generated', plan: event.val };
         case 'task-start':
@@ -115,8 +125,12 @@ function toWasmConfig(config: AgentConfig): WasmAgentConfig {
         baseUrl: config.baseUrl,
         preamble: config.preamble,
         preambleOverride: config.preambleOverride,
+        protocol: config.protocol,
         mcpServers: config.mcpServers?.map(s => ({ url: s.url, name: s.name })),
         maxTurns: config.maxTurns,
+        maxRetries: config.maxRetries,
+        retryBaseDelayMs: config.retryBaseDelayMs,
+        requireToolApproval: config.requireToolApproval,
     };
 }
 
@@ -224,6 +238,25 @@ export class WebAgent {
         }
     }
 
+    /**
+     * Run the tool call from the last 'tool-approval-request' event
+     */
+    approveTool(): void {
+        if (this.handle && this.wasm) {
+            this.wasm.approveTool(this.handle);
+        }
+    }
+
+    /**
+     * Reject the tool call from the last 'tool-approval-request' event.
+     * The model is told the reason and carries on.
+     */
+    denyTool(reason: string): void {
+        if (this.handle && this.wasm) {
+            this.wasm.denyTool(this.handle, reason);
+        }
+    }
+
     /**
      * Get conversation history
      */
@@ -243,6 +276,28 @@ export class WebAgent {
         }
     }
 
+    /**
+     * Export conversation history as JSON, for resuming the session later.
+     * The shape is a stable array of `{ role: 'user' | 'assistant', content: string }`.
+     */
+    exportHistory(): string {
+        if (!this.handle || !this.wasm) {
+            throw new Error('Agent not initialized. Call initialize() first.');
+        }
+        return this.wasm.exportHistory(this.handle);
+    }
+
+    /**
+     * Replace conversation history with JSON from exportHistory().
+     * Throws if the JSON is malformed or a message has an unknown role.
+     */
+    importHistory(json: string): void {
+        if (!this.handle || !this.wasm) {
+            throw new Error('Agent not initialized. Call initialize() first.');
+        }
+        this.wasm.importHistory(this.handle, json);
+    }
+
     /**
      * Destroy the agent and release resources
      */
@@ -31,10 +31,14 @@ export { WebAgent } from './agent.js';
 export type {
     AgentConfig,
     AgentEvent,
+    AgentProtocol,
     Message,
     MessageRole,
     ToolResultData,
     TaskInfo,
     TaskUpdateInfo,
     TaskCompleteInfo,
+    TokenUsage,
+    RetryInfo,
+    ToolApprovalRequest,
 } from './types.js';
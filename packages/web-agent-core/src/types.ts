@@ -18,7 +18,7 @@ export interface McpServerConfig {
  * Agent configuration
  */
 export interface AgentConfig {
-    /** AI provider: 'anthropic', 'openai', 'gemini', etc. */
+    /** AI provider: 'anthropic', 'openai', 'gemini', 'ollama', etc. */
     provider: string;
     /** Model name: 'claude-3-5-sonnet-20241022', 'gpt-4', etc. */
     model: string;
@@ -30,12 +30,28 @@ export interface AgentConfig {
     preamble?: string;
     /** Optional complete override of the built-in preamble (mutually exclusive with preamble) */
     preambleOverride?: string;
+    /**
+     * Built-in preamble to use (default: 'plan-execute'). 'chat' drops the
+     * [ACTION: PLAN]/[ACTION: EXECUTE] protocol rules for plain chat.
+     */
+    protocol?: AgentProtocol;
     /** List of MCP servers to connect to (enables tool calling) */
     mcpServers?: McpServerConfig[];
     /** Maximum number of tool turns before stopping (default: 25) */
     maxTurns?: number;
+    /** Retries after a transient provider error (429/5xx) before failing (default: 3) */
+    maxRetries?: number;
+    /** Delay before the first retry in milliseconds, doubling for each retry (default: 1000) */
+    retryBaseDelayMs?: number;
+    /** Pause before each tool call until approveTool() or denyTool() (default: false) */
+    requireToolApproval?: boolean;
 }
 
+/**
+ * Conversation protocol taught by the built-in preamble
+ */
+export type AgentProtocol = 'plan-execute' | 'chat';
+
 /**
  * Message role in conversation
  */
@@ -58,6 +74,15 @@ export interface ToolResultData {
     isError: boolean;
 }
 
+/**
+ * A tool call awaiting approveTool() or denyTool()
+ */
+export interface ToolApprovalRequest {
+    name: string;
+    /** Tool arguments as JSON */
+    arguments: string;
+}
+
 /**
  * Task information for task-based UI
  */
@@ -95,6 +120,27 @@ export interface ModelLoadingProgress {
     progress: number;
 }
 
+/**
+ * A transient provider error that will be retried
+ */
+export interface RetryInfo {
+    /** Retry number, starting at 1 */
+    attempt: number;
+    maxRetries: number;
+    /** Delay before this retry is sent */
+    delayMs: number;
+    /** The error being retried */
+    error: string;
+}
+
+/**
+ * Token usage for a completed stream, summed over all tool turns
+ */
+export interface TokenUsage {
+    inputTokens: number;
+    outputTokens: number;
+}
+
 /**
  * Events emitted during agent streaming
  */
@@ -103,10 +149,13 @@ export type AgentEvent =
     | { type: 'stream-start' }
     | { type: 'chunk'; text: string }
     | { type: 'complete'; text: string }
+    | { type: 'usage'; usage: TokenUsage }
     | { type: 'error'; error: string }
+    | { type: 'retrying'; retry: RetryInfo }
     // Tool events
     | { type: 'tool-call'; toolName: string }
     | { type: 'tool-result'; data: ToolResultData }
+    | { type: 'tool-approval-request'; request: ToolApprovalRequest }
     // Task lifecycle events
     | { type: 'plan-generated'; plan: string }
     | { type: 'task-start'; task: TaskInfo }
@@ -132,8 +181,12 @@ export interface WasmAgentConfig {
     baseUrl?: string;
     preamble?: string;
     preambleOverride?: string;
+    protocol?: AgentProtocol;
     mcpServers?: WasmMcpServerConfig[];
     maxTurns?: number;
+    maxRetries?: number;
+    retryBaseDelayMs?: number;
+    requireToolApproval?: boolean;
 }
 
 export interface WasmMessage {
@@ -146,13 +199,16 @@ export type WasmAgentEvent =
     | { tag: 'stream-chunk'; val: string }
     | { tag: 'stream-complete'; val: string }
     | { tag: 'stream-error'; val: string }
+    | { tag: 'retrying'; val: { attempt: number; maxRetries: number; delayMs: number; error: string } }
     | { tag: 'tool-call'; val: string }
     | { tag: 'tool-result'; val: { name: string; output: string; isError: boolean } }
+    | { tag: 'tool-approval-request'; val: { name: string; arguments: string } }
     | { tag: 'plan-generated'; val: string }
     | { tag: 'task-start'; val: { id: string; name: string; description: string } }
     | { tag: 'task-update'; val: { id: string; status: string; progress?: number } }
     | { tag: 'task-complete'; val: { id: string; success: boolean; output?: string } }
     | { tag: 'model-loading'; val: { text: string; progress: number } }
+    | { tag: 'usage'; val: { inputTokens: number; outputTokens: number } }
     | { tag: 'ready' };
 
 export type AgentHandle = number;
@@ -27,6 +27,8 @@ pub struct StreamingBuffer {
     tool_activity: Arc<Mutex<Option<String>>>,
     /// Last tool result (tool_name, result, is_error)
     last_tool_result: Arc<Mutex<Option<(String, String, bool)>>>,
+    /// Token usage reported with the final response, if the provider sent it
+    usage: Arc<Mutex<Option<TokenUsage>>>,
 }
 
 impl StreamingBuffer {
@@ -39,6 +41,7 @@ impl StreamingBuffer {
             error: Arc::new(Mutex::new(None)),
             tool_activity: Arc::new(Mutex::new(None)),
             last_tool_result: Arc::new(Mutex::new(None)),
+            usage: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -122,6 +125,18 @@ impl StreamingBuffer {
             .ok()
             .and_then(|mut tr| tr.take())
     }
+
+    /// Record token usage for the completed run
+    pub fn set_usage(&self, usage: TokenUsage) {
+        if let Ok(mut u) = self.usage.lock() {
+            *u = Some(usage);
+        }
+    }
+
+    /// Get token usage, if the stream reported any
+    pub fn get_usage(&self) -> Option<TokenUsage> {
+        self.usage.lock().ok().and_then(|u| *u)
+    }
 }
 
 impl Default for StreamingBuffer {
@@ -130,6 +145,13 @@ impl Default for StreamingBuffer {
     }
 }
 
+/// Token counts for a completed multi-turn run, summed over all turns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
 /// Result of polling the stream once
 #[derive(Debug)]
 pub enum PollResult {
@@ -158,6 +180,8 @@ pub enum StreamItem {
     },
     /// Final response
     Final,
+    /// End of the multi-turn run, with its aggregated token usage
+    Usage(TokenUsage),
     /// Other content we don't handle
     Other,
 }
@@ -201,7 +225,13 @@ impl StreamItem {
                     is_error,
                 }
             }
-            MultiTurnStreamItem::FinalResponse(_) => StreamItem::Final,
+            MultiTurnStreamItem::FinalResponse(response) => {
+                let usage = response.usage();
+                StreamItem::Usage(TokenUsage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                })
+            }
             _ => StreamItem::Other,
         }
     }
@@ -317,6 +347,10 @@ impl ActiveStream {
                             StreamItem::Final => {
                                 self.buffer.set_tool_activity(None);
                             }
+                            StreamItem::Usage(usage) => {
+                                self.buffer.set_tool_activity(None);
+                                self.buffer.set_usage(usage);
+                            }
                             StreamItem::Other => {
                                 self.buffer.set_tool_activity(None);
                             }
@@ -296,6 +296,64 @@ impl Default for ConversationHistory {
     }
 }
 
+/// One message in exported history JSON
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExportedMessage {
+    role: String,
+    content: String,
+}
+
+impl ConversationHistory {
+    /// Export user/assistant messages as JSON, for persisting a session.
+    ///
+    /// The shape is stable: an array of messages, oldest first, each with a
+    /// `role` of `"user"` or `"assistant"` and its `content`:
+    ///
+    /// ```json
+    /// [{"role": "user", "content": "Hello"}, {"role": "assistant", "content": "Hi!"}]
+    /// ```
+    ///
+    /// Tool traces and system turns are not exported.
+    pub fn export_json(&self) -> String {
+        let messages: Vec<ExportedMessage> = self
+            .user_assistant_messages()
+            .into_iter()
+            .map(|turn| ExportedMessage {
+                role: match turn.role {
+                    ConversationRole::User => "user",
+                    _ => "assistant",
+                }
+                .to_string(),
+                content: turn.content.clone(),
+            })
+            .collect();
+        serde_json::to_string(&messages).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Build a history from JSON produced by [`export_json`](Self::export_json)
+    pub fn import_json(json: &str) -> Result<Self, String> {
+        let messages: Vec<ExportedMessage> =
+            serde_json::from_str(json).map_err(|e| format!("Invalid history JSON: {}", e))?;
+
+        let mut history = Self::new();
+        for (index, message) in messages.into_iter().enumerate() {
+            let turn = match message.role.as_str() {
+                "user" => ConversationTurn::user(message.content),
+                "assistant" => ConversationTurn::assistant(message.content),
+                other => {
+                    return Err(format!(
+                        "Invalid history JSON: message {} has role \"{}\" (expected \"user\" or \"assistant\")",
+                        index, other
+                    ))
+                }
+            };
+            history.append_turn(turn);
+        }
+        Ok(history)
+    }
+}
+
 /// Builder for constructing conversation views
 ///
 /// This assembles provider-ready messages with proper ordering and constraints.
@@ -483,6 +541,40 @@ mod tests {
         assert_eq!(history.state().pinned_facts.len(), 0);
     }
 
+    #[test]
+    fn test_export_import_json_round_trip() {
+        let mut history = ConversationHistory::new();
+        history.append_turn(ConversationTurn::user("Hello"));
+        history.record_tool_call("search", "call-1", "{}");
+        history.record_tool_result("call-1", "Result", false);
+        history.append_turn(ConversationTurn::assistant("Hi \"there\""));
+
+        let json = history.export_json();
+        assert_eq!(
+            json,
+            r#"[{"role":"user","content":"Hello"},{"role":"assistant","content":"Hi \"there\""}]"#
+        );
+
+        let restored = ConversationHistory::import_json(&json).unwrap();
+        assert_eq!(restored.turns().len(), 2);
+        assert_eq!(restored.turns()[0].role, ConversationRole::User);
+        assert_eq!(restored.turns()[1].content, "Hi \"there\"");
+    }
+
+    #[test]
+    fn test_import_json_rejects_malformed_input() {
+        let err = ConversationHistory::import_json(
+            r#"[{"role":"user","content":"a"},{"role":"system","content":"b"}]"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("message 1"), "{}", err);
+        assert!(err.contains("\"system\""), "{}", err);
+
+        assert!(ConversationHistory::import_json(r#"[{"role":"user"}]"#).is_err());
+        assert!(ConversationHistory::import_json(r#"{"role":"user","content":"a"}"#).is_err());
+        assert!(ConversationHistory::import_json("not json").is_err());
+    }
+
     #[test]
     fn test_pinned_facts() {
         let mut state = ConversationState::new();
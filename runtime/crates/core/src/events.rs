@@ -11,6 +11,11 @@ pub enum AgentEvent {
     StreamChunk(String),
     /// Stream completed
     StreamComplete(String),
+    /// Token usage for the completed stream
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+    },
     /// Error occurred
     StreamError(String),
     /// Tool call starting
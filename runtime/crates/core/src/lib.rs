@@ -23,8 +23,10 @@ pub mod mcp_transport;
 pub mod models;
 pub mod models_api;
 pub mod remote_mcp_client;
+pub mod retry;
 pub mod rig_agent;
 pub mod rig_tools;
+pub mod tool_approval;
 pub mod wasi_completion_model;
 pub mod wasi_http_macro;
 pub mod wasm_async;
@@ -33,7 +35,7 @@ pub mod wasm_async;
 pub use active_stream::StreamItem;
 pub use active_stream::{
     erase_stream, ActiveStream, ActiveStreamState, ErasedConnectFuture, ErasedStream,
-    ErasedStreamResult, PollResult, StreamingBuffer,
+    ErasedStreamResult, PollResult, StreamingBuffer, TokenUsage,
 };
 pub use conversation::{
     ConversationHistory, ConversationRole, ConversationState, ConversationTurn, ConversationView,
@@ -55,10 +57,12 @@ pub use mcp_transport::{
 pub use models::{get_models_for_provider, ModelInfo, ProviderInfo, PROVIDERS};
 pub use models_api::{fetch_models_for_provider, FetchedModel, ModelFetchHttp};
 pub use remote_mcp_client::RemoteMcpClient;
+pub use retry::{is_retriable_error, RetryPolicy};
 pub use rig_agent::{process_stream, EventCollector, StreamEventHandler};
 pub use rig_tools::{build_tool_set, McpToolAdapter};
+pub use tool_approval::{ApprovalGatedTool, ToolApprovalGate, ToolApprovalRequest};
 pub use wasi_completion_model::{
-    create_anthropic_client, create_gemini_client, create_openai_client, AnthropicClient,
-    AnthropicModel, GeminiClient, GeminiModel, OpenAIClient, OpenAIModel,
+    create_anthropic_client, create_gemini_client, create_ollama_client, create_openai_client,
+    AnthropicClient, AnthropicModel, GeminiClient, GeminiModel, OpenAIClient, OpenAIModel,
 };
 pub use wasm_async::wasm_block_on;
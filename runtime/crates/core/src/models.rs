@@ -26,6 +26,9 @@ pub struct ModelInfo {
     pub name: &'static str,
 }
 
+/// Default endpoint of a local Ollama server's OpenAI-compatible API
+pub const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
+
 /// Available AI providers
 pub const PROVIDERS: &[ProviderInfo] = &[
     ProviderInfo {
@@ -52,6 +55,12 @@ pub const PROVIDERS: &[ProviderInfo] = &[
         default_base_url: Some("https://openrouter.ai/api/v1"),
         api_format: "openai",
     },
+    ProviderInfo {
+        id: "ollama",
+        name: "Ollama (Local)",
+        default_base_url: Some(OLLAMA_DEFAULT_BASE_URL),
+        api_format: "openai",
+    },
     ProviderInfo {
         id: "custom",
         name: "Custom (OpenAI-compatible)",
@@ -65,6 +74,18 @@ pub fn get_provider(provider_id: &str) -> Option<&'static ProviderInfo> {
     PROVIDERS.iter().find(|p| p.id == provider_id)
 }
 
+/// Base URL for Ollama's OpenAI-compatible API.
+///
+/// Falls back to [`OLLAMA_DEFAULT_BASE_URL`], and accepts the bare server
+/// address (`http://host:11434`) by adding the `/v1` the API lives under.
+pub fn ollama_base_url(base_url: Option<&str>) -> String {
+    match base_url.map(|url| url.trim_end_matches('/')) {
+        None | Some("") => OLLAMA_DEFAULT_BASE_URL.to_string(),
+        Some(url) if url.ends_with("/v1") => url.to_string(),
+        Some(url) => format!("{}/v1", url),
+    }
+}
+
 /// Get default model for a provider (first in the list)
 pub fn get_default_model(provider_id: &str) -> Option<ModelInfo> {
     get_models_for_provider(provider_id).into_iter().next()
@@ -195,6 +216,21 @@ pub fn get_models_for_provider(provider: &str) -> Vec<ModelInfo> {
                 name: "Gemini 3 Flash",
             },
         ],
+        // Whatever has been pulled locally; these are common starting points
+        "ollama" => vec![
+            ModelInfo {
+                id: "llama3.2",
+                name: "Llama 3.2",
+            },
+            ModelInfo {
+                id: "qwen2.5-coder",
+                name: "Qwen 2.5 Coder",
+            },
+            ModelInfo {
+                id: "mistral",
+                name: "Mistral",
+            },
+        ],
         _ => vec![],
     }
 }
@@ -231,6 +267,20 @@ mod tests {
         assert_eq!(models.len(), google_models.len());
     }
 
+    #[test]
+    fn test_ollama_base_url() {
+        assert_eq!(ollama_base_url(None), OLLAMA_DEFAULT_BASE_URL);
+        assert_eq!(ollama_base_url(Some("")), OLLAMA_DEFAULT_BASE_URL);
+        assert_eq!(
+            ollama_base_url(Some("http://gpu-box:11434")),
+            "http://gpu-box:11434/v1"
+        );
+        assert_eq!(
+            ollama_base_url(Some("http://gpu-box:11434/v1/")),
+            "http://gpu-box:11434/v1"
+        );
+    }
+
     #[test]
     fn test_unknown_provider_returns_empty() {
         let models = get_models_for_provider("unknown_provider");
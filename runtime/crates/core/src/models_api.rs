@@ -5,7 +5,7 @@
 //!
 //! For static fallback lists, see [`super::models`].
 
-use crate::models::ModelInfo;
+use crate::models::{ollama_base_url, ModelInfo};
 use serde::Deserialize;
 
 /// Owned version of ModelInfo for dynamic fetching
@@ -118,6 +118,33 @@ pub fn fetch_openai_models<H: ModelFetchHttp>(
     Ok(models)
 }
 
+/// Fetch models pulled into a local Ollama server
+///
+/// Uses the OpenAI-compatible listing, but keeps every model: local model
+/// names follow no common prefix. Ollama needs no API key.
+pub fn fetch_ollama_models<H: ModelFetchHttp>(
+    http: &H,
+    base_url: Option<&str>,
+) -> Result<Vec<FetchedModel>, String> {
+    let url = format!("{}/models", ollama_base_url(base_url));
+
+    let response = http
+        .get_json(&url, &[])
+        .map_err(|e| format!("HTTP error: {}", e))?;
+
+    let models_response: OpenAIModelsResponse =
+        serde_json::from_value(response).map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    Ok(models_response
+        .data
+        .into_iter()
+        .map(|m| FetchedModel {
+            name: m.id.clone(),
+            id: m.id,
+        })
+        .collect())
+}
+
 /// Fetch models from Anthropic API
 pub fn fetch_anthropic_models<H: ModelFetchHttp>(
     http: &H,
@@ -205,6 +232,7 @@ pub fn fetch_models_for_provider<H: ModelFetchHttp>(
     match provider {
         "anthropic" => fetch_anthropic_models(http, api_key, base_url),
         "gemini" | "google" => fetch_gemini_models(http, api_key, base_url),
+        "ollama" => fetch_ollama_models(http, base_url),
         "openai" | "custom" | "openrouter" => fetch_openai_models(http, api_key, base_url),
         _ => fetch_openai_models(http, api_key, base_url),
     }
@@ -245,6 +273,23 @@ mod tests {
         assert!(models.iter().any(|m| m.id == "gpt-4o"));
     }
 
+    #[test]
+    fn test_parse_ollama_response_keeps_all_models() {
+        let mock = MockHttp {
+            response: serde_json::json!({
+                "object": "list",
+                "data": [
+                    {"id": "llama3.2:latest", "object": "model"},
+                    {"id": "qwen2.5-coder:7b", "object": "model"},
+                ]
+            }),
+        };
+
+        let models = fetch_models_for_provider(&mock, "ollama", "", None).unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].id, "llama3.2:latest");
+    }
+
     #[test]
     fn test_parse_anthropic_response() {
         let mock = MockHttp {
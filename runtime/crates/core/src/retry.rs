@@ -0,0 +1,135 @@
+//! Retry policy for transient LLM provider errors
+//!
+//! Providers fail transiently with rate limits (429) and server errors (5xx).
+//! Callers retry the request with exponential backoff; errors that won't go
+//! away on their own (bad API key, malformed request) fail fast.
+
+use std::time::Duration;
+
+/// Default number of retries after the first attempt
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Default delay before the first retry
+pub const DEFAULT_RETRY_BASE_DELAY_MS: u32 = 1000;
+
+/// Upper bound on a single backoff delay
+const MAX_RETRY_DELAY_MS: u64 = 60_000;
+
+/// How often and how patiently to retry a failed request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles for each one after
+    pub base_delay_ms: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay_ms: DEFAULT_RETRY_BASE_DELAY_MS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy from optional config values, using the defaults for any unset
+    pub fn from_config(max_retries: Option<u32>, base_delay_ms: Option<u32>) -> Self {
+        Self {
+            max_retries: max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            base_delay_ms: base_delay_ms.unwrap_or(DEFAULT_RETRY_BASE_DELAY_MS),
+        }
+    }
+
+    /// Delay before retry number `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        let ms = (self.base_delay_ms as u64 * factor).min(MAX_RETRY_DELAY_MS);
+        Duration::from_millis(ms)
+    }
+
+    /// Whether a request that has failed `attempt` times with `error` should
+    /// be tried again
+    pub fn should_retry(&self, attempt: u32, error: &str) -> bool {
+        attempt <= self.max_retries && is_retriable_error(error)
+    }
+}
+
+/// HTTP statuses worth retrying: rate limiting and server-side failures
+const RETRIABLE_STATUSES: &[u32] = &[408, 429, 500, 502, 503, 504, 529];
+
+/// Whether a provider error message describes a transient failure.
+///
+/// Errors reach us as display strings, so this looks for a retriable HTTP
+/// status or a well-known transient phrase. Authentication failures never
+/// retry, even if the message also mentions something transient.
+pub fn is_retriable_error(error: &str) -> bool {
+    let lower = error.to_lowercase();
+    let statuses: Vec<u32> = lower
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|word| word.parse().ok())
+        .collect();
+
+    let auth_failure = statuses.iter().any(|s| matches!(s, 401 | 403))
+        || ["unauthorized", "forbidden", "api key", "api-key"]
+            .iter()
+            .any(|marker| lower.contains(marker));
+    if auth_failure {
+        return false;
+    }
+
+    statuses.iter().any(|s| RETRIABLE_STATUSES.contains(s))
+        || [
+            "rate limit",
+            "overloaded",
+            "timed out",
+            "timeout",
+            "connection reset",
+            "temporarily unavailable",
+        ]
+        .iter()
+        .any(|phrase| lower.contains(phrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retriable_errors() {
+        assert!(is_retriable_error("HTTP 429 Too Many Requests"));
+        assert!(is_retriable_error("ProviderError: status 503"));
+        assert!(is_retriable_error("overloaded_error: Overloaded"));
+        assert!(is_retriable_error("request timed out"));
+
+        assert!(!is_retriable_error("HTTP 401: invalid x-api-key"));
+        assert!(!is_retriable_error("403 Forbidden (rate limit tier)"));
+        assert!(!is_retriable_error("400 Bad Request: max_tokens too large"));
+        // Digits inside other numbers are not statuses
+        assert!(!is_retriable_error("context is 15000 tokens"));
+    }
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RetryPolicy {
+            max_retries: 20,
+            base_delay_ms: 500,
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_millis(1000));
+        assert_eq!(policy.delay(3), Duration::from_millis(2000));
+        assert_eq!(policy.delay(20), Duration::from_millis(MAX_RETRY_DELAY_MS));
+    }
+
+    #[test]
+    fn test_should_retry_respects_max() {
+        let policy = RetryPolicy::from_config(Some(2), None);
+        assert!(policy.should_retry(1, "429"));
+        assert!(policy.should_retry(2, "429"));
+        assert!(!policy.should_retry(3, "429"));
+
+        let disabled = RetryPolicy::from_config(Some(0), None);
+        assert!(!disabled.should_retry(1, "429"));
+    }
+}
@@ -50,7 +50,7 @@ where
                 StreamItem::ToolResult { .. } => {
                     handler.on_tool_result();
                 }
-                StreamItem::Final => {
+                StreamItem::Final | StreamItem::Usage(_) => {
                     break;
                 }
                 StreamItem::Other => {}
@@ -0,0 +1,205 @@
+//! Tool approval - lets the embedding app allow or reject each tool call
+//!
+//! [`ApprovalGatedTool`] wraps a tool so that calling it first queues a
+//! [`ToolApprovalRequest`] on a shared [`ToolApprovalGate`] and waits. The
+//! agent's stream simply stays pending meanwhile, so no extra state is needed
+//! in the polling loop: the app sees the request via [`ToolApprovalGate::pending`]
+//! and resumes the stream with [`ToolApprovalGate::approve`] or
+//! [`ToolApprovalGate::deny`].
+
+use rig::completion::ToolDefinition as RigToolDefinition;
+use rig::tool::{ToolDyn, ToolError};
+use rig::wasm_compat::WasmBoxedFuture;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// A tool call waiting for the app's decision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolApprovalRequest {
+    /// Distinguishes repeated calls with the same name and arguments
+    pub id: u64,
+    pub name: String,
+    /// Arguments as the model sent them (JSON)
+    pub arguments: String,
+}
+
+#[derive(Default)]
+struct GateState {
+    next_id: u64,
+    /// Requests awaiting a decision, oldest first
+    queue: VecDeque<ToolApprovalRequest>,
+    /// Decisions not yet picked up by their waiting call
+    decisions: HashMap<u64, Result<(), String>>,
+    wakers: HashMap<u64, Waker>,
+}
+
+/// Shared queue of tool calls awaiting approval
+#[derive(Clone, Default)]
+pub struct ToolApprovalGate {
+    state: Arc<Mutex<GateState>>,
+}
+
+impl ToolApprovalGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a call for approval. The future resolves to `Ok(())` once
+    /// approved, or `Err(reason)` once denied.
+    pub fn request(&self, name: &str, arguments: &str) -> ApprovalFuture {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.queue.push_back(ToolApprovalRequest {
+            id,
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        });
+        ApprovalFuture {
+            gate: self.clone(),
+            id,
+        }
+    }
+
+    /// The oldest call awaiting a decision
+    pub fn pending(&self) -> Option<ToolApprovalRequest> {
+        self.lock().queue.front().cloned()
+    }
+
+    /// Allow the oldest pending call. Returns false if none is pending.
+    pub fn approve(&self) -> bool {
+        self.decide(Ok(()))
+    }
+
+    /// Reject the oldest pending call. Returns false if none is pending.
+    pub fn deny(&self, reason: impl Into<String>) -> bool {
+        self.decide(Err(reason.into()))
+    }
+
+    /// Reject every pending call (e.g. when the stream is cancelled)
+    pub fn deny_all(&self, reason: &str) {
+        while self.deny(reason) {}
+    }
+
+    fn decide(&self, decision: Result<(), String>) -> bool {
+        let mut state = self.lock();
+        let Some(request) = state.queue.pop_front() else {
+            return false;
+        };
+        state.decisions.insert(request.id, decision);
+        if let Some(waker) = state.wakers.remove(&request.id) {
+            waker.wake();
+        }
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GateState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Resolves when the app decides on a queued tool call
+pub struct ApprovalFuture {
+    gate: ToolApprovalGate,
+    id: u64,
+}
+
+impl Future for ApprovalFuture {
+    type Output = Result<(), String>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.gate.lock();
+        match state.decisions.remove(&self.id) {
+            Some(decision) => Poll::Ready(decision),
+            None => {
+                state.wakers.insert(self.id, cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A tool that only runs once its call has been approved.
+///
+/// A denied call is not an error for the agent loop: the model gets a tool
+/// result explaining the denial so it can adapt.
+pub struct ApprovalGatedTool<T: ToolDyn> {
+    inner: T,
+    gate: ToolApprovalGate,
+}
+
+impl<T: ToolDyn> ApprovalGatedTool<T> {
+    pub fn new(inner: T, gate: ToolApprovalGate) -> Self {
+        Self { inner, gate }
+    }
+}
+
+impl<T: ToolDyn> ToolDyn for ApprovalGatedTool<T> {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn definition<'a>(&'a self, prompt: String) -> WasmBoxedFuture<'a, RigToolDefinition> {
+        self.inner.definition(prompt)
+    }
+
+    fn call<'a>(&'a self, args: String) -> WasmBoxedFuture<'a, Result<String, ToolError>> {
+        Box::pin(async move {
+            let name = self.inner.name();
+            match self.gate.request(&name, &args).await {
+                Ok(()) => self.inner.call(args).await,
+                Err(reason) => Ok(format!(
+                    "Error: the user denied this call to {}: {}",
+                    name, reason
+                )),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::task::noop_waker;
+
+    fn poll_once(future: &mut ApprovalFuture) -> Poll<Result<(), String>> {
+        let waker = noop_waker();
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    #[test]
+    fn test_calls_wait_for_decisions_in_order() {
+        let gate = ToolApprovalGate::new();
+        let mut first = gate.request("write_file", r#"{"path":"/a"}"#);
+        let mut second = gate.request("write_file", r#"{"path":"/a"}"#);
+
+        assert!(poll_once(&mut first).is_pending());
+        let pending = gate.pending().unwrap();
+        assert_eq!(pending.name, "write_file");
+        assert_eq!(pending.id, 0);
+
+        assert!(gate.approve());
+        assert_eq!(poll_once(&mut first), Poll::Ready(Ok(())));
+        assert_eq!(gate.pending().unwrap().id, 1);
+
+        assert!(gate.deny("not now"));
+        assert_eq!(block_on(second), Err("not now".to_string()));
+        assert!(gate.pending().is_none());
+        assert!(!gate.approve());
+    }
+
+    #[test]
+    fn test_deny_all_rejects_everything_pending() {
+        let gate = ToolApprovalGate::new();
+        let first = gate.request("a", "{}");
+        let second = gate.request("b", "{}");
+        gate.deny_all("cancelled");
+        assert!(block_on(first).is_err());
+        assert!(block_on(second).is_err());
+        assert!(gate.pending().is_none());
+    }
+}
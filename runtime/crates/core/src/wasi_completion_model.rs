@@ -115,6 +115,28 @@ where
     builder.api_key(api_key).build()
 }
 
+/// Create a client for a local Ollama server's OpenAI-compatible API
+///
+/// The base URL defaults to [`crate::models::OLLAMA_DEFAULT_BASE_URL`].
+/// Ollama ignores API keys, but the client always sends one, so an empty key
+/// is replaced with a placeholder.
+pub fn create_ollama_client<H>(
+    http_client: H,
+    api_key: &str,
+    base_url: Option<&str>,
+) -> Result<OpenAIClient<H>, rig::http_client::Error>
+where
+    H: HttpClientExt + Default,
+{
+    let api_key = if api_key.is_empty() {
+        "ollama"
+    } else {
+        api_key
+    };
+    let base_url = crate::models::ollama_base_url(base_url);
+    create_openai_client(http_client, api_key, Some(&base_url))
+}
+
 /// Create a Gemini client with optional base URL
 ///
 /// # Type Parameters
@@ -19,6 +19,7 @@ use bindings::wasi::io::streams::{InputStream, OutputStream};
 use anstyle_parse::{DefaultCharAccumulator, Params, Parser, Perform};
 use ropey::Rope;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{HighlightState, Style, ThemeSet};
@@ -62,9 +63,71 @@ enum Mode {
     Command,
     Visual,
     VisualLine,
+    VisualBlock,
     Search,
 }
 
+/// Operator awaiting a motion (d, c, y)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_key(key: u8) -> Option<Self> {
+        match key {
+            b'd' => Some(Self::Delete),
+            b'c' => Some(Self::Change),
+            b'y' => Some(Self::Yank),
+            _ => None,
+        }
+    }
+}
+
+/// Pending block-visual `I`/`A`: text typed on the top line is replicated to
+/// the remaining block rows when insert mode ends.
+#[derive(Clone, Copy, Debug)]
+struct BlockInsert {
+    top: usize,
+    bottom: usize,
+    col: usize,
+    // `A` pads short lines out to the block edge; `I` skips them
+    pad: bool,
+}
+
+/// Character-find motion kind (f, F, t, T)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FindKind {
+    Forward,
+    Backward,
+    TillForward,
+    TillBackward,
+}
+
+impl FindKind {
+    fn from_key(key: u8) -> Option<Self> {
+        match key {
+            b'f' => Some(Self::Forward),
+            b'F' => Some(Self::Backward),
+            b't' => Some(Self::TillForward),
+            b'T' => Some(Self::TillBackward),
+            _ => None,
+        }
+    }
+
+    /// Direction used by `,` to repeat a find backwards
+    fn reversed(self) -> Self {
+        match self {
+            Self::Forward => Self::Backward,
+            Self::Backward => Self::Forward,
+            Self::TillForward => Self::TillBackward,
+            Self::TillBackward => Self::TillForward,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum EscapeKey {
     Up,
@@ -222,11 +285,19 @@ struct Editor {
     modified: bool,
     file_path: Option<String>,
     scroll_offset: usize,
+    // Content rows in the last drawn frame, for recentering after jumps
+    view_height: usize,
     // Undo/redo stacks (edtui-inspired)
     undo_stack: Vec<UndoState>,
     redo_stack: Vec<UndoState>,
     // Visual mode selection anchor
     selection_anchor: Option<(usize, usize)>,
+    // Block-visual I/A awaiting Escape
+    block_insert: Option<BlockInsert>,
+    // Last f/F/t/T target for ; and , repeats
+    last_find: Option<(FindKind, char)>,
+    // Named marks (m<letter>); '`' holds the position before the last jump
+    marks: HashMap<char, (usize, usize)>,
     // Yank register
     yank_buffer: String,
     yank_is_line: bool,
@@ -249,6 +320,12 @@ struct Editor {
     last_cursor_pos: (usize, usize),
     // Force full redraw on next render (e.g., after resize)
     force_full_redraw: bool,
+    // Line-number gutter (:set number / :set relativenumber)
+    show_line_numbers: bool,
+    relative_line_numbers: bool,
+    // Indentation for >> and << (:set shiftwidth / :set expandtab)
+    shiftwidth: usize,
+    expandtab: bool,
 }
 
 impl Editor {
@@ -273,9 +350,13 @@ impl Editor {
             modified: false,
             file_path,
             scroll_offset: 0,
+            view_height: 22, // 24-row terminal until the first draw
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
             selection_anchor: None,
+            block_insert: None,
+            last_find: None,
+            marks: HashMap::new(),
             yank_buffer: String::new(),
             yank_is_line: false,
             search_pattern: String::new(),
@@ -289,9 +370,39 @@ impl Editor {
             previous_buffer: ScreenBuffer::new(80, 24),
             last_cursor_pos: (0, 0),
             force_full_redraw: true, // First render is full
+            show_line_numbers: false,
+            relative_line_numbers: false,
+            shiftwidth: 4,
+            expandtab: true,
         }
     }
 
+    /// Replace the buffer with another file's contents (`:e`), resetting
+    /// per-buffer state as if the editor had been launched on it.
+    fn load_buffer(&mut self, content: String, file_path: Option<String>) {
+        self.rope = if content.is_empty() {
+            Rope::from("\n")
+        } else {
+            Rope::from(content.as_str())
+        };
+        self.file_path = file_path;
+        self.modified = false;
+        self.mode = Mode::Normal;
+        self.cursor_row = 0;
+        self.cursor_col = 0;
+        self.scroll_offset = 0;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.selection_anchor = None;
+        self.block_insert = None;
+        self.marks.clear();
+        self.search_matches.clear();
+        self.current_match_idx = None;
+        self.parse_state_cache.clear();
+        self.mark_dirty_from(0);
+        self.force_full_redraw = true;
+    }
+
     /// Mark lines as needing re-highlighting from a given line onwards.
     /// This is called after any text modification.
     fn mark_dirty_from(&mut self, line: usize) {
@@ -312,6 +423,24 @@ impl Editor {
         self.rope.len_lines().max(1)
     }
 
+    /// Width of the line-number gutter: digits of the last line number plus a separator.
+    fn gutter_width(&self) -> usize {
+        if !self.show_line_numbers && !self.relative_line_numbers {
+            return 0;
+        }
+        self.line_count().to_string().len() + 1
+    }
+
+    /// Gutter label for a row: absolute, or distance from the cursor in relative mode.
+    /// The cursor line always shows its absolute number.
+    fn line_number_label(&self, row: usize) -> usize {
+        if self.relative_line_numbers && row != self.cursor_row {
+            row.abs_diff(self.cursor_row)
+        } else {
+            row + 1
+        }
+    }
+
     fn current_line_len(&self) -> usize {
         if self.cursor_row >= self.line_count() {
             return 0;
@@ -445,7 +574,10 @@ impl Editor {
 
     /// Update selection when in visual mode
     fn update_selection(&mut self) {
-        if matches!(self.mode, Mode::Visual | Mode::VisualLine) {
+        if matches!(
+            self.mode,
+            Mode::Visual | Mode::VisualLine | Mode::VisualBlock
+        ) {
             // Selection is tracked via anchor; cursor is the other end
         }
     }
@@ -462,6 +594,133 @@ impl Editor {
         })
     }
 
+    /// Block selection rectangle as (top, bottom, left, right), all inclusive
+    fn block_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        self.selection_anchor.map(|(anchor_row, anchor_col)| {
+            (
+                anchor_row.min(self.cursor_row),
+                anchor_row.max(self.cursor_row),
+                anchor_col.min(self.cursor_col),
+                anchor_col.max(self.cursor_col),
+            )
+        })
+    }
+
+    /// Text of each block row, clipped to the line (short lines yield "")
+    fn block_text(&self, top: usize, bottom: usize, left: usize, right: usize) -> String {
+        (top..=bottom)
+            .map(|row| {
+                self.get_line(row)
+                    .chars()
+                    .skip(left)
+                    .take(right + 1 - left)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn delete_block(&mut self) {
+        let Some((top, bottom, left, right)) = self.block_bounds() else {
+            return;
+        };
+        self.capture();
+        self.yank_buffer = self.block_text(top, bottom, left, right);
+        self.yank_is_line = false;
+
+        for row in top..=bottom {
+            let len = self.get_line(row).chars().count();
+            if left >= len {
+                continue;
+            }
+            let end = (right + 1).min(len);
+            let start_idx = self.char_idx(row, left);
+            let end_idx = self.char_idx(row, end);
+            self.rope.remove(start_idx..end_idx);
+        }
+
+        self.cursor_row = top;
+        self.cursor_col = left;
+        self.selection_anchor = None;
+        self.mode = Mode::Normal;
+        self.clamp_cursor();
+        self.modified = true;
+        self.mark_dirty_from(top);
+    }
+
+    fn yank_block(&mut self) {
+        if let Some((top, bottom, left, right)) = self.block_bounds() {
+            self.yank_buffer = self.block_text(top, bottom, left, right);
+            self.yank_is_line = false;
+            self.cursor_row = top;
+            self.cursor_col = left;
+            self.selection_anchor = None;
+            self.mode = Mode::Normal;
+            self.clamp_cursor();
+            self.status_message = "Block yanked".to_string();
+        }
+    }
+
+    /// Block `I` (insert at the left edge) or `A` (append after the right edge)
+    fn start_block_insert(&mut self, append: bool) {
+        let Some((top, bottom, left, right)) = self.block_bounds() else {
+            return;
+        };
+        let col = if append { right + 1 } else { left };
+        self.capture();
+        if append {
+            self.pad_line_to(top, col);
+        }
+        self.block_insert = Some(BlockInsert {
+            top,
+            bottom,
+            col,
+            pad: append,
+        });
+        self.cursor_row = top;
+        self.cursor_col = col.min(self.get_line(top).chars().count());
+        self.selection_anchor = None;
+        self.mode = Mode::Insert;
+    }
+
+    /// Replicate the text typed during a block insert onto the other rows.
+    /// Called when insert mode ends; a typed newline cancels replication.
+    fn finish_block_insert(&mut self) {
+        let Some(block) = self.block_insert.take() else {
+            return;
+        };
+        if self.cursor_row != block.top || self.cursor_col <= block.col {
+            return;
+        }
+        let inserted: String = self
+            .get_line(block.top)
+            .chars()
+            .skip(block.col)
+            .take(self.cursor_col - block.col)
+            .collect();
+
+        for row in block.top + 1..=block.bottom.min(self.line_count().saturating_sub(1)) {
+            if block.pad {
+                self.pad_line_to(row, block.col);
+            } else if self.get_line(row).chars().count() < block.col {
+                continue;
+            }
+            let idx = self.char_idx(row, block.col);
+            self.rope.insert(idx, &inserted);
+        }
+        self.modified = true;
+        self.mark_dirty_from(block.top);
+    }
+
+    /// Extend `row` with spaces so that it is at least `col` characters long
+    fn pad_line_to(&mut self, row: usize, col: usize) {
+        let len = self.get_line(row).chars().count();
+        if len < col {
+            let idx = self.char_idx(row, len);
+            self.rope.insert(idx, &" ".repeat(col - len));
+        }
+    }
+
     /// Word motion: is this a word character?
     fn is_word_char(c: char) -> bool {
         c.is_alphanumeric() || c == '_'
@@ -570,6 +829,117 @@ impl Editor {
         self.update_selection();
     }
 
+    /// Column span `[start, end)` on the cursor line covered by `count`
+    /// repetitions of a w/e/b/$/0 motion. Spans never cross the line.
+    fn motion_span(&mut self, motion: u8, count: usize, op: Operator) -> Option<(usize, usize)> {
+        let (row, col) = (self.cursor_row, self.cursor_col);
+        let line_len = self.current_line_len();
+        let on_word = self
+            .get_line(row)
+            .chars()
+            .nth(col)
+            .is_some_and(|c| !c.is_whitespace());
+        // Like vim, `cw` on a word changes to the end of the word (as `ce`)
+        let motion = if motion == b'w' && op == Operator::Change && on_word {
+            b'e'
+        } else {
+            motion
+        };
+
+        let span = match motion {
+            b'$' => Some((col, line_len)),
+            b'0' => Some((0, col)),
+            b'w' | b'e' | b'b' => {
+                for _ in 0..count {
+                    match motion {
+                        b'w' => self.move_word_forward(),
+                        b'e' => self.move_word_end(),
+                        _ => self.move_word_backward(),
+                    }
+                }
+                let target = if self.cursor_row == row {
+                    self.cursor_col
+                } else if self.cursor_row > row {
+                    line_len
+                } else {
+                    0
+                };
+                match motion {
+                    b'w' => Some((col, target)),
+                    b'e' if self.cursor_row == row => Some((col, (target + 1).min(line_len))),
+                    b'e' => Some((col, line_len)),
+                    _ => Some((target, col)),
+                }
+            }
+            _ => None,
+        };
+
+        self.cursor_row = row;
+        self.cursor_col = col;
+        span.filter(|(start, end)| start <= end)
+    }
+
+    /// Column span `[start, end)` of the inner word under the cursor: a run
+    /// of word characters, of whitespace, or of other punctuation.
+    fn inner_word_span(&self) -> Option<(usize, usize)> {
+        let chars: Vec<char> = self.get_line(self.cursor_row).chars().collect();
+        let col = self.cursor_col;
+        let current = *chars.get(col)?;
+        let class = |c: char| {
+            if Self::is_word_char(c) {
+                0
+            } else if c.is_whitespace() {
+                1
+            } else {
+                2
+            }
+        };
+        let mut start = col;
+        while start > 0 && class(chars[start - 1]) == class(current) {
+            start -= 1;
+        }
+        let mut end = col + 1;
+        while end < chars.len() && class(chars[end]) == class(current) {
+            end += 1;
+        }
+        Some((start, end))
+    }
+
+    /// Apply an operator to columns `[start, end)` of the cursor line
+    fn apply_operator(&mut self, op: Operator, start: usize, end: usize) {
+        let row = self.cursor_row;
+        let start_idx = self.char_idx(row, start);
+        let end_idx = self.char_idx(row, end);
+        self.yank_buffer = self.rope.slice(start_idx..end_idx).to_string();
+        self.yank_is_line = false;
+
+        if op != Operator::Yank && end > start {
+            self.capture();
+            self.rope.remove(start_idx..end_idx);
+            self.modified = true;
+            self.mark_dirty_from(row);
+        }
+        self.cursor_col = start;
+        if op == Operator::Change {
+            self.mode = Mode::Insert;
+        }
+        self.clamp_cursor_col();
+    }
+
+    /// `d`/`c`/`y` followed by a w/e/b/$/0 motion
+    fn operate_motion(&mut self, op: Operator, motion: u8, count: usize) {
+        if let Some((start, end)) = self.motion_span(motion, count, op) {
+            self.apply_operator(op, start, end);
+        }
+    }
+
+    /// `diw`/`ciw`/`yiw`
+    fn operate_inner_word(&mut self, op: Operator) {
+        if let Some((start, end)) = self.inner_word_span() {
+            self.apply_operator(op, start, end);
+        }
+    }
+
     fn char_idx(&self, row: usize, col: usize) -> usize {
         if row >= self.line_count() {
             return self.rope.len_chars();
@@ -765,7 +1135,70 @@ impl Editor {
         self.mark_dirty_from(self.cursor_row); // Paste affects current and possibly following lines
     }
 
+    /// Indent (or dedent) lines `first..=last` by one shiftwidth as a single
+    /// undo step, leaving the cursor on the first line's first non-blank.
+    fn shift_lines(&mut self, first: usize, last: usize, indent: bool) {
+        let last = last.min(self.line_count().saturating_sub(1));
+        let width = self.shiftwidth.max(1);
+        self.capture();
+        for row in first..=last {
+            let line = self.get_line(row);
+            let line_start = self.rope.line_to_char(row);
+            if indent {
+                // Like vim, blank lines are left alone
+                if line.is_empty() {
+                    continue;
+                }
+                let unit = if self.expandtab {
+                    " ".repeat(width)
+                } else {
+                    "\t".to_string()
+                };
+                self.rope.insert(line_start, &unit);
+            } else {
+                let mut removed_width = 0;
+                let mut remove_chars = 0;
+                for ch in line.chars() {
+                    if removed_width >= width {
+                        break;
+                    }
+                    match ch {
+                        ' ' => removed_width += 1,
+                        '\t' => removed_width += width,
+                        _ => break,
+                    }
+                    remove_chars += 1;
+                }
+                if remove_chars > 0 {
+                    self.rope.remove(line_start..line_start + remove_chars);
+                }
+            }
+        }
+        self.modified = true;
+        self.mark_dirty_from(first);
+        self.cursor_row = first;
+        self.cursor_col = self
+            .get_line(first)
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        self.clamp_cursor_col();
+    }
+
+    /// Visual `>`/`<`: shift every selected line, then leave visual mode.
+    fn shift_selection(&mut self, indent: bool) {
+        if let Some(((start_row, _), (end_row, _))) = self.get_selection() {
+            self.shift_lines(start_row, end_row, indent);
+        }
+        self.mode = Mode::Normal;
+        self.selection_anchor = None;
+    }
+
     fn delete_selection(&mut self) {
+        if self.mode == Mode::VisualBlock {
+            self.delete_block();
+            return;
+        }
         if let Some(((start_row, start_col), (end_row, end_col))) = self.get_selection() {
             self.capture();
 
@@ -807,6 +1240,10 @@ impl Editor {
     }
 
     fn yank_selection(&mut self) {
+        if self.mode == Mode::VisualBlock {
+            self.yank_block();
+            return;
+        }
         if let Some(((start_row, start_col), (end_row, end_col))) = self.get_selection() {
             if self.mode == Mode::VisualLine {
                 let start = self.rope.line_to_char(start_row);
@@ -853,6 +1290,65 @@ impl Editor {
         self.modified = true;
     }
 
+    /// Move to the `count`th occurrence of `target` on the current line (f/F/t/T).
+    /// The cursor stays put if there are not enough occurrences.
+    fn find_char(&mut self, kind: FindKind, target: char, count: usize, is_repeat: bool) {
+        let chars: Vec<char> = self.get_line(self.cursor_row).chars().collect();
+        let col = self.cursor_col;
+        // Repeating a till motion must skip the match the cursor is already parked against
+        let skip_adjacent =
+            is_repeat && matches!(kind, FindKind::TillForward | FindKind::TillBackward);
+
+        let mut found = None;
+        let mut remaining = count.max(1);
+        match kind {
+            FindKind::Forward | FindKind::TillForward => {
+                let start = col + if skip_adjacent { 2 } else { 1 };
+                for (i, &c) in chars.iter().enumerate().skip(start) {
+                    if c == target {
+                        remaining -= 1;
+                        if remaining == 0 {
+                            found = Some(i);
+                            break;
+                        }
+                    }
+                }
+            }
+            FindKind::Backward | FindKind::TillBackward => {
+                let end = col.saturating_sub(if skip_adjacent { 1 } else { 0 });
+                for i in (0..end.min(chars.len())).rev() {
+                    if chars[i] == target {
+                        remaining -= 1;
+                        if remaining == 0 {
+                            found = Some(i);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(i) = found {
+            self.cursor_col = match kind {
+                FindKind::Forward | FindKind::Backward => i,
+                FindKind::TillForward => i - 1,
+                FindKind::TillBackward => i + 1,
+            };
+            self.update_selection();
+        }
+        if !is_repeat {
+            self.last_find = Some((kind, target));
+        }
+    }
+
+    /// Repeat the last f/F/t/T (`;`), or in the opposite direction (`,`)
+    fn repeat_find(&mut self, reverse: bool, count: usize) {
+        if let Some((kind, target)) = self.last_find {
+            let kind = if reverse { kind.reversed() } else { kind };
+            self.find_char(kind, target, count, true);
+        }
+    }
+
     fn move_to_line_start(&mut self) {
         self.cursor_col = 0;
         self.update_selection();
@@ -864,22 +1360,135 @@ impl Editor {
     }
 
     fn move_to_first_line(&mut self) {
+        let from = (self.cursor_row, self.cursor_col);
         self.cursor_row = 0;
         self.clamp_cursor_col();
+        self.record_jump(from);
         self.update_selection();
     }
 
     fn move_to_last_line(&mut self) {
+        let from = (self.cursor_row, self.cursor_col);
         self.cursor_row = self.line_count().saturating_sub(1);
         self.clamp_cursor_col();
+        self.record_jump(from);
+        self.recenter_if_offscreen();
         self.update_selection();
     }
 
+    /// After a long jump, put the cursor line in the middle of the screen
+    /// (vim's `zz`) instead of letting the renderer pin it to an edge.
+    fn recenter_if_offscreen(&mut self) {
+        let height = self.view_height.max(1);
+        if self.cursor_row >= self.scroll_offset && self.cursor_row < self.scroll_offset + height {
+            return;
+        }
+        let max_scroll = self.line_count().saturating_sub(height);
+        self.scroll_offset = self.cursor_row.saturating_sub(height / 2).min(max_scroll);
+    }
+
     fn move_to_line_number(&mut self, one_based_line: usize) {
+        let from = (self.cursor_row, self.cursor_col);
         let target = one_based_line.saturating_sub(1);
         let last = self.line_count().saturating_sub(1);
         self.cursor_row = target.min(last);
         self.clamp_cursor_col();
+        self.record_jump(from);
+        self.update_selection();
+    }
+
+    /// Jump to the bracket matching the one under (or after) the cursor (%).
+    /// Nesting is respected across lines; quotes are not special.
+    fn match_bracket(&mut self) {
+        let chars: Vec<char> = self.get_line(self.cursor_row).chars().collect();
+        let Some(col) = (self.cursor_col..chars.len()).find(|&c| "()[]{}".contains(chars[c]))
+        else {
+            return;
+        };
+        let open = chars[col];
+        let (target, forward) = match open {
+            '(' => (')', true),
+            '[' => (']', true),
+            '{' => ('}', true),
+            ')' => ('(', false),
+            ']' => ('[', false),
+            _ => ('{', false),
+        };
+
+        let start = self.char_idx(self.cursor_row, col);
+        let mut depth = 0usize;
+        let mut idx = start;
+        let found = loop {
+            if forward {
+                idx += 1;
+                if idx >= self.rope.len_chars() {
+                    break None;
+                }
+            } else {
+                if idx == 0 {
+                    break None;
+                }
+                idx -= 1;
+            }
+            let c = self.rope.char(idx);
+            if c == open {
+                depth += 1;
+            } else if c == target {
+                if depth == 0 {
+                    break Some(idx);
+                }
+                depth -= 1;
+            }
+        };
+
+        if let Some(idx) = found {
+            let from = (self.cursor_row, self.cursor_col);
+            self.cursor_row = self.rope.char_to_line(idx);
+            self.cursor_col = idx - self.rope.line_to_char(self.cursor_row);
+            self.record_jump(from);
+            self.update_selection();
+        }
+    }
+
+    /// Remember `from` in the '`' mark if a jump motion moved the cursor
+    fn record_jump(&mut self, from: (usize, usize)) {
+        if from != (self.cursor_row, self.cursor_col) {
+            self.marks.insert('`', from);
+        }
+    }
+
+    /// m<letter>: set a named mark at the cursor
+    fn set_mark(&mut self, name: char) {
+        if name.is_ascii_alphabetic() {
+            self.marks.insert(name, (self.cursor_row, self.cursor_col));
+        }
+    }
+
+    /// `<mark> jumps to the exact position; '<mark> to the first non-blank of
+    /// the mark's line. Both accept ` or ' for the previous-jump mark.
+    fn jump_to_mark(&mut self, name: char, exact: bool) {
+        let key = if name == '\'' { '`' } else { name };
+        let Some(&(row, col)) = self.marks.get(&key) else {
+            self.status_message = "E20: Mark not set".to_string();
+            return;
+        };
+
+        // Clamp marks that point past the end of a buffer that has since shrunk
+        let row = row.min(self.line_count().saturating_sub(1));
+        let line = self.get_line(row);
+        let col = col.min(line.chars().count().saturating_sub(1));
+        self.marks.insert(key, (row, col));
+        let col = if exact {
+            col
+        } else {
+            line.chars().take_while(|c| c.is_whitespace()).count()
+        };
+
+        let from = (self.cursor_row, self.cursor_col);
+        self.cursor_row = row;
+        self.cursor_col = col;
+        self.clamp_cursor_col();
+        self.record_jump(from);
         self.update_selection();
     }
 
@@ -898,6 +1507,22 @@ impl Editor {
         self.update_selection();
     }
 
+    /// Scroll the view and the cursor together by `lines` (Ctrl+D/U/F/B),
+    /// clamped to the buffer.
+    fn scroll_by(&mut self, lines: usize, down: bool, content_height: usize) {
+        let last_row = self.line_count().saturating_sub(1);
+        let max_scroll = self.line_count().saturating_sub(content_height);
+        if down {
+            self.scroll_offset = (self.scroll_offset + lines).min(max_scroll);
+            self.cursor_row = (self.cursor_row + lines).min(last_row);
+        } else {
+            self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+            self.cursor_row = self.cursor_row.saturating_sub(lines);
+        }
+        self.clamp_cursor_col();
+        self.update_selection();
+    }
+
     /// Execute search and find all matches
     fn execute_search(&mut self) {
         self.search_matches.clear();
@@ -922,6 +1547,44 @@ impl Editor {
         self.status_message = format!("{} match(es)", self.search_matches.len());
     }
 
+    /// Column span `[start, end)` of the word under the cursor, or of the next
+    /// word on the line when the cursor isn't on a word character
+    fn word_span_at_cursor(&self) -> Option<(usize, usize)> {
+        let chars: Vec<char> = self.get_line(self.cursor_row).chars().collect();
+        let mut start = (self.cursor_col..chars.len()).find(|&c| Self::is_word_char(chars[c]))?;
+        if start == self.cursor_col {
+            while start > 0 && Self::is_word_char(chars[start - 1]) {
+                start -= 1;
+            }
+        }
+        let mut end = start;
+        while end < chars.len() && Self::is_word_char(chars[end]) {
+            end += 1;
+        }
+        Some((start, end))
+    }
+
+    /// `*` / `#`: search for the word under the cursor forward or backward
+    fn search_word_under_cursor(&mut self, backward: bool) {
+        let Some((start, end)) = self.word_span_at_cursor() else {
+            self.status_message = "E348: No string under cursor".to_string();
+            return;
+        };
+        self.search_pattern = self
+            .get_line(self.cursor_row)
+            .chars()
+            .skip(start)
+            .take(end - start)
+            .collect();
+        self.cursor_col = start;
+        self.execute_search();
+        if backward {
+            self.jump_to_prev_match();
+        } else {
+            self.jump_to_next_match();
+        }
+    }
+
     /// Jump to next search match
     fn jump_to_next_match(&mut self) {
         if self.search_matches.is_empty() {
@@ -943,6 +1606,8 @@ impl Editor {
         self.cursor_row = row;
         self.cursor_col = col;
         self.clamp_cursor();
+        self.record_jump(current_pos);
+        self.recenter_if_offscreen();
 
         self.status_message = format!("{}/{} matches", next_idx + 1, self.search_matches.len());
     }
@@ -968,9 +1633,73 @@ impl Editor {
         self.cursor_row = row;
         self.cursor_col = col;
         self.clamp_cursor();
+        self.record_jump(current_pos);
+        self.recenter_if_offscreen();
 
         self.status_message = format!("{}/{} matches", prev_idx + 1, self.search_matches.len());
     }
+
+    /// Literal substitution over an inclusive row range (`:s`, `:%s`, `:N,Ms`).
+    /// Returns (substitutions, lines changed). The whole operation is a single undo step.
+    fn substitute(
+        &mut self,
+        start_row: usize,
+        end_row: usize,
+        pattern: &str,
+        replacement: &str,
+        global: bool,
+    ) -> (usize, usize) {
+        if pattern.is_empty() {
+            return (0, 0);
+        }
+
+        let last_row = self.line_count().saturating_sub(1);
+        let end_row = end_row.min(last_row);
+        let mut changed: Vec<(usize, String)> = Vec::new();
+        let mut substitutions = 0;
+
+        for row in start_row..=end_row {
+            let line = self.get_line(row);
+            let matches = if global {
+                line.matches(pattern).count()
+            } else {
+                usize::from(line.contains(pattern))
+            };
+            if matches == 0 {
+                continue;
+            }
+            let new_line = if global {
+                line.replace(pattern, replacement)
+            } else {
+                line.replacen(pattern, replacement, 1)
+            };
+            substitutions += matches;
+            changed.push((row, new_line));
+        }
+
+        if changed.is_empty() {
+            return (0, 0);
+        }
+
+        self.capture();
+        for (row, new_line) in &changed {
+            let start = self.rope.line_to_char(*row);
+            let old_len = self.get_line(*row).chars().count();
+            self.rope.remove(start..start + old_len);
+            self.rope.insert(start, new_line);
+        }
+
+        // Vim leaves the cursor on the last substituted line
+        if let Some((row, _)) = changed.last() {
+            self.cursor_row = *row;
+            self.cursor_col = 0;
+        }
+        self.clamp_cursor();
+        self.modified = true;
+        self.mark_dirty_from(start_row);
+
+        (substitutions, changed.len())
+    }
 }
 
 fn map_csi_key(params: &Params, action: u8) -> Option<EscapeKey> {
@@ -1140,7 +1869,7 @@ fn run_editor(
                     &env.cwd,
                     content_height,
                 ),
-                Mode::Visual | Mode::VisualLine => {
+                Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                     handle_visual_mode(&mut editor, &mut pending_key, byte, &stdin)
                 }
                 Mode::Search => handle_search_mode(&mut editor, byte),
@@ -1170,6 +1899,7 @@ fn handle_command_mode(editor: &mut Editor, running: &mut bool, byte: u8, cwd: &
                     editor.modified = false;
                     *running = false;
                 }
+                CommandResult::Message(msg) => editor.status_message = msg,
                 CommandResult::Error(e) => editor.status_message = e,
             }
             editor.command_buffer.clear();
@@ -1193,6 +1923,7 @@ fn handle_command_mode(editor: &mut Editor, running: &mut bool, byte: u8, cwd: &
 fn handle_insert_mode(editor: &mut Editor, byte: u8) {
     match byte {
         0x1B => {
+            editor.finish_block_insert();
             editor.mode = Mode::Normal;
             if editor.cursor_col > 0 {
                 editor.cursor_col -= 1;
@@ -1220,8 +1951,31 @@ fn handle_normal_mode(
         let count = take_count(pending_count);
         match (prev, byte) {
             (b'd', b'd') => editor.delete_lines(count),
+            (b'>', b'>') | (b'<', b'<') => {
+                let first = editor.cursor_row;
+                editor.shift_lines(first, first + count - 1, byte == b'>');
+            }
             (b'y', b'y') => editor.yank_lines(count),
             (b'g', b'g') => editor.move_to_line_number(count),
+            (b'm', 0x20..=0x7E) => editor.set_mark(byte as char),
+            (b'`', 0x20..=0x7E) => editor.jump_to_mark(byte as char, true),
+            (b'\'', 0x20..=0x7E) => editor.jump_to_mark(byte as char, false),
+            (b'd' | b'c' | b'y', b'w' | b'e' | b'b' | b'$' | b'0') => {
+                if let Some(op) = Operator::from_key(prev) {
+                    editor.operate_motion(op, byte, count);
+                }
+            }
+            (b'd' | b'c' | b'y', b'i') => {
+                if let (Some(op), Some(b'w')) = (Operator::from_key(prev), read_single_byte(stdin))
+                {
+                    editor.operate_inner_word(op);
+                }
+            }
+            (b'f' | b'F' | b't' | b'T', 0x20..=0x7E) => {
+                if let Some(kind) = FindKind::from_key(prev) {
+                    editor.find_char(kind, byte as char, count, false);
+                }
+            }
             _ => {} // Unknown combo, ignore
         }
         return;
@@ -1293,7 +2047,11 @@ fn handle_normal_mode(
                 editor.move_to_last_line();
             }
         }
-        b'g' | b'd' | b'y' => {
+        b';' => editor.repeat_find(false, count),
+        b',' => editor.repeat_find(true, count),
+        b'%' => editor.match_bracket(),
+        b'g' | b'd' | b'c' | b'y' | b'f' | b'F' | b't' | b'T' | b'm' | b'`' | b'\'' | b'>'
+        | b'<' => {
             *pending = Some(byte);
             if has_count {
                 *pending_count = Some(count);
@@ -1352,6 +2110,19 @@ fn handle_normal_mode(
             editor.mode = Mode::VisualLine;
             editor.selection_anchor = Some((editor.cursor_row, 0));
         }
+        0x16 => {
+            editor.mode = Mode::VisualBlock;
+            editor.selection_anchor = Some((editor.cursor_row, editor.cursor_col));
+        } // Ctrl+V
+        // Scrolling: half pages, and full pages keeping two lines of overlap
+        0x04 | 0x15 => {
+            let half = (content_height / 2).max(1);
+            editor.scroll_by(half, byte == 0x04, content_height);
+        } // Ctrl+D / Ctrl+U
+        0x06 | 0x02 => {
+            let page = content_height.saturating_sub(2).max(1);
+            editor.scroll_by(page * count, byte == 0x06, content_height);
+        } // Ctrl+F / Ctrl+B
         // Search mode
         b'/' => {
             editor.mode = Mode::Search;
@@ -1362,6 +2133,16 @@ fn handle_normal_mode(
                 editor.jump_to_next_match();
             }
         }
+        b'*' => {
+            for _ in 0..count {
+                editor.search_word_under_cursor(false);
+            }
+        }
+        b'#' => {
+            for _ in 0..count {
+                editor.search_word_under_cursor(true);
+            }
+        }
         b'N' => {
             for _ in 0..count {
                 editor.jump_to_prev_match();
@@ -1398,6 +2179,17 @@ fn handle_visual_mode(
         b'e' => editor.move_word_end(),
         b'0' => editor.move_to_line_start(),
         b'$' => editor.move_to_line_end(),
+        b'f' | b'F' | b't' | b'T' => {
+            if let (Some(kind), Some(target)) = (FindKind::from_key(byte), read_single_byte(stdin))
+            {
+                if (0x20..=0x7E).contains(&target) {
+                    editor.find_char(kind, target as char, 1, false);
+                }
+            }
+        }
+        b';' => editor.repeat_find(false, 1),
+        b',' => editor.repeat_find(true, 1),
+        b'%' => editor.match_bracket(),
         b'G' => editor.move_to_last_line(),
         b'g' => {
             if let Some(b'g') = read_single_byte(stdin) {
@@ -1407,9 +2199,14 @@ fn handle_visual_mode(
         // Actions on selection
         b'd' | b'x' => editor.delete_selection(),
         b'y' => editor.yank_selection(),
+        b'>' => editor.shift_selection(true),
+        b'<' => editor.shift_selection(false),
+        // Block column edit
+        b'I' if editor.mode == Mode::VisualBlock => editor.start_block_insert(false),
+        b'A' if editor.mode == Mode::VisualBlock => editor.start_block_insert(true),
         // Toggle visual line
         b'V' => {
-            if editor.mode == Mode::Visual {
+            if editor.mode != Mode::VisualLine {
                 editor.mode = Mode::VisualLine;
             } else {
                 editor.mode = Mode::Normal;
@@ -1417,13 +2214,21 @@ fn handle_visual_mode(
             }
         }
         b'v' => {
-            if editor.mode == Mode::VisualLine {
+            if editor.mode != Mode::Visual {
                 editor.mode = Mode::Visual;
             } else {
                 editor.mode = Mode::Normal;
                 editor.selection_anchor = None;
             }
         }
+        0x16 => {
+            if editor.mode != Mode::VisualBlock {
+                editor.mode = Mode::VisualBlock;
+            } else {
+                editor.mode = Mode::Normal;
+                editor.selection_anchor = None;
+            }
+        } // Ctrl+V
         _ => {}
     }
 }
@@ -1462,12 +2267,222 @@ enum CommandResult {
     Quit,
     Saved,
     SavedAndQuit,
+    Message(String),
     Error(String),
 }
 
+/// Line range prefix for ex commands
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum LineRange {
+    /// No range given: the cursor line
+    Current,
+    /// `%`: every line in the buffer
+    All,
+    /// `N` or `N,M`: 1-based inclusive line numbers
+    Lines(usize, usize),
+}
+
+/// Parsed `:[range]s/pattern/replacement/[flags]` command
+#[derive(Debug, PartialEq)]
+struct Substitution {
+    range: LineRange,
+    pattern: String,
+    replacement: String,
+    global: bool,
+}
+
+/// Split a leading line range off an ex command, returning the range and the rest.
+fn parse_line_range(cmd: &str) -> (LineRange, &str) {
+    if let Some(rest) = cmd.strip_prefix('%') {
+        return (LineRange::All, rest);
+    }
+
+    let digits = cmd.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return (LineRange::Current, cmd);
+    }
+    let start: usize = cmd[..digits].parse().unwrap_or(1);
+    let rest = &cmd[digits..];
+
+    if let Some(after_comma) = rest.strip_prefix(',') {
+        let end_digits = after_comma
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if end_digits > 0 {
+            let end: usize = after_comma[..end_digits].parse().unwrap_or(start);
+            return (LineRange::Lines(start, end), &after_comma[end_digits..]);
+        }
+    }
+
+    (LineRange::Lines(start, start), rest)
+}
+
+/// Parse a substitute command. Returns None if `cmd` is not a substitution.
+/// The delimiter is the first non-alphanumeric character after `s`; a
+/// backslash escapes a literal delimiter inside the pattern or replacement.
+fn parse_substitute(cmd: &str) -> Option<Substitution> {
+    let (range, rest) = parse_line_range(cmd);
+    let rest = rest.strip_prefix('s')?;
+    let mut chars = rest.chars();
+    let delim = chars.next()?;
+    if delim.is_alphanumeric() || delim.is_whitespace() || delim == '\\' {
+        return None;
+    }
+
+    let mut parts: Vec<String> = vec![String::new()];
+    let mut escaped = false;
+    for c in chars {
+        if escaped {
+            if c != delim {
+                parts.last_mut()?.push('\\');
+            }
+            parts.last_mut()?.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delim && parts.len() < 3 {
+            parts.push(String::new());
+        } else {
+            parts.last_mut()?.push(c);
+        }
+    }
+    if escaped {
+        parts.last_mut()?.push('\\');
+    }
+
+    let mut parts = parts.into_iter();
+    let pattern = parts.next().unwrap_or_default();
+    let replacement = parts.next().unwrap_or_default();
+    let flags = parts.next().unwrap_or_default();
+
+    Some(Substitution {
+        range,
+        pattern,
+        replacement,
+        global: flags.contains('g'),
+    })
+}
+
+fn execute_substitute(sub: Substitution, editor: &mut Editor) -> CommandResult {
+    // An empty pattern reuses the last search pattern, as in vim
+    let pattern = if sub.pattern.is_empty() {
+        if editor.search_pattern.is_empty() {
+            return CommandResult::Error("No previous search pattern".to_string());
+        }
+        editor.search_pattern.clone()
+    } else {
+        sub.pattern
+    };
+
+    let last_row = editor.line_count().saturating_sub(1);
+    let (start_row, end_row) = match sub.range {
+        LineRange::Current => (editor.cursor_row, editor.cursor_row),
+        LineRange::All => (0, last_row),
+        LineRange::Lines(a, b) => {
+            let (a, b) = if a <= b { (a, b) } else { (b, a) };
+            (
+                a.saturating_sub(1).min(last_row),
+                b.saturating_sub(1).min(last_row),
+            )
+        }
+    };
+
+    let (count, lines) =
+        editor.substitute(start_row, end_row, &pattern, &sub.replacement, sub.global);
+    if count == 0 {
+        CommandResult::Error(format!("Pattern not found: {}", pattern))
+    } else {
+        CommandResult::Message(format!("{} substitutions on {} lines", count, lines))
+    }
+}
+
+/// Split `:e[dit][!] [path]` into the force flag and the (possibly empty) path.
+fn parse_edit_command(cmd: &str) -> Option<(bool, &str)> {
+    let rest = cmd.strip_prefix("edit").or_else(|| cmd.strip_prefix('e'))?;
+    let (force, rest) = match rest.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((force, rest.trim()))
+}
+
+/// Handle `:e [path]`: open another file, or re-read the current one.
+fn execute_edit(path: &str, force: bool, editor: &mut Editor, cwd: &str) -> CommandResult {
+    if editor.modified && !force {
+        return CommandResult::Error(
+            "E37: No write since last change (add ! to override)".to_string(),
+        );
+    }
+    let path = if path.is_empty() {
+        match editor.file_path.clone() {
+            Some(current) => current,
+            None => return CommandResult::Error("E32: No file name".to_string()),
+        }
+    } else {
+        path.to_string()
+    };
+    // A missing file opens an empty buffer with that name, as at launch
+    let (content, is_new) = match read_file(cwd, &path) {
+        Ok(content) => (content, false),
+        Err(e) if e.contains("not found") || e.contains("no-entry") => (String::new(), true),
+        Err(e) => return CommandResult::Error(format!("Error reading file: {}", e)),
+    };
+    editor.load_buffer(content, Some(path.clone()));
+    if is_new {
+        CommandResult::Message(format!("\"{}\" [New]", path))
+    } else {
+        CommandResult::Message(format!("\"{}\" {}L", path, editor.line_count()))
+    }
+}
+
+/// Handle `:set <option>` for editor display options.
+fn execute_set(option: &str, editor: &mut Editor) -> CommandResult {
+    if let Some(value) = option
+        .strip_prefix("shiftwidth=")
+        .or_else(|| option.strip_prefix("sw="))
+    {
+        return match value.parse::<usize>() {
+            Ok(width) if width > 0 => {
+                editor.shiftwidth = width;
+                CommandResult::Message(String::new())
+            }
+            _ => CommandResult::Error(format!("E487: Argument must be positive: {}", option)),
+        };
+    }
+    match option {
+        "expandtab" | "et" => {
+            editor.expandtab = true;
+            return CommandResult::Message(String::new());
+        }
+        "noexpandtab" | "noet" => {
+            editor.expandtab = false;
+            return CommandResult::Message(String::new());
+        }
+        _ => {}
+    }
+    match option {
+        "number" | "nu" => editor.show_line_numbers = true,
+        "nonumber" | "nonu" => editor.show_line_numbers = false,
+        "relativenumber" | "rnu" => editor.relative_line_numbers = true,
+        "norelativenumber" | "nornu" => editor.relative_line_numbers = false,
+        _ => return CommandResult::Error(format!("Unknown option: {}", option)),
+    }
+    // Gutter width changes shift every content column
+    editor.force_full_redraw = true;
+    CommandResult::Message(String::new())
+}
+
 fn execute_command(cmd: &str, editor: &mut Editor, cwd: &str) -> CommandResult {
     let cmd = cmd.trim();
 
+    if let Some(sub) = parse_substitute(cmd) {
+        return execute_substitute(sub, editor);
+    }
+
     match cmd {
         "q" | "quit" => {
             if editor.modified {
@@ -1498,6 +2513,12 @@ fn execute_command(cmd: &str, editor: &mut Editor, cwd: &str) -> CommandResult {
             }
         }
         _ => {
+            if let Some(option) = cmd.strip_prefix("set ") {
+                return execute_set(option.trim(), editor);
+            }
+            if let Some((force, path)) = parse_edit_command(cmd) {
+                return execute_edit(path, force, editor, cwd);
+            }
             if let Some(stripped) = cmd.strip_prefix("w ") {
                 let new_path = stripped.trim();
                 match write_file(cwd, new_path, &editor.rope.to_string()) {
@@ -1532,6 +2553,7 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
 
     let content_height = height.saturating_sub(2);
     if content_height > 0 {
+        editor.view_height = content_height;
         let max_scroll = editor.line_count().saturating_sub(content_height);
         editor.scroll_offset = editor.scroll_offset.min(max_scroll);
 
@@ -1558,6 +2580,7 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
         Mode::Command => "COMMAND",
         Mode::Visual => "VISUAL",
         Mode::VisualLine => "V-LINE",
+        Mode::VisualBlock => "V-BLOCK",
         Mode::Search => "SEARCH",
     };
     let filename = editor.file_path.as_deref().unwrap_or("[No Name]");
@@ -1573,6 +2596,11 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
 
     // Get selection for highlighting
     let selection = editor.get_selection();
+    let block = if editor.mode == Mode::VisualBlock {
+        editor.block_bounds()
+    } else {
+        None
+    };
 
     // Get syntax highlighting setup
     let ps = &editor.syntax_set;
@@ -1603,12 +2631,31 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
         HighlightLines::new(syntax, theme)
     };
 
+    // Line-number gutter (0 when both number modes are off)
+    let gutter = editor.gutter_width();
+    let text_width = width.saturating_sub(gutter);
+    let gutter_fg = Color::new(101, 115, 126);
+    let gutter_current_fg = Color::new(192, 197, 206);
+
     // Rows 1 to content_height: Editor content
     for i in 0..content_height {
         let row = i + 1; // Screen row (0 is title bar)
         let line_idx = editor.scroll_offset + i;
 
         if line_idx < editor.line_count() {
+            if gutter > 0 {
+                let label = editor.line_number_label(line_idx);
+                let number = format!("{:>w$} ", label, w = gutter - 1);
+                let fg = if line_idx == editor.cursor_row {
+                    gutter_current_fg
+                } else {
+                    gutter_fg
+                };
+                for (gcol, ch) in number.chars().take(width).enumerate() {
+                    editor.current_buffer.set(row, gcol, Cell::new(ch, fg, bg));
+                }
+            }
+
             let line = editor.get_line(line_idx);
             let line_with_newline = format!("{}\n", line);
 
@@ -1620,7 +2667,7 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
                 for (style, text) in ranges {
                     let fg = style_to_color(&style);
                     for c in text.chars() {
-                        if c == '\n' || col >= width {
+                        if c == '\n' || col >= text_width {
                             continue;
                         }
 
@@ -1629,7 +2676,9 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
                             && col == editor.cursor_col
                             && editor.mode != Mode::Insert;
 
-                        let in_selection = if let Some(((sr, sc), (er, ec))) = selection {
+                        let in_selection = if let Some((top, bottom, left, right)) = block {
+                            line_idx >= top && line_idx <= bottom && col >= left && col <= right
+                        } else if let Some(((sr, sc), (er, ec))) = selection {
                             if editor.mode == Mode::VisualLine {
                                 line_idx >= sr && line_idx <= er
                             } else {
@@ -1650,7 +2699,7 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
                             Cell::new(c, fg, bg)
                         };
 
-                        editor.current_buffer.set(row, col, cell);
+                        editor.current_buffer.set(row, gutter + col, cell);
                         col += 1;
                     }
                 }
@@ -1661,19 +2710,21 @@ fn render_to_buffer(editor: &mut Editor, width: usize, height: usize) {
             if line_idx == editor.cursor_row
                 && editor.cursor_col >= line_len
                 && editor.mode != Mode::Insert
-                && col < width
+                && col < text_width
             {
-                editor
-                    .current_buffer
-                    .set(row, col, Cell::new(' ', fg_reverse_bg, reverse_bg));
+                editor.current_buffer.set(
+                    row,
+                    gutter + col,
+                    Cell::new(' ', fg_reverse_bg, reverse_bg),
+                );
                 col += 1;
             }
 
             // Fill rest of line with spaces
-            while col < width {
+            while col < text_width {
                 editor
                     .current_buffer
-                    .set(row, col, Cell::new(' ', fg_white, bg));
+                    .set(row, gutter + col, Cell::new(' ', fg_white, bg));
                 col += 1;
             }
         } else {
@@ -1793,7 +2844,7 @@ fn diff_and_emit(editor: &mut Editor, width: usize, height: usize) -> String {
 
     // Position cursor at actual cursor location
     let screen_row = editor.cursor_row.saturating_sub(editor.scroll_offset) + 2;
-    let screen_col = editor.cursor_col + 1;
+    let screen_col = editor.gutter_width() + editor.cursor_col + 1;
     output.push_str(&format!("\x1b[{};{}H", screen_row, screen_col));
 
     // Show cursor
@@ -2074,6 +3125,379 @@ mod tests {
         editor.delete_char_at_cursor();
         assert_eq!(editor.get_line(0), "one two hree");
     }
+
+    #[test]
+    fn parse_substitute_handles_ranges_delimiters_and_flags() {
+        let sub = parse_substitute("%s/foo/bar/g").unwrap();
+        assert_eq!(sub.range, LineRange::All);
+        assert_eq!(sub.pattern, "foo");
+        assert_eq!(sub.replacement, "bar");
+        assert!(sub.global);
+
+        let sub = parse_substitute("2,4s#a/b#c#").unwrap();
+        assert_eq!(sub.range, LineRange::Lines(2, 4));
+        assert_eq!(sub.pattern, "a/b");
+        assert!(!sub.global);
+
+        let sub = parse_substitute(r"s/a\/b/c").unwrap();
+        assert_eq!(sub.range, LineRange::Current);
+        assert_eq!(sub.pattern, "a/b");
+        assert_eq!(sub.replacement, "c");
+
+        assert!(parse_substitute("set").is_none());
+        assert!(parse_substitute("wq").is_none());
+    }
+
+    #[test]
+    fn substitute_command_replaces_and_undoes_in_one_step() {
+        let mut editor = Editor::new("foo foo\nbar\nfoo\n".to_string(), None);
+        let result = execute_command("%s/foo/baz/g", &mut editor, "/");
+        assert!(
+            matches!(result, CommandResult::Message(ref m) if m == "3 substitutions on 2 lines")
+        );
+        assert_eq!(editor.rope.to_string(), "baz baz\nbar\nbaz\n");
+        assert!(editor.modified);
+
+        editor.undo();
+        assert_eq!(editor.rope.to_string(), "foo foo\nbar\nfoo\n");
+    }
+
+    #[test]
+    fn substitute_with_empty_pattern_reuses_search_pattern() {
+        let mut editor = Editor::new("one two\n".to_string(), None);
+        editor.search_pattern = "two".to_string();
+        execute_command("s//2/", &mut editor, "/");
+        assert_eq!(editor.get_line(0), "one 2");
+    }
+
+    #[test]
+    fn find_char_motions_and_repeats() {
+        let mut editor = Editor::new("a,b,c,d\n".to_string(), None);
+        editor.find_char(FindKind::Forward, ',', 1, false);
+        assert_eq!(editor.cursor_col, 1);
+        editor.repeat_find(false, 1);
+        assert_eq!(editor.cursor_col, 3);
+        editor.repeat_find(true, 1);
+        assert_eq!(editor.cursor_col, 1);
+
+        editor.cursor_col = 0;
+        editor.find_char(FindKind::Forward, ',', 3, false);
+        assert_eq!(editor.cursor_col, 5);
+
+        editor.cursor_col = 0;
+        editor.find_char(FindKind::TillForward, ',', 1, false);
+        assert_eq!(editor.cursor_col, 0);
+        editor.repeat_find(false, 1);
+        assert_eq!(editor.cursor_col, 2);
+
+        editor.find_char(FindKind::Backward, 'a', 1, false);
+        assert_eq!(editor.cursor_col, 0);
+        editor.find_char(FindKind::Forward, 'z', 1, false);
+        assert_eq!(editor.cursor_col, 0);
+    }
+
+    #[test]
+    fn star_and_hash_search_word_under_cursor() {
+        let mut editor = Editor::new("let foo = 1;\nfoo += bar;\nprint(foo)\n".to_string(), None);
+        editor.cursor_col = 5;
+        editor.search_word_under_cursor(false);
+        assert_eq!(editor.search_pattern, "foo");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 0));
+        assert_eq!(editor.status_message, "2/3 matches");
+
+        editor.search_word_under_cursor(true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 4));
+
+        // Off a word: use the next word on the line
+        editor.cursor_row = 1;
+        editor.cursor_col = 3;
+        editor.search_word_under_cursor(false);
+        assert_eq!(editor.search_pattern, "bar");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 7));
+    }
+
+    #[test]
+    fn ctrl_scrolling_moves_view_and_cursor() {
+        let text: String = (0..50).map(|i| format!("line {}\n", i)).collect();
+        let mut editor = Editor::new(text, None);
+        editor.scroll_by(10, true, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (10, 10));
+        editor.scroll_by(18, true, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (28, 28));
+        // Clamped so the last page stays full and the cursor stays in the buffer
+        editor.scroll_by(18, true, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (31, 46));
+        editor.scroll_by(18, true, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (31, 50));
+        editor.scroll_by(10, false, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (21, 40));
+        editor.scroll_by(100, false, 20);
+        assert_eq!((editor.scroll_offset, editor.cursor_row), (0, 0));
+    }
+
+    #[test]
+    fn edit_command_guards_unsaved_changes_and_resets_buffer() {
+        assert_eq!(parse_edit_command("e src/a.rs"), Some((false, "src/a.rs")));
+        assert_eq!(parse_edit_command("edit! b.rs"), Some((true, "b.rs")));
+        assert_eq!(parse_edit_command("e!"), Some((true, "")));
+        assert_eq!(parse_edit_command("echo"), None);
+
+        let mut editor = Editor::new("one\ntwo\n".to_string(), Some("a.txt".to_string()));
+        editor.cursor_row = 1;
+        editor.insert_char('x');
+        let result = execute_command("e b.txt", &mut editor, "/");
+        assert!(
+            matches!(result, CommandResult::Error(ref e) if e.starts_with("E37: No write since last change")),
+        );
+        assert_eq!(editor.file_path.as_deref(), Some("a.txt"));
+
+        editor.load_buffer(String::new(), Some("new.txt".to_string()));
+        assert_eq!(editor.rope.to_string(), "\n");
+        assert_eq!(editor.file_path.as_deref(), Some("new.txt"));
+        assert_eq!(
+            (editor.cursor_row, editor.cursor_col, editor.scroll_offset),
+            (0, 0, 0)
+        );
+        assert!(!editor.modified);
+        assert!(editor.undo_stack.is_empty() && editor.redo_stack.is_empty());
+        assert_eq!(editor.dirty_from, Some(0));
+    }
+
+    #[test]
+    fn shift_commands_indent_and_dedent_lines() {
+        let mut editor = Editor::new("a\n\n  b\n\tc\n".to_string(), None);
+        editor.shift_lines(0, 2, true);
+        assert_eq!(editor.rope.to_string(), "    a\n\n      b\n\tc\n");
+        assert_eq!(editor.undo_stack.len(), 1);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 4));
+
+        // Dedent removes at most one shiftwidth, counting a tab as a full one
+        editor.shift_lines(0, 3, false);
+        assert_eq!(editor.rope.to_string(), "a\n\n  b\nc\n");
+        editor.cursor_row = 2;
+        editor.shift_lines(2, 2, false);
+        assert_eq!(editor.get_line(2), "b");
+
+        execute_command("set sw=2", &mut editor, "/");
+        execute_command("set noexpandtab", &mut editor, "/");
+        editor.shift_lines(0, 0, true);
+        assert_eq!(editor.get_line(0), "\ta");
+        execute_command("set et", &mut editor, "/");
+        editor.shift_lines(2, 2, true);
+        assert_eq!(editor.get_line(2), "  b");
+
+        // Visual range is one undo step and leaves visual mode
+        let undo_before = editor.undo_stack.len();
+        editor.mode = Mode::VisualLine;
+        editor.selection_anchor = Some((0, 0));
+        editor.cursor_row = 2;
+        editor.shift_selection(true);
+        assert_eq!(editor.rope.to_string(), "  \ta\n\n    b\nc\n");
+        assert_eq!(editor.undo_stack.len(), undo_before + 1);
+        assert_eq!(editor.mode, Mode::Normal);
+        assert!(matches!(
+            execute_command("set sw=0", &mut editor, "/"),
+            CommandResult::Error(_)
+        ));
+    }
+
+    #[test]
+    fn jumps_recenter_the_viewport() {
+        let mut text: String = (0..500).map(|i| format!("line {}\n", i)).collect();
+        text = text.replace("line 300\n", "needle\n");
+        let mut editor = Editor::new(text, None);
+        editor.view_height = 20;
+
+        editor.search_pattern = "needle".to_string();
+        editor.execute_search();
+        editor.jump_to_next_match();
+        assert_eq!(editor.cursor_row, 300);
+        assert_eq!(editor.scroll_offset, 290);
+
+        // Already visible: the view stays put
+        editor.jump_to_prev_match();
+        assert_eq!(editor.scroll_offset, 290);
+
+        // Near the end the view is clamped so the last page stays full
+        editor.move_to_last_line();
+        assert_eq!(editor.cursor_row, 500);
+        assert_eq!(editor.scroll_offset, 481);
+        assert!(editor.cursor_row < editor.scroll_offset + editor.view_height);
+
+        editor.move_to_first_line();
+        editor.scroll_offset = 0;
+        editor.jump_to_prev_match();
+        assert_eq!(editor.scroll_offset, 290);
+    }
+
+    #[test]
+    fn percent_jumps_between_matching_brackets() {
+        let mut editor = Editor::new("fn f(a) {\n    [x, (y)]\n}\n".to_string(), None);
+        // Not on a bracket: scan forward on the line to the first one
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 6));
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 4));
+
+        editor.cursor_col = 8;
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (2, 0));
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 8));
+
+        editor.cursor_row = 1;
+        editor.cursor_col = 4;
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 11));
+
+        // Unmatched bracket: cursor stays put
+        let mut editor = Editor::new("(()\n".to_string(), None);
+        editor.match_bracket();
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 0));
+    }
+
+    #[test]
+    fn marks_jump_and_track_previous_position() {
+        let mut editor = Editor::new("one\n  two\nthree\nfour\n".to_string(), None);
+        editor.cursor_row = 1;
+        editor.cursor_col = 4;
+        editor.set_mark('a');
+
+        editor.move_to_last_line();
+        assert_eq!(editor.marks.get(&'`'), Some(&(1, 4)));
+
+        editor.jump_to_mark('a', false);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 2));
+        editor.jump_to_mark('a', true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 4));
+
+        // `` returns to where the last jump started
+        editor.move_to_first_line();
+        editor.jump_to_mark('`', true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 4));
+
+        editor.jump_to_mark('z', true);
+        assert_eq!(editor.status_message, "E20: Mark not set");
+
+        // Marks past the end of a shrunken buffer are clamped
+        editor.cursor_row = 3;
+        editor.cursor_col = 3;
+        editor.set_mark('b');
+        editor.rope = Rope::from("x\n");
+        editor.jump_to_mark('b', true);
+        assert_eq!((editor.cursor_row, editor.cursor_col), (1, 0));
+    }
+
+    #[test]
+    fn operator_motions_delete_change_and_yank() {
+        let mut editor = Editor::new("foo bar baz\n".to_string(), None);
+        editor.operate_motion(Operator::Delete, b'w', 1);
+        assert_eq!(editor.get_line(0), "bar baz");
+        assert_eq!(editor.yank_buffer, "foo ");
+
+        editor.operate_motion(Operator::Delete, b'e', 1);
+        assert_eq!(editor.get_line(0), " baz");
+
+        editor.undo();
+        editor.cursor_col = 4;
+        editor.operate_motion(Operator::Delete, b'b', 1);
+        assert_eq!(editor.get_line(0), "baz");
+        assert_eq!(editor.cursor_col, 0);
+
+        // cw on a word stops at the word end and enters insert mode
+        let mut editor = Editor::new("foo bar\n".to_string(), None);
+        editor.operate_motion(Operator::Change, b'w', 1);
+        assert_eq!(editor.get_line(0), " bar");
+        assert_eq!(editor.mode, Mode::Insert);
+
+        let mut editor = Editor::new("let value = 1;\n".to_string(), None);
+        editor.cursor_col = 6;
+        editor.operate_inner_word(Operator::Yank);
+        assert_eq!(editor.yank_buffer, "value");
+        assert_eq!(editor.get_line(0), "let value = 1;");
+        editor.cursor_col = 6;
+        editor.operate_inner_word(Operator::Change);
+        assert_eq!(editor.get_line(0), "let  = 1;");
+        assert_eq!((editor.cursor_col, editor.mode), (4, Mode::Insert));
+        assert_eq!(editor.undo_stack.len(), 1);
+
+        let mut editor = Editor::new("one two three\n".to_string(), None);
+        editor.cursor_col = 4;
+        editor.operate_motion(Operator::Delete, b'$', 1);
+        assert_eq!(editor.get_line(0), "one ");
+    }
+
+    #[test]
+    fn block_visual_delete_and_column_insert() {
+        let mut editor = Editor::new("abcd\nefgh\nij\n".to_string(), None);
+        editor.cursor_col = 1;
+        editor.mode = Mode::VisualBlock;
+        editor.selection_anchor = Some((0, 1));
+        editor.cursor_row = 1;
+        editor.cursor_col = 2;
+        editor.yank_selection();
+        assert_eq!(editor.yank_buffer, "bc\nfg");
+        assert_eq!(editor.mode, Mode::Normal);
+
+        editor.mode = Mode::VisualBlock;
+        editor.selection_anchor = Some((0, 1));
+        editor.cursor_row = 2;
+        editor.cursor_col = 2;
+        editor.delete_selection();
+        assert_eq!(editor.rope.to_string(), "ad\neh\ni\n");
+        assert_eq!((editor.cursor_row, editor.cursor_col), (0, 1));
+
+        // I inserts at the left edge of every block row
+        editor.mode = Mode::VisualBlock;
+        editor.selection_anchor = Some((0, 1));
+        editor.cursor_row = 2;
+        editor.cursor_col = 1;
+        editor.start_block_insert(false);
+        for c in "--".chars() {
+            editor.insert_char(c);
+        }
+        handle_insert_mode(&mut editor, 0x1B);
+        assert_eq!(editor.rope.to_string(), "a--d\ne--h\ni--\n");
+
+        // A appends after the right edge, padding short lines
+        let mut editor = Editor::new("abc\na\n".to_string(), None);
+        editor.mode = Mode::VisualBlock;
+        editor.selection_anchor = Some((0, 1));
+        editor.cursor_row = 1;
+        editor.cursor_col = 1;
+        editor.start_block_insert(true);
+        editor.insert_char('|');
+        handle_insert_mode(&mut editor, 0x1B);
+        assert_eq!(editor.rope.to_string(), "ab|c\na |\n");
+    }
+
+    #[test]
+    fn line_number_gutter_renders_absolute_and_relative_numbers() {
+        let content: String = (1..=12).map(|i| format!("l{}\n", i)).collect();
+        let mut editor = Editor::new(content, None);
+        assert_eq!(editor.gutter_width(), 0);
+
+        execute_command("set number", &mut editor, "/");
+        // 13 lines (trailing newline) -> two digits plus separator
+        assert_eq!(editor.gutter_width(), 3);
+        editor.cursor_row = 4;
+        render_to_buffer(&mut editor, 20, 10);
+        let row_text = |editor: &Editor, row: usize| -> String {
+            (0..6)
+                .map(|c| editor.current_buffer.get(row, c).unwrap().ch)
+                .collect()
+        };
+        assert_eq!(row_text(&editor, 1), " 1 l1 ");
+
+        execute_command("set rnu", &mut editor, "/");
+        render_to_buffer(&mut editor, 20, 10);
+        assert_eq!(row_text(&editor, 1), " 4 l1 ");
+        assert_eq!(row_text(&editor, 5), " 5 l5 ");
+
+        execute_command("set nonu", &mut editor, "/");
+        execute_command("set nornu", &mut editor, "/");
+        assert_eq!(editor.gutter_width(), 0);
+    }
 }
 
 bindings::export!(EdtuiModule with_types_in bindings);
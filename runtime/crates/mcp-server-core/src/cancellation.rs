@@ -0,0 +1,86 @@
+//! Cancellation of in-flight requests via `notifications/cancelled`
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Shared flag a tool polls to learn that its request was cancelled
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// The underlying flag, for code that doesn't depend on this crate
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.0.clone()
+    }
+}
+
+/// Tokens of in-flight requests, keyed by JSON-RPC request id
+#[derive(Debug, Default)]
+pub struct CancellationRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+}
+
+impl CancellationRegistry {
+    /// Start tracking a request, returning the token its handler should poll
+    pub fn register(&self, id: &serde_json::Value) -> CancellationToken {
+        let token = CancellationToken::default();
+        self.lock().insert(id.to_string(), token.clone());
+        token
+    }
+
+    /// Cancel a tracked request. Returns false if it is not in flight.
+    pub fn cancel(&self, id: &serde_json::Value) -> bool {
+        match self.lock().get(&id.to_string()) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop tracking a request once it has been answered
+    pub fn remove(&self, id: &serde_json::Value) {
+        self.lock().remove(&id.to_string());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The process-wide registry used by `handle_request`
+pub fn cancellations() -> &'static CancellationRegistry {
+    static REGISTRY: OnceLock<CancellationRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(CancellationRegistry::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn cancel_sets_the_registered_flag() {
+        let registry = CancellationRegistry::default();
+        let token = registry.register(&json!(1));
+        let other = registry.register(&json!("1"));
+
+        assert!(registry.cancel(&json!(1)));
+        assert!(token.is_cancelled());
+        // Ids compare by JSON value, so 1 and "1" are different requests
+        assert!(!other.is_cancelled());
+
+        registry.remove(&json!(1));
+        assert!(!registry.cancel(&json!(1)));
+    }
+}
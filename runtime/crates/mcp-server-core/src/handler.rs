@@ -1,9 +1,94 @@
 //! MCP request handler and McpServer trait
 
-use crate::jsonrpc::{JsonRpcRequest, JsonRpcResponse};
-use crate::protocol::{Arguments, ServerInfo, ToolDefinition, ToolResult};
+use crate::cancellation::{cancellations, CancellationToken};
+use crate::jsonrpc::{CancelledParams, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::protocol::{
+    Arguments, PromptDefinition, PromptMessage, ResourceContents, ResourceDefinition, ServerInfo,
+    ToolDefinition, ToolResult,
+};
+
+/// JSON-RPC error code for resources/read on an unknown URI, per the MCP spec
+const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// Sends `notifications/progress` for a tool call whose request asked for it
+/// with `_meta.progressToken`. Reporting is a no-op otherwise.
+///
+/// Also carries the call's cancellation token, so a tool that reports
+/// progress can check whether it should stop.
+pub struct ProgressReporter<'a> {
+    token: Option<serde_json::Value>,
+    sink: Option<&'a mut dyn FnMut(JsonRpcNotification)>,
+    cancellation: CancellationToken,
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Report through `sink` under the request's progress token, if any
+    pub fn new(
+        token: Option<serde_json::Value>,
+        sink: &'a mut dyn FnMut(JsonRpcNotification),
+    ) -> Self {
+        Self {
+            token,
+            sink: Some(sink),
+            cancellation: CancellationToken::default(),
+        }
+    }
+
+    /// A reporter that drops every update
+    pub fn none() -> Self {
+        Self {
+            token: None,
+            sink: None,
+            cancellation: CancellationToken::default(),
+        }
+    }
+
+    /// Tie the call to a request's cancellation token
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Whether the client has cancelled this call
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// The call's cancellation token, to hand to work that outlives this borrow
+    pub fn cancellation(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Whether the client asked for progress (so reporting is worthwhile)
+    pub fn is_requested(&self) -> bool {
+        self.token.is_some() && self.sink.is_some()
+    }
+
+    /// Report `progress` so far, out of `total` if known
+    pub fn report(&mut self, progress: f64, total: Option<f64>, message: Option<String>) {
+        if let (Some(token), Some(sink)) = (&self.token, self.sink.as_mut()) {
+            sink(JsonRpcNotification::progress(
+                token.clone(),
+                progress,
+                total,
+                message,
+            ));
+        }
+    }
+}
 
 /// MCP Server trait - implement this to create an MCP server
+///
+/// ## Cancellation
+/// A client may send `notifications/cancelled` for a `tools/call` still in
+/// flight. The handler then sets the call's [`CancellationToken`], reachable
+/// through [`ProgressReporter::is_cancelled`]. Cancellation is cooperative:
+/// a tool polls the token at convenient points and returns early (typically
+/// with an error result); one that never polls simply runs to completion.
+/// A response is still sent either way. The notification only reaches the
+/// call if the transport keeps reading while it runs and shares this
+/// process; a host that gives every request a fresh instance has to cancel
+/// the call itself.
 pub trait McpServer {
     /// Return server information
     fn server_info(&self) -> ServerInfo;
@@ -13,10 +98,55 @@ pub trait McpServer {
 
     /// Call a tool with the given arguments
     fn call_tool(&mut self, name: &str, arguments: Arguments) -> ToolResult;
+
+    /// Call a tool that may report progress while it runs. Servers without
+    /// long-running tools can rely on the default, which ignores `progress`.
+    fn call_tool_with_progress(
+        &mut self,
+        name: &str,
+        arguments: Arguments,
+        progress: &mut ProgressReporter<'_>,
+    ) -> ToolResult {
+        let _ = progress;
+        self.call_tool(name, arguments)
+    }
+
+    /// List available resources (none by default)
+    fn list_resources(&self) -> Vec<ResourceDefinition> {
+        Vec::new()
+    }
+
+    /// Read a resource by URI; `None` if there is no such resource
+    fn read_resource(&self, uri: &str) -> Option<ResourceContents> {
+        let _ = uri;
+        None
+    }
+
+    /// List available prompt templates (none by default)
+    fn list_prompts(&self) -> Vec<PromptDefinition> {
+        Vec::new()
+    }
+
+    /// Render a prompt with the given arguments. Errors (unknown prompt,
+    /// missing argument) are reported to the client as invalid params.
+    fn get_prompt(&self, name: &str, arguments: Arguments) -> Result<Vec<PromptMessage>, String> {
+        let _ = arguments;
+        Err(format!("Unknown prompt: {}", name))
+    }
 }
 
 /// Handle an MCP JSON-RPC request
 pub fn handle_request<S: McpServer>(server: &mut S, request: JsonRpcRequest) -> JsonRpcResponse {
+    handle_request_with_progress(server, request, &mut |_| {})
+}
+
+/// Handle an MCP JSON-RPC request, passing progress notifications from a
+/// tool call to `on_notification` as they happen (before the response).
+pub fn handle_request_with_progress<S: McpServer>(
+    server: &mut S,
+    request: JsonRpcRequest,
+    on_notification: &mut dyn FnMut(JsonRpcNotification),
+) -> JsonRpcResponse {
     match request.method.as_str() {
         "initialize" => {
             let info = server.server_info();
@@ -64,42 +194,113 @@ pub fn handle_request<S: McpServer>(server: &mut S, request: JsonRpcRequest) ->
                     .unwrap_or(serde_json::json!({})),
             );
 
+            let token = request
+                .params
+                .get("_meta")
+                .and_then(|meta| meta.get("progressToken"))
+                .cloned();
+
             match name {
                 Some(name) => {
-                    let result = server.call_tool(name, arguments);
+                    let cancellation = request
+                        .id
+                        .as_ref()
+                        .map(|id| cancellations().register(id))
+                        .unwrap_or_default();
+                    let mut progress = ProgressReporter::new(token, on_notification)
+                        .with_cancellation(cancellation);
+                    let result = server.call_tool_with_progress(name, arguments, &mut progress);
+                    if let Some(id) = &request.id {
+                        cancellations().remove(id);
+                    }
                     JsonRpcResponse::success(request.id, serde_json::to_value(result).unwrap())
                 }
                 None => JsonRpcResponse::error(request.id, -32602, "Missing tool name".to_string()),
             }
         }
 
-        // Resources - noop stubs per spec
         "resources/list" => {
-            JsonRpcResponse::success(request.id, serde_json::json!({ "resources": [] }))
+            let resources = server.list_resources();
+            JsonRpcResponse::success(request.id, serde_json::json!({ "resources": resources }))
         }
 
         "resources/read" => {
-            JsonRpcResponse::error(request.id, -32601, "Resources not supported".to_string())
+            let uri = request.params.get("uri").and_then(|v| v.as_str());
+            match uri {
+                Some(uri) => match server.read_resource(uri) {
+                    Some(contents) => JsonRpcResponse::success(
+                        request.id,
+                        serde_json::json!({ "contents": [contents] }),
+                    ),
+                    None => {
+                        let mut response = JsonRpcResponse::error(
+                            request.id,
+                            RESOURCE_NOT_FOUND,
+                            format!("Resource not found: {}", uri),
+                        );
+                        if let Some(error) = response.error.as_mut() {
+                            error.data = Some(serde_json::json!({ "uri": uri }));
+                        }
+                        response
+                    }
+                },
+                None => {
+                    JsonRpcResponse::error(request.id, -32602, "Missing resource uri".to_string())
+                }
+            }
         }
 
         "resources/templates/list" => {
             JsonRpcResponse::success(request.id, serde_json::json!({ "resourceTemplates": [] }))
         }
 
-        // Prompts - noop stubs
         "prompts/list" => {
-            JsonRpcResponse::success(request.id, serde_json::json!({ "prompts": [] }))
+            let prompts = server.list_prompts();
+            JsonRpcResponse::success(request.id, serde_json::json!({ "prompts": prompts }))
         }
 
         "prompts/get" => {
-            JsonRpcResponse::error(request.id, -32601, "No prompts available".to_string())
+            let name = request.params.get("name").and_then(|v| v.as_str());
+            let arguments = Arguments::new(
+                request
+                    .params
+                    .get("arguments")
+                    .cloned()
+                    .unwrap_or(serde_json::json!({})),
+            );
+
+            match name {
+                Some(name) => match server.get_prompt(name, arguments) {
+                    Ok(messages) => {
+                        let mut result = serde_json::json!({ "messages": messages });
+                        if let Some(description) = server
+                            .list_prompts()
+                            .into_iter()
+                            .find(|p| p.name == name)
+                            .and_then(|p| p.description)
+                        {
+                            result["description"] = serde_json::json!(description);
+                        }
+                        JsonRpcResponse::success(request.id, result)
+                    }
+                    Err(e) => JsonRpcResponse::error(request.id, -32602, e),
+                },
+                None => {
+                    JsonRpcResponse::error(request.id, -32602, "Missing prompt name".to_string())
+                }
+            }
         }
 
         // Logging
         "logging/setLevel" => JsonRpcResponse::success(request.id, serde_json::json!({})),
 
-        // Cancellation - acknowledge but no-op
-        "notifications/cancelled" => JsonRpcResponse::success(request.id, serde_json::json!({})),
+        // Cancellation - flag the in-flight request, if we still have it
+        "notifications/cancelled" => {
+            if let Ok(params) = serde_json::from_value::<CancelledParams>(request.params) {
+                cancellations().cancel(&params.request_id);
+            }
+            JsonRpcResponse::success(request.id, serde_json::json!({}))
+        }
 
         _ => JsonRpcResponse::error(
             request.id,
@@ -108,3 +309,350 @@ pub fn handle_request<S: McpServer>(server: &mut S, request: JsonRpcRequest) ->
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::PromptArgument;
+    use serde_json::json;
+
+    struct ToolsOnly;
+
+    impl McpServer for ToolsOnly {
+        fn server_info(&self) -> ServerInfo {
+            ServerInfo {
+                name: "tools-only".to_string(),
+                version: "0.1.0".to_string(),
+            }
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![]
+        }
+
+        fn call_tool(&mut self, _name: &str, _arguments: Arguments) -> ToolResult {
+            ToolResult::text("ok")
+        }
+    }
+
+    struct WithResources;
+
+    impl McpServer for WithResources {
+        fn server_info(&self) -> ServerInfo {
+            ToolsOnly.server_info()
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![]
+        }
+
+        fn call_tool(&mut self, _name: &str, _arguments: Arguments) -> ToolResult {
+            ToolResult::text("ok")
+        }
+
+        fn list_resources(&self) -> Vec<ResourceDefinition> {
+            vec![ResourceDefinition::new("file:///readme.md", "readme.md")]
+        }
+
+        fn read_resource(&self, uri: &str) -> Option<ResourceContents> {
+            (uri == "file:///readme.md").then(|| ResourceContents::text(uri, "# Readme"))
+        }
+    }
+
+    struct WithPrompts;
+
+    impl McpServer for WithPrompts {
+        fn server_info(&self) -> ServerInfo {
+            ToolsOnly.server_info()
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![]
+        }
+
+        fn call_tool(&mut self, _name: &str, _arguments: Arguments) -> ToolResult {
+            ToolResult::text("ok")
+        }
+
+        fn list_prompts(&self) -> Vec<PromptDefinition> {
+            vec![PromptDefinition {
+                name: "plan".to_string(),
+                title: None,
+                description: Some("Write a plan".to_string()),
+                arguments: vec![PromptArgument::required("goal", "What to plan for")],
+            }]
+        }
+
+        fn get_prompt(
+            &self,
+            name: &str,
+            arguments: Arguments,
+        ) -> Result<Vec<PromptMessage>, String> {
+            match name {
+                "plan" => {
+                    let goal = arguments.get_string("goal")?;
+                    Ok(vec![PromptMessage::user(format!(
+                        "Write a plan to {}",
+                        goal
+                    ))])
+                }
+                _ => Err(format!("Unknown prompt: {}", name)),
+            }
+        }
+    }
+
+    struct Counting;
+
+    impl McpServer for Counting {
+        fn server_info(&self) -> ServerInfo {
+            ToolsOnly.server_info()
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![]
+        }
+
+        fn call_tool(&mut self, name: &str, arguments: Arguments) -> ToolResult {
+            self.call_tool_with_progress(name, arguments, &mut ProgressReporter::none())
+        }
+
+        fn call_tool_with_progress(
+            &mut self,
+            _name: &str,
+            _arguments: Arguments,
+            progress: &mut ProgressReporter<'_>,
+        ) -> ToolResult {
+            for step in 1..=3 {
+                progress.report(step as f64, Some(3.0), None);
+            }
+            ToolResult::text("counted")
+        }
+    }
+
+    fn request(method: &str, params: serde_json::Value) -> JsonRpcRequest {
+        serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params
+        }))
+        .unwrap()
+    }
+
+    fn to_json(response: JsonRpcResponse) -> serde_json::Value {
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[test]
+    fn resources_default_to_empty() {
+        let response = to_json(handle_request(
+            &mut ToolsOnly,
+            request("resources/list", json!({})),
+        ));
+        assert_eq!(response["result"], json!({"resources": []}));
+
+        let response = to_json(handle_request(
+            &mut ToolsOnly,
+            request("resources/read", json!({"uri": "file:///readme.md"})),
+        ));
+        assert_eq!(response["error"]["code"], RESOURCE_NOT_FOUND);
+    }
+
+    #[test]
+    fn resources_list_and_read() {
+        let response = to_json(handle_request(
+            &mut WithResources,
+            request("resources/list", json!({})),
+        ));
+        assert_eq!(
+            response["result"]["resources"],
+            json!([{"uri": "file:///readme.md", "name": "readme.md"}])
+        );
+
+        let response = to_json(handle_request(
+            &mut WithResources,
+            request("resources/read", json!({"uri": "file:///readme.md"})),
+        ));
+        assert_eq!(
+            response["result"]["contents"],
+            json!([{"uri": "file:///readme.md", "text": "# Readme"}])
+        );
+    }
+
+    #[test]
+    fn resources_read_errors() {
+        let response = to_json(handle_request(
+            &mut WithResources,
+            request("resources/read", json!({"uri": "file:///missing"})),
+        ));
+        assert_eq!(response["error"]["code"], RESOURCE_NOT_FOUND);
+        assert_eq!(response["error"]["data"], json!({"uri": "file:///missing"}));
+        assert!(response.get("result").is_none());
+
+        let response = to_json(handle_request(
+            &mut WithResources,
+            request("resources/read", json!({})),
+        ));
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn prompts_default_to_empty() {
+        let response = to_json(handle_request(
+            &mut ToolsOnly,
+            request("prompts/list", json!({})),
+        ));
+        assert_eq!(response["result"], json!({"prompts": []}));
+
+        let response = to_json(handle_request(
+            &mut ToolsOnly,
+            request("prompts/get", json!({"name": "plan"})),
+        ));
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn prompts_list_and_get() {
+        let response = to_json(handle_request(
+            &mut WithPrompts,
+            request("prompts/list", json!({})),
+        ));
+        assert_eq!(response["result"]["prompts"][0]["name"], "plan");
+        assert_eq!(
+            response["result"]["prompts"][0]["arguments"][0]["required"],
+            true
+        );
+
+        let response = to_json(handle_request(
+            &mut WithPrompts,
+            request(
+                "prompts/get",
+                json!({"name": "plan", "arguments": {"goal": "ship v2"}}),
+            ),
+        ));
+        assert_eq!(
+            response["result"],
+            json!({
+                "description": "Write a plan",
+                "messages": [
+                    {"role": "user", "content": {"type": "text", "text": "Write a plan to ship v2"}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn prompts_get_missing_argument_is_invalid_params() {
+        let response = to_json(handle_request(
+            &mut WithPrompts,
+            request("prompts/get", json!({"name": "plan"})),
+        ));
+        assert_eq!(response["error"]["code"], -32602);
+        assert_eq!(
+            response["error"]["message"],
+            "Missing required parameter: goal"
+        );
+    }
+
+    #[test]
+    fn tool_progress_is_forwarded_under_the_request_token() {
+        let mut notifications = Vec::new();
+        let response = handle_request_with_progress(
+            &mut Counting,
+            request(
+                "tools/call",
+                json!({"name": "count", "_meta": {"progressToken": "tok-1"}}),
+            ),
+            &mut |n| notifications.push(serde_json::to_value(n).unwrap()),
+        );
+
+        assert_eq!(notifications.len(), 3);
+        assert_eq!(
+            notifications[2]["params"],
+            json!({"progressToken": "tok-1", "progress": 3.0, "total": 3.0})
+        );
+        assert_eq!(to_json(response)["result"]["content"][0]["text"], "counted");
+    }
+
+    #[test]
+    fn tool_progress_without_token_is_dropped() {
+        let mut notifications = Vec::new();
+        handle_request_with_progress(
+            &mut Counting,
+            request("tools/call", json!({"name": "count"})),
+            &mut |n| notifications.push(n),
+        );
+        assert!(notifications.is_empty());
+
+        // Servers that only implement call_tool are unaffected
+        let response = to_json(handle_request_with_progress(
+            &mut ToolsOnly,
+            request(
+                "tools/call",
+                json!({"name": "any", "_meta": {"progressToken": 1}}),
+            ),
+            &mut |_| panic!("no progress expected"),
+        ));
+        assert_eq!(response["result"]["content"][0]["text"], "ok");
+    }
+
+    #[test]
+    fn cancelled_notification_reaches_blocked_call() {
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        /// Blocks until cancelled, telling the test once it is running
+        struct Blocking(mpsc::Sender<()>);
+
+        impl McpServer for Blocking {
+            fn server_info(&self) -> ServerInfo {
+                ToolsOnly.server_info()
+            }
+
+            fn list_tools(&self) -> Vec<ToolDefinition> {
+                vec![]
+            }
+
+            fn call_tool(&mut self, name: &str, arguments: Arguments) -> ToolResult {
+                self.call_tool_with_progress(name, arguments, &mut ProgressReporter::none())
+            }
+
+            fn call_tool_with_progress(
+                &mut self,
+                _name: &str,
+                _arguments: Arguments,
+                progress: &mut ProgressReporter<'_>,
+            ) -> ToolResult {
+                self.0.send(()).unwrap();
+                let deadline = Instant::now() + Duration::from_secs(5);
+                while !progress.is_cancelled() {
+                    if Instant::now() > deadline {
+                        return ToolResult::text("finished");
+                    }
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                ToolResult::error("cancelled")
+            }
+        }
+
+        let (started_tx, started) = mpsc::channel();
+        let call = std::thread::spawn(move || {
+            let mut call = request("tools/call", json!({"name": "slow"}));
+            call.id = Some(json!("cancel-me"));
+            to_json(handle_request(&mut Blocking(started_tx), call))
+        });
+
+        // The notification arrives on another handler while the call is blocked
+        started.recv().unwrap();
+        handle_request(
+            &mut ToolsOnly,
+            request(
+                "notifications/cancelled",
+                json!({"requestId": "cancel-me", "reason": "user"}),
+            ),
+        );
+        let response = call.join().unwrap();
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(response["result"]["content"][0]["text"], "cancelled");
+    }
+}
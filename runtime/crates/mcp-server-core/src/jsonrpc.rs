@@ -57,6 +57,16 @@ impl JsonRpcResponse {
     }
 }
 
+/// Params of `notifications/cancelled`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledParams {
+    /// Id of the request to cancel
+    pub request_id: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 /// JSON-RPC Notification (no id, no response expected)
 #[derive(Debug, Serialize)]
 pub struct JsonRpcNotification {
@@ -75,9 +85,10 @@ impl JsonRpcNotification {
         }
     }
 
-    /// Create a notifications/progress notification
+    /// Create a notifications/progress notification.
+    /// The token is echoed as given, so it may be a string or a number.
     pub fn progress(
-        progress_token: impl Into<String>,
+        progress_token: impl Into<serde_json::Value>,
         progress: f64,
         total: Option<f64>,
         message: Option<String>,
@@ -99,6 +110,16 @@ impl JsonRpcNotification {
         }
     }
 
+    /// Create a notifications/cancelled notification for an in-flight request
+    pub fn cancelled(request_id: serde_json::Value, reason: Option<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: serde_json::to_value(CancelledParams { request_id, reason })
+                .unwrap_or_default(),
+        }
+    }
+
     /// Serialize to SSE event format
     pub fn to_sse_event(&self) -> String {
         let data = serde_json::to_string(self).unwrap_or_default();
@@ -129,6 +150,26 @@ mod tests {
         assert!(!json.contains("\"error\""));
     }
 
+    #[test]
+    fn test_progress_notification_keeps_token_type() {
+        let notification = JsonRpcNotification::progress(7, 1.0, Some(4.0), None);
+        let value = serde_json::to_value(&notification).unwrap();
+        assert_eq!(value["method"], "notifications/progress");
+        assert_eq!(
+            value["params"],
+            json!({"progressToken": 7, "progress": 1.0, "total": 4.0})
+        );
+    }
+
+    #[test]
+    fn test_cancelled_notification_round_trips() {
+        let notification = JsonRpcNotification::cancelled(json!(3), Some("timeout".into()));
+        assert_eq!(notification.method, "notifications/cancelled");
+        let params: CancelledParams = serde_json::from_value(notification.params).unwrap();
+        assert_eq!(params.request_id, json!(3));
+        assert_eq!(params.reason.as_deref(), Some("timeout"));
+    }
+
     #[test]
     fn test_json_rpc_response_error() {
         let resp = JsonRpcResponse::error(Some(json!(1)), -32600, "Invalid Request".to_string());
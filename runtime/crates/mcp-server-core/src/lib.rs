@@ -7,6 +7,7 @@
 //! - JSON-RPC 2.0 request/response types
 //! - MCP protocol types (tools, resources, prompts)
 //! - SSE notification helpers
+//! - Cooperative cancellation of in-flight tool calls
 //! - McpServer trait for implementing servers
 //!
 //! ## Example
@@ -33,13 +34,18 @@
 //! }
 //! ```
 
+mod cancellation;
 mod handler;
 mod jsonrpc;
 mod protocol;
 
-pub use handler::{handle_request, McpServer};
-pub use jsonrpc::{JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+pub use cancellation::{cancellations, CancellationRegistry, CancellationToken};
+pub use handler::{handle_request, handle_request_with_progress, McpServer, ProgressReporter};
+pub use jsonrpc::{
+    CancelledParams, JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+};
 pub use protocol::{
-    Arguments, LogLevel, LogMessage, ServerInfo, ToolAnnotations, ToolContent, ToolDefinition,
-    ToolResult,
+    Arguments, LogLevel, LogMessage, PromptArgument, PromptDefinition, PromptMessage,
+    ResourceContents, ResourceDefinition, Role, ServerInfo, ToolAnnotations, ToolContent,
+    ToolDefinition, ToolResult,
 };
@@ -104,6 +104,160 @@ pub struct ToolDefinition {
     pub annotations: Option<ToolAnnotations>,
 }
 
+/// MCP Resource Definition, as listed by resources/list
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceDefinition {
+    pub uri: String,
+    pub name: String,
+    /// Human-readable display name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Size in bytes, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl ResourceDefinition {
+    /// Create a resource definition with just a URI and name
+    pub fn new(uri: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            name: name.into(),
+            title: None,
+            description: None,
+            mime_type: None,
+            size: None,
+        }
+    }
+}
+
+/// MCP Resource Contents, as returned by resources/read.
+/// Exactly one of `text` or `blob` (base64) is set.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+impl ResourceContents {
+    /// Create text contents
+    pub fn text(uri: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            mime_type: None,
+            text: Some(text.into()),
+            blob: None,
+        }
+    }
+
+    /// Create binary contents from base64-encoded data
+    pub fn blob(
+        uri: impl Into<String>,
+        data: impl Into<String>,
+        mime_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            uri: uri.into(),
+            mime_type: Some(mime_type.into()),
+            text: None,
+            blob: Some(data.into()),
+        }
+    }
+
+    /// Set the MIME type
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+}
+
+/// An argument accepted by a prompt template
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+impl PromptArgument {
+    /// Create a required argument
+    pub fn required(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            required: Some(true),
+        }
+    }
+
+    /// Create an optional argument
+    pub fn optional(name: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: Some(description.into()),
+            required: None,
+        }
+    }
+}
+
+/// MCP Prompt Definition, as listed by prompts/list
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PromptDefinition {
+    pub name: String,
+    /// Human-readable display name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub arguments: Vec<PromptArgument>,
+}
+
+/// Speaker of a prompt message
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// One message of a prompt returned by prompts/get
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PromptMessage {
+    pub role: Role,
+    pub content: ToolContent,
+}
+
+impl PromptMessage {
+    /// Create a text message from the user
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: ToolContent::text(text),
+        }
+    }
+
+    /// Create a text message from the assistant
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: ToolContent::text(text),
+        }
+    }
+}
+
 /// MCP Tool Result - aligned with rmcp's CallToolResult
 #[derive(Debug, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -158,6 +312,36 @@ impl Arguments {
             .ok_or_else(|| format!("Missing required parameter: {}", key))
     }
 
+    /// Get an optional boolean parameter
+    pub fn get_optional_bool(&self, key: &str) -> Option<bool> {
+        self.0.get(key).and_then(|v| v.as_bool())
+    }
+
+    /// Get an optional integer parameter that fits in an i64
+    pub fn get_optional_i64(&self, key: &str) -> Option<i64> {
+        self.0.get(key).and_then(|v| v.as_i64())
+    }
+
+    /// Get an optional non-negative integer parameter
+    pub fn get_optional_u64(&self, key: &str) -> Option<u64> {
+        self.0.get(key).and_then(|v| v.as_u64())
+    }
+
+    /// Get an optional number parameter (integers are accepted too)
+    pub fn get_optional_f64(&self, key: &str) -> Option<f64> {
+        self.0.get(key).and_then(|v| v.as_f64())
+    }
+
+    /// Get an optional array of strings; `None` if any element is not a string
+    pub fn get_optional_string_array(&self, key: &str) -> Option<Vec<String>> {
+        self.0
+            .get(key)?
+            .as_array()?
+            .iter()
+            .map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()
+    }
+
     /// Get the inner JSON value
     pub fn inner(&self) -> &serde_json::Value {
         &self.0
@@ -410,6 +594,43 @@ mod tests {
         assert!(args.get_bool("recursive").is_err());
     }
 
+    #[test]
+    fn arguments_get_optional_i64_and_u64() {
+        let args = Arguments::new(json!({"offset": -3, "big": u64::MAX}));
+        assert_eq!(args.get_optional_i64("offset"), Some(-3));
+        assert_eq!(args.get_optional_u64("offset"), None);
+        assert_eq!(args.get_optional_i64("big"), None);
+        assert_eq!(args.get_optional_u64("big"), Some(u64::MAX));
+    }
+
+    #[test]
+    fn arguments_get_optional_f64_accepts_integers() {
+        let args = Arguments::new(json!({"ratio": 0.5, "count": 2}));
+        assert_eq!(args.get_optional_f64("ratio"), Some(0.5));
+        assert_eq!(args.get_optional_f64("count"), Some(2.0));
+    }
+
+    #[test]
+    fn arguments_typed_getters_do_not_coerce_strings() {
+        let args = Arguments::new(json!({"count": "7", "recursive": "true"}));
+        assert_eq!(args.get_optional_i64("count"), None);
+        assert_eq!(args.get_optional_f64("count"), None);
+        assert_eq!(args.get_optional_bool("recursive"), None);
+    }
+
+    #[test]
+    fn arguments_get_optional_string_array() {
+        let args =
+            Arguments::new(json!({"files": ["a.txt", "b.txt"], "mixed": ["a", 1], "one": "a"}));
+        assert_eq!(
+            args.get_optional_string_array("files"),
+            Some(vec!["a.txt".to_string(), "b.txt".to_string()])
+        );
+        assert_eq!(args.get_optional_string_array("mixed"), None);
+        assert_eq!(args.get_optional_string_array("one"), None);
+        assert_eq!(args.get_optional_string_array("missing"), None);
+    }
+
     #[test]
     fn arguments_inner_returns_value() {
         let val = json!({"key": "value"});
@@ -417,6 +638,68 @@ mod tests {
         assert_eq!(args.inner(), &val);
     }
 
+    // ---- Resource serialization tests ----
+
+    #[test]
+    fn resource_definition_skips_unset_fields() {
+        let mut resource = ResourceDefinition::new("file:///notes.md", "notes.md");
+        resource.mime_type = Some("text/markdown".to_string());
+        let serialized = serde_json::to_value(&resource).unwrap();
+        assert_eq!(
+            serialized,
+            json!({"uri": "file:///notes.md", "name": "notes.md", "mimeType": "text/markdown"})
+        );
+    }
+
+    #[test]
+    fn resource_contents_text_and_blob_serialize_correctly() {
+        let text = ResourceContents::text("file:///a.txt", "hello").with_mime_type("text/plain");
+        assert_eq!(
+            serde_json::to_value(&text).unwrap(),
+            json!({"uri": "file:///a.txt", "mimeType": "text/plain", "text": "hello"})
+        );
+
+        let blob = ResourceContents::blob("file:///a.png", "iVBORw0K", "image/png");
+        assert_eq!(
+            serde_json::to_value(&blob).unwrap(),
+            json!({"uri": "file:///a.png", "mimeType": "image/png", "blob": "iVBORw0K"})
+        );
+    }
+
+    // ---- Prompt serialization tests ----
+
+    #[test]
+    fn prompt_definition_serializes_arguments() {
+        let prompt = PromptDefinition {
+            name: "plan".to_string(),
+            title: None,
+            description: Some("Write a plan".to_string()),
+            arguments: vec![
+                PromptArgument::required("goal", "What to plan for"),
+                PromptArgument::optional("steps", "How many steps"),
+            ],
+        };
+        assert_eq!(
+            serde_json::to_value(&prompt).unwrap(),
+            json!({
+                "name": "plan",
+                "description": "Write a plan",
+                "arguments": [
+                    {"name": "goal", "description": "What to plan for", "required": true},
+                    {"name": "steps", "description": "How many steps"}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn prompt_message_serializes_role_and_content() {
+        assert_eq!(
+            serde_json::to_value(PromptMessage::assistant("ok")).unwrap(),
+            json!({"role": "assistant", "content": {"type": "text", "text": "ok"}})
+        );
+    }
+
     // ---- ToolContent serialization tests ----
 
     #[test]
@@ -4,11 +4,16 @@
 
 #[allow(warnings)]
 mod bindings;
+mod wasi_io;
 
 use bindings::exports::shell::unix::command::{ExecEnv, Guest};
+use bindings::wasi::cli::terminal_stdin::get_terminal_stdin;
 use bindings::wasi::io::streams::{InputStream, OutputStream};
+use std::io::Write;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
-use turso_core::{Database, MemoryIO, Value, IO};
+use turso_core::{Connection, Database, MemoryIO, Value, IO};
+use wasi_io::WasiIO;
 
 struct SqliteModule;
 
@@ -16,13 +21,13 @@ impl Guest for SqliteModule {
     fn run(
         name: String,
         args: Vec<String>,
-        _env: ExecEnv,
+        env: ExecEnv,
         stdin: InputStream,
         stdout: OutputStream,
         stderr: OutputStream,
     ) -> i32 {
         match name.as_str() {
-            "sqlite3" => run_sqlite3(args, stdin, stdout, stderr),
+            "sqlite3" => run_sqlite3(args, &env.cwd, stdin, stdout, stderr),
             _ => {
                 write_to_stream(&stderr, format!("Unknown command: {}\n", name).as_bytes());
                 127
@@ -38,6 +43,7 @@ impl Guest for SqliteModule {
 /// Execute SQLite commands
 fn run_sqlite3(
     args: Vec<String>,
+    cwd: &str,
     stdin: InputStream,
     stdout: OutputStream,
     stderr: OutputStream,
@@ -67,9 +73,14 @@ fn run_sqlite3(
         }
     };
 
+    // Without SQL, a terminal on stdin gets an interactive prompt
+    let interactive = sql_arg.is_none() && get_terminal_stdin().is_some();
+
     // Get SQL from arg or stdin
     let sql = if let Some(s) = sql_arg {
         s
+    } else if interactive {
+        String::new()
     } else {
         match read_all_from_stream(&stdin) {
             Ok(data) => String::from_utf8_lossy(&data).to_string(),
@@ -83,28 +94,13 @@ fn run_sqlite3(
         }
     };
 
-    if sql.trim().is_empty() {
+    if !interactive && sql.trim().is_empty() {
         write_to_stream(&stderr, b"Error: no SQL provided\n");
         return 1;
     }
 
-    // Create IO backend (memory only for now - WASI filesystem support would need WasiIO)
-    let io: Arc<dyn IO> = Arc::new(MemoryIO::new());
-
-    // Open database
-    let db = match Database::open_file(io.clone(), &db_path) {
-        Ok(db) => db,
-        Err(e) => {
-            write_to_stream(
-                &stderr,
-                format!("Error: unable to open database \"{}\": {}\n", db_path, e).as_bytes(),
-            );
-            return 1;
-        }
-    };
-
-    // Get a connection
-    let conn = match db.connect() {
+    let db_path = resolve_db_path(cwd, &db_path);
+    let conn = match open_connection(&db_path) {
         Ok(c) => c,
         Err(e) => {
             write_to_stream(&stderr, format!("Error: {}\n", e).as_bytes());
@@ -112,46 +108,625 @@ fn run_sqlite3(
         }
     };
 
-    // Execute SQL statements
-    let statements: Vec<&str> = sql
-        .split(';')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
+    let mut session = Session {
+        conn,
+        settings: OutputSettings::default(),
+        pending_sql: String::new(),
+    };
 
-    for stmt_sql in statements {
-        let mut stmt = match conn.query(stmt_sql) {
-            Ok(Some(s)) => s,
-            Ok(None) => continue,
+    let mut out = StreamWriter(&stdout);
+    let mut err = StreamWriter(&stderr);
+    if interactive {
+        let mut read_byte = || match stdin.blocking_read(1) {
+            Ok(bytes) => bytes.first().copied(),
+            Err(_) => None,
+        };
+        return run_repl(&mut session, cwd, &mut read_byte, &mut out, &mut err);
+    }
+
+    for line in sql.lines() {
+        match session.feed_line(line, cwd, &mut out, &mut err) {
+            Ok(DotOutcome::Continue) => {}
+            Ok(DotOutcome::Quit) => return 0,
             Err(e) => {
                 write_to_stream(&stderr, format!("Error: {}\n", e).as_bytes());
                 return 1;
             }
+        }
+    }
+
+    if let Err(e) = session.flush(&mut out) {
+        write_to_stream(&stderr, format!("Error: {}\n", e).as_bytes());
+        return 1;
+    }
+
+    0
+}
+
+/// Open `db_path` (already resolved against the cwd) and connect to it
+fn open_connection(db_path: &str) -> Result<Arc<Connection>, String> {
+    // Create IO backend based on database path
+    let io: Arc<dyn IO> = if db_path == ":memory:" {
+        Arc::new(MemoryIO::new())
+    } else {
+        Arc::new(WasiIO::new())
+    };
+
+    let db = Database::open_file(io, db_path)
+        .map_err(|e| format!("unable to open database \"{}\": {}", db_path, e))?;
+    db.connect().map_err(|e| e.to_string())
+}
+
+/// One connection plus the state carried between input lines
+struct Session {
+    conn: Arc<Connection>,
+    settings: OutputSettings,
+    pending_sql: String,
+}
+
+impl Session {
+    /// Whether the buffered SQL is empty or ends at a statement boundary
+    fn at_statement_boundary(&self) -> bool {
+        let trimmed = self.pending_sql.trim();
+        trimmed.is_empty() || trimmed.ends_with(';')
+    }
+
+    /// Buffer one line of input.
+    ///
+    /// Dot-commands are recognized at the start of a line between statements;
+    /// SQL runs up to that point first so settings apply in order.
+    fn feed_line(
+        &mut self,
+        line: &str,
+        cwd: &str,
+        stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<DotOutcome, String> {
+        if line.trim_start().starts_with('.') && self.at_statement_boundary() {
+            self.flush(stdout)?;
+            run_dot_command(
+                &self.conn,
+                line.trim(),
+                &mut self.settings,
+                cwd,
+                stdout,
+                stderr,
+            )
+        } else {
+            self.pending_sql.push_str(line);
+            self.pending_sql.push('\n');
+            Ok(DotOutcome::Continue)
+        }
+    }
+
+    /// Execute and clear the buffered SQL
+    fn flush(&mut self, stdout: &mut dyn Write) -> Result<(), String> {
+        let sql = std::mem::take(&mut self.pending_sql);
+        execute_sql(&self.conn, &sql, &self.settings, stdout)
+    }
+}
+
+/// Interactive prompt: statements run as soon as a line completes them.
+/// `read_byte` yields raw terminal input and returns None once it closes.
+fn run_repl(
+    session: &mut Session,
+    cwd: &str,
+    read_byte: &mut dyn FnMut() -> Option<u8>,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> i32 {
+    loop {
+        let prompt = if session.pending_sql.trim().is_empty() {
+            "sqlite> "
+        } else {
+            "   ...> "
         };
+        let _ = stdout.write_all(prompt.as_bytes());
 
-        // Collect output and write after
-        let mut output_lines: Vec<String> = Vec::new();
+        let Some(line) = read_repl_line(read_byte, stdout) else {
+            return 0;
+        };
 
-        let result = stmt.run_with_row_callback(|row| {
-            let values = row.get_values();
-            let formatted: Vec<String> = values.map(format_value).collect();
-            output_lines.push(formatted.join("|"));
-            Ok(())
-        });
+        let result = session
+            .feed_line(&line, cwd, stdout, stderr)
+            .and_then(|outcome| {
+                if !session.pending_sql.trim().is_empty() && session.at_statement_boundary() {
+                    session.flush(stdout)?;
+                }
+                Ok(outcome)
+            });
+        match result {
+            Ok(DotOutcome::Continue) => {}
+            Ok(DotOutcome::Quit) => return 0,
+            Err(e) => {
+                // A failed statement is dropped and the session continues
+                session.pending_sql.clear();
+                let _ = stderr.write_all(format!("Error: {}\n", e).as_bytes());
+            }
+        }
+    }
+}
 
-        // Write collected output
-        for line in &output_lines {
-            write_to_stream(&stdout, line.as_bytes());
-            write_to_stream(&stdout, b"\n");
+/// Read one line from a raw terminal, echoing input.
+/// Returns None on Ctrl+D at the start of a line or when input closes.
+fn read_repl_line(
+    read_byte: &mut dyn FnMut() -> Option<u8>,
+    echo: &mut dyn Write,
+) -> Option<String> {
+    let mut line: Vec<u8> = Vec::new();
+    loop {
+        let byte = read_byte()?;
+        // Echo is best-effort; a lost echo does not change the input
+        match byte {
+            b'\r' | b'\n' => {
+                let _ = echo.write_all(b"\r\n");
+                return Some(String::from_utf8_lossy(&line).to_string());
+            }
+            0x04 if line.is_empty() => {
+                let _ = echo.write_all(b"\r\n");
+                return None;
+            }
+            0x03 => {
+                // Ctrl+C abandons the current line
+                let _ = echo.write_all(b"^C\r\n");
+                return Some(String::new());
+            }
+            0x7F | 0x08 => {
+                // Erase a whole UTF-8 character, not just its last byte
+                while let Some(b) = line.pop() {
+                    if b & 0xC0 != 0x80 {
+                        let _ = echo.write_all(b"\x08 \x08");
+                        break;
+                    }
+                }
+            }
+            b if b >= 0x20 || b == b'\t' => {
+                line.push(b);
+                let _ = echo.write_all(&[b]);
+            }
+            _ => {}
         }
+    }
+}
 
-        if let Err(e) = result {
-            write_to_stream(&stderr, format!("Error: {}\n", e).as_bytes());
-            return 1;
+/// Output format selected with `.mode`
+#[derive(Clone, Copy, PartialEq)]
+enum OutputMode {
+    List,
+    Csv,
+    Column,
+    Json,
+}
+
+impl OutputMode {
+    fn name(self) -> &'static str {
+        match self {
+            OutputMode::List => "list",
+            OutputMode::Csv => "csv",
+            OutputMode::Column => "column",
+            OutputMode::Json => "json",
         }
     }
+}
 
-    0
+/// Settings changed by dot-commands and applied to every result set
+struct OutputSettings {
+    mode: OutputMode,
+    headers: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self {
+            mode: OutputMode::List,
+            headers: false,
+        }
+    }
+}
+
+enum DotOutcome {
+    Continue,
+    Quit,
+}
+
+/// Run one `.command [args]` line
+fn run_dot_command(
+    conn: &Arc<Connection>,
+    line: &str,
+    settings: &mut OutputSettings,
+    cwd: &str,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> Result<DotOutcome, String> {
+    let mut parts = line.split_whitespace();
+    let command = parts.next().unwrap_or_default();
+    let args: Vec<&str> = parts.collect();
+
+    match (command, args.as_slice()) {
+        (".quit" | ".exit", _) => return Ok(DotOutcome::Quit),
+        (".tables", _) => {
+            let (_, rows) = query_rows(
+                conn,
+                "SELECT name FROM sqlite_master WHERE type = 'table' \
+                 AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )?;
+            let names: Vec<String> = rows.iter().map(|row| format_value(&row[0])).collect();
+            if !names.is_empty() {
+                write_output(stdout, format!("{}\n", names.join("  ")).as_bytes())?;
+            }
+        }
+        (".schema", [] | [_]) => {
+            let filter = args
+                .first()
+                .map(|table| format!(" AND name = '{}'", table.replace('\'', "''")))
+                .unwrap_or_default();
+            let (_, rows) = query_rows(
+                conn,
+                &format!(
+                    "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL{} ORDER BY name",
+                    filter
+                ),
+            )?;
+            for row in &rows {
+                write_output(stdout, format!("{};\n", format_value(&row[0])).as_bytes())?;
+            }
+        }
+        (".mode", []) => {
+            let current = format!("current output mode: {}\n", settings.mode.name());
+            write_output(stdout, current.as_bytes())?;
+        }
+        (".mode", [mode]) => {
+            settings.mode = match *mode {
+                "list" => OutputMode::List,
+                "csv" => OutputMode::Csv,
+                "column" => OutputMode::Column,
+                "json" => OutputMode::Json,
+                _ => return Err("mode should be one of: column csv json list".to_string()),
+            };
+        }
+        (".import", [..]) => {
+            let csv = args.first() == Some(&"--csv");
+            let [file, table] = args[usize::from(csv)..] else {
+                return Err("usage: .import [--csv] FILE TABLE".to_string());
+            };
+            let separator = if csv || settings.mode == OutputMode::Csv {
+                ','
+            } else {
+                '|'
+            };
+            let path = resolve_db_path(cwd, file);
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot open \"{}\": {}", file, e))?;
+            let summary = import_rows(conn, table, &text, separator, settings.headers)?;
+            write_output(stderr, summary.as_bytes())?;
+        }
+        (".headers" | ".header", [flag]) => {
+            settings.headers = match *flag {
+                "on" | "yes" | "1" => true,
+                "off" | "no" | "0" => false,
+                _ => return Err(format!("not a boolean value: {}", flag)),
+            };
+        }
+        _ => {
+            return Err(format!(
+                "unknown command or invalid arguments: \"{}\"",
+                command.trim_start_matches('.')
+            ))
+        }
+    }
+    Ok(DotOutcome::Continue)
+}
+
+/// Execute `;`-separated statements, printing each result set
+fn execute_sql(
+    conn: &Arc<Connection>,
+    sql: &str,
+    settings: &OutputSettings,
+    stdout: &mut dyn Write,
+) -> Result<(), String> {
+    let statements = sql.split(';').map(|s| s.trim()).filter(|s| !s.is_empty());
+    for stmt_sql in statements {
+        let (columns, rows) = query_rows(conn, stmt_sql)?;
+        write_output(stdout, render_rows(&columns, &rows, settings).as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Run one statement and collect its column names and rows
+fn query_rows(conn: &Arc<Connection>, sql: &str) -> Result<(Vec<String>, Vec<Vec<Value>>), String> {
+    let mut stmt = match conn.query(sql).map_err(|e| e.to_string())? {
+        Some(stmt) => stmt,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let columns: Vec<String> = (0..stmt.num_columns())
+        .map(|i| stmt.get_column_name(i).to_string())
+        .collect();
+
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+    stmt.run_with_row_callback(|row| {
+        rows.push(row.get_values().cloned().collect());
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    Ok((columns, rows))
+}
+
+/// Insert the records of `text` into `table`, creating it from the header row
+/// when it does not exist. Returns the summary line for stderr.
+fn import_rows(
+    conn: &Arc<Connection>,
+    table: &str,
+    text: &str,
+    separator: char,
+    headers: bool,
+) -> Result<String, String> {
+    let mut records = parse_delimited(text, separator).into_iter();
+    let (_, rows) = query_rows(
+        conn,
+        &format!(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '{}'",
+            table.replace('\'', "''")
+        ),
+    )?;
+    let table_exists = !rows.is_empty();
+
+    let column_count = if table_exists {
+        if headers {
+            records.next();
+        }
+        let (columns, _) = query_rows(
+            conn,
+            &format!("SELECT * FROM {} LIMIT 0", quote_ident(table)),
+        )?;
+        columns.len()
+    } else {
+        let header = records
+            .next()
+            .ok_or_else(|| format!("{}: no header row to name the columns", table))?;
+        let columns: Vec<String> = header
+            .iter()
+            .map(|name| format!("{} TEXT", quote_ident(name)))
+            .collect();
+        query_rows(
+            conn,
+            &format!(
+                "CREATE TABLE {} ({})",
+                quote_ident(table),
+                columns.join(", ")
+            ),
+        )?;
+        header.len()
+    };
+    if column_count == 0 {
+        return Err(format!("{}: table has no columns", table));
+    }
+
+    let placeholders = vec!["?"; column_count].join(", ");
+    let insert_sql = format!(
+        "INSERT INTO {} VALUES ({})",
+        quote_ident(table),
+        placeholders
+    );
+    query_rows(conn, "BEGIN")?;
+    let mut stmt = match conn.prepare(&insert_sql) {
+        Ok(stmt) => stmt,
+        Err(e) => {
+            let _ = query_rows(conn, "ROLLBACK");
+            return Err(e.to_string());
+        }
+    };
+    let (mut added, mut errors) = (0usize, 0usize);
+    for record in records {
+        // Like sqlite3: missing fields become NULL, extra fields are dropped
+        stmt.reset();
+        for i in 0..column_count {
+            let value = match record.get(i) {
+                Some(field) => Value::build_text(field),
+                None => Value::Null,
+            };
+            if let Some(index) = NonZeroUsize::new(i + 1) {
+                stmt.bind_at(index, value);
+            }
+        }
+        match stmt.run_with_row_callback(|_| Ok(())) {
+            Ok(_) => added += 1,
+            Err(_) => errors += 1,
+        }
+    }
+    drop(stmt);
+    query_rows(conn, "COMMIT")?;
+
+    Ok(format!(
+        "Added {} rows with {} errors using {} lines of input\n",
+        added,
+        errors,
+        text.lines().count()
+    ))
+}
+
+/// Split `text` into records following RFC 4180: quoted fields may contain
+/// the separator, doubled quotes, and line breaks. Blank lines are skipped.
+fn parse_delimited(text: &str, separator: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == separator {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            if record.len() > 1 || !record[0].is_empty() {
+                records.push(std::mem::take(&mut record));
+            } else {
+                record.clear();
+            }
+        } else {
+            field.push(c);
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Render a result set in the current output mode
+fn render_rows(columns: &[String], rows: &[Vec<Value>], settings: &OutputSettings) -> String {
+    let mut out = String::new();
+    if rows.is_empty() && (!settings.headers || settings.mode == OutputMode::Json) {
+        return out;
+    }
+
+    match settings.mode {
+        OutputMode::List => {
+            if settings.headers {
+                out.push_str(&columns.join("|"));
+                out.push('\n');
+            }
+            for row in rows {
+                let fields: Vec<String> = row.iter().map(format_value).collect();
+                out.push_str(&fields.join("|"));
+                out.push('\n');
+            }
+        }
+        OutputMode::Csv => {
+            if settings.headers {
+                let fields: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+            for row in rows {
+                let fields: Vec<String> = row.iter().map(|v| csv_field(&format_value(v))).collect();
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+        }
+        OutputMode::Column => {
+            let cells: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| row.iter().map(format_value).collect())
+                .collect();
+            let mut widths: Vec<usize> = columns
+                .iter()
+                .map(|c| {
+                    if settings.headers {
+                        c.chars().count()
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            for row in &cells {
+                for (i, cell) in row.iter().enumerate() {
+                    if let Some(width) = widths.get_mut(i) {
+                        *width = (*width).max(cell.chars().count());
+                    }
+                }
+            }
+            let mut push_line = |fields: &[String]| {
+                let padded: Vec<String> = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| format!("{:<w$}", f, w = widths.get(i).copied().unwrap_or(0)))
+                    .collect();
+                out.push_str(padded.join("  ").trim_end());
+                out.push('\n');
+            };
+            if settings.headers {
+                push_line(columns);
+                let rules: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                push_line(&rules);
+            }
+            for row in &cells {
+                push_line(row);
+            }
+        }
+        OutputMode::Json => {
+            let objects: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    let members: Vec<String> = columns
+                        .iter()
+                        .zip(row)
+                        .map(|(name, value)| format!("{}:{}", json_string(name), json_value(value)))
+                        .collect();
+                    format!("{{{}}}", members.join(","))
+                })
+                .collect();
+            out.push('[');
+            out.push_str(&objects.join(",\n"));
+            out.push_str("]\n");
+        }
+    }
+    out
+}
+
+/// Quote a CSV field when it contains a separator, quote, or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_value(val: &Value) -> String {
+    match val {
+        Value::Null => "null".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) if f.is_finite() => f.to_string(),
+        Value::Float(_) => "null".to_string(),
+        _ => json_string(&format_value(val)),
+    }
+}
+
+/// Resolve a relative database path against the shell's working directory
+fn resolve_db_path(cwd: &str, db_path: &str) -> String {
+    if db_path == ":memory:" || db_path.starts_with('/') || cwd.is_empty() {
+        db_path.to_string()
+    } else {
+        format!("{}/{}", cwd.trim_end_matches('/'), db_path)
+    }
 }
 
 /// Format a Value for output
@@ -165,9 +740,46 @@ fn format_value(val: &Value) -> String {
     }
 }
 
-/// Helper to write data to an output stream
+/// Largest write `blocking_write_and_flush` accepts, per the WASI spec
+const MAX_WRITE: usize = 4096;
+
+/// Output a blocking write goes to; implemented by WASI output streams
+trait BlockingOutput {
+    fn write_and_flush(&self, bytes: &[u8]) -> std::io::Result<()>;
+}
+
+impl BlockingOutput for OutputStream {
+    fn write_and_flush(&self, bytes: &[u8]) -> std::io::Result<()> {
+        self.blocking_write_and_flush(bytes)
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    }
+}
+
+/// `std::io::Write` over an output stream. Each write sends at most
+/// `MAX_WRITE` bytes, so `write_all` splits larger output into chunks.
+struct StreamWriter<'a, S: BlockingOutput = OutputStream>(&'a S);
+
+impl<S: BlockingOutput> Write for StreamWriter<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let chunk = &buf[..buf.len().min(MAX_WRITE)];
+        self.0.write_and_flush(chunk)?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write command output, reporting a failed write as an error
+fn write_output(out: &mut dyn Write, data: &[u8]) -> Result<(), String> {
+    out.write_all(data)
+        .map_err(|e| format!("write failed: {}", e))
+}
+
+/// Write a prompt or diagnostic; there is nowhere to report a failure
 fn write_to_stream(stream: &OutputStream, data: &[u8]) {
-    let _ = stream.blocking_write_and_flush(data);
+    let _ = StreamWriter(stream).write_all(data);
 }
 
 /// Helper to read all data from an input stream
@@ -183,3 +795,281 @@ fn read_all_from_stream(stream: &InputStream) -> Result<Vec<u8>, String> {
 }
 
 bindings::export!(SqliteModule with_types_in bindings);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn open_session(db_path: &str) -> Session {
+        Session {
+            conn: open_connection(db_path).unwrap(),
+            settings: OutputSettings::default(),
+            pending_sql: String::new(),
+        }
+    }
+
+    fn memory_session() -> Session {
+        open_session(":memory:")
+    }
+
+    /// Feed `lines` to the session and return what it wrote to stdout
+    fn run_lines(session: &mut Session, lines: &[&str]) -> String {
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        for line in lines {
+            session.feed_line(line, "", &mut out, &mut err).unwrap();
+        }
+        session.flush(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn sample_session() -> Session {
+        let mut session = memory_session();
+        run_lines(
+            &mut session,
+            &[
+                "CREATE TABLE t (id INTEGER, name TEXT, score REAL);",
+                "INSERT INTO t VALUES (1, 'ann', 9.5);",
+                "INSERT INTO t VALUES (2, 'bob, jr', NULL);",
+            ],
+        );
+        session
+    }
+
+    fn render(mode: &str, headers: &str) -> String {
+        let mut session = sample_session();
+        run_lines(
+            &mut session,
+            &[
+                &format!(".mode {}", mode),
+                &format!(".headers {}", headers),
+                "SELECT id, name, score FROM t ORDER BY id;",
+            ],
+        )
+    }
+
+    #[test]
+    fn list_mode() {
+        assert_eq!(render("list", "off"), "1|ann|9.5\n2|bob, jr|\n");
+        assert_eq!(
+            render("list", "on"),
+            "id|name|score\n1|ann|9.5\n2|bob, jr|\n"
+        );
+    }
+
+    #[test]
+    fn csv_mode() {
+        assert_eq!(render("csv", "off"), "1,ann,9.5\n2,\"bob, jr\",\n");
+        assert_eq!(
+            render("csv", "on"),
+            "id,name,score\n1,ann,9.5\n2,\"bob, jr\",\n"
+        );
+    }
+
+    #[test]
+    fn column_mode() {
+        assert_eq!(render("column", "off"), "1  ann      9.5\n2  bob, jr\n");
+        assert_eq!(
+            render("column", "on"),
+            "id  name     score\n--  -------  -----\n1   ann      9.5\n2   bob, jr\n"
+        );
+    }
+
+    #[test]
+    fn json_mode() {
+        let expected = "[{\"id\":1,\"name\":\"ann\",\"score\":9.5},\n\
+                        {\"id\":2,\"name\":\"bob, jr\",\"score\":null}]\n";
+        assert_eq!(render("json", "off"), expected);
+        assert_eq!(render("json", "on"), expected);
+    }
+
+    #[test]
+    fn headers_on_empty_result() {
+        let mut session = sample_session();
+        let out = run_lines(
+            &mut session,
+            &[".headers on", "SELECT id, name FROM t WHERE id > 5;"],
+        );
+        assert_eq!(out, "id|name\n");
+    }
+
+    /// Records each write so tests can check the chunk sizes
+    #[derive(Default)]
+    struct ChunkRecorder(RefCell<Vec<Vec<u8>>>);
+
+    impl BlockingOutput for ChunkRecorder {
+        fn write_and_flush(&self, bytes: &[u8]) -> std::io::Result<()> {
+            assert!(bytes.len() <= MAX_WRITE, "write of {} bytes", bytes.len());
+            self.0.borrow_mut().push(bytes.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn large_result_is_written_in_chunks() {
+        let mut session = memory_session();
+        let mut lines = vec!["CREATE TABLE big (id INTEGER, payload TEXT);".to_string()];
+        let mut expected = String::new();
+        for i in 0..200 {
+            let payload = format!("{:0>40}", i);
+            lines.push(format!("INSERT INTO big VALUES ({}, '{}');", i, payload));
+            expected.push_str(&format!("{}|{}\n", i, payload));
+        }
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        run_lines(&mut session, &lines);
+        assert!(expected.len() > MAX_WRITE);
+
+        let recorder = ChunkRecorder::default();
+        let mut out = StreamWriter(&recorder);
+        execute_sql(
+            &session.conn,
+            "SELECT id, payload FROM big ORDER BY id",
+            &session.settings,
+            &mut out,
+        )
+        .unwrap();
+
+        let chunks = recorder.0.into_inner();
+        assert!(chunks.len() > 1);
+        assert_eq!(String::from_utf8(chunks.concat()).unwrap(), expected);
+    }
+
+    #[test]
+    fn file_database_persists_between_invocations() {
+        let dir = std::env::temp_dir().join(format!("sqlite-module-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cwd = dir.to_string_lossy().into_owned();
+        let db_path = resolve_db_path(&cwd, "test.db");
+
+        let mut first = open_session(&db_path);
+        run_lines(
+            &mut first,
+            &[
+                "CREATE TABLE notes (id INTEGER, body TEXT);",
+                "INSERT INTO notes VALUES (1, 'first');",
+                "INSERT INTO notes VALUES (2, 'second');",
+            ],
+        );
+        drop(first);
+
+        let mut second = open_session(&db_path);
+        let out = run_lines(&mut second, &["SELECT id, body FROM notes ORDER BY id;"]);
+        assert_eq!(out, "1|first\n2|second\n");
+
+        drop(second);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Type `input` at the prompt; returns the exit code, stdout and stderr
+    fn run_repl_input(session: &mut Session, input: &[u8]) -> (i32, String, String) {
+        let mut bytes = input.iter().copied();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        let code = run_repl(session, "", &mut || bytes.next(), &mut out, &mut err);
+        (
+            code,
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    #[test]
+    fn repl_runs_statement_spanning_lines() {
+        let mut session = sample_session();
+        let (code, out, err) =
+            run_repl_input(&mut session, b"SELECT name\nFROM t\nWHERE id = 1;\n");
+        assert_eq!(code, 0);
+        assert_eq!(err, "");
+        assert_eq!(
+            out,
+            "sqlite> SELECT name\r\n   ...> FROM t\r\n   ...> WHERE id = 1;\r\nann\nsqlite> "
+        );
+    }
+
+    #[test]
+    fn repl_dot_commands() {
+        let mut session = sample_session();
+        let (code, out, _) = run_repl_input(
+            &mut session,
+            b".mode csv\r.headers on\rSELECT id, name FROM t WHERE id = 2;\r.tables\r",
+        );
+        assert_eq!(code, 0);
+        assert!(
+            out.ends_with("\r\nid,name\n2,\"bob, jr\"\nsqlite> .tables\r\nt\nsqlite> "),
+            "out: {:?}",
+            out
+        );
+    }
+
+    #[test]
+    fn repl_quit_stops_reading() {
+        let mut session = sample_session();
+        let (code, out, _) = run_repl_input(&mut session, b".quit\nSELECT 1;\n");
+        assert_eq!(code, 0);
+        assert_eq!(out, "sqlite> .quit\r\n");
+    }
+
+    #[test]
+    fn repl_eof() {
+        // Input closing mid-statement ends the session without running it
+        let mut session = sample_session();
+        let (code, out, _) = run_repl_input(&mut session, b"SELECT 1\n");
+        assert_eq!(code, 0);
+        assert_eq!(out, "sqlite> SELECT 1\r\n   ...> ");
+
+        // Ctrl+D only ends the session on an empty line
+        let mut session = sample_session();
+        let (code, out, _) = run_repl_input(&mut session, b"SELECT\x04 2;\n\x04");
+        assert_eq!(code, 0);
+        assert_eq!(out, "sqlite> SELECT 2;\r\n2\nsqlite> \r\n");
+    }
+
+    #[test]
+    fn repl_line_editing() {
+        let mut input = Vec::new();
+        input.extend_from_slice("SELECT 'é'".as_bytes());
+        input.extend_from_slice(b"\x7f\x7fx';\n");
+        let mut bytes = input.into_iter();
+        let mut echo = Vec::new();
+        let line = read_repl_line(&mut || bytes.next(), &mut echo);
+        assert_eq!(line.as_deref(), Some("SELECT 'x';"));
+        assert_eq!(
+            String::from_utf8(echo).unwrap(),
+            "SELECT 'é'\x08 \x08\x08 \x08x';\r\n"
+        );
+
+        // Ctrl+C abandons the line; errors are reported and the session goes on
+        let mut session = sample_session();
+        let (code, out, err) = run_repl_input(
+            &mut session,
+            b"SELECT nope\x03SELECT * FROM missing;\nSELECT 3;\n",
+        );
+        assert_eq!(code, 0);
+        assert!(out.contains("^C\r\nsqlite> "), "out: {:?}", out);
+        assert!(out.ends_with("\r\n3\nsqlite> "), "out: {:?}", out);
+        assert!(err.starts_with("Error: "), "err: {:?}", err);
+    }
+
+    struct FailingOutput;
+
+    impl BlockingOutput for FailingOutput {
+        fn write_and_flush(&self, _bytes: &[u8]) -> std::io::Result<()> {
+            Err(std::io::Error::other("closed"))
+        }
+    }
+
+    #[test]
+    fn write_errors_are_reported() {
+        let session = sample_session();
+        let mut out = StreamWriter(&FailingOutput);
+        let err = execute_sql(
+            &session.conn,
+            "SELECT id FROM t",
+            &session.settings,
+            &mut out,
+        )
+        .unwrap_err();
+        assert_eq!(err, "write failed: closed");
+    }
+}
@@ -0,0 +1,138 @@
+//! `tsx --max-fetch-concurrency`: caps how many HTTP requests are in flight.
+//!
+//! Permits are counted both in total and per host. `fetch()` asks for a
+//! permit before sending and queues in JS (see shims/fetch.js) until one is
+//! released. Module loads are synchronous and cannot wait, so they take a
+//! [`Permit`] unconditionally; queued `fetch()` calls see them as in flight.
+
+use rquickjs::{Ctx, Function};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Default for `--max-fetch-concurrency`.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 16;
+
+/// Connections allowed to a single host, like a browser's per-origin pool.
+pub const MAX_PER_HOST: usize = 6;
+
+thread_local! {
+    static MAX_CONCURRENCY: Cell<usize> = const { Cell::new(DEFAULT_MAX_CONCURRENCY) };
+    static IN_FLIGHT: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Set the total limit for subsequent requests (at least 1).
+pub fn set_max_concurrency(max: usize) {
+    MAX_CONCURRENCY.with(|m| m.set(max.max(1)));
+}
+
+/// Forget every outstanding permit, e.g. ones left by an abandoned context.
+pub fn reset() {
+    IN_FLIGHT.with(|f| f.borrow_mut().clear());
+}
+
+/// Host (authority) of a URL, lowercased; empty when there is none.
+pub fn host_of(url: &str) -> String {
+    let rest = match url.find("://") {
+        Some(idx) => &url[idx + 3..],
+        None => return String::new(),
+    };
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    rest[..end].to_ascii_lowercase()
+}
+
+/// Take a permit for `host` if both the total and per-host limits allow it.
+pub fn try_acquire(host: &str) -> bool {
+    let max = MAX_CONCURRENCY.with(|m| m.get());
+    IN_FLIGHT.with(|f| {
+        let mut in_flight = f.borrow_mut();
+        let total: usize = in_flight.values().sum();
+        let for_host = in_flight.get(host).copied().unwrap_or(0);
+        if total >= max || for_host >= MAX_PER_HOST.min(max) {
+            return false;
+        }
+        *in_flight.entry(host.to_string()).or_insert(0) += 1;
+        true
+    })
+}
+
+/// Return a permit taken with [`try_acquire`].
+pub fn release(host: &str) {
+    IN_FLIGHT.with(|f| {
+        let mut in_flight = f.borrow_mut();
+        if let Some(count) = in_flight.get_mut(host) {
+            *count -= 1;
+            if *count == 0 {
+                in_flight.remove(host);
+            }
+        }
+    });
+}
+
+/// A permit held for the duration of a synchronous request.
+pub struct Permit {
+    host: String,
+}
+
+impl Permit {
+    /// Count a request that cannot be queued (it may exceed the limits).
+    pub fn hold(url: &str) -> Self {
+        let host = host_of(url);
+        IN_FLIGHT.with(|f| *f.borrow_mut().entry(host.clone()).or_insert(0) += 1);
+        Permit { host }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        release(&self.host);
+    }
+}
+
+/// Install `__tsxFetchAcquire(host)` / `__tsxFetchRelease(host)` for the fetch shim.
+pub fn install(ctx: &Ctx<'_>) -> rquickjs::Result<()> {
+    reset();
+    let globals = ctx.globals();
+    globals.set(
+        "__tsxFetchAcquire",
+        Function::new(ctx.clone(), |host: String| try_acquire(&host))?,
+    )?;
+    globals.set(
+        "__tsxFetchRelease",
+        Function::new(ctx.clone(), |host: String| release(&host))?,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_total_and_per_host() {
+        reset();
+        set_max_concurrency(8);
+        for _ in 0..MAX_PER_HOST {
+            assert!(try_acquire("a.example"));
+        }
+        assert!(!try_acquire("a.example"));
+        assert!(try_acquire("b.example"));
+        let permit = Permit::hold("https://B.example/mod.js");
+        assert!(!try_acquire("c.example"));
+
+        drop(permit);
+        assert!(try_acquire("c.example"));
+        release("a.example");
+        assert!(try_acquire("a.example"));
+        assert!(!try_acquire("d.example"));
+
+        reset();
+        set_max_concurrency(DEFAULT_MAX_CONCURRENCY);
+    }
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of("https://esm.sh/react?dev"), "esm.sh");
+        assert_eq!(host_of("http://Localhost:8080#x"), "localhost:8080");
+        assert_eq!(host_of("./local.js"), "");
+    }
+}
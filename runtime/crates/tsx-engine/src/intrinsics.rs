@@ -0,0 +1,77 @@
+//! `tsx --freeze-intrinsics`: a lockdown-lite for generated code.
+//!
+//! After the Node shims are installed, the shared prototypes are frozen so
+//! user code cannot pollute them; writes then throw in strict mode. The
+//! constructors stay writable, since libraries commonly set statics such as
+//! `Error.stackTraceLimit`. Like SES, a few commonly overridden properties
+//! become accessors first, so assigning `obj.toString = ...` on an ordinary
+//! object still creates an own property instead of hitting the frozen
+//! prototype (the "override mistake").
+
+use rquickjs::Ctx;
+use std::cell::Cell;
+
+const FREEZE_INTRINSICS_JS: &str = r#"
+(function () {
+    var TypedArray = Object.getPrototypeOf(Uint8Array);
+    var iteratorPrototype = Object.getPrototypeOf(Object.getPrototypeOf([][Symbol.iterator]()));
+    var constructors = [
+        Object, Function, Array, String, Number, Boolean, Symbol, BigInt, RegExp, Date,
+        Promise, Map, Set, WeakMap, WeakSet, ArrayBuffer, DataView, TypedArray,
+        Error, TypeError, RangeError, SyntaxError, ReferenceError, EvalError, URIError
+    ];
+    var prototypes = [iteratorPrototype];
+    for (var i = 0; i < constructors.length; i++) {
+        if (constructors[i] && constructors[i].prototype) {
+            prototypes.push(constructors[i].prototype);
+        }
+    }
+
+    var overridable = ['constructor', 'toString', 'valueOf', 'toLocaleString', 'name', 'message'];
+    function enableOverride(proto, key) {
+        var desc = Object.getOwnPropertyDescriptor(proto, key);
+        if (!desc || !('value' in desc) || !desc.configurable) return;
+        var value = desc.value;
+        Object.defineProperty(proto, key, {
+            get: function () { return value; },
+            set: function (next) {
+                if (this === proto) {
+                    throw new TypeError('Cannot assign to read only property \'' + key + '\' of frozen intrinsic');
+                }
+                Object.defineProperty(this, key, {
+                    value: next, writable: true, enumerable: true, configurable: true
+                });
+            },
+            enumerable: desc.enumerable,
+            configurable: false
+        });
+    }
+
+    for (var j = 0; j < prototypes.length; j++) {
+        for (var k = 0; k < overridable.length; k++) {
+            enableOverride(prototypes[j], overridable[k]);
+        }
+    }
+    for (var m = 0; m < prototypes.length; m++) {
+        Object.freeze(prototypes[m]);
+    }
+})();
+"#;
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Enable or disable freezing for subsequent executions.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+}
+
+/// Freeze the intrinsics of `ctx`. No-op unless enabled; must run after the
+/// bindings are installed and before any user code.
+pub fn freeze(ctx: &Ctx<'_>) -> rquickjs::Result<()> {
+    if !ENABLED.with(|e| e.get()) {
+        return Ok(());
+    }
+    ctx.eval::<(), _>(FREEZE_INTRINSICS_JS)
+}
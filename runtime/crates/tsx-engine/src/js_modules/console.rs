@@ -1,11 +1,18 @@
 //! Console module - captures output for the host.
 
-use rquickjs::prelude::Rest;
+use rquickjs::prelude::{Opt, Rest};
 use rquickjs::{Ctx, Function, Object, Result, Value};
+use std::collections::HashMap;
 
 // Captured console output logs.
 thread_local! {
     pub static CAPTURED_LOGS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+    // Per-label counters for console.count (reset for every new context)
+    static COUNTS: std::cell::RefCell<HashMap<String, u64>> = std::cell::RefCell::new(HashMap::new());
+}
+
+fn push_log(line: String) {
+    CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(line));
 }
 
 /// Clear captured logs.
@@ -47,6 +54,29 @@ fn value_to_string(val: &Value) -> String {
     }
 }
 
+/// JavaScript truthiness, for console.assert's condition.
+fn is_truthy(val: &Value) -> bool {
+    if val.is_undefined() || val.is_null() {
+        false
+    } else if let Some(b) = val.as_bool() {
+        b
+    } else if let Some(n) = val.as_number() {
+        n != 0.0 && !n.is_nan()
+    } else if let Some(s) = val.as_string() {
+        !s.to_string().unwrap_or_default().is_empty()
+    } else {
+        true
+    }
+}
+
+/// Label argument for console.count/countReset ("default" when omitted).
+fn count_label(label: &Opt<Value>) -> String {
+    match &label.0 {
+        Some(v) if !v.is_undefined() => value_to_string(v),
+        _ => "default".to_string(),
+    }
+}
+
 /// Install console bindings on the global object.
 pub fn install(ctx: &Ctx<'_>) -> Result<()> {
     let globals = ctx.globals();
@@ -55,31 +85,74 @@ pub fn install(ctx: &Ctx<'_>) -> Result<()> {
     // console.log
     let log_fn = Function::new(ctx.clone(), |args: Rest<Value>| {
         let output = format_args_to_string(&args.0);
-        CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(output));
+        push_log(output);
     })?;
     console.set("log", log_fn)?;
 
     // console.error
     let error_fn = Function::new(ctx.clone(), |args: Rest<Value>| {
         let output = format!("ERROR: {}", format_args_to_string(&args.0));
-        CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(output));
+        push_log(output);
     })?;
     console.set("error", error_fn)?;
 
     // console.warn
     let warn_fn = Function::new(ctx.clone(), |args: Rest<Value>| {
         let output = format!("WARN: {}", format_args_to_string(&args.0));
-        CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(output));
+        push_log(output);
     })?;
     console.set("warn", warn_fn)?;
 
     // console.info
     let info_fn = Function::new(ctx.clone(), |args: Rest<Value>| {
         let output = format_args_to_string(&args.0);
-        CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(output));
+        push_log(output);
     })?;
     console.set("info", info_fn)?;
 
+    // console.assert - reports through the error stream only when the condition is falsy
+    let assert_fn = Function::new(ctx.clone(), |args: Rest<Value>| {
+        if args.0.first().is_some_and(is_truthy) {
+            return;
+        }
+        let message = format_args_to_string(args.0.get(1..).unwrap_or_default());
+        if message.is_empty() {
+            push_log("ERROR: Assertion failed".to_string());
+        } else {
+            push_log(format!("ERROR: Assertion failed: {}", message));
+        }
+    })?;
+    console.set("assert", assert_fn)?;
+
+    // console.count / console.countReset
+    COUNTS.with(|counts| counts.borrow_mut().clear());
+    let count_fn = Function::new(ctx.clone(), |label: Opt<Value>| {
+        let label = count_label(&label);
+        let count = COUNTS.with(|counts| {
+            let mut counts = counts.borrow_mut();
+            let count = counts.entry(label.clone()).or_insert(0);
+            *count += 1;
+            *count
+        });
+        push_log(format!("{}: {}", label, count));
+    })?;
+    console.set("count", count_fn)?;
+
+    let count_reset_fn = Function::new(ctx.clone(), |label: Opt<Value>| {
+        let label = count_label(&label);
+        let existed = COUNTS.with(|counts| {
+            counts
+                .borrow_mut()
+                .get_mut(&label)
+                .map(|count| *count = 0)
+                .is_some()
+        });
+        if !existed {
+            push_log(format!("WARN: Count for '{}' does not exist", label));
+        }
+    })?;
+    console.set("countReset", count_reset_fn)?;
+
     globals.set("console", console)?;
     Ok(())
 }
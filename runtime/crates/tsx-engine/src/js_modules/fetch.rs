@@ -92,6 +92,7 @@ pub fn install(ctx: &Ctx<'_>) -> Result<()> {
     })?;
 
     globals.set("__syncFetch__", sync_fetch_fn)?;
+    crate::fetch_limit::install(ctx)?;
 
     // Evaluate embedded JS shims
     ctx.eval::<(), _>(HEADERS_JS)?;
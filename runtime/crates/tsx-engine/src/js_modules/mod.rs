@@ -24,6 +24,7 @@ pub mod ios_bridge;
 #[path = "module_mod.rs"]
 pub mod module_mod;
 pub mod net;
+pub mod node_test;
 pub mod os;
 pub mod path;
 pub mod perf_hooks;
@@ -33,6 +34,7 @@ pub mod querystring;
 pub mod readline;
 pub mod stream;
 pub mod string_decoder;
+pub mod test_harness;
 pub mod timers;
 pub mod tls;
 pub mod tty;
@@ -88,6 +90,7 @@ pub fn install_all(ctx: &Ctx<'_>) -> Result<()> {
     os::install(ctx)?;
     util::install(ctx)?;
     assert::install(ctx)?;
+    node_test::install(ctx)?; // After assert (t.assert is the assert module)
     stream::install(ctx)?; // Before http, https, zlib (they extend Readable/Writable/Transform)
     path::install(ctx)?;
     fs_promises::install(ctx)?;
@@ -116,6 +119,7 @@ pub fn install_all(ctx: &Ctx<'_>) -> Result<()> {
     domain::install(ctx)?;
     string_decoder::install(ctx)?;
     ios_bridge::install(ctx)?;
+    test_harness::install(ctx)?; // No-op unless a tsx-test run is active
     Ok(())
 }
 
@@ -0,0 +1,28 @@
+//! node:test module - Node's built-in `test(name, fn)` runner.
+//!
+//! Tests run sequentially on the event loop; each top-level result (with
+//! subtests flattened as `parent > child`) is recorded through the shared
+//! test harness store so `tsx --test` can print a TAP summary.
+
+use super::test_harness::{self, TestCaseResult};
+use rquickjs::{Ctx, Function, Result};
+
+const NODE_TEST_JS: &str = include_str!("shims/node_test.js");
+
+/// Install the node:test module and register as a built-in.
+pub fn install(ctx: &Ctx<'_>) -> Result<()> {
+    let report = Function::new(
+        ctx.clone(),
+        |name: String, passed: bool, duration_ms: f64, error: String| {
+            test_harness::record(TestCaseResult {
+                name,
+                passed,
+                duration_ms,
+                error: if error.is_empty() { None } else { Some(error) },
+            });
+        },
+    )?;
+    ctx.globals().set("__tsxNodeTestReport", report)?;
+    ctx.eval::<(), _>(NODE_TEST_JS)?;
+    Ok(())
+}
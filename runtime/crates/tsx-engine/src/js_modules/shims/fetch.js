@@ -55,67 +55,101 @@ globalThis.Request = Request;
 
 // Web API fetch function
 // Embedded via include_str! for IDE linting support
-// Requires __syncFetch__ to be installed by Rust
+// Requires __syncFetch__ and __tsxFetchAcquire/__tsxFetchRelease to be installed by Rust
 
-globalThis.fetch = function (resource, options = {}) {
-    return new Promise((resolve, reject) => {
-        try {
-            // Handle Request objects
-            let url = resource;
-            if (typeof resource === 'object' && resource.url) {
-                url = resource.url;
-                options = { ...resource, ...options };
-            }
-
-            if (options.signal && options.signal.aborted) {
-                reject(new TypeError('Fetch aborted'));
-                return;
-            }
+function sendFetch(url, options) {
+    if (options.signal && options.signal.aborted) {
+        return Promise.reject(new TypeError('Fetch aborted'));
+    }
 
-            // Build options JSON for Rust
-            const fetchOptions = {
-                method: options.method || 'GET',
-                headers: {},
-                body: options.body,
-                timeoutMs: options.timeoutMs
-            };
+    // Build options JSON for Rust
+    const fetchOptions = {
+        method: options.method || 'GET',
+        headers: {},
+        body: options.body,
+        timeoutMs: options.timeoutMs
+    };
 
-            // Convert headers to plain object
-            if (options.headers) {
-                if (options.headers instanceof Headers) {
-                    options.headers.forEach((value, name) => {
-                        fetchOptions.headers[name] = value;
-                    });
-                } else if (Array.isArray(options.headers)) {
-                    options.headers.forEach(([name, value]) => {
-                        fetchOptions.headers[name] = value;
-                    });
-                } else {
-                    fetchOptions.headers = options.headers;
-                }
-            }
-
-            const resultJson = __syncFetch__(url, JSON.stringify(fetchOptions));
-            const result = JSON.parse(resultJson);
+    // Convert headers to plain object
+    if (options.headers) {
+        if (options.headers instanceof Headers) {
+            options.headers.forEach((value, name) => {
+                fetchOptions.headers[name] = value;
+            });
+        } else if (Array.isArray(options.headers)) {
+            options.headers.forEach(([name, value]) => {
+                fetchOptions.headers[name] = value;
+            });
+        } else {
+            fetchOptions.headers = options.headers;
+        }
+    }
 
+    // The transport may also answer with a promise (tests mock it that way)
+    return Promise.resolve()
+        .then(() => __syncFetch__(url, JSON.stringify(fetchOptions)))
+        .then((resultJson) => JSON.parse(resultJson))
+        .then((result) => {
             if (result.status === 0 && /timeout/i.test(result.statusText || '')) {
-                reject(new TypeError(result.statusText || 'Request timeout'));
-                return;
+                throw new TypeError(result.statusText || 'Request timeout');
             }
 
             // Build Headers from response
             const responseHeaders = new Headers(result.headers || []);
 
             // Resolve with standard Response object
-            resolve(new Response(result.body, {
+            return new Response(result.body, {
                 status: result.status,
                 statusText: result.statusText,
                 headers: responseHeaders,
                 url: url
-            }));
-        } catch (e) {
+            });
+        }, (e) => {
             // Reject with error for network failures
-            reject(new TypeError('Network request failed: ' + e.message));
+            throw new TypeError('Network request failed: ' + e.message);
+        });
+}
+
+// Requests over --max-fetch-concurrency (in total or for one host) wait here
+const pendingFetches = [];
+
+function fetchHost(url) {
+    const match = /^[a-z][a-z0-9+.-]*:\/\/([^/?#]*)/i.exec(url);
+    return match ? match[1].toLowerCase() : '';
+}
+
+function startPendingFetches() {
+    for (let i = 0; i < pendingFetches.length;) {
+        const entry = pendingFetches[i];
+        if (__tsxFetchAcquire(entry.host)) {
+            pendingFetches.splice(i, 1);
+            entry.start();
+        } else {
+            i++;
         }
+    }
+}
+
+globalThis.fetch = function (resource, options = {}) {
+    // Handle Request objects
+    let url = resource;
+    if (typeof resource === 'object' && resource !== null && resource.url) {
+        url = resource.url;
+        options = { ...resource, ...options };
+    }
+    url = String(url);
+    const host = fetchHost(url);
+
+    return new Promise((resolve, reject) => {
+        pendingFetches.push({
+            host: host,
+            start: () => {
+                sendFetch(url, options).then(resolve, reject).finally(() => {
+                    __tsxFetchRelease(host);
+                    startPendingFetches();
+                });
+            }
+        });
+        startPendingFetches();
     });
 };
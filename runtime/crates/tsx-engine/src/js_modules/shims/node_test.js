@@ -0,0 +1,93 @@
+// node_test.js - node:test module compatible subset (test, subtests, t.assert)
+
+(function () {
+    var queue = [];
+    var running = false;
+
+    function now() {
+        return globalThis.performance && globalThis.performance.now
+            ? globalThis.performance.now()
+            : Date.now();
+    }
+
+    function errorMessage(e) {
+        return e && e.message !== undefined ? e.message : String(e);
+    }
+
+    function normalizeArgs(name, options, fn) {
+        if (typeof name === 'function') {
+            fn = name;
+            name = fn.name || '<anonymous>';
+            options = {};
+        } else if (typeof options === 'function') {
+            fn = options;
+            options = {};
+        }
+        return { name: String(name), options: options || {}, fn: fn };
+    }
+
+    function TestContext(name) {
+        this.name = name;
+        this.assert = globalThis.__tsxBuiltinModules.get('assert');
+        this._subtests = [];
+    }
+
+    TestContext.prototype.test = function (name, options, fn) {
+        var args = normalizeArgs(name, options, fn);
+        var promise = runTest(this.name + ' > ' + args.name, args.options, args.fn);
+        this._subtests.push(promise);
+        return promise.then(function () {});
+    };
+
+    TestContext.prototype.diagnostic = function (message) {
+        console.log('# ' + message);
+    };
+
+    // Resolves to true when the test (and all of its subtests) passed
+    async function runTest(name, options, fn) {
+        if (options.skip || options.todo) {
+            return true;
+        }
+        var t = new TestContext(name);
+        var start = now();
+        var error = null;
+        try {
+            await fn(t);
+        } catch (e) {
+            error = errorMessage(e);
+        }
+        // Node waits for subtests started by the parent before finishing it
+        var results = await Promise.all(t._subtests);
+        if (error === null && results.indexOf(false) !== -1) {
+            error = 'subtest failed';
+        }
+        __tsxNodeTestReport(name, error === null, now() - start, error === null ? '' : error);
+        return error === null;
+    }
+
+    async function drain() {
+        if (running) return;
+        running = true;
+        while (queue.length > 0) {
+            var entry = queue.shift();
+            await runTest(entry.name, entry.options, entry.fn);
+            entry.resolve();
+        }
+        running = false;
+    }
+
+    function test(name, options, fn) {
+        var args = normalizeArgs(name, options, fn);
+        return new Promise(function (resolve) {
+            queue.push({ name: args.name, options: args.options, fn: args.fn, resolve: resolve });
+            // setTimeout is microtask-backed, so this runs once the file's synchronous body finishes
+            globalThis.setTimeout(drain, 0);
+        });
+    }
+
+    test.test = test;
+    test.it = test;
+
+    // Like Node, only the node: prefixed form is a builtin ('test' stays an npm name)
+    globalThis.__tsxBuiltinModules.set('node:test', test);
+})();
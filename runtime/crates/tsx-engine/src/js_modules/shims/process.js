@@ -148,24 +148,86 @@ globalThis.process.nextTick = function (callback, ...args) {
 };
 
 let __tsxTimerId = 1;
+let __tsxTimerNow = 0;
+let __tsxTimerPumpQueued = false;
 const __tsxTimers = new Map();
 
-globalThis.setTimeout = function (callback, _ms = 0, ...args) {
+// Timers run from the microtask queue on a virtual clock: one timer per
+// microtask, earliest due first, so runtime.idle() drains them all. An
+// unref()'d timer fires like any other while a ref'd timer is pending; once
+// only unref'd timers remain they are left parked, and resume if a ref'd
+// timer is scheduled later, so keep-alive timers never block completion.
+const __tsxTimeout = class Timeout {
+    constructor(id, timer) {
+        this._id = id;
+        this._timer = timer;
+    }
+    ref() {
+        this._timer.ref = true;
+        __tsxScheduleTimers();
+        return this;
+    }
+    unref() {
+        this._timer.ref = false;
+        return this;
+    }
+    hasRef() {
+        return this._timer.ref;
+    }
+    [Symbol.toPrimitive]() {
+        return this._id;
+    }
+};
+
+function __tsxTimerKey(handle) {
+    return handle instanceof __tsxTimeout ? handle._id : handle;
+}
+
+function __tsxScheduleTimers() {
+    if (__tsxTimerPumpQueued) return;
+    __tsxTimerPumpQueued = true;
+    Promise.resolve().then(__tsxRunNextTimer);
+}
+
+// Fire the earliest due timer, unless nothing ref'd is left to wait for
+function __tsxRunNextTimer() {
+    __tsxTimerPumpQueued = false;
+    let next = null;
+    let hasRef = false;
+    for (const timer of __tsxTimers.values()) {
+        if (timer.ref) hasRef = true;
+        if (!next || timer.due < next.due || (timer.due === next.due && timer.id < next.id)) {
+            next = timer;
+        }
+    }
+    if (!next || !hasRef) return;
+
+    __tsxTimerNow = Math.max(__tsxTimerNow, next.due);
+    if (next.interval) {
+        next.due = __tsxTimerNow + next.delay;
+    } else {
+        __tsxTimers.delete(next.id);
+    }
+    __tsxScheduleTimers();
+    next.callback(...next.args);
+}
+
+function __tsxAddTimer(callback, ms, args, interval) {
     const id = __tsxTimerId++;
-    __tsxTimers.set(id, { active: true });
-    Promise.resolve().then(() => {
-        const t = __tsxTimers.get(id);
-        if (!t || !t.active) return;
-        callback(...args);
-        __tsxTimers.delete(id);
-    });
-    return id;
+    // Like Node, delays below 1ms (or not numbers) become 1ms
+    const delay = Number(ms) >= 1 ? Number(ms) : 1;
+    const timer = { id, callback, args, delay, due: __tsxTimerNow + delay, interval, ref: true };
+    __tsxTimers.set(id, timer);
+    __tsxScheduleTimers();
+    return new __tsxTimeout(id, timer);
+}
+
+globalThis.setTimeout = function (callback, ms = 0, ...args) {
+    return __tsxAddTimer(callback, ms, args, false);
 };
 
-globalThis.clearTimeout = function (id) {
-    const timer = __tsxTimers.get(id);
-    if (timer) timer.active = false;
-    __tsxTimers.delete(id);
+globalThis.clearTimeout = function (handle) {
+    __tsxTimers.delete(__tsxTimerKey(handle));
 };
 
 globalThis.setImmediate = function (callback, ...args) {
@@ -176,17 +238,8 @@ globalThis.clearImmediate = function (id) {
     globalThis.clearTimeout(id);
 };
 
-globalThis.setInterval = function (callback, _ms = 0, ...args) {
-    const id = __tsxTimerId++;
-    __tsxTimers.set(id, { active: true, interval: true });
-    const tick = () => {
-        const t = __tsxTimers.get(id);
-        if (!t || !t.active) return;
-        callback(...args);
-        Promise.resolve().then(tick);
-    };
-    Promise.resolve().then(tick);
-    return id;
+globalThis.setInterval = function (callback, ms = 0, ...args) {
+    return __tsxAddTimer(callback, ms, args, true);
 };
 
 globalThis.clearInterval = function (id) {
@@ -0,0 +1,102 @@
+// test_harness.js - minimal test()/expect() globals for the tsx-test command
+
+(function () {
+    var tests = [];
+    var next = 0;
+    var running = false;
+
+    function now() {
+        return globalThis.performance && globalThis.performance.now
+            ? globalThis.performance.now()
+            : Date.now();
+    }
+
+    function describeValue(v) {
+        if (typeof v === 'string') return JSON.stringify(v);
+        try {
+            var json = JSON.stringify(v);
+            if (json !== undefined) return json;
+        } catch (_) { /* fall through */ }
+        return String(v);
+    }
+
+    function deepEqual(a, b) {
+        if (Object.is(a, b)) return true;
+        if (typeof a !== 'object' || typeof b !== 'object' || a === null || b === null) return false;
+        if (Array.isArray(a) !== Array.isArray(b)) return false;
+        var ka = Object.keys(a);
+        var kb = Object.keys(b);
+        if (ka.length !== kb.length) return false;
+        for (var i = 0; i < ka.length; i++) {
+            if (!Object.prototype.hasOwnProperty.call(b, ka[i])) return false;
+            if (!deepEqual(a[ka[i]], b[ka[i]])) return false;
+        }
+        return true;
+    }
+
+    function makeMatchers(actual, negate) {
+        function check(pass, message) {
+            if (pass === negate) {
+                throw new Error(negate ? 'Expected not: ' + message : message);
+            }
+        }
+        return {
+            toBe: function (expected) {
+                check(Object.is(actual, expected),
+                    'Expected ' + describeValue(actual) + ' to be ' + describeValue(expected));
+            },
+            toEqual: function (expected) {
+                check(deepEqual(actual, expected),
+                    'Expected ' + describeValue(actual) + ' to equal ' + describeValue(expected));
+            },
+            toBeTruthy: function () {
+                check(!!actual, 'Expected ' + describeValue(actual) + ' to be truthy');
+            },
+            toBeFalsy: function () {
+                check(!actual, 'Expected ' + describeValue(actual) + ' to be falsy');
+            },
+            toContain: function (item) {
+                var has = actual != null && typeof actual.indexOf === 'function' && actual.indexOf(item) !== -1;
+                check(has, 'Expected ' + describeValue(actual) + ' to contain ' + describeValue(item));
+            },
+            toThrow: function () {
+                var threw = false;
+                try { actual(); } catch (_) { threw = true; }
+                check(threw, 'Expected function to throw');
+            },
+        };
+    }
+
+    function expect(actual) {
+        var matchers = makeMatchers(actual, false);
+        matchers.not = makeMatchers(actual, true);
+        return matchers;
+    }
+
+    async function runPending() {
+        if (running) return;
+        running = true;
+        while (next < tests.length) {
+            var t = tests[next++];
+            var start = now();
+            try {
+                await t.fn();
+                __tsxTestReport(t.name, true, now() - start, '');
+            } catch (e) {
+                var message = e && e.message !== undefined ? e.message : String(e);
+                __tsxTestReport(t.name, false, now() - start, message);
+            }
+        }
+        running = false;
+    }
+
+    function test(name, fn) {
+        tests.push({ name: String(name), fn: fn });
+        // setTimeout is microtask-backed, so this runs once the file's synchronous body finishes
+        globalThis.setTimeout(runPending, 0);
+    }
+
+    globalThis.test = test;
+    globalThis.it = test;
+    globalThis.expect = expect;
+})();
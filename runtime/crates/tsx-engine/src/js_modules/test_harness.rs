@@ -0,0 +1,59 @@
+//! Test harness module - `test`/`it`/`expect` globals for the tsx-test command.
+//!
+//! Only installed while a test run is active; results are reported back to Rust
+//! through `__tsxTestReport` and collected in thread-local storage.
+
+use rquickjs::{Ctx, Function, Result};
+
+const TEST_HARNESS_JS: &str = include_str!("shims/test_harness.js");
+
+/// Outcome of a single `test(name, fn)` call.
+#[derive(Clone, Debug)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub passed: bool,
+    pub duration_ms: f64,
+    pub error: Option<String>,
+}
+
+thread_local! {
+    static HARNESS_ENABLED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    static RESULTS: std::cell::RefCell<Vec<TestCaseResult>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Enable or disable installing the harness globals in new contexts.
+pub fn set_enabled(enabled: bool) {
+    HARNESS_ENABLED.with(|e| e.set(enabled));
+}
+
+/// Drain the results reported since the last call.
+pub fn take_results() -> Vec<TestCaseResult> {
+    RESULTS.with(|r| std::mem::take(&mut *r.borrow_mut()))
+}
+
+/// Record a result reported by either the tsx-test harness or `node:test`.
+pub(crate) fn record(result: TestCaseResult) {
+    RESULTS.with(|r| r.borrow_mut().push(result));
+}
+
+/// Install the harness globals when a test run is active.
+pub fn install(ctx: &Ctx<'_>) -> Result<()> {
+    if !HARNESS_ENABLED.with(|e| e.get()) {
+        return Ok(());
+    }
+
+    let report = Function::new(
+        ctx.clone(),
+        |name: String, passed: bool, duration_ms: f64, error: String| {
+            record(TestCaseResult {
+                name,
+                passed,
+                duration_ms,
+                error: if error.is_empty() { None } else { Some(error) },
+            });
+        },
+    )?;
+    ctx.globals().set("__tsxTestReport", report)?;
+    ctx.eval::<(), _>(TEST_HARNESS_JS)?;
+    Ok(())
+}
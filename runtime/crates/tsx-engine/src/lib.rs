@@ -6,10 +6,14 @@
 
 #[allow(warnings)]
 mod bindings;
+mod fetch_limit;
 mod http_client;
+mod intrinsics;
 mod js_modules;
 mod loader;
+mod profiler;
 mod resolver;
+mod test_runner;
 mod transpiler;
 
 use bindings::exports::shell::unix::command::{ExecEnv, Guest};
@@ -54,6 +58,7 @@ impl Guest for TsxEngine {
         match name.as_str() {
             "tsx" => run_tsx(args, stdin, stdout, stderr, env),
             "tsc" => run_tsc(args, stdin, stdout, stderr),
+            "tsx-test" => run_tsx_test(args, stdout, stderr, env),
             _ => {
                 write_to_stream(&stderr, format!("Unknown command: {}\n", name).as_bytes());
                 127
@@ -62,7 +67,7 @@ impl Guest for TsxEngine {
     }
 
     fn list_commands() -> Vec<String> {
-        vec!["tsx".to_string(), "tsc".to_string()]
+        vec!["tsx".to_string(), "tsc".to_string(), "tsx-test".to_string()]
     }
 }
 
@@ -165,6 +170,14 @@ fn run_tsx(
     let mut code: Option<String> = None;
     let mut file_path: Option<String> = None;
     let mut script_args: Vec<String> = Vec::new();
+    let mut test_mode = false;
+    let mut sample_profile = false;
+    let mut freeze_intrinsics = false;
+    let mut trace_resolution = false;
+    let mut conditions: Vec<String> = Vec::new();
+    let mut max_fetch_concurrency = fetch_limit::DEFAULT_MAX_CONCURRENCY;
+    let mut emit = false;
+    let mut outdir: Option<String> = None;
     let mut i = 0;
     let mut parse_options = true;
 
@@ -190,9 +203,99 @@ fn run_tsx(
                     return 1;
                 }
             }
+            "--test" => {
+                test_mode = true;
+                i += 1;
+            }
+            "--sample-profile" => {
+                sample_profile = true;
+                i += 1;
+            }
+            "--freeze-intrinsics" => {
+                freeze_intrinsics = true;
+                i += 1;
+            }
+            "--trace-resolution" => {
+                trace_resolution = true;
+                i += 1;
+            }
+            "-C" | "--conditions" => {
+                if i + 1 < args.len() {
+                    conditions.push(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    write_to_stream(&stderr, b"tsx: --conditions requires a condition name\n");
+                    return 1;
+                }
+            }
+            "--emit" => {
+                emit = true;
+                i += 1;
+            }
+            "--outdir" => {
+                if i + 1 < args.len() {
+                    emit = true;
+                    outdir = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    write_to_stream(&stderr, b"tsx: --outdir requires a directory\n");
+                    return 1;
+                }
+            }
+            "--max-fetch-concurrency" => {
+                match args.get(i + 1).and_then(|n| n.parse::<usize>().ok()) {
+                    Some(n) if n > 0 => {
+                        max_fetch_concurrency = n;
+                        i += 2;
+                    }
+                    _ => {
+                        write_to_stream(
+                            &stderr,
+                            b"tsx: --max-fetch-concurrency requires a positive number\n",
+                        );
+                        return 1;
+                    }
+                }
+            }
             "-h" | "--help" => {
                 write_to_stream(&stdout, b"Usage: tsx [options] [file]\n");
                 write_to_stream(&stdout, b"  -e, --eval <code>  Evaluate code\n");
+                write_to_stream(
+                    &stdout,
+                    b"  --test             Run node:test tests and print a TAP report\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  --sample-profile   Print the most-sampled functions to stderr\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  --freeze-intrinsics  Freeze built-in prototypes before running\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  --trace-resolution   Print each module resolution step to stderr\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  -C, --conditions <name>  Add an exports condition (e.g. browser)\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    format!(
+                        "  --max-fetch-concurrency <n>  Limit in-flight HTTP requests (default {})\n",
+                        fetch_limit::DEFAULT_MAX_CONCURRENCY
+                    )
+                    .as_bytes(),
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  --emit             Write the transpiled .js (and .js.map) next to the source\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  --outdir <dir>     Write --emit output to <dir> instead\n",
+                );
                 write_to_stream(&stdout, b"  -h, --help         Show this help\n");
                 write_to_stream(
                     &stdout,
@@ -269,11 +372,43 @@ fn run_tsx(
         }
     };
 
+    // Emitting is best effort: a read-only filesystem must not stop the run
+    if emit {
+        match file_path.as_deref() {
+            Some(path) => {
+                let fs_path = resolver::file_url_to_path(path).unwrap_or_else(|| path.to_string());
+                if let Err(e) = emit_transpiled(&fs_path, outdir.as_deref(), &transpile_result) {
+                    write_to_stream(
+                        &stderr,
+                        format!("tsx: warning: --emit failed: {}\n", e).as_bytes(),
+                    );
+                }
+            }
+            None => write_to_stream(
+                &stderr,
+                b"tsx: warning: --emit needs a source file, nothing written\n",
+            ),
+        }
+    }
+
     // Step 2: Execute the JavaScript using QuickJS with full runtime
     // Clear any captured logs from previous executions
     js_modules::console::clear_logs();
+    js_modules::test_harness::take_results();
     js_modules::process::set_argv(script_args);
     js_modules::process::set_runtime_env(env.cwd, env.vars);
+    profiler::set_enabled(sample_profile);
+    // Samples are taken at ticks the transpiler only adds while sampling is
+    // enabled; the emitted file above stays free of them
+    let transpile_result = if sample_profile {
+        transpiler::transpile(&ts_code).unwrap_or(transpile_result)
+    } else {
+        transpile_result
+    };
+    intrinsics::set_enabled(freeze_intrinsics);
+    resolver::set_trace(trace_resolution);
+    resolver::set_conditions(conditions);
+    fetch_limit::set_max_concurrency(max_fetch_concurrency);
 
     let exec_result = if transpile_result.contains_module_decls {
         execute_js_module_with_source_map(
@@ -293,6 +428,40 @@ fn run_tsx(
 
     js_modules::process::set_argv(Vec::new());
     js_modules::process::set_runtime_env("/".to_string(), Vec::new());
+    intrinsics::set_enabled(false);
+    resolver::set_conditions(Vec::new());
+    fetch_limit::set_max_concurrency(fetch_limit::DEFAULT_MAX_CONCURRENCY);
+
+    if trace_resolution {
+        resolver::set_trace(false);
+        for line in resolver::take_trace() {
+            write_to_stream(&stderr, format!("{}\n", line).as_bytes());
+        }
+    }
+
+    if sample_profile {
+        profiler::set_enabled(false);
+        let ranking = profiler::take_ranking();
+        write_to_stream(&stderr, profiler::format_ranking(&ranking, 10).as_bytes());
+    }
+
+    // node:test results are recorded whether or not --test was given
+    let test_results = js_modules::test_harness::take_results();
+    if test_mode {
+        let console_output = js_modules::console::get_logs();
+        if !console_output.is_empty() {
+            write_to_stream(&stdout, console_output.as_bytes());
+            if !console_output.ends_with('\n') {
+                write_to_stream(&stdout, b"\n");
+            }
+        }
+        write_to_stream(&stdout, test_runner::format_tap(&test_results).as_bytes());
+        if let Err(e) = &exec_result {
+            write_to_stream(&stderr, format!("tsx: {}\n", e).as_bytes());
+            return 1;
+        }
+        return test_runner::tap_exit_code(&test_results);
+    }
 
     match exec_result {
         Ok(output) => {
@@ -330,6 +499,65 @@ fn run_tsx(
     }
 }
 
+/// Discover and run test files with the test()/expect() harness
+fn run_tsx_test(
+    args: Vec<String>,
+    stdout: OutputStream,
+    stderr: OutputStream,
+    env: ExecEnv,
+) -> i32 {
+    let mut patterns: Vec<String> = Vec::new();
+    for arg in &args {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                write_to_stream(&stdout, b"Usage: tsx-test [GLOB...]\n");
+                write_to_stream(
+                    &stdout,
+                    b"  Runs matching test files (default: *.test.ts, *.test.js)\n",
+                );
+                write_to_stream(
+                    &stdout,
+                    b"  Globals: test(name, fn), it(name, fn), expect(x).toBe(y)\n",
+                );
+                return 0;
+            }
+            value if value.starts_with('-') => {
+                write_to_stream(
+                    &stderr,
+                    format!("tsx-test: unknown option: {}\n", value).as_bytes(),
+                );
+                return 1;
+            }
+            value => patterns.push(value.to_string()),
+        }
+    }
+    if patterns.is_empty() {
+        patterns = test_runner::DEFAULT_TEST_PATTERNS
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+    }
+
+    let files = test_runner::discover_test_files(&env.cwd, &patterns);
+    if files.is_empty() {
+        write_to_stream(
+            &stderr,
+            format!("tsx-test: no test files match {}\n", patterns.join(" ")).as_bytes(),
+        );
+        return 1;
+    }
+
+    js_modules::process::set_runtime_env(env.cwd, env.vars);
+    let reports: Vec<test_runner::FileReport> = files
+        .iter()
+        .map(|file| test_runner::run_test_file(file))
+        .collect();
+    js_modules::process::set_runtime_env("/".to_string(), Vec::new());
+
+    write_to_stream(&stdout, test_runner::format_reports(&reports).as_bytes());
+    test_runner::exit_code(&reports)
+}
+
 /// Transpile-only TypeScript (output JavaScript, no execution)
 fn run_tsc(
     args: Vec<String>,
@@ -463,23 +691,14 @@ fn execute_js_with_source_map_and_limits(
     configure_runtime_with_limits(&runtime, limits);
     let context = futures_lite::future::block_on(AsyncContext::full(&runtime))
         .map_err(|e| format!("Failed to create context: {}", e))?;
+    let _sampling = profiler::SamplingScope;
 
     // Install all JS modules (console, fs, path, Buffer, fetch, etc.)
     futures_lite::future::block_on(context.with(|ctx| {
         js_modules::install_all(&ctx)?;
-        let escaped = source_name.replace('\\', "\\\\").replace('\'', "\\'");
-        let bootstrap = format!(
-            "globalThis.__tsxEntryBase = '{}'; \
-             if (globalThis.__tsxCreateRequire) {{ globalThis.require = globalThis.__tsxCreateRequire(globalThis.__tsxEntryBase); }} \
-             if (globalThis.__dirname !== undefined) {{ \
-               const p = globalThis.__tsxEntryBase || '/'; \
-               const i = p.lastIndexOf('/'); \
-               globalThis.__filename = p; \
-               globalThis.__dirname = i > 0 ? p.slice(0, i) : '/'; \
-             }}",
-            escaped
-        );
-        ctx.eval::<(), _>(bootstrap)?;
+        ctx.eval::<(), _>(entry_bootstrap(source_name))?;
+        intrinsics::freeze(&ctx)?;
+        profiler::attach(&ctx)?;
         Ok::<(), rquickjs::Error>(())
     }))
     .map_err(|e| format!("Failed to install bindings: {}", e))?;
@@ -554,22 +773,13 @@ fn execute_js_module_with_source_map_and_limits(
     configure_runtime_with_limits(&runtime, limits);
     let context = futures_lite::future::block_on(AsyncContext::full(&runtime))
         .map_err(|e| format!("Failed to create context: {}", e))?;
+    let _sampling = profiler::SamplingScope;
 
     futures_lite::future::block_on(context.with(|ctx| {
         js_modules::install_all(&ctx)?;
-        let escaped = source_name.replace('\\', "\\\\").replace('\'', "\\'");
-        let bootstrap = format!(
-            "globalThis.__tsxEntryBase = '{}'; \
-             if (globalThis.__tsxCreateRequire) {{ globalThis.require = globalThis.__tsxCreateRequire(globalThis.__tsxEntryBase); }} \
-             if (globalThis.__dirname !== undefined) {{ \
-               const p = globalThis.__tsxEntryBase || '/'; \
-               const i = p.lastIndexOf('/'); \
-               globalThis.__filename = p; \
-               globalThis.__dirname = i > 0 ? p.slice(0, i) : '/'; \
-             }}",
-            escaped
-        );
-        ctx.eval::<(), _>(bootstrap)?;
+        ctx.eval::<(), _>(entry_bootstrap(source_name))?;
+        intrinsics::freeze(&ctx)?;
+        profiler::attach(&ctx)?;
         Ok::<(), rquickjs::Error>(())
     }))
     .map_err(|e| format!("Failed to install bindings: {}", e))?;
@@ -582,11 +792,21 @@ fn execute_js_module_with_source_map_and_limits(
     std::fs::write(&temp_name, js_code)
         .map_err(|e| format!("Failed to write module {}: {}", temp_name, e))?;
     let escaped_path = temp_name.replace('\\', "\\\\").replace('\'', "\\'");
+    // Without a default export, fall back to a Node-style view of the namespace
+    // so named-only modules don't silently produce blank output.
     let bootstrap = format!(
         "globalThis.__tsxModuleDefault = undefined;\n\
          globalThis.__tsxModuleError = undefined;\n\
          import('{}')\n\
-           .then((m) => {{ globalThis.__tsxModuleDefault = (m && m.default); }})\n\
+           .then((m) => {{\n\
+             if (!m) return;\n\
+             if ('default' in m) {{ globalThis.__tsxModuleDefault = m.default; return; }}\n\
+             const names = Object.keys(m);\n\
+             if (names.length === 0) return;\n\
+             const util = globalThis.__tsxBuiltinModules && globalThis.__tsxBuiltinModules.get('util');\n\
+             const view = util ? util.inspect(Object.assign({{}}, m)) : '{{ ' + names.join(', ') + ' }}';\n\
+             globalThis.__tsxModuleDefault = '[Module: null prototype] ' + view;\n\
+           }})\n\
            .catch((e) => {{ globalThis.__tsxModuleError = e; }});\n\
          undefined;",
         escaped_path
@@ -646,6 +866,7 @@ fn configure_runtime_with_limits(runtime: &AsyncRuntime, limits: RuntimeLimits)
 
         runtime
             .set_interrupt_handler(Some(Box::new(move || {
+                profiler::sample();
                 started_at.elapsed() >= limits.execution_timeout
             })))
             .await;
@@ -700,6 +921,31 @@ fn take_unhandled_error(context: &AsyncContext) -> Option<String> {
     }))
 }
 
+/// Per-entry globals shared by script and module mode: `__tsxEntryBase`,
+/// `__filename`/`__dirname`, and a global `require` bound to the entry's
+/// directory so CJS-only libraries work from ESM entries too. Pseudo entries
+/// like `<eval>` resolve requires against the process cwd, as Node does.
+fn entry_bootstrap(source_name: &str) -> String {
+    let escaped = source_name.replace('\\', "\\\\").replace('\'', "\\'");
+    format!(
+        "globalThis.__tsxEntryBase = '{}'; \
+         if (globalThis.__tsxCreateRequire) {{ \
+           const entry = globalThis.__tsxEntryBase; \
+           const base = entry.startsWith('<') \
+             ? process.cwd().replace(/\\/$/, '') + '/' + entry \
+             : entry; \
+           globalThis.require = globalThis.__tsxCreateRequire(base); \
+         }} \
+         if (globalThis.__dirname !== undefined) {{ \
+           const p = globalThis.__tsxEntryBase || '/'; \
+           const i = p.lastIndexOf('/'); \
+           globalThis.__filename = p; \
+           globalThis.__dirname = i > 0 ? p.slice(0, i) : '/'; \
+         }}",
+        escaped
+    )
+}
+
 fn temp_module_path(source_name: &str) -> String {
     let nanos = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -919,6 +1165,74 @@ fn format_js_unhandled_error(
     format!("Unhandled error in {}: {}", source_name, remapped_raw)
 }
 
+/// Write the transpiled JavaScript for `source_path` as `<stem>.js` (`.mjs`
+/// for `.mts`, `.cjs` for `.cts`), plus a `.map` next to it when a source map
+/// was generated. Returns the JS path. Refuses to overwrite the source itself.
+fn emit_transpiled(
+    source_path: &str,
+    outdir: Option<&str>,
+    result: &transpiler::TranspileResult,
+) -> Result<String, String> {
+    let source = std::path::Path::new(source_path);
+    let stem = source
+        .file_stem()
+        .ok_or_else(|| format!("{}: not a file name", source_path))?;
+    let dir = match outdir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => source.parent().map(|p| p.to_path_buf()).unwrap_or_default(),
+    };
+    if !dir.as_os_str().is_empty() {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+
+    let extension = match source.extension().and_then(|e| e.to_str()) {
+        Some("mts") => "mjs",
+        Some("cts") => "cjs",
+        _ => "js",
+    };
+    let js_name = format!("{}.{}", stem.to_string_lossy(), extension);
+    if source.file_name() == Some(std::ffi::OsStr::new(&js_name))
+        && (outdir.is_none() || same_dir(&dir, source.parent()))
+    {
+        return Err(format!(
+            "{}: output would overwrite the source",
+            source_path
+        ));
+    }
+    let js_path = dir.join(&js_name);
+    let mut code = result.code.clone();
+    if let Some(map) = &result.source_map {
+        let map_path = dir.join(format!("{}.map", js_name));
+        std::fs::write(&map_path, map).map_err(|e| format!("{}: {}", map_path.display(), e))?;
+        if !code.ends_with('\n') {
+            code.push('\n');
+        }
+        code.push_str(&format!("//# sourceMappingURL={}.map\n", js_name));
+    }
+    std::fs::write(&js_path, code).map_err(|e| format!("{}: {}", js_path.display(), e))?;
+    Ok(js_path.to_string_lossy().to_string())
+}
+
+/// Whether `dir` is the directory `parent` names. Assumes so when either
+/// can't be resolved, so a failed lookup never lets a source be overwritten.
+fn same_dir(dir: &std::path::Path, parent: Option<&std::path::Path>) -> bool {
+    let resolve = |p: &std::path::Path| {
+        let p = if p.as_os_str().is_empty() {
+            std::path::Path::new(".")
+        } else {
+            p
+        };
+        std::fs::canonicalize(p)
+    };
+    match (
+        resolve(dir),
+        resolve(parent.unwrap_or(std::path::Path::new(""))),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => true,
+    }
+}
+
 /// Helper to write data to an output stream
 fn write_to_stream(stream: &OutputStream, data: &[u8]) {
     let _ = stream.blocking_write_and_flush(data);
@@ -958,6 +1272,82 @@ mod integration_tests {
         format!("/tmp/tsx-engine-{}-{}.{}", name, nanos, ext)
     }
 
+    #[test]
+    fn test_integration_emit_writes_transpiled_js_that_runs() {
+        let root = unique_temp_path("emit", "dir");
+        std::fs::create_dir_all(&root).unwrap();
+        let source_path = format!("{}/script.ts", root);
+        let ts =
+            "const greet = (name: string): string => `hi ${name}`;\nconsole.log(greet('emit'));\n";
+        std::fs::write(&source_path, ts).unwrap();
+        let transpiled = transpiler::transpile(ts).unwrap();
+
+        let js_path = emit_transpiled(&source_path, None, &transpiled).unwrap();
+        assert_eq!(js_path, format!("{}/script.js", root));
+        let emitted = std::fs::read_to_string(&js_path).unwrap();
+        assert!(
+            emitted.starts_with(&transpiled.code),
+            "emitted: {}",
+            emitted
+        );
+        assert!(!emitted.contains(": string"), "emitted: {}", emitted);
+        if transpiled.source_map.is_some() {
+            assert!(std::path::Path::new(&format!("{}.map", js_path)).exists());
+        }
+
+        js_modules::console::clear_logs();
+        execute_js(&emitted, &js_path, None).unwrap();
+        assert!(js_modules::console::get_logs().contains("hi emit"));
+
+        let outdir = format!("{}/out", root);
+        let js_path = emit_transpiled(&source_path, Some(&outdir), &transpiled).unwrap();
+        assert_eq!(js_path, format!("{}/script.js", outdir));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_integration_emit_keeps_module_kind_extension() {
+        let root = unique_temp_path("emit-ext", "dir");
+        std::fs::create_dir_all(&root).unwrap();
+        let transpiled = transpiler::transpile("export const x: number = 1;\n").unwrap();
+
+        for (source, expected) in [("mod.mts", "mod.mjs"), ("common.cts", "common.cjs")] {
+            let source_path = format!("{}/{}", root, source);
+            std::fs::write(&source_path, "export const x: number = 1;\n").unwrap();
+            let js_path = emit_transpiled(&source_path, None, &transpiled).unwrap();
+            assert_eq!(js_path, format!("{}/{}", root, expected));
+            assert!(std::path::Path::new(&js_path).exists());
+        }
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_integration_emit_refuses_to_overwrite_source() {
+        let root = unique_temp_path("emit-self", "dir");
+        std::fs::create_dir_all(&root).unwrap();
+        let source_path = format!("{}/app.js", root);
+        let js = "const x = 1;\nconsole.log(x);\n";
+        std::fs::write(&source_path, js).unwrap();
+        let transpiled = transpiler::transpile(js).unwrap();
+
+        let err = emit_transpiled(&source_path, None, &transpiled).unwrap_err();
+        assert!(err.contains("overwrite the source"), "err: {}", err);
+        // Same directory spelled differently through --outdir
+        let err =
+            emit_transpiled(&source_path, Some(&format!("{}/.", root)), &transpiled).unwrap_err();
+        assert!(err.contains("overwrite the source"), "err: {}", err);
+        assert_eq!(std::fs::read_to_string(&source_path).unwrap(), js);
+
+        // A different directory is fine
+        let outdir = format!("{}/out", root);
+        let js_path = emit_transpiled(&source_path, Some(&outdir), &transpiled).unwrap();
+        assert_eq!(js_path, format!("{}/app.js", outdir));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn test_integration_script_mode_runs_async_and_captures_console() {
         let ts = r#"
@@ -985,6 +1375,32 @@ mod integration_tests {
         assert!(logs.contains("script-ok"), "logs: {}", logs);
     }
 
+    #[test]
+    fn test_integration_console_assert_reports_only_failures() {
+        js_modules::console::clear_logs();
+        execute_js(
+            "console.assert(1 + 1 === 2, 'math works'); console.assert(false, 'expected', 42);",
+            "<integration-console-assert>",
+            None,
+        )
+        .unwrap();
+        let logs = js_modules::console::get_logs();
+        assert_eq!(logs, "ERROR: Assertion failed: expected 42");
+    }
+
+    #[test]
+    fn test_integration_console_count_tracks_labels() {
+        js_modules::console::clear_logs();
+        execute_js(
+            "console.count('x'); console.count('x'); console.count(); console.countReset('x'); console.count('x');",
+            "<integration-console-count>",
+            None,
+        )
+        .unwrap();
+        let logs = js_modules::console::get_logs();
+        assert_eq!(logs, "x: 1\nx: 2\ndefault: 1\nx: 1");
+    }
+
     #[test]
     fn test_integration_module_mode_imports_typescript_dependency() {
         let dep_path = unique_temp_path("dep", "ts");
@@ -1038,6 +1454,189 @@ mod integration_tests {
         let _ = std::fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn test_integration_module_mode_imports_css_module_class_names() {
+        let root = unique_temp_path("css-module", "dir");
+        let _ = std::fs::create_dir_all(&root);
+        let css_path = format!("{}/a.module.css", root);
+        std::fs::write(
+            &css_path,
+            "/* .commented { } */\n\
+             .button, .card > .title:hover { background: url(\"icon.png\"); margin: .5em; }\n\
+             @media (max-width: 600px) { .button-small { padding: 0; } }\n",
+        )
+        .unwrap();
+        let source_name = format!("{}/entry.ts", root);
+        let js = "import styles from './a.module.css' with { type: 'css-module' };\n\
+                  export default [styles.button, styles.title, styles['button-small'], \
+                  String(styles.png), String(styles.commented), String(styles.missing)].join(',');";
+
+        let output = execute_js_module(js, &source_name, None).unwrap();
+        assert_eq!(
+            output,
+            "button,title,button-small,undefined,undefined,undefined"
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_integration_module_mode_without_default_surfaces_namespace() {
+        let ts = "export const x = 1;";
+        let transpiled = transpiler::transpile(ts).unwrap();
+        assert!(transpiled.contains_module_decls);
+
+        let output = execute_js_module(
+            &transpiled.code,
+            "<integration-named-only>",
+            transpiled.line_map.as_deref(),
+        )
+        .unwrap();
+
+        assert!(output.contains("[Module"), "output: {}", output);
+        assert!(output.contains("x: 1"), "output: {}", output);
+    }
+
+    #[test]
+    fn test_integration_sample_profile_ranks_hot_function_first() {
+        let js = r#"
+            function hot() {
+                let total = 0;
+                for (let i = 0; i < 3000000; i++) total += i % 7;
+                return total;
+            }
+            function cold() { return 1; }
+            hot() + cold();
+        "#;
+
+        profiler::set_enabled(true);
+        profiler::take_ranking();
+        // Samples are taken at the ticks the transpiler adds while enabled
+        let transpiled = transpiler::transpile(js).unwrap();
+        let result = execute_js(&transpiled.code, "<integration-profile>", None);
+        profiler::set_enabled(false);
+        result.unwrap();
+
+        let ranking = profiler::take_ranking();
+        assert!(!ranking.is_empty(), "no samples recorded");
+        assert_eq!(ranking[0].0, "hot", "ranking: {:?}", ranking);
+        let report = profiler::format_ranking(&ranking, 10);
+        assert!(
+            report.starts_with("Sampled profile ("),
+            "report: {}",
+            report
+        );
+    }
+
+    #[test]
+    fn test_integration_freeze_intrinsics_blocks_prototype_writes() {
+        let js = r#"
+            (function () {
+                'use strict';
+                let polluted;
+                try {
+                    Object.prototype.x = 1;
+                    polluted = 'assigned:' + ({}).x;
+                } catch (e) {
+                    polluted = 'threw:' + (e instanceof TypeError);
+                }
+                const obj = {};
+                obj.toString = () => 'own';
+                return polluted + ',' + String(obj);
+            })()
+        "#;
+
+        let unfrozen = execute_js(js, "<integration-unfrozen>", None).unwrap();
+        assert!(unfrozen.contains("assigned:1,own"), "output: {}", unfrozen);
+
+        intrinsics::set_enabled(true);
+        let frozen = execute_js(js, "<integration-frozen>", None);
+        intrinsics::set_enabled(false);
+        let frozen = frozen.unwrap();
+        assert!(frozen.contains("threw:true,own"), "output: {}", frozen);
+    }
+
+    #[test]
+    fn test_integration_fetch_concurrency_is_limited() {
+        // The mock transport answers asynchronously, so requests overlap
+        // unless the limiter queues them
+        let js = r#"
+            (async () => {
+                let active = 0;
+                const peak = {};
+                globalThis.__syncFetch__ = (url) => {
+                    active++;
+                    const host = url.split('/')[2];
+                    peak.total = Math.max(peak.total || 0, active);
+                    return new Promise((resolve) => setTimeout(() => {
+                        active--;
+                        resolve(JSON.stringify({ ok: true, status: 200, statusText: 'OK', headers: [], body: host }));
+                    }, 0));
+                };
+                const urls = [];
+                for (let i = 0; i < 5; i++) urls.push('https://a.example/' + i);
+                urls.push('https://b.example/x');
+                const bodies = await Promise.all(urls.map((u) => fetch(u).then((r) => r.text())));
+                console.log(peak.total + ':' + bodies.join(','));
+            })()
+        "#;
+
+        let run = |name: &str| {
+            js_modules::console::clear_logs();
+            execute_js(js, name, None).unwrap();
+            js_modules::console::get_logs()
+        };
+
+        fetch_limit::set_max_concurrency(2);
+        let limited = run("<fetch-limited>");
+        fetch_limit::set_max_concurrency(fetch_limit::DEFAULT_MAX_CONCURRENCY);
+        assert!(
+            limited.contains("2:a.example,a.example,a.example,a.example,a.example,b.example"),
+            "logs: {}",
+            limited
+        );
+
+        // Under the default limit all six requests overlap
+        let unlimited = run("<fetch-unlimited>");
+        assert!(unlimited.contains("6:a.example"), "logs: {}", unlimited);
+    }
+
+    #[test]
+    fn test_integration_node_test_reports_tap() {
+        let path = unique_temp_path("node-test", "ts");
+        std::fs::write(
+            &path,
+            "import { test } from 'node:test';\n\
+             test('adds', async (t) => {\n\
+               await t.test('inner', () => { t.assert.strictEqual(1 + 1, 2); });\n\
+             });\n\
+             test('broken', async (t) => { t.assert.strictEqual(1 + 1, 3); });\n",
+        )
+        .unwrap();
+
+        let source = std::fs::read_to_string(&path).unwrap();
+        let transpiled = transpiler::transpile(&source).unwrap();
+        assert!(transpiled.contains_module_decls);
+
+        js_modules::test_harness::take_results();
+        execute_js_module(&transpiled.code, &path, transpiled.line_map.as_deref()).unwrap();
+        let results = js_modules::test_harness::take_results();
+        let _ = std::fs::remove_file(&path);
+
+        let tap = test_runner::format_tap(&results);
+        assert!(tap.starts_with("TAP version 13\n"), "tap: {}", tap);
+        assert!(tap.contains("ok 1 - adds > inner"), "tap: {}", tap);
+        assert!(tap.contains("ok 2 - adds"), "tap: {}", tap);
+        assert!(tap.contains("not ok 3 - broken"), "tap: {}", tap);
+        assert!(tap.contains("Expected 2 === 3"), "tap: {}", tap);
+        assert!(
+            tap.contains("1..3\n# tests 3\n# pass 2\n# fail 1"),
+            "tap: {}",
+            tap
+        );
+        assert_eq!(test_runner::tap_exit_code(&results), 1);
+    }
+
     #[test]
     fn test_integration_module_mode_reads_process_argv() {
         let ts = "export default process.argv.slice(2).join(',');";
@@ -1286,6 +1885,162 @@ mod integration_tests {
         let _ = std::fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn test_integration_module_mode_global_require_loads_cjs() {
+        let root = unique_temp_path("esm-global-require", "dir");
+        let pkg_dir = format!("{}/node_modules/foo", root);
+        let _ = std::fs::create_dir_all(&pkg_dir);
+        let entry_path = format!("{}/entry.ts", root);
+
+        std::fs::write(
+            format!("{}/helper.cjs", root),
+            "module.exports = { double: (n) => n * 2 };",
+        )
+        .unwrap();
+        std::fs::write(
+            format!("{}/package.json", pkg_dir),
+            r#"{"name":"foo","exports":{"." :{"import":"./esm.js","require":"./cjs.cjs"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(format!("{}/esm.js", pkg_dir), "export const kind = 'esm';").unwrap();
+        std::fs::write(
+            format!("{}/cjs.cjs", pkg_dir),
+            "module.exports = { kind: 'cjs' };",
+        )
+        .unwrap();
+        std::fs::write(
+            &entry_path,
+            "const helper = require('./helper.cjs');\n\
+             const foo = require('foo');\n\
+             export default helper.double(21) + ':' + foo.kind;",
+        )
+        .unwrap();
+
+        let source = std::fs::read_to_string(&entry_path).unwrap();
+        let transpiled = transpiler::transpile(&source).unwrap();
+        assert!(transpiled.contains_module_decls);
+
+        let output = execute_js_module(
+            &transpiled.code,
+            &entry_path,
+            transpiled.line_map.as_deref(),
+        )
+        .unwrap();
+        assert_eq!(output, "42:cjs");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_integration_browser_condition_uses_browser_field_entry() {
+        let root = unique_temp_path("browser-field", "dir");
+        let pkg_dir = format!("{}/node_modules/iso", root);
+        let _ = std::fs::create_dir_all(&pkg_dir);
+        let entry_path = format!("{}/entry.ts", root);
+
+        std::fs::write(
+            format!("{}/package.json", pkg_dir),
+            r#"{"name":"iso","main":"./node.js","browser":"./browser.js"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            format!("{}/node.js", pkg_dir),
+            "export const platform = 'node';",
+        )
+        .unwrap();
+        std::fs::write(
+            format!("{}/browser.js", pkg_dir),
+            "export const platform = 'browser';",
+        )
+        .unwrap();
+        std::fs::write(
+            &entry_path,
+            "import { platform } from 'iso';\nexport default platform;",
+        )
+        .unwrap();
+
+        let source = std::fs::read_to_string(&entry_path).unwrap();
+        let transpiled = transpiler::transpile(&source).unwrap();
+
+        let output = execute_js_module(
+            &transpiled.code,
+            &entry_path,
+            transpiled.line_map.as_deref(),
+        );
+        assert_eq!(output.unwrap(), "node");
+
+        resolver::set_conditions(vec!["browser".to_string()]);
+        let output = execute_js_module(
+            &transpiled.code,
+            &entry_path,
+            transpiled.line_map.as_deref(),
+        );
+        resolver::set_conditions(Vec::new());
+        assert_eq!(output.unwrap(), "browser");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_integration_trace_resolution_names_export_entry() {
+        let root = unique_temp_path("trace-resolution", "dir");
+        let pkg_dir = format!("{}/node_modules/traced", root);
+        let _ = std::fs::create_dir_all(format!("{}/dist", pkg_dir));
+        let entry_path = format!("{}/entry.ts", root);
+
+        std::fs::write(
+            format!("{}/package.json", pkg_dir),
+            r#"{"name":"traced","exports":{".":{"import":"./dist/index.mjs","require":"./dist/index.cjs"}}}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            format!("{}/dist/index.mjs", pkg_dir),
+            "export const value = 7;",
+        )
+        .unwrap();
+        std::fs::write(
+            &entry_path,
+            "import { value } from 'traced';\nexport default value;",
+        )
+        .unwrap();
+
+        let source = std::fs::read_to_string(&entry_path).unwrap();
+        let transpiled = transpiler::transpile(&source).unwrap();
+
+        resolver::take_trace();
+        resolver::set_trace(true);
+        let output = execute_js_module(
+            &transpiled.code,
+            &entry_path,
+            transpiled.line_map.as_deref(),
+        );
+        resolver::set_trace(false);
+        assert_eq!(output.unwrap(), "7");
+
+        let trace = resolver::take_trace().join("\n");
+        assert!(
+            trace.contains("[resolve] 'traced' from "),
+            "trace: {}",
+            trace
+        );
+        assert!(
+            trace.contains("exports[\".\"] -> ./dist/index.mjs (condition: import)"),
+            "trace: {}",
+            trace
+        );
+        assert!(
+            trace.contains(&format!("=> {}/dist/index.mjs", pkg_dir)),
+            "trace: {}",
+            trace
+        );
+
+        // No tracing without the flag
+        execute_js_module(&transpiled.code, &entry_path, None).unwrap();
+        assert!(resolver::take_trace().is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn test_integration_cjs_require_esm_default_export() {
         let root = unique_temp_path("cjs-require-esm-default", "dir");
@@ -1556,6 +2311,45 @@ mod integration_tests {
         assert!(!logs.contains("timeout"), "logs: {}", logs);
     }
 
+    #[test]
+    fn test_integration_unref_timers_do_not_block_completion() {
+        // A ref'd interval would keep the event loop busy forever
+        let ts = r#"
+            let ticks = 0;
+            const keepAlive = setInterval(() => { ticks++; }, 30000);
+            keepAlive.unref();
+            const idle = setTimeout(() => console.log('unref-fired'), 60000).unref();
+            const restored = setTimeout(() => console.log('ref-fired'), 30000);
+            restored.unref();
+            restored.ref();
+            setTimeout(() => console.log('done:' + ticks + ':' + idle.hasRef() + ':' + restored.hasRef()), 0);
+        "#;
+        let transpiled = transpiler::transpile(ts).unwrap();
+        js_modules::console::clear_logs();
+        let _ = execute_js(&transpiled.code, "<unref>", transpiled.line_map.as_deref()).unwrap();
+        let logs = js_modules::console::get_logs();
+        assert!(logs.contains("ref-fired"), "logs: {}", logs);
+        assert!(logs.contains("done:0:false:true"), "logs: {}", logs);
+        assert!(!logs.contains("unref-fired"), "logs: {}", logs);
+    }
+
+    #[test]
+    fn test_integration_unref_timer_fires_while_ref_timer_pending() {
+        let ts = r#"
+            const order: string[] = [];
+            setTimeout(() => order.push('unref'), 10).unref();
+            setTimeout(() => {
+                order.push('ref');
+                console.log(order.join(','));
+            }, 50);
+        "#;
+        let transpiled = transpiler::transpile(ts).unwrap();
+        js_modules::console::clear_logs();
+        let _ = execute_js(&transpiled.code, "<unref>", transpiled.line_map.as_deref()).unwrap();
+        let logs = js_modules::console::get_logs();
+        assert!(logs.contains("unref,ref"), "logs: {}", logs);
+    }
+
     #[test]
     fn test_integration_runtime_error_reports_mapped_line() {
         let ts = r#"
@@ -9,6 +9,7 @@ use swc_ecma_ast::{Callee, EsVersion, Expr, ExprStmt, FnExpr, ModuleItem, Stmt};
 use swc_ecma_codegen::{text_writer::JsWriter, Config, Emitter};
 use swc_ecma_parser::{lexer::Lexer, EsSyntax, Parser, StringInput, Syntax};
 
+use crate::fetch_limit;
 use crate::http_client;
 use crate::transpiler;
 
@@ -29,10 +30,17 @@ impl Loader for HybridLoader {
             .map_err(|e| rquickjs::Error::new_loading_message(path, format!("{}", e)))?
             .flatten();
 
+        if path.starts_with("node:") {
+            let source = builtin_module_source(ctx, path)
+                .map_err(|e| rquickjs::Error::new_loading_message(path, e))?;
+            return Module::declare(ctx.clone(), path, source);
+        }
+
         let local_path = crate::resolver::file_url_to_path(path);
         // Fetch source code
         let source = if path.starts_with("https://") || path.starts_with("http://") {
             // Fetch from URL using synchronous WASI HTTP
+            let _permit = fetch_limit::Permit::hold(path);
             match http_client::fetch_sync(path) {
                 Ok(response) if response.ok => response.body(),
                 Ok(response) => {
@@ -64,6 +72,7 @@ impl Loader for HybridLoader {
                 })?;
                 format!("export default {};", parsed)
             }
+            Some("css-module") => css_module_source(&source),
             Some(other) => {
                 return Err(rquickjs::Error::new_loading_message(
                     path,
@@ -88,6 +97,124 @@ impl Loader for HybridLoader {
     }
 }
 
+/// Minimal CSS-modules shim: the default export maps every class name used in
+/// a selector to itself, so `styles.button === "button"`.
+fn css_module_source(css: &str) -> String {
+    let classes: serde_json::Map<String, serde_json::Value> = css_module_classes(css)
+        .into_iter()
+        .map(|name| (name.clone(), serde_json::Value::String(name)))
+        .collect();
+    format!("export default {};", serde_json::Value::Object(classes))
+}
+
+/// Class names from selector preludes only, so `url(a.png)` or `.5em` inside
+/// declarations are never mistaken for classes. Comments and strings are skipped.
+fn css_module_classes(css: &str) -> Vec<String> {
+    let mut classes: Vec<String> = Vec::new();
+    let mut prelude = String::new();
+    let mut chars = css.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' | '\'' => {
+                // Keep a placeholder so selector parsing still sees a token
+                prelude.push(' ');
+                let mut escaped = false;
+                for inner in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if inner == '\\' {
+                        escaped = true;
+                    } else if inner == c {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                if !prelude.trim_start().starts_with('@') {
+                    collect_selector_classes(&prelude, &mut classes);
+                }
+                prelude.clear();
+            }
+            ';' | '}' => prelude.clear(),
+            _ => prelude.push(c),
+        }
+    }
+    classes
+}
+
+fn collect_selector_classes(selector: &str, classes: &mut Vec<String>) {
+    let chars: Vec<char> = selector.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let starts_name =
+            |c: char| c.is_ascii_alphabetic() || c == '_' || c == '-' || !c.is_ascii();
+        if chars[i] == '.' && chars.get(i + 1).is_some_and(|&c| starts_name(c)) {
+            let start = i + 1;
+            let mut end = start;
+            while end < chars.len()
+                && (chars[end].is_ascii_alphanumeric()
+                    || chars[end] == '_'
+                    || chars[end] == '-'
+                    || !chars[end].is_ascii())
+            {
+                end += 1;
+            }
+            let name: String = chars[start..end].iter().collect();
+            if !classes.contains(&name) {
+                classes.push(name);
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Build an ES module facade over a builtin registered in `__tsxBuiltinModules`.
+///
+/// The default export is the builtin itself; its identifier-safe own keys are
+/// re-exported by name so `import { test } from 'node:test'` works.
+fn builtin_module_source(ctx: &Ctx<'_>, specifier: &str) -> std::result::Result<String, String> {
+    let quoted = serde_json::to_string(specifier).map_err(|e| e.to_string())?;
+    let keys: Option<Vec<String>> = ctx
+        .eval(format!(
+            "(() => {{ const m = globalThis.__tsxBuiltinModules.get({}); \
+             return m === undefined ? undefined : Object.keys(m); }})()",
+            quoted
+        ))
+        .map_err(|e| format!("{}", e))?;
+    let Some(keys) = keys else {
+        return Err(format!("Cannot find module '{}'", specifier));
+    };
+
+    let mut source = format!(
+        "const __builtin = globalThis.__tsxBuiltinModules.get({});\nexport default __builtin;\n",
+        quoted
+    );
+    for key in keys {
+        if key != "default" && is_identifier(&key) {
+            source.push_str(&format!("export const {0} = __builtin.{0};\n", key));
+        }
+    }
+    Ok(source)
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
 /// Fetch content from a URL using the browser's fetch API.
 ///
 /// This is called from async context.
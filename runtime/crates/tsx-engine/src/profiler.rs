@@ -0,0 +1,151 @@
+//! Coarse sampling profiler for `tsx --sample-profile`.
+//!
+//! QuickJS calls the runtime interrupt handler every few thousand bytecode
+//! operations. The handler can't safely run JS, so it only counts a pending
+//! sample. While sampling is enabled the transpiler puts a call to
+//! [`TICK_FN`] at the start of every function body and loop iteration; the
+//! tick is an ordinary native call, so it can walk the JS stack and charge
+//! the pending samples to the function on top. Sample counts approximate
+//! self time.
+
+use rquickjs::{Ctx, Function};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+/// Global the transpiler's ticks call while sampling is enabled
+pub const TICK_FN: &str = "__tsxProfileTick";
+
+/// Captures the JS stack; its own frame is skipped when attributing samples.
+const SAMPLE_STACK_JS: &str = "globalThis.__tsxSampleStack = function __tsxSampleStack() { return new Error().stack || ''; };";
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static ATTACHED: Cell<bool> = const { Cell::new(false) };
+    static PENDING: Cell<usize> = const { Cell::new(0) };
+    static SAMPLES: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Enable or disable sampling for subsequent executions.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.with(|e| e.set(enabled));
+}
+
+/// Whether sampling is enabled, i.e. whether transpiled code should tick.
+pub fn is_enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Install the tick and stack capture in a context. No-op unless enabled.
+pub fn attach(ctx: &Ctx<'_>) -> rquickjs::Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    ctx.eval::<(), _>(SAMPLE_STACK_JS)?;
+    ctx.globals().set(
+        TICK_FN,
+        Function::new(ctx.clone(), tick)?.with_name(TICK_FN)?,
+    )?;
+    PENDING.with(|p| p.set(0));
+    ATTACHED.with(|a| a.set(true));
+    Ok(())
+}
+
+/// Stops counting samples when an execution finishes, so one run's pending
+/// samples never land on the next.
+pub struct SamplingScope;
+
+impl Drop for SamplingScope {
+    fn drop(&mut self) {
+        ATTACHED.with(|a| a.set(false));
+        PENDING.with(|p| p.set(0));
+    }
+}
+
+/// Called from the interrupt handler: note that a sample is due. Touches
+/// nothing but a counter, since the interrupted code may be mid-operation.
+pub fn sample() {
+    if ATTACHED.with(|a| a.get()) {
+        PENDING.with(|p| p.set(p.get() + 1));
+    }
+}
+
+/// The native side of [`TICK_FN`]: charge pending samples to the caller.
+fn tick(ctx: Ctx<'_>) {
+    let pending = PENDING.with(|p| p.replace(0));
+    if pending == 0 {
+        return;
+    }
+    let stack = ctx
+        .globals()
+        .get::<_, Function>("__tsxSampleStack")
+        .and_then(|f| f.call::<_, String>(()));
+    match stack {
+        Ok(stack) => {
+            let name = top_frame_name(&stack);
+            SAMPLES.with(|s| *s.borrow_mut().entry(name).or_insert(0) += pending);
+        }
+        Err(_) => {
+            // Don't leave a pending exception behind in the caller
+            let _ = ctx.catch();
+        }
+    }
+}
+
+/// Name of the first frame below the sampler's own frames.
+fn top_frame_name(stack: &str) -> String {
+    stack
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.starts_with("at ") && !line.contains("__tsxSampleStack") && !line.contains(TICK_FN)
+        })
+        .map(|line| {
+            let frame = &line[3..];
+            let name = frame.split(" (").next().unwrap_or(frame).trim();
+            if name.is_empty() || name.contains('/') || name.contains(':') {
+                "<anonymous>".to_string()
+            } else {
+                name.to_string()
+            }
+        })
+        .next()
+        .unwrap_or_else(|| "<native>".to_string())
+}
+
+/// Drain recorded samples, most-sampled first (ties by name).
+pub fn take_ranking() -> Vec<(String, usize)> {
+    let samples = SAMPLES.with(|s| std::mem::take(&mut *s.borrow_mut()));
+    let mut ranking: Vec<(String, usize)> = samples.into_iter().collect();
+    ranking.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranking
+}
+
+/// Render the top entries of a ranking as a self-time table.
+pub fn format_ranking(ranking: &[(String, usize)], limit: usize) -> String {
+    let total: usize = ranking.iter().map(|(_, count)| count).sum();
+    let mut out = format!("Sampled profile ({} samples)\n", total);
+    for (name, count) in ranking.iter().take(limit) {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            *count as f64 * 100.0 / total as f64
+        };
+        out.push_str(&format!("  {:>5.1}%  {:>6}  {}\n", percent, count, name));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_frame_skips_sampler_frame() {
+        let stack = "Error\n    at __tsxSampleStack (<eval>:1:60)\n    at __tsxProfileTick (native)\n    at hot (/tmp/a.js:3:9)\n    at <anonymous> (/tmp/a.js:9:1)\n";
+        assert_eq!(top_frame_name(stack), "hot");
+        assert_eq!(
+            top_frame_name("Error\n    at __tsxSampleStack (x)\n    at /tmp/a.js:1:1\n"),
+            "<anonymous>"
+        );
+    }
+}
@@ -3,6 +3,7 @@
 use rquickjs::loader::Resolver;
 use rquickjs::{Ctx, Result};
 use serde_json::Value;
+use std::cell::{Cell, RefCell};
 use std::path::{Component, Path, PathBuf};
 
 #[derive(Clone, Copy)]
@@ -11,6 +12,57 @@ enum ResolveMode {
     Require,
 }
 
+impl ResolveMode {
+    /// Export/import conditions tried, in priority order. User conditions
+    /// (`tsx --conditions`) come before the built-in ones.
+    fn conditions(self) -> Vec<String> {
+        let builtin: &[&str] = match self {
+            ResolveMode::Import => &["import", "node", "default"],
+            ResolveMode::Require => &["require", "node", "default"],
+        };
+        let mut conditions = USER_CONDITIONS.with(|c| c.borrow().clone());
+        for condition in builtin {
+            if !conditions.iter().any(|c| c == condition) {
+                conditions.push(condition.to_string());
+            }
+        }
+        conditions
+    }
+}
+
+thread_local! {
+    static TRACE_ENABLED: Cell<bool> = const { Cell::new(false) };
+    static TRACE_LINES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static USER_CONDITIONS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Set extra export/import conditions (`tsx --conditions`). Including
+/// `browser` also enables the package.json `browser` field.
+pub fn set_conditions(conditions: Vec<String>) {
+    USER_CONDITIONS.with(|c| *c.borrow_mut() = conditions);
+}
+
+fn browser_condition_active() -> bool {
+    USER_CONDITIONS.with(|c| c.borrow().iter().any(|c| c == "browser"))
+}
+
+/// Enable or disable resolution tracing (`tsx --trace-resolution`).
+pub fn set_trace(enabled: bool) {
+    TRACE_ENABLED.with(|t| t.set(enabled));
+}
+
+/// Drain the trace lines recorded since the last call.
+pub fn take_trace() -> Vec<String> {
+    TRACE_LINES.with(|lines| std::mem::take(&mut *lines.borrow_mut()))
+}
+
+/// Record one resolution step; the message is only built when tracing.
+fn trace(message: impl FnOnce() -> String) {
+    if TRACE_ENABLED.with(|t| t.get()) {
+        TRACE_LINES.with(|lines| lines.borrow_mut().push(message()));
+    }
+}
+
 /// Hybrid resolver that routes URL imports to network and relative imports to OPFS.
 pub struct HybridResolver;
 
@@ -26,7 +78,11 @@ impl Resolver for HybridResolver {
 /// 1. Absolute URLs (`https://...`) - pass through unchanged
 /// 2. Root-relative URLs from esm.sh (`/axios@1.13.2/...`) - resolve to esm.sh origin
 /// 3. Relative paths (`./...`, `../...`) - resolve against base path
-/// 4. Bare specifiers (`lodash`) - rewrite to esm.sh URL
+/// 4. `node:` builtins (`node:test`) - pass through for the loader's builtin facade
+/// 5. Bare specifiers (`lodash`) - local node_modules, else rewrite to esm.sh URL
+///
+/// With the `browser` condition, a package.json `browser` field can replace
+/// the resolved file or a bare specifier.
 pub fn resolve(base: &str, specifier: &str) -> String {
     resolve_mode(base, specifier, ResolveMode::Import)
 }
@@ -56,6 +112,20 @@ pub fn file_url_to_path(url: &str) -> Option<String> {
 }
 
 fn resolve_mode(base: &str, specifier: &str, mode: ResolveMode) -> String {
+    trace(|| {
+        format!(
+            "[resolve] '{}' from '{}' (conditions: {})",
+            specifier,
+            base,
+            mode.conditions().join(", ")
+        )
+    });
+    let resolved = browser_remap_file(resolve_steps(base, specifier, mode), mode);
+    trace(|| format!("[resolve]   => {}", resolved));
+    resolved
+}
+
+fn resolve_steps(base: &str, specifier: &str, mode: ResolveMode) -> String {
     // Case A: Absolute URL - pass through
     if specifier.starts_with("https://")
         || specifier.starts_with("http://")
@@ -64,6 +134,11 @@ fn resolve_mode(base: &str, specifier: &str, mode: ResolveMode) -> String {
         return specifier.to_string();
     }
 
+    // Node builtins are served from __tsxBuiltinModules by the loader
+    if specifier.starts_with("node:") {
+        return specifier.to_string();
+    }
+
     // Case B: Root-relative import
     // - For esm.sh, keep resolving to esm.sh origin.
     // - For other HTTP(S) bases, resolve to same origin.
@@ -93,6 +168,12 @@ fn resolve_mode(base: &str, specifier: &str, mode: ResolveMode) -> String {
         if let Some(resolved) = resolve_package_imports(base, specifier, mode) {
             return resolved;
         }
+        trace(|| {
+            format!(
+                "[resolve]   no package.json \"imports\" entry for '{}'",
+                specifier
+            )
+        });
         return specifier.to_string();
     }
 
@@ -105,12 +186,18 @@ fn resolve_mode(base: &str, specifier: &str, mode: ResolveMode) -> String {
         return resolve_local_candidate(&resolved).unwrap_or(resolved);
     }
 
-    // Case D: Bare specifier - resolve in local node_modules first
+    // Case D: Bare specifier - the enclosing package's `browser` field may swap it
+    if let Some(resolved) = browser_remap_specifier(base, specifier, mode) {
+        return resolved;
+    }
+
+    // Case E: Bare specifier - resolve in local node_modules first
     if let Some(resolved) = resolve_node_module_specifier(base, specifier, mode) {
         return resolved;
     }
 
-    // Case E: Bare specifier fallback - rewrite to esm.sh
+    // Case F: Bare specifier fallback - rewrite to esm.sh
+    trace(|| "[resolve]   not found in node_modules, falling back to esm.sh".to_string());
     format!("https://esm.sh/{}", specifier)
 }
 
@@ -274,25 +361,44 @@ fn read_json_file(path: &Path) -> Option<Value> {
     serde_json::from_str::<Value>(&text).ok()
 }
 
-fn select_target_with_conditions(value: &Value, mode: ResolveMode) -> Option<String> {
+/// Select the target of an `exports`/`imports` entry; `field` names the
+/// entry in trace output (e.g. `exports["."]`).
+fn select_target_with_conditions(value: &Value, mode: ResolveMode, field: &str) -> Option<String> {
+    let (target, chain) = select_condition_chain(value, mode)?;
+    trace(|| {
+        if chain.is_empty() {
+            format!("[resolve]   {} -> {}", field, target)
+        } else {
+            format!(
+                "[resolve]   {} -> {} (condition: {})",
+                field,
+                target,
+                chain.join(" > ")
+            )
+        }
+    });
+    Some(target)
+}
+
+/// Pick a target from a (possibly nested) conditions object, along with the
+/// condition keys that led to it, outermost first.
+fn select_condition_chain(value: &Value, mode: ResolveMode) -> Option<(String, Vec<String>)> {
     if let Some(s) = value.as_str() {
-        return Some(s.to_string());
+        return Some((s.to_string(), Vec::new()));
     }
     let map = value.as_object()?;
-    let keys: &[&str] = match mode {
-        ResolveMode::Import => &["import", "node", "default"],
-        ResolveMode::Require => &["require", "node", "default"],
-    };
-    for key in keys {
-        if let Some(v) = map.get(*key) {
-            if let Some(target) = select_target_with_conditions(v, mode) {
-                return Some(target);
+    for key in &mode.conditions() {
+        if let Some(v) = map.get(key.as_str()) {
+            if let Some((target, mut chain)) = select_condition_chain(v, mode) {
+                chain.insert(0, key.to_string());
+                return Some((target, chain));
             }
         }
     }
-    for nested in map.values() {
-        if let Some(target) = select_target_with_conditions(nested, mode) {
-            return Some(target);
+    for (key, nested) in map {
+        if let Some((target, mut chain)) = select_condition_chain(nested, mode) {
+            chain.insert(0, key.clone());
+            return Some((target, chain));
         }
     }
     None
@@ -304,10 +410,9 @@ fn resolve_package_imports(base: &str, specifier: &str, mode: ResolveMode) -> Op
     let pkg_dir = pkg_json_path.parent()?;
     let pkg_json = read_json_file(&pkg_json_path)?;
     let imports = pkg_json.get("imports")?.as_object()?;
-    if let Some(target) = imports
-        .get(specifier)
-        .and_then(|v| select_target_with_conditions(v, mode))
-    {
+    if let Some(target) = imports.get(specifier).and_then(|v| {
+        select_target_with_conditions(v, mode, &format!("imports[\"{}\"]", specifier))
+    }) {
         let abs = if target.starts_with("./") {
             pkg_dir.join(target).to_string_lossy().to_string()
         } else {
@@ -321,7 +426,8 @@ fn resolve_package_imports(base: &str, specifier: &str, mode: ResolveMode) -> Op
             continue;
         }
         let captured = match_wildcard(key, specifier)?;
-        let target_str = select_target_with_conditions(target, mode)?;
+        let target_str =
+            select_target_with_conditions(target, mode, &format!("imports[\"{}\"]", key))?;
         let expanded = target_str.replace('*', &captured);
         let abs = if expanded.starts_with("./") {
             pkg_dir.join(expanded).to_string_lossy().to_string()
@@ -348,7 +454,7 @@ fn resolve_package_exports(
         };
         if let Some(target) = exports
             .get(&key)
-            .and_then(|v| select_target_with_conditions(v, mode))
+            .and_then(|v| select_target_with_conditions(v, mode, &format!("exports[\"{}\"]", key)))
         {
             let abs = package_root.join(target).to_string_lossy().to_string();
             return resolve_local_candidate(&abs).or(Some(normalize_path_string(&abs)));
@@ -358,14 +464,30 @@ fn resolve_package_exports(
                 continue;
             }
             let captured = match_wildcard(pattern, &key)?;
-            let target_str = select_target_with_conditions(target, mode)?;
+            let target_str =
+                select_target_with_conditions(target, mode, &format!("exports[\"{}\"]", pattern))?;
             let expanded = target_str.replace('*', &captured);
             let abs = package_root.join(expanded).to_string_lossy().to_string();
             return resolve_local_candidate(&abs).or(Some(normalize_path_string(&abs)));
         }
     }
 
+    // The string form of `browser` replaces `main`
+    if let Some(browser_main) = pkg_json
+        .get("browser")
+        .and_then(|v| v.as_str())
+        .filter(|_| browser_condition_active())
+    {
+        trace(|| format!("[resolve]   \"browser\" -> {}", browser_main));
+        let abs = package_root
+            .join(browser_main)
+            .to_string_lossy()
+            .to_string();
+        return resolve_local_candidate(&abs).or(Some(normalize_path_string(&abs)));
+    }
+
     if let Some(main) = pkg_json.get("main").and_then(|v| v.as_str()) {
+        trace(|| format!("[resolve]   \"main\" -> {}", main));
         let abs = package_root.join(main).to_string_lossy().to_string();
         if let Some(resolved) = resolve_local_candidate(&abs).or(Some(normalize_path_string(&abs)))
         {
@@ -373,6 +495,7 @@ fn resolve_package_exports(
         }
     }
     if let Some(module) = pkg_json.get("module").and_then(|v| v.as_str()) {
+        trace(|| format!("[resolve]   \"module\" -> {}", module));
         let abs = package_root.join(module).to_string_lossy().to_string();
         if let Some(resolved) = resolve_local_candidate(&abs).or(Some(normalize_path_string(&abs)))
         {
@@ -391,6 +514,7 @@ fn resolve_node_module_specifier(base: &str, specifier: &str, mode: ResolveMode)
     while let Some(current) = dir {
         let package_root = current.join("node_modules").join(package_name);
         if package_root.exists() {
+            trace(|| format!("[resolve]   found package at {}", package_root.display()));
             if let Some(resolved) = resolve_package_exports(&package_root, subpath, mode) {
                 return Some(resolved);
             }
@@ -414,6 +538,85 @@ fn resolve_node_module_specifier(base: &str, specifier: &str, mode: ResolveMode)
     None
 }
 
+fn read_nearest_package_json(start_dir: &Path) -> Option<(PathBuf, Value)> {
+    let pkg_json_path = find_nearest_package_json(start_dir)?;
+    let pkg_json = read_json_file(&pkg_json_path)?;
+    Some((pkg_json_path.parent()?.to_path_buf(), pkg_json))
+}
+
+/// Look up `target` (a file in `package_root`, or a bare specifier) in an
+/// object-form `browser` field. `false` entries (ignore the module) are not
+/// supported and leave the target as is.
+fn browser_replacement(package_root: &Path, pkg_json: &Value, target: &str) -> Option<String> {
+    let map = pkg_json.get("browser")?.as_object()?;
+    for (key, replacement) in map {
+        let Some(replacement) = replacement.as_str() else {
+            continue;
+        };
+        let matched = if key.starts_with("./") || key.starts_with("../") {
+            let key_abs = normalize_path_string(&package_root.join(key).to_string_lossy());
+            target == key_abs || resolve_local_candidate(&key_abs).as_deref() == Some(target)
+        } else {
+            key == target && replacement != key
+        };
+        if matched {
+            trace(|| format!("[resolve]   browser[\"{}\"] -> {}", key, replacement));
+            return Some(replacement.to_string());
+        }
+    }
+    None
+}
+
+/// Resolve a `browser` field replacement from within its package.
+fn resolve_browser_replacement(
+    package_root: &Path,
+    replacement: &str,
+    mode: ResolveMode,
+) -> String {
+    if replacement.starts_with("./") || replacement.starts_with("../") {
+        let abs = package_root.join(replacement).to_string_lossy().to_string();
+        return resolve_local_candidate(&abs).unwrap_or_else(|| normalize_path_string(&abs));
+    }
+    let base = package_root
+        .join("package.json")
+        .to_string_lossy()
+        .to_string();
+    resolve_steps(&base, replacement, mode)
+}
+
+/// With the `browser` condition, swap a resolved local file for its
+/// replacement in the enclosing package's `browser` field.
+fn browser_remap_file(resolved: String, mode: ResolveMode) -> String {
+    if !browser_condition_active() || !resolved.starts_with('/') {
+        return resolved;
+    }
+    let Some((package_root, pkg_json)) = Path::new(&resolved)
+        .parent()
+        .and_then(read_nearest_package_json)
+    else {
+        return resolved;
+    };
+    match browser_replacement(&package_root, &pkg_json, &resolved) {
+        Some(replacement) => resolve_browser_replacement(&package_root, &replacement, mode),
+        None => resolved,
+    }
+}
+
+/// With the `browser` condition, swap a bare specifier imported from a
+/// package whose `browser` field remaps it.
+fn browser_remap_specifier(base: &str, specifier: &str, mode: ResolveMode) -> Option<String> {
+    if !browser_condition_active() {
+        return None;
+    }
+    let (package_root, pkg_json) = read_nearest_package_json(&base_dir_for_local(base)?)?;
+    let replacement = browser_replacement(&package_root, &pkg_json, specifier)?;
+    Some(resolve_browser_replacement(
+        &package_root,
+        &replacement,
+        mode,
+    ))
+}
+
 fn normalize_path_string(path: &str) -> String {
     let mut normalized = PathBuf::new();
     for component in Path::new(path).components() {
@@ -496,6 +699,11 @@ mod tests {
         assert_eq!(resolve("", "lodash"), "https://esm.sh/lodash");
     }
 
+    #[test]
+    fn test_node_builtin_passthrough() {
+        assert_eq!(resolve("/tmp/main.ts", "node:test"), "node:test");
+    }
+
     #[test]
     fn test_relative_import() {
         assert_eq!(resolve("/src/main.ts", "./utils.ts"), "/src/utils.ts");
@@ -830,6 +1038,41 @@ mod tests {
         let _ = std::fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn test_browser_field_object_remaps_files_and_specifiers() {
+        let root = temp_root("browser-field-object");
+        let pkg_root = format!("{}/node_modules/iso", root);
+        let _ = std::fs::create_dir_all(format!("{}/lib", pkg_root));
+        let entry = format!("{}/entry.ts", root);
+
+        std::fs::write(
+            format!("{}/package.json", pkg_root),
+            r#"{"name":"iso","main":"./lib/index.js","browser":{"./lib/net.js":"./lib/net-browser.js","stream":"./lib/stream-shim.js"}}"#,
+        )
+        .unwrap();
+        for file in ["index.js", "net.js", "net-browser.js", "stream-shim.js"] {
+            std::fs::write(format!("{}/lib/{}", pkg_root, file), "export {}").unwrap();
+        }
+        std::fs::write(&entry, "export {}").unwrap();
+        let index = format!("{}/lib/index.js", pkg_root);
+
+        assert_eq!(resolve(&index, "./net"), format!("{}/lib/net.js", pkg_root));
+        assert_eq!(resolve(&index, "stream"), "https://esm.sh/stream");
+
+        set_conditions(vec!["browser".to_string()]);
+        let net = resolve(&index, "./net");
+        let stream = resolve(&index, "stream");
+        let from_entry = resolve(&entry, "stream");
+        set_conditions(Vec::new());
+
+        assert_eq!(net, format!("{}/lib/net-browser.js", pkg_root));
+        assert_eq!(stream, format!("{}/lib/stream-shim.js", pkg_root));
+        // The remap only applies inside the package that declares it
+        assert_eq!(from_entry, "https://esm.sh/stream");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn test_node_modules_exports_exact_match_precedence_over_wildcard() {
         let root = temp_root("exports-exact-vs-wildcard");
@@ -0,0 +1,294 @@
+//! tsx-test command support.
+//!
+//! Discovers test files by glob, runs each through the regular tsx execution
+//! path with the `test`/`expect` harness installed, and summarizes results.
+
+use crate::js_modules::test_harness::{self, TestCaseResult};
+use crate::{js_modules, transpiler};
+use std::path::Path;
+
+/// Patterns used when `tsx-test` is invoked without arguments.
+pub const DEFAULT_TEST_PATTERNS: &[&str] = &["*.test.ts", "*.test.js"];
+
+/// Results for one test file.
+pub struct FileReport {
+    pub path: String,
+    pub results: Vec<TestCaseResult>,
+    pub logs: String,
+    /// Transpile or top-level execution error (counts as a failure)
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.passed).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|r| !r.passed).count() + usize::from(self.error.is_some())
+    }
+}
+
+/// Resolve test file arguments: existing files are taken as-is, anything else is
+/// treated as a glob and matched recursively under `cwd`.
+pub fn discover_test_files(cwd: &str, patterns: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let direct = join_cwd(cwd, pattern);
+        if Path::new(&direct).is_file() {
+            files.push(direct);
+            continue;
+        }
+        walk_matching(Path::new(cwd), cwd, pattern, &mut files);
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn join_cwd(cwd: &str, path: &str) -> String {
+    if path.starts_with('/') || cwd.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", cwd.trim_end_matches('/'), path)
+    }
+}
+
+fn walk_matching(dir: &Path, root: &str, pattern: &str, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if path.is_dir() {
+            if name.starts_with('.') || name == "node_modules" {
+                continue;
+            }
+            walk_matching(&path, root, pattern, out);
+            continue;
+        }
+        let full = path.to_string_lossy().to_string();
+        let relative = full
+            .strip_prefix(root.trim_end_matches('/'))
+            .unwrap_or(&full)
+            .trim_start_matches('/');
+        // Slash-free patterns match the file name at any depth
+        let subject = if pattern.contains('/') {
+            relative
+        } else {
+            name.as_str()
+        };
+        if glob_match(pattern, subject) {
+            out.push(full);
+        }
+    }
+}
+
+/// Minimal glob matcher: `*` (within a segment), `**` (across segments), `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[u8], t: &[u8]) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some(b'*') if p.get(1) == Some(&b'*') => {
+                let rest = p[2..].strip_prefix(b"/").unwrap_or(&p[2..]);
+                (0..=t.len()).any(|i| matches(rest, &t[i..]))
+            }
+            Some(b'*') => {
+                let mut i = 0;
+                loop {
+                    if matches(&p[1..], &t[i..]) {
+                        return true;
+                    }
+                    if i >= t.len() || t[i] == b'/' {
+                        return false;
+                    }
+                    i += 1;
+                }
+            }
+            Some(b'?') => !t.is_empty() && t[0] != b'/' && matches(&p[1..], &t[1..]),
+            Some(c) => t.first() == Some(c) && matches(&p[1..], &t[1..]),
+        }
+    }
+    matches(pattern.as_bytes(), text.as_bytes())
+}
+
+/// Run a single test file with the harness installed.
+pub fn run_test_file(path: &str) -> FileReport {
+    let mut report = FileReport {
+        path: path.to_string(),
+        results: Vec::new(),
+        logs: String::new(),
+        error: None,
+    };
+
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            report.error = Some(format!("{}: {}", path, e));
+            return report;
+        }
+    };
+    let transpiled = match transpiler::transpile(&source) {
+        Ok(t) => t,
+        Err(e) => {
+            report.error = Some(format!("transpile error: {}", e));
+            return report;
+        }
+    };
+
+    js_modules::console::clear_logs();
+    test_harness::take_results();
+    test_harness::set_enabled(true);
+    let exec_result = if transpiled.contains_module_decls {
+        crate::execute_js_module_with_source_map(
+            &transpiled.code,
+            path,
+            transpiled.line_map.as_deref(),
+            transpiled.source_map.as_deref(),
+        )
+    } else {
+        crate::execute_js_with_source_map(
+            &transpiled.code,
+            path,
+            transpiled.line_map.as_deref(),
+            transpiled.source_map.as_deref(),
+        )
+    };
+    test_harness::set_enabled(false);
+
+    report.results = test_harness::take_results();
+    report.logs = js_modules::console::get_logs();
+    if let Err(e) = exec_result {
+        report.error = Some(e);
+    }
+    report
+}
+
+/// Nonzero when any test (or file) failed, or when nothing ran at all.
+pub fn exit_code(reports: &[FileReport]) -> i32 {
+    let failed: usize = reports.iter().map(FileReport::failed).sum();
+    let passed: usize = reports.iter().map(FileReport::passed).sum();
+    if failed > 0 || passed == 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Render per-file results and the overall summary line.
+pub fn format_reports(reports: &[FileReport]) -> String {
+    let mut out = String::new();
+    for report in reports {
+        out.push_str(&report.path);
+        out.push('\n');
+        if !report.logs.is_empty() {
+            for line in report.logs.lines() {
+                out.push_str(&format!("  | {}\n", line));
+            }
+        }
+        for result in &report.results {
+            let mark = if result.passed { "✓" } else { "✗" };
+            out.push_str(&format!(
+                "  {} {} ({:.1}ms)\n",
+                mark, result.name, result.duration_ms
+            ));
+            if let Some(err) = &result.error {
+                out.push_str(&format!("    {}\n", err));
+            }
+        }
+        if let Some(err) = &report.error {
+            out.push_str(&format!("  ✗ {}\n", err));
+        }
+    }
+
+    let passed: usize = reports.iter().map(FileReport::passed).sum();
+    let failed: usize = reports.iter().map(FileReport::failed).sum();
+    out.push_str(&format!(
+        "\nTests: {} passed, {} failed, {} total\n",
+        passed,
+        failed,
+        passed + failed
+    ));
+    out
+}
+
+/// Exit status for `tsx --test`: nonzero when any `node:test` test failed.
+pub fn tap_exit_code(results: &[TestCaseResult]) -> i32 {
+    i32::from(results.iter().any(|r| !r.passed))
+}
+
+/// Render `node:test` results as a TAP version 13 report.
+pub fn format_tap(results: &[TestCaseResult]) -> String {
+    let mut out = String::from("TAP version 13\n");
+    for (i, result) in results.iter().enumerate() {
+        let status = if result.passed { "ok" } else { "not ok" };
+        out.push_str(&format!("{} {} - {}\n", status, i + 1, result.name));
+        out.push_str("  ---\n");
+        out.push_str(&format!("  duration_ms: {:.3}\n", result.duration_ms));
+        if let Some(err) = &result.error {
+            out.push_str(&format!(
+                "  error: {}\n",
+                serde_json::Value::from(err.as_str())
+            ));
+        }
+        out.push_str("  ...\n");
+    }
+    let passed = results.iter().filter(|r| r.passed).count();
+    out.push_str(&format!("1..{}\n", results.len()));
+    out.push_str(&format!("# tests {}\n", results.len()));
+    out.push_str(&format!("# pass {}\n", passed));
+    out.push_str(&format!("# fail {}\n", results.len() - passed));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match_segments() {
+        assert!(glob_match("*.test.ts", "math.test.ts"));
+        assert!(!glob_match("*.test.ts", "math.ts"));
+        assert!(glob_match("src/**/*.test.ts", "src/a/b/math.test.ts"));
+        assert!(glob_match("src/**/*.test.ts", "src/math.test.ts"));
+        assert!(!glob_match("src/*.test.ts", "src/a/math.test.ts"));
+        assert!(glob_match("?.ts", "a.ts"));
+    }
+
+    #[test]
+    fn test_run_test_file_reports_pass_and_fail() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let root = format!("/tmp/tsx-test-runner-{}", nanos);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            format!("{}/math.test.ts", root),
+            "test('adds', () => { expect(1 + 1).toBe(2); });\n\
+             test('broken', async () => { expect(1 + 1).toBe(3); });\n",
+        )
+        .unwrap();
+
+        let files = discover_test_files(&root, &["*.test.ts".to_string()]);
+        assert_eq!(files.len(), 1);
+
+        let reports: Vec<FileReport> = files.iter().map(|f| run_test_file(f)).collect();
+        assert_eq!(reports[0].passed(), 1);
+        assert_eq!(reports[0].failed(), 1);
+
+        let output = format_reports(&reports);
+        assert!(output.contains("✓ adds"), "output: {}", output);
+        assert!(output.contains("✗ broken"), "output: {}", output);
+        assert!(output.contains("Expected 2 to be 3"), "output: {}", output);
+        assert!(
+            output.contains("Tests: 1 passed, 1 failed, 2 total"),
+            "output: {}",
+            output
+        );
+        assert_ne!(exit_code(&reports), 0);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
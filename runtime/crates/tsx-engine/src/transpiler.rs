@@ -4,6 +4,7 @@
 //! 1. TypeScript type stripping
 //! 2. AwaitLastExpr - wrap last expression with await
 //! 3. WrapInAsyncIife - wrap all code in async IIFE with error handling
+//! 4. ProfileTicks - sampling profiler ticks, only under `--sample-profile`
 //!
 //! FUTURE IMPROVEMENTS:
 //! - Add source maps for accurate error line mapping
@@ -17,15 +18,15 @@ use swc_common::{
     GLOBALS,
 };
 use swc_ecma_ast::{
-    ArrowExpr, AwaitExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, EsVersion,
-    Expr, ExprOrSpread, ExprStmt, Ident, IdentName, MemberExpr, MemberProp, Module, ModuleItem,
-    ParenExpr, Pat, Program, Stmt, ThrowStmt,
+    ArrowExpr, AwaitExpr, BindingIdent, BlockStmt, BlockStmtOrExpr, CallExpr, Callee, EmptyStmt,
+    EsVersion, Expr, ExprOrSpread, ExprStmt, Function, Ident, IdentName, MemberExpr, MemberProp,
+    Module, ModuleItem, ParenExpr, Pat, Program, Stmt, ThrowStmt,
 };
 use swc_ecma_codegen::{text_writer::JsWriter, Config, Emitter};
 use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax, TsSyntax};
 use swc_ecma_transforms_base::{fixer::fixer, resolver};
 use swc_ecma_transforms_typescript::strip;
-use swc_ecma_visit::VisitMut;
+use swc_ecma_visit::{VisitMut, VisitMutWith};
 
 // ============================================================================
 // TRANSPILE RESULT
@@ -221,6 +222,86 @@ impl WrapInAsyncIife {
     }
 }
 
+/// AST Transform: call the sampling profiler's tick on entry to every function
+/// and at the top of every loop iteration
+///
+/// Transforms:
+///   function f() { for (;;) work(); }
+/// Into:
+///   function f() { __tsxProfileTick(); for (;;) { __tsxProfileTick(); work(); } }
+///
+/// The interrupt handler can only note that a sample is due; the tick is
+/// where the profiler is allowed to walk the stack (see `profiler`).
+struct ProfileTicks;
+
+impl ProfileTicks {
+    fn tick() -> Stmt {
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: Callee::Expr(Box::new(Expr::Ident(Ident::new(
+                    crate::profiler::TICK_FN.into(),
+                    DUMMY_SP,
+                    Default::default(),
+                )))),
+                args: vec![],
+                type_args: None,
+                ctxt: Default::default(),
+            })),
+        })
+    }
+
+    /// Insert a tick after the body's directive prologue ("use strict")
+    fn prepend(body: &mut BlockStmt) {
+        let directives = body
+            .stmts
+            .iter()
+            .take_while(|stmt| match stmt {
+                Stmt::Expr(ExprStmt { expr, .. }) => {
+                    matches!(&**expr, Expr::Lit(swc_ecma_ast::Lit::Str(_)))
+                }
+                _ => false,
+            })
+            .count();
+        body.stmts.insert(directives, Self::tick());
+    }
+}
+
+impl VisitMut for ProfileTicks {
+    fn visit_mut_function(&mut self, function: &mut Function) {
+        function.visit_mut_children_with(self);
+        if let Some(body) = &mut function.body {
+            Self::prepend(body);
+        }
+    }
+
+    fn visit_mut_arrow_expr(&mut self, arrow: &mut ArrowExpr) {
+        arrow.visit_mut_children_with(self);
+        if let BlockStmtOrExpr::BlockStmt(body) = &mut *arrow.body {
+            Self::prepend(body);
+        }
+    }
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+        let body = match stmt {
+            Stmt::For(s) => &mut s.body,
+            Stmt::ForIn(s) => &mut s.body,
+            Stmt::ForOf(s) => &mut s.body,
+            Stmt::While(s) => &mut s.body,
+            Stmt::DoWhile(s) => &mut s.body,
+            _ => return,
+        };
+        let inner = mem::replace(&mut **body, Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+        **body = Stmt::Block(BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![Self::tick(), inner],
+            ctxt: Default::default(),
+        });
+    }
+}
+
 // ============================================================================
 // PUBLIC API
 // ============================================================================
@@ -231,6 +312,7 @@ impl WrapInAsyncIife {
 /// 1. TypeScript type stripping
 /// 2. AwaitLastExpr - wrap last expression with await
 /// 3. WrapInAsyncIife - wrap in async IIFE
+/// 4. ProfileTicks - while the sampling profiler is enabled
 ///
 /// Returns generated code and placeholder for future source map.
 pub fn transpile(ts_code: &str) -> Result<TranspileResult, String> {
@@ -289,6 +371,11 @@ fn transpile_inner(ts_code: &str, wrap_in_iife: bool) -> Result<TranspileResult,
     let mut pass = strip(unresolved_mark, top_level_mark);
     pass.process(&mut program);
 
+    // Before the IIFE wrapper, so the wrapper itself doesn't tick
+    if crate::profiler::is_enabled() {
+        ProfileTicks.visit_mut_program(&mut program);
+    }
+
     if wrap_in_iife && !contains_module_decls {
         // Transform 2: Await last expression
         AwaitLastExpr.visit_mut_program(&mut program);
@@ -477,6 +564,31 @@ mod tests {
         assert!(!code.contains("catch"), "Got: {}", code);
     }
 
+    #[test]
+    fn test_profile_ticks_only_while_sampling() {
+        let ts = "function f(n: number) { 'use strict'; while (n--) work(); }";
+        let code = transpile_code_only(ts).unwrap();
+        assert!(!code.contains("__tsxProfileTick"), "Got: {}", code);
+
+        crate::profiler::set_enabled(true);
+        let code = transpile_code_only(ts);
+        crate::profiler::set_enabled(false);
+        let code = code.unwrap();
+        // One on entry, one per loop iteration
+        assert_eq!(
+            code.matches("__tsxProfileTick()").count(),
+            2,
+            "Got: {}",
+            code
+        );
+        // The directive prologue stays first
+        assert!(
+            code.find("use strict").unwrap() < code.find("__tsxProfileTick").unwrap(),
+            "Got: {}",
+            code
+        );
+    }
+
     #[test]
     fn test_parse_error_shows_context() {
         let ts = "const x = {"; // Missing closing brace
@@ -10,7 +10,7 @@ use crate::bindings::{
     ExecEnv, HostLazyProcess, LazyProcess, LoaderExecEnv, ModuleLoaderHost, ShellCommandHost,
     TerminalDimensions, TerminalSize, TerminalSizeHost,
 };
-use crate::HostState;
+use crate::{module_loader, HostState};
 
 // Use the remapped wasi:io types from wasmtime_wasi
 use wasmtime_wasi::p2::bindings::io::poll::Pollable;
@@ -87,12 +87,10 @@ impl ModuleLoaderHost for HostState {
         args: Vec<String>,
         env: LoaderExecEnv,
     ) -> Resource<LazyProcess> {
-        // Spawn using module_loader and get the process ID
-        let _id = self
+        let id = self
             .module_loader
             .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        // Return a stub resource - real implementation needs resource table integration
-        Resource::new_own(0)
+        Resource::new_own(id)
     }
 
     fn spawn_interactive(
@@ -101,12 +99,16 @@ impl ModuleLoaderHost for HostState {
         command: String,
         args: Vec<String>,
         env: LoaderExecEnv,
-        _size: TerminalSize,
+        size: TerminalSize,
     ) -> Resource<LazyProcess> {
-        let _id = self
+        let size = module_loader::TerminalSize {
+            cols: size.cols,
+            rows: size.rows,
+        };
+        let id = self
             .module_loader
-            .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        Resource::new_own(0)
+            .spawn_interactive(&module, &command, args, env.cwd, env.vars, size);
+        Resource::new_own(id)
     }
 
     fn is_interactive_command(&mut self, command: String) -> bool {
@@ -123,13 +125,10 @@ impl ModuleLoaderHost for HostState {
         args: Vec<String>,
         env: LoaderExecEnv,
     ) -> Resource<LazyProcess> {
-        // Spawn as regular lazy command
-        if let Some(module) = self.module_loader.get_lazy_module(&command) {
-            let _id = self
-                .module_loader
-                .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        }
-        Resource::new_own(0)
+        let id = self
+            .module_loader
+            .spawn_worker_command(&command, args, env.cwd, env.vars);
+        Resource::new_own(id)
     }
 }
 
@@ -137,47 +136,89 @@ impl ModuleLoaderHost for HostState {
 
 impl HostLazyProcess for HostState {
     fn get_ready_pollable(&mut self, _self_: Resource<LazyProcess>) -> Resource<Pollable> {
-        // Stub - would need to create a proper pollable
-        Resource::new_own(0)
+        module_loader::ready_pollable(&mut self.table)
     }
 
-    fn is_ready(&mut self, _self_: Resource<LazyProcess>) -> bool {
-        false
+    fn is_ready(&mut self, self_: Resource<LazyProcess>) -> bool {
+        self.module_loader
+            .process(self_.rep())
+            .is_some_and(|p| p.is_ready())
     }
 
-    fn write_stdin(&mut self, _self_: Resource<LazyProcess>, _data: Vec<u8>) -> u64 {
-        0
+    fn write_stdin(&mut self, self_: Resource<LazyProcess>, data: Vec<u8>) -> u64 {
+        self.module_loader
+            .process(self_.rep())
+            .map_or(0, |p| p.write_stdin(&data))
     }
 
-    fn close_stdin(&mut self, _self_: Resource<LazyProcess>) {}
+    fn close_stdin(&mut self, self_: Resource<LazyProcess>) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.close_stdin();
+        }
+    }
 
-    fn read_stdout(&mut self, _self_: Resource<LazyProcess>, _max_bytes: u64) -> Vec<u8> {
-        vec![]
+    fn read_stdout(&mut self, self_: Resource<LazyProcess>, max_bytes: u64) -> Vec<u8> {
+        self.module_loader
+            .process(self_.rep())
+            .map(|p| p.read_stdout(max_bytes))
+            .unwrap_or_default()
     }
 
-    fn read_stderr(&mut self, _self_: Resource<LazyProcess>, _max_bytes: u64) -> Vec<u8> {
-        vec![]
+    fn read_stderr(&mut self, self_: Resource<LazyProcess>, max_bytes: u64) -> Vec<u8> {
+        self.module_loader
+            .process(self_.rep())
+            .map(|p| p.read_stderr(max_bytes))
+            .unwrap_or_default()
     }
 
-    fn try_wait(&mut self, _self_: Resource<LazyProcess>) -> Option<i32> {
-        None
+    fn try_wait(&mut self, self_: Resource<LazyProcess>) -> Option<i32> {
+        // An unknown handle has nothing left to wait for
+        self.module_loader
+            .process(self_.rep())
+            .map_or(Some(-1), |p| p.try_wait())
     }
 
-    fn get_terminal_size(&mut self, _self_: Resource<LazyProcess>) -> TerminalSize {
-        TerminalSize { cols: 80, rows: 24 }
+    fn get_terminal_size(&mut self, self_: Resource<LazyProcess>) -> TerminalSize {
+        let size = self
+            .module_loader
+            .process(self_.rep())
+            .map(|p| p.get_terminal_size())
+            .unwrap_or_default();
+        TerminalSize {
+            cols: size.cols,
+            rows: size.rows,
+        }
     }
 
-    fn set_terminal_size(&mut self, _self_: Resource<LazyProcess>, _size: TerminalSize) {}
+    fn set_terminal_size(&mut self, self_: Resource<LazyProcess>, size: TerminalSize) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.set_terminal_size(module_loader::TerminalSize {
+                cols: size.cols,
+                rows: size.rows,
+            });
+        }
+    }
 
-    fn set_raw_mode(&mut self, _self_: Resource<LazyProcess>, _enabled: bool) {}
+    fn set_raw_mode(&mut self, self_: Resource<LazyProcess>, enabled: bool) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.set_raw_mode(enabled);
+        }
+    }
 
-    fn is_raw_mode(&mut self, _self_: Resource<LazyProcess>) -> bool {
-        false
+    fn is_raw_mode(&mut self, self_: Resource<LazyProcess>) -> bool {
+        self.module_loader
+            .process(self_.rep())
+            .is_some_and(|p| p.is_raw_mode())
     }
 
-    fn send_signal(&mut self, _self_: Resource<LazyProcess>, _signum: u8) {}
+    fn send_signal(&mut self, self_: Resource<LazyProcess>, signum: u8) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.send_signal(signum);
+        }
+    }
 
-    fn drop(&mut self, _rep: Resource<LazyProcess>) -> wasmtime::Result<()> {
+    fn drop(&mut self, rep: Resource<LazyProcess>) -> wasmtime::Result<()> {
+        self.module_loader.remove_process(rep.rep());
         Ok(())
     }
 }
@@ -4,8 +4,15 @@
 //!
 //! ## Modes
 //!
-//! **TUI mode** (default): Run the interactive terminal UI.
+//! **TUI mode** (default): Run the interactive terminal UI. Host directories
+//! are mounted with `--dir HOST[:GUEST]` (default: the current directory at
+//! `/`), and `--tmp` adds a fresh temporary directory at `/tmp`.
 //! **MCP stdio mode** (`--mcp-stdio`): Run as a headless MCP server over stdin/stdout.
+//! **Shell mode** (`shell -- CMD`, or `sh` with a script on stdin): Run the
+//! shell non-interactively with the host's args, environment and stdio.
+//! `--dir` and `--tmp` apply here too, with `--work-dir` at `/` by default.
+//!
+//! Mounts are visible to lazy modules (vim, sqlite3, tsx) as well.
 //!
 //! ## Build Modes
 //!
@@ -24,21 +31,21 @@
 mod host_traits;
 mod http_router;
 
-use wasmtime_runner::{bindings, mcp_stdio, module_loader};
+use wasmtime_runner::{bindings, mcp_stdio, module_loader, shell_cli};
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use tempfile::TempDir;
 use wasmtime::component::{Component, Linker, ResourceTable};
 use wasmtime::{Config, Engine, Store};
-use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
 
-use module_loader::ModuleLoader;
+use module_loader::{ModuleLoader, Mount};
 
 // Embedded WASM components (default)
 #[cfg(feature = "embed-wasm")]
-#[allow(dead_code)] // EDTUI/SQLITE/TSX will be used for lazy-loading
 mod embedded {
     /// TUI WASM component
     pub static TUI_WASM: &[u8] = include_bytes!(concat!(
@@ -75,12 +82,30 @@ mod embedded {
 #[derive(Parser, Debug)]
 #[command(name = "wasm-tui")]
 #[command(about = "Run the TUI WASM component natively, or serve MCP over stdio")]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Run as a headless MCP server (JSON-RPC over stdin/stdout)
     #[arg(long)]
     mcp_stdio: bool,
 
+    /// Mount a host directory into the TUI or shell sandbox as HOST[:GUEST]
+    /// (repeatable; default: the current directory at /)
+    #[arg(long = "dir", value_name = "HOST[:GUEST]", value_parser = parse_mount)]
+    dirs: Vec<Mount>,
+
+    /// Mount a fresh temporary directory at /tmp in the TUI or shell sandbox
+    #[arg(long)]
+    tmp: bool,
+
+    /// Start the TUI without restoring the previous session
+    #[arg(long)]
+    fresh: bool,
+
     /// Working directory for MCP stdio mode (default: ~/.edge-agent/sandbox)
+    /// and shell mode (default: current directory)
     #[arg(long)]
     work_dir: Option<PathBuf>,
 
@@ -100,6 +125,33 @@ struct Args {
     modules_dir: PathBuf,
 }
 
+/// Subcommands; without one the TUI is launched
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the shell: `shell -- CMD...`, or a script piped on stdin
+    #[command(visible_alias = "sh")]
+    Shell {
+        /// A command line as one word, or a command and its arguments
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+}
+
+/// Parse `HOST[:GUEST]`; without a guest path the directory keeps its host path
+fn parse_mount(spec: &str) -> Result<Mount, String> {
+    let (host, guest) = match spec.rsplit_once(':') {
+        Some((host, guest)) if !host.is_empty() && guest.starts_with('/') => (host, guest),
+        _ => (spec, spec),
+    };
+    if host.is_empty() {
+        return Err("empty host path".to_string());
+    }
+    Ok(Mount {
+        host: PathBuf::from(host),
+        guest: guest.to_string(),
+    })
+}
+
 /// Host state for the WASM components
 pub struct HostState {
     wasi: WasiCtx,
@@ -143,6 +195,35 @@ async fn run(args: Args) -> Result<()> {
 
     let engine = Engine::new(&config)?;
 
+    // Shell mode — run a command line or script and exit with its status
+    if let Some(Command::Shell { command }) = &args.command {
+        let work_dir = match &args.work_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::current_dir()?,
+        };
+        let shell_args = shell_cli::shell_args(command)?;
+        let (mounts, tmp_dir) = sandbox_mounts(&args, work_dir)?;
+
+        #[cfg(feature = "embed-wasm")]
+        let modules_dir = PathBuf::from(".");
+        #[cfg(feature = "no-embed")]
+        let modules_dir = args.modules_dir.clone();
+        let loader = lazy_module_loader(modules_dir).with_mounts(mounts.clone());
+
+        #[cfg(feature = "embed-wasm")]
+        let mcp_bytes = embedded::MCP_WASM;
+
+        #[cfg(feature = "no-embed")]
+        let mcp_bytes = &std::fs::read(&args.mcp_wasm)
+            .with_context(|| format!("Failed to read MCP WASM: {:?}", args.mcp_wasm))?;
+
+        let exit_code =
+            shell_cli::run_shell(&engine, mcp_bytes, &mounts, loader, shell_args).await?;
+        // process::exit skips destructors, so remove the temp dir first
+        drop(tmp_dir);
+        std::process::exit(exit_code);
+    }
+
     // MCP stdio mode — headless server
     if args.mcp_stdio {
         // Resolve work directory
@@ -181,6 +262,21 @@ async fn run(args: Args) -> Result<()> {
         (tui, mcp)
     };
 
+    // Resolve mounts before the (slow) compile so a bad path fails fast
+    let (mounts, tmp_dir) = sandbox_mounts(&args, std::env::current_dir()?)?;
+
+    // Build WASI context
+    let mut wasi = WasiCtxBuilder::new();
+    wasi.inherit_stdio().inherit_env();
+    if args.fresh {
+        wasi.args(&["web-agent-tui", "--fresh"]);
+    }
+    for mount in &mounts {
+        wasi.preopened_dir(&mount.host, &mount.guest, DirPerms::all(), FilePerms::all())
+            .with_context(|| format!("Failed to mount {:?} at {}", mount.host, mount.guest))?;
+    }
+    let wasi = wasi.build();
+
     let tui_component = Component::new(&engine, &tui_bytes)?;
 
     // Create linker and add WASI bindings
@@ -196,9 +292,6 @@ async fn run(args: Args) -> Result<()> {
     bindings::add_module_loader_to_linker::<_, HasSelf<_>>(&mut linker, |s| s)?;
     http_router::add_to_linker(&mut linker)?;
 
-    // Build WASI context
-    let wasi = WasiCtxBuilder::new().inherit_stdio().inherit_env().build();
-
     // Get modules directory
     #[cfg(feature = "embed-wasm")]
     let modules_dir = PathBuf::from(".");
@@ -209,7 +302,7 @@ async fn run(args: Args) -> Result<()> {
         wasi,
         http: WasiHttpCtx::new(),
         table: ResourceTable::new(),
-        module_loader: ModuleLoader::new(modules_dir),
+        module_loader: lazy_module_loader(modules_dir).with_mounts(mounts),
     };
 
     let mut store = Store::new(&engine, state);
@@ -222,9 +315,56 @@ async fn run(args: Args) -> Result<()> {
 
     let (exit_code,) = run_func.call_async(&mut store, ()).await?;
 
+    // process::exit skips destructors, so remove the temp dir first
+    drop(tmp_dir);
     std::process::exit(exit_code);
 }
 
+/// Resolve `--dir` and `--tmp` into mounts, with `root` at `/` unless a
+/// `--dir` is mounted there. The temporary directory lives as long as the
+/// returned guard.
+fn sandbox_mounts(args: &Args, root: PathBuf) -> Result<(Vec<Mount>, Option<TempDir>)> {
+    let mut mounts = args.dirs.clone();
+    if let Some(bad) = mounts.iter().find(|m| !m.host.is_dir()) {
+        anyhow::bail!("Cannot mount {:?}: not a directory", bad.host);
+    }
+    if !mounts.iter().any(|m| m.guest == "/") {
+        mounts.push(Mount {
+            host: root,
+            guest: "/".to_string(),
+        });
+    }
+    let tmp_dir = if args.tmp {
+        let dir = tempfile::Builder::new()
+            .prefix("wasm-tui-")
+            .tempdir()
+            .context("Failed to create temp directory")?;
+        mounts.push(Mount {
+            host: dir.path().to_path_buf(),
+            guest: "/tmp".to_string(),
+        });
+        Some(dir)
+    } else {
+        None
+    };
+    Ok((mounts, tmp_dir))
+}
+
+/// Module loader for lazy commands, serving the bundled modules
+#[cfg(feature = "embed-wasm")]
+fn lazy_module_loader(modules_dir: PathBuf) -> ModuleLoader {
+    ModuleLoader::new(modules_dir)
+        .with_embedded("edtui_module.wasm", embedded::EDTUI_WASM)
+        .with_embedded("sqlite_module.wasm", embedded::SQLITE_WASM)
+        .with_embedded("tsx_engine.wasm", embedded::TSX_WASM)
+}
+
+/// Module loader for lazy commands, reading modules from `modules_dir`
+#[cfg(feature = "no-embed")]
+fn lazy_module_loader(modules_dir: PathBuf) -> ModuleLoader {
+    ModuleLoader::new(modules_dir)
+}
+
 /// Resolve `~/.edge-agent/sandbox` to an absolute path
 fn dirs_or_default(path: &str) -> PathBuf {
     if path.starts_with("~/") {
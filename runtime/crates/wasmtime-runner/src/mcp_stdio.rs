@@ -11,10 +11,11 @@ use anyhow::Result;
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full};
 use serde_json::Value;
+use std::collections::VecDeque;
 use std::path::PathBuf;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::oneshot;
-use wasmtime::component::{Component, Linker, ResourceTable};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot};
+use wasmtime::component::{Component, Linker, Resource, ResourceTable};
 use wasmtime::{Engine, Store};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 use wasmtime_wasi_http::bindings::http::types::Scheme;
@@ -51,13 +52,11 @@ impl WasiHttpView for McpHostState {
 }
 
 // Implement the module-loader Host trait for McpHostState
-// (same implementation as TUI's HostState but on this type)
+// (same implementation as TUI's HostState but on this type).
+// Commands whose module isn't available fall through to the shell's built-ins.
 impl crate::bindings::ModuleLoaderHost for McpHostState {
-    fn get_lazy_module(&mut self, _command: String) -> Option<String> {
-        // In the MCP test harness, always return None so commands fall through
-        // to built-in implementations. The lazy module system is for browser/native
-        // environments where separate WASM modules handle certain commands (sqlite3, etc.).
-        None
+    fn get_lazy_module(&mut self, command: String) -> Option<String> {
+        self.module_loader.get_lazy_module(&command)
     }
 
     fn spawn_lazy_command(
@@ -66,11 +65,11 @@ impl crate::bindings::ModuleLoaderHost for McpHostState {
         command: String,
         args: Vec<String>,
         env: crate::bindings::LoaderExecEnv,
-    ) -> wasmtime::component::Resource<crate::bindings::LazyProcess> {
-        let _id = self
+    ) -> Resource<crate::bindings::LazyProcess> {
+        let id = self
             .module_loader
             .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        wasmtime::component::Resource::new_own(0)
+        Resource::new_own(id)
     }
 
     fn spawn_interactive(
@@ -79,12 +78,16 @@ impl crate::bindings::ModuleLoaderHost for McpHostState {
         command: String,
         args: Vec<String>,
         env: crate::bindings::LoaderExecEnv,
-        _size: crate::bindings::TerminalSize,
-    ) -> wasmtime::component::Resource<crate::bindings::LazyProcess> {
-        let _id = self
+        size: crate::bindings::TerminalSize,
+    ) -> Resource<crate::bindings::LazyProcess> {
+        let size = crate::module_loader::TerminalSize {
+            cols: size.cols,
+            rows: size.rows,
+        };
+        let id = self
             .module_loader
-            .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        wasmtime::component::Resource::new_own(0)
+            .spawn_interactive(&module, &command, args, env.cwd, env.vars, size);
+        Resource::new_own(id)
     }
 
     fn is_interactive_command(&mut self, command: String) -> bool {
@@ -100,104 +103,123 @@ impl crate::bindings::ModuleLoaderHost for McpHostState {
         command: String,
         args: Vec<String>,
         env: crate::bindings::LoaderExecEnv,
-    ) -> wasmtime::component::Resource<crate::bindings::LazyProcess> {
-        if let Some(module) = self.module_loader.get_lazy_module(&command) {
-            let _id = self
-                .module_loader
-                .spawn_lazy_command(&module, &command, args, env.cwd, env.vars);
-        }
-        wasmtime::component::Resource::new_own(0)
+    ) -> Resource<crate::bindings::LazyProcess> {
+        let id = self
+            .module_loader
+            .spawn_worker_command(&command, args, env.cwd, env.vars);
+        Resource::new_own(id)
     }
 }
 
-// LazyProcess resource stubs for MCP mode
+// LazyProcess resource, backed by the module loader's process table
 impl crate::bindings::HostLazyProcess for McpHostState {
     fn get_ready_pollable(
         &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-    ) -> wasmtime::component::Resource<wasmtime_wasi::p2::bindings::io::poll::Pollable> {
-        wasmtime::component::Resource::new_own(0)
+        _self_: Resource<crate::bindings::LazyProcess>,
+    ) -> Resource<wasmtime_wasi::p2::bindings::io::poll::Pollable> {
+        crate::module_loader::ready_pollable(&mut self.table)
     }
-    fn is_ready(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-    ) -> bool {
-        false
+    fn is_ready(&mut self, self_: Resource<crate::bindings::LazyProcess>) -> bool {
+        self.module_loader
+            .process(self_.rep())
+            .is_some_and(|p| p.is_ready())
     }
-    fn write_stdin(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _data: Vec<u8>,
-    ) -> u64 {
-        0
+    fn write_stdin(&mut self, self_: Resource<crate::bindings::LazyProcess>, data: Vec<u8>) -> u64 {
+        self.module_loader
+            .process(self_.rep())
+            .map_or(0, |p| p.write_stdin(&data))
     }
-    fn close_stdin(&mut self, _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>) {
+    fn close_stdin(&mut self, self_: Resource<crate::bindings::LazyProcess>) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.close_stdin();
+        }
     }
     fn read_stdout(
         &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _max_bytes: u64,
+        self_: Resource<crate::bindings::LazyProcess>,
+        max_bytes: u64,
     ) -> Vec<u8> {
-        vec![]
+        self.module_loader
+            .process(self_.rep())
+            .map(|p| p.read_stdout(max_bytes))
+            .unwrap_or_default()
     }
     fn read_stderr(
         &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _max_bytes: u64,
+        self_: Resource<crate::bindings::LazyProcess>,
+        max_bytes: u64,
     ) -> Vec<u8> {
-        vec![]
+        self.module_loader
+            .process(self_.rep())
+            .map(|p| p.read_stderr(max_bytes))
+            .unwrap_or_default()
     }
-    fn try_wait(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-    ) -> Option<i32> {
-        None
+    fn try_wait(&mut self, self_: Resource<crate::bindings::LazyProcess>) -> Option<i32> {
+        self.module_loader
+            .process(self_.rep())
+            .map_or(Some(-1), |p| p.try_wait())
     }
     fn get_terminal_size(
         &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
+        self_: Resource<crate::bindings::LazyProcess>,
     ) -> crate::bindings::TerminalSize {
-        crate::bindings::TerminalSize { cols: 80, rows: 24 }
+        let size = self
+            .module_loader
+            .process(self_.rep())
+            .map(|p| p.get_terminal_size())
+            .unwrap_or_default();
+        crate::bindings::TerminalSize {
+            cols: size.cols,
+            rows: size.rows,
+        }
     }
     fn set_terminal_size(
         &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _size: crate::bindings::TerminalSize,
+        self_: Resource<crate::bindings::LazyProcess>,
+        size: crate::bindings::TerminalSize,
     ) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.set_terminal_size(crate::module_loader::TerminalSize {
+                cols: size.cols,
+                rows: size.rows,
+            });
+        }
     }
-    fn set_raw_mode(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _enabled: bool,
-    ) {
+    fn set_raw_mode(&mut self, self_: Resource<crate::bindings::LazyProcess>, enabled: bool) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.set_raw_mode(enabled);
+        }
     }
-    fn is_raw_mode(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-    ) -> bool {
-        false
+    fn is_raw_mode(&mut self, self_: Resource<crate::bindings::LazyProcess>) -> bool {
+        self.module_loader
+            .process(self_.rep())
+            .is_some_and(|p| p.is_raw_mode())
     }
-    fn send_signal(
-        &mut self,
-        _self_: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-        _signum: u8,
-    ) {
+    fn send_signal(&mut self, self_: Resource<crate::bindings::LazyProcess>, signum: u8) {
+        if let Some(p) = self.module_loader.process(self_.rep()) {
+            p.send_signal(signum);
+        }
     }
-    fn drop(
-        &mut self,
-        _rep: wasmtime::component::Resource<crate::bindings::LazyProcess>,
-    ) -> wasmtime::Result<()> {
+    fn drop(&mut self, rep: Resource<crate::bindings::LazyProcess>) -> wasmtime::Result<()> {
+        self.module_loader.remove_process(rep.rep());
         Ok(())
     }
 }
 
-/// Create the Linker and ProxyPre for MCP component invocation.
-///
-/// This is the expensive setup step. The returned `ProxyPre` can be
-/// reused across many `call_mcp_component` invocations.
-pub fn setup_mcp_proxy(engine: &Engine, mcp_bytes: &[u8]) -> Result<ProxyPre<McpHostState>> {
-    let mcp_component = Component::new(engine, mcp_bytes)?;
+impl McpHostState {
+    /// Host state around a WASI context and the loader serving lazy modules
+    pub fn new(wasi: WasiCtx, module_loader: ModuleLoader) -> Self {
+        Self {
+            wasi,
+            http: WasiHttpCtx::new(),
+            table: ResourceTable::new(),
+            module_loader,
+        }
+    }
+}
 
+/// Create a Linker with everything the MCP component imports.
+pub fn mcp_linker(engine: &Engine) -> Result<Linker<McpHostState>> {
     let mut linker: Linker<McpHostState> = Linker::new(engine);
     wasmtime_wasi::p2::add_to_linker_async(&mut linker)?;
     wasmtime_wasi_http::add_only_http_to_linker_async(&mut linker)?;
@@ -205,6 +227,17 @@ pub fn setup_mcp_proxy(engine: &Engine, mcp_bytes: &[u8]) -> Result<ProxyPre<Mcp
     use wasmtime::component::HasSelf;
     crate::bindings::add_module_loader_to_linker::<_, HasSelf<_>>(&mut linker, |s| s)?;
 
+    Ok(linker)
+}
+
+/// Create the Linker and ProxyPre for MCP component invocation.
+///
+/// This is the expensive setup step. The returned `ProxyPre` can be
+/// reused across many `call_mcp_component` invocations.
+pub fn setup_mcp_proxy(engine: &Engine, mcp_bytes: &[u8]) -> Result<ProxyPre<McpHostState>> {
+    let mcp_component = Component::new(engine, mcp_bytes)?;
+    let linker = mcp_linker(engine)?;
+
     let instance_pre = linker.instantiate_pre(&mcp_component)?;
     let proxy_pre = ProxyPre::new(instance_pre)?;
     Ok(proxy_pre)
@@ -219,20 +252,57 @@ pub async fn run_mcp_stdio(engine: &Engine, mcp_bytes: &[u8], work_dir: PathBuf)
 
     log("MCP stdio server ready");
 
-    // Read JSON-RPC from stdin, one line per request
-    let stdin = tokio::io::stdin();
-    let mut reader = BufReader::new(stdin);
-    let mut stdout = tokio::io::stdout();
-    let mut line = String::new();
-
-    loop {
-        line.clear();
-        let bytes_read = reader.read_line(&mut line).await?;
+    serve_mcp(
+        engine,
+        &proxy_pre,
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+        &work_dir,
+    )
+    .await
+}
 
-        if bytes_read == 0 {
-            // EOF — stdin closed
-            break;
+/// Answer newline-delimited JSON-RPC from `input` on `output`.
+///
+/// Requests are handled one at a time and in order, but input is still read
+/// while a call runs so `notifications/cancelled` can reach it. Each request
+/// gets a fresh component instance, which can't see the running call's
+/// cancellation token, so the host drops the call instead: the guest stops
+/// at its next host call (sleep, I/O, waiting on a lazy module) and, as MCP
+/// asks, the cancelled request gets no response.
+pub async fn serve_mcp<R, W>(
+    engine: &Engine,
+    proxy_pre: &ProxyPre<McpHostState>,
+    input: R,
+    mut output: W,
+    work_dir: &PathBuf,
+) -> Result<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    // Read JSON-RPC from input, one line per message
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(input).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if line_tx.send(line).is_err() {
+                break;
+            }
         }
+    });
+    // Messages that arrived while a call was running
+    let mut queued: VecDeque<String> = VecDeque::new();
+
+    loop {
+        let line = match queued.pop_front() {
+            Some(line) => line,
+            // None once the input is closed
+            None => match line_rx.recv().await {
+                Some(line) => line,
+                None => break,
+            },
+        };
 
         let trimmed = line.trim();
         if trimmed.is_empty() {
@@ -251,16 +321,14 @@ pub async fn run_mcp_stdio(engine: &Engine, mcp_bytes: &[u8], work_dir: PathBuf)
                     },
                     "id": null
                 });
-                let mut out = serde_json::to_string(&error_response)?;
-                out.push('\n');
-                stdout.write_all(out.as_bytes()).await?;
-                stdout.flush().await?;
+                write_message(&mut output, &serde_json::to_string(&error_response)?).await?;
                 continue;
             }
         };
 
         // Check for notifications (no id = no response expected)
-        let is_notification = json.get("id").is_none();
+        let id = json.get("id").cloned();
+        let is_notification = id.is_none();
         let method = json
             .get("method")
             .and_then(|m| m.as_str())
@@ -269,22 +337,41 @@ pub async fn run_mcp_stdio(engine: &Engine, mcp_bytes: &[u8], work_dir: PathBuf)
 
         log(&format!("→ {}", method));
 
-        // Route the request through the WASM component
-        match call_mcp_component(engine, &proxy_pre, trimmed, &work_dir).await {
-            Ok(response) => {
+        // Only a running call can be cancelled; this one has already been answered
+        if method == "notifications/cancelled" {
+            continue;
+        }
+
+        // Route the request through the WASM component, watching for its cancellation
+        let call = call_mcp_component(engine, proxy_pre, trimmed, work_dir);
+        tokio::pin!(call);
+        let result = loop {
+            tokio::select! {
+                result = &mut call => break Some(result),
+                Some(next) = line_rx.recv() => {
+                    if !is_notification && cancelled_request(&next) == id {
+                        break None;
+                    }
+                    queued.push_back(next);
+                }
+            }
+        };
+
+        match result {
+            None => log(&format!("Cancelled {}", method)),
+            Some(Ok(response)) => {
                 if !is_notification && !response.is_empty() {
-                    let mut out = response;
-                    if !out.ends_with('\n') {
-                        out.push('\n');
+                    // Progress arrives as an SSE stream ending in the response;
+                    // each event becomes its own line
+                    let messages = sse_data(&response).unwrap_or_else(|| vec![response.trim_end()]);
+                    for message in messages {
+                        write_message(&mut output, message).await?;
                     }
-                    stdout.write_all(out.as_bytes()).await?;
-                    stdout.flush().await?;
                 }
             }
-            Err(e) => {
+            Some(Err(e)) => {
                 log(&format!("Error processing {}: {}", method, e));
                 if !is_notification {
-                    let id = json.get("id").cloned().unwrap_or(Value::Null);
                     let error_response = serde_json::json!({
                         "jsonrpc": "2.0",
                         "error": {
@@ -293,10 +380,7 @@ pub async fn run_mcp_stdio(engine: &Engine, mcp_bytes: &[u8], work_dir: PathBuf)
                         },
                         "id": id
                     });
-                    let mut out = serde_json::to_string(&error_response)?;
-                    out.push('\n');
-                    stdout.write_all(out.as_bytes()).await?;
-                    stdout.flush().await?;
+                    write_message(&mut output, &serde_json::to_string(&error_response)?).await?;
                 }
             }
         }
@@ -305,6 +389,23 @@ pub async fn run_mcp_stdio(engine: &Engine, mcp_bytes: &[u8], work_dir: PathBuf)
     Ok(())
 }
 
+/// The request id a `notifications/cancelled` line refers to
+fn cancelled_request(line: &str) -> Option<Value> {
+    let json: Value = serde_json::from_str(line.trim()).ok()?;
+    if json.get("method")?.as_str()? != "notifications/cancelled" {
+        return None;
+    }
+    json.get("params")?.get("requestId").cloned()
+}
+
+/// Write one JSON-RPC message as a line and flush it
+async fn write_message<W: AsyncWrite + Unpin>(output: &mut W, message: &str) -> Result<()> {
+    output.write_all(message.as_bytes()).await?;
+    output.write_all(b"\n").await?;
+    output.flush().await?;
+    Ok(())
+}
+
 /// Send a single JSON-RPC request through the MCP WASM component.
 ///
 /// Creates a fresh Store + instance for each request (clean state),
@@ -328,14 +429,8 @@ pub async fn call_mcp_component(
         )?
         .build();
 
-    let state = McpHostState {
-        wasi,
-        http: WasiHttpCtx::new(),
-        table: ResourceTable::new(),
-        module_loader: ModuleLoader::new(work_dir.clone()),
-    };
-
-    let mut store = Store::new(engine, state);
+    let module_loader = ModuleLoader::new(work_dir.clone()).with_sandbox(work_dir.clone());
+    let mut store = Store::new(engine, McpHostState::new(wasi, module_loader));
 
     // Build the HTTP request wrapping the JSON-RPC body.
     // Map Full<Bytes> error type from Infallible to hyper::Error so it satisfies
@@ -347,6 +442,7 @@ pub async fn call_mcp_component(
         .method(hyper::Method::POST)
         .uri("http://localhost/mcp")
         .header("content-type", "application/json")
+        .header("accept", "application/json, text/event-stream")
         .body(body)
         .map_err(|e| anyhow::anyhow!("Failed to build HTTP request: {e}"))?;
 
@@ -372,6 +468,8 @@ pub async fn call_mcp_component(
             .await?;
         Ok::<_, wasmtime::Error>(())
     });
+    // Stop the instance if this call is dropped (cancelled) before it finishes
+    let _abort = AbortOnDrop(task.abort_handle());
 
     // Wait for the response from the oneshot channel
     let resp = match receiver.await {
@@ -409,6 +507,29 @@ pub async fn call_mcp_component(
     Ok(body_str)
 }
 
+/// Aborts a spawned task when dropped
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// The `data:` payloads of an SSE response body, in order, or `None` when the
+/// body is plain JSON.
+fn sse_data(body: &str) -> Option<Vec<&str>> {
+    if !body.starts_with("event:") && !body.starts_with("data:") {
+        return None;
+    }
+    Some(
+        body.lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim)
+            .collect(),
+    )
+}
+
 /// Log to stderr (stdout is reserved for JSON-RPC responses)
 fn log(msg: &str) {
     eprintln!("[mcp-stdio] {}", msg);
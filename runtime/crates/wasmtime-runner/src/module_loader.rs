@@ -2,13 +2,122 @@
 //!
 //! Provides `mcp:module-loader/loader` interface for lazy-loading WASM modules.
 //! Implements the lazy-process resource for subprocess-like I/O with WASM modules.
+//!
+//! Satellite modules (edtui, sqlite, tsx) export `shell:unix/command`. A lazy
+//! process instantiates its module from embedded bytes or `modules_dir` and
+//! calls `run` on a thread of its own, so the shell can stream stdio to it
+//! the same way it does with the browser's module loader.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use bytes::Bytes;
 use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread::JoinHandle;
+use wasmtime::component::{
+    Component, HasSelf, Instance, InstancePre, Linker, Resource, ResourceTable, TypedFunc,
+};
+use wasmtime::{AsContextMut, Config, Engine, Store, Trap, UpdateDeadline};
+use wasmtime_wasi::p2::bindings::io::poll::Pollable as PollableResource;
+use wasmtime_wasi::p2::bindings::io::streams::{InputStream, OutputStream};
+use wasmtime_wasi::p2::pipe::MemoryInputPipe;
+use wasmtime_wasi::p2::{Pollable, StreamError, StreamResult};
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
+use wasmtime_wasi_http::{WasiHttpCtx, WasiHttpView};
+
+use crate::bindings::{ExecEnv, TerminalDimensions, TerminalSizeHost};
+
+/// A host directory preopened at a guest path
+#[derive(Clone, Debug)]
+pub struct Mount {
+    pub host: PathBuf,
+    pub guest: String,
+}
+
+/// Preopen `mounts` in `wasi`. The root mount is preopened as `.`, like the
+/// shell's work directory.
+pub fn preopen_mounts(wasi: &mut WasiCtxBuilder, mounts: &[Mount]) -> Result<()> {
+    for mount in mounts {
+        let guest = if mount.guest == "/" {
+            "."
+        } else {
+            &mount.guest
+        };
+        wasi.preopened_dir(&mount.host, guest, DirPerms::all(), FilePerms::all())
+            .with_context(|| format!("Failed to mount {:?} at {}", mount.host, mount.guest))?;
+    }
+    Ok(())
+}
+
+/// Interface satellite modules (and the runtime) export `run` from
+pub const COMMAND_INTERFACE: &str = "shell:unix/command@0.1.0";
+
+/// Bytes an output stream accepts per write
+const WRITE_BUDGET: usize = 64 * 1024;
+
+/// Engine shared by all module instances. Epoch interruption lets a signal
+/// stop a running module: `send_signal` bumps the epoch, and each store's
+/// deadline callback checks whether the signal was meant for it.
+static MODULE_ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.wasm_component_model(true);
+    config.epoch_interruption(true);
+    Engine::new(&config).expect("Failed to create module engine")
+});
+
+/// Compiled and linked modules, so each is compiled once
+static MODULE_CACHE: LazyLock<Mutex<HashMap<ModuleKey, InstancePre<ModuleState>>>> =
+    LazyLock::new(Default::default);
+
+/// Where a module's code came from. Loaders with different modules
+/// directories or embedded bytes must not share a compiled module just
+/// because the file names match.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum ModuleKey {
+    /// Address and length of bytes bundled into the binary
+    Embedded(usize, usize),
+    /// Canonical path of a module file
+    File(PathBuf),
+}
+
+/// Parameters of `shell:unix/command#run`
+pub type CommandRunParams = (
+    String,
+    Vec<String>,
+    ExecEnv,
+    Resource<InputStream>,
+    Resource<OutputStream>,
+    Resource<OutputStream>,
+);
+
+/// Look up `run` in a component's `shell:unix/command` export
+pub fn command_run_func(
+    instance: &Instance,
+    mut store: impl AsContextMut,
+) -> Result<TypedFunc<CommandRunParams, (i32,)>> {
+    let interface = instance
+        .get_export_index(&mut store, None, COMMAND_INTERFACE)
+        .with_context(|| format!("Component does not export {COMMAND_INTERFACE}"))?;
+    let run = instance
+        .get_export_index(&mut store, Some(&interface), "run")
+        .with_context(|| format!("{COMMAND_INTERFACE} has no run function"))?;
+    Ok(instance.get_typed_func(&mut store, &run)?)
+}
+
+/// A pollable that is ready immediately: modules load synchronously on spawn
+pub fn ready_pollable(table: &mut ResourceTable) -> Resource<PollableResource> {
+    let loaded = table.push(Loaded).expect("resource table full");
+    wasmtime_wasi::p2::subscribe(table, loaded).expect("resource table full")
+}
+
+struct Loaded;
+
+#[async_trait::async_trait]
+impl Pollable for Loaded {
+    async fn ready(&mut self) {}
+}
 
 /// Terminal dimensions
 #[derive(Clone, Copy, Debug, Default)]
@@ -17,145 +126,282 @@ pub struct TerminalSize {
     pub rows: u32,
 }
 
+/// Store state for a satellite module instance
+struct ModuleState {
+    wasi: WasiCtx,
+    http: WasiHttpCtx,
+    table: ResourceTable,
+}
+
+impl WasiView for ModuleState {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+impl WasiHttpView for ModuleState {
+    fn ctx(&mut self) -> &mut WasiHttpCtx {
+        &mut self.http
+    }
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl TerminalSizeHost for ModuleState {
+    fn get_terminal_size(&mut self) -> TerminalDimensions {
+        let (cols, rows) = terminal_size::terminal_size()
+            .map(|(w, h)| (w.0 as u32, h.0 as u32))
+            .unwrap_or((80, 24));
+        TerminalDimensions { cols, rows }
+    }
+}
+
+/// Everything needed to call a module's `run`
+struct ModuleRun {
+    pre: InstancePre<ModuleState>,
+    command: String,
+    args: Vec<String>,
+    env: ExecEnv,
+    /// Directories preopened in the module
+    mounts: Vec<Mount>,
+    /// Signal sent to the process, 0 if none
+    signal: Arc<AtomicU8>,
+}
+
+impl ModuleRun {
+    /// Instantiate the module and call its `run` to completion
+    fn call(self, stdin: InputStream, stdout: OutputStream, stderr: OutputStream) -> Result<i32> {
+        let mut wasi = WasiCtxBuilder::new();
+        wasi.envs(&self.env.vars);
+        preopen_mounts(&mut wasi, &self.mounts)?;
+
+        let state = ModuleState {
+            wasi: wasi.build(),
+            http: WasiHttpCtx::new(),
+            table: ResourceTable::new(),
+        };
+        let mut store = Store::new(&MODULE_ENGINE, state);
+        // Trap at the next epoch bump after a signal; other bumps are for
+        // other processes, so just wait for the next one
+        let signal = self.signal.clone();
+        store.set_epoch_deadline(1);
+        store.epoch_deadline_callback(move |_| match signal.load(Ordering::SeqCst) {
+            0 => Ok(UpdateDeadline::Continue(1)),
+            _ => Err(Trap::Interrupt.into()),
+        });
+        let instance = self.pre.instantiate(&mut store)?;
+        let run = command_run_func(&instance, &mut store)?;
+
+        let table = &mut store.data_mut().table;
+        let stdin = table.push(stdin)?;
+        let stdout = table.push(stdout)?;
+        let stderr = table.push(stderr)?;
+
+        let params = (self.command, self.args, self.env, stdin, stdout, stderr);
+        match run.call(&mut store, params) {
+            Ok((code,)) => Ok(code),
+            // A module calling exit() unwinds with its status
+            Err(e) => match e.downcast_ref::<I32Exit>() {
+                Some(exit) => Ok(exit.0),
+                None => match self.signal.load(Ordering::SeqCst) {
+                    // Killed by a signal: exit like a shell reports it
+                    0 => Err(e.into()),
+                    signum => Ok(128 + i32::from(signum)),
+                },
+            },
+        }
+    }
+}
+
+/// Output a module writes and the shell drains while it runs
+#[derive(Clone, Default)]
+struct OutputBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl OutputBuffer {
+    fn drain(&self, max_bytes: u64) -> Vec<u8> {
+        let mut buf = self.0.lock().unwrap();
+        let n = std::cmp::min(max_bytes as usize, buf.len());
+        buf.drain(..n).collect()
+    }
+}
+
+impl wasmtime_wasi::p2::OutputStream for OutputBuffer {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        self.0.lock().unwrap().extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        Ok(WRITE_BUDGET)
+    }
+}
+
+#[async_trait::async_trait]
+impl Pollable for OutputBuffer {
+    async fn ready(&mut self) {}
+}
+
+/// The host terminal, handed to interactive modules and the shell
+#[derive(Clone, Copy)]
+pub enum Terminal {
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+impl wasmtime_wasi::p2::InputStream for Terminal {
+    fn read(&mut self, size: usize) -> StreamResult<Bytes> {
+        let mut buf = vec![0u8; std::cmp::min(size, WRITE_BUDGET)];
+        match std::io::stdin().read(&mut buf) {
+            Ok(0) => Err(StreamError::Closed),
+            Ok(n) => {
+                buf.truncate(n);
+                Ok(buf.into())
+            }
+            Err(e) => Err(StreamError::LastOperationFailed(e.into())),
+        }
+    }
+}
+
+impl wasmtime_wasi::p2::OutputStream for Terminal {
+    fn write(&mut self, bytes: Bytes) -> StreamResult<()> {
+        let result = match self {
+            Terminal::Stderr => std::io::stderr().write_all(&bytes),
+            _ => std::io::stdout().write_all(&bytes),
+        };
+        result.map_err(|e| StreamError::LastOperationFailed(e.into()))
+    }
+
+    fn flush(&mut self) -> StreamResult<()> {
+        let result = match self {
+            Terminal::Stderr => std::io::stderr().flush(),
+            _ => std::io::stdout().flush(),
+        };
+        result.map_err(|e| StreamError::LastOperationFailed(e.into()))
+    }
+
+    fn check_write(&mut self) -> StreamResult<usize> {
+        Ok(WRITE_BUDGET)
+    }
+}
+
+#[async_trait::async_trait]
+impl Pollable for Terminal {
+    async fn ready(&mut self) {}
+}
+
 /// A lazy-loaded process handle
-/// For now, we spawn native processes as a fallback for commands like vim/sqlite3
+///
+/// Batch commands collect stdin until it is closed and then start; interactive
+/// commands start right away on the host terminal.
 pub struct LazyProcess {
-    /// Native child process (fallback mode)
-    child: Option<Child>,
+    /// Run waiting for stdin to be closed
+    pending: Option<ModuleRun>,
+    /// Stdin written while the run is pending
+    stdin_buf: Vec<u8>,
+    /// Thread calling the module's `run`
+    thread: Option<JoinHandle<Result<i32>>>,
+    /// Shared with the run, which traps once it is set
+    signal: Arc<AtomicU8>,
+    stdout: OutputBuffer,
+    stderr: OutputBuffer,
+    /// Host-side errors, reported on stderr
+    stderr_buf: Vec<u8>,
     /// Terminal dimensions
     terminal_size: TerminalSize,
     /// Raw mode state
     raw_mode: bool,
-    /// Stdout buffer
-    stdout_buf: Vec<u8>,
-    /// Stderr buffer
-    stderr_buf: Vec<u8>,
     /// Exit code (if finished)
     exit_code: Option<i32>,
 }
 
 impl LazyProcess {
-    /// Create a new lazy process from a native command
-    pub fn spawn_native(
-        command: &str,
-        args: &[String],
-        cwd: &str,
-        env: &[(String, String)],
-    ) -> Result<Self> {
-        let mut cmd = Command::new(command);
-        cmd.args(args)
-            .current_dir(cwd)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        for (key, value) in env {
-            cmd.env(key, value);
-        }
-
-        let child = cmd.spawn()?;
-
-        Ok(Self {
-            child: Some(child),
+    fn new(run: ModuleRun) -> Self {
+        Self {
+            signal: run.signal.clone(),
+            pending: Some(run),
+            stdin_buf: Vec::new(),
+            thread: None,
+            stdout: OutputBuffer::default(),
+            stderr: OutputBuffer::default(),
+            stderr_buf: Vec::new(),
             terminal_size: TerminalSize::default(),
             raw_mode: false,
-            stdout_buf: Vec::new(),
-            stderr_buf: Vec::new(),
             exit_code: None,
-        })
+        }
     }
 
-    /// Create a stub process that immediately exits (for unimplemented commands)
+    /// Create a stub process that immediately exits (for unavailable modules)
     pub fn stub(message: &str) -> Self {
         Self {
-            child: None,
+            pending: None,
+            stdin_buf: Vec::new(),
+            thread: None,
+            signal: Arc::default(),
+            stdout: OutputBuffer::default(),
+            stderr: OutputBuffer::default(),
+            stderr_buf: format!("{}\n", message).into_bytes(),
             terminal_size: TerminalSize::default(),
             raw_mode: false,
-            stdout_buf: Vec::new(),
-            stderr_buf: format!("{}\n", message).into_bytes(),
             exit_code: Some(1),
         }
     }
 
-    pub fn is_ready(&mut self) -> bool {
-        if self.exit_code.is_some() {
-            return true;
-        }
-        // Try to check if child has exited
-        if let Some(ref mut child) = self.child {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    self.exit_code = Some(status.code().unwrap_or(-1));
-                    true
-                }
-                Ok(None) => false,
-                Err(_) => true,
-            }
-        } else {
-            true
+    /// Start the pending run on its own thread
+    fn start(&mut self, stdin: InputStream, stdout: OutputStream, stderr: OutputStream) {
+        if let Some(run) = self.pending.take() {
+            self.thread = Some(std::thread::spawn(move || run.call(stdin, stdout, stderr)));
         }
     }
 
+    /// Start on the host terminal instead of buffered stdio
+    fn start_interactive(&mut self) {
+        self.start(
+            Box::new(Terminal::Stdin),
+            Box::new(Terminal::Stdout),
+            Box::new(Terminal::Stderr),
+        );
+    }
+
+    /// Modules are loaded before the process is created, so it is always ready
+    pub fn is_ready(&mut self) -> bool {
+        true
+    }
+
     pub fn write_stdin(&mut self, data: &[u8]) -> u64 {
-        if let Some(ref mut child) = self.child {
-            if let Some(ref mut stdin) = child.stdin {
-                match stdin.write(data) {
-                    Ok(n) => return n as u64,
-                    Err(_) => return 0,
-                }
-            }
+        if self.pending.is_none() {
+            return 0;
         }
-        0
+        self.stdin_buf.extend_from_slice(data);
+        data.len() as u64
     }
 
     pub fn close_stdin(&mut self) {
-        if let Some(ref mut child) = self.child {
-            child.stdin.take();
-        }
+        let stdin = MemoryInputPipe::new(std::mem::take(&mut self.stdin_buf));
+        let stdout = self.stdout.clone();
+        let stderr = self.stderr.clone();
+        self.start(Box::new(stdin), Box::new(stdout), Box::new(stderr));
     }
 
     pub fn read_stdout(&mut self, max_bytes: u64) -> Vec<u8> {
-        // First drain any buffered data
-        if !self.stdout_buf.is_empty() {
-            let n = std::cmp::min(max_bytes as usize, self.stdout_buf.len());
-            return self.stdout_buf.drain(..n).collect();
-        }
-
-        if let Some(ref mut child) = self.child {
-            if let Some(ref mut stdout) = child.stdout {
-                let mut buf = vec![0u8; max_bytes as usize];
-                match stdout.read(&mut buf) {
-                    Ok(n) => {
-                        buf.truncate(n);
-                        return buf;
-                    }
-                    Err(_) => return Vec::new(),
-                }
-            }
-        }
-        Vec::new()
+        self.stdout.drain(max_bytes)
     }
 
     pub fn read_stderr(&mut self, max_bytes: u64) -> Vec<u8> {
-        // First drain any buffered data
+        // Host errors first, then whatever the module wrote
         if !self.stderr_buf.is_empty() {
             let n = std::cmp::min(max_bytes as usize, self.stderr_buf.len());
             return self.stderr_buf.drain(..n).collect();
         }
-
-        if let Some(ref mut child) = self.child {
-            if let Some(ref mut stderr) = child.stderr {
-                let mut buf = vec![0u8; max_bytes as usize];
-                match stderr.read(&mut buf) {
-                    Ok(n) => {
-                        buf.truncate(n);
-                        return buf;
-                    }
-                    Err(_) => return Vec::new(),
-                }
-            }
-        }
-        Vec::new()
+        self.stderr.drain(max_bytes)
     }
 
     pub fn try_wait(&mut self) -> Option<i32> {
@@ -163,17 +409,22 @@ impl LazyProcess {
             return self.exit_code;
         }
 
-        if let Some(ref mut child) = self.child {
-            match child.try_wait() {
-                Ok(Some(status)) => {
-                    self.exit_code = Some(status.code().unwrap_or(-1));
-                    self.exit_code
-                }
-                _ => None,
-            }
-        } else {
-            self.exit_code
+        if !self.thread.as_ref().is_some_and(|t| t.is_finished()) {
+            return None;
         }
+        let code = match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(Ok(code))) => code,
+            Some(Ok(Err(e))) => {
+                self.stderr_buf.extend(format!("{:#}\n", e).into_bytes());
+                1
+            }
+            _ => {
+                self.stderr_buf.extend(b"module panicked\n");
+                1
+            }
+        };
+        self.exit_code = Some(code);
+        self.exit_code
     }
 
     pub fn get_terminal_size(&self) -> TerminalSize {
@@ -192,31 +443,34 @@ impl LazyProcess {
         self.raw_mode
     }
 
+    /// Stop the process with `signum`. A running module traps the next time
+    /// it executes wasm; one blocked in a host call stops once that returns.
     pub fn send_signal(&mut self, signum: u8) {
-        #[cfg(unix)]
-        if let Some(ref child) = self.child {
-            unsafe {
-                libc::kill(child.id() as i32, signum as i32);
-            }
+        if signum == 0 || self.exit_code.is_some() {
+            return;
         }
-        #[cfg(not(unix))]
-        {
-            let _ = signum;
-            // On non-Unix, we can only kill
-            if let Some(ref mut child) = self.child {
-                let _ = child.kill();
-            }
+        if self.pending.take().is_some() {
+            // Never started: nothing to interrupt
+            self.exit_code = Some(128 + i32::from(signum));
+            return;
         }
+        self.signal.store(signum, Ordering::SeqCst);
+        MODULE_ENGINE.increment_epoch();
     }
 }
 
 /// Registry of lazy-loadable modules
 pub struct ModuleLoader {
     modules_dir: PathBuf,
+    /// Module bytes bundled into the binary, by file name
+    embedded: HashMap<&'static str, &'static [u8]>,
+    /// Directories preopened in each module instance
+    mounts: Vec<Mount>,
     /// Command -> (module_name, is_interactive)
     registry: HashMap<&'static str, (&'static str, bool)>,
-    /// Shared process table
-    processes: Arc<Mutex<Vec<LazyProcess>>>,
+    /// Live processes by resource handle
+    processes: HashMap<u32, LazyProcess>,
+    next_id: u32,
 }
 
 impl ModuleLoader {
@@ -233,17 +487,45 @@ impl ModuleLoader {
         // tsx-engine
         registry.insert("tsx", ("tsx_engine.wasm", false));
         registry.insert("tsc", ("tsx_engine.wasm", false));
+        registry.insert("tsx-test", ("tsx_engine.wasm", false));
 
         Self {
             modules_dir,
+            embedded: HashMap::new(),
+            mounts: Vec::new(),
             registry,
-            processes: Arc::new(Mutex::new(Vec::new())),
+            processes: HashMap::new(),
+            next_id: 0,
         }
     }
 
-    /// Get the module name for a command (None if not a lazy command)
+    /// Serve `module` from bytes bundled into the binary
+    pub fn with_embedded(mut self, module: &'static str, bytes: &'static [u8]) -> Self {
+        self.embedded.insert(module, bytes);
+        self
+    }
+
+    /// Preopen `dir` as the root of every module instance
+    pub fn with_sandbox(self, dir: PathBuf) -> Self {
+        self.with_mounts(vec![Mount {
+            host: dir,
+            guest: "/".to_string(),
+        }])
+    }
+
+    /// Preopen `mounts` in every module instance
+    pub fn with_mounts(mut self, mounts: Vec<Mount>) -> Self {
+        self.mounts = mounts;
+        self
+    }
+
+    /// Get the module name for a command (None if not a lazy command, or if
+    /// its module isn't available so the shell falls back to its built-ins)
     pub fn get_lazy_module(&self, command: &str) -> Option<String> {
-        self.registry.get(command).map(|(m, _)| m.to_string())
+        self.registry
+            .get(command)
+            .filter(|(m, _)| self.embedded.contains_key(m) || self.module_path(m).is_file())
+            .map(|(m, _)| m.to_string())
     }
 
     /// Check if a command is interactive (needs terminal handoff)
@@ -252,41 +534,126 @@ impl ModuleLoader {
     }
 
     /// Get the path to a module WASM file
-    #[allow(dead_code)]
     pub fn module_path(&self, module: &str) -> PathBuf {
         self.modules_dir.join(module)
     }
 
-    /// Spawn a lazy command (for now, stub or native fallback)
-    pub fn spawn_lazy_command(
+    /// Compile and link a module, or reuse it from the cache
+    fn load(&self, module: &str) -> Result<InstancePre<ModuleState>> {
+        let key = match self.embedded.get(module) {
+            Some(bytes) => ModuleKey::Embedded(bytes.as_ptr() as usize, bytes.len()),
+            None => ModuleKey::File(self.module_path(module).canonicalize()?),
+        };
+        let mut cache = MODULE_CACHE.lock().unwrap();
+        if let Some(pre) = cache.get(&key) {
+            return Ok(pre.clone());
+        }
+
+        let component = match &key {
+            ModuleKey::Embedded(..) => Component::new(&MODULE_ENGINE, self.embedded[module])?,
+            ModuleKey::File(path) => Component::from_file(&MODULE_ENGINE, path)?,
+        };
+
+        let mut linker: Linker<ModuleState> = Linker::new(&MODULE_ENGINE);
+        wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+        wasmtime_wasi_http::add_only_http_to_linker_sync(&mut linker)?;
+        crate::bindings::add_terminal_size_to_linker::<_, HasSelf<_>>(&mut linker, |s| s)?;
+        // Host APIs the native runner doesn't provide (ios:bridge) trap if called
+        linker.define_unknown_imports_as_traps(&component)?;
+
+        let pre = linker.instantiate_pre(&component)?;
+        cache.insert(key, pre.clone());
+        Ok(pre)
+    }
+
+    /// Create a process for `command`, or a stub reporting why it can't run
+    fn create_process(
         &self,
         module: &str,
         command: &str,
         args: Vec<String>,
         cwd: String,
         env: Vec<(String, String)>,
-    ) -> usize {
-        // For now, try to spawn the native command as a fallback
-        // TODO: Actually load and run the WASM module
-        let process = match LazyProcess::spawn_native(command, &args, &cwd, &env) {
-            Ok(p) => p,
-            Err(_) => LazyProcess::stub(&format!(
-                "Command '{}' (module: {}) not available in native mode",
-                command, module
+    ) -> LazyProcess {
+        match self.load(module) {
+            Ok(pre) => LazyProcess::new(ModuleRun {
+                pre,
+                command: command.to_string(),
+                args,
+                env: ExecEnv { cwd, vars: env },
+                mounts: self.mounts.clone(),
+                signal: Arc::default(),
+            }),
+            Err(e) => LazyProcess::stub(&format!(
+                "{}: failed to load module {}: {:#}",
+                command, module, e
             )),
-        };
+        }
+    }
 
-        let mut processes = self.processes.lock().unwrap();
-        let id = processes.len();
-        processes.push(process);
+    fn insert(&mut self, process: LazyProcess) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.processes.insert(id, process);
         id
     }
 
-    pub fn get_process_mut(
-        &self,
-        _id: usize,
-    ) -> Option<std::sync::MutexGuard<'_, Vec<LazyProcess>>> {
-        Some(self.processes.lock().unwrap())
+    /// Spawn a batch command; it runs once stdin is closed
+    pub fn spawn_lazy_command(
+        &mut self,
+        module: &str,
+        command: &str,
+        args: Vec<String>,
+        cwd: String,
+        env: Vec<(String, String)>,
+    ) -> u32 {
+        let process = self.create_process(module, command, args, cwd, env);
+        self.insert(process)
+    }
+
+    /// Spawn an interactive command attached to the host terminal
+    pub fn spawn_interactive(
+        &mut self,
+        module: &str,
+        command: &str,
+        args: Vec<String>,
+        cwd: String,
+        env: Vec<(String, String)>,
+        size: TerminalSize,
+    ) -> u32 {
+        let mut process = self.create_process(module, command, args, cwd, env);
+        process.set_terminal_size(size);
+        process.set_raw_mode(true);
+        process.start_interactive();
+        self.insert(process)
+    }
+
+    /// Spawn a command by name; there are no workers natively, so this is
+    /// a batch spawn for lazy commands and a stub for anything else
+    pub fn spawn_worker_command(
+        &mut self,
+        command: &str,
+        args: Vec<String>,
+        cwd: String,
+        env: Vec<(String, String)>,
+    ) -> u32 {
+        match self.get_lazy_module(command) {
+            Some(module) => self.spawn_lazy_command(&module, command, args, cwd, env),
+            None => self.insert(LazyProcess::stub(&format!(
+                "{}: not available as a worker command in native mode",
+                command
+            ))),
+        }
+    }
+
+    pub fn process(&mut self, id: u32) -> Option<&mut LazyProcess> {
+        self.processes.get_mut(&id)
+    }
+
+    /// Forget a process whose handle was dropped; a still-running module
+    /// thread is detached
+    pub fn remove_process(&mut self, id: u32) {
+        self.processes.remove(&id);
     }
 }
 
@@ -0,0 +1,126 @@
+//! Shell mode — run the runtime's shell from the host command line.
+//!
+//! Calls the MCP component's `shell:unix/command` export directly with
+//! the host's arguments, environment and stdio, so the shell can be used
+//! from scripts and CI:
+//!
+//! ```bash
+//! wasm-tui shell -- echo 'a  b'     # run a command with these arguments
+//! wasm-tui shell -- 'ls | wc -l'    # run one command line
+//! wasm-tui sh < script.sh           # run a script read from stdin
+//! wasm-tui sh                       # interactive REPL on a terminal
+//! ```
+
+use anyhow::{Context, Result};
+use std::io::{IsTerminal, Read, Write};
+use wasmtime::component::Component;
+use wasmtime::{Engine, Store};
+use wasmtime_wasi::p2::bindings::io::streams::{InputStream, OutputStream};
+use wasmtime_wasi::p2::pipe::AsyncReadStream;
+use wasmtime_wasi::WasiCtxBuilder;
+use wasmtime_wasi_http::WasiHttpView;
+
+use crate::bindings::ExecEnv;
+use crate::mcp_stdio::{mcp_linker, McpHostState};
+use crate::module_loader::{command_run_func, preopen_mounts, ModuleLoader, Mount, Terminal};
+
+/// Command name the component dispatches to its shell
+const SHELL_NAME: &str = "sh";
+
+/// Build the shell's arguments from the words after `--`.
+///
+/// A single word is run as a command line, so `shell -- 'ls | wc -l'`
+/// works. Several words are a command and its arguments: each is quoted,
+/// so `shell -- echo 'a  b'` keeps its spacing. With no words, a script
+/// piped on stdin is read up front and run with `-c`; a terminal on stdin
+/// gets the interactive REPL instead.
+pub fn shell_args(command: &[String]) -> Result<Vec<String>> {
+    match command {
+        [] => {}
+        [line] => return Ok(vec!["-c".to_string(), line.clone()]),
+        words => {
+            let words: Vec<String> = words.iter().map(|word| shell_quote(word)).collect();
+            return Ok(vec!["-c".to_string(), words.join(" ")]);
+        }
+    }
+
+    if std::io::stdin().is_terminal() {
+        return Ok(Vec::new());
+    }
+
+    let mut script = String::new();
+    std::io::stdin()
+        .read_to_string(&mut script)
+        .context("Failed to read script from stdin")?;
+    Ok(vec!["-c".to_string(), script])
+}
+
+/// Quote `word` so the shell reads it back as one word
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,+%@^".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// Run the component's shell with `args` and return its exit code.
+///
+/// `mounts` are preopened in the guest (the one at `/` is its root), the
+/// host environment is passed through as shell variables, and stdio is
+/// wired to the host's. Output is written through as the guest produces it
+/// and flushed before this returns, so the caller can exit right away.
+/// Lazy commands (tsx, sqlite3, ...) are served by
+/// `module_loader`.
+pub async fn run_shell(
+    engine: &Engine,
+    mcp_bytes: &[u8],
+    mounts: &[Mount],
+    module_loader: ModuleLoader,
+    args: Vec<String>,
+) -> Result<i32> {
+    let component = Component::new(engine, mcp_bytes)?;
+    let linker = mcp_linker(engine)?;
+
+    let mut wasi = WasiCtxBuilder::new();
+    wasi.inherit_stderr().inherit_env();
+    preopen_mounts(&mut wasi, mounts)?;
+    let wasi = wasi.build();
+    let mut store = Store::new(engine, McpHostState::new(wasi, module_loader));
+
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run_func = command_run_func(&instance, &mut store)?;
+
+    // The shell sees the root mount as its root
+    let env = ExecEnv {
+        cwd: "/".to_string(),
+        vars: std::env::vars().collect(),
+    };
+
+    let stdin: InputStream = Box::new(AsyncReadStream::new(tokio::io::stdin()));
+    let stdout: OutputStream = Box::new(Terminal::Stdout);
+    let stderr: OutputStream = Box::new(Terminal::Stderr);
+    let table = WasiHttpView::table(store.data_mut());
+    let stdin = table.push(stdin)?;
+    let stdout = table.push(stdout)?;
+    let stderr = table.push(stderr)?;
+
+    let (code,) = run_func
+        .call_async(
+            &mut store,
+            (SHELL_NAME.to_string(), args, env, stdin, stdout, stderr),
+        )
+        .await?;
+
+    // Drop the store, with any streams the guest left open, then flush
+    // what they wrote
+    drop(store);
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+
+    Ok(code)
+}
@@ -366,6 +366,17 @@ mod shell_commands {
 mod error_handling {
     use super::*;
 
+    #[test]
+    #[ignore]
+    fn bad_mount_path_fails_before_startup() {
+        let output = Command::new(binary_path())
+            .args(["--dir", "/definitely/not/a/real/dir:/work"])
+            .output()
+            .expect("Failed to run binary");
+        assert!(!output.status.success(), "Should fail: {:?}", output);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not a directory"));
+    }
+
     #[test]
     #[ignore]
     fn invalid_command_doesnt_crash() {
@@ -461,6 +472,90 @@ mod wasm_loading {
     }
 }
 
+// =============================================================================
+// SHELL SUBCOMMAND TESTS
+// =============================================================================
+
+mod shell_subcommand {
+    use super::*;
+
+    #[test]
+    #[ignore]
+    fn runs_command_after_double_dash() {
+        let output = Command::new(binary_path())
+            .args(["shell", "--", "echo", "hello"])
+            .output()
+            .expect("Failed to run shell subcommand");
+        assert!(output.status.success(), "Should exit 0: {:?}", output);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("hello"));
+    }
+
+    #[test]
+    #[ignore]
+    fn keeps_each_argument_one_word() {
+        let output = Command::new(binary_path())
+            .args(["shell", "--", "printf", "[%s]", "a  b", "it's"])
+            .output()
+            .expect("Failed to run shell subcommand");
+        assert!(output.status.success(), "Should exit 0: {:?}", output);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "[a  b][it's]");
+    }
+
+    #[test]
+    #[ignore]
+    fn flushes_output_before_exiting() {
+        let output = Command::new(binary_path())
+            .args(["sh", "--", "printf no-newline; exit 5"])
+            .output()
+            .expect("Failed to run shell subcommand");
+        assert_eq!(output.status.code(), Some(5));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "no-newline");
+    }
+
+    #[test]
+    #[ignore]
+    fn passes_through_exit_code_and_env() {
+        let output = Command::new(binary_path())
+            .args(["sh", "--", "echo $E2E_SHELL_VAR; exit 3"])
+            .env("E2E_SHELL_VAR", "from-host")
+            .output()
+            .expect("Failed to run shell subcommand");
+        assert_eq!(output.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("from-host"));
+    }
+
+    #[test]
+    #[ignore]
+    fn dispatches_tsx_to_lazy_module() {
+        let output = Command::new(binary_path())
+            .args(["shell", "--", "tsx -e 'console.log(6 * 7)'"])
+            .output()
+            .expect("Failed to run shell subcommand");
+        assert!(output.status.success(), "Should exit 0: {:?}", output);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("42"));
+    }
+
+    #[test]
+    #[ignore]
+    fn runs_script_from_stdin() {
+        let mut child = Command::new(binary_path())
+            .arg("sh")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn shell subcommand");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"for x in a b; do echo item-$x; done\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("item-a") && stdout.contains("item-b"));
+    }
+}
+
 // =============================================================================
 // INTEGRATION TESTS
 // =============================================================================
@@ -23,7 +23,9 @@ mod harness {
     use std::sync::LazyLock;
     use tempfile::TempDir;
     use wasmtime::{Config, Engine};
-    use wasmtime_runner::mcp_stdio::{call_mcp_component, setup_mcp_proxy, McpHostState};
+    use wasmtime_runner::mcp_stdio::{
+        call_mcp_component, serve_mcp, setup_mcp_proxy, McpHostState,
+    };
     use wasmtime_wasi_http::bindings::ProxyPre;
 
     /// Path to the pre-built MCP WASM component
@@ -180,6 +182,24 @@ mod harness {
                 .expect("call_mcp_component failed")
         }
 
+        /// Run the stdio server loop over `input` and `output` until input closes.
+        pub async fn serve<R, W>(&self, input: R, output: W)
+        where
+            R: tokio::io::AsyncRead + Unpin + Send + 'static,
+            W: tokio::io::AsyncWrite + Unpin,
+        {
+            let (engine, proxy_pre) = &*MCP_RUNTIME;
+            serve_mcp(
+                engine,
+                proxy_pre,
+                input,
+                output,
+                &self.dir.path().to_path_buf(),
+            )
+            .await
+            .expect("serve_mcp failed");
+        }
+
         /// Write a file into the sandbox (host-side, bypassing MCP — for test setup).
         pub fn write_fixture(&self, path: &str, content: &str) {
             let full_path = self.dir.path().join(path);
@@ -524,6 +544,62 @@ mod protocol {
         let resp: serde_json::Value = serde_json::from_str(&response_str).unwrap();
         assert_eq!(resp["error"]["code"], -32700, "Should be Parse Error");
     }
+
+    #[tokio::test]
+    async fn cancelled_notification_stops_running_call() {
+        use std::time::Duration;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let h = McpTestHarness::new();
+        let (mut client_in, server_in) = tokio::io::duplex(4096);
+        let (server_out, client_out) = tokio::io::duplex(4096);
+        let mut responses = BufReader::new(client_out).lines();
+
+        let client = async move {
+            let send = |message: serde_json::Value| format!("{}\n", message);
+            client_in
+                .write_all(
+                    send(json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": "tools/call",
+                        "params": {
+                            "name": "shell_eval",
+                            "arguments": {"command": "sleep 30; echo finished"}
+                        }
+                    }))
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            // Let the call start and block in sleep before cancelling it
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            for message in [
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/cancelled",
+                    "params": {"requestId": 1, "reason": "user"}
+                }),
+                json!({"jsonrpc": "2.0", "id": 2, "method": "ping", "params": {}}),
+            ] {
+                client_in.write_all(send(message).as_bytes()).await.unwrap();
+            }
+
+            let line = tokio::time::timeout(Duration::from_secs(10), responses.next_line())
+                .await
+                .expect("ping should be answered without waiting for the sleep")
+                .unwrap()
+                .unwrap();
+            drop(client_in);
+            let rest = responses.next_line().await.unwrap();
+            (line, rest)
+        };
+
+        let ((line, rest), ()) = tokio::join!(client, h.serve(server_in, server_out));
+        let resp: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(resp["id"], 2, "cancelled call should get no response");
+        assert_eq!(rest, None);
+    }
 }
 
 // ============================================================
@@ -3161,6 +3237,25 @@ mod shell_encoding_commands {
         );
     }
 
+    #[tokio::test]
+    async fn date_formats_current_year() {
+        let mut h = McpTestHarness::new();
+        let text = h
+            .call_tool_text("shell_eval", json!({"command": "date +%Y"}))
+            .await;
+        let year: i64 = text.trim().parse().expect("date +%Y should print a year");
+        assert!(year >= 2024, "unexpected year: {}", year);
+    }
+
+    #[tokio::test]
+    async fn date_formats_epoch_in_utc() {
+        let mut h = McpTestHarness::new();
+        let text = h
+            .call_tool_text("shell_eval", json!({"command": "date -d @0 -u +%Y-%m-%d"}))
+            .await;
+        assert_eq!(text.trim(), "1970-01-01");
+    }
+
     // --- sqlite3 ---
 
     #[tokio::test]
@@ -3188,6 +3283,50 @@ mod shell_encoding_commands {
         assert!(text.contains("bob"), "Should query bob: {}", text);
     }
 
+    #[tokio::test]
+    async fn sqlite3_import_creates_missing_table() {
+        let mut h = McpTestHarness::new();
+        h.write_fixture("people.csv", "id,name\n1,alice\n2,bob\n");
+        let text = h
+            .call_tool_text(
+                "shell_eval",
+                json!({"command": "printf '.import --csv people.csv people\\nSELECT name FROM people ORDER BY id;\\n' | sqlite3 import.db"}),
+            )
+            .await;
+        // The "Added N rows" summary is on stderr, appended after the rows
+        let stdout = text.split("\nstderr:").next().unwrap_or_default();
+        assert!(text.contains("Added 2 rows"), "import summary: {}", text);
+        let lines: Vec<&str> = stdout.trim().lines().collect();
+        assert_eq!(
+            lines,
+            vec!["alice", "bob"],
+            "header row names the columns: {}",
+            text
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite3_import_into_existing_table() {
+        let mut h = McpTestHarness::new();
+        h.write_fixture("people.csv", "1,alice\n2,bob\n");
+        let text = h
+            .call_tool_text(
+                "shell_eval",
+                json!({"command": "sqlite3 import.db 'CREATE TABLE people(id INT, name TEXT);' && printf '.import --csv people.csv people\\nSELECT id + 10, name FROM people ORDER BY id;\\n' | sqlite3 import.db"}),
+            )
+            .await;
+        // The "Added N rows" summary is on stderr, appended after the rows
+        let stdout = text.split("\nstderr:").next().unwrap_or_default();
+        assert!(text.contains("Added 2 rows"), "import summary: {}", text);
+        let lines: Vec<&str> = stdout.trim().lines().collect();
+        assert_eq!(
+            lines,
+            vec!["11|alice", "12|bob"],
+            "rows go into existing columns: {}",
+            text
+        );
+    }
+
     // --- seq ---
 
     #[tokio::test]
@@ -120,6 +120,20 @@ impl AgentCore {
         self.conversation.clear();
     }
 
+    /// Replace the conversation with previously saved messages
+    ///
+    /// Unlike `add_user_message`, no events are emitted.
+    pub fn restore_messages(&mut self, messages: &[Message]) {
+        self.conversation.clear();
+        for msg in messages {
+            let turn = match msg.role {
+                Role::User => ConversationTurn::user(&msg.content),
+                Role::Assistant => ConversationTurn::assistant(&msg.content),
+            };
+            self.conversation.append_turn(turn);
+        }
+    }
+
     // === Configuration ===
 
     /// Get the current provider name
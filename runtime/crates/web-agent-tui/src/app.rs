@@ -4,17 +4,23 @@
 
 use ratatui::Terminal;
 
-use crate::backend::{enter_alternate_screen, leave_alternate_screen, WasiBackend};
+use crate::backend::{
+    disable_mouse_capture, enable_mouse_capture, enter_alternate_screen, leave_alternate_screen,
+    WasiBackend,
+};
 use crate::bridge::{
     get_local_tool_definitions, get_system_message_for_mode, mcp_client::McpError, McpClient,
 };
 
-use crate::config::{self, Config, ServersConfig};
-use crate::input::InputBuffer;
+use crate::commands::{filter_commands, help_text, SLASH_COMMANDS};
+use crate::config::{self, Config, ServersConfig, Session};
+use crate::input::{parse_scroll_sequence, InputBuffer, ScrollEvent};
+use crate::search::SearchState;
 use crate::servers::{RemoteServerEntry, ServerConnectionStatus, ServerManager};
 
 use crate::ui::{
-    render_ui, AuxContent, AuxContentKind, Mode, Overlay, ServerManagerView, ServerStatus,
+    render_ui, AuxContent, AuxContentKind, MessagesScroll, Mode, Overlay, ServerManagerView,
+    ServerStatus,
 };
 use crate::PollableRead;
 use crate::{poll, subscribe_duration, AgentCore};
@@ -63,11 +69,23 @@ pub struct App<R: PollableRead, W: Write> {
     pub(crate) overlay: Option<Overlay>,
     /// Unified timeline: messages and display items in chronological order
     pub(crate) timeline: Vec<crate::display::TimelineEntry>,
+    /// Top line of the messages pane when scrolled back
+    pub(crate) scroll: MessagesScroll,
+    /// Active search over the message history
+    pub(crate) search: Option<SearchState>,
+    /// Keys go to the message history (`/`, n/N) rather than the input box
+    pub(crate) history_focus: bool,
 
     /// The Core Agent logic
     pub(crate) agent: AgentCore,
 }
 
+/// Lines scrolled per mouse-wheel notch
+const WHEEL_SCROLL_LINES: usize = 3;
+
+/// Rows outside the messages text: status bar, input box and message borders
+const MESSAGES_CHROME_ROWS: u16 = 6;
+
 /// Check if an error message indicates authentication failure (401, invalid API key, etc.)
 /// Returns true if the user should be prompted for a new API key
 fn is_auth_error(error: &str) -> bool {
@@ -120,7 +138,7 @@ impl<R: PollableRead, W: Write> App<R, W> {
 
         agent.remote_servers_mut().extend(remote_servers);
 
-        Self {
+        let mut app = Self {
             mode: Mode::Agent,
             state: AppState::Ready,
             input: InputBuffer::new(),
@@ -141,8 +159,20 @@ impl<R: PollableRead, W: Write> App<R, W> {
             timeline: vec![crate::display::TimelineEntry::info(
                 "Welcome to Agent in a Browser! Type /help for commands.",
             )],
+            scroll: MessagesScroll::default(),
+            search: None,
+            history_focus: false,
             agent,
+        };
+
+        // Pick up the previous conversation unless started with --fresh
+        if !config::fresh_start_requested() {
+            if let Some(session) = Session::load() {
+                app.restore_session(session);
+            }
         }
+
+        app
     }
 
     /// Create a new App for testing - bypasses OPFS and allows dependency injection
@@ -185,6 +215,9 @@ impl<R: PollableRead, W: Write> App<R, W> {
             timeline: vec![crate::display::TimelineEntry::info(
                 "Welcome to Agent in a Browser! Type /help for commands.",
             )],
+            scroll: MessagesScroll::default(),
+            search: None,
+            history_focus: false,
             agent,
         }
     }
@@ -296,6 +329,7 @@ impl<R: PollableRead, W: Write> App<R, W> {
         }
 
         // Cleanup
+        let _ = self.save_session();
         self.cleanup_terminal();
 
         0
@@ -304,14 +338,50 @@ impl<R: PollableRead, W: Write> App<R, W> {
     fn setup_terminal(&mut self) {
         let _ = self.terminal.clear();
         let _ = self.terminal.hide_cursor();
+        let _ = enable_mouse_capture(self.terminal.backend_mut().writer_mut());
     }
 
     fn cleanup_terminal(&mut self) {
+        let _ = disable_mouse_capture(self.terminal.backend_mut().writer_mut());
         let _ = self.terminal.show_cursor();
         // Leave alternate screen - need to access writer through backend
         let _ = leave_alternate_screen(self.terminal.backend_mut().writer_mut());
     }
 
+    /// Save the conversation and model selection for the next run
+    fn save_session(&self) -> Result<usize, std::io::Error> {
+        let messages = self.agent.messages();
+        Session::new(self.agent.provider(), self.agent.model(), &messages).save()?;
+        Ok(messages.len())
+    }
+
+    /// Replace the conversation with a saved session
+    fn restore_session(&mut self, session: Session) {
+        if !session.provider.is_empty() && session.provider != self.agent.provider() {
+            self.agent.set_provider(&session.provider);
+        }
+        if !session.model.is_empty() && session.model != self.agent.model() {
+            self.agent.set_model(&session.model);
+        }
+
+        let messages = session.messages();
+        self.agent.restore_messages(&messages);
+        for msg in &messages {
+            self.timeline.push(match msg.role {
+                crate::Role::User => crate::display::TimelineEntry::user_message(&msg.content),
+                crate::Role::Assistant => {
+                    crate::display::TimelineEntry::assistant_message(&msg.content)
+                }
+            });
+        }
+        if !messages.is_empty() {
+            self.notice(format!(
+                "Restored {} messages from the previous session (/clear to start over).",
+                messages.len()
+            ));
+        }
+    }
+
     /// Get the current model name for display
     pub fn model_name(&self) -> &str {
         self.agent.model()
@@ -393,6 +463,12 @@ impl<R: PollableRead, W: Write> App<R, W> {
         let overlay = self.overlay.clone();
         let display_items = self.timeline.clone();
         let theme = crate::ui::Theme::by_name(&self.agent.config().ui.theme);
+        let mut scroll = self.scroll;
+        if let Some(search) = &mut self.search {
+            // Pick up messages that arrived since the last frame
+            search.refresh(&self.timeline);
+        }
+        let search = self.search.clone();
 
         let _ = self.terminal.draw(|frame| {
             render_ui(
@@ -409,8 +485,16 @@ impl<R: PollableRead, W: Write> App<R, W> {
                 overlay.as_ref(),
                 &remote_servers,
                 &theme,
+                search.as_ref(),
+                &mut scroll,
             );
         });
+
+        // Rendering clamps the top line, and clears it once back at the bottom
+        self.scroll = scroll;
+        if let Some(search) = &mut self.search {
+            search.jump_pending = false;
+        }
     }
 
     fn handle_input(&mut self) {
@@ -440,11 +524,23 @@ impl<R: PollableRead, W: Write> App<R, W> {
             Ok(0) => {} // No data
             Ok(n) => {
                 let bytes = &buf[..n];
-                // Check for ESC key (0x1B) to cancel streaming
-                if bytes.contains(&0x1B) {
-                    // Cancel active stream
-                    self.agent.cancel();
-                    self.state = AppState::Ready;
+                let mut i = 0;
+                while i < bytes.len() {
+                    if bytes[i] == 0x1B {
+                        // Scrolling keeps the stream going
+                        if let Some((event, consumed)) = parse_scroll_sequence(&bytes[i..]) {
+                            if let Some(event) = event {
+                                self.handle_scroll(event);
+                            }
+                            i += consumed;
+                            continue;
+                        }
+                        // Any other ESC cancels the active stream
+                        self.agent.cancel();
+                        self.state = AppState::Ready;
+                        break;
+                    }
+                    i += 1;
                 }
             }
             Err(_) => {} // Would block or error
@@ -458,6 +554,28 @@ impl<R: PollableRead, W: Write> App<R, W> {
         while i < bytes.len() {
             let byte = bytes[i];
 
+            // Mouse wheel and PageUp/PageDown scroll the messages pane
+            if byte == 0x1B {
+                if let Some((event, consumed)) = parse_scroll_sequence(&bytes[i..]) {
+                    if let Some(event) = event {
+                        if self.overlay.is_none() {
+                            self.handle_scroll(event);
+                        }
+                    }
+                    i += consumed;
+                    continue;
+                }
+            }
+
+            // Search prompt and n/N navigation take keys before the input box
+            if self.search.is_some() {
+                let bare_escape = byte == 0x1B && bytes.get(i + 1) != Some(&b'[');
+                if (byte != 0x1B || bare_escape) && self.handle_search_key(byte, bare_escape) {
+                    i += 1;
+                    continue;
+                }
+            }
+
             // If overlay is active, handle with escape sequence detection
             if self.overlay.is_some() {
                 let (key, consumed) = if byte == 0x1B && i + 2 < bytes.len() && bytes[i + 1] == b'['
@@ -524,7 +642,10 @@ impl<R: PollableRead, W: Write> App<R, W> {
                         }
                     }
                 } else {
-                    // Bare/incomplete escape - just skip it
+                    // A bare Esc on an empty prompt hands focus to the history
+                    if bytes.get(i + 1) != Some(&b'[') && self.input.is_empty() {
+                        self.history_focus = true;
+                    }
                     i += 1;
                     continue;
                 }
@@ -604,6 +725,28 @@ impl<R: PollableRead, W: Write> App<R, W> {
                 }
                 true // Stop reading after enter
             }
+            // Ctrl+F, or '/' while the history has focus - search messages
+            0x06 => {
+                self.open_search();
+                false
+            }
+            b'/' if self.input.is_empty() && self.history_focused() => {
+                self.open_search();
+                false
+            }
+            // '/' on an empty prompt opens the slash-command palette
+            b'/' if self.input.is_empty()
+                && self.mode != Mode::Shell
+                && self.state != AppState::NeedsApiKey =>
+            {
+                self.history_focus = false;
+                self.input.insert_char('/');
+                self.overlay = Some(Overlay::CommandPalette {
+                    query: String::new(),
+                    selected: 0,
+                });
+                false // Keep reading - the rest of a paste goes to the palette
+            }
             // Tab - autocomplete slash commands
             0x09 => {
                 self.try_tab_complete();
@@ -611,6 +754,7 @@ impl<R: PollableRead, W: Write> App<R, W> {
             }
             // Printable ASCII - insert at cursor
             0x20..=0x7E => {
+                self.history_focus = false;
                 self.input.insert_char(byte as char);
                 false // Continue reading (for paste)
             }
@@ -674,6 +818,99 @@ impl<R: PollableRead, W: Write> App<R, W> {
         }
     }
 
+    /// Whether keys go to the message history: after Esc on an empty
+    /// prompt, while scrolled back, or while searching
+    fn history_focused(&self) -> bool {
+        self.history_focus || self.scroll.top.is_some()
+    }
+
+    /// Open the search prompt, giving the history focus for n/N
+    fn open_search(&mut self) {
+        self.search = Some(SearchState::new());
+        self.history_focus = true;
+    }
+
+    /// Route a key to the search prompt or n/N navigation
+    ///
+    /// Returns true if the key was consumed. While the query is being typed
+    /// every key goes to it; afterwards n/N cycle hits and `/` starts a new
+    /// query while the history has focus. Any other text goes to the input
+    /// box and ends the search. Esc closes the search.
+    fn handle_search_key(&mut self, byte: u8, bare_escape: bool) -> bool {
+        let focused = self.input.is_empty() && self.history_focused();
+        let Some(search) = self.search.as_mut() else {
+            return false;
+        };
+
+        if bare_escape {
+            self.search = None;
+            self.scroll.top = None;
+            self.history_focus = false;
+            return true;
+        }
+
+        if search.editing {
+            match byte {
+                // Enter - keep the hits and leave the prompt
+                0x0D | 0x0A if !search.query.is_empty() => search.editing = false,
+                // Enter or Backspace on an empty query closes the search
+                0x0D | 0x0A | 0x7F | 0x08 if search.query.is_empty() => {
+                    self.search = None;
+                    self.history_focus = false;
+                }
+                0x7F | 0x08 => search.pop_char(&self.timeline),
+                0x20..=0x7E => search.push_char(byte as char, &self.timeline),
+                _ => {}
+            }
+            return true;
+        }
+
+        match byte {
+            b'n' if focused => search.next_hit(),
+            b'N' if focused => search.prev_hit(),
+            b'/' if focused => search.edit(),
+            // Text for the input box leaves search mode
+            0x20..=0x7E => {
+                self.search = None;
+                return false;
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_scroll(&mut self, event: ScrollEvent) {
+        let page = self
+            .terminal
+            .backend()
+            .height()
+            .saturating_sub(MESSAGES_CHROME_ROWS)
+            .max(1) as usize;
+
+        match event {
+            ScrollEvent::WheelUp => self.scroll_up(WHEEL_SCROLL_LINES),
+            ScrollEvent::WheelDown => self.scroll_down(WHEEL_SCROLL_LINES),
+            ScrollEvent::PageUp => self.scroll_up(page),
+            ScrollEvent::PageDown => self.scroll_down(page),
+        }
+    }
+
+    /// Scroll the messages pane back, stopping at the oldest line
+    fn scroll_up(&mut self, lines: usize) {
+        let top = self.scroll.top.unwrap_or(self.scroll.max_top);
+        self.scroll.top = Some(top.saturating_sub(lines));
+    }
+
+    /// Scroll the messages pane forward, resuming auto-scroll at the bottom
+    fn scroll_down(&mut self, lines: usize) {
+        let max_top = self.scroll.max_top;
+        self.scroll.top = self
+            .scroll
+            .top
+            .map(|top| top + lines)
+            .filter(|top| *top < max_top);
+    }
+
     fn handle_resize(&mut self, cols: u16, rows: u16) {
         // Update the terminal backend size
         self.terminal.backend_mut().set_size(cols, rows);
@@ -683,6 +920,9 @@ impl<R: PollableRead, W: Write> App<R, W> {
 
     fn submit_input(&mut self) {
         let input = self.input.take();
+        // Jump back to the latest output
+        self.scroll.top = None;
+        self.history_focus = false;
 
         match self.state {
             AppState::NeedsApiKey => {
@@ -1786,6 +2026,63 @@ impl<R: PollableRead, W: Write> App<R, W> {
                     _ => {}
                 }
             }
+            Overlay::CommandPalette { query, selected } => {
+                // The input box mirrors "/" + query while the palette is open
+                let matches = filter_commands(query);
+                match byte {
+                    0x1B => {
+                        // Esc - close, keeping the typed text
+                        self.overlay = None;
+                    }
+                    0xF0 => {
+                        // Up arrow (decoded)
+                        *selected = selected.saturating_sub(1);
+                    }
+                    0xF1 => {
+                        // Down arrow (decoded)
+                        if *selected + 1 < matches.len() {
+                            *selected += 1;
+                        }
+                    }
+                    0x0D | 0x0A => {
+                        // Enter - run the highlighted command, or the typed
+                        // text as-is when nothing matches
+                        let command = matches.get(*selected).map(|cmd| cmd.name);
+                        self.overlay = None;
+                        if let Some(name) = command {
+                            self.input.set_text(name.to_string());
+                        }
+                        self.submit_input();
+                    }
+                    0x09 => {
+                        // Tab - complete the highlighted command to add arguments
+                        if let Some(cmd) = matches.get(*selected) {
+                            self.input.set_text(format!("{} ", cmd.name));
+                        }
+                        self.overlay = None;
+                    }
+                    0x7F | 0x08 => {
+                        // Backspace - deleting the "/" closes the palette
+                        if query.pop().is_none() {
+                            self.overlay = None;
+                        } else {
+                            *selected = 0;
+                        }
+                        self.input.delete_char_before();
+                    }
+                    b' ' => {
+                        // Space - the user is typing arguments themselves
+                        self.overlay = None;
+                        self.input.insert_char(' ');
+                    }
+                    b if b > 0x20 && b < 0x7F => {
+                        query.push(b as char);
+                        *selected = 0;
+                        self.input.insert_char(b as char);
+                    }
+                    _ => {}
+                }
+            }
         }
     }
 
@@ -2167,23 +2464,6 @@ impl<R: PollableRead, W: Write> App<R, W> {
         self.agent.collect_all_tools()
     }
 
-    /// Slash commands for tab completion
-    const SLASH_COMMANDS: &'static [&'static str] = &[
-        "/help",
-        "/tools",
-        "/mcp",
-        "/model",
-        "/provider",
-        "/theme",
-        "/shell",
-        "/plan",
-        "/mode",
-        "/config",
-        "/key",
-        "/clear",
-        "/quit",
-    ];
-
     /// Try to complete the current input with Tab
     fn try_tab_complete(&mut self) {
         // Only complete slash commands for now
@@ -2194,10 +2474,10 @@ impl<R: PollableRead, W: Write> App<R, W> {
         let prefix = self.input.text();
 
         // Find matching commands
-        let matches: Vec<&str> = Self::SLASH_COMMANDS
+        let matches: Vec<&str> = SLASH_COMMANDS
             .iter()
-            .filter(|cmd| cmd.starts_with(prefix) && **cmd != prefix)
-            .copied()
+            .map(|cmd| cmd.name)
+            .filter(|name| name.starts_with(prefix) && *name != prefix)
             .collect();
 
         match matches.len() {
@@ -2224,20 +2504,12 @@ impl<R: PollableRead, W: Write> App<R, W> {
             "/help" | "/h" => {
                 self.notice(
                     [
-                        "Commands:",
-                        "  /help     - Show this help",
-                        "  /tools    - List available tools",
-                        "  /mcp      - MCP server manager (j/k=nav, Enter=select)",
-                        "  /model    - Select AI model",
-                        "  /provider - Select AI provider (Anthropic/OpenAI)",
-                        "  /theme    - Change theme (dark, light, gruvbox, catppuccin)",
-                        "  /shell    - Enter shell mode (^D to exit)",
-                        "  /plan     - Enter plan mode (Ctrl+P to toggle)",
-                        "  /mode     - View/change mode (normal, plan, shell)",
-                        "  /config   - View current configuration",
-                        "  /key      - Set API key",
-                        "  /clear    - Clear messages",
-                        "  /quit     - Exit (or ^C)",
+                        help_text().as_str(),
+                        "",
+                        "Keys:",
+                        "  Esc       - Focus the message history (PgUp/wheel scroll it)",
+                        "  /         - Search the history while it has focus (^F anywhere)",
+                        "  n / N     - Next / previous match (Esc closes the search)",
                     ]
                     .join("\n"),
                 );
@@ -2670,6 +2942,20 @@ impl<R: PollableRead, W: Write> App<R, W> {
                 self.agent.clear_messages();
                 self.notice("Messages cleared.".to_string());
             }
+            "/save" => match self.save_session() {
+                Ok(count) => self.notice(format!("Saved {} messages to the session.", count)),
+                Err(e) => self.notice_error(format!("Failed to save session: {}", e)),
+            },
+            "/load" => match Session::load() {
+                Some(session) => {
+                    self.timeline.clear();
+                    self.search = None;
+                    self.scroll.top = None;
+                    self.history_focus = false;
+                    self.restore_session(session);
+                }
+                None => self.notice("No saved session."),
+            },
             "/quit" | "/q" => {
                 self.should_quit = true;
             }
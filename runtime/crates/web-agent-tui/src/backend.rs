@@ -29,6 +29,11 @@ impl<W: Write> WasiBackend<W> {
         self.height = height;
     }
 
+    /// Last known terminal height in rows
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
     /// Get mutable reference to the writer
     pub fn writer_mut(&mut self) -> &mut W {
         &mut self.writer
@@ -220,3 +225,18 @@ pub fn leave_alternate_screen<W: Write>(writer: &mut W) -> IOResult<()> {
     writer.write_all(b"\x1b[?1049l")?;
     writer.flush()
 }
+
+/// Enable mouse reporting (button events, SGR extended encoding)
+///
+/// Wheel events then arrive on stdin as `ESC [ < 64;x;y M` (up) and
+/// `ESC [ < 65;x;y M` (down).
+pub fn enable_mouse_capture<W: Write>(writer: &mut W) -> IOResult<()> {
+    writer.write_all(b"\x1b[?1000h\x1b[?1006h")?;
+    writer.flush()
+}
+
+/// Disable mouse reporting
+pub fn disable_mouse_capture<W: Write>(writer: &mut W) -> IOResult<()> {
+    writer.write_all(b"\x1b[?1006l\x1b[?1000l")?;
+    writer.flush()
+}
@@ -21,16 +21,7 @@ pub fn handle_command(cmd: &str) -> CommandResult {
     let _args = &parts[1..];
 
     match command {
-        "/help" | "/h" => CommandResult::Message(
-            "Commands:\n\
-             /help, /h     - Show this help\n\
-             /shell, /sh   - Enter shell mode\n\
-             /agent        - Return to agent mode\n\
-             /model        - Select AI model\n\
-             /clear        - Clear message history\n\
-             /quit, /q     - Exit application"
-                .to_string(),
-        ),
+        "/help" | "/h" => CommandResult::Message(help_text()),
         "/shell" | "/sh" => CommandResult::SwitchMode(crate::ui::Mode::Shell),
         "/agent" => CommandResult::SwitchMode(crate::ui::Mode::Agent),
         "/plan" => CommandResult::SwitchMode(crate::ui::Mode::Plan),
@@ -39,3 +30,171 @@ pub fn handle_command(cmd: &str) -> CommandResult {
         _ => CommandResult::Unknown(command.to_string()),
     }
 }
+
+/// A slash command listed in the command palette and used for completion
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlashCommand {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// All slash commands, in the order `/help` lists them
+pub const SLASH_COMMANDS: &[SlashCommand] = &[
+    SlashCommand {
+        name: "/help",
+        description: "Show this help",
+    },
+    SlashCommand {
+        name: "/tools",
+        description: "List available tools",
+    },
+    SlashCommand {
+        name: "/mcp",
+        description: "MCP server manager",
+    },
+    SlashCommand {
+        name: "/model",
+        description: "Select AI model",
+    },
+    SlashCommand {
+        name: "/provider",
+        description: "Select AI provider",
+    },
+    SlashCommand {
+        name: "/theme",
+        description: "Change theme (dark, light, gruvbox, catppuccin)",
+    },
+    SlashCommand {
+        name: "/shell",
+        description: "Enter shell mode",
+    },
+    SlashCommand {
+        name: "/plan",
+        description: "Enter plan mode",
+    },
+    SlashCommand {
+        name: "/mode",
+        description: "View/change mode",
+    },
+    SlashCommand {
+        name: "/config",
+        description: "View current configuration",
+    },
+    SlashCommand {
+        name: "/key",
+        description: "Set API key",
+    },
+    SlashCommand {
+        name: "/clear",
+        description: "Clear messages",
+    },
+    SlashCommand {
+        name: "/save",
+        description: "Save the conversation (also saved on exit)",
+    },
+    SlashCommand {
+        name: "/load",
+        description: "Reload the saved conversation",
+    },
+    SlashCommand {
+        name: "/quit",
+        description: "Exit (or ^C)",
+    },
+];
+
+/// The `Commands:` section of `/help`, one line per entry in `SLASH_COMMANDS`
+pub fn help_text() -> String {
+    let mut text = String::from("Commands:");
+    for cmd in SLASH_COMMANDS {
+        text.push_str(&format!("\n  {:<10}- {}", cmd.name, cmd.description));
+    }
+    text
+}
+
+/// Commands fuzzily matching `query` (the text after `/`), best first
+///
+/// Ties keep registry order, so an empty query lists every command.
+pub fn filter_commands(query: &str) -> Vec<&'static SlashCommand> {
+    let mut matches: Vec<(usize, &'static SlashCommand)> = SLASH_COMMANDS
+        .iter()
+        .filter_map(|cmd| fuzzy_score(cmd.name, query).map(|score| (score, cmd)))
+        .collect();
+    matches.sort_by_key(|(score, _)| *score);
+    matches.into_iter().map(|(_, cmd)| cmd).collect()
+}
+
+/// Score how well `query` matches command `name` (lower is better)
+///
+/// Every query character must appear in order, ignoring case. Prefix
+/// matches rank first, then substrings by position, then scattered
+/// subsequences by how many characters they skip.
+fn fuzzy_score(name: &str, query: &str) -> Option<usize> {
+    let name = name.trim_start_matches('/').to_lowercase();
+    let query = query.to_lowercase();
+
+    if name.starts_with(&query) {
+        return Some(0);
+    }
+    if let Some(pos) = name.find(&query) {
+        return Some(1 + pos);
+    }
+
+    let mut skipped = 0;
+    let mut chars = name.chars();
+    for q in query.chars() {
+        loop {
+            match chars.next() {
+                Some(c) if c == q => break,
+                Some(_) => skipped += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(100 + skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(query: &str) -> Vec<&'static str> {
+        filter_commands(query).iter().map(|cmd| cmd.name).collect()
+    }
+
+    #[test]
+    fn empty_query_lists_everything_in_order() {
+        let all: Vec<&str> = SLASH_COMMANDS.iter().map(|cmd| cmd.name).collect();
+        assert_eq!(names(""), all);
+    }
+
+    #[test]
+    fn prefix_matches_rank_first() {
+        assert_eq!(names("mo"), vec!["/model", "/mode"]);
+        assert_eq!(names("M"), vec!["/mcp", "/model", "/mode", "/theme"]);
+    }
+
+    #[test]
+    fn substring_and_subsequence_matches() {
+        // "ode" is inside /model and /mode, and scattered through /provider
+        assert_eq!(names("ode"), vec!["/model", "/mode", "/provider"]);
+        assert_eq!(names("pvd"), vec!["/provider"]);
+        // A prefix beats substrings
+        assert_eq!(names("he"), vec!["/help", "/theme", "/shell"]);
+    }
+
+    #[test]
+    fn help_lists_every_command() {
+        let help = help_text();
+        assert!(help.starts_with("Commands:"));
+        for cmd in SLASH_COMMANDS {
+            let line = format!("  {:<10}- {}", cmd.name, cmd.description);
+            assert!(help.lines().any(|l| l == line), "missing {}", cmd.name);
+        }
+    }
+
+    #[test]
+    fn no_match() {
+        assert!(names("xyz").is_empty());
+        assert!(names("helpx").is_empty());
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Reads/writes config from OPFS at .config/web-agent/
 
+use crate::{Message, Role};
 use agent_bridge::models::{get_default_model, get_provider};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -13,6 +14,7 @@ const CONFIG_FILE: &str = ".config/web-agent/config.toml";
 const SERVERS_FILE: &str = ".config/web-agent/servers.toml";
 const AGENT_HISTORY_FILE: &str = ".config/web-agent/agent_history";
 const SHELL_HISTORY_FILE: &str = ".config/web-agent/shell_history";
+const SESSION_FILE: &str = ".config/web-agent/session.json";
 const MAX_HISTORY_ENTRIES: usize = 1000;
 
 /// Application configuration
@@ -464,6 +466,113 @@ pub fn add_to_history(history: &mut Vec<String>, command: String) {
     }
 }
 
+// ============================================================================
+// Session (conversation persistence)
+// ============================================================================
+
+/// Current session file format; `Session::from_json` migrates older ones
+pub const SESSION_VERSION: u32 = 1;
+
+/// Conversation and model selection saved between runs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Session {
+    pub version: u32,
+    pub provider: String,
+    pub model: String,
+    #[serde(default)]
+    pub messages: Vec<SessionMessage>,
+}
+
+/// A saved conversation message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SessionMessage {
+    pub role: SessionRole,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionRole {
+    User,
+    Assistant,
+}
+
+impl Session {
+    /// Snapshot the current conversation and model selection
+    pub fn new(provider: &str, model: &str, messages: &[Message]) -> Self {
+        Self {
+            version: SESSION_VERSION,
+            provider: provider.to_string(),
+            model: model.to_string(),
+            messages: messages
+                .iter()
+                .map(|msg| SessionMessage {
+                    role: match msg.role {
+                        Role::User => SessionRole::User,
+                        Role::Assistant => SessionRole::Assistant,
+                    },
+                    content: msg.content.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The saved conversation as agent messages
+    pub fn messages(&self) -> Vec<Message> {
+        self.messages
+            .iter()
+            .map(|msg| Message {
+                role: match msg.role {
+                    SessionRole::User => Role::User,
+                    SessionRole::Assistant => Role::Assistant,
+                },
+                content: msg.content.clone(),
+            })
+            .collect()
+    }
+
+    /// Load the saved session from OPFS, if there is a readable one
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(SESSION_FILE).ok()?;
+        Self::from_json(&contents)
+    }
+
+    /// Save the session to OPFS
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        ensure_config_dir()?;
+        if let Some(json) = self.to_json() {
+            fs::write(SESSION_FILE, json)?;
+        }
+        Ok(())
+    }
+
+    /// Parse a session, migrating older versions
+    ///
+    /// Returns None for malformed files and for versions newer than this
+    /// build understands.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let value: serde_json::Value = serde_json::from_str(json).ok()?;
+        match value.get("version")?.as_u64()? {
+            1 => serde_json::from_value(value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Serialize to a JSON string
+    pub fn to_json(&self) -> Option<String> {
+        serde_json::to_string_pretty(self).ok()
+    }
+}
+
+/// Whether the TUI was started with `--fresh` (skip restoring the session)
+pub fn fresh_start_requested() -> bool {
+    has_fresh_flag(std::env::args().skip(1))
+}
+
+fn has_fresh_flag(args: impl IntoIterator<Item = String>) -> bool {
+    args.into_iter().any(|arg| arg == "--fresh")
+}
+
 // ============================================================================
 // Helpers
 // ============================================================================
@@ -547,6 +656,55 @@ api_key = "sk-test"
         assert_eq!(history.len(), 2); // No duplicate added
     }
 
+    #[test]
+    fn session_roundtrip_json() {
+        let messages = vec![
+            Message {
+                role: Role::User,
+                content: "list files".to_string(),
+            },
+            Message {
+                role: Role::Assistant,
+                content: "Found:\n- a.txt".to_string(),
+            },
+        ];
+        let session = Session::new("openai", "gpt-4o", &messages);
+
+        let json = session.to_json().expect("should serialize");
+        assert!(json.contains("\"version\": 1"));
+        assert!(json.contains("\"role\": \"assistant\""));
+
+        let parsed = Session::from_json(&json).expect("should parse");
+        assert_eq!(parsed, session);
+        let restored = parsed.messages();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].role, Role::User);
+        assert_eq!(restored[1].content, "Found:\n- a.txt");
+    }
+
+    #[test]
+    fn session_rejects_unknown_versions_and_garbage() {
+        let newer = r#"{"version": 2, "provider": "openai", "model": "x", "messages": []}"#;
+        assert!(Session::from_json(newer).is_none());
+        let unversioned = r#"{"provider": "openai", "model": "x", "messages": []}"#;
+        assert!(Session::from_json(unversioned).is_none());
+        assert!(Session::from_json("not json").is_none());
+    }
+
+    #[test]
+    fn session_messages_default_to_empty() {
+        let json = r#"{"version": 1, "provider": "anthropic", "model": "m"}"#;
+        let session = Session::from_json(json).expect("should parse");
+        assert!(session.messages.is_empty());
+    }
+
+    #[test]
+    fn fresh_flag_detection() {
+        assert!(has_fresh_flag(vec!["--fresh".to_string()]));
+        assert!(!has_fresh_flag(vec!["--fresher".to_string()]));
+        assert!(!has_fresh_flag(Vec::new()));
+    }
+
     #[test]
     fn add_to_history_skips_empty() {
         let mut history = vec!["first".to_string()];
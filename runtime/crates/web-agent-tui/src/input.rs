@@ -182,6 +182,51 @@ impl InputBuffer {
     }
 }
 
+/// Scrollback request decoded from a terminal escape sequence
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollEvent {
+    WheelUp,
+    WheelDown,
+    PageUp,
+    PageDown,
+}
+
+/// Parse a mouse or PageUp/PageDown sequence at the start of `bytes`
+///
+/// Returns the scroll event (None for mouse events that aren't wheel
+/// motion, which should still be swallowed) and the number of bytes
+/// consumed, or None if `bytes` doesn't start with such a sequence.
+pub fn parse_scroll_sequence(bytes: &[u8]) -> Option<(Option<ScrollEvent>, usize)> {
+    if bytes.len() < 4 || bytes[0] != 0x1B || bytes[1] != b'[' {
+        return None;
+    }
+
+    match bytes[2] {
+        // SGR mouse report: ESC [ < button ; col ; row (M = press, m = release)
+        b'<' => {
+            let Some(end) = bytes[3..].iter().position(|&b| b == b'M' || b == b'm') else {
+                // Incomplete report - swallow the rest of the buffer
+                return Some((None, bytes.len()));
+            };
+            let end = end + 3;
+            let button = std::str::from_utf8(&bytes[3..end])
+                .ok()
+                .and_then(|params| params.split(';').next())
+                .and_then(|b| b.parse::<u16>().ok());
+            // Strip Shift/Meta/Ctrl modifier bits
+            let event = match (button.map(|b| b & !0b1_1100), bytes[end]) {
+                (Some(64), b'M') => Some(ScrollEvent::WheelUp),
+                (Some(65), b'M') => Some(ScrollEvent::WheelDown),
+                _ => None,
+            };
+            Some((event, end + 1))
+        }
+        b'5' if bytes[3] == b'~' => Some((Some(ScrollEvent::PageUp), 4)),
+        b'6' if bytes[3] == b'~' => Some((Some(ScrollEvent::PageDown), 4)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -422,4 +467,51 @@ mod tests {
 
         assert_eq!(buf.text(), "");
     }
+
+    // === Scroll Sequence Parsing ===
+
+    #[test]
+    fn parse_sgr_wheel_events() {
+        assert_eq!(
+            parse_scroll_sequence(b"\x1b[<64;10;5M"),
+            Some((Some(ScrollEvent::WheelUp), 11))
+        );
+        assert_eq!(
+            parse_scroll_sequence(b"\x1b[<65;10;5Mrest"),
+            Some((Some(ScrollEvent::WheelDown), 11))
+        );
+        // Ctrl+wheel still scrolls
+        assert_eq!(
+            parse_scroll_sequence(b"\x1b[<80;1;1M"),
+            Some((Some(ScrollEvent::WheelUp), 10))
+        );
+    }
+
+    #[test]
+    fn parse_other_mouse_events_are_swallowed() {
+        // Left button press and release
+        assert_eq!(parse_scroll_sequence(b"\x1b[<0;3;4M"), Some((None, 9)));
+        assert_eq!(parse_scroll_sequence(b"\x1b[<0;3;4m"), Some((None, 9)));
+        // Incomplete report
+        assert_eq!(parse_scroll_sequence(b"\x1b[<64;1"), Some((None, 7)));
+    }
+
+    #[test]
+    fn parse_page_keys() {
+        assert_eq!(
+            parse_scroll_sequence(b"\x1b[5~"),
+            Some((Some(ScrollEvent::PageUp), 4))
+        );
+        assert_eq!(
+            parse_scroll_sequence(b"\x1b[6~"),
+            Some((Some(ScrollEvent::PageDown), 4))
+        );
+    }
+
+    #[test]
+    fn parse_non_scroll_sequences() {
+        assert_eq!(parse_scroll_sequence(b"\x1b[A"), None);
+        assert_eq!(parse_scroll_sequence(b"\x1b[8;24;80t"), None);
+        assert_eq!(parse_scroll_sequence(b"\x1b"), None);
+    }
 }
@@ -12,6 +12,7 @@ pub mod config;
 pub mod display;
 pub mod events;
 pub mod input;
+pub mod search;
 pub mod servers;
 pub mod ui;
 
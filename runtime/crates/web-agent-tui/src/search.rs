@@ -0,0 +1,216 @@
+//! Message history search
+//!
+//! Case-insensitive substring search over the timeline's messages, with a
+//! current hit that `n`/`N` cycle through.
+
+use crate::display::TimelineEntry;
+
+/// Active search over the message history
+#[derive(Clone, Debug, Default)]
+pub struct SearchState {
+    /// Query text
+    pub query: String,
+    /// Matches as (timeline index, byte offset in the lowercased content)
+    pub hits: Vec<(usize, usize)>,
+    /// Index into `hits` of the current match
+    pub current: usize,
+    /// Query is still being typed
+    pub editing: bool,
+    /// Scroll the current match into view on the next render
+    pub jump_pending: bool,
+}
+
+impl SearchState {
+    /// Open a search prompt with an empty query
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Type a character into the query and search again from the top
+    pub fn push_char(&mut self, c: char, timeline: &[TimelineEntry]) {
+        self.query.push(c);
+        self.restart(timeline);
+    }
+
+    /// Delete the last query character and search again from the top
+    pub fn pop_char(&mut self, timeline: &[TimelineEntry]) {
+        self.query.pop();
+        self.restart(timeline);
+    }
+
+    /// Start typing a new query
+    pub fn edit(&mut self) {
+        self.query.clear();
+        self.hits.clear();
+        self.current = 0;
+        self.editing = true;
+    }
+
+    /// Recompute hits for the current query, keeping the current hit in range
+    pub fn refresh(&mut self, timeline: &[TimelineEntry]) {
+        self.hits = find_hits(timeline, &self.query);
+        if self.current >= self.hits.len() {
+            self.current = 0;
+        }
+    }
+
+    /// Move to the next hit, wrapping to the first
+    pub fn next_hit(&mut self) {
+        if !self.hits.is_empty() {
+            self.current = (self.current + 1) % self.hits.len();
+            self.jump_pending = true;
+        }
+    }
+
+    /// Move to the previous hit, wrapping to the last
+    pub fn prev_hit(&mut self) {
+        if !self.hits.is_empty() {
+            self.current = (self.current + self.hits.len() - 1) % self.hits.len();
+            self.jump_pending = true;
+        }
+    }
+
+    /// The current hit, if any
+    pub fn current_hit(&self) -> Option<(usize, usize)> {
+        self.hits.get(self.current).copied()
+    }
+
+    /// Which occurrence within its message the current hit is (0-based)
+    pub fn current_occurrence(&self) -> Option<usize> {
+        let (entry, _) = self.current_hit()?;
+        Some(
+            self.hits[..self.current]
+                .iter()
+                .filter(|(index, _)| *index == entry)
+                .count(),
+        )
+    }
+
+    fn restart(&mut self, timeline: &[TimelineEntry]) {
+        self.current = 0;
+        self.refresh(timeline);
+        self.jump_pending = !self.hits.is_empty();
+    }
+}
+
+/// Byte offsets of every case-insensitive occurrence of `query` in `text`
+///
+/// Offsets index the lowercased text. An empty query matches nothing.
+pub fn find_matches(text: &str, query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let query = query.to_lowercase();
+    text.to_lowercase()
+        .match_indices(&query)
+        .map(|(offset, _)| offset)
+        .collect()
+}
+
+/// All matches of `query` across the timeline's messages, in order
+fn find_hits(timeline: &[TimelineEntry], query: &str) -> Vec<(usize, usize)> {
+    timeline
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| match entry {
+            TimelineEntry::Message(msg) => Some((index, &msg.content)),
+            TimelineEntry::Display(_) => None,
+        })
+        .flat_map(|(index, content)| {
+            find_matches(content, query)
+                .into_iter()
+                .map(move |offset| (index, offset))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline() -> Vec<TimelineEntry> {
+        vec![
+            TimelineEntry::info("Welcome"),
+            TimelineEntry::user_message("List the Rust files"),
+            TimelineEntry::assistant_message("Found main.rs and lib.rs; rust is great"),
+            TimelineEntry::info("rust notice is not a message"),
+        ]
+    }
+
+    #[test]
+    fn find_matches_is_case_insensitive() {
+        assert_eq!(
+            find_matches("Rust and RUST and rust", "rust"),
+            vec![0, 9, 18]
+        );
+        assert_eq!(find_matches("hello", "HeLLo"), vec![0]);
+        assert!(find_matches("hello", "").is_empty());
+        assert!(find_matches("hello", "world").is_empty());
+    }
+
+    #[test]
+    fn typing_finds_hits_in_messages_only() {
+        let timeline = timeline();
+        let mut search = SearchState::new();
+        for c in "rust".chars() {
+            search.push_char(c, &timeline);
+        }
+        assert_eq!(search.hits, vec![(1, 9), (2, 26)]);
+        assert_eq!(search.current_hit(), Some((1, 9)));
+        assert!(search.jump_pending);
+
+        search.pop_char(&timeline);
+        search.pop_char(&timeline);
+        search.pop_char(&timeline);
+        // "r" also matches ".rs" and "great"
+        assert_eq!(search.hits.len(), 5);
+    }
+
+    #[test]
+    fn next_and_prev_wrap() {
+        let timeline = timeline();
+        let mut search = SearchState::new();
+        for c in ".rs".chars() {
+            search.push_char(c, &timeline);
+        }
+        assert_eq!(search.hits, vec![(2, 10), (2, 21)]);
+
+        search.next_hit();
+        assert_eq!(search.current_hit(), Some((2, 21)));
+        assert_eq!(search.current_occurrence(), Some(1));
+        search.next_hit();
+        assert_eq!(search.current_hit(), Some((2, 10)));
+        assert_eq!(search.current_occurrence(), Some(0));
+        search.prev_hit();
+        assert_eq!(search.current_hit(), Some((2, 21)));
+    }
+
+    #[test]
+    fn no_hits_keeps_navigation_inert() {
+        let timeline = timeline();
+        let mut search = SearchState::new();
+        search.push_char('z', &timeline);
+        assert!(search.hits.is_empty());
+        assert!(!search.jump_pending);
+        search.next_hit();
+        search.prev_hit();
+        assert_eq!(search.current_hit(), None);
+        assert_eq!(search.current_occurrence(), None);
+    }
+
+    #[test]
+    fn refresh_clamps_current_after_clear() {
+        let timeline = timeline();
+        let mut search = SearchState::new();
+        for c in "rust".chars() {
+            search.push_char(c, &timeline);
+        }
+        search.next_hit();
+        search.refresh(&[]);
+        assert!(search.hits.is_empty());
+        assert_eq!(search.current, 0);
+    }
+}
@@ -42,6 +42,8 @@ impl<'a, R: PollableRead, W: Write> Widget for AppWidget<'a, R, W> {
             self.app.state,
             &self.app.server_status,
             self.app.model_name(),
+            self.app.scroll.top.is_some(),
+            self.app.search.as_ref(),
         )
         .render(v_chunks[1], buf);
 
@@ -71,8 +73,9 @@ impl<'a, R: PollableRead, W: Write> AppWidget<'a, R, W> {
             &self.app.timeline,
             self.app.state,
             &theme,
+            self.app.search.as_ref(),
         )
-        .render(chunks[0], buf);
+        .render(chunks[0], buf, &mut self.app.scroll.clone());
 
         // Input Box - we can't set cursor here, caller must handle it
         let mut cursor_state = None;
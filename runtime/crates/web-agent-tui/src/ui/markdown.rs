@@ -0,0 +1,357 @@
+//! Lightweight markdown styling for message text
+//!
+//! Covers the subset assistant replies lean on: `**bold**`, inline
+//! `` `code` ``, fenced code blocks and `-`/`*`/`1.` list items.
+//! Unbalanced markers are rendered literally.
+
+use ratatui::prelude::*;
+
+use crate::ui::Theme;
+
+/// Tab stop used when clipping code block lines
+const TAB_WIDTH: usize = 4;
+
+/// Render `text` as styled lines word-wrapped to `width` columns
+///
+/// Code blocks are never wrapped; their lines are clipped to `width` and
+/// padded so the background forms a solid block. A `width` of 0 disables
+/// wrapping and clipping.
+pub fn render_markdown(text: &str, width: usize, base: Style, theme: &Theme) -> Vec<Line<'static>> {
+    let source: Vec<&str> = text.split('\n').collect();
+    let fences = fence_lines(&source);
+
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for (i, line) in source.iter().enumerate() {
+        if fences.contains(&i) {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push(code_block_line(line, width, theme));
+        } else {
+            lines.extend(text_lines(line, width, base, theme));
+        }
+    }
+
+    if lines.is_empty() {
+        lines.push(Line::default());
+    }
+
+    lines
+}
+
+/// Indices of the fence lines that open and close code blocks
+///
+/// A trailing fence without a partner (e.g. mid-stream) stays literal text.
+fn fence_lines(source: &[&str]) -> Vec<usize> {
+    let mut fences: Vec<usize> = source
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.trim_start().starts_with("```"))
+        .map(|(i, _)| i)
+        .collect();
+    if fences.len() % 2 == 1 {
+        fences.pop();
+    }
+    fences
+}
+
+/// One line of a fenced code block: clipped, padded, on the code background
+fn code_block_line(line: &str, width: usize, theme: &Theme) -> Line<'static> {
+    let style = Style::default().fg(theme.fg).bg(theme.code_bg);
+    let expanded = line.replace('\t', &" ".repeat(TAB_WIDTH));
+
+    if width == 0 {
+        return Line::from(Span::styled(expanded, style));
+    }
+
+    let mut clipped: String = expanded.chars().take(width).collect();
+    let pad = width - clipped.chars().count();
+    clipped.extend(std::iter::repeat(' ').take(pad));
+    Line::from(Span::styled(clipped, style))
+}
+
+/// Split a list marker (`- `, `* `, `+ `, `1. `) off `line`
+///
+/// Returns the marker as displayed (bullets become `•`) including any
+/// leading indentation, and the item text.
+fn list_marker(line: &str) -> Option<(String, &str)> {
+    let content = line.trim_start_matches(' ');
+    let indent = line.len() - content.len();
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(item) = content.strip_prefix(bullet) {
+            return Some((format!("{}• ", " ".repeat(indent)), item));
+        }
+    }
+
+    let digits = content.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(item) = content[digits..].strip_prefix(". ") {
+            return Some((
+                format!("{}{}", " ".repeat(indent), &content[..digits + 2]),
+                item,
+            ));
+        }
+    }
+
+    None
+}
+
+/// Wrap one non-code source line, hanging list items under their text
+fn text_lines(line: &str, width: usize, base: Style, theme: &Theme) -> Vec<Line<'static>> {
+    let (marker, content) = match list_marker(line) {
+        Some((marker, item)) => (Some(marker), item),
+        None => (None, line),
+    };
+
+    let hang = marker.as_ref().map_or(0, |m| m.chars().count());
+    let spans = inline_spans(content, base, theme);
+    let mut lines = wrap_spans(&spans, width.saturating_sub(hang), base);
+
+    if let Some(marker) = marker {
+        for (i, line) in lines.iter_mut().enumerate() {
+            let prefix = if i == 0 {
+                Span::styled(marker.clone(), Style::default().fg(theme.accent))
+            } else {
+                Span::raw(" ".repeat(hang))
+            };
+            line.spans.insert(0, prefix);
+        }
+    }
+
+    lines
+}
+
+/// Split inline markup into styled spans
+///
+/// `` `code` `` and `**bold**` need a closing marker on the same line and
+/// non-empty content; anything else is kept as literal text.
+fn inline_spans(text: &str, base: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(['`', '*']) {
+        let (before, from) = rest.split_at(pos);
+        plain.push_str(before);
+
+        let styled = if let Some(after) = from.strip_prefix('`') {
+            after
+                .find('`')
+                .filter(|&end| end > 0)
+                .map(|end| (&after[..end], base.fg(theme.code), &after[end + 1..]))
+        } else if let Some(after) = from.strip_prefix("**") {
+            after.find("**").filter(|&end| end > 0).map(|end| {
+                (
+                    &after[..end],
+                    base.add_modifier(Modifier::BOLD),
+                    &after[end + 2..],
+                )
+            })
+        } else {
+            None
+        };
+
+        match styled {
+            Some((inner, style, remaining)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.push(Span::styled(inner.to_string(), style));
+                rest = remaining;
+            }
+            None => {
+                // Unbalanced marker - keep it as text
+                plain.push_str(&from[..1]);
+                rest = &from[1..];
+            }
+        }
+    }
+
+    plain.push_str(rest);
+    if !plain.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+
+    spans
+}
+
+/// A whitespace-delimited word, possibly made of several styled pieces
+struct Word {
+    pieces: Vec<(String, Style)>,
+    width: usize,
+}
+
+/// Greedy word wrap over styled spans, collapsing runs of whitespace
+fn wrap_spans(spans: &[Span<'static>], width: usize, base: Style) -> Vec<Line<'static>> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut in_word = false;
+
+    for span in spans {
+        for c in span.content.chars() {
+            if c.is_whitespace() {
+                in_word = false;
+                continue;
+            }
+            if !in_word {
+                words.push(Word {
+                    pieces: Vec::new(),
+                    width: 0,
+                });
+                in_word = true;
+            }
+            let word = words.last_mut().expect("word was just pushed");
+            match word.pieces.last_mut() {
+                Some((text, style)) if *style == span.style => text.push(c),
+                _ => word.pieces.push((c.to_string(), span.style)),
+            }
+            word.width += 1;
+        }
+    }
+
+    if words.is_empty() {
+        return vec![Line::default()];
+    }
+
+    let mut lines = Vec::new();
+    let mut current: Vec<Span<'static>> = Vec::new();
+    let mut current_width = 0;
+
+    for word in words {
+        if current_width > 0 {
+            if width > 0 && current_width + 1 + word.width > width {
+                lines.push(Line::from(std::mem::take(&mut current)));
+                current_width = 0;
+            } else {
+                current.push(Span::styled(" ", base));
+                current_width += 1;
+            }
+        }
+        current_width += word.width;
+        current.extend(
+            word.pieces
+                .into_iter()
+                .map(|(text, style)| Span::styled(text, style)),
+        );
+    }
+    lines.push(Line::from(current));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme() -> Theme {
+        Theme::dark()
+    }
+
+    /// Flatten rendered lines to their text
+    fn texts(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn inline_bold_and_code() {
+        let base = Style::default();
+        let spans = inline_spans("run **cargo** with `--release` now", base, &theme());
+        let parts: Vec<(&str, Style)> = spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("run ", base),
+                ("cargo", base.add_modifier(Modifier::BOLD)),
+                (" with ", base),
+                ("--release", base.fg(theme().code)),
+                (" now", base),
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_unbalanced_markers_are_literal() {
+        let base = Style::default();
+        for text in ["a **b", "a `b", "2 * 3 = 6", "a ** b", "``", "****"] {
+            let spans = inline_spans(text, base, &theme());
+            assert_eq!(spans.len(), 1, "{:?}", text);
+            assert_eq!(spans[0].content, text);
+            assert_eq!(spans[0].style, base);
+        }
+    }
+
+    #[test]
+    fn inline_code_keeps_stars() {
+        let base = Style::default();
+        let spans = inline_spans("`a ** b`", base, &theme());
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].content, "a ** b");
+        assert_eq!(spans[0].style, base.fg(theme().code));
+    }
+
+    #[test]
+    fn wraps_styled_words() {
+        let lines = render_markdown("one **two three** four", 10, Style::default(), &theme());
+        assert_eq!(texts(&lines), vec!["one two", "three four"]);
+        // "three" keeps the bold style after wrapping
+        assert_eq!(lines[1].spans[0].content, "three");
+        assert!(lines[1].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn fenced_code_is_clipped_not_wrapped() {
+        let text = "Example:\n```rust\nlet answer = 42; // a long comment\n```\ndone";
+        let lines = render_markdown(text, 12, Style::default(), &theme());
+        assert_eq!(texts(&lines), vec!["Example:", "let answer =", "done"]);
+        assert_eq!(lines[1].spans[0].style.bg, Some(theme().code_bg));
+    }
+
+    #[test]
+    fn fenced_code_pads_short_lines() {
+        let lines = render_markdown("```\nx\n```", 4, Style::default(), &theme());
+        assert_eq!(texts(&lines), vec!["x   "]);
+    }
+
+    #[test]
+    fn unclosed_fence_is_literal() {
+        let lines = render_markdown("```sh\nls -la", 20, Style::default(), &theme());
+        assert_eq!(texts(&lines), vec!["```sh", "ls -la"]);
+        assert_eq!(lines[1].spans[0].style.bg, None);
+    }
+
+    #[test]
+    fn list_markers_hang_wrapped_lines() {
+        let text = "- first item here\n2. second\n  * nested";
+        let lines = render_markdown(text, 12, Style::default(), &theme());
+        assert_eq!(
+            texts(&lines),
+            vec!["• first item", "  here", "2. second", "  • nested"]
+        );
+        assert_eq!(lines[0].spans[0].style.fg, Some(theme().accent));
+    }
+
+    #[test]
+    fn not_a_list_marker() {
+        assert!(list_marker("-dash").is_none());
+        assert!(list_marker("1.5 apples").is_none());
+        assert!(list_marker("**bold**").is_none());
+    }
+
+    #[test]
+    fn blank_lines_are_kept() {
+        let lines = render_markdown("a\n\nb", 10, Style::default(), &theme());
+        assert_eq!(texts(&lines), vec!["a", "", "b"]);
+    }
+}
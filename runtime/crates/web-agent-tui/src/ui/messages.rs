@@ -5,14 +5,26 @@ use ratatui::{
 
 use crate::app::AppState;
 use crate::display::{DisplayItem, TimelineEntry};
+use crate::search::SearchState;
+use crate::ui::markdown::render_markdown;
 use crate::ui::Theme;
 use crate::{Message, Role};
 
+/// Scroll position of the messages pane
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MessagesScroll {
+    /// First visible line (None = follow new output)
+    pub top: Option<usize>,
+    /// Last line that could be at the top, as of the latest render
+    pub max_top: usize,
+}
+
 pub struct MessagesWidget<'a> {
     pub messages: &'a [Message],
     pub timeline: &'a [TimelineEntry],
     pub state: AppState,
     pub theme: &'a Theme,
+    pub search: Option<&'a SearchState>,
 }
 
 impl<'a> MessagesWidget<'a> {
@@ -21,26 +33,41 @@ impl<'a> MessagesWidget<'a> {
         timeline: &'a [TimelineEntry],
         state: AppState,
         theme: &'a Theme,
+        search: Option<&'a SearchState>,
     ) -> Self {
         Self {
             messages,
             timeline,
             state,
             theme,
+            search,
         }
     }
 }
 
-impl<'a> Widget for MessagesWidget<'a> {
-    fn render(self, area: Rect, buf: &mut Buffer) {
+impl<'a> StatefulWidget for MessagesWidget<'a> {
+    type State = MessagesScroll;
+
+    fn render(self, area: Rect, buf: &mut Buffer, scroll: &mut Self::State) {
         let inner_width = area.width.saturating_sub(4) as usize; // Account for borders + prefix
         let visible_height = area.height.saturating_sub(2) as usize;
 
         // Build wrapped lines with styling from the unified timeline
         let mut lines: Vec<Line> = Vec::new();
 
+        // Search highlighting, and the line to reveal for the current hit
+        let query = self
+            .search
+            .map(|search| search.query.to_lowercase())
+            .filter(|query| !query.is_empty());
+        let current_hit = self.search.and_then(|search| search.current_hit());
+        let current_occurrence = self.search.and_then(|search| search.current_occurrence());
+        let match_style = Style::default().bg(self.theme.muted).fg(self.theme.fg);
+        let current_style = Style::default().bg(self.theme.warning).fg(Color::Black);
+        let mut hit_line = None;
+
         // Render timeline entries in chronological order (unified messages + display items)
-        for entry in self.timeline {
+        for (index, entry) in self.timeline.iter().enumerate() {
             match entry {
                 TimelineEntry::Message(msg) => {
                     let (prefix, style) = match msg.role {
@@ -53,15 +80,48 @@ impl<'a> Widget for MessagesWidget<'a> {
                         Role::Assistant => ("◆ ", Style::default().fg(self.theme.assistant_msg)),
                     };
 
-                    // Word-wrap the content manually for better control
-                    let wrapped = wrap_text(&msg.content, inner_width.saturating_sub(2));
+                    // Word-wrap the content manually for better control;
+                    // assistant replies also get markdown styling
+                    let text_width = inner_width.saturating_sub(2);
+                    let text_style = style.remove_modifier(Modifier::BOLD);
+                    let body: Vec<Line> = match msg.role {
+                        Role::User => wrap_text(&msg.content, text_width)
+                            .into_iter()
+                            .map(|line_text| Line::from(Span::styled(line_text, text_style)))
+                            .collect(),
+                        Role::Assistant => {
+                            render_markdown(&msg.content, text_width, text_style, self.theme)
+                        }
+                    };
+
+                    let is_current = current_hit.is_some_and(|(hit, _)| hit == index);
+                    if is_current {
+                        hit_line = Some(lines.len());
+                    }
+                    let mut seen = 0;
 
-                    for (i, line_text) in wrapped.iter().enumerate() {
+                    for (i, line) in body.into_iter().enumerate() {
+                        let mut line = match &query {
+                            Some(query) => {
+                                let current = current_occurrence.filter(|_| is_current);
+                                let (line, has_current) = highlight_matches(
+                                    line,
+                                    query,
+                                    match_style,
+                                    current_style,
+                                    current,
+                                    &mut seen,
+                                );
+                                if has_current {
+                                    hit_line = Some(lines.len());
+                                }
+                                line
+                            }
+                            None => line,
+                        };
                         let line_prefix = if i == 0 { prefix } else { "  " };
-                        lines.push(Line::from(vec![
-                            Span::styled(line_prefix, style),
-                            Span::styled(line_text.clone(), style.remove_modifier(Modifier::BOLD)),
-                        ]));
+                        line.spans.insert(0, Span::styled(line_prefix, style));
+                        lines.push(line);
                     }
                 }
                 TimelineEntry::Display(display_item) => {
@@ -112,12 +172,18 @@ impl<'a> Widget for MessagesWidget<'a> {
             ]));
         }
 
-        // Calculate scroll offset to show latest
-        let scroll_offset = if lines.len() > visible_height {
-            lines.len() - visible_height
-        } else {
-            0
-        };
+        // Show the latest lines, unless scrolled back to a fixed top line
+        let max_top = lines.len().saturating_sub(visible_height);
+        if self.search.is_some_and(|search| search.jump_pending) {
+            if let Some(line) = hit_line {
+                // Reveal the current hit a third of the way down the pane
+                scroll.top = Some(line.saturating_sub(visible_height / 3));
+            }
+        }
+        let top = scroll.top.map_or(max_top, |top| top.min(max_top));
+        // Back at the bottom - resume auto-scroll
+        scroll.top = (top < max_top).then_some(top);
+        scroll.max_top = max_top;
 
         // Use Paragraph with scroll for wrapped text
         let text = Text::from(lines);
@@ -131,12 +197,69 @@ impl<'a> Widget for MessagesWidget<'a> {
                     ))
                     .border_type(BorderType::Rounded),
             )
-            .scroll((scroll_offset as u16, 0));
+            .scroll((top as u16, 0));
 
         paragraph.render(area, buf);
     }
 }
 
+/// Highlight occurrences of the lowercased `query` in `line`
+///
+/// `seen` counts occurrences across a message's lines; occurrence number
+/// `current` gets `current_style`. Returns the line and whether it holds
+/// the current occurrence. Matches split across spans aren't highlighted.
+fn highlight_matches<'l>(
+    line: Line<'l>,
+    query: &str,
+    match_style: Style,
+    current_style: Style,
+    current: Option<usize>,
+    seen: &mut usize,
+) -> (Line<'l>, bool) {
+    let mut has_current = false;
+    let mut spans = Vec::new();
+
+    for span in line.spans {
+        let lower = span.content.to_lowercase();
+        // Offsets only line up when lowercasing keeps the byte length
+        if lower.len() != span.content.len() {
+            spans.push(span);
+            continue;
+        }
+
+        let mut last = 0;
+        for (offset, _) in lower.match_indices(query) {
+            if offset > last {
+                spans.push(Span::styled(
+                    span.content[last..offset].to_string(),
+                    span.style,
+                ));
+            }
+            let patch = if current == Some(*seen) {
+                has_current = true;
+                current_style
+            } else {
+                match_style
+            };
+            let end = offset + query.len();
+            spans.push(Span::styled(
+                span.content[offset..end].to_string(),
+                span.style.patch(patch),
+            ));
+            *seen += 1;
+            last = end;
+        }
+
+        if last == 0 {
+            spans.push(span);
+        } else if last < span.content.len() {
+            spans.push(Span::styled(span.content[last..].to_string(), span.style));
+        }
+    }
+
+    (Line::from(spans), has_current)
+}
+
 /// Simple word wrap implementation
 fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
     if max_width == 0 {
@@ -238,4 +361,142 @@ mod tests {
             vec!["The quick", "brown fox", "jumps over", "the lazy", "dog"]
         );
     }
+
+    fn render_lines(count: usize, height: u16, scroll: &mut MessagesScroll) -> String {
+        let timeline: Vec<TimelineEntry> = (0..count)
+            .map(|i| TimelineEntry::info(format!("line {}", i)))
+            .collect();
+        let theme = Theme::dark();
+        let area = Rect::new(0, 0, 30, height);
+        let mut buf = Buffer::empty(area);
+        MessagesWidget::new(&[], &timeline, AppState::Ready, &theme, None)
+            .render(area, &mut buf, scroll);
+        (0..height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn scrolled_to(top: usize) -> MessagesScroll {
+        MessagesScroll {
+            top: Some(top),
+            ..MessagesScroll::default()
+        }
+    }
+
+    #[test]
+    fn messages_follow_latest_by_default() {
+        let mut scroll = MessagesScroll::default();
+        let out = render_lines(20, 7, &mut scroll);
+        assert!(out.contains("line 19"));
+        assert!(!out.contains("line 14"));
+        assert_eq!(scroll.top, None);
+        assert_eq!(scroll.max_top, 15);
+    }
+
+    #[test]
+    fn messages_scroll_shows_earlier_lines() {
+        let mut scroll = scrolled_to(10);
+        let out = render_lines(20, 7, &mut scroll);
+        assert!(out.contains("line 10"));
+        assert!(out.contains("line 14"));
+        assert!(!out.contains("line 15"));
+        assert_eq!(scroll.top, Some(10));
+    }
+
+    #[test]
+    fn messages_scroll_stays_put_as_output_grows() {
+        let mut scroll = scrolled_to(3);
+        render_lines(20, 7, &mut scroll);
+        let out = render_lines(30, 7, &mut scroll);
+        assert!(out.contains("line 3 "));
+        assert!(out.contains("line 7 "));
+        assert_eq!(scroll.top, Some(3));
+        assert_eq!(scroll.max_top, 25);
+    }
+
+    #[test]
+    fn messages_scroll_resumes_following_at_bottom() {
+        let mut scroll = scrolled_to(100);
+        let out = render_lines(20, 7, &mut scroll);
+        assert!(out.contains("line 19"));
+        assert_eq!(scroll.top, None);
+    }
+
+    #[test]
+    fn messages_scroll_clears_when_content_fits() {
+        let mut scroll = scrolled_to(0);
+        render_lines(2, 7, &mut scroll);
+        assert_eq!(scroll.top, None);
+    }
+
+    #[test]
+    fn search_jump_reveals_and_highlights_current_hit() {
+        let timeline: Vec<TimelineEntry> = (0..20)
+            .map(|i| TimelineEntry::user_message(format!("Line {}", i)))
+            .collect();
+        let mut search = SearchState::new();
+        search.push_char('3', &timeline);
+        assert_eq!(search.hits, vec![(3, 5), (13, 6)]);
+        search.next_hit();
+
+        let theme = Theme::dark();
+        let area = Rect::new(0, 0, 30, 7);
+        let mut buf = Buffer::empty(area);
+        let mut scroll = MessagesScroll::default();
+        MessagesWidget::new(&[], &timeline, AppState::Ready, &theme, Some(&search)).render(
+            area,
+            &mut buf,
+            &mut scroll,
+        );
+
+        // Line 13 sits a third of the way down: top = 13 - 5 / 3 = 12
+        assert_eq!(scroll.top, Some(12));
+        // Row 2 (border + one line above) holds the current hit
+        let row: String = (0..area.width).map(|x| buf[(x, 2)].symbol()).collect();
+        assert!(row.contains("Line 13"), "{:?}", row);
+        // Border and "› " prefix, then "Line 1" before the match
+        assert_eq!(buf[(9, 2)].bg, theme.warning);
+        assert_eq!(buf[(8, 2)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn highlight_counts_occurrences_across_spans() {
+        let line = Line::from(vec![
+            Span::raw("› "),
+            Span::raw("Foo bar foo"),
+            Span::raw(" FOO"),
+        ]);
+        let mut seen = 0;
+        let (line, has_current) = highlight_matches(
+            line,
+            "foo",
+            Style::default().bg(Color::Gray),
+            Style::default().bg(Color::Yellow),
+            Some(1),
+            &mut seen,
+        );
+        assert!(has_current);
+        assert_eq!(seen, 3);
+        let parts: Vec<(&str, Option<Color>)> = line
+            .spans
+            .iter()
+            .map(|s| (s.content.as_ref(), s.style.bg))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("› ", None),
+                ("Foo", Some(Color::Gray)),
+                (" bar ", None),
+                ("foo", Some(Color::Yellow)),
+                (" ", None),
+                ("FOO", Some(Color::Gray)),
+            ]
+        );
+    }
 }
@@ -5,6 +5,7 @@
 mod agent_mode;
 pub mod app_widget;
 pub mod input_box;
+pub mod markdown;
 pub mod messages;
 mod overlays;
 pub mod panels;
@@ -16,10 +17,11 @@ pub mod theme;
 use ratatui::prelude::*;
 
 pub use crate::app::AppState;
+use crate::search::SearchState;
 pub use crate::servers::{RemoteServerEntry, ServerConnectionStatus};
 pub use crate::Message;
 pub use input_box::InputBoxWidget;
-pub use messages::MessagesWidget;
+pub use messages::{MessagesScroll, MessagesWidget};
 pub use panels::{
     render_aux_panel, AuxContent, AuxContentKind, AuxPanelWidget, RemoteServer, ServerStatus,
 };
@@ -59,6 +61,8 @@ pub fn render_ui(
     overlay: Option<&Overlay>,
     remote_servers: &[RemoteServerEntry],
     theme: &Theme,
+    search: Option<&SearchState>,
+    scroll: &mut MessagesScroll,
 ) {
     let area = frame.area();
 
@@ -91,6 +95,8 @@ pub fn render_ui(
             messages,
             timeline,
             theme,
+            search,
+            scroll,
         );
         render_aux_panel(frame, h_chunks[1], aux_content, server_status);
     } else {
@@ -105,12 +111,21 @@ pub fn render_ui(
             messages,
             timeline,
             theme,
+            search,
+            scroll,
         );
     }
 
     // Status bar
     frame.render_widget(
-        StatusBarWidget::new(mode, state, server_status, model_name),
+        StatusBarWidget::new(
+            mode,
+            state,
+            server_status,
+            model_name,
+            scroll.top.is_some(),
+            search,
+        ),
         v_chunks[1],
     );
 
@@ -137,6 +152,8 @@ fn render_main_panel(
     messages: &[Message],
     timeline: &[crate::display::TimelineEntry],
     theme: &Theme,
+    search: Option<&SearchState>,
+    scroll: &mut MessagesScroll,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -147,9 +164,10 @@ fn render_main_panel(
         .split(area);
 
     // Messages
-    frame.render_widget(
-        MessagesWidget::new(messages, timeline, state, theme),
+    frame.render_stateful_widget(
+        MessagesWidget::new(messages, timeline, state, theme, search),
         chunks[0],
+        scroll,
     );
 
     // Input Box
@@ -174,6 +192,7 @@ pub fn render_app<R: crate::PollableRead, W: std::io::Write>(
     app: &crate::app::App<R, W>,
 ) {
     let theme = Theme::by_name(&app.agent.config().ui.theme);
+    let mut scroll = app.scroll;
     render_ui(
         frame,
         app.mode,
@@ -188,6 +207,8 @@ pub fn render_app<R: crate::PollableRead, W: std::io::Write>(
         app.overlay.as_ref(),
         app.agent.remote_servers(),
         &theme,
+        app.search.as_ref(),
+        &mut scroll,
     );
 }
 
@@ -231,6 +252,8 @@ mod tests {
                     None,
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -392,6 +415,8 @@ mod tests {
                     None,
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -429,6 +454,8 @@ mod tests {
                     })),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -464,6 +491,8 @@ mod tests {
                     Some(&Overlay::ProviderSelector { selected: 0 }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -539,6 +568,8 @@ mod tests {
                     }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -585,6 +616,8 @@ mod tests {
                     }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -638,6 +671,8 @@ mod tests {
                     }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -685,6 +720,8 @@ mod tests {
                     }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -736,6 +773,8 @@ mod tests {
                     }),
                     &[],
                     &theme,
+                    None,
+                    &mut MessagesScroll::default(),
                 );
             })
             .unwrap();
@@ -1 +1,92 @@
-//! Overlay components (model selector, secrets input)
+//! Overlay components (model selector, secrets input, command palette)
+
+use ratatui::{prelude::*, widgets::*};
+
+use crate::commands::filter_commands;
+use crate::ui::server_manager::centered_rect;
+
+/// Render the slash-command palette filtered by `query`
+pub fn render_command_palette(frame: &mut Frame, area: Rect, query: &str, selected: usize) {
+    let popup = centered_rect(50, 50, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(format!("/{} ", query))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+
+    let matches = filter_commands(query);
+    if matches.is_empty() {
+        let empty = Paragraph::new("No matching commands")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        frame.render_widget(empty, popup);
+    } else {
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|cmd| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<10}", cmd.name),
+                        Style::default().fg(Color::White),
+                    ),
+                    Span::styled(cmd.description, Style::default().fg(Color::DarkGray)),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(
+                Style::default()
+                    .add_modifier(Modifier::REVERSED)
+                    .fg(Color::Cyan),
+            )
+            .highlight_symbol("▶ ");
+
+        let mut state = ListState::default();
+        state.select(Some(selected.min(matches.len() - 1)));
+        frame.render_stateful_widget(list, popup, &mut state);
+    }
+
+    // Hints at bottom
+    let hints = Paragraph::new("↑↓ Navigate │ Enter Run │ Tab Complete │ Esc Close")
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    let hint_area = Rect::new(popup.x, popup.y + popup.height, popup.width, 1);
+    if hint_area.y < area.height {
+        frame.render_widget(hints, hint_area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn render(query: &str, selected: usize) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        terminal
+            .draw(|frame| render_command_palette(frame, frame.area(), query, selected))
+            .unwrap();
+        terminal.backend().to_string()
+    }
+
+    #[test]
+    fn palette_lists_filtered_commands() {
+        let output = render("mod", 0);
+        assert!(output.contains("/mod "));
+        assert!(output.contains("▶ /model"));
+        assert!(output.contains("/mode "));
+        assert!(!output.contains("/help"));
+    }
+
+    #[test]
+    fn palette_clamps_selection_and_reports_no_match() {
+        let output = render("mod", 9);
+        assert!(output.contains("▶ /mode "));
+
+        let output = render("zzz", 0);
+        assert!(output.contains("No matching commands"));
+    }
+}
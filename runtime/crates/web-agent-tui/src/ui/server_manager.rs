@@ -65,6 +65,11 @@ pub enum Overlay {
         input: String,
         error: Option<String>,
     },
+    /// Slash-command palette, filtered by the text typed after `/`
+    CommandPalette {
+        query: String,
+        selected: usize,
+    },
 }
 
 /// Target for a secret key input - what the key is for
@@ -659,6 +664,9 @@ pub fn render_overlay(
         } => {
             render_secret_key_input(frame, area, target, input, error.as_deref(), remote_servers);
         }
+        Overlay::CommandPalette { query, selected } => {
+            super::overlays::render_command_palette(frame, area, query, *selected);
+        }
     }
 }
 
@@ -1,6 +1,7 @@
 use ratatui::{prelude::*, widgets::Paragraph};
 
 use crate::app::AppState;
+use crate::search::SearchState;
 use crate::ui::{Mode, ServerStatus};
 
 pub struct StatusBarWidget<'a> {
@@ -8,6 +9,10 @@ pub struct StatusBarWidget<'a> {
     pub state: AppState,
     pub server_status: &'a ServerStatus,
     pub model_name: &'a str,
+    /// Messages pane is scrolled back from the latest output
+    pub scrolled_up: bool,
+    /// Active message search, shown as a prompt or match counter
+    pub search: Option<&'a SearchState>,
 }
 
 impl<'a> StatusBarWidget<'a> {
@@ -16,12 +21,16 @@ impl<'a> StatusBarWidget<'a> {
         state: AppState,
         server_status: &'a ServerStatus,
         model_name: &'a str,
+        scrolled_up: bool,
+        search: Option<&'a SearchState>,
     ) -> Self {
         Self {
             mode,
             state,
             server_status,
             model_name,
+            scrolled_up,
+            search,
         }
     }
 }
@@ -108,6 +117,38 @@ impl<'a> Widget for StatusBarWidget<'a> {
             spans.push(Span::styled(state_str, state_style));
         }
 
+        if self.scrolled_up {
+            spans.push(Span::styled(
+                " ↑ SCROLLED ", // U+2191 UPWARDS ARROW (1 cell)
+                Style::default().bg(Color::DarkGray).fg(Color::White),
+            ));
+        }
+
+        if let Some(search) = self.search {
+            let (text, style) = if search.editing {
+                (
+                    format!(" /{}▏", search.query), // U+258F LEFT ONE EIGHTH BLOCK (1 cell)
+                    Style::default().fg(Color::White),
+                )
+            } else if search.hits.is_empty() {
+                (
+                    format!(" no match: {} ", search.query),
+                    Style::default().fg(Color::Red),
+                )
+            } else {
+                (
+                    format!(
+                        " /{} [{}/{}] n/N ",
+                        search.query,
+                        search.current + 1,
+                        search.hits.len()
+                    ),
+                    Style::default().fg(Color::Yellow),
+                )
+            };
+            spans.push(Span::styled(text, style));
+        }
+
         spans.extend([
             Span::raw(" │ "),
             Span::styled(self.model_name, Style::default().fg(Color::Cyan)),
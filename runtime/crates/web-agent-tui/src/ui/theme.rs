@@ -27,6 +27,10 @@ pub struct Theme {
     pub assistant_msg: Color,
     /// System message color
     pub system_msg: Color,
+    /// Inline code color
+    pub code: Color,
+    /// Fenced code block background
+    pub code_bg: Color,
 }
 
 impl Theme {
@@ -54,6 +58,8 @@ impl Theme {
             user_msg: Color::Green,
             assistant_msg: Color::Cyan,
             system_msg: Color::Yellow,
+            code: Color::Rgb(187, 154, 247), // #bb9af7
+            code_bg: Color::Rgb(36, 40, 59), // #24283b
         }
     }
 
@@ -70,6 +76,8 @@ impl Theme {
             user_msg: Color::Rgb(0, 100, 0),
             assistant_msg: Color::Rgb(0, 80, 120),
             system_msg: Color::Rgb(120, 80, 0),
+            code: Color::Rgb(90, 74, 120),      // #5a4a78
+            code_bg: Color::Rgb(196, 198, 205), // #c4c6cd
         }
     }
 
@@ -91,6 +99,8 @@ impl Theme {
             user_msg: Color::Rgb(184, 187, 38),       // green
             assistant_msg: Color::Rgb(131, 165, 152), // aqua
             system_msg: Color::Rgb(250, 189, 47),     // yellow
+            code: Color::Rgb(211, 134, 155),          // purple
+            code_bg: Color::Rgb(60, 56, 54),          // bg1
         }
     }
 
@@ -107,6 +117,8 @@ impl Theme {
             user_msg: Color::Rgb(166, 227, 161),      // green
             assistant_msg: Color::Rgb(137, 180, 250), // blue
             system_msg: Color::Rgb(249, 226, 175),    // yellow
+            code: Color::Rgb(203, 166, 247),          // mauve
+            code_bg: Color::Rgb(49, 50, 68),          // surface0
         }
     }
 }
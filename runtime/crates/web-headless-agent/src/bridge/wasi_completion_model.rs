@@ -46,6 +46,14 @@ pub fn create_openai_client(
     agent_bridge::create_openai_client(WasiHttpClient::new(), api_key, base_url)
 }
 
+/// Create a client for a local Ollama server (base URL defaults to localhost:11434)
+pub fn create_ollama_client(
+    api_key: &str,
+    base_url: Option<&str>,
+) -> Result<OpenAIClient, rig::http_client::Error> {
+    agent_bridge::create_ollama_client(WasiHttpClient::new(), api_key, base_url)
+}
+
 /// Create a Gemini client with optional base URL
 pub fn create_gemini_client(
     api_key: &str,
@@ -20,8 +20,8 @@ use std::future::IntoFuture;
 
 use bridge::mcp_client::SandboxMcpClient;
 use bridge::wasi_completion_model::{
-    create_anthropic_client, create_gemini_client, create_openai_client, AnthropicModel,
-    GeminiModel, OpenAIModel,
+    create_anthropic_client, create_gemini_client, create_ollama_client, create_openai_client,
+    AnthropicModel, GeminiModel, OpenAIModel,
 };
 use bridge::HeadlessHttpClient;
 
@@ -83,11 +83,13 @@ where
 // Agent Types - use bindings for WIT types
 // ============================================================================
 
-use bindings::{AgentConfig, AgentEvent, AgentHandle, Message, MessageRole};
+use bindings::{AgentConfig, AgentEvent, AgentHandle, AgentProtocol, Message, MessageRole};
 
-/// Build tool server aggregating tools from multiple MCP clients
+/// Build tool server aggregating tools from multiple MCP clients.
+/// With an approval gate, every tool waits for approval before running.
 fn build_tool_server(
     mcp_clients: Vec<Arc<SandboxMcpClient>>,
+    approval_gate: Option<&agent_bridge::ToolApprovalGate>,
 ) -> Result<rig::tool::server::ToolServerHandle, String> {
     let mut tool_set = rig::tool::ToolSet::default();
 
@@ -97,7 +99,12 @@ fn build_tool_server(
         // Get tools from this client and add them individually
         let tools = agent_bridge::McpToolAdapter::from_transport(client)?;
         for tool in tools {
-            tool_set.add_tool(tool);
+            match approval_gate {
+                Some(gate) => {
+                    tool_set.add_tool(agent_bridge::ApprovalGatedTool::new(tool, gate.clone()))
+                }
+                None => tool_set.add_tool(tool),
+            }
         }
     }
 
@@ -203,6 +210,14 @@ You:
 4. Respond: "Task complete. Calculator written and tested successfully."
 "##;
 
+/// System preamble for plain chat, without the PLAN/EXECUTE protocol
+const CHAT_PREAMBLE: &str = r##"You are a helpful assistant running in a browser-based sandbox.
+
+Answer the user's messages directly and concisely. When tools are available and a
+request needs them (reading or writing files, running commands), use them, then
+report what you found or changed.
+"##;
+
 /// Agent with tools (uses multi_turn for tool loop)
 enum AgentWithTools {
     Anthropic(Agent<AnthropicModel>),
@@ -233,25 +248,54 @@ struct HeadlessAgent {
     active_stream: Option<agent_bridge::ActiveStream>,
     /// Track last tool activity for event emission
     last_tool_activity: Option<String>,
+    /// Retry policy for transient provider errors
+    retry_policy: agent_bridge::RetryPolicy,
+    /// Message and history of the in-flight request, to reconnect on retry
+    pending_request: Option<(String, Vec<RigMessage>)>,
+    /// Retries made so far for the in-flight request
+    retry_attempt: u32,
+    /// When to reconnect after a transient error
+    retry_at: Option<std::time::Instant>,
+    /// Whether the in-flight stream has produced anything. Once it has, tools
+    /// may have run, so an error is not retried.
+    stream_progressed: bool,
+    /// Set when `require_tool_approval` is on: tool calls wait here
+    approval_gate: Option<agent_bridge::ToolApprovalGate>,
+    /// Id of the approval request already reported to the app
+    announced_approval: Option<u64>,
+    /// Protocol selected at creation; plan/execute need PlanExecute
+    protocol: AgentProtocol,
 }
 
 impl HeadlessAgent {
     fn new(config: AgentConfig) -> Result<Self, String> {
         let base_url = config.base_url.as_deref();
 
-        // Build preamble: override completely OR add to default
+        let protocol = config.protocol.unwrap_or(AgentProtocol::PlanExecute);
+        let base_preamble = match protocol {
+            AgentProtocol::PlanExecute => DEFAULT_PREAMBLE,
+            AgentProtocol::Chat => CHAT_PREAMBLE,
+        };
+
+        // Build preamble: override completely OR add to the protocol's preamble
         let preamble = if let Some(override_preamble) = &config.preamble_override {
             // Complete override - use only the override text
             override_preamble.clone()
         } else if let Some(additional) = &config.preamble {
             // Add to default
-            format!("{}\n\n{}", DEFAULT_PREAMBLE, additional)
+            format!("{}\n\n{}", base_preamble, additional)
         } else {
             // Just use default
-            DEFAULT_PREAMBLE.to_string()
+            base_preamble.to_string()
         };
 
         let max_turns = config.max_turns.unwrap_or(25) as usize;
+        let retry_policy =
+            agent_bridge::RetryPolicy::from_config(config.max_retries, config.retry_base_delay_ms);
+        let approval_gate = config
+            .require_tool_approval
+            .unwrap_or(false)
+            .then(agent_bridge::ToolApprovalGate::new);
 
         // Check if we have MCP servers
         let provider = if let Some(servers) = config.mcp_servers.as_ref() {
@@ -276,6 +320,15 @@ impl HeadlessAgent {
                             .build();
                         SimpleAgent::Gemini(agent)
                     }
+                    "ollama" => {
+                        let client = create_ollama_client(&config.api_key, base_url)
+                            .map_err(|e| e.to_string())?;
+                        let model = OpenAIModel::new(client, &config.model);
+                        let agent = rig::agent::AgentBuilder::new(model)
+                            .preamble(&preamble)
+                            .build();
+                        SimpleAgent::OpenAI(agent)
+                    }
                     _ => {
                         let client = create_openai_client(&config.api_key, base_url)
                             .map_err(|e| e.to_string())?;
@@ -295,7 +348,7 @@ impl HeadlessAgent {
                     .collect();
 
                 // Build tool server with aggregated tools from all servers
-                let tool_handle = build_tool_server(mcp_clients)
+                let tool_handle = build_tool_server(mcp_clients, approval_gate.as_ref())
                     .map_err(|e| format!("Failed to build tool server: {}", e))?;
 
                 let agent = match config.provider.as_str() {
@@ -319,6 +372,16 @@ impl HeadlessAgent {
                             .build();
                         AgentWithTools::Gemini(agent)
                     }
+                    "ollama" => {
+                        let client = create_ollama_client(&config.api_key, base_url)
+                            .map_err(|e| e.to_string())?;
+                        let model = OpenAIModel::new(client, &config.model);
+                        let agent = rig::agent::AgentBuilder::new(model)
+                            .preamble(&preamble)
+                            .tool_server_handle(tool_handle)
+                            .build();
+                        AgentWithTools::OpenAI(agent)
+                    }
                     _ => {
                         // Default to OpenAI-compatible
                         let client = create_openai_client(&config.api_key, base_url)
@@ -355,6 +418,15 @@ impl HeadlessAgent {
                         .build();
                     SimpleAgent::Gemini(agent)
                 }
+                "ollama" => {
+                    let client = create_ollama_client(&config.api_key, base_url)
+                        .map_err(|e| e.to_string())?;
+                    let model = OpenAIModel::new(client, &config.model);
+                    let agent = rig::agent::AgentBuilder::new(model)
+                        .preamble(&preamble)
+                        .build();
+                    SimpleAgent::OpenAI(agent)
+                }
                 _ => {
                     let client = create_openai_client(&config.api_key, base_url)
                         .map_err(|e| e.to_string())?;
@@ -377,6 +449,14 @@ impl HeadlessAgent {
             max_turns,
             active_stream: None,
             last_tool_activity: None,
+            retry_policy,
+            pending_request: None,
+            retry_attempt: 0,
+            retry_at: None,
+            stream_progressed: false,
+            approval_gate,
+            announced_approval: None,
+            protocol,
         })
     }
 
@@ -388,6 +468,9 @@ impl HeadlessAgent {
         // Clear any stale events/state from previous stream
         self.events.clear();
         self.last_tool_activity = None;
+        self.retry_attempt = 0;
+        self.retry_at = None;
+        self.stream_progressed = false;
 
         // Add user message to conversation history
         self.conversation
@@ -419,15 +502,23 @@ impl HeadlessAgent {
 
         // Create the active stream (but don't block on it)
         let active_stream = match &self.provider {
-            AgentProvider::WithTools(agent) => Some(create_active_stream_with_tools(
-                agent,
-                message,
-                history,
-                self.max_turns,
-            )),
+            AgentProvider::WithTools(agent) => {
+                self.pending_request = Some((message.to_string(), history.clone()));
+                Some(create_active_stream_with_tools(
+                    agent,
+                    message,
+                    history,
+                    self.max_turns,
+                ))
+            }
             AgentProvider::Simple(agent) => {
                 // For simple agents, we still block since they don't need tool loops
-                let result = run_simple_agent(agent, message);
+                let result = run_simple_agent_with_retry(
+                    agent,
+                    message,
+                    self.retry_policy,
+                    &mut self.events,
+                );
                 match result {
                     Ok(text) => {
                         self.events.push_back(AgentEvent::StreamChunk(text.clone()));
@@ -453,6 +544,7 @@ impl HeadlessAgent {
     /// Planning phase - sends user request with [ACTION: PLAN] marker
     /// Agent should analyze and write /plan.md, then stop and wait for approval
     fn plan(&mut self, user_request: &str) -> Result<(), String> {
+        self.require_plan_execute("plan")?;
         let message = format!("[ACTION: PLAN]\n{}", user_request);
         self.send(&message)
     }
@@ -460,8 +552,19 @@ impl HeadlessAgent {
     /// Execution phase - sends [ACTION: EXECUTE] marker
     /// Agent should read /plan.md and execute all steps
     fn execute(&mut self) -> Result<(), String> {
+        self.require_plan_execute("execute")?;
         self.send("[ACTION: EXECUTE]")
     }
+
+    fn require_plan_execute(&self, method: &str) -> Result<(), String> {
+        match self.protocol {
+            AgentProtocol::PlanExecute => Ok(()),
+            AgentProtocol::Chat => Err(format!(
+                "{}() is only valid with the plan-execute protocol; this agent uses chat (use send-message instead)",
+                method
+            )),
+        }
+    }
 }
 
 // ============================================================================
@@ -510,6 +613,42 @@ fn create_active_stream_with_tools(
     agent_bridge::ActiveStream::from_future(connect_future)
 }
 
+/// Run a simple agent, retrying transient errors with backoff. Blocks between
+/// attempts and queues a `Retrying` event for each retry.
+fn run_simple_agent_with_retry(
+    agent: &SimpleAgent,
+    message: &str,
+    policy: agent_bridge::RetryPolicy,
+    events: &mut std::collections::VecDeque<AgentEvent>,
+) -> Result<String, String> {
+    let mut attempt = 0;
+    loop {
+        match run_simple_agent(agent, message) {
+            Err(e) if policy.should_retry(attempt + 1, &e) => {
+                attempt += 1;
+                let delay = policy.delay(attempt);
+                events.push_back(retrying_event(policy, attempt, delay, e));
+                std::thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn retrying_event(
+    policy: agent_bridge::RetryPolicy,
+    attempt: u32,
+    delay: std::time::Duration,
+    error: String,
+) -> AgentEvent {
+    AgentEvent::Retrying(bindings::RetryInfo {
+        attempt,
+        max_retries: policy.max_retries,
+        delay_ms: delay.as_millis().min(u32::MAX as u128) as u32,
+        error,
+    })
+}
+
 fn run_simple_agent(agent: &SimpleAgent, message: &str) -> Result<String, String> {
     match agent {
         SimpleAgent::Anthropic(a) => wasm_block_on(a.prompt(message).into_future())
@@ -530,10 +669,42 @@ impl HeadlessAgent {
             return Some(event);
         }
 
+        // Waiting out a retry backoff: reconnect once it has elapsed
+        if let Some(retry_at) = self.retry_at {
+            if std::time::Instant::now() < retry_at {
+                return None;
+            }
+            self.retry_at = None;
+            if let (AgentProvider::WithTools(agent), Some((message, history))) =
+                (&self.provider, &self.pending_request)
+            {
+                self.active_stream = Some(create_active_stream_with_tools(
+                    agent,
+                    message,
+                    history.clone(),
+                    self.max_turns,
+                ));
+            }
+        }
+
         // If we have an active stream, poll it
         if let Some(stream) = &mut self.active_stream {
             let result = stream.poll_once();
 
+            // A tool call is waiting for approval: the stream stays pending
+            // until approve_tool/deny_tool, so report the request once
+            if let Some(request) = self.approval_gate.as_ref().and_then(|g| g.pending()) {
+                if self.announced_approval != Some(request.id) {
+                    self.announced_approval = Some(request.id);
+                    self.events.push_back(AgentEvent::ToolApprovalRequest(
+                        bindings::ToolApprovalInfo {
+                            name: request.name,
+                            arguments: request.arguments,
+                        },
+                    ));
+                }
+            }
+
             // Check for tool activity updates (like TUI does)
             let activity = stream.buffer().get_tool_activity();
             if activity != self.last_tool_activity {
@@ -554,6 +725,7 @@ impl HeadlessAgent {
 
             match result {
                 PollResult::Chunk => {
+                    self.stream_progressed = true;
                     let content = stream.buffer().get_content();
                     self.events.push_back(AgentEvent::StreamChunk(content));
                 }
@@ -565,18 +737,43 @@ impl HeadlessAgent {
                     let content = stream.buffer().get_content();
                     self.events
                         .push_back(AgentEvent::StreamChunk(content.clone()));
+                    if let Some(usage) = stream.buffer().get_usage() {
+                        self.events
+                            .push_back(AgentEvent::Usage(bindings::TokenUsage {
+                                input_tokens: saturate_u32(usage.input_tokens),
+                                output_tokens: saturate_u32(usage.output_tokens),
+                            }));
+                    }
                     self.events
                         .push_back(AgentEvent::StreamComplete(content.clone()));
                     self.conversation
                         .append_turn(agent_bridge::ConversationTurn::assistant(&content));
                     self.is_streaming = false;
                     self.active_stream = None;
+                    self.pending_request = None;
                     self.events.push_back(AgentEvent::Ready);
                 }
+                PollResult::Error(e)
+                    if !self.stream_progressed
+                        && self.retry_policy.should_retry(self.retry_attempt + 1, &e) =>
+                {
+                    // Transient failure before any output: reconnect after a backoff
+                    self.retry_attempt += 1;
+                    let delay = self.retry_policy.delay(self.retry_attempt);
+                    self.events.push_back(retrying_event(
+                        self.retry_policy,
+                        self.retry_attempt,
+                        delay,
+                        e,
+                    ));
+                    self.retry_at = Some(std::time::Instant::now() + delay);
+                    self.active_stream = None;
+                }
                 PollResult::Error(e) => {
                     self.events.push_back(AgentEvent::StreamError(e));
                     self.is_streaming = false;
                     self.active_stream = None;
+                    self.pending_request = None;
                     self.events.push_back(AgentEvent::Ready);
                 }
             }
@@ -588,10 +785,31 @@ impl HeadlessAgent {
         None
     }
 
+    /// Let the tool call awaiting approval run
+    fn approve_tool(&mut self) -> Result<(), String> {
+        match &self.approval_gate {
+            Some(gate) if gate.approve() => Ok(()),
+            _ => Err("No tool call awaiting approval".to_string()),
+        }
+    }
+
+    /// Reject the tool call awaiting approval; the model sees `reason`
+    fn deny_tool(&mut self, reason: &str) -> Result<(), String> {
+        match &self.approval_gate {
+            Some(gate) if gate.deny(reason) => Ok(()),
+            _ => Err("No tool call awaiting approval".to_string()),
+        }
+    }
+
     fn cancel(&mut self) {
+        if let Some(gate) = &self.approval_gate {
+            gate.deny_all("cancelled");
+        }
         if self.is_streaming {
             self.is_streaming = false;
             self.active_stream = None;
+            self.pending_request = None;
+            self.retry_at = None;
             self.events.clear();
             self.events.push_back(AgentEvent::Ready);
         }
@@ -616,6 +834,24 @@ impl HeadlessAgent {
     fn clear_history(&mut self) {
         self.conversation.clear();
     }
+
+    fn export_history(&self) -> String {
+        self.conversation.export_json()
+    }
+
+    /// Replace the conversation with previously exported history
+    fn import_history(&mut self, json: &str) -> Result<(), String> {
+        if self.is_streaming {
+            return Err("Cannot import history while streaming".to_string());
+        }
+        self.conversation = agent_bridge::ConversationHistory::import_json(json)?;
+        Ok(())
+    }
+}
+
+/// Token counts cross the WIT boundary as u32 (a JS number, not a BigInt)
+fn saturate_u32(n: u64) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
 }
 
 // Note: wasm_block_on is now imported from agent_bridge
@@ -660,6 +896,26 @@ impl Guest for HeadlessAgentComponent {
         });
     }
 
+    fn approve_tool(handle: AgentHandle) -> Result<(), String> {
+        with_storage(|s| {
+            if let Some(agent) = s.get_mut(handle) {
+                agent.approve_tool()
+            } else {
+                Err("Invalid agent handle".to_string())
+            }
+        })
+    }
+
+    fn deny_tool(handle: AgentHandle, reason: String) -> Result<(), String> {
+        with_storage(|s| {
+            if let Some(agent) = s.get_mut(handle) {
+                agent.deny_tool(&reason)
+            } else {
+                Err("Invalid agent handle".to_string())
+            }
+        })
+    }
+
     fn plan(handle: AgentHandle, message: String) -> Result<(), String> {
         with_storage(|s| {
             if let Some(agent) = s.get_mut(handle) {
@@ -696,6 +952,26 @@ impl Guest for HeadlessAgentComponent {
         });
     }
 
+    fn export_history(handle: AgentHandle) -> Result<String, String> {
+        with_storage(|s| {
+            if let Some(agent) = s.get_mut(handle) {
+                Ok(agent.export_history())
+            } else {
+                Err("Invalid agent handle".to_string())
+            }
+        })
+    }
+
+    fn import_history(handle: AgentHandle, json: String) -> Result<(), String> {
+        with_storage(|s| {
+            if let Some(agent) = s.get_mut(handle) {
+                agent.import_history(&json)
+            } else {
+                Err("Invalid agent handle".to_string())
+            }
+        })
+    }
+
     fn list_providers() -> Vec<bindings::ProviderInfo> {
         agent_bridge::PROVIDERS
             .iter()
@@ -14,10 +14,15 @@ use syn::{
 ///
 /// # Arguments
 /// - `description` - The tool description shown to LLMs
+/// - `params(name = "...", ...)` - Optional descriptions for the tool's parameters;
+///   parameters left out are described as "The <name> parameter"
 ///
 /// # Example
 /// ```ignore
-/// #[mcp_tool(description = "Execute TypeScript code")]
+/// #[mcp_tool(
+///     description = "Execute TypeScript code",
+///     params(code = "TypeScript source to run")
+/// )]
 /// fn run_typescript(&mut self, code: String) -> ToolResult {
 ///     // implementation
 /// }
@@ -48,9 +53,10 @@ pub fn mcp_tool_marker(attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
-/// Parsed arguments from #[mcp_tool(description = "...")]
+/// Parsed arguments from #[mcp_tool(description = "...", params(name = "...", ...))]
 struct ToolAttrArgs {
     description: LitStr,
+    params: Vec<(Ident, LitStr)>,
 }
 
 impl syn::parse::Parse for ToolAttrArgs {
@@ -61,7 +67,37 @@ impl syn::parse::Parse for ToolAttrArgs {
         }
         input.parse::<syn::Token![=]>()?;
         let description: LitStr = input.parse()?;
-        Ok(ToolAttrArgs { description })
+
+        let mut params = Vec::new();
+        if input.peek(syn::Token![,]) {
+            input.parse::<syn::Token![,]>()?;
+        }
+        if !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            if ident != "params" {
+                return Err(syn::Error::new(ident.span(), "expected `params`"));
+            }
+            let content;
+            syn::parenthesized!(content in input);
+            let entries = content.parse_terminated(
+                |entry| {
+                    let name: Ident = entry.parse()?;
+                    entry.parse::<syn::Token![=]>()?;
+                    let desc: LitStr = entry.parse()?;
+                    Ok((name, desc))
+                },
+                syn::Token![,],
+            )?;
+            params.extend(entries);
+            if input.peek(syn::Token![,]) {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(ToolAttrArgs {
+            description,
+            params,
+        })
     }
 }
 
@@ -71,14 +107,9 @@ impl ToTokens for ToolAttrArgs {
     }
 }
 
-/// Parses the description from a #[mcp_tool(description = "...")] attribute
-fn parse_tool_description(attr: &Attribute) -> Option<String> {
-    if !attr.path().is_ident("mcp_tool") {
-        return None;
-    }
-
-    let args: syn::Result<ToolAttrArgs> = attr.parse_args();
-    args.ok().map(|a| a.description.value())
+/// Parses a #[mcp_tool(description = "...", params(...))] attribute
+fn parse_tool_attr(attr: &Attribute) -> syn::Result<ToolAttrArgs> {
+    attr.parse_args()
 }
 
 /// Attribute macro to generate the MCP tool router for an impl block.
@@ -102,6 +133,7 @@ pub fn mcp_tool_router(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     // Collect tool information
     let mut tools: Vec<ToolInfo> = Vec::new();
+    let mut errors: Vec<proc_macro2::TokenStream> = Vec::new();
     let mut methods: Vec<ImplItemFn> = Vec::new();
     let mut other_items: Vec<ImplItem> = Vec::new();
 
@@ -112,16 +144,34 @@ pub fn mcp_tool_router(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 let tool_attr = method.attrs.iter().find(|a| a.path().is_ident("mcp_tool"));
 
                 if let Some(attr) = tool_attr {
-                    if let Some(description) = parse_tool_description(attr) {
-                        // Extract parameter info
-                        let params = extract_params(&method);
-
-                        tools.push(ToolInfo {
-                            name: method.sig.ident.to_string(),
-                            description,
-                            params,
-                            method_ident: method.sig.ident.clone(),
-                        });
+                    match parse_tool_attr(attr) {
+                        Ok(args) => {
+                            // Extract parameter info
+                            let mut params = extract_params(&method);
+                            for (name, desc) in &args.params {
+                                match params.iter_mut().find(|p| name == &p.name) {
+                                    Some(param) => param.description = Some(desc.value()),
+                                    None => errors.push(
+                                        syn::Error::new(
+                                            name.span(),
+                                            format!(
+                                                "`{}` is not a parameter of `{}`",
+                                                name, method.sig.ident
+                                            ),
+                                        )
+                                        .to_compile_error(),
+                                    ),
+                                }
+                            }
+
+                            tools.push(ToolInfo {
+                                name: method.sig.ident.to_string(),
+                                description: args.description.value(),
+                                params,
+                                method_ident: method.sig.ident.clone(),
+                            });
+                        }
+                        Err(e) => errors.push(e.to_compile_error()),
                     }
 
                     // Remove the mcp_tool attribute from the output
@@ -146,6 +196,8 @@ pub fn mcp_tool_router(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let output = quote! {
+        #(#errors)*
+
         impl #impl_generics #self_ty #ty_generics #where_clause {
             #(#methods)*
         }
@@ -176,7 +228,35 @@ struct ToolInfo {
 struct ParamInfo {
     name: String,
     param_type: String,
+    /// Scalar Rust type (the `T` of `Option<T>`, or the element of a `Vec`), e.g. `u32`
+    rust_type: String,
+    /// JSON schema type of array elements, for `Vec<T>` parameters
+    item_type: Option<String>,
     is_optional: bool,
+    /// From `params(...)` in `#[mcp_tool]`
+    description: Option<String>,
+    /// A `&mut ProgressReporter` parameter: passed through from the request,
+    /// not part of the tool's input schema
+    is_progress: bool,
+}
+
+/// A parameter type as seen by the JSON schema and the argument extraction
+struct ParsedType {
+    json_type: String,
+    rust_type: String,
+    item_type: Option<String>,
+    is_optional: bool,
+}
+
+impl ParsedType {
+    fn scalar(json_type: &str, rust_type: &str) -> Self {
+        Self {
+            json_type: json_type.to_string(),
+            rust_type: rust_type.to_string(),
+            item_type: None,
+            is_optional: false,
+        }
+    }
 }
 
 fn extract_params(method: &ImplItemFn) -> Vec<ParamInfo> {
@@ -197,12 +277,16 @@ fn extract_params(method: &ImplItemFn) -> Vec<ParamInfo> {
                         return None;
                     }
 
-                    let (param_type, is_optional) = parse_type(&pat_type.ty);
+                    let parsed = parse_type(&pat_type.ty);
 
                     Some(ParamInfo {
                         name,
-                        param_type,
-                        is_optional,
+                        param_type: parsed.json_type,
+                        rust_type: parsed.rust_type,
+                        item_type: parsed.item_type,
+                        is_optional: parsed.is_optional,
+                        description: None,
+                        is_progress: is_progress_reporter(&pat_type.ty),
                     })
                 }
                 FnArg::Receiver(_) => None,
@@ -211,7 +295,25 @@ fn extract_params(method: &ImplItemFn) -> Vec<ParamInfo> {
         .collect()
 }
 
-fn parse_type(ty: &Type) -> (String, bool) {
+/// Whether a parameter is declared as `&mut ProgressReporter`
+fn is_progress_reporter(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) if reference.mutability.is_some() => {
+            matches!(
+                &*reference.elem,
+                Type::Path(type_path)
+                    if type_path
+                        .path
+                        .segments
+                        .last()
+                        .is_some_and(|seg| seg.ident == "ProgressReporter")
+            )
+        }
+        _ => false,
+    }
+}
+
+fn parse_type(ty: &Type) -> ParsedType {
     match ty {
         Type::Path(type_path) => {
             let path = &type_path.path;
@@ -219,33 +321,114 @@ fn parse_type(ty: &Type) -> (String, bool) {
 
             if let Some(seg) = segment {
                 let ident = seg.ident.to_string();
+                let inner_ty = match &seg.arguments {
+                    syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                        Some(syn::GenericArgument::Type(inner_ty)) => Some(inner_ty),
+                        _ => None,
+                    },
+                    _ => None,
+                };
 
                 // Check for Option<T>
                 if ident == "Option" {
-                    if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
-                        if let Some(syn::GenericArgument::Type(inner_ty)) = args.args.first() {
-                            let (inner_type, _) = parse_type(inner_ty);
-                            return (inner_type, true);
-                        }
-                    }
-                    return ("string".to_string(), true);
+                    let mut parsed = inner_ty
+                        .map(parse_type)
+                        .unwrap_or_else(|| ParsedType::scalar("string", "String"));
+                    parsed.is_optional = true;
+                    return parsed;
+                }
+
+                // Vec<T> is an array of scalars
+                if ident == "Vec" {
+                    let item = inner_ty
+                        .map(parse_type)
+                        .unwrap_or_else(|| ParsedType::scalar("string", "String"));
+                    return ParsedType {
+                        json_type: "array".to_string(),
+                        rust_type: item.rust_type,
+                        item_type: Some(item.json_type),
+                        is_optional: false,
+                    };
                 }
 
                 // Map Rust types to JSON schema types
                 let json_type = match ident.as_str() {
                     "String" | "str" => "string",
-                    "i32" | "i64" | "u32" | "u64" | "usize" | "isize" => "integer",
+                    "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64"
+                    | "usize" => "integer",
                     "f32" | "f64" => "number",
                     "bool" => "boolean",
                     _ => "string",
                 };
 
-                return (json_type.to_string(), false);
+                return ParsedType::scalar(json_type, &ident);
             }
 
-            ("string".to_string(), false)
+            ParsedType::scalar("string", "String")
+        }
+        _ => ParsedType::scalar("string", "String"),
+    }
+}
+
+/// Expression reading a parameter as `Result<Option<T>, String>` in its
+/// declared Rust type: `Ok(None)` when absent or null, and an error naming
+/// the expected type when the value has the wrong type or is out of range.
+fn optional_argument_value(p: &ParamInfo) -> proc_macro2::TokenStream {
+    let param_name = &p.name;
+    let rust_type = format_ident!("{}", p.rust_type);
+    let integer_range = quote! {
+        format!("an integer from {} to {}", #rust_type::MIN, #rust_type::MAX)
+    };
+    let (convert, expected) = match (p.param_type.as_str(), p.rust_type.as_str()) {
+        ("boolean", _) => (quote! { value.as_bool() }, quote! { "a boolean" }),
+        ("integer", "i64") => (quote! { value.as_i64() }, integer_range),
+        ("integer", "u64") => (quote! { value.as_u64() }, integer_range),
+        ("integer", unsigned) if unsigned.starts_with('u') => (
+            quote! { value.as_u64().and_then(|n| #rust_type::try_from(n).ok()) },
+            integer_range,
+        ),
+        ("integer", _) => (
+            quote! { value.as_i64().and_then(|n| #rust_type::try_from(n).ok()) },
+            integer_range,
+        ),
+        ("number", "f64") => (quote! { value.as_f64() }, quote! { "a number" }),
+        ("number", _) => (
+            quote! { value.as_f64().map(|n| n as #rust_type) },
+            quote! { "a number" },
+        ),
+        ("array", "String") => (
+            quote! {
+                value.as_array().and_then(|items| {
+                    items
+                        .iter()
+                        .map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect::<Option<Vec<String>>>()
+                })
+            },
+            quote! { "an array of strings" },
+        ),
+        ("array", _) => {
+            return quote! {
+                compile_error!("mcp_tool array parameters must be Vec<String>")
+            }
+        }
+        _ => (
+            quote! { value.as_str().map(|s| s.to_string()) },
+            quote! { "a string" },
+        ),
+    };
+    quote! {
+        match arguments.inner().get(#param_name) {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(value) => match #convert {
+                Some(v) => Ok(Some(v)),
+                None => Err(format!(
+                    "Invalid parameter {}: expected {}",
+                    #param_name,
+                    #expected
+                )),
+            },
         }
-        _ => ("string".to_string(), false),
     }
 }
 
@@ -255,16 +438,28 @@ fn generate_list_tools(tools: &[ToolInfo]) -> proc_macro2::TokenStream {
         let description = &tool.description;
 
         // Build properties object
-        let properties = tool.params.iter().map(|p| {
+        let properties = tool.params.iter().filter(|p| !p.is_progress).map(|p| {
             let param_name = &p.name;
             let param_type = &p.param_type;
-            let param_desc = format!("The {} parameter", param_name);
-
-            quote! {
-                #param_name: {
-                    "type": #param_type,
-                    "description": #param_desc
-                }
+            let param_desc = p
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("The {} parameter", param_name));
+
+            match &p.item_type {
+                Some(item_type) => quote! {
+                    #param_name: {
+                        "type": #param_type,
+                        "items": { "type": #item_type },
+                        "description": #param_desc
+                    }
+                },
+                None => quote! {
+                    #param_name: {
+                        "type": #param_type,
+                        "description": #param_desc
+                    }
+                },
             }
         });
 
@@ -272,7 +467,7 @@ fn generate_list_tools(tools: &[ToolInfo]) -> proc_macro2::TokenStream {
         let required: Vec<_> = tool
             .params
             .iter()
-            .filter(|p| !p.is_optional)
+            .filter(|p| !p.is_optional && !p.is_progress)
             .map(|p| &p.name)
             .collect();
 
@@ -309,41 +504,70 @@ fn generate_call_tool(tools: &[ToolInfo]) -> proc_macro2::TokenStream {
         let method_ident = &tool.method_ident;
 
         // Generate argument extraction using Arguments methods
-        let arg_extractions = tool.params.iter().map(|p| {
+        let arg_extractions = tool.params.iter().filter(|p| !p.is_progress).map(|p| {
             let param_name = &p.name;
             let param_ident = format_ident!("{}", param_name);
 
+            let value = optional_argument_value(p);
+
             if p.is_optional {
                 quote! {
-                    let #param_ident = arguments.get_optional_string(#param_name);
+                    let #param_ident = match #value {
+                        Ok(v) => v,
+                        Err(e) => return crate::mcp_server::ToolResult::error(e),
+                    };
                 }
             } else {
                 quote! {
-                    let #param_ident = match arguments.get_string(#param_name) {
-                        Ok(v) => v,
+                    let #param_ident = match #value {
+                        Ok(Some(v)) => v,
+                        Ok(None) => {
+                            return crate::mcp_server::ToolResult::error(format!(
+                                "Missing required parameter: {}",
+                                #param_name
+                            ))
+                        }
                         Err(e) => return crate::mcp_server::ToolResult::error(e),
                     };
                 }
             }
         });
 
-        // Generate method call with arguments
-        let arg_idents: Vec<_> = tool
+        // Generate method call with arguments; the reporter goes in its declared position
+        let call_args: Vec<_> = tool
             .params
             .iter()
-            .map(|p| format_ident!("{}", p.name))
+            .map(|p| {
+                if p.is_progress {
+                    quote! { progress }
+                } else {
+                    let param_ident = format_ident!("{}", p.name);
+                    quote! { #param_ident }
+                }
+            })
             .collect();
 
         quote! {
             #name => {
                 #(#arg_extractions)*
-                self.#method_ident(#(#arg_idents),*)
+                self.#method_ident(#(#call_args),*)
             }
         }
     });
 
     quote! {
         fn call_tool(&mut self, name: &str, arguments: crate::mcp_server::Arguments) -> crate::mcp_server::ToolResult {
+            self.call_tool_with_progress(name, arguments, &mut crate::mcp_server::ProgressReporter::none())
+        }
+
+        fn call_tool_with_progress(
+            &mut self,
+            name: &str,
+            arguments: crate::mcp_server::Arguments,
+            progress: &mut crate::mcp_server::ProgressReporter<'_>,
+        ) -> crate::mcp_server::ToolResult {
+            // Only tools that declare a reporter use it
+            let _ = &progress;
             match name {
                 #(#match_arms)*
                 _ => crate::mcp_server::ToolResult::error(format!("Unknown tool: {}", name)),
@@ -0,0 +1,207 @@
+//! Compile tests for `#[mcp_tool_router]`: the generated code must build and
+//! dispatch against the real mcp-server-core types.
+
+use mcp_server::{Arguments, McpServer, ProgressReporter, ToolResult};
+use runtime_macros::mcp_tool_router;
+use serde_json::json;
+
+// Generated code refers to `crate::mcp_server`, as in the runtime crate
+mod mcp_server {
+    pub use mcp_server_core::*;
+}
+
+struct TestServer;
+
+#[mcp_tool_router]
+impl TestServer {
+    #[mcp_tool(description = "Join file names")]
+    fn join_files(&mut self, files: Vec<String>, separator: Option<String>) -> ToolResult {
+        ToolResult::text(files.join(&separator.unwrap_or_else(|| ",".to_string())))
+    }
+
+    #[mcp_tool(description = "Count tags")]
+    fn count_tags(&mut self, tags: Option<Vec<String>>) -> ToolResult {
+        ToolResult::text(format!("{:?}", tags.map(|t| t.len())))
+    }
+
+    #[mcp_tool(
+        description = "Greet someone",
+        params(name = "Who to greet", greeting = "Word to greet with")
+    )]
+    fn greet(
+        &mut self,
+        name: String,
+        greeting: Option<String>,
+        punctuation: Option<String>,
+    ) -> ToolResult {
+        ToolResult::text(format!(
+            "{} {}{}",
+            greeting.unwrap_or_else(|| "Hello".to_string()),
+            name,
+            punctuation.unwrap_or_default()
+        ))
+    }
+
+    #[mcp_tool(description = "Count to a number")]
+    fn count_to(&mut self, progress: &mut ProgressReporter<'_>, to: u32) -> ToolResult {
+        for n in 1..=to {
+            progress.report(n as f64, Some(to as f64), None);
+        }
+        ToolResult::text(to.to_string())
+    }
+
+    #[mcp_tool(description = "Scale a value")]
+    fn scale(&mut self, verbose: bool, factor: f64, offset: i64, limit: Option<i64>) -> ToolResult {
+        ToolResult::text(format!("{} {} {} {:?}", verbose, factor, offset, limit))
+    }
+}
+
+fn text(result: &ToolResult) -> String {
+    serde_json::to_value(&result.content[0]).unwrap()["text"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn array_param_schema() {
+    let tools = TestServer.list_tools();
+    let join = tools.iter().find(|t| t.name == "join_files").unwrap();
+    assert_eq!(join.input_schema["properties"]["files"]["type"], "array");
+    assert_eq!(
+        join.input_schema["properties"]["files"]["items"],
+        json!({"type": "string"})
+    );
+    assert_eq!(join.input_schema["required"], json!(["files"]));
+
+    let count = tools.iter().find(|t| t.name == "count_tags").unwrap();
+    assert_eq!(count.input_schema["required"], json!([]));
+}
+
+#[test]
+fn array_param_dispatch() {
+    let mut server = TestServer;
+
+    let result = server.call_tool(
+        "join_files",
+        Arguments::new(json!({"files": ["a.txt", "b.txt"], "separator": " "})),
+    );
+    assert_eq!(text(&result), "a.txt b.txt");
+
+    let result = server.call_tool("join_files", Arguments::new(json!({})));
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(text(&result), "Missing required parameter: files");
+
+    let result = server.call_tool("count_tags", Arguments::new(json!({"tags": ["x", "y"]})));
+    assert_eq!(text(&result), "Some(2)");
+    let result = server.call_tool("count_tags", Arguments::new(json!({})));
+    assert_eq!(text(&result), "None");
+}
+
+#[test]
+fn param_descriptions() {
+    let tools = TestServer.list_tools();
+    let greet = tools.iter().find(|t| t.name == "greet").unwrap();
+    let properties = &greet.input_schema["properties"];
+    assert_eq!(properties["name"]["description"], "Who to greet");
+    assert_eq!(properties["greeting"]["description"], "Word to greet with");
+    // Parameters without a description keep the generic one
+    assert_eq!(
+        properties["punctuation"]["description"],
+        "The punctuation parameter"
+    );
+    assert_eq!(greet.description, "Greet someone");
+}
+
+#[test]
+fn progress_param() {
+    let tools = TestServer.list_tools();
+    let count_to = tools.iter().find(|t| t.name == "count_to").unwrap();
+    assert_eq!(count_to.input_schema["required"], json!(["to"]));
+    assert!(count_to.input_schema["properties"]["progress"].is_null());
+
+    let mut updates = Vec::new();
+    let mut sink = |n: mcp_server::JsonRpcNotification| updates.push(n.params);
+    let mut progress = ProgressReporter::new(Some(json!("t")), &mut sink);
+    let result = TestServer.call_tool_with_progress(
+        "count_to",
+        Arguments::new(json!({"to": 2})),
+        &mut progress,
+    );
+    assert_eq!(text(&result), "2");
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[1]["progress"], 2.0);
+
+    // Plain call_tool still works, reporting nowhere
+    let result = TestServer.call_tool("count_to", Arguments::new(json!({"to": 3})));
+    assert_eq!(text(&result), "3");
+}
+
+#[test]
+fn typed_param_dispatch() {
+    let mut server = TestServer;
+
+    let result = server.call_tool(
+        "scale",
+        Arguments::new(json!({"verbose": true, "factor": 1.5, "offset": -3, "limit": 10})),
+    );
+    assert_eq!(result.is_error, None);
+    assert_eq!(text(&result), "true 1.5 -3 Some(10)");
+
+    // Integers are accepted as numbers, and null reads as absent
+    let result = server.call_tool(
+        "scale",
+        Arguments::new(json!({"verbose": false, "factor": 2, "offset": 0, "limit": null})),
+    );
+    assert_eq!(text(&result), "false 2 0 None");
+
+    let result = server.call_tool(
+        "scale",
+        Arguments::new(json!({"verbose": true, "factor": 1.0})),
+    );
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(text(&result), "Missing required parameter: offset");
+}
+
+#[test]
+fn invalid_param_errors() {
+    let mut server = TestServer;
+
+    let result = server.call_tool(
+        "scale",
+        Arguments::new(json!({"verbose": "yes", "factor": 1.0, "offset": 0})),
+    );
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(
+        text(&result),
+        "Invalid parameter verbose: expected a boolean"
+    );
+
+    let result = server.call_tool(
+        "scale",
+        Arguments::new(json!({"verbose": true, "factor": 1.0, "offset": 0, "limit": "ten"})),
+    );
+    assert_eq!(
+        text(&result),
+        "Invalid parameter limit: expected an integer from -9223372036854775808 to 9223372036854775807"
+    );
+
+    let result = server.call_tool("count_to", Arguments::new(json!({"to": -1})));
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(
+        text(&result),
+        "Invalid parameter to: expected an integer from 0 to 4294967295"
+    );
+
+    let result = server.call_tool("count_to", Arguments::new(json!({"to": 4294967296u64})));
+    assert_eq!(
+        text(&result),
+        "Invalid parameter to: expected an integer from 0 to 4294967295"
+    );
+
+    let result = server.call_tool("join_files", Arguments::new(json!({"files": ["a", 1]})));
+    assert_eq!(
+        text(&result),
+        "Invalid parameter files: expected an array of strings"
+    );
+}
@@ -11,23 +11,12 @@ use crate::bindings::wasi::http::{
 /// Response from a fetch operation
 pub struct FetchResponse {
     pub status: u16,
-    pub ok: bool,
+    /// Response headers in the order received
+    pub headers: Vec<(String, String)>,
     /// Raw bytes of response body
     pub bytes: Vec<u8>,
 }
 
-impl FetchResponse {
-    /// Get body as string, using lossy conversion for non-UTF8
-    pub fn text_lossy(&self) -> String {
-        String::from_utf8_lossy(&self.bytes).to_string()
-    }
-
-    /// Get body as string (alias for text_lossy)
-    pub fn body(&self) -> String {
-        self.text_lossy()
-    }
-}
-
 /// Parse a URL into scheme, authority, and path components
 fn parse_url(url: &str) -> Result<(Scheme, String, String), String> {
     let (scheme, rest) = if url.starts_with("https://") {
@@ -144,27 +133,30 @@ pub fn fetch(
                 .map_err(|e| format!("HTTP error: {:?}", e))?;
 
             let status = response.status();
-            let ok = status >= 200 && status < 300;
+            let headers = response
+                .headers()
+                .entries()
+                .into_iter()
+                .map(|(name, value)| (name, String::from_utf8_lossy(&value).to_string()))
+                .collect();
 
             let body_handle = response
                 .consume()
                 .map_err(|_| "Failed to consume response body")?;
             let bytes = read_body_bytes(body_handle)?;
 
-            return Ok(FetchResponse { status, ok, bytes });
+            return Ok(FetchResponse {
+                status,
+                headers,
+                bytes,
+            });
         }
     }
 }
 
-/// Convenience: POST request with JSON headers string (legacy compatibility)
-pub fn fetch_request(
-    method: &str,
-    url: &str,
-    headers_json: Option<&str>,
-    body: Option<&str>,
-) -> Result<FetchResponse, String> {
-    // Parse method
-    let method_enum = match method.to_uppercase().as_str() {
+/// Map a method name to the WASI HTTP method, case-insensitively
+pub fn parse_method(method: &str) -> Method {
+    match method.to_uppercase().as_str() {
         "GET" => Method::Get,
         "HEAD" => Method::Head,
         "POST" => Method::Post,
@@ -174,25 +166,5 @@ pub fn fetch_request(
         "TRACE" => Method::Trace,
         "PATCH" => Method::Patch,
         _ => Method::Other(method.to_string()),
-    };
-
-    // Parse JSON headers
-    let mut header_vec = Vec::new();
-    if let Some(headers_str) = headers_json {
-        for pair in headers_str
-            .trim_matches(|c| c == '{' || c == '}')
-            .split(',')
-        {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let value = parts[1].trim().trim_matches('"');
-                if !key.is_empty() && !value.is_empty() {
-                    header_vec.push((key, value));
-                }
-            }
-        }
     }
-
-    fetch(method_enum, url, &header_vec, body.map(|s| s.as_bytes()))
 }
@@ -5,7 +5,8 @@
 
 use crate::bindings::exports::shell::unix::command::ExecEnv;
 use crate::bindings::wasi::io::streams::{InputStream, OutputStream};
-use crate::shell::{run_pipeline, ShellEnv};
+use crate::shell::{poll_background_jobs, run_pipeline_streaming, run_trap, ShellEnv};
+use futures_lite::io::AsyncReadExt;
 use std::fs;
 use std::path::PathBuf;
 
@@ -14,6 +15,13 @@ const CONFIG_DIR: &str = "/.config/web-agent";
 const SHELL_HISTORY_FILE: &str = "/.config/web-agent/shell_history";
 const MAX_HISTORY_ENTRIES: usize = 1000;
 
+/// Capacity of the pipes command output streams through
+const PIPE_CAPACITY: usize = 65536;
+
+/// Bytes forwarded per write; with \n expanded to \r\n this stays within
+/// the 4096-byte limit of blocking_write_and_flush
+const FORWARD_CHUNK: usize = 2048;
+
 /// Result of reading a line
 enum LineResult {
     /// A complete line was read
@@ -61,24 +69,55 @@ fn run_command_string(
         let _ = shell_env.set_var(key, value);
     }
 
-    // Execute the command
-    let result = futures_lite::future::block_on(run_pipeline(command, &mut shell_env));
+    // Execute the command, streaming its output
+    run_streaming(command, &mut shell_env, stdout, stderr)
+}
 
-    // Output results
-    if !result.stdout.is_empty() {
-        write_str(stdout, &result.stdout);
-        if !result.stdout.ends_with('\n') {
-            write_str(stdout, "\n");
+/// Run a command line, forwarding its stdout and stderr to the given
+/// streams as they are produced, and return the exit code
+fn run_streaming(
+    command: &str,
+    shell_env: &mut ShellEnv,
+    stdout: &OutputStream,
+    stderr: &OutputStream,
+) -> i32 {
+    let (stdout_reader, stdout_writer) = piper::pipe(PIPE_CAPACITY);
+    let (stderr_reader, stderr_writer) = piper::pipe(PIPE_CAPACITY);
+
+    let (code, _) = futures_lite::future::block_on(futures_lite::future::zip(
+        run_pipeline_streaming(command, shell_env, stdout_writer, stderr_writer),
+        futures_lite::future::zip(
+            forward_output(stdout_reader, stdout),
+            forward_output(stderr_reader, stderr),
+        ),
+    ));
+    code
+}
+
+/// Copy a pipe to an output stream for raw terminal mode, ending
+/// non-empty output with a newline
+async fn forward_output(mut reader: piper::Reader, stream: &OutputStream) {
+    let mut chunk = [0u8; FORWARD_CHUNK];
+    let mut last = None;
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let mut normalized = Vec::with_capacity(n * 2);
+                for &byte in &chunk[..n] {
+                    if byte == b'\n' {
+                        normalized.push(b'\r');
+                    }
+                    normalized.push(byte);
+                }
+                write_bytes(stream, &normalized);
+                last = Some(chunk[n - 1]);
+            }
         }
     }
-    if !result.stderr.is_empty() {
-        write_str(stderr, &result.stderr);
-        if !result.stderr.ends_with('\n') {
-            write_str(stderr, "\n");
-        }
+    if last.is_some_and(|byte| byte != b'\n') {
+        write_str(stream, "\n");
     }
-
-    result.code
 }
 
 /// Interactive shell REPL
@@ -103,6 +142,11 @@ fn run_interactive_shell(
     let mut history_index = history.len();
 
     loop {
+        // Background jobs run, and report finishing, before each prompt
+        let jobs = futures_lite::future::block_on(poll_background_jobs(&mut shell_env));
+        write_str(&stdout, &jobs.stdout);
+        write_str(&stdout, &jobs.stderr);
+
         // Render prompt: /current/path$
         let prompt = format!("{}$ ", shell_env.cwd.display());
         write_str(&stdout, &prompt);
@@ -139,6 +183,7 @@ fn run_interactive_shell(
 
                 // Handle exit command
                 if line == "exit" || line.starts_with("exit ") {
+                    fire_trap("EXIT", &mut shell_env, &stdout);
                     write_str(&stdout, "Bye!\n");
                     return 0;
                 }
@@ -158,31 +203,26 @@ fn run_interactive_shell(
                     continue;
                 }
 
-                // Execute using the full shell executor!
-                let result = futures_lite::future::block_on(run_pipeline(line, &mut shell_env));
+                // Execute using the full shell executor, streaming output
+                // (stderr included) to the terminal as it is produced
+                run_streaming(line, &mut shell_env, &stdout, &stdout);
 
-                // Output result
-                if !result.stdout.is_empty() {
-                    write_str(&stdout, &result.stdout);
-                    if !result.stdout.ends_with('\n') {
-                        write_str(&stdout, "\n");
-                    }
-                }
-                if !result.stderr.is_empty() {
-                    write_str(&stdout, &result.stderr);
-                    if !result.stderr.ends_with('\n') {
-                        write_str(&stdout, "\n");
-                    }
+                // `exit` or `set -e` inside the line ends the session
+                if shell_env.exiting {
+                    fire_trap("EXIT", &mut shell_env, &stdout);
+                    return shell_env.last_exit_code;
                 }
             }
             LineResult::Eof => {
                 // Ctrl+D - exit
                 write_str(&stdout, "\nexit\n");
+                fire_trap("EXIT", &mut shell_env, &stdout);
                 return 0;
             }
             LineResult::Interrupt => {
-                // Ctrl+C - cancel current line, show new prompt
+                // Ctrl+C - cancel current line, run any INT trap, show new prompt
                 write_str(&stdout, "^C\n");
+                fire_trap("INT", &mut shell_env, &stdout);
             }
             LineResult::ClearScreen(_pending_input) => {
                 // Screen was cleared by Ctrl+L, just continue to redraw prompt
@@ -192,6 +232,14 @@ fn run_interactive_shell(
     }
 }
 
+/// Run the handler registered with `trap` for `signal` and print its output
+fn fire_trap(signal: &str, shell_env: &mut ShellEnv, stdout: &OutputStream) {
+    if let Some(result) = futures_lite::future::block_on(run_trap(signal, shell_env)) {
+        write_str(stdout, &result.stdout);
+        write_str(stdout, &result.stderr);
+    }
+}
+
 /// Read a line from stdin with echo and readline-style editing
 fn read_line(
     stdin: &InputStream,
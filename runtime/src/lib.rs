@@ -19,7 +19,10 @@ use bindings::wasi::http::types::{
     Fields, IncomingRequest, OutgoingBody, OutgoingResponse, ResponseOutparam,
 };
 use bindings::wasi::io::streams::{InputStream, OutputStream};
-use mcp_server::{JsonRpcRequest, JsonRpcResponse, ToolResult};
+use futures_lite::future::zip;
+use mcp_server::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, ProgressReporter, ToolResult,
+};
 use runtime_macros::mcp_tool_router;
 use serde_json::json;
 
@@ -41,7 +44,10 @@ impl ShellMcpServer {
     // MCP Tools - these are auto-registered by #[mcp_tool_router]
     // ============================================================
 
-    #[mcp_tool(description = "Read the contents of a file at the given path.")]
+    #[mcp_tool(
+        description = "Read the contents of a file at the given path.",
+        params(path = "Path of the file to read")
+    )]
     fn read_file(&self, path: String) -> ToolResult {
         use std::fs;
 
@@ -55,7 +61,11 @@ impl ShellMcpServer {
     }
 
     #[mcp_tool(
-        description = "Write content to a file at the given path. Creates parent directories if needed."
+        description = "Write content to a file at the given path. Creates parent directories if needed.",
+        params(
+            path = "Path of the file to write",
+            content = "Text to write; replaces any existing content"
+        )
     )]
     fn write_file(&self, path: String, content: String) -> ToolResult {
         use std::fs;
@@ -80,7 +90,10 @@ impl ShellMcpServer {
         }
     }
 
-    #[mcp_tool(description = "List files and directories at the given path.")]
+    #[mcp_tool(
+        description = "List files and directories at the given path.",
+        params(path = "Directory to list (default: /)")
+    )]
     fn list(&self, path: Option<String>) -> ToolResult {
         use std::fs;
 
@@ -108,7 +121,13 @@ impl ShellMcpServer {
         }
     }
 
-    #[mcp_tool(description = "Search for a pattern in files under the given path.")]
+    #[mcp_tool(
+        description = "Search for a pattern in files under the given path.",
+        params(
+            pattern = "Text to search for (case-insensitive substring)",
+            path = "Directory to search recursively (default: /)"
+        )
+    )]
     fn grep(&self, pattern: String, path: Option<String>) -> ToolResult {
         use std::fs;
 
@@ -170,15 +189,38 @@ impl ShellMcpServer {
     }
 
     #[mcp_tool(
-        description = "Execute shell commands with pipe support. Supports 50+ commands including: echo, ls, cat, grep, sed, awk, jq, curl, sqlite3, tsx, tar, gzip, and more. Example: 'ls /data | head -n 5'"
+        description = "Execute shell commands with pipe support. Supports 50+ commands including: echo, ls, cat, grep, sed, awk, jq, curl, sqlite3, tsx, tar, gzip, and more. Example: 'ls /data | head -n 5'",
+        params(command = "Shell command line to run")
     )]
-    fn shell_eval(&self, command: String) -> ToolResult {
+    fn shell_eval(&self, command: String, progress: &mut ProgressReporter<'_>) -> ToolResult {
         if command.is_empty() {
             return ToolResult::error("No command provided");
         }
 
         let mut env = shell::ShellEnv::new();
-        let result = futures_lite::future::block_on(shell::run_pipeline(&command, &mut env));
+        // notifications/cancelled stops the command line at the next command or stage
+        env.cancel_flag = Some(progress.cancellation().flag());
+
+        let result = if progress.is_requested() {
+            // Stream stdout as progress messages; progress counts bytes so far
+            progress.report(0.0, None, Some(format!("Running: {}", command)));
+            let (stdout_reader, stdout_writer) = piper::pipe(STREAM_PIPE_CAPACITY);
+            let (stderr_reader, stderr_writer) = piper::pipe(STREAM_PIPE_CAPACITY);
+            let (code, (stdout, stderr)) = futures_lite::future::block_on(zip(
+                shell::run_pipeline_streaming(&command, &mut env, stdout_writer, stderr_writer),
+                zip(
+                    stream_progress(stdout_reader, progress),
+                    read_lossy(stderr_reader),
+                ),
+            ));
+            shell::ShellResult {
+                stdout,
+                stderr,
+                code,
+            }
+        } else {
+            futures_lite::future::block_on(shell::run_pipeline(&command, &mut env))
+        };
 
         if result.code == 0 {
             if result.stdout.is_empty() && result.stderr.is_empty() {
@@ -197,7 +239,12 @@ impl ShellMcpServer {
     }
 
     #[mcp_tool(
-        description = "Edit a file by replacing old_str with new_str. The old_str must match exactly and uniquely in the file. For multiple edits, call this tool multiple times. Use read_file first to see the current content."
+        description = "Edit a file by replacing old_str with new_str. The old_str must match exactly and uniquely in the file. For multiple edits, call this tool multiple times. Use read_file first to see the current content.",
+        params(
+            path = "Path of the file to edit",
+            old_str = "Exact text to replace; must occur once in the file",
+            new_str = "Replacement text"
+        )
     )]
     fn edit_file(&self, path: String, old_str: String, new_str: String) -> ToolResult {
         use std::fs;
@@ -277,10 +324,20 @@ impl ShellMcpServer {
 /// Creates a fresh ShellMcpServer instance per request to avoid RefCell borrow
 /// conflicts in sync mode (Safari). The server is stateless, so this is safe.
 fn handle_mcp_request(request_str: &str) -> String {
+    handle_mcp_request_with_progress(request_str, &mut |_| {})
+}
+
+/// Handle JSON-RPC request, passing progress notifications from tool calls
+/// to `on_notification` as they are reported
+fn handle_mcp_request_with_progress(
+    request_str: &str,
+    on_notification: &mut dyn FnMut(JsonRpcNotification),
+) -> String {
     match serde_json::from_str::<JsonRpcRequest>(request_str) {
         Ok(req) => {
             let mut server = ShellMcpServer::new().expect("Failed to create MCP server");
-            let response = mcp_server::handle_request(&mut server, req);
+            let response =
+                mcp_server::handle_request_with_progress(&mut server, req, on_notification);
             serde_json::to_string(&response)
                 .unwrap_or_else(|_| r#"{"error":"serialize failed"}"#.to_string())
         }
@@ -323,57 +380,114 @@ impl HttpGuest for Component {
             k.to_lowercase() == "accept" && String::from_utf8_lossy(v).contains("text/event-stream")
         });
 
+        let request_str = String::from_utf8_lossy(&request_bytes);
+        let is_sse_endpoint = path.starts_with("/sse") && accept_sse;
+        // A JSON-RPC request asking for progress gets an SSE stream when the client
+        // accepts one: progress events as they happen, then the response itself
+        let stream_progress = !is_sse_endpoint && accept_sse && wants_progress(&request_str);
+
+        // Send response headers first so streamed events reach the client immediately
+        let resp = OutgoingResponse::new(response_headers(is_sse_endpoint || stream_progress));
+        resp.set_status_code(200).ok();
+
+        let body = resp.body().expect("response body");
+        ResponseOutparam::set(outparam, Ok(resp));
+
+        let out = body.write().expect("write stream");
+
         // Simple endpoint routing
-        let response_body = if path.starts_with("/sse") && accept_sse {
+        if is_sse_endpoint {
             // SSE endpoint - establish connection
-            handle_sse_connection(&request_bytes)
+            write_body(&out, handle_sse_connection(&request_bytes).as_bytes());
+        } else if stream_progress {
+            let response = handle_mcp_request_with_progress(&request_str, &mut |notification| {
+                write_body(&out, notification.to_sse_event().as_bytes())
+            });
+            write_body(
+                &out,
+                format!("event: message\ndata: {}\n\n", response).as_bytes(),
+            );
         } else {
             // JSON-RPC endpoint
-            let request_str = String::from_utf8_lossy(&request_bytes);
-            handle_mcp_request(&request_str)
-        };
+            write_body(&out, handle_mcp_request(&request_str).as_bytes());
+        }
 
-        // Prepare response headers
-        let hdrs = Fields::new();
+        drop(out);
+        OutgoingBody::finish(body, None).unwrap();
+    }
+}
 
-        if accept_sse && path.starts_with("/sse") {
-            hdrs.set(
-                &"content-type".to_string(),
-                &[b"text/event-stream".to_vec()],
-            )
-            .ok();
-            hdrs.set(&"cache-control".to_string(), &[b"no-cache".to_vec()])
-                .ok();
-            hdrs.set(&"connection".to_string(), &[b"keep-alive".to_vec()])
-                .ok();
-        } else {
-            hdrs.set(&"content-type".to_string(), &[b"application/json".to_vec()])
-                .ok();
-        }
+/// Whether a JSON-RPC request carries `params._meta.progressToken`
+fn wants_progress(request_str: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(request_str)
+        .ok()
+        .is_some_and(|req| !req["params"]["_meta"]["progressToken"].is_null())
+}
+
+fn response_headers(event_stream: bool) -> Fields {
+    let hdrs = Fields::new();
 
-        hdrs.set(&"access-control-allow-origin".to_string(), &[b"*".to_vec()])
+    if event_stream {
+        hdrs.set(
+            &"content-type".to_string(),
+            &[b"text/event-stream".to_vec()],
+        )
+        .ok();
+        hdrs.set(&"cache-control".to_string(), &[b"no-cache".to_vec()])
+            .ok();
+        hdrs.set(&"connection".to_string(), &[b"keep-alive".to_vec()])
             .ok();
+    } else {
+        hdrs.set(&"content-type".to_string(), &[b"application/json".to_vec()])
+            .ok();
+    }
 
-        // Send response
-        let resp = OutgoingResponse::new(hdrs);
-        resp.set_status_code(200).ok();
+    hdrs.set(&"access-control-allow-origin".to_string(), &[b"*".to_vec()])
+        .ok();
+    hdrs
+}
 
-        let body = resp.body().expect("response body");
-        ResponseOutparam::set(outparam, Ok(resp));
+/// Write to the response body in chunks — blocking_write_and_flush is limited
+/// to 4096 bytes per WASI spec
+fn write_body(out: &OutputStream, bytes: &[u8]) {
+    for chunk in bytes.chunks(4096) {
+        out.blocking_write_and_flush(chunk).expect("write");
+    }
+}
 
-        let out = body.write().expect("write stream");
-        // Write in chunks — blocking_write_and_flush is limited to 4096 bytes per WASI spec
-        let response_bytes = response_body.as_bytes();
-        let mut offset = 0;
-        while offset < response_bytes.len() {
-            let end = (offset + 4096).min(response_bytes.len());
-            out.blocking_write_and_flush(&response_bytes[offset..end])
-                .expect("write");
-            offset = end;
+/// Capacity of the pipes shell_eval streams command output through
+const STREAM_PIPE_CAPACITY: usize = 65536;
+
+/// Report each chunk read from `reader` as a progress message, counting
+/// bytes so far, and return everything read
+async fn stream_progress(mut reader: piper::Reader, progress: &mut ProgressReporter<'_>) -> String {
+    use futures_lite::io::AsyncReadExt;
+
+    let mut collected = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                collected.extend_from_slice(&chunk[..n]);
+                progress.report(
+                    collected.len() as f64,
+                    None,
+                    Some(String::from_utf8_lossy(&chunk[..n]).into_owned()),
+                );
+            }
         }
-        drop(out);
-        OutgoingBody::finish(body, None).unwrap();
     }
+    String::from_utf8_lossy(&collected).into_owned()
+}
+
+/// Read a pipe to the end as (lossy) UTF-8
+async fn read_lossy(mut reader: piper::Reader) -> String {
+    use futures_lite::io::AsyncReadExt;
+
+    let mut buffer = Vec::new();
+    let _ = reader.read_to_end(&mut buffer).await;
+    String::from_utf8_lossy(&buffer).into_owned()
 }
 
 /// Handle SSE connection for MCP streaming protocol
@@ -0,0 +1,2142 @@
+//! awk: a lexer, parser and interpreter for the POSIX awk language subset
+//! behind the `awk` command.
+//!
+//! A program is parsed once into rules, then [`AwkInterp`] runs BEGIN, each
+//! input record and END in turn, collecting printed output for the caller
+//! to write.
+
+use super::string::get_random_u64;
+use super::text::posix_to_regex;
+use super::util::{
+    format_printf_float, format_printf_integer, pad_printf, parse_printf_spec, PrintfSpec,
+};
+
+/// An awk value. Strings that come from input (fields, `split` pieces, `-v`)
+/// compare as numbers when they look like numbers, as POSIX "strnum"s do.
+#[derive(Clone, Debug)]
+pub(super) enum AwkValue {
+    Uninit,
+    Num(f64),
+    Str(String),
+    StrNum(String),
+}
+
+impl AwkValue {
+    fn from_bool(b: bool) -> Self {
+        AwkValue::Num(if b { 1.0 } else { 0.0 })
+    }
+
+    fn to_num(&self) -> f64 {
+        match self {
+            AwkValue::Uninit => 0.0,
+            AwkValue::Num(n) => *n,
+            AwkValue::Str(s) | AwkValue::StrNum(s) => awk_str_to_num(s),
+        }
+    }
+
+    fn to_str(&self) -> String {
+        match self {
+            AwkValue::Uninit => String::new(),
+            AwkValue::Num(n) => awk_num_to_str(*n),
+            AwkValue::Str(s) | AwkValue::StrNum(s) => s.clone(),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        match self {
+            AwkValue::Uninit => false,
+            AwkValue::Num(n) => *n != 0.0,
+            AwkValue::Str(s) => !s.is_empty(),
+            AwkValue::StrNum(s) if awk_looks_numeric(s) => awk_str_to_num(s) != 0.0,
+            AwkValue::StrNum(s) => !s.is_empty(),
+        }
+    }
+
+    /// Whether comparisons treat this value as a number
+    fn is_numeric(&self) -> bool {
+        match self {
+            AwkValue::Uninit | AwkValue::Num(_) => true,
+            AwkValue::Str(_) => false,
+            AwkValue::StrNum(s) => awk_looks_numeric(s),
+        }
+    }
+}
+
+/// Length of the number at the start of `s`, as C's strtod reads it
+fn awk_number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let digits_at = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let start = if matches!(bytes.first(), Some(b'+' | b'-')) {
+        1
+    } else {
+        0
+    };
+    let int_end = digits_at(start);
+    let mut end = int_end;
+    if bytes.get(end) == Some(&b'.') {
+        end = digits_at(end + 1);
+    }
+    // Neither integer nor fraction digits: not a number
+    if int_end == start && end <= int_end + 1 {
+        return 0;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+        let exp_end = digits_at(end + 1 + sign);
+        if exp_end > end + 1 + sign {
+            end = exp_end;
+        }
+    }
+    end
+}
+
+/// Numeric value of a string: its leading number, or 0
+fn awk_str_to_num(s: &str) -> f64 {
+    let s = s.trim_start();
+    s[..awk_number_len(s)].parse().unwrap_or(0.0)
+}
+
+/// Whether the whole string (less surrounding blanks) is a number
+fn awk_looks_numeric(s: &str) -> bool {
+    let s = s.trim();
+    !s.is_empty() && awk_number_len(s) == s.len()
+}
+
+/// Integers print as integers; anything else with `%.6g` (awk's CONVFMT)
+fn awk_num_to_str(n: f64) -> String {
+    if n == n.trunc() && n.abs() < 1e16 {
+        (n as i64).to_string()
+    } else {
+        let spec = PrintfSpec {
+            precision: Some(6),
+            ..Default::default()
+        };
+        format_printf_float(n, 'g', &spec)
+    }
+}
+
+/// Translate an escape sequence in a string literal or `-v` value; `chars[*i]`
+/// is the character after the backslash
+fn push_awk_escape(chars: &[char], i: &mut usize, out: &mut String) {
+    let Some(&c) = chars.get(*i) else {
+        out.push('\\');
+        return;
+    };
+    *i += 1;
+    match c {
+        'n' => out.push('\n'),
+        't' => out.push('\t'),
+        'r' => out.push('\r'),
+        'a' => out.push('\x07'),
+        'b' => out.push('\x08'),
+        'f' => out.push('\x0c'),
+        'v' => out.push('\x0b'),
+        '0'..='7' => {
+            let mut code = c.to_digit(8).unwrap_or(0);
+            for _ in 0..2 {
+                match chars.get(*i).and_then(|d| d.to_digit(8)) {
+                    Some(d) => {
+                        code = code * 8 + d;
+                        *i += 1;
+                    }
+                    None => break,
+                }
+            }
+            out.extend(char::from_u32(code));
+        }
+        // \\, \", \/ and unknown escapes all stand for the character itself
+        _ => out.push(c),
+    }
+}
+
+/// Process escape sequences in a command-line value (`-v`, `-F`)
+pub(super) fn awk_unescape(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        if c == '\\' {
+            push_awk_escape(&chars, &mut i, &mut out);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum AwkToken {
+    Num(f64),
+    Str(String),
+    Regex(String),
+    Name(String),
+    Punct(&'static str),
+    Newline,
+}
+
+/// Operators and punctuation, longest first
+const AWK_PUNCT: &[&str] = &[
+    "+=", "-=", "*=", "/=", "%=", "^=", "==", "<=", ">=", "!=", "++", "--", "&&", "||", ">>", "!~",
+    "{", "}", "(", ")", "[", "]", ";", ",", "+", "-", "*", "/", "%", "^", "!", ">", "<", "|", "?",
+    ":", "~", "$", "=",
+];
+
+const AWK_KEYWORDS: &[&str] = &[
+    "BEGIN", "END", "if", "else", "while", "for", "do", "in", "print", "printf", "next", "exit",
+    "break", "continue", "delete", "getline", "function", "return",
+];
+
+const AWK_BUILTINS: &[&str] = &[
+    "length", "substr", "index", "split", "sub", "gsub", "match", "sprintf", "tolower", "toupper",
+    "int", "sqrt", "exp", "log", "sin", "cos", "atan2", "rand", "srand",
+];
+
+/// A `/` starts a regex unless it follows something that ends an operand
+fn awk_regex_allowed(prev: Option<&AwkToken>) -> bool {
+    match prev {
+        Some(AwkToken::Num(_) | AwkToken::Str(_) | AwkToken::Regex(_)) => false,
+        Some(AwkToken::Name(name)) => AWK_KEYWORDS.contains(&name.as_str()),
+        Some(AwkToken::Punct(p)) => !matches!(*p, ")" | "]" | "$" | "++" | "--"),
+        _ => true,
+    }
+}
+
+fn tokenize_awk(src: &str) -> Result<Vec<AwkToken>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\r' => i += 1,
+            // Line continuation
+            '\\' if chars.get(i + 1) == Some(&'\n') => i += 2,
+            '\n' => {
+                tokens.push(AwkToken::Newline);
+                i += 1;
+            }
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '"' => {
+                i += 1;
+                let mut s = String::new();
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err("unterminated string".to_string()),
+                        Some('"') => break,
+                        Some('\\') => {
+                            i += 1;
+                            push_awk_escape(&chars, &mut i, &mut s);
+                            continue;
+                        }
+                        Some(&ch) => s.push(ch),
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push(AwkToken::Str(s));
+            }
+            '/' if awk_regex_allowed(tokens.last()) => {
+                i += 1;
+                let mut re = String::new();
+                loop {
+                    match chars.get(i) {
+                        None | Some('\n') => return Err("unterminated regex".to_string()),
+                        Some('/') => break,
+                        Some('\\') if chars.get(i + 1) == Some(&'/') => {
+                            re.push('/');
+                            i += 1;
+                        }
+                        Some('\\') => {
+                            re.push('\\');
+                            i += 1;
+                            match chars.get(i) {
+                                Some(&ch) => re.push(ch),
+                                None => return Err("unterminated regex".to_string()),
+                            }
+                        }
+                        Some(&ch) => re.push(ch),
+                    }
+                    i += 1;
+                }
+                i += 1;
+                tokens.push(AwkToken::Regex(re));
+            }
+            _ if c.is_ascii_digit()
+                || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) =>
+            {
+                let rest: String = chars[i..].iter().collect();
+                let len = awk_number_len(&rest);
+                let n = rest[..len]
+                    .parse()
+                    .map_err(|_| format!("invalid number: {}", &rest[..len]))?;
+                tokens.push(AwkToken::Num(n));
+                i += rest[..len].chars().count();
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(AwkToken::Name(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let ahead: String = chars[i..chars.len().min(i + 2)].iter().collect();
+                match AWK_PUNCT.iter().find(|p| ahead.starts_with(**p)) {
+                    Some(p) => {
+                        tokens.push(AwkToken::Punct(p));
+                        i += p.len();
+                    }
+                    None => return Err(format!("syntax error: unexpected character `{}'", c)),
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AwkBinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Concat,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Clone, Debug)]
+enum AwkExpr {
+    Num(f64),
+    Str(String),
+    /// A bare `/regex/`, which matches against `$0`
+    Regex(regex::Regex),
+    Var(String),
+    Field(Box<AwkExpr>),
+    Index(String, Vec<AwkExpr>),
+    /// `(a, b)`, only meaningful before `in` or as print's argument list
+    Grouping(Vec<AwkExpr>),
+    In(Vec<AwkExpr>, String),
+    Assign {
+        target: Box<AwkExpr>,
+        op: Option<AwkBinOp>,
+        value: Box<AwkExpr>,
+    },
+    Cond(Box<AwkExpr>, Box<AwkExpr>, Box<AwkExpr>),
+    And(Box<AwkExpr>, Box<AwkExpr>),
+    Or(Box<AwkExpr>, Box<AwkExpr>),
+    Match {
+        subject: Box<AwkExpr>,
+        pattern: Box<AwkExpr>,
+        negate: bool,
+    },
+    Binary(AwkBinOp, Box<AwkExpr>, Box<AwkExpr>),
+    Not(Box<AwkExpr>),
+    Neg(Box<AwkExpr>),
+    Plus(Box<AwkExpr>),
+    IncDec {
+        target: Box<AwkExpr>,
+        delta: f64,
+        prefix: bool,
+    },
+    Call(String, Vec<AwkExpr>),
+}
+
+impl AwkExpr {
+    fn is_lvalue(&self) -> bool {
+        matches!(
+            self,
+            AwkExpr::Var(_) | AwkExpr::Field(_) | AwkExpr::Index(..)
+        )
+    }
+}
+
+#[derive(Debug)]
+enum AwkStmt {
+    Print(Vec<AwkExpr>),
+    Printf(Vec<AwkExpr>),
+    Expr(AwkExpr),
+    If(AwkExpr, Box<AwkStmt>, Option<Box<AwkStmt>>),
+    While(AwkExpr, Box<AwkStmt>),
+    DoWhile(Box<AwkStmt>, AwkExpr),
+    For(
+        Option<AwkExpr>,
+        Option<AwkExpr>,
+        Option<AwkExpr>,
+        Box<AwkStmt>,
+    ),
+    ForIn(String, String, Box<AwkStmt>),
+    Block(Vec<AwkStmt>),
+    Next,
+    Exit(Option<AwkExpr>),
+    Break,
+    Continue,
+    Delete(String, Option<Vec<AwkExpr>>),
+}
+
+#[derive(Debug)]
+enum AwkPattern {
+    Always,
+    Expr(AwkExpr),
+    Range(AwkExpr, AwkExpr),
+}
+
+/// A pattern-action rule; no action means `{ print }`
+#[derive(Debug)]
+struct AwkRule {
+    pattern: AwkPattern,
+    action: Option<Vec<AwkStmt>>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct AwkProgram {
+    begin: Vec<AwkStmt>,
+    rules: Vec<AwkRule>,
+    end: Vec<AwkStmt>,
+}
+
+impl AwkProgram {
+    /// A program of only BEGIN actions never reads input
+    pub(super) fn reads_input(&self) -> bool {
+        !self.rules.is_empty() || !self.end.is_empty()
+    }
+}
+
+/// Parse an awk program
+pub(super) fn parse_awk_program(program: &str) -> Result<AwkProgram, String> {
+    let mut parser = AwkParser {
+        tokens: tokenize_awk(program)?,
+        pos: 0,
+        no_gt: false,
+    };
+    parser.parse_program()
+}
+
+struct AwkParser {
+    tokens: Vec<AwkToken>,
+    pos: usize,
+    /// Inside print's arguments, where an unparenthesized `>` is redirection
+    no_gt: bool,
+}
+
+impl AwkParser {
+    fn peek(&self) -> Option<&AwkToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&AwkToken> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn is_punct(&self, p: &str) -> bool {
+        matches!(self.peek(), Some(AwkToken::Punct(q)) if *q == p)
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(AwkToken::Name(n)) if n == name)
+    }
+
+    fn eat_punct(&mut self, p: &str) -> bool {
+        let found = self.is_punct(p);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, p: &str) -> Result<(), String> {
+        if self.eat_punct(p) {
+            Ok(())
+        } else {
+            Err(format!("syntax error: expected `{}' {}", p, self.near()))
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(AwkToken::Name(n)) if !AWK_KEYWORDS.contains(&n.as_str()) => {
+                let n = n.clone();
+                self.pos += 1;
+                Ok(n)
+            }
+            _ => Err(format!("syntax error: expected a name {}", self.near())),
+        }
+    }
+
+    /// Where a syntax error happened, for messages
+    fn near(&self) -> String {
+        match self.peek() {
+            None => "at end of program".to_string(),
+            Some(AwkToken::Newline) => "at end of line".to_string(),
+            Some(AwkToken::Num(n)) => format!("near `{}'", awk_num_to_str(*n)),
+            Some(AwkToken::Str(s)) => format!("near `\"{}\"'", s),
+            Some(AwkToken::Regex(r)) => format!("near `/{}/'", r),
+            Some(AwkToken::Name(n)) => format!("near `{}'", n),
+            Some(AwkToken::Punct(p)) => format!("near `{}'", p),
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek() == Some(&AwkToken::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_terminators(&mut self) {
+        while matches!(self.peek(), Some(AwkToken::Newline | AwkToken::Punct(";"))) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_program(&mut self) -> Result<AwkProgram, String> {
+        let mut program = AwkProgram::default();
+        loop {
+            self.skip_terminators();
+            if self.peek().is_none() {
+                return Ok(program);
+            }
+            if self.is_name("BEGIN") || self.is_name("END") {
+                let is_begin = self.is_name("BEGIN");
+                self.pos += 1;
+                let body = self.parse_block()?;
+                if is_begin {
+                    program.begin.extend(body);
+                } else {
+                    program.end.extend(body);
+                }
+                continue;
+            }
+            if self.is_name("function") {
+                return Err("user-defined functions are not supported".to_string());
+            }
+            let pattern = if self.is_punct("{") {
+                AwkPattern::Always
+            } else {
+                let first = self.parse_expr()?;
+                if self.eat_punct(",") {
+                    self.skip_newlines();
+                    AwkPattern::Range(first, self.parse_expr()?)
+                } else {
+                    AwkPattern::Expr(first)
+                }
+            };
+            let action = if self.is_punct("{") {
+                Some(self.parse_block()?)
+            } else {
+                None
+            };
+            program.rules.push(AwkRule { pattern, action });
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<AwkStmt>, String> {
+        self.expect_punct("{")?;
+        let mut stmts = Vec::new();
+        loop {
+            self.skip_terminators();
+            if self.eat_punct("}") {
+                return Ok(stmts);
+            }
+            if self.peek().is_none() {
+                return Err("syntax error: missing `}'".to_string());
+            }
+            stmts.push(self.parse_stmt()?);
+        }
+    }
+
+    /// A statement body after `if (...)`, `while (...)` and the like
+    fn parse_body(&mut self) -> Result<Box<AwkStmt>, String> {
+        self.skip_newlines();
+        if self.eat_punct(";") {
+            return Ok(Box::new(AwkStmt::Block(Vec::new())));
+        }
+        Ok(Box::new(self.parse_stmt()?))
+    }
+
+    fn parse_condition(&mut self) -> Result<AwkExpr, String> {
+        self.expect_punct("(")?;
+        let cond = self.parse_expr()?;
+        self.expect_punct(")")?;
+        Ok(cond)
+    }
+
+    fn parse_stmt(&mut self) -> Result<AwkStmt, String> {
+        if self.is_punct("{") {
+            return Ok(AwkStmt::Block(self.parse_block()?));
+        }
+        let keyword = match self.peek() {
+            Some(AwkToken::Name(n)) if AWK_KEYWORDS.contains(&n.as_str()) => n.clone(),
+            _ => String::new(),
+        };
+        let stmt = match keyword.as_str() {
+            "if" => {
+                self.pos += 1;
+                let cond = self.parse_condition()?;
+                let then = self.parse_body()?;
+                // `else` may follow on a later line or after a `;`
+                let save = self.pos;
+                self.skip_terminators();
+                let otherwise = if self.is_name("else") {
+                    self.pos += 1;
+                    Some(self.parse_body()?)
+                } else {
+                    self.pos = save;
+                    None
+                };
+                return Ok(AwkStmt::If(cond, then, otherwise));
+            }
+            "while" => {
+                self.pos += 1;
+                let cond = self.parse_condition()?;
+                return Ok(AwkStmt::While(cond, self.parse_body()?));
+            }
+            "do" => {
+                self.pos += 1;
+                let body = self.parse_body()?;
+                self.skip_terminators();
+                if !self.is_name("while") {
+                    return Err(format!("syntax error: expected `while' {}", self.near()));
+                }
+                self.pos += 1;
+                AwkStmt::DoWhile(body, self.parse_condition()?)
+            }
+            "for" => {
+                self.pos += 1;
+                self.expect_punct("(")?;
+                if let (
+                    Some(AwkToken::Name(var)),
+                    Some(AwkToken::Name(kw)),
+                    Some(AwkToken::Name(array)),
+                    Some(AwkToken::Punct(")")),
+                ) = (
+                    self.peek(),
+                    self.peek_at(1),
+                    self.peek_at(2),
+                    self.peek_at(3),
+                ) {
+                    if kw == "in" {
+                        let (var, array) = (var.clone(), array.clone());
+                        self.pos += 4;
+                        return Ok(AwkStmt::ForIn(var, array, self.parse_body()?));
+                    }
+                }
+                let init = self.parse_optional_expr(";")?;
+                self.expect_punct(";")?;
+                self.skip_newlines();
+                let cond = self.parse_optional_expr(";")?;
+                self.expect_punct(";")?;
+                self.skip_newlines();
+                let post = self.parse_optional_expr(")")?;
+                self.expect_punct(")")?;
+                return Ok(AwkStmt::For(init, cond, post, self.parse_body()?));
+            }
+            "print" | "printf" => self.parse_print(keyword == "printf")?,
+            "next" => {
+                self.pos += 1;
+                AwkStmt::Next
+            }
+            "break" => {
+                self.pos += 1;
+                AwkStmt::Break
+            }
+            "continue" => {
+                self.pos += 1;
+                AwkStmt::Continue
+            }
+            "exit" => {
+                self.pos += 1;
+                let code = if self.at_simple_end() {
+                    None
+                } else {
+                    Some(self.parse_expr()?)
+                };
+                AwkStmt::Exit(code)
+            }
+            "delete" => {
+                self.pos += 1;
+                let name = self.expect_name()?;
+                let subscript = if self.eat_punct("[") {
+                    let subs = self.parse_expr_list()?;
+                    self.expect_punct("]")?;
+                    Some(subs)
+                } else {
+                    None
+                };
+                AwkStmt::Delete(name, subscript)
+            }
+            "getline" | "function" | "return" => {
+                return Err(format!("`{}' is not supported", keyword));
+            }
+            "" => AwkStmt::Expr(self.parse_expr()?),
+            _ => return Err(format!("syntax error {}", self.near())),
+        };
+        self.end_simple()?;
+        Ok(stmt)
+    }
+
+    fn parse_optional_expr(&mut self, terminator: &str) -> Result<Option<AwkExpr>, String> {
+        if self.is_punct(terminator) {
+            Ok(None)
+        } else {
+            Ok(Some(self.parse_expr()?))
+        }
+    }
+
+    fn at_simple_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some(AwkToken::Newline | AwkToken::Punct(";" | "}"))
+        )
+    }
+
+    /// A simple statement ends at a newline, `;`, or the block's `}`
+    fn end_simple(&mut self) -> Result<(), String> {
+        match self.peek() {
+            None | Some(AwkToken::Punct("}")) => Ok(()),
+            Some(AwkToken::Newline | AwkToken::Punct(";")) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(format!("syntax error {}", self.near())),
+        }
+    }
+
+    fn parse_print(&mut self, is_printf: bool) -> Result<AwkStmt, String> {
+        self.pos += 1;
+        let mut args = Vec::new();
+        if !self.at_simple_end() {
+            self.no_gt = true;
+            let parsed = self.parse_expr_list();
+            self.no_gt = false;
+            args = parsed?;
+        }
+        // print (a, b) is the same as print a, b
+        if let [AwkExpr::Grouping(items)] = args.as_slice() {
+            args = items.clone();
+        }
+        if self.is_punct(">") || self.is_punct(">>") || self.is_punct("|") {
+            return Err("output redirection is not supported".to_string());
+        }
+        if !is_printf {
+            return Ok(AwkStmt::Print(args));
+        }
+        if args.is_empty() {
+            return Err("printf: no format".to_string());
+        }
+        Ok(AwkStmt::Printf(args))
+    }
+
+    fn parse_expr_list(&mut self) -> Result<Vec<AwkExpr>, String> {
+        let mut items = vec![self.parse_expr()?];
+        while self.eat_punct(",") {
+            self.skip_newlines();
+            items.push(self.parse_expr()?);
+        }
+        Ok(items)
+    }
+
+    fn parse_expr(&mut self) -> Result<AwkExpr, String> {
+        let cond = self.parse_or()?;
+        let op = match self.peek() {
+            Some(AwkToken::Punct("=")) => Some(None),
+            Some(AwkToken::Punct("+=")) => Some(Some(AwkBinOp::Add)),
+            Some(AwkToken::Punct("-=")) => Some(Some(AwkBinOp::Sub)),
+            Some(AwkToken::Punct("*=")) => Some(Some(AwkBinOp::Mul)),
+            Some(AwkToken::Punct("/=")) => Some(Some(AwkBinOp::Div)),
+            Some(AwkToken::Punct("%=")) => Some(Some(AwkBinOp::Mod)),
+            Some(AwkToken::Punct("^=")) => Some(Some(AwkBinOp::Pow)),
+            _ => None,
+        };
+        if let Some(op) = op {
+            if !cond.is_lvalue() {
+                return Err(format!(
+                    "syntax error: assignment to non-variable {}",
+                    self.near()
+                ));
+            }
+            self.pos += 1;
+            self.skip_newlines();
+            let value = self.parse_expr()?;
+            return Ok(AwkExpr::Assign {
+                target: Box::new(cond),
+                op,
+                value: Box::new(value),
+            });
+        }
+        if self.eat_punct("?") {
+            self.skip_newlines();
+            let then = self.parse_expr()?;
+            self.skip_newlines();
+            self.expect_punct(":")?;
+            self.skip_newlines();
+            let otherwise = self.parse_expr()?;
+            return Ok(AwkExpr::Cond(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(cond)
+    }
+
+    fn parse_or(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_punct("||") {
+            self.skip_newlines();
+            let right = self.parse_and()?;
+            left = AwkExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_in()?;
+        while self.eat_punct("&&") {
+            self.skip_newlines();
+            let right = self.parse_in()?;
+            left = AwkExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_in(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_match()?;
+        while self.is_name("in") {
+            self.pos += 1;
+            let array = self.expect_name()?;
+            let subs = match left {
+                AwkExpr::Grouping(items) => items,
+                other => vec![other],
+            };
+            left = AwkExpr::In(subs, array);
+        }
+        Ok(left)
+    }
+
+    fn parse_match(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_comparison()?;
+        loop {
+            let negate = if self.eat_punct("~") {
+                false
+            } else if self.eat_punct("!~") {
+                true
+            } else {
+                return Ok(left);
+            };
+            let pattern = self.parse_comparison()?;
+            left = AwkExpr::Match {
+                subject: Box::new(left),
+                pattern: Box::new(pattern),
+                negate,
+            };
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<AwkExpr, String> {
+        let left = self.parse_concat()?;
+        let op = match self.peek() {
+            Some(AwkToken::Punct("<")) => AwkBinOp::Lt,
+            Some(AwkToken::Punct("<=")) => AwkBinOp::Le,
+            Some(AwkToken::Punct(">")) if !self.no_gt => AwkBinOp::Gt,
+            Some(AwkToken::Punct(">=")) => AwkBinOp::Ge,
+            Some(AwkToken::Punct("==")) => AwkBinOp::Eq,
+            Some(AwkToken::Punct("!=")) => AwkBinOp::Ne,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.parse_concat()?;
+        Ok(AwkExpr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    /// Whether the next token can begin a concatenated operand
+    fn starts_operand(&self) -> bool {
+        match self.peek() {
+            Some(AwkToken::Num(_) | AwkToken::Str(_)) => true,
+            Some(AwkToken::Name(n)) => !AWK_KEYWORDS.contains(&n.as_str()),
+            Some(AwkToken::Punct(p)) => matches!(*p, "$" | "("),
+            _ => false,
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_additive()?;
+        while self.starts_operand() {
+            let right = self.parse_additive()?;
+            left = AwkExpr::Binary(AwkBinOp::Concat, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = if self.eat_punct("+") {
+                AwkBinOp::Add
+            } else if self.eat_punct("-") {
+                AwkBinOp::Sub
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_multiplicative()?;
+            left = AwkExpr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<AwkExpr, String> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = if self.eat_punct("*") {
+                AwkBinOp::Mul
+            } else if self.eat_punct("/") {
+                AwkBinOp::Div
+            } else if self.eat_punct("%") {
+                AwkBinOp::Mod
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_unary()?;
+            left = AwkExpr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<AwkExpr, String> {
+        if self.eat_punct("!") {
+            Ok(AwkExpr::Not(Box::new(self.parse_unary()?)))
+        } else if self.eat_punct("-") {
+            Ok(AwkExpr::Neg(Box::new(self.parse_unary()?)))
+        } else if self.eat_punct("+") {
+            Ok(AwkExpr::Plus(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_power()
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<AwkExpr, String> {
+        let base = self.parse_postfix()?;
+        if self.eat_punct("^") {
+            // Right-associative, and binds tighter than unary minus on its left
+            let exponent = self.parse_unary()?;
+            return Ok(AwkExpr::Binary(
+                AwkBinOp::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn parse_postfix(&mut self) -> Result<AwkExpr, String> {
+        let expr = self.parse_primary()?;
+        if expr.is_lvalue() && (self.is_punct("++") || self.is_punct("--")) {
+            let delta = if self.eat_punct("++") {
+                1.0
+            } else {
+                self.pos += 1;
+                -1.0
+            };
+            return Ok(AwkExpr::IncDec {
+                target: Box::new(expr),
+                delta,
+                prefix: false,
+            });
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<AwkExpr, String> {
+        let Some(token) = self.peek().cloned() else {
+            return Err("syntax error: unexpected end of program".to_string());
+        };
+        self.pos += 1;
+        match token {
+            AwkToken::Num(n) => Ok(AwkExpr::Num(n)),
+            AwkToken::Str(s) => Ok(AwkExpr::Str(s)),
+            AwkToken::Regex(re) => Ok(AwkExpr::Regex(compile_awk_regex(&re)?)),
+            AwkToken::Punct("$") => {
+                // `$` binds tighter than anything but grouping and ++/--
+                let index = if self.eat_punct("-") {
+                    AwkExpr::Neg(Box::new(self.parse_primary()?))
+                } else {
+                    self.parse_primary()?
+                };
+                Ok(AwkExpr::Field(Box::new(index)))
+            }
+            AwkToken::Punct("(") => {
+                let saved = std::mem::replace(&mut self.no_gt, false);
+                let result = self.parse_grouping();
+                self.no_gt = saved;
+                result
+            }
+            AwkToken::Punct(p @ ("++" | "--")) => {
+                let target = self.parse_primary()?;
+                if !target.is_lvalue() {
+                    return Err(format!("syntax error: `{}' needs a variable", p));
+                }
+                Ok(AwkExpr::IncDec {
+                    target: Box::new(target),
+                    delta: if p == "++" { 1.0 } else { -1.0 },
+                    prefix: true,
+                })
+            }
+            AwkToken::Name(name) if AWK_BUILTINS.contains(&name.as_str()) => {
+                if !self.eat_punct("(") {
+                    // `length` alone means length($0)
+                    if name == "length" {
+                        return Ok(AwkExpr::Call(name, Vec::new()));
+                    }
+                    return Err(format!("syntax error: expected `(' after `{}'", name));
+                }
+                let saved = std::mem::replace(&mut self.no_gt, false);
+                let args = if self.is_punct(")") {
+                    Ok(Vec::new())
+                } else {
+                    self.parse_expr_list()
+                };
+                self.no_gt = saved;
+                let args = args?;
+                self.expect_punct(")")?;
+                Ok(AwkExpr::Call(name, args))
+            }
+            AwkToken::Name(name) if !AWK_KEYWORDS.contains(&name.as_str()) => {
+                if self.is_punct("(") {
+                    return Err(format!("function `{}' is not defined", name));
+                }
+                if self.eat_punct("[") {
+                    let subs = self.parse_expr_list()?;
+                    self.expect_punct("]")?;
+                    return Ok(AwkExpr::Index(name, subs));
+                }
+                Ok(AwkExpr::Var(name))
+            }
+            _ => {
+                self.pos -= 1;
+                Err(format!("syntax error {}", self.near()))
+            }
+        }
+    }
+
+    /// After `(`: a parenthesized expression or a `(a, b)` grouping
+    fn parse_grouping(&mut self) -> Result<AwkExpr, String> {
+        self.skip_newlines();
+        let first = self.parse_expr()?;
+        if !self.is_punct(",") {
+            self.expect_punct(")")?;
+            return Ok(first);
+        }
+        let mut items = vec![first];
+        while self.eat_punct(",") {
+            self.skip_newlines();
+            items.push(self.parse_expr()?);
+        }
+        self.expect_punct(")")?;
+        Ok(AwkExpr::Grouping(items))
+    }
+}
+
+/// Compile an awk (POSIX extended) regex
+fn compile_awk_regex(pattern: &str) -> Result<regex::Regex, String> {
+    regex::Regex::new(&posix_to_regex(pattern, true))
+        .map_err(|e| format!("invalid regex /{}/: {}", pattern, e))
+}
+
+/// Array elements in insertion order, which `for (k in a)` follows
+#[derive(Default)]
+struct AwkArray {
+    values: std::collections::HashMap<String, AwkValue>,
+    order: Vec<String>,
+}
+
+impl AwkArray {
+    fn get(&mut self, key: &str) -> AwkValue {
+        // Referencing an element creates it, as in awk
+        if !self.values.contains_key(key) {
+            self.set(key.to_string(), AwkValue::Uninit);
+        }
+        self.values[key].clone()
+    }
+
+    fn set(&mut self, key: String, value: AwkValue) {
+        if !self.values.contains_key(&key) {
+            self.order.push(key.clone());
+        }
+        self.values.insert(key, value);
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.values.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+}
+
+/// How a statement finished
+enum AwkFlow {
+    Normal,
+    Next,
+    Exit,
+    Break,
+    Continue,
+}
+
+/// Runs a parsed program, one record at a time, collecting printed output
+pub(super) struct AwkInterp<'a> {
+    program: &'a AwkProgram,
+    vars: std::collections::HashMap<String, AwkValue>,
+    arrays: std::collections::HashMap<String, AwkArray>,
+    record: String,
+    fields: Vec<String>,
+    /// Whether each rule's range pattern is between its start and end
+    range_active: Vec<bool>,
+    regex_cache: std::collections::HashMap<String, regex::Regex>,
+    out: String,
+    pub(super) exit_code: i32,
+    rand_seed: f64,
+    rand_state: u64,
+}
+
+impl<'a> AwkInterp<'a> {
+    pub(super) fn new(program: &'a AwkProgram) -> Self {
+        let mut interp = AwkInterp {
+            program,
+            vars: std::collections::HashMap::new(),
+            arrays: std::collections::HashMap::new(),
+            record: String::new(),
+            fields: Vec::new(),
+            range_active: vec![false; program.rules.len()],
+            regex_cache: std::collections::HashMap::new(),
+            out: String::new(),
+            exit_code: 0,
+            rand_seed: 0.0,
+            rand_state: 0,
+        };
+        for (name, value) in [
+            ("FS", " "),
+            ("OFS", " "),
+            ("ORS", "\n"),
+            ("SUBSEP", "\x1c"),
+            ("FILENAME", ""),
+        ] {
+            interp.set_var(name, AwkValue::Str(value.to_string()));
+        }
+        for name in ["NR", "FNR", "RSTART"] {
+            interp.set_var(name, AwkValue::Num(0.0));
+        }
+        interp.set_var("RLENGTH", AwkValue::Num(-1.0));
+        interp
+    }
+
+    /// Take what the program has printed so far
+    pub(super) fn take_output(&mut self) -> String {
+        std::mem::take(&mut self.out)
+    }
+
+    /// Run the BEGIN actions; true if they called `exit`
+    pub(super) fn run_begin(&mut self) -> Result<bool, String> {
+        let program = self.program;
+        Ok(matches!(self.exec_block(&program.begin)?, AwkFlow::Exit))
+    }
+
+    /// Start reading a new input file ("" for stdin)
+    pub(super) fn start_file(&mut self, name: &str) {
+        self.set_var("FILENAME", AwkValue::Str(name.to_string()));
+        self.set_var("FNR", AwkValue::Num(0.0));
+    }
+
+    /// Run the rules over one input line; true if they called `exit`
+    pub(super) fn run_record(&mut self, line: &str) -> Result<bool, String> {
+        for counter in ["NR", "FNR"] {
+            let n = self.get_var(counter).to_num();
+            self.set_var(counter, AwkValue::Num(n + 1.0));
+        }
+        self.set_record(line.to_string())?;
+
+        let program = self.program;
+        for (idx, rule) in program.rules.iter().enumerate() {
+            let selected = match &rule.pattern {
+                AwkPattern::Always => true,
+                AwkPattern::Expr(expr) => self.eval(expr)?.is_true(),
+                AwkPattern::Range(start, end) => {
+                    // The end is checked on the start line too
+                    let inside = self.range_active[idx] || self.eval(start)?.is_true();
+                    if inside {
+                        self.range_active[idx] = !self.eval(end)?.is_true();
+                    }
+                    inside
+                }
+            };
+            if !selected {
+                continue;
+            }
+            match &rule.action {
+                None => self.print_line(self.record.clone()),
+                Some(stmts) => match self.exec_block(stmts)? {
+                    AwkFlow::Next => return Ok(false),
+                    AwkFlow::Exit => return Ok(true),
+                    _ => {}
+                },
+            }
+        }
+        Ok(false)
+    }
+
+    /// Run the END actions
+    pub(super) fn run_end(&mut self) -> Result<(), String> {
+        let program = self.program;
+        self.exec_block(&program.end)?;
+        Ok(())
+    }
+
+    fn print_line(&mut self, line: String) {
+        self.out.push_str(&line);
+        let ors = self.get_var("ORS").to_str();
+        self.out.push_str(&ors);
+    }
+
+    fn get_var(&self, name: &str) -> AwkValue {
+        if name == "NF" {
+            return AwkValue::Num(self.fields.len() as f64);
+        }
+        self.vars.get(name).cloned().unwrap_or(AwkValue::Uninit)
+    }
+
+    pub(super) fn set_var(&mut self, name: &str, value: AwkValue) {
+        if name == "NF" {
+            // Assigning NF drops or adds fields and rebuilds $0
+            let n = value.to_num().max(0.0) as usize;
+            self.fields.resize(n, String::new());
+            self.rebuild_record();
+            return;
+        }
+        self.vars.insert(name.to_string(), value);
+    }
+
+    fn set_record(&mut self, record: String) -> Result<(), String> {
+        let fs = self.get_var("FS").to_str();
+        self.fields = self.split_text(&record, &fs)?;
+        self.record = record;
+        Ok(())
+    }
+
+    fn rebuild_record(&mut self) {
+        self.record = self.fields.join(&self.get_var("OFS").to_str());
+    }
+
+    /// Split on FS rules: " " splits on runs of blanks, any other single
+    /// character literally, and anything longer as a regex
+    fn split_text(&mut self, text: &str, fs: &str) -> Result<Vec<String>, String> {
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+        if fs == " " {
+            return Ok(text.split_whitespace().map(String::from).collect());
+        }
+        let mut chars = fs.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c != '\\' {
+                return Ok(text.split(c).map(String::from).collect());
+            }
+        }
+        let re = self.dynamic_regex(fs)?;
+        Ok(re.split(text).map(String::from).collect())
+    }
+
+    fn dynamic_regex(&mut self, pattern: &str) -> Result<regex::Regex, String> {
+        if let Some(re) = self.regex_cache.get(pattern) {
+            return Ok(re.clone());
+        }
+        let re = compile_awk_regex(pattern)?;
+        self.regex_cache.insert(pattern.to_string(), re.clone());
+        Ok(re)
+    }
+
+    /// The regex a `~` operand or sub/gsub/match argument stands for
+    fn regex_operand(&mut self, expr: &AwkExpr) -> Result<regex::Regex, String> {
+        if let AwkExpr::Regex(re) = expr {
+            return Ok(re.clone());
+        }
+        let pattern = self.eval(expr)?.to_str();
+        self.dynamic_regex(&pattern)
+    }
+
+    fn field_index(&mut self, expr: &AwkExpr) -> Result<usize, String> {
+        let n = self.eval(expr)?.to_num();
+        if n < 0.0 {
+            return Err(format!("trying to access out of range field {}", n as i64));
+        }
+        Ok(n as usize)
+    }
+
+    fn get_field(&self, index: usize) -> AwkValue {
+        match index {
+            0 => AwkValue::StrNum(self.record.clone()),
+            _ => match self.fields.get(index - 1) {
+                Some(field) => AwkValue::StrNum(field.clone()),
+                None => AwkValue::Uninit,
+            },
+        }
+    }
+
+    fn set_field(&mut self, index: usize, value: String) -> Result<(), String> {
+        if index == 0 {
+            return self.set_record(value);
+        }
+        if self.fields.len() < index {
+            self.fields.resize(index, String::new());
+        }
+        self.fields[index - 1] = value;
+        self.rebuild_record();
+        Ok(())
+    }
+
+    /// Array subscripts, joined with SUBSEP
+    fn subscript(&mut self, subs: &[AwkExpr]) -> Result<String, String> {
+        let mut parts = Vec::with_capacity(subs.len());
+        for sub in subs {
+            parts.push(self.eval(sub)?.to_str());
+        }
+        Ok(parts.join(&self.get_var("SUBSEP").to_str()))
+    }
+
+    fn assign(&mut self, target: &AwkExpr, value: AwkValue) -> Result<(), String> {
+        match target {
+            AwkExpr::Var(name) => self.set_var(name, value),
+            AwkExpr::Field(index) => {
+                let index = self.field_index(index)?;
+                self.set_field(index, value.to_str())?;
+            }
+            AwkExpr::Index(name, subs) => {
+                let key = self.subscript(subs)?;
+                self.arrays.entry(name.clone()).or_default().set(key, value);
+            }
+            _ => return Err("assignment to non-variable".to_string()),
+        }
+        Ok(())
+    }
+
+    fn exec_block(&mut self, stmts: &[AwkStmt]) -> Result<AwkFlow, String> {
+        for stmt in stmts {
+            let flow = self.exec(stmt)?;
+            if !matches!(flow, AwkFlow::Normal) {
+                return Ok(flow);
+            }
+        }
+        Ok(AwkFlow::Normal)
+    }
+
+    /// Run a loop body; `Some` when the loop must stop with that flow
+    fn exec_loop_body(&mut self, body: &AwkStmt) -> Result<Option<AwkFlow>, String> {
+        Ok(match self.exec(body)? {
+            AwkFlow::Normal | AwkFlow::Continue => None,
+            AwkFlow::Break => Some(AwkFlow::Normal),
+            flow => Some(flow),
+        })
+    }
+
+    fn exec(&mut self, stmt: &AwkStmt) -> Result<AwkFlow, String> {
+        match stmt {
+            AwkStmt::Print(args) => {
+                let line = if args.is_empty() {
+                    self.record.clone()
+                } else {
+                    let mut parts = Vec::with_capacity(args.len());
+                    for arg in args {
+                        parts.push(self.eval(arg)?.to_str());
+                    }
+                    parts.join(&self.get_var("OFS").to_str())
+                };
+                self.print_line(line);
+            }
+            AwkStmt::Printf(args) => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                let formatted = awk_sprintf(&values[0].to_str(), &values[1..]);
+                self.out.push_str(&formatted);
+            }
+            AwkStmt::Expr(expr) => {
+                self.eval(expr)?;
+            }
+            AwkStmt::If(cond, then, otherwise) => {
+                if self.eval(cond)?.is_true() {
+                    return self.exec(then);
+                }
+                if let Some(otherwise) = otherwise {
+                    return self.exec(otherwise);
+                }
+            }
+            AwkStmt::While(cond, body) => {
+                while self.eval(cond)?.is_true() {
+                    if let Some(flow) = self.exec_loop_body(body)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            AwkStmt::DoWhile(body, cond) => loop {
+                if let Some(flow) = self.exec_loop_body(body)? {
+                    return Ok(flow);
+                }
+                if !self.eval(cond)?.is_true() {
+                    break;
+                }
+            },
+            AwkStmt::For(init, cond, post, body) => {
+                if let Some(init) = init {
+                    self.eval(init)?;
+                }
+                loop {
+                    if let Some(cond) = cond {
+                        if !self.eval(cond)?.is_true() {
+                            break;
+                        }
+                    }
+                    if let Some(flow) = self.exec_loop_body(body)? {
+                        return Ok(flow);
+                    }
+                    if let Some(post) = post {
+                        self.eval(post)?;
+                    }
+                }
+            }
+            AwkStmt::ForIn(var, array, body) => {
+                let keys = self
+                    .arrays
+                    .get(array)
+                    .map(|a| a.order.clone())
+                    .unwrap_or_default();
+                for key in keys {
+                    // Skip elements deleted earlier in the loop
+                    if !self
+                        .arrays
+                        .get(array)
+                        .is_some_and(|a| a.values.contains_key(&key))
+                    {
+                        continue;
+                    }
+                    self.set_var(var, AwkValue::StrNum(key));
+                    if let Some(flow) = self.exec_loop_body(body)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            AwkStmt::Block(stmts) => return self.exec_block(stmts),
+            AwkStmt::Next => return Ok(AwkFlow::Next),
+            AwkStmt::Break => return Ok(AwkFlow::Break),
+            AwkStmt::Continue => return Ok(AwkFlow::Continue),
+            AwkStmt::Exit(code) => {
+                if let Some(code) = code {
+                    self.exit_code = self.eval(code)?.to_num() as i32;
+                }
+                return Ok(AwkFlow::Exit);
+            }
+            AwkStmt::Delete(name, None) => {
+                self.arrays.remove(name);
+            }
+            AwkStmt::Delete(name, Some(subs)) => {
+                let key = self.subscript(subs)?;
+                if let Some(array) = self.arrays.get_mut(name) {
+                    array.remove(&key);
+                }
+            }
+        }
+        Ok(AwkFlow::Normal)
+    }
+
+    fn eval(&mut self, expr: &AwkExpr) -> Result<AwkValue, String> {
+        Ok(match expr {
+            AwkExpr::Num(n) => AwkValue::Num(*n),
+            AwkExpr::Str(s) => AwkValue::Str(s.clone()),
+            AwkExpr::Regex(re) => AwkValue::from_bool(re.is_match(&self.record)),
+            AwkExpr::Var(name) => self.get_var(name),
+            AwkExpr::Field(index) => {
+                let index = self.field_index(index)?;
+                self.get_field(index)
+            }
+            AwkExpr::Index(name, subs) => {
+                let key = self.subscript(subs)?;
+                self.arrays.entry(name.clone()).or_default().get(&key)
+            }
+            AwkExpr::Grouping(items) => AwkValue::Str(self.subscript(items)?),
+            AwkExpr::In(subs, name) => {
+                let key = self.subscript(subs)?;
+                AwkValue::from_bool(
+                    self.arrays
+                        .get(name)
+                        .is_some_and(|a| a.values.contains_key(&key)),
+                )
+            }
+            AwkExpr::Assign { target, op, value } => {
+                let value = self.eval(value)?;
+                let value = match op {
+                    None => value,
+                    Some(op) => {
+                        let current = self.eval(target)?;
+                        awk_binary(*op, &current, &value)?
+                    }
+                };
+                self.assign(target, value.clone())?;
+                value
+            }
+            AwkExpr::Cond(cond, then, otherwise) => {
+                if self.eval(cond)?.is_true() {
+                    self.eval(then)?
+                } else {
+                    self.eval(otherwise)?
+                }
+            }
+            AwkExpr::And(left, right) => {
+                AwkValue::from_bool(self.eval(left)?.is_true() && self.eval(right)?.is_true())
+            }
+            AwkExpr::Or(left, right) => {
+                AwkValue::from_bool(self.eval(left)?.is_true() || self.eval(right)?.is_true())
+            }
+            AwkExpr::Match {
+                subject,
+                pattern,
+                negate,
+            } => {
+                let text = self.eval(subject)?.to_str();
+                let re = self.regex_operand(pattern)?;
+                AwkValue::from_bool(re.is_match(&text) != *negate)
+            }
+            AwkExpr::Binary(op, left, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                awk_binary(*op, &left, &right)?
+            }
+            AwkExpr::Not(inner) => AwkValue::from_bool(!self.eval(inner)?.is_true()),
+            AwkExpr::Neg(inner) => AwkValue::Num(-self.eval(inner)?.to_num()),
+            AwkExpr::Plus(inner) => AwkValue::Num(self.eval(inner)?.to_num()),
+            AwkExpr::IncDec {
+                target,
+                delta,
+                prefix,
+            } => {
+                let old = self.eval(target)?.to_num();
+                self.assign(target, AwkValue::Num(old + delta))?;
+                AwkValue::Num(if *prefix { old + delta } else { old })
+            }
+            AwkExpr::Call(name, args) => self.call(name, args)?,
+        })
+    }
+
+    fn call(&mut self, name: &str, args: &[AwkExpr]) -> Result<AwkValue, String> {
+        let (min, max) = match name {
+            "length" => (0, 1),
+            "rand" => (0, 0),
+            "srand" => (0, 1),
+            "substr" | "split" | "sub" | "gsub" => (2, 3),
+            "index" | "match" | "atan2" => (2, 2),
+            "sprintf" => (1, usize::MAX),
+            _ => (1, 1),
+        };
+        if args.len() < min || args.len() > max {
+            return Err(format!("{}: wrong number of arguments", name));
+        }
+
+        let num_arg = |interp: &mut Self, i: usize| -> Result<f64, String> {
+            Ok(interp.eval(&args[i])?.to_num())
+        };
+        let str_arg = |interp: &mut Self, i: usize| -> Result<String, String> {
+            Ok(interp.eval(&args[i])?.to_str())
+        };
+
+        Ok(match name {
+            "length" => {
+                if let Some(AwkExpr::Var(array)) = args.first() {
+                    if let Some(array) = self.arrays.get(array) {
+                        return Ok(AwkValue::Num(array.values.len() as f64));
+                    }
+                }
+                let s = match args.first() {
+                    Some(_) => str_arg(self, 0)?,
+                    None => self.record.clone(),
+                };
+                AwkValue::Num(s.chars().count() as f64)
+            }
+            "substr" => {
+                let chars: Vec<char> = str_arg(self, 0)?.chars().collect();
+                // Positions are 1-based and rounded; out-of-range parts are dropped
+                let start = num_arg(self, 1)?.round();
+                let end = match args.get(2) {
+                    Some(_) => start + num_arg(self, 2)?.round(),
+                    None => f64::INFINITY,
+                };
+                let from = start.max(1.0);
+                let to = end.min(chars.len() as f64 + 1.0);
+                if to <= from {
+                    AwkValue::Str(String::new())
+                } else {
+                    AwkValue::Str(chars[from as usize - 1..to as usize - 1].iter().collect())
+                }
+            }
+            "index" => {
+                let s = str_arg(self, 0)?;
+                let t = str_arg(self, 1)?;
+                let pos = s.find(&t).map_or(0, |b| s[..b].chars().count() + 1);
+                AwkValue::Num(pos as f64)
+            }
+            "split" => {
+                let text = str_arg(self, 0)?;
+                let AwkExpr::Var(array) = &args[1] else {
+                    return Err("split: second argument is not an array".to_string());
+                };
+                let pieces = match args.get(2) {
+                    Some(AwkExpr::Regex(re)) if !text.is_empty() => {
+                        re.split(&text).map(String::from).collect()
+                    }
+                    Some(AwkExpr::Regex(_)) => Vec::new(),
+                    Some(sep) => {
+                        let sep = self.eval(sep)?.to_str();
+                        self.split_text(&text, &sep)?
+                    }
+                    None => {
+                        let fs = self.get_var("FS").to_str();
+                        self.split_text(&text, &fs)?
+                    }
+                };
+                let mut elements = AwkArray::default();
+                for (i, piece) in pieces.iter().enumerate() {
+                    elements.set((i + 1).to_string(), AwkValue::StrNum(piece.clone()));
+                }
+                self.arrays.insert(array.clone(), elements);
+                AwkValue::Num(pieces.len() as f64)
+            }
+            "sub" | "gsub" => {
+                let re = self.regex_operand(&args[0])?;
+                let replacement = str_arg(self, 1)?;
+                let record = AwkExpr::Field(Box::new(AwkExpr::Num(0.0)));
+                let target = args.get(2).unwrap_or(&record);
+                if !target.is_lvalue() {
+                    return Err(format!("{}: third argument is not a variable", name));
+                }
+                let text = self.eval(target)?.to_str();
+                let (result, count) = awk_substitute(&re, &text, &replacement, name == "gsub");
+                if count > 0 {
+                    self.assign(target, AwkValue::Str(result))?;
+                }
+                AwkValue::Num(count as f64)
+            }
+            "match" => {
+                let text = str_arg(self, 0)?;
+                let re = self.regex_operand(&args[1])?;
+                let (start, length) = match re.find(&text) {
+                    Some(m) => (
+                        text[..m.start()].chars().count() as f64 + 1.0,
+                        m.as_str().chars().count() as f64,
+                    ),
+                    None => (0.0, -1.0),
+                };
+                self.set_var("RSTART", AwkValue::Num(start));
+                self.set_var("RLENGTH", AwkValue::Num(length));
+                AwkValue::Num(start)
+            }
+            "sprintf" => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                AwkValue::Str(awk_sprintf(&values[0].to_str(), &values[1..]))
+            }
+            "tolower" => AwkValue::Str(str_arg(self, 0)?.to_lowercase()),
+            "toupper" => AwkValue::Str(str_arg(self, 0)?.to_uppercase()),
+            "int" => AwkValue::Num(num_arg(self, 0)?.trunc()),
+            "sqrt" => AwkValue::Num(num_arg(self, 0)?.sqrt()),
+            "exp" => AwkValue::Num(num_arg(self, 0)?.exp()),
+            "log" => AwkValue::Num(num_arg(self, 0)?.ln()),
+            "sin" => AwkValue::Num(num_arg(self, 0)?.sin()),
+            "cos" => AwkValue::Num(num_arg(self, 0)?.cos()),
+            "atan2" => AwkValue::Num(num_arg(self, 0)?.atan2(num_arg(self, 1)?)),
+            "rand" => {
+                // 64-bit LCG; the top 53 bits make a float in [0, 1)
+                self.rand_state = self
+                    .rand_state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                AwkValue::Num((self.rand_state >> 11) as f64 / (1u64 << 53) as f64)
+            }
+            "srand" => {
+                let seed = match args.first() {
+                    Some(_) => num_arg(self, 0)?,
+                    None => (get_random_u64() % 1_000_000_000) as f64,
+                };
+                self.rand_state = seed.to_bits();
+                AwkValue::Num(std::mem::replace(&mut self.rand_seed, seed))
+            }
+            _ => return Err(format!("function `{}' is not defined", name)),
+        })
+    }
+}
+
+fn awk_binary(op: AwkBinOp, left: &AwkValue, right: &AwkValue) -> Result<AwkValue, String> {
+    let arith = |f: fn(f64, f64) -> f64| AwkValue::Num(f(left.to_num(), right.to_num()));
+    Ok(match op {
+        AwkBinOp::Add => arith(|a, b| a + b),
+        AwkBinOp::Sub => arith(|a, b| a - b),
+        AwkBinOp::Mul => arith(|a, b| a * b),
+        AwkBinOp::Pow => arith(f64::powf),
+        AwkBinOp::Div | AwkBinOp::Mod => {
+            if right.to_num() == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            if matches!(op, AwkBinOp::Div) {
+                arith(|a, b| a / b)
+            } else {
+                arith(|a, b| a % b)
+            }
+        }
+        AwkBinOp::Concat => AwkValue::Str(left.to_str() + &right.to_str()),
+        _ => {
+            let ordering = if left.is_numeric() && right.is_numeric() {
+                left.to_num().partial_cmp(&right.to_num())
+            } else {
+                Some(left.to_str().cmp(&right.to_str()))
+            };
+            let result = match op {
+                AwkBinOp::Lt => ordering == Some(std::cmp::Ordering::Less),
+                AwkBinOp::Le => matches!(
+                    ordering,
+                    Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+                ),
+                AwkBinOp::Gt => ordering == Some(std::cmp::Ordering::Greater),
+                AwkBinOp::Ge => matches!(
+                    ordering,
+                    Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal)
+                ),
+                AwkBinOp::Eq => ordering == Some(std::cmp::Ordering::Equal),
+                _ => ordering != Some(std::cmp::Ordering::Equal),
+            };
+            AwkValue::from_bool(result)
+        }
+    })
+}
+
+/// sub/gsub: `&` in the replacement is the matched text, `\&` a literal `&`
+fn awk_substitute(
+    re: &regex::Regex,
+    text: &str,
+    replacement: &str,
+    global: bool,
+) -> (String, usize) {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut count = 0;
+    for m in re.find_iter(text) {
+        out.push_str(&text[last..m.start()]);
+        let mut chars = replacement.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '&' => out.push_str(m.as_str()),
+                '\\' => match chars.next() {
+                    Some(next @ ('&' | '\\')) => out.push(next),
+                    Some(next) => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                    None => out.push('\\'),
+                },
+                _ => out.push(c),
+            }
+        }
+        last = m.end();
+        count += 1;
+        if !global {
+            break;
+        }
+    }
+    out.push_str(&text[last..]);
+    (out, count)
+}
+
+/// awk's printf/sprintf, on the shared printf spec and number formatting
+fn awk_sprintf(format: &str, args: &[AwkValue]) -> String {
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+    let mut args = args.iter();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+        let spec = parse_printf_spec(&mut chars, || {
+            args.next().map_or(0, |value| value.to_num() as i64)
+        });
+        let Some(conv) = chars.next() else {
+            out.push('%');
+            break;
+        };
+        if !"cdieEfFgGosuxX".contains(conv) {
+            // Not a conversion: print it as written
+            out.push('%');
+            out.push(conv);
+            continue;
+        }
+        let arg = args.next().cloned().unwrap_or(AwkValue::Uninit);
+        let formatted = match conv {
+            's' => {
+                let s = arg.to_str();
+                match spec.precision {
+                    Some(p) => s.chars().take(p).collect(),
+                    None => s,
+                }
+            }
+            // A number prints as the character with that code
+            'c' => match arg {
+                AwkValue::Num(n) => char::from_u32(n as u32)
+                    .map(String::from)
+                    .unwrap_or_default(),
+                other => other
+                    .to_str()
+                    .chars()
+                    .next()
+                    .map(String::from)
+                    .unwrap_or_default(),
+            },
+            'd' | 'i' => {
+                let n = arg.to_num().trunc();
+                format_printf_integer(n < 0.0, n.abs() as u64, 10, false, &spec)
+            }
+            'o' | 'u' | 'x' | 'X' => {
+                let n = arg.to_num().trunc() as i64 as u64;
+                let radix = match conv {
+                    'x' | 'X' => 16,
+                    'o' => 8,
+                    _ => 10,
+                };
+                let digits = format_printf_integer(false, n, radix, n != 0, &spec);
+                if conv == 'X' {
+                    digits.to_uppercase()
+                } else {
+                    digits
+                }
+            }
+            _ => format_printf_float(arg.to_num(), conv, &spec),
+        };
+        out.push_str(&pad_printf(&formatted, conv, &spec));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn awk(program: &str, input: &str) -> String {
+        let parsed = parse_awk_program(program).unwrap();
+        let mut interp = AwkInterp::new(&parsed);
+        if !interp.run_begin().unwrap() {
+            for line in input.lines() {
+                if interp.run_record(line).unwrap() {
+                    break;
+                }
+            }
+        }
+        interp.run_end().unwrap();
+        interp.take_output()
+    }
+
+    #[test]
+    fn test_awk_parse_simple() {
+        let prog = parse_awk_program("{print}").unwrap();
+        assert_eq!(prog.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_awk_parse_field() {
+        let prog = parse_awk_program("{print $1}").unwrap();
+        assert_eq!(prog.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_awk_parse_multiple_fields() {
+        let prog = parse_awk_program("{print $1,$3}").unwrap();
+        assert_eq!(prog.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_awk_execute() {
+        assert_eq!(awk("{print $2}", "one two three"), "two\n");
+    }
+
+    #[test]
+    fn test_awk_field_extraction() {
+        let input = "alice 30 nyc\nbob  25   sf\n";
+        assert_eq!(awk("{print $1, $NF}", input), "alice nyc\nbob sf\n");
+        assert_eq!(awk("{print NF, $(NF-1)}", input), "3 30\n3 25\n");
+        assert_eq!(awk("{print $5 \"|\"}", input), "|\n|\n");
+        assert_eq!(awk("BEGIN { FS = \":\" } { print $2 }", "a:b:c\n"), "b\n");
+        assert_eq!(
+            awk("BEGIN { FS = \"[,;]\" } { print $3 }", "a,b;c\n"),
+            "c\n"
+        );
+        assert_eq!(awk("{ $2 = \"X\"; print }", "a b c\n"), "a X c\n");
+        assert_eq!(
+            awk("BEGIN { OFS = \"-\" } { $1 = $1; print }", "a b\n"),
+            "a-b\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_begin_end_and_counters() {
+        let program = "BEGIN { print \"start\" } { n++ } END { print n, NR }";
+        assert_eq!(awk(program, "x\ny\nz\n"), "start\n3 3\n");
+        assert_eq!(awk("{ s += $2 } END { print s }", "a 1\nb 1.5\n"), "2.5\n");
+        assert_eq!(awk("BEGIN { print 1/3 }", ""), "0.333333\n");
+        assert_eq!(awk("BEGIN { print 2^10, 7 % 3, -2^2 }", ""), "1024 1 -4\n");
+        assert_eq!(awk("END { print NR }", ""), "0\n");
+    }
+
+    #[test]
+    fn test_awk_patterns() {
+        let input = "a 90\nb 50\nc 80\nd 10\n";
+        assert_eq!(awk("/b|c/ { print $1 }", input), "b\nc\n");
+        assert_eq!(awk("$2 >= 80 { print $1 }", input), "a\nc\n");
+        assert_eq!(awk("NR > 1 && NR < 4", input), "b 50\nc 80\n");
+        assert_eq!(awk("/^b/,/^c/ { print $1 }", input), "b\nc\n");
+        assert_eq!(awk("$1 !~ /[ab]/ { print $1 }", input), "c\nd\n");
+        assert_eq!(awk("!/0$/", "x0\ny1\n"), "y1\n");
+    }
+
+    #[test]
+    fn test_awk_strnum_comparisons() {
+        // Fields that look numeric compare as numbers
+        assert_eq!(awk("$1 < $2 { print \"lt\" }", "9 10\n"), "lt\n");
+        assert_eq!(awk("$1 == 10 { print \"eq\" }", "10.0\n"), "eq\n");
+        // Strings compare as strings
+        assert_eq!(
+            awk(
+                "BEGIN { if (\"9\" < \"10\") print \"lt\"; else print \"ge\" }",
+                ""
+            ),
+            "ge\n"
+        );
+        assert_eq!(
+            awk("BEGIN { if (x == 0 && x == \"\") print \"uninit\" }", ""),
+            "uninit\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_printf_and_sprintf() {
+        assert_eq!(
+            awk(
+                "{ printf \"%-5s|%3d|%.2f\\n\", $1, $2, $3 }",
+                "ab 7 3.14159\n"
+            ),
+            "ab   |  7|3.14\n"
+        );
+        assert_eq!(
+            awk("BEGIN { x = sprintf(\"%05.1f%%\", 2.25); print x }", ""),
+            "002.2%\n"
+        );
+        assert_eq!(awk("BEGIN { printf \"%c%c\\n\", 65, \"bc\" }", ""), "Ab\n");
+        assert_eq!(
+            awk("BEGIN { printf \"%x %o %e\\n\", 255, 8, 1234.5 }", ""),
+            "ff 10 1.234500e+03\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_builtins() {
+        assert_eq!(
+            awk("{ print length(), length($1) }", "hello world\n"),
+            "11 5\n"
+        );
+        assert_eq!(awk("{ print length }", "abc\n"), "3\n");
+        assert_eq!(
+            awk(
+                "BEGIN { s = \"hello\"; print substr(s, 2, 3), substr(s, 4), substr(s, 0, 2) }",
+                ""
+            ),
+            "ell lo h\n"
+        );
+        assert_eq!(
+            awk(
+                "BEGIN { print index(\"hello\", \"ll\"), index(\"a\", \"z\") }",
+                ""
+            ),
+            "3 0\n"
+        );
+        assert_eq!(
+            awk(
+                "BEGIN { n = split(\"a:b:c\", parts, \":\"); print n, parts[1], parts[3] }",
+                ""
+            ),
+            "3 a c\n"
+        );
+        assert_eq!(
+            awk("BEGIN { print toupper(\"ab\") tolower(\"CD\") }", ""),
+            "ABcd\n"
+        );
+        assert_eq!(
+            awk("{ n = gsub(/o/, \"0\"); print n, $0 }", "foo boo\n"),
+            "4 f00 b00\n"
+        );
+        assert_eq!(
+            awk("{ sub(/o+/, \"[&]\"); print }", "foo boo\n"),
+            "f[oo] boo\n"
+        );
+        assert_eq!(
+            awk("{ sub(/b/, \"x\", $2); print $2 }", "abc abc\n"),
+            "axc\n"
+        );
+        assert_eq!(
+            awk("{ print match($0, /[0-9]+/), RSTART, RLENGTH }", "ab123c\n"),
+            "3 3 3\n"
+        );
+        assert_eq!(
+            awk("BEGIN { print int(3.9), int(-3.9), sqrt(16) }", ""),
+            "3 -3 4\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_arrays_and_loops() {
+        let input = "a 1\nb 2\na 3\n";
+        assert_eq!(
+            awk(
+                "{ sum[$1] += $2 } END { for (k in sum) print k, sum[k] }",
+                input
+            ),
+            "a 4\nb 2\n"
+        );
+        assert_eq!(
+            awk(
+                "{ seen[$1]++ } END { print (\"a\" in seen), (\"z\" in seen), length(seen) }",
+                input
+            ),
+            "1 0 2\n"
+        );
+        assert_eq!(
+            awk("BEGIN { for (i = 1; i <= 3; i++) s = s i; print s }", ""),
+            "123\n"
+        );
+        assert_eq!(
+            awk(
+                "BEGIN { i = 0; while (1) { if (++i > 2) break }; print i }",
+                ""
+            ),
+            "3\n"
+        );
+        assert_eq!(
+            awk(
+                "BEGIN { a[1]; a[2]; delete a[1]; for (k in a) print k }",
+                ""
+            ),
+            "2\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_next_and_exit() {
+        let input = "1\n2\n3\n4\n";
+        assert_eq!(awk("$1 == 2 { next } { print }", input), "1\n3\n4\n");
+        assert_eq!(
+            awk("$1 == 3 { exit } { print } END { print \"end\" }", input),
+            "1\n2\nend\n"
+        );
+        assert_eq!(awk("BEGIN { exit } END { print \"end\" }", input), "end\n");
+    }
+
+    #[test]
+    fn test_awk_multiline_program() {
+        let program = "
+            # count words
+            {
+                for (i = 1; i <= NF; i++)
+                    words++
+            }
+            END {
+                if (words > 3)
+                    print \"many\", words
+                else
+                    print \"few\"
+            }";
+        assert_eq!(awk(program, "a b\nc d e\n"), "many 5\n");
+    }
+
+    #[test]
+    fn test_awk_errors() {
+        for program in [
+            "{ print ",
+            "{ x = }",
+            "{ print > \"f\" }",
+            "function f() {}",
+            "{ foo(1) }",
+        ] {
+            assert!(
+                parse_awk_program(program).is_err(),
+                "{:?} should not parse",
+                program
+            );
+        }
+        let parsed = parse_awk_program("{ print 1 / $1 }").unwrap();
+        let mut interp = AwkInterp::new(&parsed);
+        assert_eq!(interp.run_record("0").unwrap_err(), "division by zero");
+    }
+
+    #[test]
+    fn test_awk_parse_print_all() {
+        let prog = parse_awk_program("{print $0}").unwrap();
+        assert_eq!(prog.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_awk_empty_line() {
+        assert_eq!(awk("{print $1}", "\n"), "\n");
+    }
+
+    #[test]
+    fn test_awk_field_out_of_bounds() {
+        assert_eq!(awk("{print $10}", "one two"), "\n");
+    }
+
+    #[test]
+    fn test_awk_nr_variable() {
+        assert_eq!(awk("{print}", "x"), "x\n");
+        assert_eq!(awk("{print NR}", "a\nb\nc"), "1\n2\n3\n");
+    }
+
+    #[test]
+    fn test_awk_nf_variable() {
+        assert_eq!(awk("{print $1}", "a b c d"), "a\n");
+        assert_eq!(awk("{print NF}", "a b c d"), "4\n");
+    }
+
+    #[test]
+    fn test_awk_with_pattern() {
+        let prog = parse_awk_program("/hello/{print $1}").unwrap();
+        assert!(!prog.rules.is_empty());
+        assert_eq!(
+            awk("/hello/{print $1}", "hello world\ngoodbye world"),
+            "hello\n"
+        );
+    }
+
+    #[test]
+    fn test_awk_multiple_print_fields_joined() {
+        assert_eq!(awk("{print $1,$2,$3}", "a b c"), "a b c\n");
+    }
+}
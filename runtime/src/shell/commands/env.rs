@@ -1,9 +1,10 @@
-//! Environment commands: env, printenv
+//! Environment commands: env, printenv, dotenv
 
 use futures_lite::io::AsyncWriteExt;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
+use super::helpers::resolve_path;
 use super::parse_common;
 
 /// Environment commands.
@@ -69,4 +70,394 @@ impl EnvCommands {
             0
         })
     }
+
+    /// dotenv - read and write .env files
+    #[shell_command(
+        name = "dotenv",
+        usage = "dotenv get KEY [FILE] | set KEY=VALUE [FILE] | list [FILE] | run [-f FILE] -- CMD [ARGS...]",
+        description = "Read, update and run commands with .env files"
+    )]
+    fn cmd_dotenv(
+        args: Vec<String>,
+        env: &ShellEnv,
+        stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let env = env.clone();
+        Box::pin(async move {
+            let cwd = env.cwd.to_string_lossy().to_string();
+            let Some(subcommand) = args.first() else {
+                let _ = stderr
+                    .write_all(b"dotenv: missing subcommand (get, set, list, run)\n")
+                    .await;
+                return 1;
+            };
+            let rest = &args[1..];
+
+            // The shell runs `dotenv run` itself, so the command can be a
+            // function, builtin or lazy module; callers using the registry
+            // directly get registry commands only
+            if subcommand == "run" {
+                let run = match parse_dotenv_run(rest, &cwd) {
+                    Ok(run) => run,
+                    Err(e) => {
+                        let _ = stderr.write_all(e.as_bytes()).await;
+                        return 1;
+                    }
+                };
+                let cmd_name = &run.command[0];
+                let mut child_env = env.clone();
+                for (key, value) in &run.vars {
+                    if let Err(e) = child_env.export_var(key, Some(value)) {
+                        let _ = stderr
+                            .write_all(format!("dotenv: {}\n", e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                }
+                let Some(cmd_fn) = super::ShellCommands::get_command(cmd_name) else {
+                    let _ = stderr
+                        .write_all(format!("dotenv: {}: command not found\n", cmd_name).as_bytes())
+                        .await;
+                    return 127;
+                };
+                return cmd_fn(run.command[1..].to_vec(), &child_env, stdin, stdout, stderr).await;
+            }
+
+            let (_, rest) = parse_common(rest);
+            match subcommand.as_str() {
+                "get" => {
+                    let Some(key) = rest.first() else {
+                        let _ = stderr.write_all(b"dotenv: get: missing KEY\n").await;
+                        return 1;
+                    };
+                    let file = rest.get(1).map(String::as_str).unwrap_or(".env");
+                    let content = match read_dotenv_file(&resolve_path(&cwd, file)) {
+                        Ok(content) => content,
+                        Err(e) => {
+                            let _ = stderr
+                                .write_all(format!("dotenv: {}: {}\n", file, e).as_bytes())
+                                .await;
+                            return 1;
+                        }
+                    };
+                    // Later assignments win, as when the file is sourced
+                    match parse_dotenv(&content)
+                        .into_iter()
+                        .rev()
+                        .find(|(k, _)| k == key)
+                    {
+                        Some((_, value)) => {
+                            let _ = stdout.write_all(format!("{}\n", value).as_bytes()).await;
+                            0
+                        }
+                        None => 1,
+                    }
+                }
+                "set" => {
+                    let Some((key, value)) = rest.first().and_then(|a| a.split_once('=')) else {
+                        let _ = stderr.write_all(b"dotenv: set: expected KEY=VALUE\n").await;
+                        return 1;
+                    };
+                    if !is_dotenv_key(key) {
+                        let _ = stderr
+                            .write_all(format!("dotenv: set: invalid key: {}\n", key).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                    let file = rest.get(1).map(String::as_str).unwrap_or(".env");
+                    let path = resolve_path(&cwd, file);
+                    // A missing file is created
+                    let content = std::fs::read_to_string(&path).unwrap_or_default();
+                    let updated = set_dotenv_value(&content, key, value);
+                    if let Err(e) = std::fs::write(&path, updated) {
+                        let _ = stderr
+                            .write_all(format!("dotenv: {}: {}\n", file, e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                    0
+                }
+                "list" => {
+                    let file = rest.first().map(String::as_str).unwrap_or(".env");
+                    match read_dotenv_file(&resolve_path(&cwd, file)) {
+                        Ok(content) => {
+                            for (key, value) in parse_dotenv(&content) {
+                                let line = format!("{}={}\n", key, value);
+                                let _ = stdout.write_all(line.as_bytes()).await;
+                            }
+                            0
+                        }
+                        Err(e) => {
+                            let _ = stderr
+                                .write_all(format!("dotenv: {}: {}\n", file, e).as_bytes())
+                                .await;
+                            1
+                        }
+                    }
+                }
+                other => {
+                    let _ = stderr
+                        .write_all(format!("dotenv: unknown subcommand: {}\n", other).as_bytes())
+                        .await;
+                    1
+                }
+            }
+        })
+    }
+}
+
+/// A `dotenv run [-f FILE] -- CMD [ARGS...]` command line
+pub(crate) struct DotenvRun {
+    /// Variables read from the file, to export to the command
+    pub(crate) vars: Vec<(String, String)>,
+    /// The command and its arguments (never empty)
+    pub(crate) command: Vec<String>,
+}
+
+/// Parse the arguments after `dotenv run` and read the file from `cwd`.
+/// Errors are complete stderr lines.
+pub(crate) fn parse_dotenv_run(args: &[String], cwd: &str) -> Result<DotenvRun, String> {
+    let mut file = ".env".to_string();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--" => {
+                i += 1;
+                break;
+            }
+            "-f" | "--file" if i + 1 < args.len() => {
+                file = args[i + 1].clone();
+                i += 2;
+            }
+            _ => break,
+        }
+    }
+    let command = args[i..].to_vec();
+    if command.is_empty() {
+        return Err("dotenv: run: missing command\n".to_string());
+    }
+    let content = read_dotenv_file(&resolve_path(cwd, &file))
+        .map_err(|e| format!("dotenv: {}: {}\n", file, e))?;
+    Ok(DotenvRun {
+        vars: parse_dotenv(&content),
+        command,
+    })
+}
+
+fn read_dotenv_file(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+fn is_dotenv_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Split a line into its key and raw value, ignoring blanks, comments and an
+/// optional `export ` prefix.
+fn split_dotenv_line(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return None;
+    }
+    let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+    let (key, raw) = trimmed.split_once('=')?;
+    let key = key.trim();
+    is_dotenv_key(key).then_some((key, raw))
+}
+
+/// Decode a raw value: double quotes honour `\n`, `\t`, `\"` and `\\`
+/// escapes, single quotes are literal, and unquoted values end at ` #`.
+fn parse_dotenv_value(raw: &str) -> String {
+    let raw = raw.trim();
+    if let Some(inner) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(other) => value.push(other),
+                    None => value.push('\\'),
+                },
+                _ => value.push(c),
+            }
+        }
+        return value;
+    }
+    if let Some(inner) = raw.strip_prefix('\'') {
+        return inner.split('\'').next().unwrap_or("").to_string();
+    }
+    let value = match raw.find(" #") {
+        Some(pos) => &raw[..pos],
+        None => raw,
+    };
+    value.trim_end().to_string()
+}
+
+/// Parse `.env` content into key/value pairs in file order.
+fn parse_dotenv(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(split_dotenv_line)
+        .map(|(key, raw)| (key.to_string(), parse_dotenv_value(raw)))
+        .collect()
+}
+
+/// Encode a value, quoting only when it would not survive unquoted.
+fn format_dotenv_value(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(['"', '\''])
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c == '#' || c == '\\');
+    if !needs_quotes {
+        return value.to_string();
+    }
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Replace the first assignment of `key` in place (keeping any `export `
+/// prefix), or append one; all other lines are left untouched.
+fn set_dotenv_value(content: &str, key: &str, value: &str) -> String {
+    let encoded = format_dotenv_value(value);
+    let mut replaced = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|line| match split_dotenv_line(line) {
+            Some((k, _)) if k == key && !replaced => {
+                replaced = true;
+                let export = if line.trim_start().starts_with("export ") {
+                    "export "
+                } else {
+                    ""
+                };
+                format!("{}{}={}", export, key, encoded)
+            }
+            _ => line.to_string(),
+        })
+        .collect();
+    if !replaced {
+        lines.push(format!("{}={}", key, encoded));
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::run_pipeline;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let dir = std::env::temp_dir().join(format!("dotenv-{}-{}", name, nanos));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_dotenv_parse_quoting() {
+        let content = "# comment\nexport A=1\nB=\"two words\\n\\\"q\\\"\"\nC='lit $X \\n'\nD=plain # note\n\nbad line\n";
+        assert_eq!(
+            parse_dotenv(content),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words\n\"q\"".to_string()),
+                ("C".to_string(), "lit $X \\n".to_string()),
+                ("D".to_string(), "plain".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dotenv_set_get_round_trip_preserves_other_lines() {
+        let original = "# database\nexport HOST=localhost\nPORT=5432 # default\n\n# trailing\n";
+        let updated = set_dotenv_value(original, "PORT", "6543");
+        assert_eq!(
+            updated,
+            "# database\nexport HOST=localhost\nPORT=6543\n\n# trailing\n"
+        );
+        let updated = set_dotenv_value(&updated, "HOST", "db #1");
+        let updated = set_dotenv_value(&updated, "NEW", "a \"b\"");
+        assert_eq!(
+            updated,
+            "# database\nexport HOST=\"db #1\"\nPORT=6543\n\n# trailing\nNEW=\"a \\\"b\\\"\"\n"
+        );
+        let entries = parse_dotenv(&updated);
+        assert_eq!(entries[0], ("HOST".to_string(), "db #1".to_string()));
+        assert_eq!(entries[2], ("NEW".to_string(), "a \"b\"".to_string()));
+
+        let dir = temp_dir("roundtrip");
+        std::fs::write(dir.join(".env"), original).unwrap();
+        let mut env = ShellEnv::new();
+        env.cwd = dir.clone();
+        let result =
+            futures_lite::future::block_on(run_pipeline("dotenv set PORT='1 2'", &mut env));
+        assert_eq!(result.code, 0, "stderr: {}", result.stderr);
+        let result = futures_lite::future::block_on(run_pipeline("dotenv get PORT", &mut env));
+        assert_eq!(result.stdout, "1 2\n");
+        let content = std::fs::read_to_string(dir.join(".env")).unwrap();
+        assert!(content.starts_with("# database\nexport HOST=localhost\n"));
+        assert!(content.ends_with("\n# trailing\n"));
+        let result = futures_lite::future::block_on(run_pipeline("dotenv get MISSING", &mut env));
+        assert_eq!(result.code, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dotenv_run_injects_variables() {
+        let dir = temp_dir("run");
+        std::fs::write(dir.join("app.env"), "GREETING=\"hello there\"\n").unwrap();
+        let mut env = ShellEnv::new();
+        env.cwd = dir.clone();
+        let result = futures_lite::future::block_on(run_pipeline(
+            "dotenv run -f app.env -- printenv GREETING",
+            &mut env,
+        ));
+        assert_eq!(result.code, 0, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "hello there\n");
+        // The calling shell's environment is unchanged
+        assert!(env.get_var("GREETING").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_dotenv_run_resolves_functions_and_builtins() {
+        let dir = temp_dir("run_shell");
+        std::fs::write(dir.join(".env"), "NAME=world\n").unwrap();
+        let mut env = ShellEnv::new();
+        env.cwd = dir.clone();
+        let script = r#"greet() { echo "hello $NAME"; }
+dotenv run -- greet
+dotenv run -- cd /no/such/dir
+echo "cd $?"
+echo piped | dotenv run -- cat"#;
+        let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+        assert_eq!(result.stdout, "hello world\ncd 1\npiped\n");
+        assert!(env.get_var("NAME").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,12 +1,12 @@
-//! File manipulation commands: ls, cat, touch, mkdir, rmdir, rm, mv, cp
+//! File manipulation commands: ls, cat, touch, mkdir, rmdir, rm, mv, cp, tree
 
-use crate::bindings::wasi::cli::terminal_stdout::get_terminal_stdout;
 use futures_lite::io::AsyncWriteExt;
 use lexopt::prelude::*;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
-use super::helpers::resolve_path;
+use super::gitignore::GitIgnore;
+use super::helpers::{resolve_path, stdout_is_terminal};
 use super::{make_parser, parse_common};
 
 /// File manipulation commands.
@@ -85,8 +85,7 @@ impl FileCommands {
             }
 
             // Determine if we should use color using WASI isatty equivalent
-            // get_terminal_stdout() returns Some(_) if stdout is a TTY, None otherwise
-            let is_tty = get_terminal_stdout().is_some();
+            let is_tty = stdout_is_terminal();
             let use_color = match color_mode {
                 ColorMode::Always => true,
                 ColorMode::Never => false,
@@ -249,8 +248,12 @@ impl FileCommands {
     /// touch - create empty file or update timestamps
     #[shell_command(
         name = "touch",
-        usage = "touch FILE...",
-        description = "Create empty files or update timestamps"
+        usage = "touch [-acm] [-t STAMP | -d DATE] FILE...",
+        description = "Create empty files or update timestamps (to now by default).\n\
+        -a: Change only the access time   -m: Change only the modification time\n\
+        -c, --no-create: Do not create missing files\n\
+        -t STAMP: Use [[CC]YY]MMDDhhmm[.ss] instead of the current time\n\
+        -d, --date DATE: Use DATE (@EPOCH or ISO-8601, as with date -d)"
     )]
     fn cmd_touch(
         args: Vec<String>,
@@ -262,20 +265,86 @@ impl FileCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            if remaining.is_empty() {
+            let mut access = false;
+            let mut modify = false;
+            let mut no_create = false;
+            let mut secs: Option<i64> = None;
+            let mut files: Vec<String> = Vec::new();
+            let mut parser = make_parser(remaining);
+
+            while let Some(arg) = parser.next().ok().flatten() {
+                match arg {
+                    Short('a') => access = true,
+                    Short('m') => modify = true,
+                    Short('c') | Long("no-create") => no_create = true,
+                    Short('t') | Short('d') | Long("date") => {
+                        let touch_stamp = matches!(arg, Short('t'));
+                        let Ok(val) = parser.value() else {
+                            let _ = stderr
+                                .write_all(b"touch: option requires an argument\n")
+                                .await;
+                            return 1;
+                        };
+                        let val = val.string().unwrap_or_default();
+                        let stamp = if touch_stamp {
+                            parse_touch_stamp(&val)
+                        } else {
+                            super::misc::parse_date(&val)
+                        };
+                        match stamp {
+                            Some(stamp) => secs = Some(stamp),
+                            None => {
+                                let msg = format!("touch: invalid date format '{}'\n", val);
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                                return 1;
+                            }
+                        }
+                    }
+                    Value(val) => files.push(val.string().unwrap_or_default()),
+                    _ => {}
+                }
+            }
+
+            if files.is_empty() {
                 let _ = stderr.write_all(b"touch: missing file operand\n").await;
                 return 1;
             }
 
+            let time = secs.map_or_else(std::time::SystemTime::now, |secs| {
+                let since = std::time::Duration::from_secs(secs.unsigned_abs());
+                if secs < 0 {
+                    std::time::UNIX_EPOCH - since
+                } else {
+                    std::time::UNIX_EPOCH + since
+                }
+            });
+            // Neither -a nor -m means both
+            let mut times = std::fs::FileTimes::new();
+            if access || !modify {
+                times = times.set_accessed(time);
+            }
+            if modify || !access {
+                times = times.set_modified(time);
+            }
+
             let mut exit_code = 0;
-            for arg in &remaining {
-                let path = resolve_path(&cwd, &arg);
+            for arg in &files {
+                let path = resolve_path(&cwd, arg);
+                let exists = std::fs::metadata(&path).is_ok();
+                if !exists && no_create {
+                    continue;
+                }
 
-                if let Err(e) = std::fs::OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .open(&path)
-                {
+                // Directories can't be opened for writing
+                let opened = if exists && std::path::Path::new(&path).is_dir() {
+                    std::fs::File::open(&path)
+                } else {
+                    std::fs::OpenOptions::new()
+                        .create(true)
+                        .write(true)
+                        .open(&path)
+                };
+                if let Err(e) = opened.and_then(|file| file.set_times(times)) {
                     let msg = format!("touch: {}: {}\n", path, e);
                     let _ = stderr.write_all(msg.as_bytes()).await;
                     exit_code = 1;
@@ -553,134 +622,37 @@ impl FileCommands {
     /// find - search for files
     #[shell_command(
         name = "find",
-        usage = "find [PATH] [-name PATTERN] [-type f|d]",
-        description = "Search for files in a directory hierarchy"
+        usage = "find [PATH]... [--gitignore] [-name GLOB] [-path GLOB] [-type f|d|l] [-maxdepth N] [-mindepth N] [-exec CMD {} ;|+]",
+        description = "Search for files in a directory hierarchy.\n\
+        --gitignore: Skip .git and paths ignored by .gitignore files"
     )]
     fn cmd_find(
         args: Vec<String>,
         env: &ShellEnv,
         _stdin: piper::Reader,
         mut stdout: piper::Writer,
-        _stderr: piper::Writer,
+        mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
-        let cwd = env.cwd.to_string_lossy().to_string();
+        let cwd = env.cwd.clone();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut search_path = "/".to_string();
-            let mut name_pattern: Option<String> = None;
-            let mut type_filter: Option<char> = None;
-            let mut max_depth: Option<usize> = None;
-
-            // Parse manually because find uses POSIX primary expressions
-            // (-name, -type) not GNU-style flags that lexopt handles.
-            let mut i = 0;
-            let mut path_set = false;
-            while i < remaining.len() {
-                match remaining[i].as_str() {
-                    "-name" | "--name" => {
-                        if i + 1 < remaining.len() {
-                            name_pattern = Some(remaining[i + 1].clone());
-                            i += 2;
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    "-type" | "--type" => {
-                        if i + 1 < remaining.len() {
-                            type_filter = remaining[i + 1].chars().next();
-                            i += 2;
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    "-maxdepth" => {
-                        if i + 1 < remaining.len() {
-                            max_depth = remaining[i + 1].parse().ok();
-                            i += 2;
-                        } else {
-                            i += 1;
-                        }
-                    }
-                    arg if !arg.starts_with('-') && !path_set => {
-                        // First non-option arg is the search path
-                        let path = arg.to_string();
-                        search_path = resolve_path(&cwd, &path);
-                        path_set = true;
-                        i += 1;
-                    }
-                    _ => {
-                        i += 1;
-                    }
-                }
-            }
-
-            fn find_recursive(
-                dir: &str,
-                pattern: &Option<String>,
-                type_filter: Option<char>,
-                max_depth: Option<usize>,
-                current_depth: usize,
-                results: &mut Vec<String>,
-            ) {
-                if let Some(max) = max_depth {
-                    if current_depth > max {
-                        return;
-                    }
-                }
-                if let Ok(entries) = std::fs::read_dir(dir) {
-                    for entry in entries.filter_map(|e| e.ok()) {
-                        let path = entry.path();
-                        let path_str = path.to_string_lossy().to_string();
-                        let file_name = entry.file_name().to_string_lossy().to_string();
-                        let is_dir = path.is_dir();
-
-                        // Check type filter
-                        let type_ok = match type_filter {
-                            Some('f') => !is_dir,
-                            Some('d') => is_dir,
-                            _ => true,
-                        };
-
-                        // Check name pattern (glob-like)
-                        let name_ok = match pattern {
-                            Some(pat) => glob_match(pat, &file_name),
-                            None => true,
-                        };
-
-                        if type_ok && name_ok {
-                            results.push(path_str.clone());
-                        }
-
-                        if is_dir {
-                            find_recursive(
-                                &path_str,
-                                pattern,
-                                type_filter,
-                                max_depth,
-                                current_depth + 1,
-                                results,
-                            );
-                        }
-                    }
+            let run = find_run(&remaining, &cwd.to_string_lossy());
+            let _ = stdout.write_all(run.stdout.as_bytes()).await;
+            let _ = stderr.write_all(run.stderr.as_bytes()).await;
+
+            // The shell runs -exec commands itself (see `find_runs_commands`);
+            // callers using the registry directly get registry commands only,
+            // in a fresh environment rooted at our cwd
+            let mut exec_env = ShellEnv::new();
+            exec_env.cwd = cwd;
+            let mut exit_code = run.code;
+            for exec in &run.execs {
+                let code = run_find_exec(&exec.argv, &exec_env, &mut stdout, &mut stderr).await;
+                if exec.batch && code != 0 {
+                    exit_code = 1;
                 }
             }
-
-            let mut results = Vec::new();
-            find_recursive(
-                &search_path,
-                &name_pattern,
-                type_filter,
-                max_depth,
-                1,
-                &mut results,
-            );
-            results.sort();
-
-            for path in results {
-                let _ = stdout.write_all(path.as_bytes()).await;
-                let _ = stdout.write_all(b"\n").await;
-            }
-            0
+            exit_code
         })
     }
 
@@ -843,6 +815,79 @@ impl FileCommands {
         })
     }
 
+    /// stat - display file status
+    #[shell_command(
+        name = "stat",
+        usage = "stat [-L] [-t] [-c FORMAT] FILE...",
+        description = "Display file size, type and modification time.\n\
+        -L: Follow symbolic links\n\
+        -t: Terse one-line form: NAME SIZE MTIME TYPE (f, d or l)\n\
+        -c, --format FORMAT: Print FORMAT for each file, where\n\
+        %n is the name, %s the size in bytes, %F the file type,\n\
+        %Y the mtime in seconds since the epoch and %y the mtime as a timestamp"
+    )]
+    fn cmd_stat(
+        args: Vec<String>,
+        env: &ShellEnv,
+        _stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.to_string_lossy().to_string();
+        Box::pin(async move {
+            let (_, remaining) = parse_common(&args);
+            let mut dereference = false;
+            let mut terse = false;
+            let mut format: Option<String> = None;
+            let mut files: Vec<String> = Vec::new();
+            let mut parser = make_parser(remaining);
+
+            while let Some(arg) = parser.next().ok().flatten() {
+                match arg {
+                    Short('L') | Long("dereference") => dereference = true,
+                    Short('t') | Long("terse") => terse = true,
+                    Short('c') | Long("format") => match parser.value() {
+                        Ok(val) => format = Some(val.string().unwrap_or_default()),
+                        Err(_) => {
+                            let _ = stderr
+                                .write_all(b"stat: option requires an argument -- 'c'\n")
+                                .await;
+                            return 1;
+                        }
+                    },
+                    Value(val) => files.push(val.string().unwrap_or_default()),
+                    _ => {}
+                }
+            }
+
+            if files.is_empty() {
+                let _ = stderr.write_all(b"stat: missing operand\n").await;
+                return 1;
+            }
+
+            let mut exit_code = 0;
+            for file in &files {
+                let path = resolve_path(&cwd, file);
+                match StatInfo::read(&path, dereference) {
+                    Ok(info) => {
+                        let out = match &format {
+                            Some(format) => format!("{}\n", format_stat(format, file, &info)),
+                            None if terse => info.terse(file),
+                            None => info.report(file),
+                        };
+                        let _ = stdout.write_all(out.as_bytes()).await;
+                    }
+                    Err(e) => {
+                        let msg = format!("stat: cannot stat '{}': {}\n", file, e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        exit_code = 1;
+                    }
+                }
+            }
+            exit_code
+        })
+    }
+
     /// realpath - resolve canonical path
     #[shell_command(
         name = "realpath",
@@ -1161,10 +1206,514 @@ impl FileCommands {
             exit_code
         })
     }
+
+    /// tree - list directory contents as a tree
+    #[shell_command(
+        name = "tree",
+        usage = "tree [-a] [-d] [-L LEVEL] [PATH]",
+        description = "List contents of directories in a tree-like format.\n\
+        -a: Include hidden files\n\
+        -d: List directories only\n\
+        -L LEVEL: Descend at most LEVEL directories deep"
+    )]
+    fn cmd_tree(
+        args: Vec<String>,
+        env: &ShellEnv,
+        _stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.to_string_lossy().to_string();
+        Box::pin(async move {
+            let (_, remaining) = parse_common(&args);
+            let mut opts = TreeOptions::default();
+            let mut path: Option<String> = None;
+
+            let mut iter = remaining.iter();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "-a" => opts.show_all = true,
+                    "-d" => opts.dirs_only = true,
+                    "-L" => match iter.next().and_then(|n| n.parse::<usize>().ok()) {
+                        Some(level) if level > 0 => opts.max_depth = Some(level),
+                        _ => {
+                            let _ = stderr
+                                .write_all(b"tree: -L requires a positive level\n")
+                                .await;
+                            return 1;
+                        }
+                    },
+                    s if !s.starts_with('-') && path.is_none() => path = Some(s.to_string()),
+                    s => {
+                        let _ = stderr
+                            .write_all(format!("tree: invalid option '{}'\n", s).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                }
+            }
+
+            let display = path.unwrap_or_else(|| ".".to_string());
+            let full_path = resolve_path(&cwd, &display);
+            if !std::path::Path::new(&full_path).is_dir() {
+                let _ = stderr
+                    .write_all(format!("tree: {}: not a directory\n", display).as_bytes())
+                    .await;
+                return 1;
+            }
+
+            let _ = stdout
+                .write_all(render_tree(&full_path, &display, &opts).as_bytes())
+                .await;
+            0
+        })
+    }
+}
+
+/// Options for `tree`.
+#[derive(Default)]
+struct TreeOptions {
+    show_all: bool,
+    dirs_only: bool,
+    max_depth: Option<usize>,
+}
+
+/// Render `path` (shown as `display`) as a tree, ending with the summary line.
+fn render_tree(path: &str, display: &str, opts: &TreeOptions) -> String {
+    let mut out = format!("{}\n", display);
+    let (mut dirs, mut files) = (0, 0);
+    render_tree_level(path, "", 1, opts, &mut out, &mut dirs, &mut files);
+
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    out.push('\n');
+    out.push_str(&plural(dirs, "directory", "directories"));
+    if !opts.dirs_only {
+        out.push_str(", ");
+        out.push_str(&plural(files, "file", "files"));
+    }
+    out.push('\n');
+    out
+}
+
+fn render_tree_level(
+    path: &str,
+    prefix: &str,
+    depth: usize,
+    opts: &TreeOptions,
+    out: &mut String,
+    dirs: &mut usize,
+    files: &mut usize,
+) {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    // symlink_metadata so linked directories are not followed
+    let mut items: Vec<(String, bool)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let is_dir = std::fs::symlink_metadata(e.path()).ok()?.is_dir();
+            Some((name, is_dir))
+        })
+        .filter(|(name, is_dir)| {
+            (opts.show_all || !name.starts_with('.')) && (*is_dir || !opts.dirs_only)
+        })
+        .collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (i, (name, is_dir)) in items.iter().enumerate() {
+        let last = i + 1 == items.len();
+        let connector = if last { "└── " } else { "├── " };
+        out.push_str(&format!("{}{}{}\n", prefix, connector, name));
+
+        if *is_dir {
+            *dirs += 1;
+            if opts.max_depth.is_none_or(|max| depth < max) {
+                let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                render_tree_level(
+                    &format!("{}/{}", path.trim_end_matches('/'), name),
+                    &child_prefix,
+                    depth + 1,
+                    opts,
+                    out,
+                    dirs,
+                    files,
+                );
+            }
+        } else {
+            *files += 1;
+        }
+    }
+}
+
+/// Whether a `find` command line runs commands with `-exec`. The shell runs
+/// those through its executor, so functions and lazy modules work.
+pub(crate) fn find_runs_commands(args: &[String]) -> bool {
+    let (opts, remaining) = parse_common(args);
+    !opts.help && remaining.iter().any(|arg| arg == "-exec")
+}
+
+/// What a `find` command line does once the walk is done
+pub(crate) struct FindRun {
+    /// Matching paths, one per line, when there is no `-exec`
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) code: i32,
+    /// `-exec` commands in the order they run
+    pub(crate) execs: Vec<FindExecCall>,
+}
+
+/// One `-exec` command to run. A failing command only makes a per-path
+/// `-exec` false; a failing batched (`+`) one makes find fail.
+pub(crate) struct FindExecCall {
+    pub(crate) argv: Vec<String>,
+    pub(crate) batch: bool,
+}
+
+/// Walk and match a `find` command line (without `--help`) from `cwd`
+pub(crate) fn find_run(args: &[String], cwd: &str) -> FindRun {
+    let mut run = FindRun {
+        stdout: String::new(),
+        stderr: String::new(),
+        code: 0,
+        execs: Vec::new(),
+    };
+    let query = match parse_find_args(args) {
+        Ok(query) => query,
+        Err(e) => {
+            run.stderr = format!("find: {}\n", e);
+            run.code = 1;
+            return run;
+        }
+    };
+
+    let mut entries = Vec::new();
+    for root in &query.roots {
+        let abs = resolve_path(cwd, root);
+        if let Err(e) = find_walk(root, &abs, query.max_depth, query.gitignore, &mut entries) {
+            run.stderr.push_str(&format!("find: '{}': {}\n", root, e));
+            run.code = 1;
+        }
+    }
+
+    let mut batched: Vec<String> = Vec::new();
+    for entry in entries.iter().filter(|e| query.matches(e)) {
+        if query.execs.is_empty() {
+            run.stdout.push_str(&format!("{}\n", entry.display));
+            continue;
+        }
+        batched.push(entry.display.clone());
+        for exec in query.execs.iter().filter(|x| !x.batch) {
+            run.execs.push(FindExecCall {
+                argv: exec.argv(std::slice::from_ref(&entry.display)),
+                batch: false,
+            });
+        }
+    }
+    if !batched.is_empty() {
+        for exec in query.execs.iter().filter(|x| x.batch) {
+            run.execs.push(FindExecCall {
+                argv: exec.argv(&batched),
+                batch: true,
+            });
+        }
+    }
+    run
+}
+
+/// A path visited by `find`.
+struct FindEntry {
+    /// Path as printed: the search root as given, joined with the relative path
+    display: String,
+    /// Path relative to the search root ("" for the root itself)
+    relative: String,
+    /// Final path component
+    name: String,
+    /// Depth below the search root (the root itself is 0)
+    depth: usize,
+    /// File type: 'f', 'd' or 'l'
+    kind: char,
+}
+
+/// A `find` test; all tests must hold for an entry to match.
+enum FindTest {
+    Name(String),
+    Path(String),
+    Type(char),
+}
+
+/// A `-exec` action. `{}` in the template is replaced by the matched path;
+/// batched (`+`) actions run once with every match in place of `{}`.
+struct FindExec {
+    template: Vec<String>,
+    batch: bool,
+}
+
+impl FindExec {
+    fn argv(&self, paths: &[String]) -> Vec<String> {
+        let mut argv = Vec::new();
+        for arg in &self.template {
+            if self.batch && arg == "{}" {
+                argv.extend(paths.iter().cloned());
+            } else if !self.batch {
+                argv.push(arg.replace("{}", &paths[0]));
+            } else {
+                argv.push(arg.clone());
+            }
+        }
+        argv
+    }
+}
+
+/// Parsed `find` command line.
+struct FindQuery {
+    roots: Vec<String>,
+    tests: Vec<FindTest>,
+    max_depth: Option<usize>,
+    min_depth: usize,
+    execs: Vec<FindExec>,
+    /// Skip `.git` and `.gitignore`d paths while walking
+    gitignore: bool,
+}
+
+impl FindQuery {
+    fn matches(&self, entry: &FindEntry) -> bool {
+        entry.depth >= self.min_depth
+            && self.tests.iter().all(|test| match test {
+                FindTest::Name(pattern) => glob_match(pattern, &entry.name),
+                FindTest::Path(pattern) => glob_match(pattern, &entry.relative),
+                FindTest::Type(kind) => entry.kind == *kind,
+            })
+    }
+}
+
+/// Parse `find` arguments: leading operands are search roots (default "."),
+/// followed by tests and actions.
+fn parse_find_args(args: &[String]) -> Result<FindQuery, String> {
+    let mut query = FindQuery {
+        roots: Vec::new(),
+        tests: Vec::new(),
+        max_depth: None,
+        min_depth: 0,
+        execs: Vec::new(),
+        gitignore: false,
+    };
+
+    // --gitignore is an option rather than a test, so it may appear anywhere
+    query.gitignore = args.iter().any(|arg| arg == "--gitignore");
+    let args: Vec<String> = args
+        .iter()
+        .filter(|arg| *arg != "--gitignore")
+        .cloned()
+        .collect();
+
+    let mut i = 0;
+    while i < args.len() && !args[i].starts_with('-') {
+        query.roots.push(args[i].clone());
+        i += 1;
+    }
+    if query.roots.is_empty() {
+        query.roots.push(".".to_string());
+    }
+
+    while i < args.len() {
+        let primary = args[i].as_str();
+        i += 1;
+        if primary == "-exec" {
+            let start = i;
+            let mut batch = None;
+            while i < args.len() {
+                if args[i] == ";" {
+                    batch = Some(false);
+                    break;
+                }
+                if args[i] == "+" && i > start && args[i - 1] == "{}" {
+                    batch = Some(true);
+                    break;
+                }
+                i += 1;
+            }
+            let Some(batch) = batch.filter(|_| i > start) else {
+                return Err("missing argument to `-exec'".to_string());
+            };
+            query.execs.push(FindExec {
+                template: args[start..i].to_vec(),
+                batch,
+            });
+            i += 1;
+            continue;
+        }
+
+        let Some(value) = args.get(i) else {
+            return Err(match primary {
+                "-name" | "--name" | "-path" | "-type" | "--type" | "-maxdepth" | "-mindepth" => {
+                    format!("missing argument to `{}'", primary)
+                }
+                _ => format!("unknown predicate `{}'", primary),
+            });
+        };
+        match primary {
+            // The double-dash spellings are accepted for compatibility
+            "-name" | "--name" => query.tests.push(FindTest::Name(value.clone())),
+            "-path" => query.tests.push(FindTest::Path(value.clone())),
+            "-type" | "--type" => match value.as_str() {
+                "f" | "d" | "l" => query
+                    .tests
+                    .push(FindTest::Type(value.chars().next().unwrap())),
+                _ => return Err(format!("Unknown argument to -type: {}", value)),
+            },
+            "-maxdepth" | "-mindepth" => {
+                let depth: usize = value.parse().map_err(|_| {
+                    format!(
+                        "{}: {}: positive decimal integer argument expected",
+                        primary, value
+                    )
+                })?;
+                if primary == "-maxdepth" {
+                    query.max_depth = Some(depth);
+                } else {
+                    query.min_depth = depth;
+                }
+            }
+            _ => return Err(format!("unknown predicate `{}'", primary)),
+        }
+        i += 1;
+    }
+    Ok(query)
+}
+
+/// Classify a path without following symlinks.
+fn find_kind(path: &str) -> std::io::Result<char> {
+    let file_type = std::fs::symlink_metadata(path)?.file_type();
+    Ok(if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_dir() {
+        'd'
+    } else {
+        'f'
+    })
+}
+
+/// Collect the search root and everything below it (down to `max_depth`)
+/// in pre-order, visiting directory entries in name order. With
+/// `gitignore`, ignored paths are left out and not descended into.
+fn find_walk(
+    root: &str,
+    abs_root: &str,
+    max_depth: Option<usize>,
+    gitignore: bool,
+    out: &mut Vec<FindEntry>,
+) -> Result<(), String> {
+    let kind = find_kind(abs_root).map_err(|_| "No such file or directory".to_string())?;
+    let name = root
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(root);
+    out.push(FindEntry {
+        display: root.to_string(),
+        relative: String::new(),
+        name: if name.is_empty() { root } else { name }.to_string(),
+        depth: 0,
+        kind,
+    });
+    if kind == 'd' {
+        let mut ignore = gitignore.then(GitIgnore::new);
+        find_walk_dir(root, abs_root, "", 1, max_depth, ignore.as_mut(), out);
+    }
+    Ok(())
+}
+
+fn find_walk_dir(
+    display: &str,
+    abs: &str,
+    relative: &str,
+    depth: usize,
+    max_depth: Option<usize>,
+    mut ignore: Option<&mut GitIgnore>,
+    out: &mut Vec<FindEntry>,
+) {
+    if max_depth.is_some_and(|max| depth > max) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(abs) else {
+        return;
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    if let Some(rules) = ignore.as_deref_mut() {
+        rules.enter(abs, relative);
+    }
+
+    for name in names {
+        let child_display = if display.ends_with('/') {
+            format!("{}{}", display, name)
+        } else {
+            format!("{}/{}", display, name)
+        };
+        let child_abs = format!("{}/{}", abs.trim_end_matches('/'), name);
+        let child_relative = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let Ok(kind) = find_kind(&child_abs) else {
+            continue;
+        };
+        if ignore
+            .as_deref()
+            .is_some_and(|rules| rules.is_ignored(&child_relative, kind == 'd'))
+        {
+            continue;
+        }
+        out.push(FindEntry {
+            display: child_display.clone(),
+            relative: child_relative.clone(),
+            name,
+            depth,
+            kind,
+        });
+        if kind == 'd' {
+            find_walk_dir(
+                &child_display,
+                &child_abs,
+                &child_relative,
+                depth + 1,
+                max_depth,
+                ignore.as_deref_mut(),
+                out,
+            );
+        }
+    }
+    if let Some(rules) = ignore {
+        rules.leave();
+    }
+}
+
+/// Run one `-exec` command from the registry, copying its output through.
+async fn run_find_exec(
+    argv: &[String],
+    env: &ShellEnv,
+    stdout: &mut piper::Writer,
+    stderr: &mut piper::Writer,
+) -> i32 {
+    let Some(cmd_fn) = super::ShellCommands::get_command(&argv[0]) else {
+        let msg = format!("find: {}: command not found\n", argv[0]);
+        let _ = stderr.write_all(msg.as_bytes()).await;
+        return 127;
+    };
+    let (code, out, err) = super::misc::run_captured(cmd_fn, argv[1..].to_vec(), env).await;
+    let _ = stdout.write_all(&out).await;
+    let _ = stderr.write_all(&err).await;
+    code
 }
 
 /// Simple glob pattern matching (supports * and ?)
-fn glob_match(pattern: &str, text: &str) -> bool {
+pub(super) fn glob_match(pattern: &str, text: &str) -> bool {
     let mut p_chars = pattern.chars().peekable();
     let mut t_chars = text.chars().peekable();
 
@@ -1203,6 +1752,154 @@ fn glob_match(pattern: &str, text: &str) -> bool {
     t_chars.peek().is_none()
 }
 
+/// Parse a `touch -t` stamp, `[[CC]YY]MMDDhhmm[.ss]`, as UTC seconds since
+/// the Unix epoch. Without a century, 69-99 mean 19xx and 00-68 mean 20xx;
+/// without a year, the current year is used.
+fn parse_touch_stamp(stamp: &str) -> Option<i64> {
+    let (digits, seconds) = match stamp.split_once('.') {
+        Some((digits, seconds))
+            if seconds.len() == 2 && seconds.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            (digits, seconds.parse::<i64>().ok()?)
+        }
+        Some(_) => return None,
+        None => (stamp, 0),
+    };
+    if !digits.bytes().all(|b| b.is_ascii_digit()) || !matches!(digits.len(), 8 | 10 | 12) {
+        return None;
+    }
+
+    let (year, rest) = digits.split_at(digits.len() - 8);
+    let year: i64 = match year.len() {
+        4 => year.parse().ok()?,
+        2 => {
+            let yy: i64 = year.parse().ok()?;
+            if yy >= 69 {
+                1900 + yy
+            } else {
+                2000 + yy
+            }
+        }
+        _ => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs() as i64);
+            let (year, ..) = super::misc::days_to_ymd(now.div_euclid(86_400));
+            year
+        }
+    };
+    let field = |i: usize| rest[i..i + 2].parse::<u32>().ok();
+    let (month, day, hour, minute) = (field(0)?, field(2)?, field(4)?, field(6)?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || seconds > 60
+    {
+        return None;
+    }
+
+    let days = super::misc::ymd_to_days(year, month, day);
+    Some(days * 86_400 + i64::from(hour * 3600 + minute * 60) + seconds)
+}
+
+/// What `stat` reports about a path.
+struct StatInfo {
+    size: u64,
+    /// File type: 'f', 'd' or 'l', as with `find -type`
+    kind: char,
+    /// Modification time since the Unix epoch: seconds and nanoseconds
+    mtime: (i64, u32),
+}
+
+impl StatInfo {
+    /// Read metadata, following a final symlink only with `dereference`.
+    fn read(path: &str, dereference: bool) -> std::io::Result<Self> {
+        let metadata = if dereference {
+            std::fs::metadata(path)?
+        } else {
+            std::fs::symlink_metadata(path)?
+        };
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_symlink() {
+            'l'
+        } else if file_type.is_dir() {
+            'd'
+        } else {
+            'f'
+        };
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or((0, 0), |since| {
+                (since.as_secs() as i64, since.subsec_nanos())
+            });
+        Ok(Self {
+            size: metadata.len(),
+            kind,
+            mtime,
+        })
+    }
+
+    /// File type as `%F` prints it.
+    fn type_name(&self) -> &'static str {
+        match self.kind {
+            'd' => "directory",
+            'l' => "symbolic link",
+            _ if self.size == 0 => "regular empty file",
+            _ => "regular file",
+        }
+    }
+
+    /// Modification time as `%y` prints it.
+    fn timestamp(&self) -> String {
+        super::misc::format_date("%Y-%m-%d %H:%M:%S.%N %z", self.mtime.0, self.mtime.1)
+    }
+
+    /// The `-t` line: name, size, mtime seconds and one-letter type.
+    fn terse(&self, name: &str) -> String {
+        format!("{} {} {} {}\n", name, self.size, self.mtime.0, self.kind)
+    }
+
+    /// The default multi-line report.
+    fn report(&self, name: &str) -> String {
+        format!(
+            "  File: {}\n  Size: {:<15} Type: {}\nModify: {}\n",
+            name,
+            self.size,
+            self.type_name(),
+            self.timestamp()
+        )
+    }
+}
+
+/// Expand a `stat -c` format; unknown specifiers are kept as is.
+fn format_stat(format: &str, name: &str, info: &StatInfo) -> String {
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push_str(name),
+            Some('s') => out.push_str(&info.size.to_string()),
+            Some('F') => out.push_str(info.type_name()),
+            Some('Y') => out.push_str(&info.mtime.0.to_string()),
+            Some('y') => out.push_str(&info.timestamp()),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
 /// Detect file type by examining magic bytes and metadata
 fn detect_file_type(path: &str) -> String {
     let metadata = match std::fs::metadata(path) {
@@ -1662,4 +2359,167 @@ mod tests {
         assert!(glob_match("file?.txt", "file1.txt"));
         assert!(!glob_match("file?.txt", "file10.txt"));
     }
+
+    #[test]
+    fn test_parse_touch_stamp() {
+        assert_eq!(parse_touch_stamp("202401021530.45"), Some(1_704_209_445));
+        assert_eq!(parse_touch_stamp("2401021530"), Some(1_704_209_400));
+        // Two-digit years from 69 are in the 1900s
+        assert_eq!(parse_touch_stamp("6901010000"), Some(-31_536_000));
+        assert!(parse_touch_stamp("01021530").is_some());
+
+        for bad in [
+            "2024010215",
+            "202413021530",
+            "20240102153a",
+            "202401021530.4",
+            "",
+        ] {
+            assert_eq!(parse_touch_stamp(bad), None, "{}", bad);
+        }
+    }
+
+    fn tree_fixture(name: &str) -> String {
+        let root = std::env::temp_dir().join(format!("tree-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src/bin")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        std::fs::write(root.join(".hidden"), "").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("src/bin/main.rs"), "").unwrap();
+        root.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_tree_renders_nested_directories() {
+        let root = tree_fixture("nested");
+        let out = render_tree(&root, "proj", &TreeOptions::default());
+        assert_eq!(
+            out,
+            "proj\n\
+             ├── Cargo.toml\n\
+             └── src\n\
+             \u{20}   ├── bin\n\
+             \u{20}   │   └── main.rs\n\
+             \u{20}   └── lib.rs\n\
+             \n\
+             2 directories, 3 files\n"
+        );
+
+        let all = render_tree(
+            &root,
+            "proj",
+            &TreeOptions {
+                show_all: true,
+                ..Default::default()
+            },
+        );
+        assert!(all.contains("├── .hidden\n"), "{}", all);
+        assert!(all.ends_with("2 directories, 4 files\n"), "{}", all);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_tree_depth_limit_and_dirs_only() {
+        let root = tree_fixture("depth");
+        let out = render_tree(
+            &root,
+            ".",
+            &TreeOptions {
+                max_depth: Some(1),
+                ..Default::default()
+            },
+        );
+        assert_eq!(out, ".\n├── Cargo.toml\n└── src\n\n1 directory, 1 file\n");
+
+        let dirs = render_tree(
+            &root,
+            ".",
+            &TreeOptions {
+                dirs_only: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(dirs, ".\n└── src\n    └── bin\n\n2 directories\n");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    fn find_args(args: &[&str]) -> Result<FindQuery, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_find_args(&args)
+    }
+
+    #[test]
+    fn test_find_parses_roots_tests_and_exec() {
+        let query = find_args(&["a", "b", "-type", "f", "-exec", "wc", "-l", "{}", ";"]).unwrap();
+        assert_eq!(query.roots, vec!["a", "b"]);
+        assert_eq!(query.tests.len(), 1);
+        assert_eq!(query.execs.len(), 1);
+        assert!(!query.execs[0].batch);
+        assert_eq!(
+            query.execs[0].argv(&["a/x".to_string()]),
+            vec!["wc", "-l", "a/x"]
+        );
+
+        let query = find_args(&["-name", "*.rs", "-exec", "echo", "{}", "+"]).unwrap();
+        assert_eq!(query.roots, vec!["."]);
+        assert!(query.execs[0].batch);
+        assert_eq!(
+            query.execs[0].argv(&["./a.rs".to_string(), "./b.rs".to_string()]),
+            vec!["echo", "./a.rs", "./b.rs"]
+        );
+    }
+
+    #[test]
+    fn test_find_rejects_bad_arguments() {
+        assert!(find_args(&["-name"]).is_err_and(|e| e.contains("missing argument")));
+        assert!(find_args(&["-type", "x"]).is_err());
+        assert!(find_args(&["-maxdepth", "-1"]).is_err());
+        assert!(find_args(&["-bogus", "1"]).is_err_and(|e| e.contains("unknown predicate")));
+        // "+" only ends -exec right after "{}"
+        assert!(find_args(&["-exec", "echo", "+"]).is_err());
+    }
+
+    #[test]
+    fn test_find_walk_depths_and_types() {
+        let root = std::env::temp_dir().join(format!(
+            "find-walk-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        ));
+        std::fs::create_dir_all(root.join("d/e")).unwrap();
+        std::fs::write(root.join("d/f.txt"), "x").unwrap();
+        let abs = root.to_string_lossy().to_string();
+
+        let mut entries = Vec::new();
+        find_walk("r/", &abs, None, false, &mut entries).unwrap();
+        let seen: Vec<(String, String, usize, char)> = entries
+            .iter()
+            .map(|e| (e.display.clone(), e.relative.clone(), e.depth, e.kind))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("r/".to_string(), String::new(), 0, 'd'),
+                ("r/d".to_string(), "d".to_string(), 1, 'd'),
+                ("r/d/e".to_string(), "d/e".to_string(), 2, 'd'),
+                ("r/d/f.txt".to_string(), "d/f.txt".to_string(), 2, 'f'),
+            ]
+        );
+
+        let mut entries = Vec::new();
+        find_walk("r", &abs, Some(1), false, &mut entries).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(find_walk(
+            "r",
+            &format!("{}/missing", abs),
+            None,
+            false,
+            &mut Vec::new()
+        )
+        .is_err());
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
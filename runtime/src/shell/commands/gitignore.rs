@@ -0,0 +1,214 @@
+//! `.gitignore` matching for recursive commands (`find`, `grep -r`).
+//!
+//! Rules are loaded from each directory's `.gitignore` as a walk descends,
+//! so nested files apply only beneath their own directory and override
+//! rules from further up. Within and across files the last matching
+//! pattern wins, which is how `!negation` re-includes a path. `.git` is
+//! always ignored.
+
+use super::super::expand::glob_match;
+
+/// One pattern line from a `.gitignore`.
+struct IgnorePattern {
+    /// Pattern split on `/`; a `**` segment matches any number of segments
+    segments: Vec<String>,
+    /// `!pattern` - re-include a previously ignored path
+    negated: bool,
+    /// `pattern/` - only match directories
+    dir_only: bool,
+    /// Contains a `/` - match against the whole path rather than the name
+    anchored: bool,
+}
+
+impl IgnorePattern {
+    /// Parse one `.gitignore` line; blank lines and comments yield `None`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut pattern = line.trim_end();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return None;
+        }
+
+        // `\!` and `\#` escape a literal leading `!` or `#`
+        let negated = pattern.starts_with('!');
+        if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#") {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            segments: pattern.split('/').map(String::from).collect(),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Match against a path relative to the `.gitignore`'s directory.
+    fn matches(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            let path: Vec<&str> = relative.split('/').collect();
+            match_segments(&self.segments, &path)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            glob_match(name, &self.segments[0])
+        }
+    }
+}
+
+/// Match path segments against pattern segments, expanding `**`.
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => glob_match(name, first) && match_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// Patterns from one `.gitignore`.
+struct IgnoreFile {
+    /// Directory holding the file, relative to the walk root ("" for the root)
+    base: String,
+    patterns: Vec<IgnorePattern>,
+}
+
+/// The `.gitignore` rules in effect at the current point of a walk.
+///
+/// Call [`GitIgnore::enter`] before listing a directory and
+/// [`GitIgnore::leave`] once done with it.
+#[derive(Default)]
+pub(super) struct GitIgnore {
+    files: Vec<IgnoreFile>,
+}
+
+impl GitIgnore {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load `abs_dir/.gitignore` (if any) for the directory at `relative`.
+    pub(super) fn enter(&mut self, abs_dir: &str, relative: &str) {
+        let path = format!("{}/.gitignore", abs_dir.trim_end_matches('/'));
+        let patterns = std::fs::read_to_string(path)
+            .map(|content| content.lines().filter_map(IgnorePattern::parse).collect())
+            .unwrap_or_default();
+        self.files.push(IgnoreFile {
+            base: relative.to_string(),
+            patterns,
+        });
+    }
+
+    /// Drop the rules loaded by the matching [`GitIgnore::enter`].
+    pub(super) fn leave(&mut self) {
+        self.files.pop();
+    }
+
+    /// Whether the path at `relative` (from the walk root) is ignored.
+    pub(super) fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        if name == ".git" {
+            return true;
+        }
+
+        let mut ignored = false;
+        for file in &self.files {
+            let local = if file.base.is_empty() {
+                relative
+            } else {
+                match relative
+                    .strip_prefix(file.base.as_str())
+                    .and_then(|rest| rest.strip_prefix('/'))
+                {
+                    Some(local) => local,
+                    None => continue,
+                }
+            };
+            for pattern in &file.patterns {
+                if pattern.matches(local, is_dir) {
+                    ignored = !pattern.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(base: &str, content: &str) -> IgnoreFile {
+        IgnoreFile {
+            base: base.to_string(),
+            patterns: content.lines().filter_map(IgnorePattern::parse).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        assert!(IgnorePattern::parse("").is_none());
+        assert!(IgnorePattern::parse("   ").is_none());
+        assert!(IgnorePattern::parse("# comment").is_none());
+        assert!(IgnorePattern::parse("\\#literal").is_some());
+
+        let pattern = IgnorePattern::parse("!/build/").unwrap();
+        assert!(pattern.negated && pattern.dir_only && pattern.anchored);
+        assert_eq!(pattern.segments, vec!["build"]);
+    }
+
+    #[test]
+    fn test_name_patterns_match_at_any_depth() {
+        let ignore = GitIgnore {
+            files: vec![rules("", "node_modules/\n*.log")],
+        };
+        assert!(ignore.is_ignored("node_modules", true));
+        assert!(ignore.is_ignored("pkg/node_modules", true));
+        assert!(!ignore.is_ignored("node_modules", false));
+        assert!(ignore.is_ignored("pkg/debug.log", false));
+        assert!(!ignore.is_ignored("pkg/log.txt", false));
+        assert!(ignore.is_ignored(".git", true));
+    }
+
+    #[test]
+    fn test_anchored_and_double_star_patterns() {
+        let ignore = GitIgnore {
+            files: vec![rules("", "/dist\ndocs/*.html\na/**/b")],
+        };
+        assert!(ignore.is_ignored("dist", true));
+        assert!(!ignore.is_ignored("src/dist", true));
+        assert!(ignore.is_ignored("docs/index.html", false));
+        assert!(!ignore.is_ignored("docs/api/index.html", false));
+        assert!(ignore.is_ignored("a/b", true));
+        assert!(ignore.is_ignored("a/x/y/b", true));
+    }
+
+    #[test]
+    fn test_negation_and_nested_files() {
+        let ignore = GitIgnore {
+            files: vec![
+                rules("", "*.log\n!keep.log"),
+                rules("sub", "!debug.log\n/local"),
+            ],
+        };
+        assert!(ignore.is_ignored("debug.log", false));
+        assert!(!ignore.is_ignored("keep.log", false));
+        // The nested file re-includes, but only beneath its own directory
+        assert!(!ignore.is_ignored("sub/debug.log", false));
+        assert!(ignore.is_ignored("sub/local", false));
+        assert!(!ignore.is_ignored("local", false));
+    }
+}
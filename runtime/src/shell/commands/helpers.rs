@@ -5,6 +5,7 @@
 //! - `shell_err!`: write formatted error messages to stderr
 //! - `write_stdout!` / `write_line!`: write to stdout
 //! - `truncate_line`: Unicode-safe string truncation
+//! - `stdout_is_terminal`: the isatty check behind `--color=auto`
 
 /// Resolve a path relative to the current working directory.
 ///
@@ -24,6 +25,21 @@ pub fn resolve_path(cwd: &str, path: &str) -> String {
     }
 }
 
+/// Whether the process's stdout is a terminal, for `--color=auto`.
+///
+/// Uses WASI `get_terminal_stdout()`; native builds (unit tests) have no
+/// terminal, so this is always false there.
+pub fn stdout_is_terminal() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        crate::bindings::wasi::cli::terminal_stdout::get_terminal_stdout().is_some()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        false
+    }
+}
+
 /// Truncate a string to at most `max_len` characters (Unicode-safe).
 ///
 /// If the string is longer than `max_len`, it is truncated and `...` is appended.
@@ -0,0 +1,1239 @@
+//! jq filter language: a parser and evaluator over `serde_json::Value`.
+//!
+//! Supports the commonly used subset of jq: paths (`.a.b`, `.[0]`, `.[]`,
+//! `.[1:3]`, `..`, `?`), pipes and `,`, literals, array and object
+//! construction, arithmetic, comparisons, `and`/`or`/`//`,
+//! `if ... then ... else ... end`, and a set of builtins (`map`, `select`,
+//! `keys`, `values`, `length`, `has`, ...). Variables, `reduce` and
+//! string interpolation are not supported.
+//!
+//! Like jq, every filter maps one input to zero or more outputs, so
+//! evaluation returns a `Vec` and binary operators take the cartesian
+//! product of their operands' outputs.
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+/// A parsed jq filter, ready to apply to any number of inputs.
+pub(super) struct JqFilter(Expr);
+
+impl JqFilter {
+    /// Parse a filter, reporting syntax errors.
+    pub(super) fn parse(filter: &str) -> Result<Self, String> {
+        let tokens = tokenize(filter)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_pipe()?;
+        match parser.peek() {
+            None => Ok(Self(expr)),
+            Some(token) => Err(format!("syntax error: unexpected {}", token)),
+        }
+    }
+
+    /// Run the filter against one input value.
+    pub(super) fn apply(&self, input: &Value) -> Result<Vec<Value>, String> {
+        eval(&self.0, input)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// `.`
+    Dot,
+    /// `..`
+    DotDot,
+    /// `.name` or `."name"`
+    Field(String),
+    Ident(String),
+    Str(String),
+    Num(f64),
+    /// Operators and punctuation: `|`, `,`, `==`, `+`, `(`, ...
+    Punct(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Dot => write!(f, "'.'"),
+            Token::DotDot => write!(f, "'..'"),
+            Token::Field(name) => write!(f, "'.{}'", name),
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Punct(p) => write!(f, "'{}'", p),
+        }
+    }
+}
+
+/// Two-character operators must be listed before their one-character prefixes.
+const PUNCTUATION: &[&str] = &[
+    "==", "!=", "<=", ">=", "//", "|", ",", ":", ";", "?", "(", ")", "[", "]", "{", "}", "<", ">",
+    "+", "-", "*", "/", "%",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = filter.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '.' {
+            i += 1;
+            if chars.get(i) == Some(&'.') {
+                tokens.push(Token::DotDot);
+                i += 1;
+            } else if chars.get(i) == Some(&'"') {
+                let (s, next) = lex_string(&chars, i)?;
+                tokens.push(Token::Field(s));
+                i = next;
+            } else if chars.get(i).copied().is_some_and(is_ident_start) {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Field(chars[start..i].iter().collect()));
+            } else {
+                tokens.push(Token::Dot);
+            }
+        } else if c == '"' {
+            let (s, next) = lex_string(&chars, i)?;
+            tokens.push(Token::Str(s));
+            i = next;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| format!("syntax error: invalid number {}", text))?;
+            tokens.push(Token::Num(n));
+        } else if is_ident_start(c) {
+            let start = i;
+            while i < chars.len() && is_ident_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '$' {
+            return Err("syntax error: variables are not supported".to_string());
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match PUNCTUATION.iter().find(|p| rest.starts_with(**p)) {
+                Some(p) => {
+                    tokens.push(Token::Punct(p));
+                    i += p.len();
+                }
+                None => return Err(format!("syntax error: unexpected '{}'", c)),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Lex a JSON-style string literal starting at the opening quote, returning
+/// its value and the index just past the closing quote.
+fn lex_string(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let mut s = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return Ok((s, i + 1)),
+            '\\' => {
+                i += 1;
+                match chars.get(i) {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = chars.iter().skip(i + 1).take(4).collect();
+                        let code = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("syntax error: invalid escape \\u{}", hex))?;
+                        s.push(code);
+                        i += 4;
+                    }
+                    Some('(') => {
+                        return Err("syntax error: string interpolation is not supported".into())
+                    }
+                    Some(&other) => s.push(other),
+                    None => break,
+                }
+                i += 1;
+            }
+            other => {
+                s.push(other);
+                i += 1;
+            }
+        }
+    }
+    Err("syntax error: unterminated string".to_string())
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Identity,
+    Recurse,
+    Literal(Value),
+    /// `target[index]`, including `.name`
+    Index(Box<Expr>, Box<Expr>),
+    /// `target[from:to]`
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    /// `target[]`
+    Iterate(Box<Expr>),
+    /// `expr?`
+    Try(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    /// `[expr]`
+    Collect(Box<Expr>),
+    /// `{key: value, ...}`
+    Object(Vec<(Expr, Expr)>),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    /// `a // b`
+    Alternative(Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Call(String, Vec<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Consume `p` if it is the next token.
+    fn eat(&mut self, p: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Punct(q)) if *q == p) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, p: &str) -> Result<(), String> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", p)))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("'{}'", keyword)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(token) => format!("syntax error: expected {}, found {}", expected, token),
+            None => format!("syntax error: expected {} at end of filter", expected),
+        }
+    }
+
+    /// `a | b`, the loosest binding
+    fn parse_pipe(&mut self) -> Result<Expr, String> {
+        let left = self.parse_comma()?;
+        if self.eat("|") {
+            let right = self.parse_pipe()?;
+            return Ok(Expr::Pipe(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn parse_comma(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_alternative()?;
+        while self.eat(",") {
+            let right = self.parse_alternative()?;
+            left = Expr::Comma(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_alternative(&mut self) -> Result<Expr, String> {
+        let left = self.parse_or()?;
+        if self.eat("//") {
+            let right = self.parse_alternative()?;
+            return Ok(Expr::Alternative(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_comparison()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_additive()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                let right = self.parse_additive()?;
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                "+"
+            } else if self.eat("-") {
+                "-"
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_postfix()?;
+        loop {
+            let op = if self.eat("*") {
+                "*"
+            } else if self.eat("/") {
+                "/"
+            } else if self.eat("%") {
+                "%"
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_postfix()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    /// A term followed by any number of `.name`, `[...]` and `?` suffixes.
+    fn parse_postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_term()?;
+        loop {
+            match self.peek() {
+                Some(Token::Field(name)) => {
+                    let key = Expr::Literal(Value::String(name.clone()));
+                    self.pos += 1;
+                    expr = Expr::Index(Box::new(expr), Box::new(key));
+                }
+                Some(Token::Dot)
+                    if matches!(self.tokens.get(self.pos + 1), Some(Token::Punct("["))) =>
+                {
+                    // `.a.[0]` is the same as `.a[0]`
+                    self.pos += 1;
+                }
+                Some(Token::Punct("[")) => {
+                    self.pos += 1;
+                    expr = self.parse_brackets(expr)?;
+                }
+                Some(Token::Punct("?")) => {
+                    self.pos += 1;
+                    expr = Expr::Try(Box::new(expr));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// The inside of `target[...]`, after the opening bracket.
+    fn parse_brackets(&mut self, target: Expr) -> Result<Expr, String> {
+        let target = Box::new(target);
+        if self.eat("]") {
+            return Ok(Expr::Iterate(target));
+        }
+        let from = if matches!(self.peek(), Some(Token::Punct(":"))) {
+            None
+        } else {
+            Some(Box::new(self.parse_pipe()?))
+        };
+        if self.eat(":") {
+            let to = if matches!(self.peek(), Some(Token::Punct("]"))) {
+                None
+            } else {
+                Some(Box::new(self.parse_pipe()?))
+            };
+            self.expect("]")?;
+            return Ok(Expr::Slice(target, from, to));
+        }
+        self.expect("]")?;
+        match from {
+            Some(index) => Ok(Expr::Index(target, index)),
+            None => Err(self.unexpected("an index")),
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, String> {
+        let Some(token) = self.next() else {
+            return Err("syntax error: unexpected end of filter".to_string());
+        };
+        match token {
+            Token::Dot => Ok(Expr::Identity),
+            Token::DotDot => Ok(Expr::Recurse),
+            Token::Field(name) => Ok(Expr::Index(
+                Box::new(Expr::Identity),
+                Box::new(Expr::Literal(Value::String(name))),
+            )),
+            Token::Num(n) => Ok(Expr::Literal(number(n))),
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Punct("(") => {
+                let expr = self.parse_pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Punct("[") => {
+                if self.eat("]") {
+                    return Ok(Expr::Literal(Value::Array(Vec::new())));
+                }
+                let expr = self.parse_pipe()?;
+                self.expect("]")?;
+                Ok(Expr::Collect(Box::new(expr)))
+            }
+            Token::Punct("{") => self.parse_object(),
+            Token::Punct("-") => Ok(Expr::Neg(Box::new(self.parse_postfix()?))),
+            Token::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "if" => self.parse_if(),
+                _ => {
+                    let mut args = Vec::new();
+                    if self.eat("(") {
+                        loop {
+                            args.push(self.parse_pipe()?);
+                            if !self.eat(";") {
+                                break;
+                            }
+                        }
+                        self.expect(")")?;
+                    }
+                    Ok(Expr::Call(name, args))
+                }
+            },
+            other => Err(format!("syntax error: unexpected {}", other)),
+        }
+    }
+
+    /// `if` has been consumed; parse through the matching `end`.
+    fn parse_if(&mut self) -> Result<Expr, String> {
+        let condition = self.parse_pipe()?;
+        self.expect_keyword("then")?;
+        let then = self.parse_pipe()?;
+        let otherwise = if self.eat_keyword("elif") {
+            Some(Box::new(self.parse_if()?))
+        } else if self.eat_keyword("else") {
+            let otherwise = self.parse_pipe()?;
+            self.expect_keyword("end")?;
+            Some(Box::new(otherwise))
+        } else {
+            self.expect_keyword("end")?;
+            None
+        };
+        Ok(Expr::If(Box::new(condition), Box::new(then), otherwise))
+    }
+
+    /// `{` has been consumed; parse entries through the closing `}`.
+    fn parse_object(&mut self) -> Result<Expr, String> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            let (key, shorthand) = match self.next() {
+                Some(Token::Ident(name)) | Some(Token::Str(name)) => {
+                    let key = Expr::Literal(Value::String(name.clone()));
+                    let shorthand = Expr::Index(Box::new(Expr::Identity), Box::new(key.clone()));
+                    (key, Some(shorthand))
+                }
+                Some(Token::Punct("(")) => {
+                    let key = self.parse_pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected("an object key"));
+                }
+            };
+            let value = if self.eat(":") {
+                self.parse_alternative()?
+            } else {
+                shorthand.ok_or_else(|| self.unexpected("':'"))?
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+fn eval(expr: &Expr, input: &Value) -> Result<Vec<Value>, String> {
+    match expr {
+        Expr::Identity => Ok(vec![input.clone()]),
+        Expr::Recurse => {
+            let mut out = Vec::new();
+            recurse(input, &mut out);
+            Ok(out)
+        }
+        Expr::Literal(value) => Ok(vec![value.clone()]),
+        Expr::Index(target, index) => {
+            let mut out = Vec::new();
+            for key in eval(index, input)? {
+                for value in eval(target, input)? {
+                    out.push(index_value(&value, &key)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::Slice(target, from, to) => {
+            let bound = |expr: &Option<Box<Expr>>| -> Result<Vec<Option<Value>>, String> {
+                match expr {
+                    Some(expr) => Ok(eval(expr, input)?.into_iter().map(Some).collect()),
+                    None => Ok(vec![None]),
+                }
+            };
+            let mut out = Vec::new();
+            for to in bound(to)? {
+                for from in bound(from)? {
+                    for value in eval(target, input)? {
+                        out.push(slice_value(&value, from.as_ref(), to.as_ref())?);
+                    }
+                }
+            }
+            Ok(out)
+        }
+        Expr::Iterate(target) => {
+            let mut out = Vec::new();
+            for value in eval(target, input)? {
+                out.extend(iterate(&value)?);
+            }
+            Ok(out)
+        }
+        Expr::Try(inner) => Ok(eval(inner, input).unwrap_or_default()),
+        Expr::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for value in eval(left, input)? {
+                out.extend(eval(right, &value)?);
+            }
+            Ok(out)
+        }
+        Expr::Comma(left, right) => {
+            let mut out = eval(left, input)?;
+            out.extend(eval(right, input)?);
+            Ok(out)
+        }
+        Expr::Collect(inner) => Ok(vec![Value::Array(eval(inner, input)?)]),
+        Expr::Object(entries) => {
+            let mut objects = vec![Map::new()];
+            for (key_expr, value_expr) in entries {
+                let keys = eval(key_expr, input)?;
+                let values = eval(value_expr, input)?;
+                let mut next = Vec::new();
+                for object in &objects {
+                    for key in &keys {
+                        let Value::String(key) = key else {
+                            return Err(format!(
+                                "Object keys must be strings, not {}",
+                                type_name(key)
+                            ));
+                        };
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.insert(key.clone(), value.clone());
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            Ok(objects.into_iter().map(Value::Object).collect())
+        }
+        Expr::Neg(inner) => eval(inner, input)?
+            .iter()
+            .map(|value| match value {
+                Value::Number(n) => Ok(number(-n.as_f64().unwrap_or(0.0))),
+                other => Err(format!("{} cannot be negated", type_name(other))),
+            })
+            .collect(),
+        Expr::Binary(op, left, right) => {
+            let mut out = Vec::new();
+            for r in eval(right, input)? {
+                for l in eval(left, input)? {
+                    out.push(binary(op, &l, &r)?);
+                }
+            }
+            Ok(out)
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let is_and = matches!(expr, Expr::And(..));
+            let mut out = Vec::new();
+            for l in eval(left, input)? {
+                if truthy(&l) != is_and {
+                    // `false and _` / `true or _` short-circuit
+                    out.push(Value::Bool(!is_and));
+                    continue;
+                }
+                for r in eval(right, input)? {
+                    out.push(Value::Bool(truthy(&r)));
+                }
+            }
+            Ok(out)
+        }
+        Expr::Alternative(left, right) => {
+            let found: Vec<Value> = eval(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(truthy)
+                .collect();
+            if found.is_empty() {
+                eval(right, input)
+            } else {
+                Ok(found)
+            }
+        }
+        Expr::If(condition, then, otherwise) => {
+            let mut out = Vec::new();
+            for value in eval(condition, input)? {
+                if truthy(&value) {
+                    out.extend(eval(then, input)?);
+                } else if let Some(otherwise) = otherwise {
+                    out.extend(eval(otherwise, input)?);
+                } else {
+                    out.push(input.clone());
+                }
+            }
+            Ok(out)
+        }
+        Expr::Call(name, args) => call(name, args, input),
+    }
+}
+
+/// Builtin functions, by name and arity.
+fn call(name: &str, args: &[Expr], input: &Value) -> Result<Vec<Value>, String> {
+    match (name, args) {
+        ("empty", []) => Ok(Vec::new()),
+        ("not", []) => Ok(vec![Value::Bool(!truthy(input))]),
+        ("length", []) => {
+            let length = match input {
+                Value::Null => 0.0,
+                Value::Bool(_) => return Err(format!("boolean ({}) has no length", input)),
+                Value::Number(n) => n.as_f64().unwrap_or(0.0).abs(),
+                Value::String(s) => s.chars().count() as f64,
+                Value::Array(arr) => arr.len() as f64,
+                Value::Object(map) => map.len() as f64,
+            };
+            Ok(vec![number(length)])
+        }
+        ("keys", []) => match input {
+            Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                Ok(vec![Value::Array(
+                    keys.into_iter().map(|k| Value::String(k.clone())).collect(),
+                )])
+            }
+            Value::Array(arr) => Ok(vec![Value::Array(
+                (0..arr.len()).map(|i| Value::from(i as u64)).collect(),
+            )]),
+            other => Err(format!("{} has no keys", type_name(other))),
+        },
+        // As in jq, `values` drops nulls; use `.[]` for an object's values
+        ("values", []) => Ok(if input.is_null() {
+            Vec::new()
+        } else {
+            vec![input.clone()]
+        }),
+        ("type", []) => Ok(vec![Value::String(type_name(input).to_string())]),
+        ("add", []) => {
+            let mut sum = Value::Null;
+            for value in iterate(input)? {
+                sum = binary("+", &sum, &value)?;
+            }
+            Ok(vec![sum])
+        }
+        ("first", []) => index_value(input, &Value::from(0)).map(|v| vec![v]),
+        ("last", []) => index_value(input, &Value::from(-1)).map(|v| vec![v]),
+        ("reverse", []) => match input {
+            Value::Array(arr) => Ok(vec![Value::Array(arr.iter().rev().cloned().collect())]),
+            Value::String(s) => Ok(vec![Value::String(s.chars().rev().collect())]),
+            Value::Null => Ok(vec![Value::Array(Vec::new())]),
+            other => Err(format!("Cannot reverse {}", type_name(other))),
+        },
+        ("sort", []) => {
+            let mut arr = as_array(input, "sort")?.clone();
+            arr.sort_by(compare_values);
+            Ok(vec![Value::Array(arr)])
+        }
+        ("unique", []) => {
+            let mut arr = as_array(input, "unique")?.clone();
+            arr.sort_by(compare_values);
+            arr.dedup_by(|a, b| compare_values(a, b) == Ordering::Equal);
+            Ok(vec![Value::Array(arr)])
+        }
+        ("tostring", []) => Ok(vec![Value::String(match input {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })]),
+        ("tonumber", []) => match input {
+            Value::Number(_) => Ok(vec![input.clone()]),
+            Value::String(s) => s
+                .trim()
+                .parse::<f64>()
+                .map(|n| vec![number(n)])
+                .map_err(|_| format!("Cannot parse '{}' as a number", s)),
+            other => Err(format!("{} cannot be parsed as a number", type_name(other))),
+        },
+        ("to_entries", []) => match input {
+            Value::Object(map) => Ok(vec![Value::Array(
+                map.iter()
+                    .map(|(k, v)| serde_json::json!({"key": k, "value": v}))
+                    .collect(),
+            )]),
+            other => Err(format!("{} has no keys", type_name(other))),
+        },
+        ("from_entries", []) => {
+            let mut map = Map::new();
+            for entry in as_array(input, "from_entries")? {
+                let key = ["key", "k", "name"]
+                    .iter()
+                    .find_map(|k| entry.get(k).filter(|v| !v.is_null()));
+                let key = match key {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Number(n)) => n.to_string(),
+                    Some(Value::Bool(b)) => b.to_string(),
+                    _ => return Err("from_entries: entry has no string key".to_string()),
+                };
+                let value = ["value", "v"]
+                    .iter()
+                    .find_map(|k| entry.get(k))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                map.insert(key, value);
+            }
+            Ok(vec![Value::Object(map)])
+        }
+        ("map", [f]) => {
+            let mut out = Vec::new();
+            for value in iterate(input)? {
+                out.extend(eval(f, &value)?);
+            }
+            Ok(vec![Value::Array(out)])
+        }
+        ("select", [f]) => Ok(eval(f, input)?
+            .iter()
+            .filter(|v| truthy(v))
+            .map(|_| input.clone())
+            .collect()),
+        ("has", [f]) => eval(f, input)?
+            .iter()
+            .map(|key| match (input, key) {
+                (Value::Object(map), Value::String(k)) => Ok(Value::Bool(map.contains_key(k))),
+                (Value::Array(arr), Value::Number(n)) => Ok(Value::Bool(
+                    n.as_f64()
+                        .is_some_and(|i| i >= 0.0 && (i as usize) < arr.len()),
+                )),
+                _ => Err(format!(
+                    "Cannot check whether {} has a {} key",
+                    type_name(input),
+                    type_name(key)
+                )),
+            })
+            .collect(),
+        ("sort_by", [f]) => {
+            let mut keyed = Vec::new();
+            for value in as_array(input, "sort_by")? {
+                keyed.push((Value::Array(eval(f, value)?), value.clone()));
+            }
+            keyed.sort_by(|a, b| compare_values(&a.0, &b.0));
+            Ok(vec![Value::Array(
+                keyed.into_iter().map(|(_, v)| v).collect(),
+            )])
+        }
+        ("join", [f]) => {
+            let arr = as_array(input, "join")?;
+            eval(f, input)?
+                .iter()
+                .map(|sep| {
+                    let Value::String(sep) = sep else {
+                        return Err("join: separator must be a string".to_string());
+                    };
+                    let parts = arr
+                        .iter()
+                        .map(|item| match item {
+                            Value::Null => Ok(String::new()),
+                            Value::String(s) => Ok(s.clone()),
+                            Value::Number(_) | Value::Bool(_) => Ok(item.to_string()),
+                            other => Err(format!("Cannot join with {}", type_name(other))),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(Value::String(parts.join(sep)))
+                })
+                .collect()
+        }
+        _ => Err(format!("{}/{} is not defined", name, args.len())),
+    }
+}
+
+fn recurse(value: &Value, out: &mut Vec<Value>) {
+    out.push(value.clone());
+    match value {
+        Value::Array(arr) => arr.iter().for_each(|v| recurse(v, out)),
+        Value::Object(map) => map.values().for_each(|v| recurse(v, out)),
+        _ => {}
+    }
+}
+
+fn iterate(value: &Value) -> Result<Vec<Value>, String> {
+    match value {
+        Value::Array(arr) => Ok(arr.clone()),
+        Value::Object(map) => Ok(map.values().cloned().collect()),
+        other => Err(format!("Cannot iterate over {}", type_name(other))),
+    }
+}
+
+fn as_array<'a>(value: &'a Value, function: &str) -> Result<&'a Vec<Value>, String> {
+    match value {
+        Value::Array(arr) => Ok(arr),
+        other => Err(format!(
+            "{}: {} is not an array",
+            function,
+            type_name(other)
+        )),
+    }
+}
+
+fn index_value(value: &Value, key: &Value) -> Result<Value, String> {
+    match (value, key) {
+        (Value::Object(map), Value::String(k)) => Ok(map.get(k).cloned().unwrap_or(Value::Null)),
+        (Value::Array(arr), Value::Number(n)) => {
+            let i = n.as_f64().unwrap_or(0.0).floor() as i64;
+            let i = if i < 0 { i + arr.len() as i64 } else { i };
+            Ok(usize::try_from(i)
+                .ok()
+                .and_then(|i| arr.get(i))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        (Value::Null, Value::String(_) | Value::Number(_)) => Ok(Value::Null),
+        (_, Value::String(k)) => Err(format!("Cannot index {} with \"{}\"", type_name(value), k)),
+        _ => Err(format!(
+            "Cannot index {} with {}",
+            type_name(value),
+            type_name(key)
+        )),
+    }
+}
+
+fn slice_value(value: &Value, from: Option<&Value>, to: Option<&Value>) -> Result<Value, String> {
+    let bounds = |len: usize| -> Result<(usize, usize), String> {
+        let resolve = |bound: Option<&Value>, default: usize| match bound {
+            None | Some(Value::Null) => Ok(default),
+            Some(Value::Number(n)) => {
+                let i = n.as_f64().unwrap_or(0.0).floor() as i64;
+                let i = if i < 0 { i + len as i64 } else { i };
+                Ok(i.clamp(0, len as i64) as usize)
+            }
+            Some(other) => Err(format!("Cannot slice with {}", type_name(other))),
+        };
+        let start = resolve(from, 0)?;
+        Ok((start, resolve(to, len)?.max(start)))
+    };
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::Array(arr) => {
+            let (start, end) = bounds(arr.len())?;
+            Ok(Value::Array(arr[start..end].to_vec()))
+        }
+        Value::String(s) => {
+            let chars: Vec<char> = s.chars().collect();
+            let (start, end) = bounds(chars.len())?;
+            Ok(Value::String(chars[start..end].iter().collect()))
+        }
+        other => Err(format!("Cannot slice {}", type_name(other))),
+    }
+}
+
+fn binary(op: &str, left: &Value, right: &Value) -> Result<Value, String> {
+    let ordering = || compare_values(left, right);
+    match op {
+        "==" => return Ok(Value::Bool(ordering() == Ordering::Equal)),
+        "!=" => return Ok(Value::Bool(ordering() != Ordering::Equal)),
+        "<" => return Ok(Value::Bool(ordering() == Ordering::Less)),
+        "<=" => return Ok(Value::Bool(ordering() != Ordering::Greater)),
+        ">" => return Ok(Value::Bool(ordering() == Ordering::Greater)),
+        ">=" => return Ok(Value::Bool(ordering() != Ordering::Less)),
+        _ => {}
+    }
+
+    match (op, left, right) {
+        ("+", Value::Null, other) | ("+", other, Value::Null) => Ok(other.clone()),
+        ("+", Value::String(l), Value::String(r)) => Ok(Value::String(format!("{}{}", l, r))),
+        ("+", Value::Array(l), Value::Array(r)) => {
+            Ok(Value::Array(l.iter().chain(r).cloned().collect()))
+        }
+        ("+", Value::Object(l), Value::Object(r)) => {
+            let mut merged = l.clone();
+            merged.extend(r.iter().map(|(k, v)| (k.clone(), v.clone())));
+            Ok(Value::Object(merged))
+        }
+        ("-", Value::Array(l), Value::Array(r)) => Ok(Value::Array(
+            l.iter().filter(|v| !r.contains(v)).cloned().collect(),
+        )),
+        (_, Value::Number(l), Value::Number(r)) => {
+            let (l, r) = (l.as_f64().unwrap_or(0.0), r.as_f64().unwrap_or(0.0));
+            let divide_by_zero = || {
+                Err(format!(
+                    "{} and {} cannot be divided because the divisor is zero",
+                    l, r
+                ))
+            };
+            let result = match op {
+                "+" => l + r,
+                "-" => l - r,
+                "*" => l * r,
+                "/" if r == 0.0 => return divide_by_zero(),
+                "/" => l / r,
+                _ => {
+                    // `%` works on the truncated integers, so 0.5 is a zero divisor
+                    let divisor = r.trunc() as i64;
+                    if divisor == 0 {
+                        return divide_by_zero();
+                    }
+                    // Only i64::MIN % -1 overflows, and its remainder is 0
+                    (l.trunc() as i64).checked_rem(divisor).unwrap_or(0) as f64
+                }
+            };
+            Ok(number(result))
+        }
+        _ => {
+            let verb = match op {
+                "+" => "added",
+                "-" => "subtracted",
+                "*" => "multiplied",
+                "/" => "divided",
+                _ => "divided (remainder)",
+            };
+            Err(format!(
+                "{} and {} cannot be {}",
+                type_name(left),
+                type_name(right),
+                verb
+            ))
+        }
+    }
+}
+
+/// jq's total order: null < false < true < numbers < strings < arrays < objects.
+fn compare_values(left: &Value, right: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => {
+            let (l, r) = (l.as_f64().unwrap_or(0.0), r.as_f64().unwrap_or(0.0));
+            l.partial_cmp(&r).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(l), Value::String(r)) => l.cmp(r),
+        (Value::Array(l), Value::Array(r)) => l
+            .iter()
+            .zip(r)
+            .map(|(a, b)| compare_values(a, b))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| l.len().cmp(&r.len())),
+        (Value::Object(l), Value::Object(r)) => {
+            let mut l_keys: Vec<&String> = l.keys().collect();
+            let mut r_keys: Vec<&String> = r.keys().collect();
+            l_keys.sort();
+            r_keys.sort();
+            l_keys.cmp(&r_keys).then_with(|| {
+                l_keys
+                    .iter()
+                    .map(|k| compare_values(&l[*k], &r[*k]))
+                    .find(|o| *o != Ordering::Equal)
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(left).cmp(&rank(right)),
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Numbers with no fractional part are kept as integers so they print
+/// without a trailing `.0`.
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn apply_jq_filter(json: &Value, filter: &str) -> Result<Vec<Value>, String> {
+        JqFilter::parse(filter)?.apply(json)
+    }
+
+    #[test]
+    fn test_jq_identity() {
+        let data = json!({"a": 1});
+        let result = apply_jq_filter(&data, ".").unwrap();
+        assert_eq!(result, vec![data]);
+    }
+
+    #[test]
+    fn test_jq_field_access() {
+        let data = json!({"name": "test", "count": 5});
+        let result = apply_jq_filter(&data, ".name").unwrap();
+        assert_eq!(result, vec![json!("test")]);
+    }
+
+    #[test]
+    fn test_jq_nested_field() {
+        let data = json!({"user": {"name": "alice"}});
+        let result = apply_jq_filter(&data, ".user.name").unwrap();
+        assert_eq!(result, vec![json!("alice")]);
+    }
+
+    #[test]
+    fn test_jq_array_index() {
+        let data = json!({"items": [1, 2, 3]});
+        let result = apply_jq_filter(&data, ".items[1]").unwrap();
+        assert_eq!(result, vec![json!(2)]);
+    }
+
+    #[test]
+    fn test_jq_keys() {
+        let data = json!({"a": 1, "b": 2});
+        let result = apply_jq_filter(&data, "keys").unwrap();
+        assert_eq!(result.len(), 1);
+        if let serde_json::Value::Array(keys) = &result[0] {
+            assert!(keys.contains(&json!("a")));
+            assert!(keys.contains(&json!("b")));
+        } else {
+            panic!("Expected array");
+        }
+    }
+
+    #[test]
+    fn test_jq_length() {
+        let arr = json!([1, 2, 3, 4]);
+        let result = apply_jq_filter(&arr, "length").unwrap();
+        assert_eq!(result, vec![json!(4)]);
+
+        let obj = json!({"a": 1, "b": 2});
+        let result = apply_jq_filter(&obj, "length").unwrap();
+        assert_eq!(result, vec![json!(2)]);
+    }
+
+    #[test]
+    fn test_jq_iterate() {
+        let data = json!([1, 2, 3]);
+        let result = apply_jq_filter(&data, ".[]").unwrap();
+        assert_eq!(result, vec![json!(1), json!(2), json!(3)]);
+    }
+
+    #[test]
+    fn test_jq_null_for_missing() {
+        let data = json!({"a": 1});
+        let result = apply_jq_filter(&data, ".missing").unwrap();
+        assert_eq!(result, vec![serde_json::Value::Null]);
+    }
+
+    #[test]
+    fn test_jq_unsupported() {
+        let data = json!({});
+        let result = apply_jq_filter(&data, "some_unknown_func()");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_jq_map_over_field() {
+        let data = json!({"items": [{"name": "a"}, {"name": "b"}]});
+        let result = apply_jq_filter(&data, ".items | map(.name)").unwrap();
+        assert_eq!(result, vec![json!(["a", "b"])]);
+    }
+
+    #[test]
+    fn test_jq_select_truthy() {
+        let data = json!([
+            {"id": 1, "active": true},
+            {"id": 2, "active": false},
+            {"id": 3}
+        ]);
+        let result = apply_jq_filter(&data, ".[] | select(.active) | .id").unwrap();
+        assert_eq!(result, vec![json!(1)]);
+    }
+
+    #[test]
+    fn test_jq_comparisons_and_logic() {
+        let data = json!([{"name": "a", "age": 25}, {"name": "b", "age": 40}]);
+        let result = apply_jq_filter(&data, ".[] | select(.age > 30) | .name").unwrap();
+        assert_eq!(result, vec![json!("b")]);
+
+        let result =
+            apply_jq_filter(&data, "map(select(.age >= 25 and .name != \"a\")) | length").unwrap();
+        assert_eq!(result, vec![json!(1)]);
+
+        let result =
+            apply_jq_filter(&json!(null), "1 < \"a\", null < false, [1] == [1.0]").unwrap();
+        assert_eq!(result, vec![json!(true), json!(true), json!(true)]);
+    }
+
+    #[test]
+    fn test_jq_has_values_and_alternative() {
+        let data = json!({"a": 1, "b": null});
+        let result = apply_jq_filter(&data, "has(\"a\"), has(\"c\")").unwrap();
+        assert_eq!(result, vec![json!(true), json!(false)]);
+
+        let result = apply_jq_filter(&data, "[.[] | values]").unwrap();
+        assert_eq!(result, vec![json!([1])]);
+
+        let result = apply_jq_filter(&data, ".b // \"default\"").unwrap();
+        assert_eq!(result, vec![json!("default")]);
+    }
+
+    #[test]
+    fn test_jq_construction_and_arithmetic() {
+        let data = json!({"user": {"first": "Ada", "born": 1815}, "tags": ["x", "y", "z"]});
+        let result = apply_jq_filter(
+            &data,
+            "{name: .user.first, age: (1852 - .user.born), tags: (.tags[1:] | join(\",\"))}",
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![json!({"name": "Ada", "age": 37, "tags": "y,z"})]
+        );
+
+        let result = apply_jq_filter(&data, ".tags[-1], (.tags | length) * 2").unwrap();
+        assert_eq!(result, vec![json!("z"), json!(6)]);
+
+        let result =
+            apply_jq_filter(&json!(3), "if . > 2 then \"big\" else \"small\" end").unwrap();
+        assert_eq!(result, vec![json!("big")]);
+    }
+
+    #[test]
+    fn test_jq_errors() {
+        assert!(apply_jq_filter(&json!(5), ".name").is_err());
+        assert_eq!(
+            apply_jq_filter(&json!(5), ".name?").unwrap(),
+            Vec::<Value>::new()
+        );
+        assert!(apply_jq_filter(&json!({}), ".a |").is_err());
+        assert_eq!(
+            apply_jq_filter(&json!({}), "nope").unwrap_err(),
+            "nope/0 is not defined"
+        );
+
+        for filter in ["5 / 0", "5 % 0", "5 % 0.5"] {
+            let err = apply_jq_filter(&json!(null), filter).unwrap_err();
+            assert!(
+                err.ends_with("cannot be divided because the divisor is zero"),
+                "{}",
+                err
+            );
+        }
+        assert_eq!(
+            apply_jq_filter(&json!(null), "-9223372036854775808 % -1, 7 % -2.5").unwrap(),
+            vec![json!(0), json!(1)]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-//! JSON and pipeline commands: jq, xargs
+//! JSON and pipeline commands: jq, yaml, xargs
 
 use futures_lite::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use futures_lite::StreamExt;
@@ -6,7 +6,8 @@ use lexopt::prelude::*;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
-use super::helpers::resolve_path;
+use super::helpers::{resolve_path, stdout_is_terminal};
+use super::jq::JqFilter;
 use super::{make_parser, parse_common};
 
 /// JSON and pipeline commands.
@@ -17,8 +18,16 @@ impl JsonCommands {
     /// jq - JSON processor
     #[shell_command(
         name = "jq",
-        usage = "jq [-r] FILTER [FILE]",
-        description = "JSON processor for querying and transforming data"
+        usage = "jq [-rcCM] [--color[=WHEN]] [--jsonc] FILTER [FILE]",
+        description = "JSON processor for querying and transforming data.\n\
+        Supports paths (.a.b, .[0], .[], .[1:3]), pipes, map, select, keys,\n\
+        values, length, has, comparisons, arithmetic and if/then/else.\n\
+        -r: Print strings without quotes\n\
+        -c: Print each result on one line\n\
+        -C, --color[=always|auto|never]: Colorize output (default: auto,\n\
+        when stdout is a terminal and NO_COLOR is unset)\n\
+        -M: Never colorize output\n\
+        --jsonc: Accept comments and trailing commas (tsconfig.json, .vscode)"
     )]
     fn cmd_jq(
         args: Vec<String>,
@@ -28,15 +37,36 @@ impl JsonCommands {
         mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
         let cwd = env.cwd.to_string_lossy().to_string();
+        let color_auto = env.get_var("NO_COLOR").is_none() && stdout_is_terminal();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
             let mut raw_output = false;
+            let mut compact = false;
+            let mut color = color_auto;
+            let mut jsonc = false;
             let mut positional: Vec<String> = Vec::new();
             let mut parser = make_parser(remaining);
 
             while let Some(arg) = parser.next().ok().flatten() {
                 match arg {
-                    Short('r') => raw_output = true,
+                    Short('r') | Long("raw-output") => raw_output = true,
+                    Short('c') | Long("compact-output") => compact = true,
+                    Short('C') | Long("color-output") => color = true,
+                    Short('M') | Long("monochrome-output") => color = false,
+                    Long("color") => {
+                        let when = parser.optional_value().and_then(|v| v.into_string().ok());
+                        color = match when.as_deref() {
+                            None | Some("always") => true,
+                            Some("never") => false,
+                            Some("auto") => color_auto,
+                            Some(other) => {
+                                let msg = format!("jq: invalid --color value: {}\n", other);
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                                return 1;
+                            }
+                        };
+                    }
+                    Long("jsonc") => jsonc = true,
                     Value(val) => positional.push(val.string().unwrap_or_default()),
                     _ => {}
                 }
@@ -47,7 +77,14 @@ impl JsonCommands {
                 return 1;
             }
 
-            let filter = &positional[0];
+            let filter = match JqFilter::parse(&positional[0]) {
+                Ok(filter) => filter,
+                Err(e) => {
+                    let msg = format!("jq: {}\n", e);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    return 1;
+                }
+            };
             let file = positional.get(1);
 
             // Read JSON input
@@ -74,574 +111,594 @@ impl JsonCommands {
                 content
             };
 
-            // Parse JSON
-            let json: serde_json::Value = match serde_json::from_str(&input) {
-                Ok(v) => v,
-                Err(e) => {
-                    let msg = format!("jq: parse error: {}\n", e);
-                    let _ = stderr.write_all(msg.as_bytes()).await;
-                    return 1;
-                }
-            };
+            // Each whitespace-separated JSON value is a separate input
+            let input = if jsonc { strip_jsonc(&input) } else { input };
+            let mut output = String::new();
+            for json in serde_json::Deserializer::from_str(&input).into_iter::<serde_json::Value>()
+            {
+                let json = match json {
+                    Ok(v) => v,
+                    Err(e) => {
+                        let _ = stdout.write_all(output.as_bytes()).await;
+                        let msg = format!("jq: parse error: {}\n", e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 1;
+                    }
+                };
 
-            // Apply filter
-            let result = match apply_jq_filter(&json, filter) {
-                Ok(v) => v,
-                Err(e) => {
-                    let msg = format!("jq: {}\n", e);
-                    let _ = stderr.write_all(msg.as_bytes()).await;
-                    return 1;
-                }
-            };
+                let results = match filter.apply(&json) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        let _ = stdout.write_all(output.as_bytes()).await;
+                        let msg = format!("jq: error: {}\n", e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 1;
+                    }
+                };
 
-            // Output result
-            for value in result {
-                let output = if raw_output {
+                for value in results {
                     match &value {
-                        serde_json::Value::String(s) => s.clone(),
-                        v => v.to_string(),
+                        serde_json::Value::String(s) if raw_output => output.push_str(s),
+                        v => write_json(v, !compact, color, 0, &mut output),
                     }
-                } else {
-                    serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string())
-                };
-                let _ = stdout.write_all(output.as_bytes()).await;
-                let _ = stdout.write_all(b"\n").await;
+                    output.push('\n');
+                }
             }
+            let _ = stdout.write_all(output.as_bytes()).await;
 
             0
         })
     }
 
-    /// xargs - build and execute commands from stdin
+    /// yaml - convert between YAML and JSON
     #[shell_command(
-        name = "xargs",
-        usage = "xargs [-n NUM] [-I REPLACE] COMMAND [ARGS]...",
-        description = "Build and execute commands from stdin"
+        name = "yaml",
+        usage = "yaml to-json [-c] [FILE] | yaml from-json [--jsonc] [FILE]",
+        description = "Convert YAML to JSON and back"
     )]
-    fn cmd_xargs(
+    fn cmd_yaml(
         args: Vec<String>,
         env: &ShellEnv,
         stdin: piper::Reader,
         mut stdout: piper::Writer,
         mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
-        let env = env.clone();
+        let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut max_args: Option<usize> = None;
-            let mut replace_str: Option<String> = None;
-            let mut command_args: Vec<String> = Vec::new();
-            let mut in_options = true;
-
-            let mut i = 0;
-            while i < remaining.len() {
-                if in_options {
-                    match remaining[i].as_str() {
-                        "-n" => {
-                            i += 1;
-                            if i < remaining.len() {
-                                max_args = remaining[i].parse().ok();
-                            }
-                        }
-                        "-I" => {
-                            i += 1;
-                            if i < remaining.len() {
-                                replace_str = Some(remaining[i].clone());
-                            }
-                        }
-                        s if s.starts_with('-') => {
-                            // Skip unknown options
-                        }
-                        _ => {
-                            in_options = false;
-                            command_args.push(remaining[i].clone());
-                        }
-                    }
-                } else {
-                    command_args.push(remaining[i].clone());
-                }
-                i += 1;
-            }
-
-            if command_args.is_empty() {
-                command_args.push("echo".to_string());
-            }
-
-            // Read items from stdin
-            let mut items: Vec<String> = Vec::new();
-            let reader = BufReader::new(stdin);
-            let mut lines = reader.lines();
+            let mut compact = false;
+            let mut jsonc = false;
+            let mut positional: Vec<String> = Vec::new();
+            let mut parser = make_parser(remaining);
 
-            while let Some(Ok(line)) = lines.next().await {
-                for word in line.split_whitespace() {
-                    items.push(word.to_string());
+            while let Some(arg) = parser.next().ok().flatten() {
+                match arg {
+                    Short('c') | Long("compact") => compact = true,
+                    Long("jsonc") => jsonc = true,
+                    Value(val) => positional.push(val.string().unwrap_or_default()),
+                    _ => {}
                 }
             }
 
-            if items.is_empty() {
-                return 0;
+            let Some(subcommand) = positional.first().cloned() else {
+                let _ = stderr
+                    .write_all(b"yaml: missing subcommand (to-json, from-json)\n")
+                    .await;
+                return 1;
+            };
+            if subcommand != "to-json" && subcommand != "from-json" {
+                let msg = format!("yaml: unknown subcommand: {}\n", subcommand);
+                let _ = stderr.write_all(msg.as_bytes()).await;
+                return 1;
             }
 
-            // Execute the built commands
-            if let Some(ref repl) = replace_str {
-                // -I mode: one command per item, replacing the placeholder
-                for item in &items {
-                    let cmd_line: Vec<String> = command_args
-                        .iter()
-                        .map(|arg| arg.replace(repl, item))
-                        .collect();
-                    let code = execute_xargs_cmd(&cmd_line, &env, &mut stdout, &mut stderr).await;
-                    if code != 0 {
-                        return code;
+            let input = if let Some(file_path) = positional.get(1) {
+                let path = resolve_path(&cwd, file_path);
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        let msg = format!("yaml: {}: {}\n", path, e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 1;
                     }
                 }
-            } else if let Some(n) = max_args {
-                // -n mode: batch items
-                for chunk in items.chunks(n) {
-                    let mut cmd_line = command_args.clone();
-                    cmd_line.extend(chunk.iter().cloned());
-                    let code = execute_xargs_cmd(&cmd_line, &env, &mut stdout, &mut stderr).await;
-                    if code != 0 {
-                        return code;
-                    }
+            } else {
+                let mut content = String::new();
+                let reader = BufReader::new(stdin);
+                let mut lines = reader.lines();
+                while let Some(Ok(line)) = lines.next().await {
+                    content.push_str(&line);
+                    content.push('\n');
                 }
+                content
+            };
+
+            let output = if subcommand == "to-json" {
+                yaml_to_json(&input).map(|docs| {
+                    docs.iter()
+                        .map(|doc| {
+                            if compact {
+                                doc.to_string()
+                            } else {
+                                serde_json::to_string_pretty(doc)
+                                    .unwrap_or_else(|_| doc.to_string())
+                            }
+                        })
+                        .map(|line| line + "\n")
+                        .collect::<String>()
+                })
+            } else if jsonc {
+                json_to_yaml(&strip_jsonc(&input))
             } else {
-                // Default: all items in one command
-                let mut cmd_line = command_args.clone();
-                cmd_line.extend(items);
-                return execute_xargs_cmd(&cmd_line, &env, &mut stdout, &mut stderr).await;
-            }
+                json_to_yaml(&input)
+            };
 
-            0
+            match output {
+                Ok(text) => {
+                    let _ = stdout.write_all(text.as_bytes()).await;
+                    0
+                }
+                Err(e) => {
+                    let msg = format!("yaml: {}\n", e);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    1
+                }
+            }
         })
     }
-}
-
-/// Execute a single command for xargs, with concurrent output draining
-async fn execute_xargs_cmd(
-    cmd_line: &[String],
-    env: &ShellEnv,
-    stdout: &mut piper::Writer,
-    stderr: &mut piper::Writer,
-) -> i32 {
-    use futures::future::join;
-    use futures_lite::io::AsyncReadExt;
-
-    if cmd_line.is_empty() {
-        return 0;
-    }
-    let cmd_name = &cmd_line[0];
-    let cmd_args = cmd_line[1..].to_vec();
-
-    if let Some(cmd_fn) = super::ShellCommands::get_command(cmd_name) {
-        let (child_stdin_r, child_stdin_w) = piper::pipe(1024);
-        let (child_stdout_r, child_stdout_w) = piper::pipe(65536);
-        let (child_stderr_r, child_stderr_w) = piper::pipe(65536);
-        drop(child_stdin_w); // No stdin for child command
-
-        // Run command and drain outputs concurrently to avoid deadlocks
-        let cmd = cmd_fn(cmd_args, env, child_stdin_r, child_stdout_w, child_stderr_w);
-        let drain_out = async {
-            let mut buf = Vec::new();
-            let mut r = child_stdout_r;
-            let _ = r.read_to_end(&mut buf).await;
-            buf
-        };
-        let drain_err = async {
-            let mut buf = Vec::new();
-            let mut r = child_stderr_r;
-            let _ = r.read_to_end(&mut buf).await;
-            buf
-        };
-
-        let (code, (out_bytes, err_bytes)) = join(cmd, join(drain_out, drain_err)).await;
 
-        if !out_bytes.is_empty() {
-            let _ = stdout.write_all(&out_bytes).await;
-        }
-        if !err_bytes.is_empty() {
-            let _ = stderr.write_all(&err_bytes).await;
-        }
-
-        code
-    } else {
-        let msg = format!("xargs: {}: command not found\n", cmd_name);
-        let _ = stderr.write_all(msg.as_bytes()).await;
-        127
-    }
-}
+    /// xargs - build and execute commands from stdin
+    #[shell_command(
+        name = "xargs",
+        usage = "xargs [-0] [-d DELIM] [-n NUM] [-I REPLACE] COMMAND [ARGS]...",
+        description = "Build and execute commands from stdin"
+    )]
+    fn cmd_xargs(
+        args: Vec<String>,
+        env: &ShellEnv,
+        stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.clone();
+        Box::pin(async move {
+            use futures_lite::io::AsyncReadExt;
 
-/// Apply a jq-style filter to a JSON value
-fn apply_jq_filter(
-    json: &serde_json::Value,
-    filter: &str,
-) -> Result<Vec<serde_json::Value>, String> {
-    let filter = filter.trim();
-
-    // Handle pipe chains: split on | and process sequentially
-    if let Some((left, right)) = split_jq_pipe(filter) {
-        let intermediate = apply_jq_filter(json, left.trim())?;
-        let mut results = Vec::new();
-        for item in &intermediate {
-            let sub = apply_jq_filter(item, right.trim())?;
-            results.extend(sub);
-        }
-        return Ok(results);
-    }
+            let (_, remaining) = parse_common(&args);
+            let opts = match parse_xargs_args(&remaining) {
+                Ok(opts) => opts,
+                Err(e) => {
+                    let _ = stderr.write_all(format!("xargs: {}\n", e).as_bytes()).await;
+                    return 1;
+                }
+            };
 
-    if filter == "." {
-        return Ok(vec![json.clone()]);
-    }
+            let mut input = Vec::new();
+            let mut reader = stdin;
+            let _ = reader.read_to_end(&mut input).await;
+            let items = match split_xargs_input(&String::from_utf8_lossy(&input), &opts) {
+                Ok(items) => items,
+                Err(e) => {
+                    let _ = stderr.write_all(format!("xargs: {}\n", e).as_bytes()).await;
+                    return 1;
+                }
+            };
 
-    if filter == "keys" {
-        return match json {
-            serde_json::Value::Object(map) => {
-                let keys: Vec<serde_json::Value> = map
-                    .keys()
-                    .map(|k| serde_json::Value::String(k.clone()))
-                    .collect();
-                Ok(vec![serde_json::Value::Array(keys)])
-            }
-            serde_json::Value::Array(arr) => {
-                let indices: Vec<serde_json::Value> = (0..arr.len())
-                    .map(|i| serde_json::Value::Number(i.into()))
-                    .collect();
-                Ok(vec![serde_json::Value::Array(indices)])
+            // Each command gets a fresh environment rooted at our cwd
+            let mut child_env = ShellEnv::new();
+            child_env.cwd = cwd;
+
+            // Like GNU xargs: keep going after a failed command and report
+            // 123 at the end, but stop at once if the command is missing
+            let mut exit_code = 0;
+            for cmd_line in xargs_command_lines(&opts, &items) {
+                match execute_xargs_cmd(&cmd_line, &child_env, &mut stdout, &mut stderr).await {
+                    0 => {}
+                    127 => return 127,
+                    _ => exit_code = 123,
+                }
             }
-            _ => Err("keys: not an object or array".to_string()),
-        };
-    }
-
-    if filter == "length" {
-        return match json {
-            serde_json::Value::Object(map) => Ok(vec![serde_json::json!(map.len())]),
-            serde_json::Value::Array(arr) => Ok(vec![serde_json::json!(arr.len())]),
-            serde_json::Value::String(s) => Ok(vec![serde_json::json!(s.len())]),
-            serde_json::Value::Null => Ok(vec![serde_json::json!(0)]),
-            _ => Err("length: not applicable".to_string()),
-        };
-    }
-
-    if filter == "type" {
-        let type_name = match json {
-            serde_json::Value::Null => "null",
-            serde_json::Value::Bool(_) => "boolean",
-            serde_json::Value::Number(_) => "number",
-            serde_json::Value::String(_) => "string",
-            serde_json::Value::Array(_) => "array",
-            serde_json::Value::Object(_) => "object",
-        };
-        return Ok(vec![serde_json::Value::String(type_name.to_string())]);
+            exit_code
+        })
     }
+}
 
-    if filter == ".[]" {
-        return match json {
-            serde_json::Value::Array(arr) => Ok(arr.clone()),
-            serde_json::Value::Object(map) => Ok(map.values().cloned().collect()),
-            _ => Err(".[] requires an array or object".to_string()),
-        };
-    }
+/// How `xargs` splits its input and builds command lines.
+#[derive(Debug, Default, PartialEq)]
+struct XargsOptions {
+    /// Item delimiter from -0/-d; otherwise items are blank-separated words
+    delimiter: Option<char>,
+    max_args: Option<usize>,
+    replace: Option<String>,
+    command: Vec<String>,
+}
 
-    // Handle .[].field or .[].field1.field2 — iterate then access fields on each element
-    if let Some(rest) = filter.strip_prefix(".[].") {
-        let items = match json {
-            serde_json::Value::Array(arr) => arr.clone(),
-            serde_json::Value::Object(map) => map.values().cloned().collect(),
-            _ => return Err(".[].field requires an array or object".to_string()),
-        };
-        let sub_filter = format!(".{}", rest);
-        let mut results = Vec::new();
-        for item in &items {
-            let sub = apply_jq_filter(item, &sub_filter)?;
-            results.extend(sub);
+/// Parse xargs options up to the first operand, which starts the command.
+fn parse_xargs_args(args: &[String]) -> Result<XargsOptions, String> {
+    let mut opts = XargsOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if !arg.starts_with('-') || arg == "-" {
+            break;
         }
-        return Ok(results);
-    }
-
-    // select(.field op value) — filter
-    if filter.starts_with("select(") && filter.ends_with(')') {
-        let inner = &filter[7..filter.len() - 1];
-        let matches = evaluate_jq_condition(json, inner)?;
-        if matches {
-            return Ok(vec![json.clone()]);
-        } else {
-            return Ok(vec![]);
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        if arg == "-0" || arg == "--null" {
+            opts.delimiter = Some('\0');
+            continue;
         }
-    }
 
-    // has("key") — check if key exists
-    if filter.starts_with("has(") && filter.ends_with(')') {
-        let inner = &filter[4..filter.len() - 1].trim();
-        let key = inner.trim_matches('"');
-        let result = match json {
-            serde_json::Value::Object(map) => map.contains_key(key),
-            serde_json::Value::Array(arr) => {
-                if let Ok(idx) = key.parse::<usize>() {
-                    idx < arr.len()
-                } else {
-                    false
-                }
+        // Options taking a value accept it attached (-n2, --max-args=2)
+        let (name, attached) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name, Some(value.to_string())),
+            // A multibyte character right after the dash is an unknown option
+            _ if !arg.starts_with("--") && arg.len() > 2 && arg.is_char_boundary(2) => {
+                let (name, value) = arg.split_at(2);
+                (name, Some(value.to_string()))
             }
-            _ => false,
+            _ => (arg, None),
         };
-        return Ok(vec![serde_json::Value::Bool(result)]);
-    }
-
-    // map(expr) — apply expr to each element
-    if filter.starts_with("map(") && filter.ends_with(')') {
-        let inner = &filter[4..filter.len() - 1];
-        return match json {
-            serde_json::Value::Array(arr) => {
-                let mut results = Vec::new();
-                for item in arr {
-                    let sub = apply_jq_map_expr(item, inner.trim())?;
-                    results.push(sub);
-                }
-                Ok(vec![serde_json::Value::Array(results)])
+        if !matches!(
+            name,
+            "-n" | "--max-args" | "-I" | "--replace" | "-d" | "--delimiter"
+        ) {
+            return Err(format!("invalid option '{}'", arg));
+        }
+        let value = match attached {
+            Some(value) => value,
+            None => {
+                let value = args
+                    .get(i)
+                    .ok_or_else(|| format!("option requires an argument -- '{}'", name))?;
+                i += 1;
+                value.clone()
             }
-            _ => Err("map requires an array".to_string()),
         };
+        match name {
+            "-n" | "--max-args" => match value.parse::<usize>() {
+                Ok(n) if n > 0 => opts.max_args = Some(n),
+                _ => return Err(format!("invalid number \"{}\" for -n option", value)),
+            },
+            "-I" | "--replace" => opts.replace = Some(value),
+            _ => opts.delimiter = Some(parse_xargs_delimiter(&value)?),
+        }
     }
+    opts.command = args[i..].to_vec();
+    if opts.command.is_empty() {
+        opts.command.push("echo".to_string());
+    }
+    Ok(opts)
+}
 
-    // Handle field access: .field or .field1.field2
-    if filter.starts_with('.') {
-        let path = &filter[1..];
-        let mut current = json;
-
-        for part in path.split('.') {
-            if part.is_empty() {
-                continue;
+/// A -d delimiter: one character or a backslash escape like `\n` or `\0`.
+fn parse_xargs_delimiter(value: &str) -> Result<char, String> {
+    let delim = match value {
+        "\\n" => '\n',
+        "\\t" => '\t',
+        "\\0" => '\0',
+        "\\\\" => '\\',
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("invalid delimiter \"{}\"", value)),
             }
+        }
+    };
+    Ok(delim)
+}
 
-            // Check for array index: [n]
-            if let Some(bracket_start) = part.find('[') {
-                let field = &part[..bracket_start];
-                let rest = &part[bracket_start..];
-
-                // Access field first if present
-                if !field.is_empty() {
-                    current = match current.get(field) {
-                        Some(v) => v,
-                        None => return Ok(vec![serde_json::Value::Null]),
-                    };
+/// Split xargs input into items.
+///
+/// With a delimiter, items are taken verbatim between delimiters. With -I,
+/// each non-blank line is one item (leading blanks removed). Otherwise items
+/// are blank-separated words where quotes and backslashes protect blanks.
+fn split_xargs_input(input: &str, opts: &XargsOptions) -> Result<Vec<String>, String> {
+    if let Some(delim) = opts.delimiter {
+        let mut items: Vec<String> = input.split(delim).map(str::to_string).collect();
+        if items.last().is_some_and(|last| last.is_empty()) {
+            items.pop();
+        }
+        return Ok(items);
+    }
+    if opts.replace.is_some() {
+        return Ok(input
+            .lines()
+            .map(|line| line.trim_start())
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect());
+    }
+
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_item = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_item {
+                    items.push(std::mem::take(&mut current));
+                    in_item = false;
                 }
-
-                // Then handle bracket(s)
-                let mut temp = current;
-                for cap in rest.split('[').skip(1) {
-                    if let Some(idx_str) = cap.strip_suffix(']') {
-                        if let Ok(idx) = idx_str.parse::<usize>() {
-                            temp = match temp.get(idx) {
-                                Some(v) => v,
-                                None => return Ok(vec![serde_json::Value::Null]),
-                            };
+            }
+            '\'' | '"' => {
+                in_item = true;
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some('\n') | None => {
+                            let kind = if c == '\'' { "single" } else { "double" };
+                            return Err(format!("unmatched {} quote", kind));
                         }
+                        Some(other) => current.push(other),
                     }
                 }
-                current = temp;
-            } else {
-                current = match current.get(part) {
-                    Some(v) => v,
-                    None => return Ok(vec![serde_json::Value::Null]),
-                };
             }
-        }
-
-        return Ok(vec![current.clone()]);
-    }
-
-    Err(format!("unsupported filter: {}", filter))
-}
-
-/// Split a jq filter on the top-level pipe character, respecting parentheses
-fn split_jq_pipe(filter: &str) -> Option<(&str, &str)> {
-    let mut paren_depth = 0;
-    let mut bracket_depth = 0;
-    let mut in_string = false;
-    let bytes = filter.as_bytes();
-
-    for i in 0..bytes.len() {
-        match bytes[i] {
-            b'"' if !in_string => in_string = true,
-            b'"' if in_string => in_string = false,
-            b'(' if !in_string => paren_depth += 1,
-            b')' if !in_string => paren_depth -= 1,
-            b'[' if !in_string => bracket_depth += 1,
-            b']' if !in_string => bracket_depth -= 1,
-            b'|' if !in_string && paren_depth == 0 && bracket_depth == 0 => {
-                return Some((&filter[..i], &filter[i + 1..]));
+            '\\' => {
+                in_item = true;
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            _ => {
+                in_item = true;
+                current.push(c);
             }
-            _ => {}
         }
     }
-    None
-}
-
-/// Evaluate a simple jq condition (for select)
-fn evaluate_jq_condition(json: &serde_json::Value, condition: &str) -> Result<bool, String> {
-    let condition = condition.trim();
-
-    // Parse: .field op value
-    // Operators: >, <, >=, <=, ==, !=
-    let ops = [">=", "<=", "!=", "==", ">", "<"];
-    for op in &ops {
-        if let Some(pos) = condition.find(op) {
-            let left_expr = condition[..pos].trim();
-            let right_expr = condition[pos + op.len()..].trim();
-
-            // Evaluate left side
-            let left_vals = apply_jq_filter(json, left_expr)?;
-            let left_val = left_vals
-                .first()
-                .cloned()
-                .unwrap_or(serde_json::Value::Null);
-
-            // Parse right side as a value
-            let right_val = parse_jq_literal(right_expr);
-
-            return Ok(compare_jq_values(&left_val, *op, &right_val));
-        }
+    if in_item {
+        items.push(current);
     }
+    Ok(items)
+}
 
-    // Just a field access as boolean
-    let vals = apply_jq_filter(json, condition)?;
-    Ok(vals
-        .first()
-        .map(|v| match v {
-            serde_json::Value::Bool(b) => *b,
-            serde_json::Value::Null => false,
-            _ => true,
+/// Build the command lines to run: one per item with -I (replacing the
+/// placeholder), batches of -n items, or a single line with every item.
+fn xargs_command_lines(opts: &XargsOptions, items: &[String]) -> Vec<Vec<String>> {
+    if let Some(repl) = &opts.replace {
+        return items
+            .iter()
+            .map(|item| {
+                opts.command
+                    .iter()
+                    .map(|arg| arg.replace(repl, item))
+                    .collect()
+            })
+            .collect();
+    }
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let batch = opts.max_args.unwrap_or(items.len());
+    items
+        .chunks(batch)
+        .map(|chunk| {
+            let mut cmd_line = opts.command.clone();
+            cmd_line.extend(chunk.iter().cloned());
+            cmd_line
         })
-        .unwrap_or(false))
+        .collect()
 }
 
-/// Parse a literal value in a jq expression
-fn parse_jq_literal(s: &str) -> serde_json::Value {
-    let s = s.trim();
-    // Try string (quoted)
-    if (s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\\') && s.contains('"')) {
-        let unquoted = s
-            .trim_matches('"')
-            .trim_start_matches("\\\"")
-            .trim_end_matches("\\\"");
-        // Also handle escaped quotes within
-        let clean = s.trim_matches(|c| c == '"' || c == '\\');
-        if !clean.is_empty() {
-            return serde_json::Value::String(clean.to_string());
-        }
-        return serde_json::Value::String(unquoted.to_string());
-    }
-    // Try number
-    if let Ok(n) = s.parse::<i64>() {
-        return serde_json::json!(n);
-    }
-    if let Ok(n) = s.parse::<f64>() {
-        return serde_json::json!(n);
-    }
-    // Try bool/null
-    match s {
-        "true" => serde_json::Value::Bool(true),
-        "false" => serde_json::Value::Bool(false),
-        "null" => serde_json::Value::Null,
-        _ => serde_json::Value::String(s.to_string()),
-    }
+/// Execute a single command for xargs, copying its output through
+async fn execute_xargs_cmd(
+    cmd_line: &[String],
+    env: &ShellEnv,
+    stdout: &mut piper::Writer,
+    stderr: &mut piper::Writer,
+) -> i32 {
+    let cmd_name = &cmd_line[0];
+    let Some(cmd_fn) = super::ShellCommands::get_command(cmd_name) else {
+        let msg = format!("xargs: {}: command not found\n", cmd_name);
+        let _ = stderr.write_all(msg.as_bytes()).await;
+        return 127;
+    };
+    let (code, out, err) = super::misc::run_captured(cmd_fn, cmd_line[1..].to_vec(), env).await;
+    let _ = stdout.write_all(&out).await;
+    let _ = stderr.write_all(&err).await;
+    code
 }
 
-/// Compare two jq values with an operator
-fn compare_jq_values(left: &serde_json::Value, op: &str, right: &serde_json::Value) -> bool {
-    // Numeric comparison
-    let left_num = match left {
-        serde_json::Value::Number(n) => n.as_f64(),
-        _ => None,
-    };
-    let right_num = match right {
-        serde_json::Value::Number(n) => n.as_f64(),
-        _ => None,
-    };
+/// Parse YAML (one or more `---` separated documents) into JSON values.
+fn yaml_to_json(input: &str) -> Result<Vec<serde_json::Value>, String> {
+    use serde::Deserialize;
+
+    let mut docs = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(input) {
+        let value = serde_json::Value::deserialize(document).map_err(|e| match e.location() {
+            Some(loc) => format!(
+                "parse error at line {}, column {}: {}",
+                loc.line(),
+                loc.column(),
+                e
+            ),
+            None => format!("parse error: {}", e),
+        })?;
+        docs.push(value);
+    }
+    Ok(docs)
+}
 
-    if let (Some(l), Some(r)) = (left_num, right_num) {
-        return match op {
-            ">" => l > r,
-            "<" => l < r,
-            ">=" => l >= r,
-            "<=" => l <= r,
-            "==" => (l - r).abs() < f64::EPSILON,
-            "!=" => (l - r).abs() >= f64::EPSILON,
-            _ => false,
-        };
-    }
+/// Serialize a stream of JSON values as YAML, one document per value.
+fn json_to_yaml(input: &str) -> Result<String, String> {
+    let mut docs = Vec::new();
+    for value in serde_json::Deserializer::from_str(input).into_iter::<serde_json::Value>() {
+        let value = value.map_err(|e| {
+            format!(
+                "parse error at line {}, column {}: {}",
+                e.line(),
+                e.column(),
+                e
+            )
+        })?;
+        docs.push(serde_yaml::to_string(&value).map_err(|e| e.to_string())?);
+    }
+    Ok(docs.join("---\n"))
+}
 
-    // String comparison
-    let left_str = match left {
-        serde_json::Value::String(s) => Some(s.as_str()),
-        _ => None,
+// ANSI colors for jq output
+const JSON_COLOR_RESET: &str = "\x1b[0m";
+const JSON_COLOR_NULL: &str = "\x1b[1;30m"; // Gray
+const JSON_COLOR_BOOL: &str = "\x1b[0;33m"; // Yellow
+const JSON_COLOR_NUMBER: &str = "\x1b[0;36m"; // Cyan
+const JSON_COLOR_STRING: &str = "\x1b[0;32m"; // Green
+const JSON_COLOR_KEY: &str = "\x1b[34;1m"; // Bold blue
+
+/// Serialize `value` like `serde_json::to_string_pretty` (or `to_string`
+/// when not `pretty`), optionally wrapping each scalar and key in ANSI
+/// colors. `depth` is the current nesting level for indentation.
+fn write_json(
+    value: &serde_json::Value,
+    pretty: bool,
+    color: bool,
+    depth: usize,
+    out: &mut String,
+) {
+    use serde_json::Value;
+
+    let paint = |out: &mut String, code: &str, text: &str| {
+        if color {
+            out.push_str(code);
+            out.push_str(text);
+            out.push_str(JSON_COLOR_RESET);
+        } else {
+            out.push_str(text);
+        }
     };
-    let right_str = match right {
-        serde_json::Value::String(s) => Some(s.as_str()),
-        _ => None,
+    let newline = |out: &mut String, depth: usize| {
+        if pretty {
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+        }
     };
 
-    if let (Some(l), Some(r)) = (left_str, right_str) {
-        return match op {
-            "==" => l == r,
-            "!=" => l != r,
-            ">" => l > r,
-            "<" => l < r,
-            ">=" => l >= r,
-            "<=" => l <= r,
-            _ => false,
-        };
-    }
-
-    // General equality
-    match op {
-        "==" => left == right,
-        "!=" => left != right,
-        _ => false,
+    match value {
+        Value::Null => paint(out, JSON_COLOR_NULL, "null"),
+        Value::Bool(b) => paint(out, JSON_COLOR_BOOL, &b.to_string()),
+        Value::Number(n) => paint(out, JSON_COLOR_NUMBER, &n.to_string()),
+        Value::String(_) => paint(out, JSON_COLOR_STRING, &value.to_string()),
+        Value::Array(arr) if arr.is_empty() => out.push_str("[]"),
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Array(arr) => {
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                write_json(item, pretty, color, depth + 1, out);
+            }
+            newline(out, depth);
+            out.push(']');
+        }
+        Value::Object(map) => {
+            out.push('{');
+            for (i, (key, item)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(out, depth + 1);
+                paint(out, JSON_COLOR_KEY, &Value::String(key.clone()).to_string());
+                out.push_str(if pretty { ": " } else { ":" });
+                write_json(item, pretty, color, depth + 1, out);
+            }
+            newline(out, depth);
+            out.push('}');
+        }
     }
 }
 
-/// Apply a simple map expression (. * N, . + N, etc.)
-fn apply_jq_map_expr(item: &serde_json::Value, expr: &str) -> Result<serde_json::Value, String> {
-    let expr = expr.trim();
-
-    // Handle ". * N" or ". + N"
-    let ops = ["*", "+", "-", "/"];
-    for op in &ops {
-        if let Some(pos) = expr.find(op) {
-            let left = expr[..pos].trim();
-            let right = expr[pos + 1..].trim();
-
-            if left == "." {
-                let item_num = match item {
-                    serde_json::Value::Number(n) => n.as_f64().unwrap_or(0.0),
-                    _ => return Ok(item.clone()),
-                };
-                let right_num: f64 = right.parse().unwrap_or(0.0);
-                let result = match *op {
-                    "*" => item_num * right_num,
-                    "+" => item_num + right_num,
-                    "-" => item_num - right_num,
-                    "/" => {
-                        if right_num != 0.0 {
-                            item_num / right_num
-                        } else {
-                            0.0
-                        }
-                    }
-                    _ => item_num,
-                };
-                // Return as integer if possible
-                if result == result.floor() && result.abs() < i64::MAX as f64 {
-                    return Ok(serde_json::json!(result as i64));
+/// Reduce JSONC to strict JSON: drop `//` and `/* */` comments and commas
+/// that directly precede a closing `}` or `]`. Comments become spaces (or
+/// keep their newlines) so parse errors still point at the right line.
+fn strip_jsonc(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut stripped = String::with_capacity(input.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            stripped.push(c);
+            if c == '\\' {
+                if let Some(&escaped) = chars.get(i + 1) {
+                    stripped.push(escaped);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+                i += 1;
+            }
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    stripped.push(' ');
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                stripped.push_str("  ");
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    stripped.push(if chars[i] == '\n' { '\n' } else { ' ' });
+                    i += 1;
+                }
+                if i < chars.len() {
+                    stripped.push_str("  ");
+                    i += 2;
                 }
-                return Ok(serde_json::json!(result));
+            }
+            _ => {
+                stripped.push(c);
+                i += 1;
             }
         }
     }
 
-    // Simple field access
-    apply_jq_filter(item, expr).map(|v| v.into_iter().next().unwrap_or(serde_json::Value::Null))
+    // With comments gone, a comma is trailing when only whitespace separates
+    // it from a closing bracket.
+    let chars: Vec<char> = stripped.chars().collect();
+    let mut output = String::with_capacity(stripped.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            if c == '\\' {
+                output.push(c);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|ch| !ch.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                output.push(' ');
+                i += 1;
+                continue;
+            }
+        }
+        if let Some(&ch) = chars.get(i) {
+            output.push(ch);
+        }
+        i += 1;
+    }
+    output
 }
 
 #[cfg(test)]
@@ -650,75 +707,148 @@ mod tests {
     use serde_json::json;
 
     #[test]
-    fn test_jq_identity() {
-        let data = json!({"a": 1});
-        let result = apply_jq_filter(&data, ".").unwrap();
-        assert_eq!(result, vec![data]);
+    fn test_yaml_round_trip() {
+        let yaml = "name: demo\nreplicas: 3\nenabled: true\ntags:\n  - web\n  - api\nlimits:\n  cpu: 0.5\n  memory: null\n";
+        let docs = yaml_to_json(yaml).unwrap();
+        assert_eq!(
+            docs,
+            vec![json!({
+                "name": "demo",
+                "replicas": 3,
+                "enabled": true,
+                "tags": ["web", "api"],
+                "limits": {"cpu": 0.5, "memory": null}
+            })]
+        );
+
+        let back = json_to_yaml(&docs[0].to_string()).unwrap();
+        assert_eq!(yaml_to_json(&back).unwrap(), docs);
     }
 
     #[test]
-    fn test_jq_field_access() {
-        let data = json!({"name": "test", "count": 5});
-        let result = apply_jq_filter(&data, ".name").unwrap();
-        assert_eq!(result, vec![json!("test")]);
+    fn test_yaml_multi_document() {
+        let docs = yaml_to_json("---\na: 1\n---\n- x\n- y\n").unwrap();
+        assert_eq!(docs, vec![json!({"a": 1}), json!(["x", "y"])]);
+
+        let yaml = json_to_yaml("{\"a\": 1}\n[\"x\"]\n").unwrap();
+        assert_eq!(yaml, "a: 1\n---\n- x\n");
     }
 
     #[test]
-    fn test_jq_nested_field() {
-        let data = json!({"user": {"name": "alice"}});
-        let result = apply_jq_filter(&data, ".user.name").unwrap();
-        assert_eq!(result, vec![json!("alice")]);
+    fn test_yaml_error_has_location() {
+        let err = yaml_to_json("a: 1\nb: [1, 2\n").unwrap_err();
+        assert!(err.contains("line "), "err: {}", err);
+        assert!(err.contains("column "), "err: {}", err);
     }
 
     #[test]
-    fn test_jq_array_index() {
-        let data = json!({"items": [1, 2, 3]});
-        let result = apply_jq_filter(&data, ".items[1]").unwrap();
-        assert_eq!(result, vec![json!(2)]);
+    fn test_write_json_matches_serde_layout() {
+        let value = json!({"a": [1, {"b": null}, []], "c": {}, "d": "x\"y", "e": true});
+        let mut pretty = String::new();
+        write_json(&value, true, false, 0, &mut pretty);
+        assert_eq!(pretty, serde_json::to_string_pretty(&value).unwrap());
+
+        let mut compact = String::new();
+        write_json(&value, false, false, 0, &mut compact);
+        assert_eq!(compact, value.to_string());
+
+        let mut colored = String::new();
+        write_json(&json!({"k": 1}), false, true, 0, &mut colored);
+        assert_eq!(colored, "{\x1b[34;1m\"k\"\x1b[0m:\x1b[0;36m1\x1b[0m}");
     }
 
     #[test]
-    fn test_jq_keys() {
-        let data = json!({"a": 1, "b": 2});
-        let result = apply_jq_filter(&data, "keys").unwrap();
-        assert_eq!(result.len(), 1);
-        if let serde_json::Value::Array(keys) = &result[0] {
-            assert!(keys.contains(&json!("a")));
-            assert!(keys.contains(&json!("b")));
-        } else {
-            panic!("Expected array");
-        }
+    fn test_strip_jsonc() {
+        let input = r#"{
+            // line comment
+            "path": "a//b /* not a comment */",
+            "escaped": "quote \" // still a string",
+            /* block
+               comment */
+            "list": [1, 2, 3,],
+        }"#;
+        assert!(serde_json::from_str::<serde_json::Value>(input).is_err());
+
+        let stripped = strip_jsonc(input);
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["path"], "a//b /* not a comment */");
+        assert_eq!(value["escaped"], "quote \" // still a string");
+        assert_eq!(value["list"], serde_json::json!([1, 2, 3]));
+        // Line numbers survive for error messages
+        assert_eq!(stripped.lines().count(), input.lines().count());
+    }
+
+    fn xargs_opts(args: &[&str]) -> XargsOptions {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_xargs_args(&args).unwrap()
     }
 
     #[test]
-    fn test_jq_length() {
-        let arr = json!([1, 2, 3, 4]);
-        let result = apply_jq_filter(&arr, "length").unwrap();
-        assert_eq!(result, vec![json!(4)]);
-
-        let obj = json!({"a": 1, "b": 2});
-        let result = apply_jq_filter(&obj, "length").unwrap();
-        assert_eq!(result, vec![json!(2)]);
+    fn test_xargs_parses_options_until_command() {
+        let opts = xargs_opts(&["-n2", "-d", "\\n", "grep", "-n", "x"]);
+        assert_eq!(opts.max_args, Some(2));
+        assert_eq!(opts.delimiter, Some('\n'));
+        assert_eq!(opts.command, vec!["grep", "-n", "x"]);
+
+        let opts = xargs_opts(&["-0", "-I{}"]);
+        assert_eq!(opts.delimiter, Some('\0'));
+        assert_eq!(opts.replace.as_deref(), Some("{}"));
+        assert_eq!(opts.command, vec!["echo"]);
+
+        let args: Vec<String> = vec!["-n".into(), "0".into()];
+        assert!(parse_xargs_args(&args).is_err());
+        let args: Vec<String> = vec!["-z".into()];
+        assert!(parse_xargs_args(&args).is_err());
     }
 
     #[test]
-    fn test_jq_iterate() {
-        let data = json!([1, 2, 3]);
-        let result = apply_jq_filter(&data, ".[]").unwrap();
-        assert_eq!(result, vec![json!(1), json!(2), json!(3)]);
+    fn test_xargs_multibyte_options() {
+        let args: Vec<String> = vec!["-é".into()];
+        assert!(parse_xargs_args(&args).is_err());
+        let args: Vec<String> = vec!["-éx".into()];
+        assert!(parse_xargs_args(&args).is_err());
+
+        let opts = xargs_opts(&["-d→", "cat"]);
+        assert_eq!(opts.delimiter, Some('→'));
+        assert_eq!(opts.command, vec!["cat"]);
     }
 
     #[test]
-    fn test_jq_null_for_missing() {
-        let data = json!({"a": 1});
-        let result = apply_jq_filter(&data, ".missing").unwrap();
-        assert_eq!(result, vec![serde_json::Value::Null]);
+    fn test_xargs_splits_words_lines_and_delimiters() {
+        let opts = xargs_opts(&[]);
+        assert_eq!(
+            split_xargs_input("a  'b c'\n\"d\" e\\ f\n", &opts).unwrap(),
+            vec!["a", "b c", "d", "e f"]
+        );
+        assert!(split_xargs_input("'open\n", &opts).is_err());
+
+        let opts = xargs_opts(&["-I", "X"]);
+        assert_eq!(
+            split_xargs_input("  one two\n\nthree\n", &opts).unwrap(),
+            vec!["one two", "three"]
+        );
+
+        let opts = xargs_opts(&["-0"]);
+        assert_eq!(
+            split_xargs_input("a b\0\0c\0", &opts).unwrap(),
+            vec!["a b", "", "c"]
+        );
     }
 
     #[test]
-    fn test_jq_unsupported() {
-        let data = json!({});
-        let result = apply_jq_filter(&data, "some_unknown_func()");
-        assert!(result.is_err());
+    fn test_xargs_command_lines() {
+        let items: Vec<String> = ["1", "2", "3"].iter().map(|s| s.to_string()).collect();
+        let opts = xargs_opts(&["-n", "2", "echo", "n"]);
+        assert_eq!(
+            xargs_command_lines(&opts, &items),
+            vec![vec!["echo", "n", "1", "2"], vec!["echo", "n", "3"]]
+        );
+
+        let opts = xargs_opts(&["-I", "{}", "mv", "{}", "{}.bak"]);
+        assert_eq!(
+            xargs_command_lines(&opts, &items[..1]),
+            vec![vec!["mv", "1", "1.bak"]]
+        );
+        assert!(xargs_command_lines(&xargs_opts(&[]), &[]).is_empty());
     }
 }
@@ -14,13 +14,110 @@ use crate::bindings::wasi::clocks::wall_clock;
 /// Miscellaneous commands.
 pub struct MiscCommands;
 
+/// Block for `secs` seconds on the WASI monotonic clock.
+fn sleep_seconds(secs: f64) {
+    // Convert seconds to nanoseconds for WASI Duration
+    let nanos = (secs * 1_000_000_000.0) as u64;
+
+    if nanos > 0 {
+        // Idiomatic WASI: subscribe to a duration pollable and block until ready
+        let pollable = monotonic_clock::subscribe_duration(nanos);
+        pollable.block();
+    }
+}
+
+/// Per-attempt (stdout, stderr), with retry progress appended to stderr.
+pub(crate) type RetryLog = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Run one attempt of a command with no stdin, capturing stdout and stderr.
+pub(super) async fn run_captured(
+    cmd_fn: super::CommandFn,
+    args: Vec<String>,
+    env: &ShellEnv,
+) -> (i32, Vec<u8>, Vec<u8>) {
+    use futures::future::join;
+    use futures_lite::io::AsyncReadExt;
+
+    let (child_stdin_r, child_stdin_w) = piper::pipe(1024);
+    let (child_stdout_r, child_stdout_w) = piper::pipe(65536);
+    let (child_stderr_r, child_stderr_w) = piper::pipe(65536);
+    drop(child_stdin_w);
+
+    // Run command and drain outputs concurrently to avoid deadlocks
+    let cmd = cmd_fn(args, env, child_stdin_r, child_stdout_w, child_stderr_w);
+    let drain_out = async {
+        let mut buf = Vec::new();
+        let mut r = child_stdout_r;
+        let _ = r.read_to_end(&mut buf).await;
+        buf
+    };
+    let drain_err = async {
+        let mut buf = Vec::new();
+        let mut r = child_stderr_r;
+        let _ = r.read_to_end(&mut buf).await;
+        buf
+    };
+    let (code, (out, err)) = join(cmd, join(drain_out, drain_err)).await;
+    (code, out, err)
+}
+
+/// Run `attempt` until it exits 0 or `retries` retries are used up, waiting
+/// `delay` seconds between attempts (doubling with `backoff`). Returns the
+/// last exit code.
+pub(crate) async fn run_with_retries<F, Fut>(
+    retries: usize,
+    delay: f64,
+    backoff: bool,
+    mut attempt: F,
+) -> (i32, RetryLog)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = (i32, Vec<u8>, Vec<u8>)>,
+{
+    let mut log = RetryLog::new();
+    let total = retries + 1;
+    let mut wait = delay;
+    let mut code = 0;
+    for n in 1..=total {
+        let (status, out, mut err) = attempt().await;
+        code = status;
+        if code == 0 {
+            log.push((out, err));
+            break;
+        }
+        let message = if n == total {
+            format!(
+                "retry: attempt {}/{} failed with exit code {}, giving up\n",
+                n, total, code
+            )
+        } else {
+            format!(
+                "retry: attempt {}/{} failed with exit code {}, retrying in {}s\n",
+                n, total, code, wait
+            )
+        };
+        err.extend_from_slice(message.as_bytes());
+        log.push((out, err));
+        if n == total {
+            break;
+        }
+        sleep_seconds(wait);
+        if backoff {
+            wait *= 2.0;
+        }
+    }
+    (code, log)
+}
+
 #[shell_commands]
 impl MiscCommands {
     /// seq - print sequence of numbers
     #[shell_command(
         name = "seq",
-        usage = "seq [FIRST] [INCREMENT] LAST",
-        description = "Print sequence of numbers"
+        usage = "seq [-w] [-s SEP] [FIRST [INCREMENT]] LAST",
+        description = "Print numbers from FIRST (default 1) to LAST by INCREMENT (default 1).\n\
+        -s SEP: Separate numbers with SEP instead of a newline\n\
+        -w: Pad numbers with leading zeros to equal width"
     )]
     fn cmd_seq(
         args: Vec<String>,
@@ -31,30 +128,110 @@ impl MiscCommands {
     ) -> futures_lite::future::Boxed<i32> {
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let nums: Vec<i64> = remaining.iter().filter_map(|s| s.parse().ok()).collect();
+            let mut separator = "\n".to_string();
+            let mut equal_width = false;
+            let mut operands: Vec<String> = Vec::new();
+            let mut i = 0;
+
+            // Parsed by hand: negative operands like -1 must not be taken as flags
+            while i < remaining.len() {
+                let arg = &remaining[i];
+                if arg == "-w" || arg == "--equal-width" {
+                    equal_width = true;
+                } else if arg == "-s" || arg == "--separator" {
+                    i += 1;
+                    match remaining.get(i) {
+                        Some(sep) => separator = sep.clone(),
+                        None => {
+                            let _ = stderr
+                                .write_all(b"seq: option requires an argument -- 's'\n")
+                                .await;
+                            return 1;
+                        }
+                    }
+                } else if let Some(sep) = arg.strip_prefix("--separator=") {
+                    separator = sep.to_string();
+                } else if let Some(sep) = arg.strip_prefix("-s") {
+                    separator = sep.to_string();
+                } else {
+                    operands.push(arg.clone());
+                }
+                i += 1;
+            }
+
+            let mut nums = Vec::new();
+            for operand in &operands {
+                match operand.parse::<f64>() {
+                    Ok(n) if n.is_finite() => nums.push(n),
+                    _ => {
+                        let msg = format!("seq: invalid floating point argument: '{}'\n", operand);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 1;
+                    }
+                }
+            }
 
             let (first, incr, last) = match nums.len() {
                 0 => {
                     let _ = stderr.write_all(b"seq: missing operand\n").await;
                     return 1;
                 }
-                1 => (1, 1, nums[0]),
-                2 => (nums[0], 1, nums[1]),
-                _ => (nums[0], nums[1], nums[2]),
+                1 => (1.0, 1.0, nums[0]),
+                2 => (nums[0], 1.0, nums[1]),
+                3 => (nums[0], nums[1], nums[2]),
+                _ => {
+                    let msg = format!("seq: extra operand '{}'\n", operands[3]);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    return 1;
+                }
+            };
+
+            if incr == 0.0 {
+                let msg = format!("seq: invalid Zero increment value: '{}'\n", operands[1]);
+                let _ = stderr.write_all(msg.as_bytes()).await;
+                return 1;
+            }
+
+            // Precision follows the operands that shape each value, as in GNU seq
+            let precision = match operands.len() {
+                1 => 0,
+                2 => decimal_places(&operands[0]),
+                _ => decimal_places(&operands[0]).max(decimal_places(&operands[1])),
+            };
+            let width = if equal_width {
+                format_seq_number(first, precision, 0)
+                    .len()
+                    .max(format_seq_number(last, precision, 0).len())
+            } else {
+                0
             };
 
-            let mut current = first;
-            if incr > 0 {
-                while current <= last {
-                    let _ = stdout.write_all(format!("{}\n", current).as_bytes()).await;
-                    current += incr;
+            // Tolerate rounding error in the last step, e.g. `seq 0 0.1 0.3`
+            let slack = incr.abs() * 1e-9;
+            let mut out = String::new();
+            let mut n = 0u64;
+            loop {
+                let value = first + n as f64 * incr;
+                if (incr > 0.0 && value > last + slack) || (incr < 0.0 && value < last - slack) {
+                    break;
                 }
-            } else if incr < 0 {
-                while current >= last {
-                    let _ = stdout.write_all(format!("{}\n", current).as_bytes()).await;
-                    current += incr;
+                if n > 0 {
+                    out.push_str(&separator);
                 }
+                out.push_str(&format_seq_number(value, precision, width));
+                n += 1;
+
+                if out.len() >= 8192 {
+                    if stdout.write_all(out.as_bytes()).await.is_err() {
+                        return 0;
+                    }
+                    out.clear();
+                }
+            }
+            if n > 0 {
+                out.push('\n');
             }
+            let _ = stdout.write_all(out.as_bytes()).await;
             0
         })
     }
@@ -80,15 +257,7 @@ impl MiscCommands {
             }
 
             let secs: f64 = remaining[0].parse().unwrap_or(0.0);
-
-            // Convert seconds to nanoseconds for WASI Duration
-            let nanos = (secs * 1_000_000_000.0) as u64;
-
-            if nanos > 0 {
-                // Idiomatic WASI: subscribe to a duration pollable and block until ready
-                let pollable = monotonic_clock::subscribe_duration(nanos);
-                pollable.block();
-            }
+            sleep_seconds(secs);
 
             0
         })
@@ -97,46 +266,71 @@ impl MiscCommands {
     /// date - print the current date and time
     #[shell_command(
         name = "date",
-        usage = "date [+FORMAT]",
-        description = "Print the current date and time"
+        usage = "date [-u] [-d DATE] [+FORMAT]",
+        description = "Print the current date and time, or DATE, in FORMAT.\n\
+        -d DATE: Show DATE instead of now: @EPOCH or ISO-8601 (2024-01-31T12:00:00Z)\n\
+        -u: Show UTC (the sandbox has no time zones, so this is always UTC)\n\
+        FORMAT: strftime-style, e.g. +%Y-%m-%d, +%s, +%A"
     )]
     fn cmd_date(
         args: Vec<String>,
         _env: &ShellEnv,
         _stdin: piper::Reader,
         mut stdout: piper::Writer,
-        _stderr: piper::Writer,
+        mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
         Box::pin(async move {
-            let (_, _remaining) = parse_common(&args);
-            // Get current wall clock time from WASI
-            let datetime = wall_clock::now();
-
-            // Convert to human-readable format
-            // datetime.seconds is seconds since Unix epoch (1970-01-01 00:00:00 UTC)
-            let total_secs = datetime.seconds;
-
-            // Simple date/time calculation (UTC)
-            const SECS_PER_MIN: u64 = 60;
-            const SECS_PER_HOUR: u64 = 3600;
-            const SECS_PER_DAY: u64 = 86400;
-
-            let days_since_epoch = total_secs / SECS_PER_DAY;
-            let time_of_day = total_secs % SECS_PER_DAY;
-
-            let hours = time_of_day / SECS_PER_HOUR;
-            let minutes = (time_of_day % SECS_PER_HOUR) / SECS_PER_MIN;
-            let seconds = time_of_day % SECS_PER_MIN;
+            let (_, remaining) = parse_common(&args);
+            let mut date_spec: Option<String> = None;
+            let mut format = DEFAULT_DATE_FORMAT.to_string();
 
-            // Calculate year, month, day from days since epoch
-            // Using a simplified algorithm
-            let (year, month, day) = days_to_ymd(days_since_epoch);
+            let mut iter = remaining.iter();
+            while let Some(arg) = iter.next() {
+                match arg.as_str() {
+                    "-u" | "--utc" | "--universal" => {}
+                    "-d" | "--date" => match iter.next() {
+                        Some(spec) => date_spec = Some(spec.clone()),
+                        None => {
+                            let _ = stderr
+                                .write_all(b"date: option requires an argument -- 'd'\n")
+                                .await;
+                            return 1;
+                        }
+                    },
+                    s => {
+                        if let Some(spec) = s.strip_prefix("--date=") {
+                            date_spec = Some(spec.to_string());
+                        } else if let Some(fmt) = s.strip_prefix('+') {
+                            format = fmt.to_string();
+                        } else {
+                            let _ = stderr
+                                .write_all(format!("date: invalid option '{}'\n", s).as_bytes())
+                                .await;
+                            return 1;
+                        }
+                    }
+                }
+            }
 
-            let output = format!(
-                "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC\n",
-                year, month, day, hours, minutes, seconds
-            );
+            let (secs, nanos) = match date_spec.as_deref() {
+                None | Some("now") => {
+                    // Get current wall clock time from WASI
+                    let now = wall_clock::now();
+                    (now.seconds as i64, now.nanoseconds)
+                }
+                Some(spec) => match parse_date(spec) {
+                    Some(secs) => (secs, 0),
+                    None => {
+                        let _ = stderr
+                            .write_all(format!("date: invalid date '{}'\n", spec).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                },
+            };
 
+            let mut output = format_date(&format, secs, nanos);
+            output.push('\n');
             let _ = stdout.write_all(output.as_bytes()).await;
             0
         })
@@ -145,7 +339,7 @@ impl MiscCommands {
     /// curl - transfer data from URLs
     #[shell_command(
         name = "curl",
-        usage = "curl [-X METHOD] [-H HEADER] [-d DATA] [-o FILE] [-s] URL",
+        usage = "curl [-fiLsS] [-X METHOD] [-H HEADER]... [-d DATA]... [--data-binary DATA] [-o FILE] URL",
         description = "Transfer data from or to a server"
     )]
     fn cmd_curl(
@@ -158,121 +352,112 @@ impl MiscCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut method = "GET".to_string();
-            let mut headers: Vec<(String, String)> = Vec::new();
-            let mut data: Option<String> = None;
-            let mut output_file: Option<String> = None;
-            let mut silent = false;
-            let mut url: Option<String> = None;
-
-            // Manual argument parsing for complex options
-            let mut i = 0;
-            while i < remaining.len() {
-                let arg = &remaining[i];
-                match arg.as_str() {
-                    "-X" | "--request" => {
-                        i += 1;
-                        if i < remaining.len() {
-                            method = remaining[i].clone();
-                        }
-                    }
-                    "-H" | "--header" => {
-                        i += 1;
-                        if i < remaining.len() {
-                            let header = &remaining[i];
-                            if let Some(colon_pos) = header.find(':') {
-                                let name = header[..colon_pos].trim().to_string();
-                                let value = header[colon_pos + 1..].trim().to_string();
-                                headers.push((name, value));
-                            }
-                        }
-                    }
-                    "-d" | "--data" => {
-                        i += 1;
-                        if i < remaining.len() {
-                            data = Some(remaining[i].clone());
-                            if method == "GET" {
-                                method = "POST".to_string();
-                            }
-                        }
-                    }
-                    "-o" | "--output" => {
-                        i += 1;
-                        if i < remaining.len() {
-                            output_file = Some(remaining[i].clone());
-                        }
-                    }
-                    "-s" | "--silent" => {
-                        silent = true;
-                    }
-                    s if !s.starts_with('-') => {
-                        url = Some(s.to_string());
-                    }
-                    _ => {}
-                }
-                i += 1;
-            }
-
-            let url = match url {
-                Some(u) => u,
-                None => {
-                    let _ = stderr.write_all(b"curl: no URL specified\n").await;
-                    return 1;
+            let opts = match parse_curl_args(&remaining, &cwd) {
+                Ok(opts) => opts,
+                Err(e) => {
+                    let _ = stderr.write_all(format!("curl: {}\n", e).as_bytes()).await;
+                    return 2;
                 }
             };
+            let show_errors = !opts.silent || opts.show_error;
+            let Some(mut url) = opts.url.clone() else {
+                let _ = stderr.write_all(b"curl: no URL specified\n").await;
+                return 2;
+            };
 
-            // Build headers JSON
-            let headers_json = if headers.is_empty() {
-                None
-            } else {
-                let pairs: Vec<String> = headers
+            let mut headers: Vec<(&str, &str)> = opts
+                .headers
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.as_str()))
+                .collect();
+            if opts.data.is_some()
+                && !headers
                     .iter()
-                    .map(|(k, v)| format!("\"{}\":\"{}\"", k, v))
-                    .collect();
-                Some(format!("{{{}}}", pairs.join(",")))
-            };
+                    .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            {
+                headers.push(("Content-Type", "application/x-www-form-urlencoded"));
+            }
 
-            // Make HTTP request using our existing http_client
-            match crate::http_client::fetch_request(
-                &method,
-                &url,
-                headers_json.as_deref(),
-                data.as_deref(),
-            ) {
-                Ok(response) => {
-                    if let Some(out_path) = output_file {
-                        let path = resolve_path(&cwd, &out_path);
-                        if let Err(e) = std::fs::write(&path, &response.body()) {
-                            let msg = format!("curl: {}: {}\n", path, e);
-                            let _ = stderr.write_all(msg.as_bytes()).await;
-                            return 1;
-                        }
-                    } else {
-                        let _ = stdout.write_all(response.body().as_bytes()).await;
-                        if !response.body().ends_with('\n') {
-                            let _ = stdout.write_all(b"\n").await;
+            let mut method = opts.method().to_string();
+            let mut body = opts.data.as_deref();
+            let mut output: Vec<u8> = Vec::new();
+            let mut redirects = 0;
+            let response = loop {
+                let response = match crate::http_client::fetch(
+                    crate::http_client::parse_method(&method),
+                    &url,
+                    &headers,
+                    body,
+                ) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        if show_errors {
+                            let _ = stderr.write_all(format!("curl: {}\n", e).as_bytes()).await;
                         }
+                        return 1;
                     }
+                };
+                if opts.include {
+                    output.extend(format_curl_head(response.status, &response.headers).as_bytes());
+                }
 
-                    if !silent && !response.ok {
-                        let msg = format!("curl: HTTP {}\n", response.status);
-                        let _ = stderr.write_all(msg.as_bytes()).await;
+                let location = response
+                    .headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("location"))
+                    .map(|(_, v)| v.clone());
+                match location {
+                    Some(location)
+                        if opts.location && matches!(response.status, 301..=303 | 307 | 308) =>
+                    {
+                        redirects += 1;
+                        if redirects > CURL_MAX_REDIRECTS {
+                            if show_errors {
+                                let msg = format!(
+                                    "curl: (47) Maximum ({}) redirects followed\n",
+                                    CURL_MAX_REDIRECTS
+                                );
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                            }
+                            return 47;
+                        }
+                        // Like curl, a POST turns into a GET on 301/302/303
+                        // unless the method was set explicitly with -X
+                        if response.status <= 303 && opts.method.is_none() && body.is_some() {
+                            method = "GET".to_string();
+                            body = None;
+                        }
+                        url = resolve_curl_location(&url, &location);
                     }
+                    _ => break response,
+                }
+            };
 
-                    if response.ok {
-                        0
-                    } else {
-                        22
-                    } // curl uses 22 for HTTP errors
+            if opts.fail && response.status >= 400 {
+                if show_errors {
+                    let msg = format!(
+                        "curl: (22) The requested URL returned error: {}\n",
+                        response.status
+                    );
+                    let _ = stderr.write_all(msg.as_bytes()).await;
                 }
-                Err(e) => {
-                    if !silent {
-                        let msg = format!("curl: {}\n", e);
+                return 22;
+            }
+            output.extend(&response.bytes);
+
+            if let Some(out_path) = &opts.output {
+                let path = resolve_path(&cwd, out_path);
+                if let Err(e) = std::fs::write(&path, &output) {
+                    if show_errors {
+                        let msg = format!("curl: (23) {}: {}\n", out_path, e);
                         let _ = stderr.write_all(msg.as_bytes()).await;
                     }
-                    1
+                    return 23;
                 }
+            } else {
+                let _ = stdout.write_all(&output).await;
             }
+            0
         })
     }
 
@@ -434,42 +619,624 @@ impl MiscCommands {
     }
 }
 
-/// Convert days since Unix epoch to year, month, day
-fn days_to_ymd(days: u64) -> (i32, u32, u32) {
-    // Days since 1970-01-01
-    let mut remaining_days = days as i64;
-    let mut year = 1970i32;
+/// Output format of `date` without a +FORMAT argument
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
 
-    loop {
-        let days_in_year = if is_leap_year(year) { 366 } else { 365 };
-        if remaining_days < days_in_year {
-            break;
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Convert days since Unix epoch to year, month (1-12), day (1-31)
+pub(super) fn days_to_ymd(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's civil_from_days, valid before the epoch too
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Convert a year, month (1-12) and day (1-31) to days since Unix epoch
+pub(super) fn ymd_to_days(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Format a Unix timestamp (UTC) with strftime-style conversions.
+/// Unknown conversions are copied through unchanged.
+pub(super) fn format_date(format: &str, secs: i64, nanos: u32) -> String {
+    let days = secs.div_euclid(86_400);
+    let time_of_day = secs.rem_euclid(86_400);
+    let (year, month, day) = days_to_ymd(days);
+    let hour = time_of_day / 3600;
+    let minute = time_of_day % 3600 / 60;
+    let second = time_of_day % 60;
+    // 1970-01-01 was a Thursday
+    let weekday = (days + 4).rem_euclid(7) as usize;
+    let yday = days - ymd_to_days(year, 1, 1);
+    let hour12 = if hour % 12 == 0 { 12 } else { hour % 12 };
+    let weekday_name = WEEKDAYS[weekday];
+    let month_name = MONTHS[month as usize - 1];
+
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
         }
-        remaining_days -= days_in_year;
-        year += 1;
+        let Some(conv) = chars.next() else {
+            out.push('%');
+            break;
+        };
+        let piece = match conv {
+            'Y' => year.to_string(),
+            'C' => format!("{:02}", year.div_euclid(100)),
+            'y' => format!("{:02}", year.rem_euclid(100)),
+            'm' => format!("{:02}", month),
+            'd' => format!("{:02}", day),
+            'e' => format!("{:2}", day),
+            'H' => format!("{:02}", hour),
+            'k' => format!("{:2}", hour),
+            'I' => format!("{:02}", hour12),
+            'l' => format!("{:2}", hour12),
+            'M' => format!("{:02}", minute),
+            'S' => format!("{:02}", second),
+            'N' => format!("{:09}", nanos),
+            'p' => (if hour < 12 { "AM" } else { "PM" }).to_string(),
+            'P' => (if hour < 12 { "am" } else { "pm" }).to_string(),
+            'j' => format!("{:03}", yday + 1),
+            'a' => weekday_name[..3].to_string(),
+            'A' => weekday_name.to_string(),
+            'b' | 'h' => month_name[..3].to_string(),
+            'B' => month_name.to_string(),
+            'u' => (if weekday == 0 { 7 } else { weekday }).to_string(),
+            'w' => weekday.to_string(),
+            // Week of the year, starting on Sunday / Monday
+            'U' => format!("{:02}", (yday + 7 - weekday as i64) / 7),
+            'W' => format!("{:02}", (yday + 7 - (weekday as i64 + 6) % 7) / 7),
+            's' => secs.to_string(),
+            'Z' => "UTC".to_string(),
+            'z' => "+0000".to_string(),
+            'F' => format!("{}-{:02}-{:02}", year, month, day),
+            'T' => format!("{:02}:{:02}:{:02}", hour, minute, second),
+            'R' => format!("{:02}:{:02}", hour, minute),
+            'D' => format!("{:02}/{:02}/{:02}", month, day, year.rem_euclid(100)),
+            'c' => format!(
+                "{} {} {:2} {:02}:{:02}:{:02} {}",
+                &weekday_name[..3],
+                &month_name[..3],
+                day,
+                hour,
+                minute,
+                second,
+                year
+            ),
+            'n' => "\n".to_string(),
+            't' => "\t".to_string(),
+            '%' => "%".to_string(),
+            other => format!("%{}", other),
+        };
+        out.push_str(&piece);
+    }
+    out
+}
+
+/// Parse a `date -d` argument into a Unix timestamp: `@EPOCH`, or
+/// ISO-8601 `YYYY-MM-DD[(T| )HH:MM[:SS[.frac]]][Z|UTC|±HH[:MM]]`
+pub(super) fn parse_date(spec: &str) -> Option<i64> {
+    let spec = spec.trim();
+    if let Some(epoch) = spec.strip_prefix('@') {
+        return epoch.parse().ok();
+    }
+
+    let mut parts = spec.get(..10)?.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut secs = ymd_to_days(year, month, day) * 86_400;
+
+    let rest = spec[10..].trim_start_matches(['T', ' ']);
+    if rest.is_empty() {
+        return Some(secs);
     }
 
-    let leap = is_leap_year(year);
-    let days_in_months: [i64; 12] = if leap {
-        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    } else {
-        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    // Split the time of day from a trailing zone designator
+    let zone_start = rest.find(['Z', 'U', '+', '-', ' ']).unwrap_or(rest.len());
+    let (time, zone) = rest.split_at(zone_start);
+    let mut fields = time.split(':');
+    let hour: i64 = fields.next()?.parse().ok()?;
+    let minute: i64 = fields.next()?.parse().ok()?;
+    let second: i64 = match fields.next() {
+        Some(field) => field.split('.').next()?.parse().ok()?,
+        None => 0,
     };
+    if fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    secs += hour * 3600 + minute * 60 + second;
 
-    let mut month = 1u32;
-    for days_in_month in days_in_months.iter() {
-        if remaining_days < *days_in_month {
-            break;
+    let zone = zone.trim();
+    let offset = match zone {
+        "" | "Z" | "UTC" => 0,
+        _ => {
+            let sign = match zone.chars().next()? {
+                '+' => 1,
+                '-' => -1,
+                _ => return None,
+            };
+            let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+            if !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let hours: i64 = digits[..2].parse().ok()?;
+            let minutes: i64 = if digits.len() == 4 {
+                digits[2..].parse().ok()?
+            } else {
+                0
+            };
+            sign * (hours * 3600 + minutes * 60)
         }
-        remaining_days -= days_in_month;
-        month += 1;
+    };
+    Some(secs - offset)
+}
+
+/// Digits after the decimal point in a `seq` operand
+fn decimal_places(operand: &str) -> usize {
+    let mantissa = operand.split(['e', 'E']).next().unwrap_or(operand);
+    mantissa.split_once('.').map_or(0, |(_, frac)| frac.len())
+}
+
+/// Format a `seq` value with `precision` decimals, zero-padded to `width`
+fn format_seq_number(value: f64, precision: usize, width: usize) -> String {
+    // Adding 0.0 turns -0.0 into 0.0 so it doesn't print as "-0"
+    let formatted = format!("{:.*}", precision, value + 0.0);
+    if formatted.len() >= width {
+        return formatted;
+    }
+    let zeros = "0".repeat(width - formatted.len());
+    match formatted.strip_prefix('-') {
+        Some(digits) => format!("-{}{}", zeros, digits),
+        None => format!("{}{}", zeros, formatted),
     }
+}
 
-    let day = (remaining_days + 1) as u32;
+/// Redirects `curl -L` follows before giving up (curl's default)
+const CURL_MAX_REDIRECTS: usize = 50;
 
-    (year, month, day)
+/// A parsed `curl` command line.
+#[derive(Debug, Default, PartialEq)]
+struct CurlOptions {
+    /// Method from -X, if given
+    method: Option<String>,
+    headers: Vec<(String, String)>,
+    /// Request body from -d/--data-binary
+    data: Option<Vec<u8>>,
+    output: Option<String>,
+    silent: bool,
+    show_error: bool,
+    include: bool,
+    fail: bool,
+    location: bool,
+    url: Option<String>,
+}
+
+impl CurlOptions {
+    /// -X wins; otherwise a request body implies POST
+    fn method(&self) -> &str {
+        match (&self.method, &self.data) {
+            (Some(method), _) => method,
+            (None, Some(_)) => "POST",
+            (None, None) => "GET",
+        }
+    }
+
+    /// Append one -d/--data-binary value; repeated values are joined with `&`
+    fn add_data(&mut self, value: &[u8]) {
+        let data = self.data.get_or_insert_with(Vec::new);
+        if !data.is_empty() {
+            data.push(b'&');
+        }
+        data.extend_from_slice(value);
+    }
 }
 
-fn is_leap_year(year: i32) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || (year % 400 == 0)
+/// Parse curl arguments. Short flags may be bundled (`-fsS`) and short
+/// options may take their value attached (`-XPUT`); long options accept
+/// `--name=value`. `-d @file` reads the file without newlines, while
+/// `--data-binary @file` sends it unchanged.
+fn parse_curl_args(args: &[String], cwd: &str) -> Result<CurlOptions, String> {
+    fn read_data(value: &str, cwd: &str, binary: bool) -> Result<Vec<u8>, String> {
+        let Some(file) = value.strip_prefix('@') else {
+            return Ok(value.as_bytes().to_vec());
+        };
+        let mut bytes = std::fs::read(resolve_path(cwd, file))
+            .map_err(|e| format!("Failed to open {}: {}", file, e))?;
+        if !binary {
+            bytes.retain(|b| *b != b'\n' && *b != b'\r');
+        }
+        Ok(bytes)
+    }
+
+    let mut opts = CurlOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        i += 1;
+
+        // Normalize to (option name, attached value)
+        let (name, attached): (String, Option<String>) = if let Some(long) = arg.strip_prefix("--")
+        {
+            match long.split_once('=') {
+                Some((name, value)) => (format!("--{}", name), Some(value.to_string())),
+                None => (arg.clone(), None),
+            }
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            let mut flags = arg[1..].char_indices();
+            let mut option = None;
+            for (idx, c) in flags.by_ref() {
+                match c {
+                    's' => opts.silent = true,
+                    'S' => opts.show_error = true,
+                    'i' => opts.include = true,
+                    'f' => opts.fail = true,
+                    'L' => opts.location = true,
+                    'X' | 'H' | 'd' | 'o' => {
+                        let rest = &arg[2 + idx..];
+                        let value = (!rest.is_empty()).then(|| rest.to_string());
+                        option = Some((format!("-{}", c), value));
+                        break;
+                    }
+                    _ => return Err(format!("option -{}: is unknown", c)),
+                }
+            }
+            match option {
+                Some(option) => option,
+                None => continue,
+            }
+        } else {
+            if opts.url.is_some() {
+                return Err("only one URL is supported".to_string());
+            }
+            opts.url = Some(arg.clone());
+            continue;
+        };
+
+        match name.as_str() {
+            "--silent" => opts.silent = true,
+            "--show-error" => opts.show_error = true,
+            "--include" => opts.include = true,
+            "--fail" => opts.fail = true,
+            "--location" => opts.location = true,
+            "-X" | "--request" | "-H" | "--header" | "-d" | "--data" | "--data-ascii"
+            | "--data-raw" | "--data-binary" | "-o" | "--output" => {
+                let value = match attached {
+                    Some(value) => value,
+                    None => {
+                        let value = args
+                            .get(i)
+                            .ok_or_else(|| format!("option {}: requires parameter", name))?;
+                        i += 1;
+                        value.clone()
+                    }
+                };
+                match name.as_str() {
+                    "-X" | "--request" => opts.method = Some(value),
+                    "-H" | "--header" => {
+                        if let Some((k, v)) = value.split_once(':') {
+                            opts.headers
+                                .push((k.trim().to_string(), v.trim().to_string()));
+                        }
+                    }
+                    "-d" | "--data" | "--data-ascii" => {
+                        let data = read_data(&value, cwd, false)?;
+                        opts.add_data(&data);
+                    }
+                    "--data-raw" => opts.add_data(value.as_bytes()),
+                    "--data-binary" => {
+                        let data = read_data(&value, cwd, true)?;
+                        opts.add_data(&data);
+                    }
+                    _ => opts.output = Some(value),
+                }
+            }
+            _ => return Err(format!("option {}: is unknown", name)),
+        }
+    }
+    Ok(opts)
+}
+
+/// Status line and headers as `curl -i` prints them
+fn format_curl_head(status: u16, headers: &[(String, String)]) -> String {
+    let mut head = format!("HTTP/1.1 {}\r\n", status);
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    head
+}
+
+/// Resolve a redirect `Location` against the URL that returned it
+fn resolve_curl_location(base: &str, location: &str) -> String {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        return location.to_string();
+    }
+    let (scheme, rest) = base.split_once("://").unwrap_or(("http", base));
+    if let Some(authority_relative) = location.strip_prefix("//") {
+        return format!("{}://{}", scheme, authority_relative);
+    }
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, "/"),
+    };
+    if location.starts_with('/') {
+        return format!("{}://{}{}", scheme, authority, location);
+    }
+    // Relative to the directory of the current path (ignoring any query)
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let dir = &path[..path.rfind('/').map_or(0, |idx| idx + 1)];
+    format!("{}://{}{}{}", scheme, authority, dir, location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shell::run_pipeline;
+
+    fn date(args: &str) -> (i32, String) {
+        let mut env = ShellEnv::new();
+        let result =
+            futures_lite::future::block_on(run_pipeline(&format!("date {}", args), &mut env));
+        (result.code, result.stdout)
+    }
+
+    #[test]
+    fn test_date_from_epoch() {
+        assert_eq!(date("-d @0 -u +%Y-%m-%d"), (0, "1970-01-01\n".to_string()));
+        assert_eq!(
+            date("-d @1700000000"),
+            (0, "2023-11-14 22:13:20 UTC\n".to_string())
+        );
+        assert_eq!(
+            date("-d @-1 '+%F %T'"),
+            (0, "1969-12-31 23:59:59\n".to_string())
+        );
+        assert_eq!(date("-d nonsense").0, 1);
+    }
+
+    #[test]
+    fn test_date_from_iso8601() {
+        assert_eq!(date("-d 2024-02-29 +%s"), (0, "1709164800\n".to_string()));
+        assert_eq!(
+            date("--date=2024-02-29T12:30:00+02:00 +%s"),
+            (0, "1709202600\n".to_string())
+        );
+        assert_eq!(
+            date("-d '2023-11-14 22:13:20 UTC' +%s"),
+            (0, "1700000000\n".to_string())
+        );
+        assert_eq!(date("-d 2024-13-01").0, 1);
+    }
+
+    #[test]
+    fn test_format_date_conversions() {
+        assert_eq!(
+            format_date(
+                "%a %A %b %B %j %U %W %u %w %e %I %p %y %C",
+                1_700_000_000,
+                0
+            ),
+            "Tue Tuesday Nov November 318 46 46 2 2 14 10 PM 23 20"
+        );
+        assert_eq!(
+            format_date("%D %R %%", 1_700_000_000, 0),
+            "11/14/23 22:13 %"
+        );
+        assert_eq!(format_date("%j %a", -86_400 * 365, 0), "001 Wed");
+    }
+
+    fn seq(args: &str) -> (i32, String) {
+        let mut env = ShellEnv::new();
+        let result =
+            futures_lite::future::block_on(run_pipeline(&format!("seq {}", args), &mut env));
+        (result.code, result.stdout)
+    }
+
+    #[test]
+    fn test_seq_ascending() {
+        assert_eq!(seq("3"), (0, "1\n2\n3\n".to_string()));
+        assert_eq!(seq("2 4"), (0, "2\n3\n4\n".to_string()));
+        assert_eq!(seq("1 2 6"), (0, "1\n3\n5\n".to_string()));
+        assert_eq!(
+            seq("0 0.25 1"),
+            (0, "0.00\n0.25\n0.50\n0.75\n1.00\n".to_string())
+        );
+        assert_eq!(seq("0 0.1 0.3"), (0, "0.0\n0.1\n0.2\n0.3\n".to_string()));
+        assert_eq!(seq("5 1"), (0, String::new()));
+    }
+
+    #[test]
+    fn test_seq_descending() {
+        assert_eq!(seq("3 -1 1"), (0, "3\n2\n1\n".to_string()));
+        assert_eq!(seq("1 -1 -1"), (0, "1\n0\n-1\n".to_string()));
+        assert_eq!(seq("1 -0.5 0"), (0, "1.0\n0.5\n0.0\n".to_string()));
+    }
+
+    #[test]
+    fn test_seq_separator_and_width() {
+        assert_eq!(seq("-s , 3"), (0, "1,2,3\n".to_string()));
+        assert_eq!(seq("-s: 1 3"), (0, "1:2:3\n".to_string()));
+        assert_eq!(seq("-w 8 10"), (0, "08\n09\n10\n".to_string()));
+        assert_eq!(seq("-w -1 1"), (0, "-1\n00\n01\n".to_string()));
+    }
+
+    #[test]
+    fn test_seq_rejects_bad_operands() {
+        assert_eq!(seq("1 0 5").0, 1);
+        assert_eq!(seq("abc").0, 1);
+        assert_eq!(seq("").0, 1);
+    }
+
+    #[test]
+    fn test_retry_succeeds_after_failures() {
+        let mut calls = 0;
+        let (code, log) = futures_lite::future::block_on(run_with_retries(3, 0.0, true, || {
+            calls += 1;
+            let status = if calls < 3 { 1 } else { 0 };
+            async move { (status, format!("try {}\n", calls).into_bytes(), Vec::new()) }
+        }));
+        assert_eq!(code, 0);
+        assert_eq!(calls, 3);
+        assert_eq!(log.len(), 3);
+        let stderr: Vec<u8> = log.into_iter().flat_map(|(_, err)| err).collect();
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            "retry: attempt 1/4 failed with exit code 1, retrying in 0s\n\
+             retry: attempt 2/4 failed with exit code 1, retrying in 0s\n"
+        );
+    }
+
+    fn curl_args(args: &[&str], cwd: &str) -> Result<CurlOptions, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        parse_curl_args(&args, cwd)
+    }
+
+    #[test]
+    fn test_curl_default_is_plain_get() {
+        let opts = curl_args(&["https://example.com/x"], "/").unwrap();
+        assert_eq!(opts.method(), "GET");
+        assert_eq!(opts.url.as_deref(), Some("https://example.com/x"));
+        assert!(opts.data.is_none() && opts.headers.is_empty());
+        assert!(!opts.fail && !opts.include && !opts.silent);
+    }
+
+    #[test]
+    fn test_curl_parses_method_headers_and_data() {
+        let opts = curl_args(
+            &[
+                "-fsSiL",
+                "-XPUT",
+                "-H",
+                "Authorization: Bearer t:k",
+                "--header=Accept: application/json",
+                "-d",
+                "a=1",
+                "--data",
+                "b=2",
+                "-o",
+                "out.json",
+                "http://localhost:8080/api",
+            ],
+            "/",
+        )
+        .unwrap();
+        assert!(opts.fail && opts.silent && opts.show_error && opts.include && opts.location);
+        assert_eq!(opts.method(), "PUT");
+        assert_eq!(
+            opts.headers,
+            vec![
+                ("Authorization".to_string(), "Bearer t:k".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]
+        );
+        assert_eq!(opts.data.as_deref(), Some(&b"a=1&b=2"[..]));
+        assert_eq!(opts.output.as_deref(), Some("out.json"));
+
+        // A body without -X implies POST
+        let opts = curl_args(&["-d", "{}", "http://localhost/"], "/").unwrap();
+        assert_eq!(opts.method(), "POST");
+    }
+
+    #[test]
+    fn test_curl_reads_data_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "curl-data-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or(0)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("body.txt"), "line1\nline2\n").unwrap();
+        let cwd = dir.to_string_lossy().to_string();
+
+        let opts = curl_args(&["--data-binary", "@body.txt", "http://h/"], &cwd).unwrap();
+        assert_eq!(opts.data.as_deref(), Some(&b"line1\nline2\n"[..]));
+        let opts = curl_args(&["-d", "@body.txt", "http://h/"], &cwd).unwrap();
+        assert_eq!(opts.data.as_deref(), Some(&b"line1line2"[..]));
+        assert!(curl_args(&["-d", "@missing.txt", "http://h/"], &cwd).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_curl_rejects_bad_arguments() {
+        assert!(
+            curl_args(&["http://h/", "-H"], "/").is_err_and(|e| e.contains("requires parameter"))
+        );
+        assert!(curl_args(&["-Z", "http://h/"], "/").is_err_and(|e| e.contains("unknown")));
+        assert!(curl_args(&["--bogus", "http://h/"], "/").is_err());
+        assert!(curl_args(&["http://a/", "http://b/"], "/").is_err());
+    }
+
+    #[test]
+    fn test_curl_include_head_and_redirect_locations() {
+        let headers = vec![("content-type".to_string(), "text/plain".to_string())];
+        assert_eq!(
+            format_curl_head(404, &headers),
+            "HTTP/1.1 404\r\ncontent-type: text/plain\r\n\r\n"
+        );
+
+        let base = "https://example.com/a/b?q=1";
+        assert_eq!(
+            resolve_curl_location(base, "http://other/x"),
+            "http://other/x"
+        );
+        assert_eq!(
+            resolve_curl_location(base, "//cdn.test/y"),
+            "https://cdn.test/y"
+        );
+        assert_eq!(
+            resolve_curl_location(base, "/root"),
+            "https://example.com/root"
+        );
+        assert_eq!(resolve_curl_location(base, "c"), "https://example.com/a/c");
+        assert_eq!(
+            resolve_curl_location("http://host", "next"),
+            "http://host/next"
+        );
+    }
 }
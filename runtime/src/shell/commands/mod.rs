@@ -5,11 +5,14 @@
 //!
 //! Uses `lexopt` for minimal argument parsing in the busybox style.
 
+mod awk;
 mod core;
 mod encoding;
 mod env;
 mod file;
+mod gitignore;
 pub mod helpers;
+mod jq;
 mod json;
 mod misc;
 mod path;
@@ -41,6 +44,11 @@ pub use self::test::TestCommands;
 pub use self::text::TextCommands;
 pub use self::util::UtilCommands;
 
+// Behind the executor's `dotenv run`, `find -exec` and `retry` builtins
+pub(crate) use self::env::parse_dotenv_run;
+pub(crate) use self::file::{find_run, find_runs_commands};
+pub(crate) use self::misc::run_with_retries;
+
 // TsxCommands moved to tsx-engine module (lazy-loaded)
 #[cfg(feature = "archive")]
 pub use self::archive::ArchiveCommands;
@@ -1,23 +1,24 @@
-//! String manipulation commands: expr, awk, paste, rev, fold
+//! String manipulation commands: expr, awk, paste, rev, fold, tmpl
 
 use futures_lite::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use futures_lite::StreamExt;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
+use super::awk::{awk_unescape, parse_awk_program, AwkInterp, AwkValue};
 use super::helpers::resolve_path;
 use super::parse_common;
 
 /// Get a random u64 - uses WASI in production, std in tests
 #[cfg(not(test))]
-fn get_random_u64() -> u64 {
+pub(super) fn get_random_u64() -> u64 {
     use crate::bindings::wasi::random::random as wasi_random;
     wasi_random::get_random_u64()
 }
 
 /// Get a random u64 - uses time-based entropy for native tests
 #[cfg(test)]
-fn get_random_u64() -> u64 {
+pub(super) fn get_random_u64() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     let duration = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -70,11 +71,15 @@ impl StringCommands {
         })
     }
 
-    /// awk - pattern scanning (simplified)
+    /// awk - pattern scanning and processing
     #[shell_command(
         name = "awk",
-        usage = "awk [-F sep] 'pattern { action }' [file...]",
-        description = "Pattern scanning and processing (simplified)"
+        usage = "awk [-F sep] [-v var=value]... 'program' [file...]",
+        description = "Pattern scanning and processing language (POSIX subset).\n\
+        BEGIN/END blocks, /regex/ and expression patterns, ranges (p1, p2),\n\
+        fields $0 $1..$NF, NF NR FNR FS OFS ORS, arrays, if/while/for/next/exit,\n\
+        print, printf, length substr index split sub gsub match sprintf\n\
+        tolower toupper int sqrt exp log sin cos atan2 rand srand"
     )]
     fn cmd_awk(
         args: Vec<String>,
@@ -86,128 +91,185 @@ impl StringCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            // Parse options
-            let mut field_sep = " \t".to_string();
-            let mut program = String::new();
+            let mut field_sep: Option<String> = None;
+            let mut assignments: Vec<String> = Vec::new();
+            let mut program: Option<String> = None;
             let mut files: Vec<String> = Vec::new();
-            let mut i = 0;
 
+            // Options come before the program; everything after it is a file
+            let mut i = 0;
             while i < remaining.len() {
-                if remaining[i] == "-F" && i + 1 < remaining.len() {
-                    field_sep = remaining[i + 1].clone();
-                    i += 2;
-                } else if remaining[i].starts_with("-F") {
-                    field_sep = remaining[i][2..].to_string();
-                    i += 1;
-                } else if program.is_empty() {
-                    program = remaining[i].clone();
+                let arg = remaining[i].clone();
+                i += 1;
+                if program.is_some() {
+                    files.push(arg);
+                    continue;
+                }
+                if arg == "--" {
+                    if let Some(p) = remaining.get(i) {
+                        program = Some(p.clone());
+                        i += 1;
+                    }
+                    continue;
+                }
+                let (flag, inline) = match arg.get(..2) {
+                    Some(flag @ ("-F" | "-v" | "-f")) => (flag.to_string(), &arg[2..]),
+                    _ if arg.starts_with('-') && arg != "-" => {
+                        let msg = format!("awk: unknown option {}\n", arg);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 2;
+                    }
+                    _ => {
+                        program = Some(arg);
+                        continue;
+                    }
+                };
+                let value = if !inline.is_empty() {
+                    inline.to_string()
+                } else if let Some(v) = remaining.get(i) {
                     i += 1;
+                    v.clone()
                 } else {
-                    files.push(remaining[i].clone());
-                    i += 1;
+                    let msg = format!("awk: option {} requires an argument\n", flag);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    return 2;
+                };
+                match flag.as_str() {
+                    "-F" => field_sep = Some(value),
+                    "-v" => assignments.push(value),
+                    _ => match std::fs::read_to_string(resolve_path(&cwd, &value)) {
+                        Ok(source) => program = Some(source),
+                        Err(e) => {
+                            let msg = format!("awk: {}: {}\n", value, e);
+                            let _ = stderr.write_all(msg.as_bytes()).await;
+                            return 2;
+                        }
+                    },
                 }
             }
 
-            if program.is_empty() {
+            let Some(program) = program else {
                 let _ = stderr.write_all(b"awk: missing program\n").await;
-                return 1;
-            }
-
-            // Parse the awk program
+                return 2;
+            };
             let parsed = match parse_awk_program(&program) {
                 Ok(p) => p,
                 Err(e) => {
                     let _ = stderr.write_all(format!("awk: {}\n", e).as_bytes()).await;
-                    return 1;
+                    return 2;
                 }
             };
 
-            let mut nr = 0;
-            let mut accumulator: f64 = 0.0;
+            let mut interp = AwkInterp::new(&parsed);
+            if let Some(fs) = field_sep {
+                interp.set_var("FS", AwkValue::Str(awk_unescape(&fs)));
+            }
+            for assignment in &assignments {
+                match assignment.split_once('=') {
+                    Some((name, value)) => {
+                        interp.set_var(name, AwkValue::StrNum(awk_unescape(value)));
+                    }
+                    None => {
+                        let msg = format!("awk: invalid -v argument: {}\n", assignment);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        return 2;
+                    }
+                }
+            }
 
-            // Helper to process a line
-            async fn write_awk_line(
-                parsed: &AwkProgram,
+            /// Run one record and write what it printed
+            async fn feed_record(
+                interp: &mut AwkInterp<'_>,
                 line: &str,
-                field_sep: &str,
-                nr: usize,
                 stdout: &mut piper::Writer,
-                accumulator: &mut f64,
-            ) -> Result<(), std::io::Error> {
-                let fields: Vec<&str> = if field_sep == " \t" {
-                    line.split_whitespace().collect()
-                } else {
-                    line.split(field_sep).collect()
-                };
+            ) -> Result<bool, String> {
+                let result = interp.run_record(line);
+                let _ = stdout.write_all(interp.take_output().as_bytes()).await;
+                result
+            }
 
-                let output = execute_awk_action(parsed, line, &fields, nr, accumulator);
-                if !output.is_empty() {
-                    stdout.write_all(output.as_bytes()).await?;
-                    if !output.ends_with('\n') {
-                        stdout.write_all(b"\n").await?;
-                    }
+            let mut status = 0;
+            let mut exited = match interp.run_begin() {
+                Ok(exited) => exited,
+                Err(e) => {
+                    let _ = stdout.write_all(interp.take_output().as_bytes()).await;
+                    let _ = stderr.write_all(format!("awk: {}\n", e).as_bytes()).await;
+                    return 2;
                 }
-                Ok(())
-            }
+            };
+            let _ = stdout.write_all(interp.take_output().as_bytes()).await;
 
-            if files.is_empty() {
-                // Read from stdin
-                let reader = BufReader::new(stdin);
-                let mut lines = reader.lines();
-                while let Some(Ok(line)) = lines.next().await {
-                    nr += 1;
-                    let _ = write_awk_line(
-                        &parsed,
-                        &line,
-                        &field_sep,
-                        nr,
-                        &mut stdout,
-                        &mut accumulator,
-                    )
-                    .await;
+            if !exited && parsed.reads_input() {
+                if files.is_empty() {
+                    files.push("-".to_string());
                 }
-            } else {
+                let mut stdin = Some(stdin);
                 for file in &files {
-                    let path = resolve_path(&cwd, &file);
+                    if exited {
+                        break;
+                    }
+                    if file == "-" {
+                        interp.start_file("");
+                        let Some(reader) = stdin.take() else {
+                            continue;
+                        };
+                        let mut lines = BufReader::new(reader).lines();
+                        while let Some(Ok(line)) = lines.next().await {
+                            match feed_record(&mut interp, &line, &mut stdout).await {
+                                Ok(false) => {}
+                                Ok(true) => {
+                                    exited = true;
+                                    break;
+                                }
+                                Err(e) => {
+                                    let msg = format!("awk: {}\n", e);
+                                    let _ = stderr.write_all(msg.as_bytes()).await;
+                                    return 2;
+                                }
+                            }
+                        }
+                        continue;
+                    }
 
-                    match std::fs::read_to_string(&path) {
+                    interp.start_file(file);
+                    match std::fs::read_to_string(resolve_path(&cwd, file)) {
                         Ok(content) => {
                             for line in content.lines() {
-                                nr += 1;
-                                let _ = write_awk_line(
-                                    &parsed,
-                                    line,
-                                    &field_sep,
-                                    nr,
-                                    &mut stdout,
-                                    &mut accumulator,
-                                )
-                                .await;
+                                match feed_record(&mut interp, line, &mut stdout).await {
+                                    Ok(false) => {}
+                                    Ok(true) => {
+                                        exited = true;
+                                        break;
+                                    }
+                                    Err(e) => {
+                                        let msg = format!("awk: {}\n", e);
+                                        let _ = stderr.write_all(msg.as_bytes()).await;
+                                        return 2;
+                                    }
+                                }
                             }
                         }
                         Err(e) => {
-                            let _ = stderr
-                                .write_all(format!("awk: {}: {}\n", file, e).as_bytes())
-                                .await;
-                            return 1;
+                            let msg = format!("awk: {}: {}\n", file, e);
+                            let _ = stderr.write_all(msg.as_bytes()).await;
+                            status = 2;
                         }
                     }
                 }
             }
 
-            // Handle END block
-            if let Some(ref end_expr) = parsed.end_action {
-                // Parse and evaluate END expression
-                let output = evaluate_awk_end_expr(end_expr, accumulator);
-                if !output.is_empty() {
-                    let _ = stdout.write_all(output.as_bytes()).await;
-                    if !output.ends_with('\n') {
-                        let _ = stdout.write_all(b"\n").await;
-                    }
-                }
+            // END runs even after `exit` in BEGIN or a rule
+            let result = interp.run_end();
+            let _ = stdout.write_all(interp.take_output().as_bytes()).await;
+            if let Err(e) = result {
+                let _ = stderr.write_all(format!("awk: {}\n", e).as_bytes()).await;
+                return 2;
+            }
+            if interp.exit_code != 0 {
+                interp.exit_code
+            } else {
+                status
             }
-
-            0
         })
     }
 
@@ -711,6 +773,120 @@ impl StringCommands {
             0
         })
     }
+
+    /// tmpl - render a mustache-style template
+    #[shell_command(
+        name = "tmpl",
+        usage = "tmpl [--data FILE] [--allow-missing] [TEMPLATE] [KEY=VALUE...]",
+        description = "Render {{var}}, {{#each}} and {{#if}} templates"
+    )]
+    fn cmd_tmpl(
+        args: Vec<String>,
+        env: &ShellEnv,
+        stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.to_string_lossy().to_string();
+        Box::pin(async move {
+            let (_, remaining) = parse_common(&args);
+            let mut data_file: Option<String> = None;
+            let mut allow_missing = false;
+            let mut template_file: Option<String> = None;
+            let mut vars: Vec<(String, String)> = Vec::new();
+            let mut i = 0;
+
+            while i < remaining.len() {
+                let arg = &remaining[i];
+                if arg == "--data" && i + 1 < remaining.len() {
+                    data_file = Some(remaining[i + 1].clone());
+                    i += 2;
+                    continue;
+                }
+                if let Some(file) = arg.strip_prefix("--data=") {
+                    data_file = Some(file.to_string());
+                } else if arg == "--allow-missing" {
+                    allow_missing = true;
+                } else if let Some((key, value)) = arg.split_once('=') {
+                    vars.push((key.to_string(), value.to_string()));
+                } else if template_file.is_none() {
+                    template_file = Some(arg.clone());
+                } else {
+                    let _ = stderr
+                        .write_all(format!("tmpl: unexpected argument: {}\n", arg).as_bytes())
+                        .await;
+                    return 1;
+                }
+                i += 1;
+            }
+
+            let mut context = serde_json::Map::new();
+            if let Some(file) = &data_file {
+                let path = resolve_path(&cwd, file);
+                let parsed = std::fs::read_to_string(&path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|content| {
+                        serde_json::from_str::<serde_json::Value>(&content)
+                            .map_err(|e| e.to_string())
+                    });
+                match parsed {
+                    Ok(serde_json::Value::Object(map)) => context = map,
+                    Ok(_) => {
+                        let _ = stderr
+                            .write_all(
+                                format!("tmpl: {}: data must be a JSON object\n", file).as_bytes(),
+                            )
+                            .await;
+                        return 1;
+                    }
+                    Err(e) => {
+                        let _ = stderr
+                            .write_all(format!("tmpl: {}: {}\n", file, e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                }
+            }
+            // KEY=VALUE arguments override values from --data
+            for (key, value) in vars {
+                context.insert(key, serde_json::Value::String(value));
+            }
+
+            let template = if let Some(file) = &template_file {
+                let path = resolve_path(&cwd, file);
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        let _ = stderr
+                            .write_all(format!("tmpl: {}: {}\n", file, e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                }
+            } else {
+                let mut content = String::new();
+                let reader = BufReader::new(stdin);
+                let mut lines = reader.lines();
+                while let Some(Ok(line)) = lines.next().await {
+                    content.push_str(&line);
+                    content.push('\n');
+                }
+                content
+            };
+
+            let context = serde_json::Value::Object(context);
+            match render_template(&template, &context, allow_missing) {
+                Ok(output) => {
+                    let _ = stdout.write_all(output.as_bytes()).await;
+                    0
+                }
+                Err(e) => {
+                    let _ = stderr.write_all(format!("tmpl: {}\n", e).as_bytes()).await;
+                    1
+                }
+            }
+        })
+    }
 }
 
 // ============================================================================
@@ -878,419 +1054,173 @@ fn apply_expr_op(op: &str, left: &str, right: &str) -> Result<String, String> {
 }
 
 // ============================================================================
-// Simplified awk implementation
+// tmpl renderer (mustache subset)
 // ============================================================================
 
-/// Parsed awk program
-/// Represents a field reference: fixed number, or NF (last field)
-#[derive(Clone, Debug)]
-enum AwkFieldRef {
-    Fixed(i32),
-    NF, // $NF — last field
-}
-
-/// Represents what an awk rule prints
-#[derive(Clone, Debug)]
-enum AwkAction {
-    PrintFields(Vec<AwkFieldRef>),
-    PrintNF, // print NF (field count, not $NF)
-    PrintLiteral(String),
-    Accumulate(String, AwkFieldRef), // var += $field
-    Nothing,
-}
-
-/// Represents a condition for an awk rule
-#[derive(Clone, Debug)]
-enum AwkCondition {
-    Always,
-    Pattern(String), // /regex/
-    FieldCompare {
-        // $N op value
-        field: AwkFieldRef,
-        op: String,
-        value: f64,
-    },
-}
-
-/// A single awk rule: condition + action
-#[derive(Clone, Debug)]
-struct AwkRule {
-    condition: AwkCondition,
-    action: AwkAction,
+/// Parsed template node
+#[derive(Debug, PartialEq)]
+enum TmplNode {
+    Text(String),
+    Var(String),
+    Each(String, Vec<TmplNode>),
+    If(String, Vec<TmplNode>, Vec<TmplNode>),
 }
 
-struct AwkProgram {
-    #[allow(dead_code)]
-    begin_action: Option<AwkAction>,
-    end_action: Option<String>, // raw expression for END block
-    rules: Vec<AwkRule>,
-    ofs: String,
-}
-
-/// Parse a simplified awk program
-fn parse_awk_program(program: &str) -> Result<AwkProgram, String> {
-    let trimmed = program.trim();
-
-    let mut begin_action = None;
-    let mut end_action = None;
-    let mut rules = Vec::new();
-    let ofs = " ".to_string();
-
-    // Split into rule blocks: BEGIN{...} rule{...} END{...}
-    let mut remaining = trimmed;
-
-    // Extract BEGIN block
-    if let Some(begin_pos) = remaining.find("BEGIN") {
-        let after = &remaining[begin_pos + 5..].trim_start();
-        if after.starts_with('{') {
-            if let Some(end) = find_matching_brace(after) {
-                let _body = &after[1..end].trim();
-                // Parse BEGIN body (accumulator init etc)
-                begin_action = Some(AwkAction::Nothing);
-                let after_begin = &after[end + 1..].trim_start();
-                remaining = after_begin;
-            }
-        }
+/// Render a template against a JSON context.
+///
+/// Supports `{{path.to.value}}`, `{{#each list}}...{{/each}}` (items are in
+/// scope, with `{{this}}`/`{{.}}` for the item itself and `{{@index}}`), and
+/// `{{#if cond}}...{{else}}...{{/if}}`.
+fn render_template(
+    template: &str,
+    context: &serde_json::Value,
+    allow_missing: bool,
+) -> Result<String, String> {
+    let mut tokens = tokenize_template(template)?.into_iter().peekable();
+    let (nodes, closing) = parse_tmpl_nodes(&mut tokens)?;
+    if let Some(tag) = closing {
+        return Err(format!("unexpected {{{{{}}}}}", tag));
     }
+    let mut out = String::new();
+    render_tmpl_nodes(&nodes, &mut vec![context.clone()], allow_missing, &mut out)?;
+    Ok(out)
+}
 
-    // Extract END block
-    if let Some(end_pos) = remaining.find("END") {
-        let before_end = &remaining[..end_pos].trim_end();
-        let after = &remaining[end_pos + 3..].trim_start();
-        if after.starts_with('{') {
-            if let Some(end) = find_matching_brace(after) {
-                let body = after[1..end].trim().to_string();
-                end_action = Some(body);
-                remaining = before_end;
-            }
+/// Split a template into literal text and `{{tag}}` tokens (Ok = text, Err = tag)
+fn tokenize_template(template: &str) -> Result<Vec<Result<String, String>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Ok(rest[..start].to_string()));
         }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unterminated {{ tag".to_string())?;
+        tokens.push(Err(after[..end].trim().to_string()));
+        rest = &after[end + 2..];
     }
-
-    // Parse remaining rules
-    let remaining = remaining.trim();
-    if !remaining.is_empty() {
-        // Could be:
-        // '{print $1}'
-        // '/pattern/{print}'
-        // '$2 >= 80 {print $1}'
-        // '{sum+=$1}'
-
-        let (condition, action_str) = parse_awk_condition_action(remaining);
-
-        let action = parse_awk_action_body(&action_str);
-
-        rules.push(AwkRule { condition, action });
+    if !rest.is_empty() {
+        tokens.push(Ok(rest.to_string()));
     }
-
-    // If BEGIN was found and there are accumulator actions, set up the program
-    Ok(AwkProgram {
-        begin_action,
-        end_action,
-        rules,
-        ofs,
-    })
+    Ok(tokens)
 }
 
-/// Find the matching closing brace for an opening brace at position 0
-fn find_matching_brace(s: &str) -> Option<usize> {
-    let mut depth = 0;
-    for (i, c) in s.char_indices() {
-        match c {
-            '{' => depth += 1,
-            '}' => {
-                depth -= 1;
-                if depth == 0 {
-                    return Some(i);
-                }
+/// Parse nodes until a closing tag (`/each`, `/if`, `else`) or end of input.
+/// Returns the nodes and the closing tag that stopped parsing, if any.
+fn parse_tmpl_nodes(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<Result<String, String>>>,
+) -> Result<(Vec<TmplNode>, Option<String>), String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Ok(text) => {
+                nodes.push(TmplNode::Text(text));
+                continue;
             }
-            _ => {}
-        }
-    }
-    None
-}
-
-/// Parse condition + action from an awk rule string
-fn parse_awk_condition_action(s: &str) -> (AwkCondition, String) {
-    let s = s.trim();
-
-    // /pattern/{action}
-    if s.starts_with('/') {
-        if let Some(end) = s[1..].find('/') {
-            let pat = s[1..end + 1].to_string();
-            let rest = s[end + 2..].trim();
-            let action = rest
-                .trim_start_matches('{')
-                .trim_end_matches('}')
-                .trim()
-                .to_string();
-            return (AwkCondition::Pattern(pat), action);
-        }
-    }
+            Err(tag) => tag,
+        };
 
-    // $N op value {action}
-    if s.starts_with('$') {
-        // Find the { that starts the action block
-        if let Some(brace_pos) = s.find('{') {
-            let cond_str = s[..brace_pos].trim();
-            let action = s[brace_pos..]
-                .trim_start_matches('{')
-                .trim_end_matches('}')
-                .trim()
-                .to_string();
-
-            // Parse condition: $2 >= 80
-            let cond_str = cond_str.trim_start_matches('$');
-            let ops = [">=", "<=", "!=", "==", ">", "<"];
-            for op in &ops {
-                if let Some(op_pos) = cond_str.find(op) {
-                    let field_str = cond_str[..op_pos].trim();
-                    let val_str = cond_str[op_pos + op.len()..].trim();
-                    let field_num: i32 = field_str.parse().unwrap_or(0);
-                    let value: f64 = val_str.parse().unwrap_or(0.0);
-                    return (
-                        AwkCondition::FieldCompare {
-                            field: AwkFieldRef::Fixed(field_num),
-                            op: op.to_string(),
-                            value,
-                        },
-                        action,
-                    );
-                }
+        if let Some(name) = tag.strip_prefix("#each ") {
+            let (body, closing) = parse_tmpl_nodes(tokens)?;
+            if closing.as_deref() != Some("/each") {
+                return Err(format!("unclosed {{{{#each {}}}}}", name.trim()));
             }
-        }
-    }
-
-    // {action} — no condition
-    let action = s
-        .trim_start_matches('{')
-        .trim_end_matches('}')
-        .trim()
-        .to_string();
-    (AwkCondition::Always, action)
-}
-
-/// Parse the body of an awk action
-fn parse_awk_action_body(action: &str) -> AwkAction {
-    let action = action.trim();
-
-    if action.is_empty() || action == "print" || action == "print $0" {
-        return AwkAction::PrintFields(vec![AwkFieldRef::Fixed(0)]);
-    }
-
-    // Handle accumulator: sum+=$1
-    if action.contains("+=") {
-        if let Some(eq_pos) = action.find("+=") {
-            let var = action[..eq_pos].trim().to_string();
-            let field_ref = parse_awk_field_ref(action[eq_pos + 2..].trim());
-            return AwkAction::Accumulate(var, field_ref);
-        }
-    }
-
-    // print NF (field count, not $NF)
-    if action == "print NF" {
-        return AwkAction::PrintNF;
-    }
-
-    // print $NF, $1, etc
-    if action.starts_with("print ") {
-        let fields_str = &action[6..];
-        let mut refs = Vec::new();
-
-        for field in fields_str.split(',') {
-            let field = field.trim();
-            if field.starts_with('$') {
-                refs.push(parse_awk_field_ref(field));
-            } else if field.starts_with('"') {
-                // String literal in print — ignore for now, just extract fields
-                // Handle string concat patterns like "\"prefix\" $1"
+            nodes.push(TmplNode::Each(name.trim().to_string(), body));
+        } else if let Some(name) = tag.strip_prefix("#if ") {
+            let (then_branch, mut closing) = parse_tmpl_nodes(tokens)?;
+            let mut else_branch = Vec::new();
+            if closing.as_deref() == Some("else") {
+                let (nodes, next) = parse_tmpl_nodes(tokens)?;
+                else_branch = nodes;
+                closing = next;
             }
+            if closing.as_deref() != Some("/if") {
+                return Err(format!("unclosed {{{{#if {}}}}}", name.trim()));
+            }
+            nodes.push(TmplNode::If(
+                name.trim().to_string(),
+                then_branch,
+                else_branch,
+            ));
+        } else if tag == "/each" || tag == "/if" || tag == "else" {
+            return Ok((nodes, Some(tag)));
+        } else {
+            nodes.push(TmplNode::Var(tag));
         }
-        if refs.is_empty() {
-            // Could be a string concatenation pattern
-            return AwkAction::PrintLiteral(fields_str.to_string());
-        }
-        return AwkAction::PrintFields(refs);
     }
-
-    AwkAction::PrintFields(vec![AwkFieldRef::Fixed(0)])
+    Ok((nodes, None))
 }
 
-/// Parse a field reference like $1, $NF, $0
-fn parse_awk_field_ref(s: &str) -> AwkFieldRef {
-    let s = s.trim().trim_start_matches('$');
-    if s == "NF" {
-        AwkFieldRef::NF
-    } else if let Ok(n) = s.parse::<i32>() {
-        AwkFieldRef::Fixed(n)
-    } else {
-        AwkFieldRef::Fixed(0)
+/// Resolve a dotted path against the scope stack, innermost scope first
+fn lookup_tmpl_value(scopes: &[serde_json::Value], path: &str) -> Option<serde_json::Value> {
+    if path == "this" || path == "." {
+        return scopes.last().cloned();
     }
-}
-
-/// Resolve a field reference to its value
-fn resolve_field<'a>(field: &AwkFieldRef, line: &'a str, fields: &[&'a str]) -> &'a str {
-    match field {
-        AwkFieldRef::Fixed(0) => line,
-        AwkFieldRef::Fixed(n) if *n > 0 => fields.get((*n - 1) as usize).copied().unwrap_or(""),
-        AwkFieldRef::NF => fields.last().copied().unwrap_or(""),
-        _ => "",
+    let mut parts = path.split('.');
+    let first = parts.next()?;
+    let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+    for part in parts {
+        value = match value {
+            serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => value.get(part)?,
+        };
     }
+    Some(value.clone())
 }
 
-/// Execute awk action on a line
-fn execute_awk_action(
-    program: &AwkProgram,
-    line: &str,
-    fields: &[&str],
-    _nr: usize,
-    accumulator: &mut f64,
-) -> String {
-    for rule in &program.rules {
-        // Check condition
-        let matches = match &rule.condition {
-            AwkCondition::Always => true,
-            AwkCondition::Pattern(pat) => line.contains(pat),
-            AwkCondition::FieldCompare { field, op, value } => {
-                let field_val = resolve_field(field, line, fields);
-                let fv: f64 = field_val.parse().unwrap_or(0.0);
-                match op.as_str() {
-                    ">=" => fv >= *value,
-                    "<=" => fv <= *value,
-                    ">" => fv > *value,
-                    "<" => fv < *value,
-                    "==" => (fv - value).abs() < f64::EPSILON,
-                    "!=" => (fv - value).abs() >= f64::EPSILON,
-                    _ => false,
-                }
-            }
-        };
-
-        if !matches {
-            continue;
-        }
-
-        match &rule.action {
-            AwkAction::PrintFields(refs) => {
-                let parts: Vec<&str> = refs
-                    .iter()
-                    .map(|r| resolve_field(r, line, fields))
-                    .collect();
-                return parts.join(&program.ofs);
-            }
-            AwkAction::PrintNF => {
-                return fields.len().to_string();
-            }
-            AwkAction::PrintLiteral(expr) => {
-                // Handle string concatenation with fields
-                return evaluate_awk_print_expr(expr, line, fields);
-            }
-            AwkAction::Accumulate(_var, field_ref) => {
-                let val: f64 = resolve_field(field_ref, line, fields)
-                    .parse()
-                    .unwrap_or(0.0);
-                *accumulator += val;
-                return String::new();
-            }
-            AwkAction::Nothing => {}
-        }
+fn tmpl_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Array(items) => !items.is_empty(),
+        serde_json::Value::Object(_) => true,
     }
-
-    String::new()
 }
 
-/// Evaluate a complex awk print expression with string concatenation
-fn evaluate_awk_print_expr(expr: &str, line: &str, fields: &[&str]) -> String {
-    let mut result = String::new();
-    let mut chars = expr.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        match c {
-            '"' => {
-                // String literal
-                while let Some(&next) = chars.peek() {
-                    if next == '"' {
-                        chars.next();
-                        break;
-                    }
-                    if next == '\\' {
-                        chars.next();
-                        if let Some(&escaped) = chars.peek() {
-                            match escaped {
-                                'n' => result.push('\n'),
-                                't' => result.push('\t'),
-                                '"' => result.push('"'),
-                                '\\' => result.push('\\'),
-                                _ => {
-                                    result.push('\\');
-                                    result.push(escaped);
-                                }
-                            }
-                            chars.next();
-                        }
-                    } else {
-                        result.push(next);
-                        chars.next();
-                    }
-                }
-            }
-            '$' => {
-                // Field reference
-                let mut num = String::new();
-                while let Some(&next) = chars.peek() {
-                    if next.is_ascii_digit() {
-                        num.push(next);
-                        chars.next();
-                    } else if next == 'N' {
-                        // $NF
-                        chars.next();
-                        if chars.peek() == Some(&'F') {
-                            chars.next();
-                            result.push_str(fields.last().copied().unwrap_or(""));
-                        }
-                        break;
-                    } else {
-                        break;
-                    }
-                }
-                if !num.is_empty() {
-                    let n: usize = num.parse().unwrap_or(0);
-                    if n == 0 {
-                        result.push_str(line);
-                    } else {
-                        result.push_str(fields.get(n - 1).copied().unwrap_or(""));
-                    }
+fn render_tmpl_nodes(
+    nodes: &[TmplNode],
+    scopes: &mut Vec<serde_json::Value>,
+    allow_missing: bool,
+    out: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            TmplNode::Text(text) => out.push_str(text),
+            TmplNode::Var(path) => match lookup_tmpl_value(scopes, path) {
+                Some(serde_json::Value::String(s)) => out.push_str(&s),
+                Some(serde_json::Value::Null) => {}
+                Some(value) => out.push_str(&value.to_string()),
+                None if allow_missing => {}
+                None => return Err(format!("undefined variable: {}", path)),
+            },
+            TmplNode::Each(path, body) => {
+                let items = match lookup_tmpl_value(scopes, path) {
+                    Some(serde_json::Value::Array(items)) => items,
+                    Some(serde_json::Value::Null) => Vec::new(),
+                    Some(_) => return Err(format!("{} is not a list", path)),
+                    None if allow_missing => Vec::new(),
+                    None => return Err(format!("undefined variable: {}", path)),
+                };
+                for (index, item) in items.into_iter().enumerate() {
+                    scopes.push(serde_json::json!({ "@index": index }));
+                    scopes.push(item);
+                    let result = render_tmpl_nodes(body, scopes, allow_missing, out);
+                    scopes.truncate(scopes.len() - 2);
+                    result?;
                 }
             }
-            ' ' => {
-                // Space between concatenation parts — skip
-            }
-            _ => {
-                // Could be a variable name or other text
+            TmplNode::If(path, then_branch, else_branch) => {
+                let cond = lookup_tmpl_value(scopes, path).is_some_and(|v| tmpl_truthy(&v));
+                let branch = if cond { then_branch } else { else_branch };
+                render_tmpl_nodes(branch, scopes, allow_missing, out)?;
             }
         }
     }
-
-    result
-}
-
-/// Evaluate an END block expression (e.g., "print sum")
-fn evaluate_awk_end_expr(expr: &str, accumulator: f64) -> String {
-    let expr = expr.trim();
-
-    // "print sum" or "print variable_name"
-    if expr.starts_with("print ") {
-        let var = expr[6..].trim();
-        // The accumulator holds the sum
-        if var == "sum" || var == "total" || var == "count" || var == "s" || var == "n" {
-            // Format as integer if possible
-            if accumulator == accumulator.floor() && accumulator.abs() < i64::MAX as f64 {
-                return (accumulator as i64).to_string();
-            }
-            return accumulator.to_string();
-        }
-    }
-
-    String::new()
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1387,34 +1317,44 @@ mod tests {
     }
 
     // ========================================================================
-    // awk tests
+    // tmpl tests
     // ========================================================================
 
     #[test]
-    fn test_awk_parse_simple() {
-        let prog = parse_awk_program("{print}").unwrap();
-        assert_eq!(prog.rules.len(), 1);
+    fn test_tmpl_simple_substitution() {
+        let ctx = serde_json::json!({ "name": "widget", "count": 3 });
+        let out = render_template("{{ name }} x{{count}}\n", &ctx, false).unwrap();
+        assert_eq!(out, "widget x3\n");
+
+        let err = render_template("{{missing}}", &ctx, false).unwrap_err();
+        assert!(err.contains("missing"), "err: {}", err);
+        assert_eq!(render_template("[{{missing}}]", &ctx, true).unwrap(), "[]");
     }
 
     #[test]
-    fn test_awk_parse_field() {
-        let prog = parse_awk_program("{print $1}").unwrap();
-        assert_eq!(prog.rules.len(), 1);
+    fn test_tmpl_each_over_json_array() {
+        let ctx = serde_json::json!({
+            "prefix": "-",
+            "items": [{ "name": "a" }, { "name": "b" }],
+            "tags": ["x", "y"]
+        });
+        let out = render_template(
+            "{{#each items}}{{prefix}}{{name}}{{/each}}|{{#each tags}}{{@index}}={{this}} {{/each}}",
+            &ctx,
+            false,
+        )
+        .unwrap();
+        assert_eq!(out, "-a-b|0=x 1=y ");
     }
 
     #[test]
-    fn test_awk_parse_multiple_fields() {
-        let prog = parse_awk_program("{print $1,$3}").unwrap();
-        assert_eq!(prog.rules.len(), 1);
-    }
+    fn test_tmpl_if_block() {
+        let ctx = serde_json::json!({ "debug": true, "empty": [], "user": { "name": "kim" } });
+        let tmpl = "{{#if debug}}on{{else}}off{{/if}},{{#if empty}}yes{{else}}no{{/if}},{{#if user.name}}{{user.name}}{{/if}}";
+        assert_eq!(render_template(tmpl, &ctx, false).unwrap(), "on,no,kim");
 
-    #[test]
-    fn test_awk_execute() {
-        let prog = parse_awk_program("{print $2}").unwrap();
-        let fields = vec!["one", "two", "three"];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "one two three", &fields, 1, &mut acc);
-        assert_eq!(result, "two");
+        let err = render_template("{{#if debug}}unclosed", &ctx, false).unwrap_err();
+        assert!(err.contains("unclosed"), "err: {}", err);
     }
 
     // ========================================================================
@@ -1520,73 +1460,4 @@ mod tests {
         let result = evaluate_expr(&["2".to_string(), "+".to_string(), "3".to_string()]).unwrap();
         assert_eq!(result, "5");
     }
-
-    // ========================================================================
-    // awk edge cases
-    // ========================================================================
-
-    #[test]
-    fn test_awk_parse_print_all() {
-        let prog = parse_awk_program("{print $0}").unwrap();
-        assert_eq!(prog.rules.len(), 1);
-    }
-
-    #[test]
-    fn test_awk_empty_line() {
-        let prog = parse_awk_program("{print $1}").unwrap();
-        let fields: Vec<&str> = vec![];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "", &fields, 1, &mut acc);
-        assert_eq!(result, "");
-    }
-
-    #[test]
-    fn test_awk_field_out_of_bounds() {
-        let prog = parse_awk_program("{print $10}").unwrap();
-        let fields = vec!["one", "two"];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "one two", &fields, 1, &mut acc);
-        assert_eq!(result, "");
-    }
-
-    #[test]
-    fn test_awk_nr_variable() {
-        let prog = parse_awk_program("{print}").unwrap();
-        let fields = vec!["x"];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "x", &fields, 42, &mut acc);
-        assert_eq!(result, "x");
-    }
-
-    #[test]
-    fn test_awk_nf_variable() {
-        let prog = parse_awk_program("{print $1}").unwrap();
-        let fields = vec!["a", "b", "c", "d"];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "a b c d", &fields, 1, &mut acc);
-        assert_eq!(result, "a");
-    }
-
-    #[test]
-    fn test_awk_with_pattern() {
-        let prog = parse_awk_program("/hello/{print $1}").unwrap();
-        assert!(!prog.rules.is_empty());
-
-        let mut acc = 0.0;
-        let fields = vec!["hello", "world"];
-        let result = execute_awk_action(&prog, "hello world", &fields, 1, &mut acc);
-        assert_eq!(result, "hello");
-
-        let result = execute_awk_action(&prog, "goodbye world", &["goodbye", "world"], 1, &mut acc);
-        assert_eq!(result, "");
-    }
-
-    #[test]
-    fn test_awk_multiple_print_fields_joined() {
-        let prog = parse_awk_program("{print $1,$2,$3}").unwrap();
-        let fields = vec!["a", "b", "c"];
-        let mut acc = 0.0;
-        let result = execute_awk_action(&prog, "a b c", &fields, 1, &mut acc);
-        assert_eq!(result, "a b c");
-    }
 }
@@ -1,4 +1,4 @@
-//! Text processing commands: grep, wc, sort, uniq, head, tail, tee
+//! Text processing commands: grep, wc, sort, uniq, head, tail, tee, diffstat, cloc
 
 use futures_lite::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use futures_lite::StreamExt;
@@ -6,6 +6,7 @@ use lexopt::prelude::*;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
+use super::gitignore::GitIgnore;
 use super::helpers::resolve_path;
 use super::{make_parser, parse_common};
 
@@ -189,8 +190,14 @@ impl TextCommands {
     /// grep - search for patterns
     #[shell_command(
         name = "grep",
-        usage = "grep [-ivnclrE] PATTERN [FILE]...",
-        description = "Search for patterns in files"
+        usage = "grep [-EFivnclrqwx] [--gitignore] [-e PATTERN]... PATTERN [FILE]...",
+        description = "Search for lines matching a pattern (basic regex by default).\n\
+        -E: Extended regex   -F: Fixed string   -e PATTERN: Add a pattern\n\
+        -i: Ignore case      -v: Select non-matching lines\n\
+        -n: Show line numbers   -c: Count matching lines   -l: List matching files\n\
+        -r: Search directories recursively (default: .)\n\
+        --gitignore: With -r, skip .git and paths ignored by .gitignore files\n\
+        -w: Match whole words   -x: Match whole lines   -q: Quiet, exit status only"
     )]
     fn cmd_grep(
         args: Vec<String>,
@@ -202,98 +209,117 @@ impl TextCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
+            let mut opts = GrepOptions::default();
+            let mut syntax = GrepSyntax::Basic;
             let mut ignore_case = false;
-            let mut invert = false;
-            let mut show_line_numbers = false;
-            let mut count_only = false;
-            let mut files_only = false;
+            let mut word = false;
+            let mut whole_line = false;
             let mut recursive = false;
-            let mut use_regex = false;
+            let mut gitignore = false;
+            let mut patterns: Vec<String> = Vec::new();
             let mut positional: Vec<String> = Vec::new();
 
-            // Manual parsing to handle combined flags like -rn
-            for arg in &remaining {
-                if arg.starts_with('-') && arg.len() > 1 && !arg.starts_with("--") {
-                    for c in arg[1..].chars() {
-                        match c {
-                            'i' => ignore_case = true,
-                            'v' => invert = true,
-                            'n' => show_line_numbers = true,
-                            'c' => count_only = true,
-                            'l' => files_only = true,
-                            'r' | 'R' => recursive = true,
-                            'E' => use_regex = true,
-                            _ => {}
-                        }
+            // Manual parsing to handle combined flags like -rin and -e PATTERN
+            let mut i = 0;
+            while i < remaining.len() {
+                let arg = &remaining[i];
+                i += 1;
+                if arg == "--" {
+                    positional.extend(remaining[i..].iter().cloned());
+                    break;
+                }
+                if let Some(long) = arg.strip_prefix("--") {
+                    match long {
+                        "ignore-case" => ignore_case = true,
+                        "invert-match" => opts.invert = true,
+                        "line-number" => opts.line_numbers = true,
+                        "count" => opts.count_only = true,
+                        "files-with-matches" => opts.files_only = true,
+                        "recursive" => recursive = true,
+                        "gitignore" => gitignore = true,
+                        "extended-regexp" => syntax = GrepSyntax::Extended,
+                        "fixed-strings" => syntax = GrepSyntax::Fixed,
+                        "word-regexp" => word = true,
+                        "line-regexp" => whole_line = true,
+                        "quiet" | "silent" => opts.quiet = true,
+                        _ => match long.strip_prefix("regexp=") {
+                            Some(p) => patterns.push(p.to_string()),
+                            None => {
+                                let msg = format!("grep: unrecognized option '{}'\n", arg);
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                                return 2;
+                            }
+                        },
                     }
-                } else {
+                    continue;
+                }
+                if !arg.starts_with('-') || arg.len() == 1 {
                     positional.push(arg.clone());
+                    continue;
+                }
+                for (idx, c) in arg.char_indices().skip(1) {
+                    match c {
+                        'i' => ignore_case = true,
+                        'v' => opts.invert = true,
+                        'n' => opts.line_numbers = true,
+                        'c' => opts.count_only = true,
+                        'l' => opts.files_only = true,
+                        'r' | 'R' => recursive = true,
+                        'E' => syntax = GrepSyntax::Extended,
+                        'F' => syntax = GrepSyntax::Fixed,
+                        'G' => syntax = GrepSyntax::Basic,
+                        'w' => word = true,
+                        'x' => whole_line = true,
+                        'q' => opts.quiet = true,
+                        'e' => {
+                            // -ePATTERN or -e PATTERN
+                            let rest = &arg[idx + 1..];
+                            if !rest.is_empty() {
+                                patterns.push(rest.to_string());
+                            } else if let Some(p) = remaining.get(i) {
+                                patterns.push(p.clone());
+                                i += 1;
+                            } else {
+                                let _ = stderr
+                                    .write_all(b"grep: option requires an argument -- 'e'\n")
+                                    .await;
+                                return 2;
+                            }
+                            break;
+                        }
+                        _ => {}
+                    }
                 }
             }
 
-            if positional.is_empty() {
-                let _ = stderr.write_all(b"grep: missing pattern\n").await;
-                return 1;
-            }
-
-            let pattern = &positional[0];
-            let pattern_lower = pattern.to_lowercase();
-            let files: Vec<_> = positional[1..].to_vec();
-            let mut found = false;
-
-            // Build regex if -E flag
-            let regex = if use_regex {
-                let pat = if ignore_case {
-                    format!("(?i){}", pattern)
-                } else {
-                    pattern.clone()
-                };
-                match regex::Regex::new(&pat) {
-                    Ok(re) => Some(re),
-                    Err(e) => {
-                        let msg = format!("grep: invalid regex: {}\n", e);
-                        let _ = stderr.write_all(msg.as_bytes()).await;
-                        return 2;
-                    }
+            if patterns.is_empty() {
+                if positional.is_empty() {
+                    let _ = stderr.write_all(b"grep: missing pattern\n").await;
+                    return 2;
                 }
+                patterns.push(positional.remove(0));
+            }
+            // With -r and no files, search the current directory
+            let default_dir = recursive && positional.is_empty();
+            let files = if default_dir {
+                vec![".".to_string()]
             } else {
-                None
+                positional
             };
 
-            let matches = |line: &str| -> bool {
-                let matched = if let Some(ref re) = regex {
-                    re.is_match(line)
-                } else {
-                    let line_check = if ignore_case {
-                        line.to_lowercase()
-                    } else {
-                        line.to_string()
-                    };
-                    let pat = if ignore_case { &pattern_lower } else { pattern };
-                    line_check.contains(pat)
-                };
-                if invert {
-                    !matched
-                } else {
-                    matched
+            let regex = match build_grep_regex(&patterns, syntax, ignore_case, word, whole_line) {
+                Ok(re) => re,
+                Err(e) => {
+                    let msg = format!("grep: invalid regex: {}\n", e);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    return 2;
                 }
             };
 
-            // Collect files to search (handle -r recursive)
-            let mut search_files: Vec<(String, String)> = Vec::new(); // (display_name, path)
-            if recursive && !files.is_empty() {
-                for file in &files {
-                    let path = resolve_path(&cwd, &file);
-                    collect_files_recursive(&path, file, &mut search_files);
-                }
-            } else {
-                for file in &files {
-                    let path = resolve_path(&cwd, &file);
-                    search_files.push((file.clone(), path));
-                }
-            }
+            let mut found = false;
+            let mut had_error = false;
 
-            if search_files.is_empty() && files.is_empty() {
+            if files.is_empty() {
                 // Read from stdin
                 let reader = BufReader::new(stdin);
                 let mut lines_stream = reader.lines();
@@ -301,86 +327,95 @@ impl TextCommands {
                 let mut line_num = 0usize;
                 while let Some(Ok(line)) = lines_stream.next().await {
                     line_num += 1;
-                    if matches(&line) {
-                        match_count += 1;
-                        if !count_only {
-                            if show_line_numbers {
-                                let _ = stdout.write_all(format!("{}:", line_num).as_bytes()).await;
-                            }
-                            let _ = stdout.write_all(line.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
+                    if !opts.selects(&regex, &line) {
+                        continue;
+                    }
+                    match_count += 1;
+                    if opts.quiet {
+                        break;
+                    }
+                    if !opts.count_only && !opts.files_only {
+                        let out = opts.format_line(None, line_num, &line);
+                        let _ = stdout.write_all(out.as_bytes()).await;
+                    }
+                }
+                found = match_count > 0;
+                let summary = opts.format_summary("(standard input)", match_count, false);
+                let _ = stdout.write_all(summary.as_bytes()).await;
+            } else {
+                // Collect files to search (handle -r recursive)
+                let mut search_files: Vec<(String, String)> = Vec::new(); // (display_name, path)
+                let mut searched_dir = false;
+                for file in &files {
+                    let path = resolve_path(&cwd, file);
+                    if std::path::Path::new(&path).is_dir() {
+                        if recursive {
+                            searched_dir = true;
+                            let mut ignore = gitignore.then(GitIgnore::new);
+                            collect_files_recursive(
+                                &path,
+                                file,
+                                "",
+                                ignore.as_mut(),
+                                &mut search_files,
+                            );
+                        } else {
+                            let msg = format!("grep: {}: Is a directory\n", file);
+                            let _ = stderr.write_all(msg.as_bytes()).await;
+                            had_error = true;
                         }
-                        found = true;
+                    } else {
+                        search_files.push((file.clone(), path));
                     }
                 }
-                if count_only {
-                    let _ = stdout
-                        .write_all(format!("{}\n", match_count).as_bytes())
-                        .await;
-                    if match_count > 0 {
-                        found = true;
+
+                if default_dir {
+                    for (display, _) in &mut search_files {
+                        if let Some(rel) = display.strip_prefix("./") {
+                            *display = rel.to_string();
+                        }
                     }
                 }
-            } else {
-                let show_filename = search_files.len() > 1 || recursive;
+
+                let show_filename = files.len() > 1 || searched_dir;
                 for (display, path) in &search_files {
-                    match std::fs::read_to_string(path) {
-                        Ok(content) => {
-                            let mut match_count = 0usize;
-                            let mut file_matched = false;
-                            for (line_num, line) in content.lines().enumerate() {
-                                if matches(line) {
-                                    match_count += 1;
-                                    file_matched = true;
-                                    found = true;
-                                    if files_only {
-                                        break;
-                                    }
-                                    if !count_only {
-                                        if show_filename {
-                                            let _ = stdout
-                                                .write_all(format!("{}:", display).as_bytes())
-                                                .await;
-                                        }
-                                        if show_line_numbers {
-                                            let _ = stdout
-                                                .write_all(format!("{}:", line_num + 1).as_bytes())
-                                                .await;
-                                        }
-                                        let _ = stdout.write_all(line.as_bytes()).await;
-                                        let _ = stdout.write_all(b"\n").await;
-                                    }
-                                }
-                            }
-                            if files_only && file_matched {
-                                let _ = stdout.write_all(format!("{}\n", display).as_bytes()).await;
-                            }
-                            if count_only {
-                                if show_filename {
-                                    let _ = stdout
-                                        .write_all(
-                                            format!("{}:{}\n", display, match_count).as_bytes(),
-                                        )
-                                        .await;
-                                } else {
-                                    let _ = stdout
-                                        .write_all(format!("{}\n", match_count).as_bytes())
-                                        .await;
-                                }
-                                if match_count > 0 {
-                                    found = true;
-                                }
-                            }
-                        }
+                    let content = match std::fs::read(path) {
+                        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
                         Err(e) => {
                             let msg = format!("grep: {}: {}\n", display, e);
                             let _ = stderr.write_all(msg.as_bytes()).await;
+                            had_error = true;
+                            continue;
+                        }
+                    };
+
+                    let name = show_filename.then_some(display.as_str());
+                    let mut out = String::new();
+                    let mut match_count = 0usize;
+                    for (line_num, line) in content.lines().enumerate() {
+                        if !opts.selects(&regex, line) {
+                            continue;
+                        }
+                        match_count += 1;
+                        if opts.quiet || opts.files_only {
+                            break;
                         }
+                        if !opts.count_only {
+                            out.push_str(&opts.format_line(name, line_num + 1, line));
+                        }
+                    }
+                    found |= match_count > 0;
+                    out.push_str(&opts.format_summary(display, match_count, show_filename));
+                    let _ = stdout.write_all(out.as_bytes()).await;
+                    if found && opts.quiet {
+                        return 0;
                     }
                 }
             }
 
-            if found {
+            if had_error && !(found && opts.quiet) {
+                2
+            } else if found {
                 0
             } else {
                 1
@@ -391,7 +426,7 @@ impl TextCommands {
     /// wc - word, line, character count
     #[shell_command(
         name = "wc",
-        usage = "wc [-lwc] [FILE]...",
+        usage = "wc [-lwmc] [FILE]...",
         description = "Print line, word, and byte counts"
     )]
     fn cmd_wc(
@@ -404,121 +439,73 @@ impl TextCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut show_lines = false;
-            let mut show_words = false;
-            let mut show_chars = false;
+            let mut columns = WcColumns::default();
             let mut files: Vec<String> = Vec::new();
             let mut parser = make_parser(remaining);
 
             while let Some(arg) = parser.next().ok().flatten() {
                 match arg {
-                    Short('l') => show_lines = true,
-                    Short('w') => show_words = true,
-                    Short('c') => show_chars = true,
+                    Short('l') | Long("lines") => columns.lines = true,
+                    Short('w') | Long("words") => columns.words = true,
+                    Short('m') | Long("chars") => columns.chars = true,
+                    Short('c') | Long("bytes") => columns.bytes = true,
                     Value(val) => files.push(val.string().unwrap_or_default()),
                     _ => {}
                 }
             }
 
-            if !show_lines && !show_words && !show_chars {
-                show_lines = true;
-                show_words = true;
-                show_chars = true;
+            if !columns.lines && !columns.words && !columns.chars && !columns.bytes {
+                columns.lines = true;
+                columns.words = true;
+                columns.bytes = true;
             }
 
-            let count_content = |content: &str| -> (usize, usize, usize) {
-                (
-                    content.lines().count(),
-                    content.split_whitespace().count(),
-                    content.len(),
-                )
-            };
-
-            let num_flags = show_lines as usize + show_words as usize + show_chars as usize;
-            let format_counts = |l: usize, w: usize, c: usize, name: Option<&str>| -> String {
-                let mut parts = Vec::new();
-                // Use padding only when showing multiple stats or a filename
-                let pad = name.is_some() || num_flags > 1;
-                if show_lines {
-                    if pad {
-                        parts.push(format!("{:8}", l));
-                    } else {
-                        parts.push(l.to_string());
-                    }
-                }
-                if show_words {
-                    if pad {
-                        parts.push(format!("{:8}", w));
-                    } else {
-                        parts.push(w.to_string());
-                    }
-                }
-                if show_chars {
-                    if pad {
-                        parts.push(format!("{:8}", c));
-                    } else {
-                        parts.push(c.to_string());
-                    }
-                }
-                let mut result = parts.join("");
-                if let Some(n) = name {
-                    result.push(' ');
-                    result.push_str(n);
-                }
-                result.push('\n');
-                result
-            };
-
             if files.is_empty() {
-                let mut content = String::new();
-                let reader = BufReader::new(stdin);
-                let mut lines_iter = reader.lines();
-                while let Some(Ok(line)) = lines_iter.next().await {
-                    content.push_str(&line);
-                    content.push('\n');
-                }
-                let (l, w, c) = count_content(&content);
-                let _ = stdout
-                    .write_all(format_counts(l, w, c, None).as_bytes())
-                    .await;
-            } else {
-                let mut total = (0, 0, 0);
-                for file in &files {
-                    let path = resolve_path(&cwd, &file);
+                use futures_lite::io::AsyncReadExt;
+                let mut data = Vec::new();
+                let mut reader = stdin;
+                let _ = reader.read_to_end(&mut data).await;
+                let line = format_wc_line(&WcCounts::of(&data), &columns, None);
+                let _ = stdout.write_all(line.as_bytes()).await;
+                return 0;
+            }
 
-                    match std::fs::read_to_string(&path) {
-                        Ok(content) => {
-                            let (l, w, c) = count_content(&content);
-                            total.0 += l;
-                            total.1 += w;
-                            total.2 += c;
-                            let _ = stdout
-                                .write_all(format_counts(l, w, c, Some(file)).as_bytes())
-                                .await;
-                        }
-                        Err(e) => {
-                            let msg = format!("wc: {}: {}\n", file, e);
-                            let _ = stderr.write_all(msg.as_bytes()).await;
-                        }
+            let mut code = 0;
+            let mut total = WcCounts::default();
+            for file in &files {
+                let path = resolve_path(&cwd, file);
+                match std::fs::read(&path) {
+                    Ok(data) => {
+                        let counts = WcCounts::of(&data);
+                        total.add(&counts);
+                        let line = format_wc_line(&counts, &columns, Some(file));
+                        let _ = stdout.write_all(line.as_bytes()).await;
+                    }
+                    Err(e) => {
+                        let msg = format!("wc: {}: {}\n", file, e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        code = 1;
                     }
-                }
-                if files.len() > 1 {
-                    let _ = stdout
-                        .write_all(
-                            format_counts(total.0, total.1, total.2, Some("total")).as_bytes(),
-                        )
-                        .await;
                 }
             }
-            0
+            if files.len() > 1 {
+                let line = format_wc_line(&total, &columns, Some("total"));
+                let _ = stdout.write_all(line.as_bytes()).await;
+            }
+            code
         })
     }
 
     /// sort - sort lines
     #[shell_command(
         name = "sort",
-        usage = "sort [-rnuk] [-t SEP] [FILE]",
-        description = "Sort lines of text"
+        usage = "sort [-rnu] [-k N[,M]] [-t SEP] [FILE]...",
+        description = "Sort lines of text.\n\
+        -n: Compare leading numbers (non-numeric lines count as 0)\n\
+        -r: Reverse the result\n\
+        -u: Output only the first of lines with equal keys\n\
+        -k N[,M]: Sort by fields N through M (default: to end of line)\n\
+        -t SEP: Fields are separated by SEP instead of blanks"
     )]
     fn cmd_sort(
         args: Vec<String>,
@@ -530,52 +517,61 @@ impl TextCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut reverse = false;
-            let mut numeric = false;
-            let mut unique = false;
-            let mut key_field: Option<usize> = None;
-            let mut separator: Option<String> = None;
+            let mut opts = SortOptions::default();
             let mut files: Vec<String> = Vec::new();
 
             // Manual parsing because we need to handle -t, -k, and combined flags like -nr
             let mut i = 0;
             while i < remaining.len() {
                 let arg = &remaining[i];
-                if arg == "-t" && i + 1 < remaining.len() {
-                    separator = Some(remaining[i + 1].clone());
-                    i += 2;
-                } else if arg.starts_with("-t") && arg.len() > 2 {
-                    separator = Some(arg[2..].to_string());
-                    i += 1;
-                } else if arg == "-k" && i + 1 < remaining.len() {
-                    // Parse key spec: just take the first number
-                    let spec = &remaining[i + 1];
-                    key_field = spec
-                        .split(|c: char| !c.is_ascii_digit())
-                        .next()
-                        .and_then(|s| s.parse::<usize>().ok());
-                    i += 2;
-                } else if arg.starts_with("-k") && arg.len() > 2 {
-                    let spec = &arg[2..];
-                    key_field = spec
-                        .split(|c: char| !c.is_ascii_digit())
-                        .next()
-                        .and_then(|s| s.parse::<usize>().ok());
-                    i += 1;
-                } else if arg.starts_with('-') && !arg.starts_with("--") {
-                    for c in arg[1..].chars() {
-                        match c {
-                            'r' => reverse = true,
-                            'n' => numeric = true,
-                            'u' => unique = true,
-                            _ => {}
+                let (flag, value) = match arg.as_str() {
+                    "-t" | "-k" | "--field-separator" | "--key" => {
+                        i += 1;
+                        match remaining.get(i) {
+                            Some(v) => (arg.as_str(), v.clone()),
+                            None => {
+                                let msg =
+                                    format!("sort: option requires an argument -- '{}'\n", arg);
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                                return 2;
+                            }
                         }
                     }
-                    i += 1;
-                } else {
-                    files.push(arg.clone());
-                    i += 1;
+                    _ if arg.starts_with("--field-separator=") => {
+                        ("-t", arg["--field-separator=".len()..].to_string())
+                    }
+                    _ if arg.starts_with("--key=") => ("-k", arg["--key=".len()..].to_string()),
+                    _ if arg.starts_with("-t") && arg.len() > 2 => ("-t", arg[2..].to_string()),
+                    _ if arg.starts_with("-k") && arg.len() > 2 => ("-k", arg[2..].to_string()),
+                    _ => (arg.as_str(), String::new()),
+                };
+
+                match flag {
+                    "-t" | "--field-separator" => opts.separator = Some(value),
+                    "-k" | "--key" => match parse_sort_key(&value) {
+                        Some(key) => opts.key = Some(key),
+                        None => {
+                            let msg = format!("sort: invalid key specification '{}'\n", value);
+                            let _ = stderr.write_all(msg.as_bytes()).await;
+                            return 2;
+                        }
+                    },
+                    "--reverse" => opts.reverse = true,
+                    "--numeric-sort" => opts.numeric = true,
+                    "--unique" => opts.unique = true,
+                    f if f.starts_with('-') && !f.starts_with("--") && f.len() > 1 => {
+                        for c in f[1..].chars() {
+                            match c {
+                                'r' => opts.reverse = true,
+                                'n' => opts.numeric = true,
+                                'u' => opts.unique = true,
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => files.push(arg.clone()),
                 }
+                i += 1;
             }
 
             let mut lines: Vec<String> = Vec::new();
@@ -588,7 +584,7 @@ impl TextCommands {
                 }
             } else {
                 for file in &files {
-                    let path = resolve_path(&cwd, &file);
+                    let path = resolve_path(&cwd, file);
 
                     match std::fs::read_to_string(&path) {
                         Ok(content) => {
@@ -599,80 +595,20 @@ impl TextCommands {
                         Err(e) => {
                             let msg = format!("sort: {}: {}\n", file, e);
                             let _ = stderr.write_all(msg.as_bytes()).await;
-                            return 1;
+                            return 2;
                         }
                     }
                 }
             }
 
-            // Extract sort key from a line
-            let extract_key = |line: &str| -> String {
-                if let Some(k) = key_field {
-                    let sep = separator.as_deref();
-                    let parts: Vec<&str> = if let Some(s) = sep {
-                        line.split(s).collect()
-                    } else {
-                        line.split_whitespace().collect()
-                    };
-                    parts.get(k.saturating_sub(1)).unwrap_or(&"").to_string()
-                } else {
-                    line.to_string()
-                }
-            };
-
-            // Extract leading numeric value from a string (like sort -n does)
-            let parse_leading_number = |s: &str| -> f64 {
-                let s = s.trim_start();
-                let mut end = 0;
-                let chars: Vec<char> = s.chars().collect();
-                // Optional sign
-                if end < chars.len() && (chars[end] == '-' || chars[end] == '+') {
-                    end += 1;
-                }
-                let mut has_dot = false;
-                while end < chars.len() {
-                    if chars[end].is_ascii_digit() {
-                        end += 1;
-                    } else if chars[end] == '.' && !has_dot {
-                        has_dot = true;
-                        end += 1;
-                    } else {
-                        break;
-                    }
-                }
-                if end == 0 || (end == 1 && (chars[0] == '-' || chars[0] == '+')) {
-                    return 0.0;
-                }
-                s[..end].parse().unwrap_or(0.0)
-            };
-
-            // Sort using extracted keys
-            lines.sort_by(|a, b| {
-                let ka = extract_key(a);
-                let kb = extract_key(b);
-                let cmp = if numeric {
-                    let na = parse_leading_number(&ka);
-                    let nb = parse_leading_number(&kb);
-                    na.partial_cmp(&nb).unwrap_or(std::cmp::Ordering::Equal)
-                } else {
-                    ka.cmp(&kb)
-                };
-                if reverse {
-                    cmp.reverse()
-                } else {
-                    cmp
-                }
-            });
-
-            // Deduplicate if -u
-            if unique {
-                lines.dedup();
-            }
+            sort_lines(&mut lines, &opts);
 
+            let mut out = String::new();
             for line in lines {
-                let _ = stdout.write_all(line.as_bytes()).await;
-                let _ = stdout.write_all(b"\n").await;
+                out.push_str(&line);
+                out.push('\n');
             }
+            let _ = stdout.write_all(out.as_bytes()).await;
             0
         })
     }
@@ -789,50 +725,69 @@ impl TextCommands {
 
             while let Some(arg) = parser.next().ok().flatten() {
                 match arg {
-                    Short('a') => append = true,
+                    Short('a') | Long("append") => append = true,
                     Value(val) => files.push(val.string().unwrap_or_default()),
                     _ => {}
                 }
             }
 
-            let mut content = Vec::new();
-            let mut reader = stdin;
-            let mut buf = [0u8; 4096];
-            loop {
-                match futures_lite::io::AsyncReadExt::read(&mut reader, &mut buf).await {
-                    Ok(0) => break,
-                    Ok(n) => content.extend_from_slice(&buf[..n]),
-                    Err(_) => break,
-                }
-            }
-
-            let _ = stdout.write_all(&content).await;
-
+            // Open every file up front; one that can't be opened is reported
+            // and skipped so the rest still get a copy
+            let mut code = 0;
+            let mut outputs: Vec<(String, std::fs::File)> = Vec::new();
             for file in files {
                 let path = resolve_path(&cwd, &file);
-
-                let result = std::fs::OpenOptions::new()
+                match std::fs::OpenOptions::new()
                     .create(true)
                     .write(true)
                     .append(append)
                     .truncate(!append)
                     .open(&path)
-                    .and_then(|mut f| std::io::Write::write_all(&mut f, &content));
+                {
+                    Ok(f) => outputs.push((path, f)),
+                    Err(e) => {
+                        let msg = format!("tee: {}: {}\n", path, e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        code = 1;
+                    }
+                }
+            }
 
-                if let Err(e) = result {
-                    let msg = format!("tee: {}: {}\n", path, e);
-                    let _ = stderr.write_all(msg.as_bytes()).await;
+            // Copy as data arrives rather than after EOF, so tee works mid-pipeline
+            let mut reader = stdin;
+            let mut buf = [0u8; 4096];
+            loop {
+                let n = match futures_lite::io::AsyncReadExt::read(&mut reader, &mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                let _ = stdout.write_all(&buf[..n]).await;
+
+                let mut failed = Vec::new();
+                for (i, (path, f)) in outputs.iter_mut().enumerate() {
+                    if let Err(e) = std::io::Write::write_all(f, &buf[..n]) {
+                        let msg = format!("tee: {}: {}\n", path, e);
+                        let _ = stderr.write_all(msg.as_bytes()).await;
+                        failed.push(i);
+                    }
+                }
+                for i in failed.into_iter().rev() {
+                    outputs.remove(i);
+                    code = 1;
                 }
             }
-            0
+            code
         })
     }
 
     /// sed - stream editor
     #[shell_command(
         name = "sed",
-        usage = "sed [-n] SCRIPT [FILE]...",
-        description = "Stream editor for text transformation"
+        usage = "sed [-nE] [-e SCRIPT]... [SCRIPT] [FILE]...",
+        description = "Stream editor for text transformation.\n\
+        Commands: s/RE/REPL/[gipN], d, p, q, = with optional ADDR[,ADDR][!]\n\
+        Addresses: N, $, /RE/   Separate commands with ; or give several -e\n\
+        -n: Print only what p/s///p print   -E: Extended regex"
     )]
     fn cmd_sed(
         args: Vec<String>,
@@ -844,39 +799,86 @@ impl TextCommands {
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            // Parse -n flag and script
             let mut quiet = false;
-            let mut script_idx = 0;
-            for (i, arg) in remaining.iter().enumerate() {
-                if arg == "-n" {
-                    quiet = true;
-                } else {
-                    script_idx = i;
+            let mut extended = false;
+            let mut scripts: Vec<String> = Vec::new();
+            let mut positional: Vec<String> = Vec::new();
+
+            let mut i = 0;
+            while i < remaining.len() {
+                let arg = &remaining[i];
+                i += 1;
+                if arg == "--" {
+                    positional.extend(remaining[i..].iter().cloned());
                     break;
                 }
+                if let Some(long) = arg.strip_prefix("--") {
+                    match long {
+                        "quiet" | "silent" => quiet = true,
+                        "regexp-extended" => extended = true,
+                        _ => match long.strip_prefix("expression=") {
+                            Some(script) => scripts.push(script.to_string()),
+                            None => {
+                                let msg = format!("sed: unknown option '{}'\n", arg);
+                                let _ = stderr.write_all(msg.as_bytes()).await;
+                                return 1;
+                            }
+                        },
+                    }
+                    continue;
+                }
+                if !arg.starts_with('-') || arg.len() == 1 {
+                    positional.push(arg.clone());
+                    continue;
+                }
+                for (idx, c) in arg.char_indices().skip(1) {
+                    match c {
+                        'n' => quiet = true,
+                        'E' | 'r' => extended = true,
+                        'e' => {
+                            // -eSCRIPT or -e SCRIPT
+                            let rest = &arg[idx + 1..];
+                            if !rest.is_empty() {
+                                scripts.push(rest.to_string());
+                            } else if let Some(script) = remaining.get(i) {
+                                scripts.push(script.clone());
+                                i += 1;
+                            } else {
+                                let _ = stderr
+                                    .write_all(b"sed: option requires an argument -- 'e'\n")
+                                    .await;
+                                return 1;
+                            }
+                            break;
+                        }
+                        _ => {
+                            let msg = format!("sed: invalid option -- '{}'\n", c);
+                            let _ = stderr.write_all(msg.as_bytes()).await;
+                            return 1;
+                        }
+                    }
+                }
             }
 
-            if script_idx >= remaining.len()
-                || (remaining[script_idx] == "-n" && remaining.len() <= script_idx + 1)
-            {
-                let _ = stderr.write_all(b"sed: missing script\n").await;
-                return 1;
-            }
-
-            // Skip -n flags to find the script
-            let mut actual_script_idx = 0;
-            for (i, arg) in remaining.iter().enumerate() {
-                if arg != "-n" {
-                    actual_script_idx = i;
-                    break;
+            // Without -e, the first operand is the script
+            if scripts.is_empty() {
+                if positional.is_empty() {
+                    let _ = stderr.write_all(b"sed: missing script\n").await;
+                    return 1;
                 }
+                scripts.push(positional.remove(0));
             }
+            let files = positional;
 
-            let script = &remaining[actual_script_idx];
-            let files: Vec<_> = remaining[actual_script_idx + 1..].to_vec();
-
-            // Parse the sed command
-            let sed_cmd = parse_sed_command(script);
+            // Several -e scripts run as one, in order
+            let mut commands = match parse_sed_program(&scripts.join("\n"), extended) {
+                Ok(commands) => commands,
+                Err(e) => {
+                    let msg = format!("sed: {}\n", e);
+                    let _ = stderr.write_all(msg.as_bytes()).await;
+                    return 1;
+                }
+            };
 
             // Collect all lines
             let mut all_lines: Vec<String> = Vec::new();
@@ -889,7 +891,7 @@ impl TextCommands {
                 }
             } else {
                 for file in &files {
-                    let path = resolve_path(&cwd, &file);
+                    let path = resolve_path(&cwd, file);
 
                     match std::fs::read_to_string(&path) {
                         Ok(content) => {
@@ -906,68 +908,8 @@ impl TextCommands {
                 }
             }
 
-            let total = all_lines.len();
-
-            for (idx, line) in all_lines.iter().enumerate() {
-                let line_num = idx + 1; // 1-based
-                match &sed_cmd {
-                    SedCommand::Substitute {
-                        pattern,
-                        replacement,
-                        global,
-                        use_regex,
-                    } => {
-                        let result = if *use_regex {
-                            if let Ok(re) = regex::Regex::new(pattern) {
-                                if *global {
-                                    re.replace_all(line, replacement.as_str()).to_string()
-                                } else {
-                                    re.replace(line, replacement.as_str()).to_string()
-                                }
-                            } else if *global {
-                                line.replace(pattern, replacement)
-                            } else {
-                                line.replacen(pattern, replacement, 1)
-                            }
-                        } else if *global {
-                            line.replace(pattern, replacement)
-                        } else {
-                            line.replacen(pattern, replacement, 1)
-                        };
-                        if !quiet {
-                            let _ = stdout.write_all(result.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
-                        }
-                    }
-                    SedCommand::Delete { addr } => {
-                        let should_delete = match addr {
-                            SedAddr::Line(n) => line_num == *n,
-                            SedAddr::Last => line_num == total,
-                            SedAddr::None => true,
-                        };
-                        if !should_delete && !quiet {
-                            let _ = stdout.write_all(line.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
-                        }
-                    }
-                    SedCommand::Print { start, end } => {
-                        // -n mode: only print lines in range
-                        if line_num >= *start && line_num <= *end {
-                            let _ = stdout.write_all(line.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
-                        } else if !quiet {
-                            let _ = stdout.write_all(line.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
-                        }
-                    }
-                    SedCommand::Unknown => {
-                        if !quiet {
-                            let _ = stdout.write_all(line.as_bytes()).await;
-                            let _ = stdout.write_all(b"\n").await;
-                        }
-                    }
-                }
-            }
+            let output = run_sed(&mut commands, &all_lines, quiet);
+            let _ = stdout.write_all(output.as_bytes()).await;
             0
         })
     }
@@ -1465,10 +1407,881 @@ impl TextCommands {
             0
         })
     }
+
+    /// diffstat - summarize a unified diff
+    #[shell_command(
+        name = "diffstat",
+        usage = "diffstat [-w WIDTH] [FILE]",
+        description = "Summarize insertions and deletions per file in a unified diff.\n\
+        -w WIDTH: Maximum histogram width (default 50)"
+    )]
+    fn cmd_diffstat(
+        args: Vec<String>,
+        env: &ShellEnv,
+        stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.to_string_lossy().to_string();
+        Box::pin(async move {
+            let (_, remaining) = parse_common(&args);
+            let mut width: usize = 50;
+            let mut file: Option<String> = None;
+            let mut parser = make_parser(remaining);
+
+            while let Some(arg) = parser.next().ok().flatten() {
+                match arg {
+                    Short('w') => match parser.value().ok().and_then(|v| v.parse().ok()) {
+                        Some(w) if w > 0 => width = w,
+                        _ => {
+                            let _ = stderr.write_all(b"diffstat: -w requires a width\n").await;
+                            return 1;
+                        }
+                    },
+                    Value(val) => file = Some(val.string().unwrap_or_default()),
+                    _ => {}
+                }
+            }
+
+            let diff = if let Some(f) = file {
+                let path = resolve_path(&cwd, &f);
+                match std::fs::read(&path) {
+                    Ok(data) => String::from_utf8_lossy(&data).to_string(),
+                    Err(e) => {
+                        let _ = stderr
+                            .write_all(format!("diffstat: {}: {}\n", f, e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                }
+            } else {
+                use futures_lite::io::AsyncReadExt;
+                let mut content = Vec::new();
+                let mut reader = stdin;
+                let _ = reader.read_to_end(&mut content).await;
+                String::from_utf8_lossy(&content).to_string()
+            };
+
+            let stats = parse_diffstat(&diff);
+            let _ = stdout
+                .write_all(format_diffstat(&stats, width).as_bytes())
+                .await;
+            0
+        })
+    }
+
+    /// cloc - count blank, comment, and code lines per language
+    #[shell_command(
+        name = "cloc",
+        usage = "cloc [PATH]",
+        description = "Count blank, comment, and code lines by language"
+    )]
+    fn cmd_cloc(
+        args: Vec<String>,
+        env: &ShellEnv,
+        _stdin: piper::Reader,
+        mut stdout: piper::Writer,
+        mut stderr: piper::Writer,
+    ) -> futures_lite::future::Boxed<i32> {
+        let cwd = env.cwd.to_string_lossy().to_string();
+        Box::pin(async move {
+            let (_, remaining) = parse_common(&args);
+            let target = remaining
+                .first()
+                .cloned()
+                .unwrap_or_else(|| ".".to_string());
+            let path = resolve_path(&cwd, &target);
+
+            if std::fs::metadata(&path).is_err() {
+                let _ = stderr
+                    .write_all(format!("cloc: {}: No such file or directory\n", target).as_bytes())
+                    .await;
+                return 1;
+            }
+
+            let stats = count_lines_of_code(&path);
+            let _ = stdout.write_all(format_cloc(&stats).as_bytes()).await;
+            0
+        })
+    }
+}
+
+/// How `grep` reads its patterns
+#[derive(Clone, Copy)]
+enum GrepSyntax {
+    /// POSIX basic regex: `( ) { } | + ?` are literal unless backslashed
+    Basic,
+    /// POSIX extended regex (`-E`)
+    Extended,
+    /// Plain strings (`-F`)
+    Fixed,
+}
+
+/// Output options for `grep`
+#[derive(Default)]
+struct GrepOptions {
+    invert: bool,
+    line_numbers: bool,
+    count_only: bool,
+    files_only: bool,
+    quiet: bool,
+}
+
+impl GrepOptions {
+    fn selects(&self, regex: &regex::Regex, line: &str) -> bool {
+        regex.is_match(line) != self.invert
+    }
+
+    /// A selected line, prefixed with its file name and/or line number
+    fn format_line(&self, name: Option<&str>, line_num: usize, line: &str) -> String {
+        let mut out = String::new();
+        if let Some(name) = name {
+            out.push_str(name);
+            out.push(':');
+        }
+        if self.line_numbers {
+            out.push_str(&line_num.to_string());
+            out.push(':');
+        }
+        out.push_str(line);
+        out.push('\n');
+        out
+    }
+
+    /// What follows an input's lines: its count (`-c`) or name (`-l`)
+    fn format_summary(&self, name: &str, match_count: usize, show_filename: bool) -> String {
+        if self.quiet {
+            String::new()
+        } else if self.files_only {
+            if match_count > 0 {
+                format!("{}\n", name)
+            } else {
+                String::new()
+            }
+        } else if self.count_only {
+            if show_filename {
+                format!("{}:{}\n", name, match_count)
+            } else {
+                format!("{}\n", match_count)
+            }
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Compile `grep` patterns into one regex matching any of them
+fn build_grep_regex(
+    patterns: &[String],
+    syntax: GrepSyntax,
+    ignore_case: bool,
+    word: bool,
+    whole_line: bool,
+) -> Result<regex::Regex, regex::Error> {
+    // A pattern argument holding several lines is several patterns
+    let alternatives: Vec<String> = patterns
+        .iter()
+        .flat_map(|p| p.split('\n'))
+        .map(|p| {
+            let re = match syntax {
+                GrepSyntax::Basic => posix_to_regex(p, false),
+                GrepSyntax::Extended => posix_to_regex(p, true),
+                GrepSyntax::Fixed => regex::escape(p),
+            };
+            if whole_line {
+                format!("^(?:{})$", re)
+            } else if word {
+                format!(r"\b(?:{})\b", re)
+            } else {
+                format!("(?:{})", re)
+            }
+        })
+        .collect();
+    regex::RegexBuilder::new(&alternatives.join("|"))
+        .case_insensitive(ignore_case)
+        .build()
+}
+
+/// Translate a POSIX basic (or, with `extended`, extended) regex to the
+/// `regex` crate's syntax
+pub(super) fn posix_to_regex(pattern: &str, extended: bool) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if i + 1 < chars.len() => {
+                let next = chars[i + 1];
+                i += 2;
+                match next {
+                    // GNU word boundaries
+                    '<' | '>' => out.push_str(r"\b"),
+                    'w' | 'W' | 's' | 'S' | 'b' | 'B' | 'n' | 't' => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                    // In BRE these are the operators; in ERE, escaped literals
+                    '(' | ')' | '{' | '}' | '|' | '+' | '?' if !extended => out.push(next),
+                    _ => out.push_str(&regex::escape(&next.to_string())),
+                }
+                continue;
+            }
+            '(' | ')' | '{' | '}' | '|' | '+' | '?' if !extended => {
+                out.push_str(&regex::escape(&c.to_string()));
+            }
+            // A leading `*` has nothing to repeat, so it is literal
+            '*' if i == 0 || (i == 1 && chars[0] == '^') => out.push_str(r"\*"),
+            '[' => match posix_bracket(&chars, i) {
+                Some((class, end)) => {
+                    out.push_str(&class);
+                    i = end;
+                    continue;
+                }
+                None => out.push_str(r"\["),
+            },
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Translate the bracket expression starting at `chars[start]`, returning it
+/// and the index just past its closing `]`
+fn posix_bracket(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut out = String::from("[");
+    let mut i = start + 1;
+    if chars.get(i) == Some(&'^') {
+        out.push('^');
+        i += 1;
+    }
+    // A `]` first in the list is literal
+    if chars.get(i) == Some(&']') {
+        out.push_str(r"\]");
+        i += 1;
+    }
+    while i < chars.len() {
+        match chars[i] {
+            ']' => {
+                out.push(']');
+                return Some((out, i + 1));
+            }
+            // Character classes like [:alpha:] pass through
+            '[' if chars.get(i + 1) == Some(&':') => {
+                let close = (i + 2..chars.len().saturating_sub(1))
+                    .find(|&j| chars[j] == ':' && chars[j + 1] == ']')?;
+                out.extend(&chars[i..close + 2]);
+                i = close + 2;
+                continue;
+            }
+            // Literal inside POSIX brackets, special inside regex crate classes
+            c @ ('\\' | '[' | '&' | '~') => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Options for `sort`
+#[derive(Default)]
+struct SortOptions {
+    reverse: bool,
+    numeric: bool,
+    unique: bool,
+    /// 1-based first and (inclusive) last field; no last field means to end of line
+    key: Option<(usize, Option<usize>)>,
+    /// Field separator; fields are runs of non-blanks when unset
+    separator: Option<String>,
+}
+
+/// Parse a `-k` KEYDEF like `2` or `2,3`. Per-key options such as `2n` are
+/// accepted but ignored.
+fn parse_sort_key(spec: &str) -> Option<(usize, Option<usize>)> {
+    let field = |s: &str| -> Option<usize> {
+        let digits: String = s.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok().filter(|&n| n > 0)
+    };
+    match spec.split_once(',') {
+        Some((start, end)) => Some((field(start)?, Some(field(end)?))),
+        None => Some((field(spec)?, None)),
+    }
+}
+
+/// The part of `line` that `sort` compares
+fn sort_key<'a>(line: &'a str, opts: &SortOptions) -> &'a str {
+    let Some((first, last)) = opts.key else {
+        return line;
+    };
+
+    // Byte spans of each field
+    let spans: Vec<(usize, usize)> = match opts.separator.as_deref() {
+        Some(sep) if !sep.is_empty() => {
+            let mut spans = Vec::new();
+            let mut start = 0;
+            for (idx, _) in line.match_indices(sep) {
+                spans.push((start, idx));
+                start = idx + sep.len();
+            }
+            spans.push((start, line.len()));
+            spans
+        }
+        _ => {
+            let mut spans = Vec::new();
+            let mut start = None;
+            for (idx, c) in line.char_indices() {
+                match (c.is_whitespace(), start) {
+                    (false, None) => start = Some(idx),
+                    (true, Some(s)) => {
+                        spans.push((s, idx));
+                        start = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(s) = start {
+                spans.push((s, line.len()));
+            }
+            spans
+        }
+    };
+
+    let Some(&(from, _)) = spans.get(first - 1) else {
+        return "";
+    };
+    let to = match last {
+        Some(last) if last >= first => spans.get(last - 1).map_or(line.len(), |&(_, end)| end),
+        Some(_) => from,
+        None => line.len(),
+    };
+    &line[from..to]
+}
+
+/// Leading number of a sort key, like `sort -n`: optional blanks, sign,
+/// digits, and a fraction. Anything else counts as zero.
+fn leading_number(key: &str) -> f64 {
+    let s = key.trim_start();
+    let bytes = s.as_bytes();
+    let mut end = 0;
+    if end < bytes.len() && (bytes[end] == b'-' || bytes[end] == b'+') {
+        end += 1;
+    }
+    let mut has_dot = false;
+    while end < bytes.len() {
+        if bytes[end].is_ascii_digit() {
+            end += 1;
+        } else if bytes[end] == b'.' && !has_dot {
+            has_dot = true;
+            end += 1;
+        } else {
+            break;
+        }
+    }
+    s[..end].parse().unwrap_or(0.0)
+}
+
+/// Sort lines as coreutils `sort` does: by key, then (unless `-u`) by the
+/// whole line as a last resort so equal keys still order predictably.
+fn sort_lines(lines: &mut Vec<String>, opts: &SortOptions) {
+    use std::cmp::Ordering;
+
+    let compare_keys = |a: &str, b: &str| -> Ordering {
+        let (ka, kb) = (sort_key(a, opts), sort_key(b, opts));
+        if opts.numeric {
+            leading_number(ka)
+                .partial_cmp(&leading_number(kb))
+                .unwrap_or(Ordering::Equal)
+        } else {
+            ka.cmp(kb)
+        }
+    };
+
+    lines.sort_by(|a, b| {
+        let mut cmp = compare_keys(a, b);
+        if cmp == Ordering::Equal && !opts.unique {
+            cmp = a.cmp(b);
+        }
+        if opts.reverse {
+            cmp.reverse()
+        } else {
+            cmp
+        }
+    });
+
+    if opts.unique {
+        lines.dedup_by(|b, a| compare_keys(a, b) == Ordering::Equal);
+    }
+}
+
+/// Which counts `wc` prints
+#[derive(Default)]
+struct WcColumns {
+    lines: bool,
+    words: bool,
+    chars: bool,
+    bytes: bool,
+}
+
+/// Counts for one `wc` input
+#[derive(Debug, Default, PartialEq)]
+struct WcCounts {
+    lines: usize,
+    words: usize,
+    chars: usize,
+    bytes: usize,
+}
+
+impl WcCounts {
+    fn of(data: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(data);
+        WcCounts {
+            lines: data.iter().filter(|&&b| b == b'\n').count(),
+            words: text.split_whitespace().count(),
+            chars: text.chars().count(),
+            bytes: data.len(),
+        }
+    }
+
+    fn add(&mut self, other: &WcCounts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+    }
+}
+
+/// One line of `wc` output: the selected counts in lines, words, chars, bytes
+/// order, right-aligned in columns unless a single bare count is printed
+fn format_wc_line(counts: &WcCounts, columns: &WcColumns, name: Option<&str>) -> String {
+    let selected: Vec<usize> = [
+        (columns.lines, counts.lines),
+        (columns.words, counts.words),
+        (columns.chars, counts.chars),
+        (columns.bytes, counts.bytes),
+    ]
+    .iter()
+    .filter(|(shown, _)| *shown)
+    .map(|(_, n)| *n)
+    .collect();
+
+    let mut out = if selected.len() == 1 && name.is_none() {
+        selected[0].to_string()
+    } else {
+        selected
+            .iter()
+            .map(|n| format!("{:>7}", n))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    if let Some(name) = name {
+        out.push(' ');
+        out.push_str(name);
+    }
+    out.push('\n');
+    out
+}
+
+/// Comment syntax for a language counted by `cloc`
+struct ClocLanguage {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    line_comment: &'static str,
+    block_comment: Option<(&'static str, &'static str)>,
+}
+
+const CLOC_LANGUAGES: &[ClocLanguage] = &[
+    ClocLanguage {
+        name: "JavaScript",
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        line_comment: "//",
+        block_comment: Some(("/*", "*/")),
+    },
+    ClocLanguage {
+        name: "TypeScript",
+        extensions: &["ts", "tsx", "mts", "cts"],
+        line_comment: "//",
+        block_comment: Some(("/*", "*/")),
+    },
+    ClocLanguage {
+        name: "Rust",
+        extensions: &["rs"],
+        line_comment: "//",
+        block_comment: Some(("/*", "*/")),
+    },
+    ClocLanguage {
+        name: "Python",
+        extensions: &["py"],
+        line_comment: "#",
+        block_comment: None,
+    },
+    ClocLanguage {
+        name: "Shell",
+        extensions: &["sh", "bash", "zsh"],
+        line_comment: "#",
+        block_comment: None,
+    },
+];
+
+/// Per-language totals reported by `cloc`
+#[derive(Debug, Default, PartialEq)]
+struct ClocStats {
+    files: usize,
+    blank: usize,
+    comment: usize,
+    code: usize,
+}
+
+/// Classify each line as blank, comment, or code. A line with any code
+/// outside a comment counts as code.
+fn count_source_lines(text: &str, lang: &ClocLanguage, stats: &mut ClocStats) {
+    let mut in_block = false;
+    for line in text.lines() {
+        let mut rest = line.trim();
+        if rest.is_empty() {
+            stats.blank += 1;
+            continue;
+        }
+
+        let mut has_code = false;
+        while !rest.is_empty() {
+            if in_block {
+                let close = lang
+                    .block_comment
+                    .map(|(_, close)| close)
+                    .unwrap_or_default();
+                match rest.find(close) {
+                    Some(idx) => {
+                        in_block = false;
+                        rest = rest[idx + close.len()..].trim_start();
+                    }
+                    None => break,
+                }
+            } else if rest.starts_with(lang.line_comment) {
+                break;
+            } else if let Some(after) = lang
+                .block_comment
+                .and_then(|(open, _)| rest.strip_prefix(open))
+            {
+                in_block = true;
+                rest = after;
+            } else {
+                has_code = true;
+                break;
+            }
+        }
+
+        if has_code {
+            stats.code += 1;
+        } else {
+            stats.comment += 1;
+        }
+    }
+}
+
+/// Patterns from one `.gitignore`, relative to the directory holding it
+struct GitignoreRules {
+    base: String,
+    patterns: Vec<(String, bool)>,
+}
+
+fn read_gitignore(dir: &str) -> Option<GitignoreRules> {
+    let content = std::fs::read_to_string(format!("{}/.gitignore", dir)).ok()?;
+    let patterns = content
+        .lines()
+        .map(str::trim)
+        // Negations are not supported; they are skipped rather than misapplied
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .map(|l| match l.strip_suffix('/') {
+            Some(dir_only) => (dir_only.to_string(), true),
+            None => (l.to_string(), false),
+        })
+        .collect();
+    Some(GitignoreRules {
+        base: dir.to_string(),
+        patterns,
+    })
+}
+
+fn is_gitignored(rules: &[GitignoreRules], path: &str, is_dir: bool) -> bool {
+    rules.iter().any(|r| {
+        let relative = path
+            .strip_prefix(&r.base)
+            .map(|p| p.trim_start_matches('/'))
+            .unwrap_or(path);
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        r.patterns.iter().any(|(pattern, dir_only)| {
+            if *dir_only && !is_dir {
+                return false;
+            }
+            // Patterns with a slash are anchored to the .gitignore's directory
+            match pattern.trim_start_matches('/') {
+                p if pattern.contains('/') => super::file::glob_match(p, relative),
+                p => super::file::glob_match(p, name),
+            }
+        })
+    })
+}
+
+fn count_lines_in_dir(
+    dir: &str,
+    rules: &mut Vec<GitignoreRules>,
+    stats: &mut std::collections::BTreeMap<&'static str, ClocStats>,
+) {
+    let pushed = match read_gitignore(dir) {
+        Some(r) => {
+            rules.push(r);
+            true
+        }
+        None => false,
+    };
+
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = format!("{}/{}", dir.trim_end_matches('/'), name);
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_gitignored(rules, &path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                if !name.starts_with('.') {
+                    count_lines_in_dir(&path, rules, stats);
+                }
+            } else {
+                count_file_lines(&path, stats);
+            }
+        }
+    }
+
+    if pushed {
+        rules.pop();
+    }
+}
+
+fn count_file_lines(path: &str, stats: &mut std::collections::BTreeMap<&'static str, ClocStats>) {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let Some(lang) = CLOC_LANGUAGES
+        .iter()
+        .find(|l| l.extensions.contains(&ext.as_str()))
+    else {
+        return;
+    };
+    // Binary or unreadable files are skipped
+    if let Ok(text) = std::fs::read_to_string(path) {
+        let entry = stats.entry(lang.name).or_default();
+        entry.files += 1;
+        count_source_lines(&text, lang, entry);
+    }
+}
+
+/// Count a file or directory tree, keyed by language name
+fn count_lines_of_code(path: &str) -> std::collections::BTreeMap<&'static str, ClocStats> {
+    let mut stats = std::collections::BTreeMap::new();
+    if std::path::Path::new(path).is_dir() {
+        count_lines_in_dir(path, &mut Vec::new(), &mut stats);
+    } else {
+        count_file_lines(path, &mut stats);
+    }
+    stats
+}
+
+/// Table of languages ordered by code lines, with a SUM row
+fn format_cloc(stats: &std::collections::BTreeMap<&'static str, ClocStats>) -> String {
+    let mut rows: Vec<(&str, &ClocStats)> = stats.iter().map(|(k, v)| (*k, v)).collect();
+    rows.sort_by(|a, b| b.1.code.cmp(&a.1.code).then_with(|| a.0.cmp(b.0)));
+
+    let rule = "-".repeat(54);
+    let mut out = format!(
+        "{}\n{:<14}{:>10}{:>10}{:>10}{:>10}\n{}\n",
+        rule, "Language", "files", "blank", "comment", "code", rule
+    );
+    let mut total = ClocStats::default();
+    for (name, s) in rows {
+        out.push_str(&format!(
+            "{:<14}{:>10}{:>10}{:>10}{:>10}\n",
+            name, s.files, s.blank, s.comment, s.code
+        ));
+        total.files += s.files;
+        total.blank += s.blank;
+        total.comment += s.comment;
+        total.code += s.code;
+    }
+    out.push_str(&format!(
+        "{}\n{:<14}{:>10}{:>10}{:>10}{:>10}\n{}\n",
+        rule, "SUM", total.files, total.blank, total.comment, total.code, rule
+    ));
+    out
+}
+
+/// Insertions and deletions for one file of a unified diff
+#[derive(Debug, PartialEq)]
+struct FileDiffStat {
+    path: String,
+    insertions: usize,
+    deletions: usize,
+}
+
+/// Path named by a `---`/`+++` header, without the `a/`/`b/` prefix or timestamp
+fn diff_header_path(header: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" || path.is_empty() {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Old and new line counts from `@@ -o[,ol] +n[,nl] @@`
+fn parse_hunk_counts(line: &str) -> Option<(usize, usize)> {
+    let mut ranges = line.strip_prefix("@@ ")?.split(' ');
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, n)) => n.parse().ok(),
+        None => Some(1),
+    };
+    let old = count(ranges.next()?.strip_prefix('-')?)?;
+    let new = count(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+/// Count changed lines per file. Hunk headers give the line counts, so
+/// removed lines that look like `--- ` headers are still counted.
+fn parse_diffstat(diff: &str) -> Vec<FileDiffStat> {
+    let mut stats: Vec<FileDiffStat> = Vec::new();
+    let mut old_path: Option<String> = None;
+    let mut new_path: Option<String> = None;
+    let mut git_path: Option<String> = None;
+    let (mut old_left, mut new_left) = (0usize, 0usize);
+
+    for line in diff.lines() {
+        if old_left > 0 || new_left > 0 {
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    new_left = new_left.saturating_sub(1);
+                    if let Some(stat) = stats.last_mut() {
+                        stat.insertions += 1;
+                    }
+                }
+                Some(b'-') => {
+                    old_left = old_left.saturating_sub(1);
+                    if let Some(stat) = stats.last_mut() {
+                        stat.deletions += 1;
+                    }
+                }
+                Some(b'\\') => {}
+                _ => {
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            git_path = rest.rsplit_once(" b/").map(|(_, p)| p.to_string());
+            old_path = None;
+            new_path = None;
+        } else if let Some(rest) = line.strip_prefix("--- ") {
+            old_path = diff_header_path(rest);
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            new_path = diff_header_path(rest);
+        } else if let Some((old, new)) = parse_hunk_counts(line) {
+            let path = new_path
+                .clone()
+                .or_else(|| old_path.clone())
+                .or_else(|| git_path.clone())
+                .unwrap_or_default();
+            if stats.last().map(|s| &s.path) != Some(&path) {
+                stats.push(FileDiffStat {
+                    path,
+                    insertions: 0,
+                    deletions: 0,
+                });
+            }
+            old_left = old;
+            new_left = new;
+        }
+    }
+    stats
+}
+
+/// Render per-file rows with a `+`/`-` histogram scaled to `width`, then a total line
+fn format_diffstat(stats: &[FileDiffStat], width: usize) -> String {
+    let name_width = stats.iter().map(|s| s.path.len()).max().unwrap_or(0);
+    let max_changes = stats
+        .iter()
+        .map(|s| s.insertions + s.deletions)
+        .max()
+        .unwrap_or(0);
+    let count_width = max_changes.to_string().len();
+
+    let mut out = String::new();
+    for stat in stats {
+        let changes = stat.insertions + stat.deletions;
+        let bar = if max_changes <= width {
+            changes
+        } else {
+            // Scale down, but keep any change visible
+            (changes * width / max_changes).max(usize::from(changes > 0))
+        };
+        let plus = (stat.insertions * bar + changes / 2)
+            .checked_div(changes)
+            .unwrap_or(0)
+            .max(usize::from(stat.insertions > 0 && bar > 1))
+            .min(bar - usize::from(stat.deletions > 0 && bar > 1));
+        out.push_str(&format!(
+            " {:<nw$} | {:>cw$} {}{}\n",
+            stat.path,
+            changes,
+            "+".repeat(plus),
+            "-".repeat(bar - plus),
+            nw = name_width,
+            cw = count_width,
+        ));
+    }
+
+    let insertions: usize = stats.iter().map(|s| s.insertions).sum();
+    let deletions: usize = stats.iter().map(|s| s.deletions).sum();
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    let mut total = format!(" {}", plural(stats.len(), "file changed", "files changed"));
+    if insertions > 0 || deletions == 0 {
+        total.push_str(&format!(
+            ", {}",
+            plural(insertions, "insertion(+)", "insertions(+)")
+        ));
+    }
+    if deletions > 0 || insertions == 0 {
+        total.push_str(&format!(
+            ", {}",
+            plural(deletions, "deletion(-)", "deletions(-)")
+        ));
+    }
+    out.push_str(&total);
+    out.push('\n');
+    out
 }
 
-/// Recursively collect files for grep -r
-fn collect_files_recursive(dir: &str, display_base: &str, results: &mut Vec<(String, String)>) {
+/// Recursively collect files for grep -r, skipping ignored paths when
+/// `ignore` is given. `relative` is the path below the search root.
+fn collect_files_recursive(
+    dir: &str,
+    display_base: &str,
+    relative: &str,
+    mut ignore: Option<&mut GitIgnore>,
+    results: &mut Vec<(String, String)>,
+) {
     let meta = match std::fs::metadata(dir) {
         Ok(m) => m,
         Err(_) => return,
@@ -1481,142 +2294,410 @@ fn collect_files_recursive(dir: &str, display_base: &str, results: &mut Vec<(Str
         return;
     }
     if let Ok(entries) = std::fs::read_dir(dir) {
+        if let Some(rules) = ignore.as_deref_mut() {
+            rules.enter(dir, relative);
+        }
         let mut sorted: Vec<_> = entries.filter_map(|e| e.ok()).collect();
         sorted.sort_by_key(|e| e.file_name());
         for entry in sorted {
             let name = entry.file_name().to_string_lossy().to_string();
             let child_path = entry.path().to_string_lossy().to_string();
             let child_display = format!("{}/{}", display_base, name);
-            if entry.path().is_dir() {
-                collect_files_recursive(&child_path, &child_display, results);
+            let child_relative = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            let is_dir = entry.path().is_dir();
+            if ignore
+                .as_deref()
+                .is_some_and(|rules| rules.is_ignored(&child_relative, is_dir))
+            {
+                continue;
+            }
+            if is_dir {
+                collect_files_recursive(
+                    &child_path,
+                    &child_display,
+                    &child_relative,
+                    ignore.as_deref_mut(),
+                    results,
+                );
             } else {
                 results.push((child_display, child_path));
             }
         }
+        if let Some(rules) = ignore {
+            rules.leave();
+        }
     }
 }
 
-/// Sed address types
+/// A sed line address
 enum SedAddr {
     Line(usize),
     Last,
-    None,
+    Regex(regex::Regex),
 }
 
-/// Parsed sed command
-enum SedCommand {
-    Substitute {
-        pattern: String,
-        replacement: String,
-        global: bool,
-        use_regex: bool,
-    },
-    Delete {
-        addr: SedAddr,
-    },
-    Print {
-        start: usize,
-        end: usize,
-    },
-    Unknown,
+impl SedAddr {
+    fn matches(&self, line_num: usize, total: usize, text: &str) -> bool {
+        match self {
+            SedAddr::Line(n) => line_num == *n,
+            SedAddr::Last => line_num == total,
+            SedAddr::Regex(re) => re.is_match(text),
+        }
+    }
 }
 
-/// Parse a sed command (supports s///, Nd, $d, N,Mp)
-fn parse_sed_command(script: &str) -> SedCommand {
-    let trimmed = script.trim();
-
-    // Handle address + command: "1d", "$d", "2,4p"
-    // Range print: N,Mp
-    if let Some(comma_pos) = trimmed.find(',') {
-        let before = &trimmed[..comma_pos];
-        let after = &trimmed[comma_pos + 1..];
-        if after.ends_with('p') {
-            let start: usize = before.parse().unwrap_or(1);
-            let end_str = &after[..after.len() - 1];
-            let end: usize = end_str.parse().unwrap_or(usize::MAX);
-            return SedCommand::Print { start, end };
-        }
+/// The `s` command's pattern, replacement, and flags
+struct SedSubstitution {
+    regex: regex::Regex,
+    /// Replacement with `&` and `\N` still unexpanded
+    replacement: String,
+    /// 1-based match to replace first (the `N` flag)
+    occurrence: usize,
+    /// Also replace every match after `occurrence` (the `g` flag)
+    global: bool,
+    /// Print the pattern space when a replacement was made (the `p` flag)
+    print: bool,
+}
+
+enum SedAction {
+    Substitute(SedSubstitution),
+    Delete,
+    Print,
+    PrintLineNumber,
+    Quit,
+}
+
+/// One command of a sed script, with its optional address range
+struct SedCommand {
+    start: Option<SedAddr>,
+    end: Option<SedAddr>,
+    negate: bool,
+    /// Whether a two-address range has started and not yet ended
+    in_range: bool,
+    action: SedAction,
+}
+
+impl SedCommand {
+    /// Whether this command applies to the current line, advancing range state
+    fn selects(&mut self, line_num: usize, total: usize, text: &str) -> bool {
+        let selected = match (&self.start, &self.end) {
+            (None, _) => true,
+            (Some(start), None) => start.matches(line_num, total, text),
+            (Some(start), Some(end)) => {
+                if self.in_range {
+                    let ended = match end {
+                        SedAddr::Line(n) => line_num >= *n,
+                        _ => end.matches(line_num, total, text),
+                    };
+                    self.in_range = !ended;
+                    true
+                } else if start.matches(line_num, total, text) {
+                    // The end address is only checked from the next line on,
+                    // but a line number already reached ends the range at once
+                    self.in_range = match end {
+                        SedAddr::Line(n) => *n > line_num,
+                        SedAddr::Last => line_num != total,
+                        SedAddr::Regex(_) => true,
+                    };
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        selected != self.negate
     }
+}
 
-    // Delete command: Nd or $d
-    if trimmed.ends_with('d') {
-        let addr_str = &trimmed[..trimmed.len() - 1];
-        let addr = if addr_str == "$" {
-            SedAddr::Last
-        } else if let Ok(n) = addr_str.parse::<usize>() {
-            SedAddr::Line(n)
-        } else {
-            SedAddr::None
+/// Parse a sed script: commands separated by `;` or newlines
+fn parse_sed_program(script: &str, extended: bool) -> Result<Vec<SedCommand>, String> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut commands = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < chars.len() && (chars[i].is_whitespace() || chars[i] == ';') {
+            i += 1;
+        }
+        if i >= chars.len() {
+            return Ok(commands);
+        }
+
+        let start = parse_sed_addr(&chars, &mut i, extended)?;
+        let mut end = None;
+        if start.is_some() && chars.get(i) == Some(&',') {
+            i += 1;
+            end = parse_sed_addr(&chars, &mut i, extended)?;
+            if end.is_none() {
+                return Err("unexpected `,'".to_string());
+            }
+        }
+        skip_sed_blanks(&chars, &mut i);
+        let negate = chars.get(i) == Some(&'!');
+        if negate {
+            i += 1;
+            skip_sed_blanks(&chars, &mut i);
+        }
+
+        let Some(&c) = chars.get(i) else {
+            return Err("missing command".to_string());
+        };
+        i += 1;
+        let action = match c {
+            's' => SedAction::Substitute(parse_sed_substitution(&chars, &mut i, extended)?),
+            'd' => SedAction::Delete,
+            'p' => SedAction::Print,
+            '=' => SedAction::PrintLineNumber,
+            'q' => SedAction::Quit,
+            _ => return Err(format!("unknown command: `{}'", c)),
         };
-        return SedCommand::Delete { addr };
-    }
-
-    // Substitution: s/pattern/replacement/flags
-    if trimmed.starts_with('s') && trimmed.len() >= 4 {
-        let delim = trimmed.chars().nth(1).unwrap();
-        let rest = &trimmed[2..];
-        // Split by delimiter, handling escaped delimiters
-        let parts = split_sed_parts(rest, delim);
-        if parts.len() >= 2 {
-            let raw_pattern = parts[0].clone();
-            let replacement = parts[1].clone();
-            let flags = parts.get(2).map(|s| s.as_str()).unwrap_or("");
-            let global = flags.contains('g');
-            // Check if pattern contains regex metacharacters
-            let use_regex = raw_pattern.contains('[')
-                || raw_pattern.contains('\\')
-                || raw_pattern.contains('+')
-                || raw_pattern.contains('*')
-                || raw_pattern.contains('(')
-                || raw_pattern.contains('.')
-                || raw_pattern.contains('^')
-                || raw_pattern.contains('$');
-            // Convert BRE (Basic Regular Expression) to ERE for regex crate:
-            // BRE uses \+, \?, \{, \}, \(, \) while ERE uses +, ?, {, }, (, )
-            let pattern = if use_regex {
-                raw_pattern
-                    .replace("\\+", "+")
-                    .replace("\\?", "?")
-                    .replace("\\(", "(")
-                    .replace("\\)", ")")
-                    .replace("\\{", "{")
-                    .replace("\\}", "}")
+
+        skip_sed_blanks(&chars, &mut i);
+        if let Some(&c) = chars.get(i) {
+            if c != ';' && c != '\n' {
+                return Err(format!("extra characters after command: `{}'", c));
+            }
+        }
+        commands.push(SedCommand {
+            start,
+            end,
+            negate,
+            in_range: false,
+            action,
+        });
+    }
+}
+
+fn skip_sed_blanks(chars: &[char], i: &mut usize) {
+    while *i < chars.len() && (chars[*i] == ' ' || chars[*i] == '\t') {
+        *i += 1;
+    }
+}
+
+/// Parse a line address (`N`, `$`, `/RE/`, `\cREc`) if one starts at `chars[*i]`
+fn parse_sed_addr(
+    chars: &[char],
+    i: &mut usize,
+    extended: bool,
+) -> Result<Option<SedAddr>, String> {
+    match chars.get(*i) {
+        Some(c) if c.is_ascii_digit() => {
+            let begin = *i;
+            while *i < chars.len() && chars[*i].is_ascii_digit() {
+                *i += 1;
+            }
+            let digits: String = chars[begin..*i].iter().collect();
+            match digits.parse::<usize>() {
+                Ok(0) => Err("invalid usage of line address 0".to_string()),
+                Ok(n) => Ok(Some(SedAddr::Line(n))),
+                Err(_) => Err(format!("invalid line address: {}", digits)),
+            }
+        }
+        Some('$') => {
+            *i += 1;
+            Ok(Some(SedAddr::Last))
+        }
+        Some(&c) if c == '/' || c == '\\' => {
+            let delim = if c == '\\' {
+                *i += 1;
+                *chars.get(*i).ok_or("unterminated address regex")?
             } else {
-                raw_pattern
-            };
-            return SedCommand::Substitute {
-                pattern,
-                replacement,
-                global,
-                use_regex,
+                c
             };
+            *i += 1;
+            let pattern = read_sed_delimited(chars, i, delim)
+                .ok_or_else(|| "unterminated address regex".to_string())?;
+            // A trailing I makes the address case-insensitive
+            let ignore_case = chars.get(*i) == Some(&'I');
+            if ignore_case {
+                *i += 1;
+            }
+            let regex = regex::RegexBuilder::new(&posix_to_regex(&pattern, extended))
+                .case_insensitive(ignore_case)
+                .build()
+                .map_err(|e| format!("invalid regex: {}", e))?;
+            Ok(Some(SedAddr::Regex(regex)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse `s` after the command letter: `/RE/REPL/FLAGS` with any delimiter
+fn parse_sed_substitution(
+    chars: &[char],
+    i: &mut usize,
+    extended: bool,
+) -> Result<SedSubstitution, String> {
+    let unterminated = || "unterminated `s' command".to_string();
+    let delim = *chars.get(*i).ok_or_else(unterminated)?;
+    if delim == '\\' || delim == '\n' {
+        return Err("unterminated `s' command".to_string());
+    }
+    *i += 1;
+    let pattern = read_sed_delimited(chars, i, delim).ok_or_else(unterminated)?;
+    let replacement = read_sed_delimited(chars, i, delim).ok_or_else(unterminated)?;
+
+    let mut occurrence = None;
+    let mut global = false;
+    let mut print = false;
+    let mut ignore_case = false;
+    while let Some(&c) = chars.get(*i) {
+        match c {
+            'g' => global = true,
+            'p' => print = true,
+            'i' | 'I' => ignore_case = true,
+            '0'..='9' => {
+                let begin = *i;
+                while *i < chars.len() && chars[*i].is_ascii_digit() {
+                    *i += 1;
+                }
+                let digits: String = chars[begin..*i].iter().collect();
+                match digits.parse::<usize>() {
+                    Ok(n) if n > 0 => occurrence = Some(n),
+                    _ => return Err("number option to `s' command may not be zero".to_string()),
+                }
+                continue;
+            }
+            ';' | '\n' | ' ' | '\t' => break,
+            _ => return Err("unknown option to `s'".to_string()),
         }
+        *i += 1;
     }
 
-    SedCommand::Unknown
+    let regex = regex::RegexBuilder::new(&posix_to_regex(&pattern, extended))
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| format!("invalid regex: {}", e))?;
+    Ok(SedSubstitution {
+        regex,
+        replacement,
+        occurrence: occurrence.unwrap_or(1),
+        global,
+        print,
+    })
 }
 
-/// Split sed s command parts by delimiter, handling escapes
-fn split_sed_parts(s: &str, delim: char) -> Vec<String> {
-    let mut parts = Vec::new();
-    let mut current = String::new();
-    let mut escaped = false;
-    for c in s.chars() {
-        if escaped {
-            current.push(c);
-            escaped = false;
-        } else if c == '\\' {
-            escaped = true;
-            current.push(c);
-        } else if c == delim {
-            parts.push(current.clone());
-            current.clear();
+/// Read up to the next unescaped `delim`, consuming it. An escaped delimiter
+/// becomes a plain one; other escapes are kept for the regex or replacement.
+fn read_sed_delimited(chars: &[char], i: &mut usize, delim: char) -> Option<String> {
+    let mut out = String::new();
+    while let Some(&c) = chars.get(*i) {
+        *i += 1;
+        if c == delim {
+            return Some(out);
+        }
+        if c == '\\' {
+            let next = *chars.get(*i)?;
+            *i += 1;
+            if next != delim {
+                out.push('\\');
+            }
+            out.push(next);
         } else {
-            current.push(c);
+            out.push(c);
+        }
+    }
+    None
+}
+
+/// Apply a substitution, or return `None` when nothing was replaced
+fn sed_substitute(text: &str, sub: &SedSubstitution) -> Option<String> {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut replaced = false;
+    for (idx, caps) in sub.regex.captures_iter(text).enumerate() {
+        let n = idx + 1;
+        if n < sub.occurrence {
+            continue;
+        }
+        if n > sub.occurrence && !sub.global {
+            break;
         }
+        let whole = caps.get(0).expect("group 0 always matches");
+        out.push_str(&text[last..whole.start()]);
+        expand_sed_replacement(&sub.replacement, &caps, &mut out);
+        last = whole.end();
+        replaced = true;
     }
-    parts.push(current);
-    parts
+    if !replaced {
+        return None;
+    }
+    out.push_str(&text[last..]);
+    Some(out)
+}
+
+/// Expand `&` (the whole match), `\1`-`\9` (groups), `\n`, and `\t`
+fn expand_sed_replacement(template: &str, caps: &regex::Captures, out: &mut String) {
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => out.push_str(&caps[0]),
+            '\\' => match chars.next() {
+                Some(d @ '0'..='9') => {
+                    let group = d.to_digit(10).unwrap_or(0) as usize;
+                    if let Some(m) = caps.get(group) {
+                        out.push_str(m.as_str());
+                    }
+                }
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Run parsed sed commands over the input lines, returning the output
+fn run_sed(commands: &mut [SedCommand], lines: &[String], quiet: bool) -> String {
+    let mut out = String::new();
+    let total = lines.len();
+    'lines: for (idx, line) in lines.iter().enumerate() {
+        let line_num = idx + 1;
+        let mut text = line.clone();
+        let mut quit = false;
+        for cmd in commands.iter_mut() {
+            if !cmd.selects(line_num, total, &text) {
+                continue;
+            }
+            match &cmd.action {
+                SedAction::Substitute(sub) => {
+                    if let Some(replaced) = sed_substitute(&text, sub) {
+                        text = replaced;
+                        if sub.print {
+                            out.push_str(&text);
+                            out.push('\n');
+                        }
+                    }
+                }
+                SedAction::Delete => continue 'lines,
+                SedAction::Print => {
+                    out.push_str(&text);
+                    out.push('\n');
+                }
+                SedAction::PrintLineNumber => {
+                    out.push_str(&line_num.to_string());
+                    out.push('\n');
+                }
+                SedAction::Quit => {
+                    quit = true;
+                    break;
+                }
+            }
+        }
+        if !quiet {
+            out.push_str(&text);
+            out.push('\n');
+        }
+        if quit {
+            break;
+        }
+    }
+    out
 }
 
 /// Legacy parse for unit tests
@@ -1707,6 +2788,133 @@ fn expand_char_set(s: &str) -> Vec<char> {
 mod tests {
     use super::*;
 
+    fn sorted(input: &[&str], opts: &SortOptions) -> Vec<String> {
+        let mut lines: Vec<String> = input.iter().map(|s| s.to_string()).collect();
+        sort_lines(&mut lines, opts);
+        lines
+    }
+
+    #[test]
+    fn test_sort_numeric_vs_lexicographic() {
+        let input = ["10", "9", "-2", "abc", "1.5", "100"];
+        assert_eq!(
+            sorted(&input, &SortOptions::default()),
+            ["-2", "1.5", "10", "100", "9", "abc"]
+        );
+        let numeric = SortOptions {
+            numeric: true,
+            ..Default::default()
+        };
+        // Non-numeric lines count as zero
+        assert_eq!(
+            sorted(&input, &numeric),
+            ["-2", "abc", "1.5", "9", "10", "100"]
+        );
+        let reverse_numeric = SortOptions {
+            numeric: true,
+            reverse: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&input, &reverse_numeric),
+            ["100", "10", "9", "1.5", "abc", "-2"]
+        );
+    }
+
+    #[test]
+    fn test_sort_key_fields() {
+        assert_eq!(parse_sort_key("2"), Some((2, None)));
+        assert_eq!(parse_sort_key("2,3"), Some((2, Some(3))));
+        assert_eq!(parse_sort_key("2n,2"), Some((2, Some(2))));
+        assert_eq!(parse_sort_key("0"), None);
+        assert_eq!(parse_sort_key("x"), None);
+
+        let second = SortOptions {
+            key: Some((2, Some(2))),
+            ..Default::default()
+        };
+        assert_eq!(sort_key("  alice   30  x", &second), "30");
+        assert_eq!(sort_key("alice", &second), "");
+        let rest = SortOptions {
+            key: Some((2, None)),
+            ..Default::default()
+        };
+        assert_eq!(sort_key("alice 30 x", &rest), "30 x");
+        let colon = SortOptions {
+            key: Some((3, Some(3))),
+            separator: Some(":".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(sort_key("root:x:0:0", &colon), "0");
+
+        let by_age = SortOptions {
+            key: Some((2, Some(2))),
+            numeric: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&["bob 30", "amy 4", "cat 30", "dan 100"], &by_age),
+            ["amy 4", "bob 30", "cat 30", "dan 100"]
+        );
+    }
+
+    #[test]
+    fn test_sort_unique_compares_keys() {
+        let opts = SortOptions {
+            numeric: true,
+            unique: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            sorted(&["2", "01", "1", "02", "3"], &opts),
+            ["01", "2", "3"]
+        );
+    }
+
+    #[test]
+    fn test_wc_counts() {
+        let counts = WcCounts::of("héllo world\nbye".as_bytes());
+        assert_eq!(
+            counts,
+            WcCounts {
+                lines: 1,
+                words: 3,
+                chars: 15,
+                bytes: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_format_wc_line() {
+        let counts = WcCounts {
+            lines: 2,
+            words: 5,
+            chars: 20,
+            bytes: 24,
+        };
+        let only_lines = WcColumns {
+            lines: true,
+            ..Default::default()
+        };
+        assert_eq!(format_wc_line(&counts, &only_lines, None), "2\n");
+        assert_eq!(
+            format_wc_line(&counts, &only_lines, Some("f")),
+            "      2 f\n"
+        );
+
+        let default = WcColumns {
+            lines: true,
+            words: true,
+            bytes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_wc_line(&counts, &default, None),
+            "      2       5      24\n"
+        );
+    }
+
     #[test]
     fn test_parse_sed_script_basic() {
         let result = parse_sed_script("s/foo/bar/");
@@ -1743,6 +2951,84 @@ mod tests {
         assert!(parse_sed_script("x/foo/bar/").is_none());
     }
 
+    fn sed(script: &str, input: &str, quiet: bool) -> String {
+        let mut commands = parse_sed_program(script, false).unwrap();
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        run_sed(&mut commands, &lines, quiet)
+    }
+
+    #[test]
+    fn test_sed_substitute_first_vs_global() {
+        assert_eq!(sed("s/o/0/", "foo boo\n", false), "f0o boo\n");
+        assert_eq!(sed("s/o/0/g", "foo boo\n", false), "f00 b00\n");
+        assert_eq!(sed("s/o/0/3", "foo boo\n", false), "foo b0o\n");
+        assert_eq!(sed("s/o/0/2g", "foo boo\n", false), "fo0 b00\n");
+        assert_eq!(sed("s/FOO/x/i", "foo\n", false), "x\n");
+    }
+
+    #[test]
+    fn test_sed_replacement_references() {
+        assert_eq!(sed("s/[0-9]\\+/<&>/g", "a1 b22\n", false), "a<1> b<22>\n");
+        assert_eq!(
+            sed(r"s/\(\w*\)=\(\w*\)/\2=\1/", "key=value\n", false),
+            "value=key\n"
+        );
+        assert_eq!(sed(r"s/a/\&/", "a\n", false), "&\n");
+        assert_eq!(sed("s|/usr|/opt|", "/usr/bin\n", false), "/opt/bin\n");
+        assert_eq!(sed(r"s/\//_/g", "a/b/c\n", false), "a_b_c\n");
+    }
+
+    #[test]
+    fn test_sed_extended_regex() {
+        let mut commands = parse_sed_program("s/(a+)b/[\\1]/", true).unwrap();
+        let lines = vec!["xaaab".to_string()];
+        assert_eq!(run_sed(&mut commands, &lines, false), "x[aaa]\n");
+    }
+
+    #[test]
+    fn test_sed_print_and_quiet() {
+        let input = "one\ntwo\nthree\n";
+        assert_eq!(sed("/t/p", input, true), "two\nthree\n");
+        assert_eq!(sed("2p", input, false), "one\ntwo\ntwo\nthree\n");
+        assert_eq!(sed("s/o/0/p", input, true), "0ne\ntw0\n");
+        assert_eq!(sed("$=", input, true), "3\n");
+        assert_eq!(sed("2q", input, false), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_sed_address_ranges() {
+        let input = "a\nb\nc\nd\ne\n";
+        assert_eq!(sed("2,4d", input, false), "a\ne\n");
+        assert_eq!(sed("/b/,/d/d", input, false), "a\ne\n");
+        assert_eq!(sed("/c/,$d", input, false), "a\nb\n");
+        assert_eq!(sed("2,4!d", input, false), "b\nc\nd\n");
+        assert_eq!(sed("/d/d", input, false), "a\nb\nc\ne\n");
+        // An end line already passed selects only the start line
+        assert_eq!(sed("3,1d", input, false), "a\nb\nd\ne\n");
+        // The end regex is only tried from the line after the start
+        assert_eq!(sed("/b/,/b/d", "a\nb\nc\nb\ne\n", false), "a\ne\n");
+        // A range can start again after it ends
+        assert_eq!(sed("/x/,/y/d", "x\n1\ny\n2\nx\n3\n", false), "2\n");
+    }
+
+    #[test]
+    fn test_sed_multiple_commands_in_sequence() {
+        assert_eq!(sed("s/a/b/; s/b/c/", "a\n", false), "c\n");
+        assert_eq!(sed("s/a/b/\ns/b/c/", "a\n", false), "c\n");
+        assert_eq!(sed("1d;s/x/y/", "x\nx\n", false), "y\n");
+    }
+
+    #[test]
+    fn test_sed_parse_errors() {
+        for script in ["k", "s/a/b", "s/a/b/z", "0d", "1,d", "/a/", "p x"] {
+            assert!(
+                parse_sed_program(script, false).is_err(),
+                "{:?} should not parse",
+                script
+            );
+        }
+    }
+
     #[test]
     fn test_parse_field_spec_single() {
         assert_eq!(parse_field_spec("1"), vec![1]);
@@ -1788,4 +3074,215 @@ mod tests {
     fn test_expand_char_set_mixed() {
         assert_eq!(expand_char_set("a-cx"), vec!['a', 'b', 'c', 'x']);
     }
+
+    const TWO_FILE_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs\n\
+index 1111111..2222222 100644\n\
+--- a/src/lib.rs\n\
++++ b/src/lib.rs\n\
+@@ -1,3 +1,4 @@\n\
+ fn main() {\n\
+-    old();\n\
++    new();\n\
++    more();\n\
+ }\n\
+@@ -10,2 +11,3 @@\n\
+ a\n\
++b\n\
+ c\n\
+--- README.md\t2024-01-01\n\
++++ README.md\t2024-01-02\n\
+@@ -1,3 +1,1 @@\n\
+--- removed rule\n\
+-text\n\
+ kept\n";
+
+    #[test]
+    fn test_diffstat_counts_per_file() {
+        let stats = parse_diffstat(TWO_FILE_DIFF);
+        assert_eq!(
+            stats,
+            vec![
+                FileDiffStat {
+                    path: "src/lib.rs".to_string(),
+                    insertions: 3,
+                    deletions: 1,
+                },
+                FileDiffStat {
+                    path: "README.md".to_string(),
+                    insertions: 0,
+                    deletions: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            format_diffstat(&stats, 50),
+            " src/lib.rs | 4 +++-\n README.md  | 2 --\n 2 files changed, 3 insertions(+), 3 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn test_diffstat_scales_histogram() {
+        let stats = vec![
+            FileDiffStat {
+                path: "big".to_string(),
+                insertions: 75,
+                deletions: 25,
+            },
+            FileDiffStat {
+                path: "small".to_string(),
+                insertions: 1,
+                deletions: 0,
+            },
+        ];
+        let out = format_diffstat(&stats, 20);
+        assert!(out.starts_with(" big   | 100 "), "{}", out);
+        assert!(
+            out.contains(&format!("{}{}\n", "+".repeat(15), "-".repeat(5))),
+            "{}",
+            out
+        );
+        assert!(out.contains(" small |   1 +\n"), "{}", out);
+        assert!(out.ends_with(" 2 files changed, 76 insertions(+), 25 deletions(-)\n"));
+    }
+
+    #[test]
+    fn test_cloc_counts_per_language() {
+        let root = std::env::temp_dir().join(format!("cloc-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.gen.ts\n").unwrap();
+        std::fs::write(
+            root.join("src/main.rs"),
+            "// entry point\nfn main() {\n\n    /* inline */ run();\n    /*\n     * block\n     */\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/app.ts"),
+            "const a = 1; // trailing\n\n// note\nexport { a };\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/skip.gen.ts"), "const generated = true;\n").unwrap();
+        std::fs::write(root.join("target/out.rs"), "fn built() {}\n").unwrap();
+        std::fs::write(
+            root.join("tool.py"),
+            "#!/usr/bin/env python3\n# helper\nprint('hi')\n\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("README.md"), "# not counted\n").unwrap();
+
+        let stats = count_lines_of_code(&root.to_string_lossy());
+        assert_eq!(
+            stats.get("Rust"),
+            Some(&ClocStats {
+                files: 1,
+                blank: 1,
+                comment: 4,
+                code: 3
+            })
+        );
+        assert_eq!(
+            stats.get("TypeScript"),
+            Some(&ClocStats {
+                files: 1,
+                blank: 1,
+                comment: 1,
+                code: 2
+            })
+        );
+        assert_eq!(
+            stats.get("Python"),
+            Some(&ClocStats {
+                files: 1,
+                blank: 1,
+                comment: 2,
+                code: 1
+            })
+        );
+        assert_eq!(stats.len(), 3);
+
+        let table = format_cloc(&stats);
+        assert!(
+            table.contains("SUM                    3         3         7         6"),
+            "{}",
+            table
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    fn grep_matches(patterns: &[&str], syntax: GrepSyntax, line: &str) -> bool {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        build_grep_regex(&patterns, syntax, false, false, false)
+            .unwrap()
+            .is_match(line)
+    }
+
+    #[test]
+    fn test_posix_to_regex_basic() {
+        // BRE operators are backslashed; bare ones are literal
+        assert_eq!(posix_to_regex(r"a\(b\)\+", false), "a(b)+");
+        assert_eq!(posix_to_regex("a+(b)", false), r"a\+\(b\)");
+        assert_eq!(posix_to_regex("*ab", false), r"\*ab");
+        assert_eq!(posix_to_regex(r"\<fn\>", false), r"\bfn\b");
+        assert_eq!(posix_to_regex("[[:digit:]]x", false), "[[:digit:]]x");
+        assert_eq!(posix_to_regex(r"[]a\]", false), r"[\]a\\]");
+        assert_eq!(posix_to_regex("a[b", false), r"a\[b");
+    }
+
+    #[test]
+    fn test_posix_to_regex_extended() {
+        assert_eq!(posix_to_regex("(ab)+|c?", true), "(ab)+|c?");
+        assert_eq!(posix_to_regex(r"\(x\)", true), r"\(x\)");
+    }
+
+    #[test]
+    fn test_build_grep_regex_syntaxes() {
+        assert!(grep_matches(&["fo*bar"], GrepSyntax::Basic, "fooobar"));
+        assert!(grep_matches(&["a+b"], GrepSyntax::Basic, "xa+b"));
+        assert!(!grep_matches(&["a+b"], GrepSyntax::Basic, "aab"));
+        assert!(grep_matches(&["a+b"], GrepSyntax::Extended, "aab"));
+        assert!(grep_matches(&["a.b"], GrepSyntax::Fixed, "xa.b"));
+        assert!(!grep_matches(&["a.b"], GrepSyntax::Fixed, "axb"));
+        assert!(grep_matches(&["one", "two"], GrepSyntax::Basic, "has two"));
+        assert!(grep_matches(&["one\ntwo"], GrepSyntax::Basic, "has two"));
+    }
+
+    #[test]
+    fn test_build_grep_regex_modifiers() {
+        let pat = vec!["Error".to_string()];
+        let re = build_grep_regex(&pat, GrepSyntax::Basic, true, false, false).unwrap();
+        assert!(re.is_match("an error here"));
+
+        let re = build_grep_regex(&pat, GrepSyntax::Basic, false, true, false).unwrap();
+        assert!(re.is_match("an Error here"));
+        assert!(!re.is_match("Errors"));
+
+        let re = build_grep_regex(&pat, GrepSyntax::Basic, false, false, true).unwrap();
+        assert!(re.is_match("Error"));
+        assert!(!re.is_match("Error!"));
+    }
+
+    #[test]
+    fn test_grep_options_output() {
+        let opts = GrepOptions {
+            line_numbers: true,
+            ..Default::default()
+        };
+        assert_eq!(opts.format_line(Some("a.txt"), 3, "hit"), "a.txt:3:hit\n");
+        assert_eq!(opts.format_line(None, 3, "hit"), "3:hit\n");
+
+        let opts = GrepOptions {
+            count_only: true,
+            ..Default::default()
+        };
+        assert_eq!(opts.format_summary("a.txt", 2, true), "a.txt:2\n");
+        assert_eq!(opts.format_summary("a.txt", 0, false), "0\n");
+
+        let opts = GrepOptions {
+            files_only: true,
+            ..Default::default()
+        };
+        assert_eq!(opts.format_summary("a.txt", 2, true), "a.txt\n");
+        assert_eq!(opts.format_summary("a.txt", 0, true), "");
+    }
 }
@@ -1,11 +1,11 @@
-//! Additional utility commands: printf, read, stat, ln, mktemp, type, which
+//! Additional utility commands: printf, ln, mktemp, uuidgen
 
-use futures_lite::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use futures_lite::io::AsyncWriteExt;
 use runtime_macros::shell_commands;
 
 use super::super::ShellEnv;
 use super::helpers::resolve_path;
-use super::{parse_common, ShellCommands};
+use super::parse_common;
 
 // Import WASI random bindings for cryptographic randomness
 use crate::bindings::wasi::random::random as wasi_random;
@@ -20,8 +20,10 @@ impl UtilCommands {
         name = "printf",
         usage = "printf FORMAT [ARGUMENT]...",
         description = "Format and print ARGUMENTS under control of FORMAT.\n\
-        Format specifiers: %s (string), %d (decimal), %x (hex), %o (octal), %c (char), %% (literal %)\n\
-        Escape sequences: \\n, \\t, \\r, \\\\, \\0NNN"
+        The format is reused until all ARGUMENTS are consumed.\n\
+        Specifiers: %s, %b (string with escapes), %c, %d/%i, %u, %x/%X, %o, %f, %e, %g, %%\n\
+        Flags, width and precision: %-8s, %05d, %.3s, %8.2f, %*d\n\
+        Escape sequences: \\n, \\t, \\r, \\\\, \\NNN, \\xHH"
     )]
     pub fn cmd_printf(
         args: Vec<String>,
@@ -37,184 +39,26 @@ impl UtilCommands {
                 return 1;
             }
 
-            let format = &remaining[0];
-            let arguments = &remaining[1..];
-            let mut arg_idx = 0;
-
-            let output = format_printf(format, arguments, &mut arg_idx);
+            let (output, errors) = run_printf(&remaining[0], &remaining[1..]);
             let _ = stdout.write_all(output.as_bytes()).await;
 
-            0
-        })
-    }
-
-    /// read - read a line from stdin into a variable
-    #[shell_command(
-        name = "read",
-        usage = "read [-r] [-p PROMPT] VAR...",
-        description = "Read a line from stdin and split it into variables.\n\
-        -r: Do not treat backslashes as escape characters\n\
-        -p PROMPT: Output PROMPT before reading"
-    )]
-    pub fn cmd_read(
-        args: Vec<String>,
-        _env: &ShellEnv,
-        stdin: piper::Reader,
-        mut stdout: piper::Writer,
-        _stderr: piper::Writer,
-    ) -> futures_lite::future::Boxed<i32> {
-        Box::pin(async move {
-            let (_, remaining) = parse_common(&args);
-            let mut raw_mode = false;
-            let mut prompt = None;
-            let mut var_names = Vec::new();
-            let mut i = 0;
-
-            while i < remaining.len() {
-                match remaining[i].as_str() {
-                    "-r" => raw_mode = true,
-                    "-p" => {
-                        i += 1;
-                        if i < remaining.len() {
-                            prompt = Some(remaining[i].clone());
-                        }
-                    }
-                    s if !s.starts_with('-') => {
-                        var_names.push(s.to_string());
-                    }
-                    _ => {}
-                }
-                i += 1;
-            }
-
-            if var_names.is_empty() {
-                var_names.push("REPLY".to_string());
-            }
-
-            // Print prompt if specified
-            if let Some(p) = prompt {
-                let _ = stdout.write_all(p.as_bytes()).await;
-            }
-
-            // Read a line from stdin
-            let mut reader = BufReader::new(stdin);
-            let mut line = String::new();
-            match reader.read_line(&mut line).await {
-                Ok(0) => return 1, // EOF
-                Ok(_) => {}
-                Err(_) => return 1,
-            }
-
-            // Trim trailing newline
-            let line = line.trim_end_matches('\n').trim_end_matches('\r');
-
-            // Process backslashes if not in raw mode
-            let line = if raw_mode {
-                line.to_string()
-            } else {
-                line.replace("\\n", "\n").replace("\\t", "\t")
-            };
-
-            // Output variable assignments (the caller will need to capture these)
-            // In a real shell, we'd set env variables directly
-            // For now, we output in a format the caller can parse
-            let words: Vec<&str> = line.split_whitespace().collect();
-
-            for (i, name) in var_names.iter().enumerate() {
-                let value = if i == var_names.len() - 1 {
-                    // Last variable gets remainder
-                    words[i..].join(" ")
-                } else {
-                    words.get(i).unwrap_or(&"").to_string()
-                };
-                let _ = stdout
-                    .write_all(format!("{}={}\n", name, value).as_bytes())
+            for error in &errors {
+                let _ = stderr
+                    .write_all(format!("printf: {}\n", error).as_bytes())
                     .await;
             }
-
-            0
-        })
-    }
-
-    /// stat - display file status
-    #[shell_command(
-        name = "stat",
-        usage = "stat [FILE]...",
-        description = "Display file or file system status."
-    )]
-    pub fn cmd_stat(
-        args: Vec<String>,
-        env: &ShellEnv,
-        _stdin: piper::Reader,
-        mut stdout: piper::Writer,
-        mut stderr: piper::Writer,
-    ) -> futures_lite::future::Boxed<i32> {
-        let cwd = env.cwd.to_string_lossy().to_string();
-        Box::pin(async move {
-            let (_, remaining) = parse_common(&args);
-            if remaining.is_empty() {
-                let _ = stderr.write_all(b"stat: missing operand\n").await;
-                return 1;
-            }
-
-            // cwd already cloned above
-            let mut exit_code = 0;
-
-            for file in &remaining {
-                let path = resolve_path(&cwd, &file);
-
-                match std::fs::metadata(&path) {
-                    Ok(metadata) => {
-                        let file_type = if metadata.is_dir() {
-                            "directory"
-                        } else if metadata.is_file() {
-                            "regular file"
-                        } else {
-                            "other"
-                        };
-
-                        let _ = stdout
-                            .write_all(
-                                format!(
-                                    "  File: {}\n  Size: {}\t\tType: {}\n",
-                                    file,
-                                    metadata.len(),
-                                    file_type
-                                )
-                                .as_bytes(),
-                            )
-                            .await;
-
-                        // Try to get times
-                        if let Ok(modified) = metadata.modified() {
-                            if let Ok(duration) = modified.duration_since(std::time::UNIX_EPOCH) {
-                                let _ = stdout
-                                    .write_all(
-                                        format!("Modify: {}\n", duration.as_secs()).as_bytes(),
-                                    )
-                                    .await;
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let _ = stderr
-                            .write_all(format!("stat: {}: {}\n", file, e).as_bytes())
-                            .await;
-                        exit_code = 1;
-                    }
-                }
-            }
-
-            exit_code
+            i32::from(!errors.is_empty())
         })
     }
 
     /// ln - create links
     #[shell_command(
         name = "ln",
-        usage = "ln [-s] TARGET LINK_NAME",
-        description = "Create a link to TARGET.\n\
-        -s: Create symbolic link"
+        usage = "ln [-sf] TARGET [LINK_NAME]\n       ln [-sf] TARGET... DIRECTORY",
+        description = "Create a link to TARGET named LINK_NAME, or links to each TARGET\n\
+        in DIRECTORY. With a single TARGET the link is made in the current directory.\n\
+        -s: Create symbolic links instead of hard links\n\
+        -f: Remove existing destination files"
     )]
     pub fn cmd_ln(
         args: Vec<String>,
@@ -223,55 +67,41 @@ impl UtilCommands {
         _stdout: piper::Writer,
         mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
+        use lexopt::prelude::*;
+
         let cwd = env.cwd.to_string_lossy().to_string();
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
             let mut symbolic = false;
-            let mut paths = Vec::new();
+            let mut force = false;
+            let mut operands: Vec<String> = Vec::new();
+            let mut parser = super::make_parser(remaining);
 
-            for arg in &remaining {
-                match arg.as_str() {
-                    "-s" => symbolic = true,
-                    s if !s.starts_with('-') => paths.push(s.to_string()),
+            while let Some(arg) = parser.next().ok().flatten() {
+                match arg {
+                    Short('s') | Long("symbolic") => symbolic = true,
+                    Short('f') | Long("force") => force = true,
+                    Value(val) => operands.push(val.string().unwrap_or_default()),
                     _ => {}
                 }
             }
 
-            if paths.len() < 2 {
-                let _ = stderr.write_all(b"ln: missing operand\n").await;
-                return 1;
-            }
-
-            // cwd already cloned above
-            let target = resolve_path(&cwd, &paths[0]);
-
-            let link_name = resolve_path(&cwd, &paths[1]);
-
-            let result = if symbolic {
-                #[cfg(unix)]
-                {
-                    std::os::unix::fs::symlink(&target, &link_name)
-                }
-                #[cfg(target_os = "wasi")]
-                {
-                    wasi_symlink_at(&target, &link_name)
-                }
-                #[cfg(not(any(unix, target_os = "wasi")))]
-                {
-                    // On platforms without symlink support, copy instead
-                    std::fs::copy(&target, &link_name).map(|_| ())
+            let links = match plan_links(&cwd, &operands) {
+                Ok(links) => links,
+                Err(e) => {
+                    let _ = stderr.write_all(format!("ln: {}\n", e).as_bytes()).await;
+                    return 1;
                 }
-            } else {
-                std::fs::hard_link(&target, &link_name)
             };
 
-            match result {
-                Ok(_) => 0,
-                Err(e) => {
+            let mut exit_code = 0;
+            for (target, link) in &links {
+                if let Err(e) = make_link(&cwd, target, link, symbolic, force) {
                     let _ = stderr.write_all(format!("ln: {}\n", e).as_bytes()).await;
-                    1
+                    exit_code = 1;
                 }
             }
+            exit_code
         })
     }
 
@@ -341,209 +171,637 @@ impl UtilCommands {
         })
     }
 
-    /// type - describe a command
+    /// uuidgen - generate UUIDs
     #[shell_command(
-        name = "type",
-        usage = "type NAME...",
-        description = "Describe how NAME would be interpreted if used as a command."
+        name = "uuidgen",
+        usage = "uuidgen [-r] [-t] [-n COUNT] [--seed N]",
+        description = "Generate UUIDs.\n\
+        -r, --random: Random (version 4) UUID (default)\n\
+        -t, --time: Time-based (version 1) UUID\n\
+        -n COUNT: Print COUNT UUIDs\n\
+        --seed N: Seed the generator for reproducible output"
     )]
-    pub fn cmd_type(
+    pub fn cmd_uuidgen(
         args: Vec<String>,
         _env: &ShellEnv,
         _stdin: piper::Reader,
         mut stdout: piper::Writer,
         mut stderr: piper::Writer,
     ) -> futures_lite::future::Boxed<i32> {
+        use lexopt::prelude::*;
+
         Box::pin(async move {
             let (_, remaining) = parse_common(&args);
-            let mut exit_code = 0;
+            let mut time_based = false;
+            let mut count: usize = 1;
+            let mut seed: Option<u64> = None;
+            let mut parser = super::make_parser(remaining);
 
-            for name in &remaining {
-                if ShellCommands::get_command(name).is_some() {
-                    let _ = stdout
-                        .write_all(format!("{} is a shell builtin\n", name).as_bytes())
-                        .await;
-                } else if is_shell_keyword(name) {
-                    let _ = stdout
-                        .write_all(format!("{} is a shell keyword\n", name).as_bytes())
-                        .await;
-                } else {
+            loop {
+                let arg = match parser.next() {
+                    Ok(Some(arg)) => arg,
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = stderr
+                            .write_all(format!("uuidgen: {}\n", e).as_bytes())
+                            .await;
+                        return 1;
+                    }
+                };
+                let parsed = match arg {
+                    Short('r') | Long("random") => {
+                        time_based = false;
+                        Ok(())
+                    }
+                    Short('t') | Long("time") => {
+                        time_based = true;
+                        Ok(())
+                    }
+                    Short('n') | Long("count") => parser
+                        .value()
+                        .map_err(|e| e.to_string())
+                        .and_then(|v| v.string().map_err(|_| "invalid count".to_string()))
+                        .and_then(|v| v.parse().map_err(|_| format!("invalid count: {}", v)))
+                        .map(|n| count = n),
+                    Long("seed") => parser
+                        .value()
+                        .map_err(|e| e.to_string())
+                        .and_then(|v| v.string().map_err(|_| "invalid seed".to_string()))
+                        .and_then(|v| v.parse().map_err(|_| format!("invalid seed: {}", v)))
+                        .map(|n| seed = Some(n)),
+                    other => Err(other.unexpected().to_string()),
+                };
+                if let Err(e) = parsed {
                     let _ = stderr
-                        .write_all(format!("type: {}: not found\n", name).as_bytes())
+                        .write_all(format!("uuidgen: {}\n", e).as_bytes())
                         .await;
-                    exit_code = 1;
+                    return 1;
                 }
             }
 
-            exit_code
+            let mut rng = UuidRng::new(seed.unwrap_or_else(wasi_random::get_random_u64));
+            let mut output = String::new();
+            for i in 0..count {
+                let uuid = if time_based {
+                    // Successive UUIDs from one run get distinct timestamps
+                    uuid_v1(uuid_timestamp() + i as u64, &mut rng)
+                } else {
+                    uuid_v4(&mut rng)
+                };
+                output.push_str(&uuid);
+                output.push('\n');
+            }
+            let _ = stdout.write_all(output.as_bytes()).await;
+            0
         })
     }
+}
 
-    /// which - locate a command
-    #[shell_command(
-        name = "which",
-        usage = "which NAME...",
-        description = "Locate a command."
-    )]
-    pub fn cmd_which(
-        args: Vec<String>,
-        _env: &ShellEnv,
-        _stdin: piper::Reader,
-        mut stdout: piper::Writer,
-        mut stderr: piper::Writer,
-    ) -> futures_lite::future::Boxed<i32> {
-        Box::pin(async move {
-            let (_, remaining) = parse_common(&args);
-            let mut exit_code = 0;
+/// Run FORMAT over ARGS the way coreutils printf does: the format is reused
+/// until every argument has been consumed. Returns the output and any
+/// conversion errors (the output still uses 0 for those arguments).
+fn run_printf(format: &str, args: &[String]) -> (String, Vec<String>) {
+    let mut out = String::new();
+    let mut errors = Vec::new();
+    let mut arg_idx = 0;
+    loop {
+        let before = arg_idx;
+        out.push_str(&format_printf_once(format, args, &mut arg_idx, &mut errors));
+        // Stop when done, or when the format consumes nothing (no specifiers)
+        if arg_idx >= args.len() || arg_idx == before {
+            break;
+        }
+    }
+    (out, errors)
+}
 
-            for name in &remaining {
-                if ShellCommands::get_command(name).is_some() {
-                    let _ = stdout
-                        .write_all(format!("{}: shell builtin\n", name).as_bytes())
-                        .await;
-                } else {
-                    let _ = stderr
-                        .write_all(format!("{}: not found\n", name).as_bytes())
-                        .await;
-                    exit_code = 1;
-                }
-            }
+/// A parsed `%[flags][width][.precision]conversion` directive
+#[derive(Default)]
+pub(super) struct PrintfSpec {
+    pub(super) left: bool,
+    pub(super) zero: bool,
+    pub(super) plus: bool,
+    pub(super) space: bool,
+    pub(super) alt: bool,
+    pub(super) width: usize,
+    pub(super) precision: Option<usize>,
+}
 
-            exit_code
-        })
+/// Parse the flags, width, precision and length modifiers after a `%`.
+/// A `*` width or precision takes its value from `star`.
+pub(super) fn parse_printf_spec(
+    chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    mut star: impl FnMut() -> i64,
+) -> PrintfSpec {
+    let mut spec = PrintfSpec::default();
+    while let Some(&flag) = chars.peek() {
+        match flag {
+            '-' => spec.left = true,
+            '0' => spec.zero = true,
+            '+' => spec.plus = true,
+            ' ' => spec.space = true,
+            '#' => spec.alt = true,
+            _ => break,
+        }
+        chars.next();
+    }
+    if chars.peek() == Some(&'*') {
+        chars.next();
+        let width = star();
+        spec.left |= width < 0;
+        spec.width = width.unsigned_abs() as usize;
+    } else {
+        spec.width = take_digits(chars).unwrap_or(0);
+    }
+    if chars.peek() == Some(&'.') {
+        chars.next();
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            spec.precision = usize::try_from(star()).ok();
+        } else {
+            spec.precision = Some(take_digits(chars).unwrap_or(0));
+        }
+    }
+    // Length modifiers mean nothing here
+    while matches!(chars.peek(), Some('h' | 'l' | 'L' | 'q' | 'j' | 'z' | 't')) {
+        chars.next();
     }
+    spec
 }
 
-/// Format printf string with arguments
-fn format_printf(format: &str, args: &[String], arg_idx: &mut usize) -> String {
+/// One pass over FORMAT. Specifiers without an argument print as empty/zero.
+fn format_printf_once(
+    format: &str,
+    args: &[String],
+    arg_idx: &mut usize,
+    errors: &mut Vec<String>,
+) -> String {
     let mut result = String::new();
     let mut chars = format.chars().peekable();
+    let next_arg = |arg_idx: &mut usize| -> Option<String> {
+        let arg = args.get(*arg_idx).cloned();
+        if arg.is_some() {
+            *arg_idx += 1;
+        }
+        arg
+    };
 
     while let Some(c) = chars.next() {
         match c {
-            '%' => match chars.peek() {
-                Some('%') => {
+            '%' => {
+                if chars.peek() == Some(&'%') {
                     chars.next();
                     result.push('%');
+                    continue;
                 }
-                Some('s') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        result.push_str(arg);
-                        *arg_idx += 1;
+
+                let spec = parse_printf_spec(&mut chars, || {
+                    printf_int(&next_arg(arg_idx).unwrap_or_default(), errors)
+                });
+
+                let Some(conv) = chars.next() else {
+                    errors.push("missing format character".to_string());
+                    break;
+                };
+                let arg = next_arg(arg_idx);
+                let formatted = match conv {
+                    's' => {
+                        let s = arg.unwrap_or_default();
+                        match spec.precision {
+                            Some(p) => s.chars().take(p).collect(),
+                            None => s,
+                        }
                     }
-                }
-                Some('d') | Some('i') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        let num: i64 = arg.parse().unwrap_or(0);
-                        result.push_str(&num.to_string());
-                        *arg_idx += 1;
+                    'b' => interpret_escapes(&arg.unwrap_or_default()),
+                    'c' => arg
+                        .and_then(|a| a.chars().next())
+                        .map(String::from)
+                        .unwrap_or_default(),
+                    'd' | 'i' => {
+                        let n = printf_int(&arg.unwrap_or_default(), errors);
+                        format_printf_integer(n < 0, n.unsigned_abs(), 10, false, &spec)
                     }
-                }
-                Some('x') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        let num: i64 = arg.parse().unwrap_or(0);
-                        result.push_str(&format!("{:x}", num));
-                        *arg_idx += 1;
+                    'u' | 'x' | 'X' | 'o' => {
+                        // Negative values wrap like C's unsigned conversion
+                        let n = printf_int(&arg.unwrap_or_default(), errors) as u64;
+                        let radix = match conv {
+                            'x' | 'X' => 16,
+                            'o' => 8,
+                            _ => 10,
+                        };
+                        let digits = format_printf_integer(false, n, radix, n != 0, &spec);
+                        if conv == 'X' {
+                            digits.to_uppercase()
+                        } else {
+                            digits
+                        }
                     }
-                }
-                Some('X') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        let num: i64 = arg.parse().unwrap_or(0);
-                        result.push_str(&format!("{:X}", num));
-                        *arg_idx += 1;
+                    'f' | 'F' | 'e' | 'E' | 'g' | 'G' => {
+                        let n = printf_float(&arg.unwrap_or_default(), errors);
+                        format_printf_float(n, conv, &spec)
                     }
-                }
-                Some('o') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        let num: i64 = arg.parse().unwrap_or(0);
-                        result.push_str(&format!("{:o}", num));
-                        *arg_idx += 1;
+                    other => {
+                        errors.push(format!("%{}: invalid conversion specification", other));
+                        break;
                     }
-                }
-                Some('c') => {
-                    chars.next();
-                    if let Some(arg) = args.get(*arg_idx) {
-                        if let Some(c) = arg.chars().next() {
-                            result.push(c);
-                        }
-                        *arg_idx += 1;
+                };
+                result.push_str(&pad_printf(&formatted, conv, &spec));
+            }
+            '\\' => push_escape(&mut chars, &mut result),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<usize> {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits.parse().ok()
+}
+
+/// Expand one backslash escape (the backslash is already consumed)
+fn push_escape(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, result: &mut String) {
+    match chars.next() {
+        Some('n') => result.push('\n'),
+        Some('t') => result.push('\t'),
+        Some('r') => result.push('\r'),
+        Some('a') => result.push('\x07'),
+        Some('b') => result.push('\x08'),
+        Some('f') => result.push('\x0C'),
+        Some('v') => result.push('\x0B'),
+        Some('e') => result.push('\x1B'),
+        Some('\\') => result.push('\\'),
+        Some('"') => result.push('"'),
+        Some('\'') => result.push('\''),
+        Some(first @ '0'..='7') => {
+            // \NNN, or \0NNN as in `echo -e`
+            let mut oct = String::new();
+            let max = if first == '0' { 3 } else { 2 };
+            if first != '0' {
+                oct.push(first);
+            }
+            for _ in 0..max {
+                match chars.peek() {
+                    Some(&c @ '0'..='7') => {
+                        oct.push(c);
+                        chars.next();
                     }
+                    _ => break,
                 }
-                _ => {
-                    result.push('%');
-                }
-            },
-            '\\' => {
-                match chars.next() {
-                    Some('n') => result.push('\n'),
-                    Some('t') => result.push('\t'),
-                    Some('r') => result.push('\r'),
-                    Some('\\') => result.push('\\'),
-                    Some('0') => {
-                        // Octal escape
-                        let mut oct = String::new();
-                        for _ in 0..3 {
-                            if let Some(&c) = chars.peek() {
-                                if c >= '0' && c <= '7' {
-                                    oct.push(chars.next().unwrap());
-                                } else {
-                                    break;
-                                }
-                            }
-                        }
-                        if let Ok(n) = u8::from_str_radix(&oct, 8) {
-                            result.push(n as char);
-                        }
-                    }
-                    Some(c) => {
-                        result.push('\\');
-                        result.push(c);
+            }
+            let n = u32::from_str_radix(&oct, 8).unwrap_or(0);
+            result.push(char::from_u32(n & 0xFF).unwrap_or('\0'));
+        }
+        Some('x') => {
+            let mut hex = String::new();
+            while hex.len() < 2 {
+                match chars.peek() {
+                    Some(&c) if c.is_ascii_hexdigit() => {
+                        hex.push(c);
+                        chars.next();
                     }
-                    None => result.push('\\'),
+                    _ => break,
                 }
             }
-            _ => result.push(c),
+            match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                Some(c) => result.push(c),
+                None => result.push_str("\\x"),
+            }
         }
+        Some(c) => {
+            result.push('\\');
+            result.push(c);
+        }
+        None => result.push('\\'),
     }
+}
 
+/// Expand backslash escapes in a `%b` argument
+fn interpret_escapes(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            push_escape(&mut chars, &mut result);
+        } else {
+            result.push(c);
+        }
+    }
     result
 }
 
-/// Check if a name is a shell keyword
-fn is_shell_keyword(name: &str) -> bool {
-    matches!(
-        name,
-        "if" | "then"
-            | "else"
-            | "elif"
-            | "fi"
-            | "for"
-            | "while"
-            | "until"
-            | "do"
-            | "done"
-            | "case"
-            | "esac"
-            | "in"
-            | "function"
-            | "{"
-            | "}"
-            | "!"
-            | "[["
-            | "]]"
+/// Parse an integer argument: decimal, 0x hex, 0 octal, or 'c for a
+/// character code. Bad input counts as 0 (or its valid prefix) and is reported.
+fn printf_int(arg: &str, errors: &mut Vec<String>) -> i64 {
+    let trimmed = arg.trim();
+    if trimmed.is_empty() {
+        return 0;
+    }
+    if let Some(rest) = trimmed.strip_prefix(['\'', '"']) {
+        return rest.chars().next().map_or(0, |c| c as i64);
+    }
+
+    let (negative, unsigned) = match trimmed.as_bytes()[0] {
+        b'-' => (true, &trimmed[1..]),
+        b'+' => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    let (radix, digits) = if let Some(hex) = unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        (16, hex)
+    } else if unsigned.len() > 1 && unsigned.starts_with('0') {
+        (8, &unsigned[1..])
+    } else {
+        (10, unsigned)
+    };
+
+    let valid_len = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let magnitude = i64::from_str_radix(&digits[..valid_len], radix).unwrap_or(0);
+    if valid_len == 0 {
+        errors.push(format!("'{}': expected a numeric value", arg));
+    } else if valid_len < digits.len() {
+        errors.push(format!("'{}': value not completely converted", arg));
+    }
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Parse a floating-point argument, reporting bad input as `printf_int` does
+fn printf_float(arg: &str, errors: &mut Vec<String>) -> f64 {
+    let trimmed = arg.trim();
+    if trimmed.is_empty() {
+        return 0.0;
+    }
+    if let Some(rest) = trimmed.strip_prefix(['\'', '"']) {
+        return rest.chars().next().map_or(0.0, |c| c as u32 as f64);
+    }
+    if let Ok(n) = trimmed.parse::<f64>() {
+        return n;
+    }
+    // Longest valid prefix, e.g. "3.5abc" -> 3.5
+    let prefix = (1..trimmed.len())
+        .rev()
+        .filter(|&end| trimmed.is_char_boundary(end))
+        .find_map(|end| trimmed[..end].parse::<f64>().ok());
+    match prefix {
+        Some(n) => {
+            errors.push(format!("'{}': value not completely converted", arg));
+            n
+        }
+        None => {
+            errors.push(format!("'{}': expected a numeric value", arg));
+            0.0
+        }
+    }
+}
+
+/// Digits of an integer conversion with sign, precision and `#` prefix;
+/// width padding happens in `pad_printf`
+pub(super) fn format_printf_integer(
+    negative: bool,
+    magnitude: u64,
+    radix: u32,
+    nonzero: bool,
+    spec: &PrintfSpec,
+) -> String {
+    let mut digits = match radix {
+        16 => format!("{:x}", magnitude),
+        8 => format!("{:o}", magnitude),
+        _ => magnitude.to_string(),
+    };
+    if let Some(p) = spec.precision {
+        if p == 0 && magnitude == 0 {
+            digits.clear();
+        } else if digits.len() < p {
+            digits = format!("{}{}", "0".repeat(p - digits.len()), digits);
+        }
+    }
+    let prefix = match radix {
+        16 if spec.alt && nonzero => "0x",
+        8 if spec.alt && !digits.starts_with('0') => "0",
+        10 if negative => "-",
+        10 if spec.plus => "+",
+        10 if spec.space => " ",
+        _ => "",
+    };
+    format!("{}{}", prefix, digits)
+}
+
+/// Floating-point conversions: %f, %e and %g, with C's exponent style
+pub(super) fn format_printf_float(n: f64, conv: char, spec: &PrintfSpec) -> String {
+    let precision = spec.precision.unwrap_or(6);
+    let body = if !n.is_finite() {
+        let s = if n.is_nan() { "nan" } else { "inf" };
+        s.to_string()
+    } else {
+        match conv.to_ascii_lowercase() {
+            'f' => format!("{:.*}", precision, n.abs()),
+            'e' => c_exponent(n.abs(), precision),
+            _ => {
+                let p = precision.max(1);
+                let exp = c_exponent(n.abs(), p - 1);
+                let x: i32 = exp
+                    .rsplit_once('e')
+                    .and_then(|(_, e)| e.parse().ok())
+                    .unwrap_or(0);
+                let mut s = if x < -4 || x >= p as i32 {
+                    exp
+                } else {
+                    format!("{:.*}", (p as i32 - 1 - x) as usize, n.abs())
+                };
+                if !spec.alt {
+                    s = strip_trailing_zeros(&s);
+                }
+                s
+            }
+        }
+    };
+    let body = if conv.is_ascii_uppercase() {
+        body.to_uppercase()
+    } else {
+        body
+    };
+    let sign = if n.is_sign_negative() && !n.is_nan() {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}", sign, body)
+}
+
+/// `d.ddde±XX` like C, from Rust's `d.ddde-X`
+fn c_exponent(n: f64, precision: usize) -> String {
+    let s = format!("{:.*e}", precision, n);
+    let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exp.abs())
+}
+
+/// Drop trailing fractional zeros (and a bare point) for %g
+fn strip_trailing_zeros(s: &str) -> String {
+    let (mantissa, exp) = match s.find('e') {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, ""),
+    };
+    let mantissa = if mantissa.contains('.') {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        mantissa
+    };
+    format!("{}{}", mantissa, exp)
+}
+
+/// Pad a converted value to the field width
+pub(super) fn pad_printf(formatted: &str, conv: char, spec: &PrintfSpec) -> String {
+    let len = formatted.chars().count();
+    if len >= spec.width {
+        return formatted.to_string();
+    }
+    let fill = spec.width - len;
+    if spec.left {
+        return format!("{}{}", formatted, " ".repeat(fill));
+    }
+
+    // C ignores 0 for integers with an explicit precision
+    let zero_pad = match conv {
+        'd' | 'i' | 'u' | 'x' | 'X' | 'o' => spec.zero && spec.precision.is_none(),
+        'f' | 'F' | 'e' | 'E' | 'g' | 'G' => spec.zero,
+        _ => false,
+    };
+    if !zero_pad {
+        return format!("{}{}", " ".repeat(fill), formatted);
+    }
+
+    // Zeros go after any sign or 0x prefix
+    let prefix_len = if formatted.starts_with("0x") || formatted.starts_with("0X") {
+        2
+    } else if formatted.starts_with(['-', '+', ' ']) {
+        1
+    } else {
+        0
+    };
+    format!(
+        "{}{}{}",
+        &formatted[..prefix_len],
+        "0".repeat(fill),
+        &formatted[prefix_len..]
     )
 }
 
+/// Pair each `ln` target with the link to create, both as given on the
+/// command line. The last operand names a directory to link into when it
+/// is one, or when there are several targets.
+fn plan_links(cwd: &str, operands: &[String]) -> Result<Vec<(String, String)>, String> {
+    let basename = |path: &str| {
+        let trimmed = path.trim_end_matches('/');
+        trimmed.rsplit('/').next().unwrap_or(trimmed).to_string()
+    };
+
+    let (last, targets) = match operands {
+        [] => return Err("missing file operand".to_string()),
+        [target] => return Ok(vec![(target.clone(), basename(target))]),
+        [targets @ .., last] => (last, targets),
+    };
+
+    if std::path::Path::new(&resolve_path(cwd, last)).is_dir() {
+        let dir = last.trim_end_matches('/');
+        Ok(targets
+            .iter()
+            .map(|target| (target.clone(), format!("{}/{}", dir, basename(target))))
+            .collect())
+    } else if targets.len() == 1 {
+        Ok(vec![(targets[0].clone(), last.clone())])
+    } else {
+        Err(format!("target '{}' is not a directory", last))
+    }
+}
+
+/// Create one link. A symlink stores `target` verbatim, so a relative
+/// target resolves against the link's directory; a hard link's target is
+/// resolved against `cwd`.
+fn make_link(
+    cwd: &str,
+    target: &str,
+    link: &str,
+    symbolic: bool,
+    force: bool,
+) -> Result<(), String> {
+    let link_path = resolve_path(cwd, link);
+    if force {
+        if let Ok(metadata) = std::fs::symlink_metadata(&link_path) {
+            if metadata.is_dir() {
+                return Err(format!("'{}': cannot overwrite directory", link));
+            }
+            std::fs::remove_file(&link_path)
+                .map_err(|e| format!("cannot remove '{}': {}", link, e))?;
+        }
+    }
+
+    let result = if symbolic {
+        create_symlink(target, &link_path)
+    } else {
+        std::fs::hard_link(resolve_path(cwd, target), &link_path)
+    };
+    result.map_err(|e| {
+        let kind = if symbolic {
+            "symbolic link"
+        } else {
+            "hard link"
+        };
+        if e.kind() == std::io::ErrorKind::Unsupported {
+            format!(
+                "failed to create {} '{}': links are not supported by this filesystem",
+                kind, link
+            )
+        } else {
+            format!("failed to create {} '{}': {}", kind, link, e)
+        }
+    })
+}
+
+/// Create a symbolic link at `link_path` pointing to `target`.
+fn create_symlink(target: &str, link_path: &str) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link_path)
+    }
+    #[cfg(target_os = "wasi")]
+    {
+        wasi_symlink_at(target, link_path)
+    }
+    #[cfg(not(any(unix, target_os = "wasi")))]
+    {
+        let _ = (target, link_path);
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+}
+
 /// Create a symbolic link using the WASI filesystem symlink_at interface.
 /// Resolves the link_name to a preopened directory descriptor + relative path.
 #[cfg(target_os = "wasi")]
 fn wasi_symlink_at(target: &str, link_name: &str) -> std::io::Result<()> {
     use crate::bindings::wasi::filesystem::preopens;
+    use crate::bindings::wasi::filesystem::types::ErrorCode;
 
     let dirs = preopens::get_directories();
 
@@ -563,19 +821,159 @@ fn wasi_symlink_at(target: &str, link_name: &str) -> std::io::Result<()> {
     let relative_link = link_name.strip_prefix(prefix.as_str()).unwrap_or(link_name);
     let relative_link = relative_link.strip_prefix('/').unwrap_or(relative_link);
 
-    desc.symlink_at(target, relative_link)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("symlink_at: {e:?}")))
+    desc.symlink_at(target, relative_link).map_err(|e| match e {
+        ErrorCode::Unsupported => std::io::ErrorKind::Unsupported.into(),
+        ErrorCode::Exist => std::io::ErrorKind::AlreadyExists.into(),
+        ErrorCode::NoEntry => std::io::ErrorKind::NotFound.into(),
+        other => std::io::Error::other(format!("symlink_at: {other:?}")),
+    })
+}
+
+/// SplitMix64: small, seedable PRNG for uuidgen.
+struct UuidRng(u64);
+
+impl UuidRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            let random = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&random[..chunk.len()]);
+        }
+    }
+}
+
+fn format_uuid(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Random UUID (RFC 4122 version 4).
+fn uuid_v4(rng: &mut UuidRng) -> String {
+    let mut bytes = [0u8; 16];
+    rng.fill(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    format_uuid(&bytes)
+}
+
+/// 100ns intervals since the Gregorian epoch (1582-10-15), as used by v1 UUIDs.
+fn uuid_timestamp() -> u64 {
+    const GREGORIAN_OFFSET: u64 = 0x01B2_1DD2_1381_4000;
+    let since_unix = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    GREGORIAN_OFFSET + (since_unix.as_nanos() / 100) as u64
+}
+
+/// Time-based UUID (version 1). There is no MAC address to use, so the node
+/// is random with the multicast bit set, as RFC 4122 allows.
+fn uuid_v1(timestamp: u64, rng: &mut UuidRng) -> String {
+    let mut bytes = [0u8; 16];
+    bytes[0..4].copy_from_slice(&(timestamp as u32).to_be_bytes());
+    bytes[4..6].copy_from_slice(&((timestamp >> 32) as u16).to_be_bytes());
+    let time_hi = ((timestamp >> 48) as u16 & 0x0fff) | 0x1000;
+    bytes[6..8].copy_from_slice(&time_hi.to_be_bytes());
+    let mut random = [0u8; 8];
+    rng.fill(&mut random);
+    bytes[8] = (random[0] & 0x3f) | 0x80;
+    bytes[9] = random[1];
+    bytes[10..16].copy_from_slice(&random[2..8]);
+    bytes[10] |= 0x01;
+    format_uuid(&bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const UUID_V4: &str = "^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$";
+
+    #[test]
+    fn test_uuidgen_v4_format() {
+        let re = regex::Regex::new(UUID_V4).unwrap();
+        let mut rng = UuidRng::new(7);
+        for _ in 0..20 {
+            let uuid = uuid_v4(&mut rng);
+            assert!(re.is_match(&uuid), "not a v4 UUID: {}", uuid);
+        }
+
+        let v1 = uuid_v1(uuid_timestamp(), &mut rng);
+        let v1_re = regex::Regex::new(
+            "^[0-9a-f]{8}-[0-9a-f]{4}-1[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$",
+        )
+        .unwrap();
+        assert!(v1_re.is_match(&v1), "not a v1 UUID: {}", v1);
+    }
+
+    #[test]
+    fn test_uuidgen_count_emits_distinct_uuids() {
+        let mut env = ShellEnv::new();
+        let result = futures_lite::future::block_on(crate::shell::run_pipeline(
+            // Seeded: the WASI entropy source isn't available to native tests
+            "uuidgen --seed 1 -n 3",
+            &mut env,
+        ));
+        assert_eq!(result.code, 0, "stderr: {}", result.stderr);
+        let mut lines: Vec<&str> = result.stdout.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let re = regex::Regex::new(UUID_V4).unwrap();
+        assert!(lines.iter().all(|line| re.is_match(line)), "{:?}", lines);
+        lines.sort();
+        lines.dedup();
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_uuidgen_seed_is_reproducible() {
+        let mut env = ShellEnv::new();
+        let run = |env: &mut ShellEnv| {
+            futures_lite::future::block_on(crate::shell::run_pipeline(
+                "uuidgen --seed 42 -n 2",
+                env,
+            ))
+            .stdout
+        };
+        let first = run(&mut env);
+        assert_eq!(first.lines().count(), 2);
+        assert_eq!(first, run(&mut env));
+        assert_ne!(
+            first,
+            futures_lite::future::block_on(crate::shell::run_pipeline(
+                "uuidgen --seed 43 -n 2",
+                &mut env,
+            ))
+            .stdout
+        );
+    }
+
     #[test]
     fn test_printf_string() {
         let mut idx = 0;
         assert_eq!(
-            format_printf("Hello %s!", &["world".to_string()], &mut idx),
+            format_printf_once(
+                "Hello %s!",
+                &["world".to_string()],
+                &mut idx,
+                &mut Vec::new()
+            ),
             "Hello world!"
         );
     }
@@ -584,7 +982,7 @@ mod tests {
     fn test_printf_number() {
         let mut idx = 0;
         assert_eq!(
-            format_printf("Number: %d", &["42".to_string()], &mut idx),
+            format_printf_once("Number: %d", &["42".to_string()], &mut idx, &mut Vec::new()),
             "Number: 42"
         );
     }
@@ -593,16 +991,73 @@ mod tests {
     fn test_printf_hex() {
         let mut idx = 0;
         assert_eq!(
-            format_printf("Hex: %x", &["255".to_string()], &mut idx),
+            format_printf_once("Hex: %x", &["255".to_string()], &mut idx, &mut Vec::new()),
             "Hex: ff"
         );
     }
 
+    fn printf(format: &str, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        run_printf(format, &args).0
+    }
+
+    #[test]
+    fn test_printf_cycles_format() {
+        assert_eq!(printf("%s=%d\\n", &["a", "1", "b", "2"]), "a=1\nb=2\n");
+        // Missing arguments print as empty/zero
+        assert_eq!(printf("[%s|%d]", &["x", "1", "y"]), "[x|1][y|0]");
+        // No specifiers: printed once regardless of arguments
+        assert_eq!(printf("hi\\n", &["ignored"]), "hi\n");
+        assert_eq!(printf("%s %s\\n", &[]), " \n");
+    }
+
+    #[test]
+    fn test_printf_numeric_conversions() {
+        assert_eq!(
+            printf("%5d|%-5d|%05d", &["42", "42", "-42"]),
+            "   42|42   |-0042"
+        );
+        assert_eq!(printf("%+d %.3d", &["7", "7"]), "+7 007");
+        assert_eq!(
+            printf("%x %X %#x %o %#o", &["255", "255", "255", "8", "8"]),
+            "ff FF 0xff 10 010"
+        );
+        assert_eq!(printf("%d %d %d", &["0x1F", "017", "'A"]), "31 15 65");
+        assert_eq!(printf("%u", &["-1"]), "18446744073709551615");
+        assert_eq!(
+            printf("%5.2f|%-8.3f|%08.2f", &["3.14159", "2.5", "-1.5"]),
+            " 3.14|2.500   |-0001.50"
+        );
+        assert_eq!(printf("%f", &["1"]), "1.000000");
+        assert_eq!(
+            printf("%e %.2E", &["12345.678", "0.000123"]),
+            "1.234568e+04 1.23E-04"
+        );
+        assert_eq!(
+            printf("%g %g %g %g", &["100000", "1000000", "0.0001", "3.50"]),
+            "100000 1e+06 0.0001 3.5"
+        );
+        assert_eq!(printf("%c%c", &["hello", "world"]), "hw");
+        assert_eq!(
+            printf("%.3s|%6s|%*s", &["abcdef", "ab", "3", "x"]),
+            "abc|    ab|  x"
+        );
+    }
+
+    #[test]
+    fn test_printf_reports_bad_numbers() {
+        let args = vec!["abc".to_string(), "12x".to_string()];
+        let (out, errors) = run_printf("%d %d", &args);
+        assert_eq!(out, "0 12");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("expected a numeric value"));
+    }
+
     #[test]
     fn test_printf_escape() {
         let mut idx = 0;
         assert_eq!(
-            format_printf("Line1\\nLine2", &[], &mut idx),
+            format_printf_once("Line1\\nLine2", &[], &mut idx, &mut Vec::new()),
             "Line1\nLine2"
         );
     }
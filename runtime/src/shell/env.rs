@@ -5,11 +5,17 @@
 
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use super::parser::ParsedCommand;
 
 /// Global session counter for generating unique session IDs ($$)
 static SESSION_COUNTER: AtomicU64 = AtomicU64::new(1);
 
+/// Global counter for background job process ids ($!)
+static JOB_PID_COUNTER: AtomicU64 = AtomicU64::new(1000);
+
 // ============================================================================
 // Shell Value Types (modeled after brush-core)
 // ============================================================================
@@ -326,6 +332,8 @@ pub struct ShellOptions {
     pub extglob: bool,
     /// nullglob: Expand globs with no matches to empty
     pub nullglob: bool,
+    /// failglob: A glob with no matches is an error (wins over nullglob)
+    pub failglob: bool,
     /// dotglob: Include dotfiles in glob expansion
     pub dotglob: bool,
     /// globstar: Enable ** recursive globbing
@@ -402,6 +410,7 @@ impl ShellOptions {
         match opt {
             "extglob" => self.extglob = enable,
             "nullglob" => self.nullglob = enable,
+            "failglob" => self.failglob = enable,
             "dotglob" => self.dotglob = enable,
             "globstar" => self.globstar = enable,
             "nocasematch" => self.nocasematch = enable,
@@ -417,6 +426,19 @@ impl ShellOptions {
 // Shell Environment
 // ============================================================================
 
+/// A background job started with `&`.
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// Job number, shown as `[n]` and addressed as `%n`
+    pub id: usize,
+    /// Process-style id, reported by `$!`
+    pub pid: u64,
+    /// Command text, as listed by `jobs`
+    pub command: String,
+    /// Exit code once the job has finished
+    pub status: Option<i32>,
+}
+
 /// Shell execution environment.
 #[derive(Debug, Clone)]
 pub struct ShellEnv {
@@ -446,12 +468,22 @@ pub struct ShellEnv {
     /// Subshell depth (for nested execution)
     pub subshell_depth: usize,
     /// Trap handlers (signal -> command)
-    #[allow(dead_code)] // kept for POSIX shell trap support
     pub traps: HashMap<String, String>,
     /// Shell functions (name -> body)
-    pub functions: HashMap<String, String>,
+    pub functions: HashMap<String, ParsedCommand>,
     /// Are we in a function scope?
     pub in_function: bool,
+    /// Set by `return`; the rest of the function body is skipped
+    pub returning: bool,
+    /// Set by `exit`, or by a failing command under `set -e`; the rest of
+    /// the command line is skipped
+    pub exiting: bool,
+    /// Nesting depth of `if`/`while` conditions and `&&`/`||` left-hand
+    /// sides, where `set -e` does not apply
+    pub errexit_suppressed: usize,
+    /// Bytes of its stdin the last command consumed (`read` stops after a
+    /// line); command lists pass the rest on to the commands that follow
+    pub stdin_consumed: usize,
 
     // Loop control
     /// Current loop nesting depth (0 = not in loop)
@@ -464,10 +496,20 @@ pub struct ShellEnv {
     /// Command aliases (name -> expansion)
     pub aliases: HashMap<String, String>,
 
+    /// Background jobs that have not been reported or waited for yet.
+    /// The futures driving them live in the executor, keyed by pid.
+    pub jobs: Vec<Job>,
+    /// Pid of the most recent background job ($!)
+    pub last_background_pid: Option<u64>,
+
     /// Whether stdout is connected to a terminal (for color output).
     /// This is the shell equivalent of isatty(STDOUT_FILENO).
     pub is_interactive: bool,
 
+    /// Set by the caller to abort the command line; checked before each
+    /// command and pipeline stage starts (e.g. MCP request cancellation).
+    pub cancel_flag: Option<Arc<AtomicBool>>,
+
     // Legacy compatibility fields (to avoid breaking existing code)
     /// Alias for exported variables lookup
     pub env_vars: HashMap<String, String>,
@@ -520,14 +562,22 @@ impl ShellEnv {
             traps: HashMap::new(),
             functions: HashMap::new(),
             in_function: false,
+            returning: false,
+            exiting: false,
+            errexit_suppressed: 0,
+            stdin_consumed: 0,
             // Loop control
             loop_depth: 0,
             break_level: 0,
             continue_level: 0,
             // Aliases
             aliases: HashMap::new(),
+            // Job control
+            jobs: Vec::new(),
+            last_background_pid: None,
             // Interactive mode (default false, set true for REPL)
             is_interactive: false,
+            cancel_flag: None,
             // Legacy compatibility
             env_vars,
             local_vars: HashMap::new(),
@@ -535,6 +585,13 @@ impl ShellEnv {
         }
     }
 
+    /// Whether the caller has asked the running command line to stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
     // ========================================================================
     // Variable Access (Enhanced API)
     // ========================================================================
@@ -907,13 +964,17 @@ impl ShellEnv {
         self.positional_params.len()
     }
 
-    /// Get all positional parameters as a single string ($*)
+    /// Get all positional parameters as a single string ($*), joined by the
+    /// first character of IFS (a space when IFS is unset, nothing when empty)
     pub fn all_params_string(&self) -> String {
-        self.positional_params.join(" ")
+        let sep = match self.get_var("IFS") {
+            Some(ifs) => ifs.chars().next().map(String::from).unwrap_or_default(),
+            None => " ".to_string(),
+        };
+        self.positional_params.join(&sep)
     }
 
-    /// Get all positional parameters as quoted strings ($@)
-    #[allow(dead_code)] // POSIX shell API
+    /// Get all positional parameters as separate words ($@)
     pub fn all_params(&self) -> &[String] {
         &self.positional_params
     }
@@ -961,15 +1022,52 @@ impl ShellEnv {
     pub fn subshell(&self) -> Self {
         let mut sub = self.clone();
         sub.subshell_depth += 1;
+        // Jobs belong to the shell that started them
+        sub.jobs.clear();
+        // Caught signals are reset in a subshell; ignored ones stay ignored
+        sub.traps.retain(|_, action| action.is_empty());
         sub
     }
 
+    // ========================================================================
+    // Job Control
+    // ========================================================================
+
+    /// Register a new background job and make it the current job ($!).
+    pub fn add_job(&mut self, command: String) -> &Job {
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        let pid = JOB_PID_COUNTER.fetch_add(1, Ordering::SeqCst);
+        self.last_background_pid = Some(pid);
+        self.jobs.push(Job {
+            id,
+            pid,
+            command,
+            status: None,
+        });
+        self.jobs.last().unwrap()
+    }
+
+    /// Find a job by `%n`, `%%`/`%+` (current), `%-` (previous) or pid.
+    pub fn find_job(&self, spec: &str) -> Option<&Job> {
+        match spec.strip_prefix('%') {
+            Some("%" | "+" | "") => self.jobs.last(),
+            Some("-") => self.jobs.iter().rev().nth(1),
+            Some(number) => {
+                let id: usize = number.parse().ok()?;
+                self.jobs.iter().find(|job| job.id == id)
+            }
+            None => {
+                let pid: u64 = spec.parse().ok()?;
+                self.jobs.iter().find(|job| job.pid == pid)
+            }
+        }
+    }
+
     // ========================================================================
     // Trap Support
     // ========================================================================
 
     /// Set a trap handler
-    #[allow(dead_code)] // kept for POSIX trap support
     pub fn set_trap(&mut self, signal: &str, command: Option<&str>) {
         if let Some(cmd) = command {
             self.traps.insert(signal.to_string(), cmd.to_string());
@@ -979,7 +1077,6 @@ impl ShellEnv {
     }
 
     /// Get a trap handler
-    #[allow(dead_code)] // kept for POSIX trap support
     pub fn get_trap(&self, signal: &str) -> Option<&String> {
         self.traps.get(signal)
     }
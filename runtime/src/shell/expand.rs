@@ -117,12 +117,17 @@ fn expand_dollar(
         }
         Some('@') => {
             chars.next();
-            Ok(env.all_params_string()) // In non-array context, same as $*
+            // Outside of word context ("$@" as a whole argument is split by
+            // expand_word_fields), the parameters are joined with spaces
+            Ok(env.all_params().join(" "))
         }
         Some('!') => {
             chars.next();
-            // Background job PID - not supported, return empty
-            Ok(String::new())
+            // Pid of the most recent background job
+            Ok(env
+                .last_background_pid
+                .map(|pid| pid.to_string())
+                .unwrap_or_default())
         }
         Some('-') => {
             chars.next();
@@ -204,7 +209,10 @@ fn expand_simple_variable(
             return Ok("/bin/sh".to_string());
         }
         "IFS" => {
-            return Ok(" \t\n".to_string());
+            return Ok(env
+                .get_var("IFS")
+                .cloned()
+                .unwrap_or_else(|| " \t\n".to_string()));
         }
         _ => {}
     }
@@ -262,10 +270,18 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
     // ${#var} - length of variable
     if content.starts_with('#') && content.len() > 1 {
         let name = &content[1..];
-        let value = env.get_var(name).cloned().unwrap_or_default();
+        let value = lookup_parameter(name, env).unwrap_or_default();
         return Ok(value.len().to_string());
     }
 
+    // ${10}, ${#}, ${?}, ${*}, ${@} - positional and special parameters,
+    // which would otherwise be mistaken for operators below
+    if matches!(content, "#" | "?" | "*" | "@")
+        || (!content.is_empty() && content.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return Ok(lookup_parameter(content, env).unwrap_or_default());
+    }
+
     // ${!var} - indirect expansion or name expansion
     if content.starts_with('!') && content.len() > 1 {
         let indirect_name = &content[1..];
@@ -314,7 +330,7 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
         let name = &content[..at_pos];
         let transform = &content[at_pos + 1..];
         if !name.is_empty() && transform.len() == 1 {
-            let value = env.get_var(name).cloned().unwrap_or_default();
+            let value = lookup_parameter(name, env).unwrap_or_default();
             return apply_transform_operator(&value, transform);
         }
     }
@@ -334,7 +350,7 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
             // Substring expansion starts with a digit, or space followed by negative number
             if c.is_ascii_digit() {
                 // This is substring expansion ${var:offset} or ${var:offset:length}
-                let value = env.get_var(name).cloned().unwrap_or_default();
+                let value = lookup_parameter(name, env).unwrap_or_default();
                 return apply_substring_expansion(&value, rest);
             } else if c == ' ' {
                 // Could be ${var: -5} with space before negative
@@ -342,7 +358,7 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
                 if trimmed.starts_with('-') && trimmed.len() > 1 {
                     let second = trimmed.chars().nth(1);
                     if second.map(|c| c.is_ascii_digit()).unwrap_or(false) {
-                        let value = env.get_var(name).cloned().unwrap_or_default();
+                        let value = lookup_parameter(name, env).unwrap_or_default();
                         return apply_substring_expansion(&value, rest);
                     }
                 }
@@ -366,8 +382,8 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
     }
 
     // No operator - simple variable lookup
-    match env.get_var(content) {
-        Some(val) => Ok(val.clone()),
+    match lookup_parameter(content, env) {
+        Some(val) => Ok(val),
         None => {
             if env.options.nounset {
                 Err(format!("{}: unbound variable", content))
@@ -378,6 +394,89 @@ fn parse_braced_expansion(content: &str, env: &ShellEnv) -> Result<String, Strin
     }
 }
 
+/// Look up a parameter by the name used inside `${...}`: positional
+/// parameters (`${10}`), the special parameters `#`, `?`, `*` and `@`,
+/// or a variable. Returns None when the parameter is unset.
+fn lookup_parameter(name: &str, env: &ShellEnv) -> Option<String> {
+    if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) {
+        let index = name.parse::<usize>().ok()?;
+        return env.get_positional(index).cloned();
+    }
+    match name {
+        "#" => Some(env.param_count().to_string()),
+        "?" => Some(env.last_exit_code.to_string()),
+        "*" | "@" if env.param_count() == 0 => None,
+        "*" => Some(env.all_params_string()),
+        "@" => Some(env.all_params().join(" ")),
+        _ => env.get_var(name).cloned(),
+    }
+}
+
+/// Expand a command word into fields. A `$@` (or `${@}`) in the word
+/// produces one field per positional parameter, with any text before it
+/// joined to the first field and any text after it to the last; with no
+/// parameters, a bare `$@` produces no fields at all. Every other word
+/// expands to exactly one field via [`expand_string`].
+pub fn expand_word_fields(word: &str, env: &ShellEnv) -> Result<Vec<String>, String> {
+    let Some((start, end)) = find_all_params(word) else {
+        return Ok(vec![expand_string(word, env, false)?]);
+    };
+
+    let prefix = expand_string(&word[..start], env, false)?;
+    let mut suffix = if end < word.len() {
+        expand_word_fields(&word[end..], env)?
+    } else {
+        Vec::new()
+    };
+
+    let mut fields = env.all_params().to_vec();
+    if fields.is_empty() {
+        if let Some(first) = suffix.first_mut() {
+            first.insert_str(0, &prefix);
+            return Ok(suffix);
+        }
+        return Ok(if start == 0 { Vec::new() } else { vec![prefix] });
+    }
+
+    fields[0].insert_str(0, &prefix);
+    if !suffix.is_empty() {
+        let rest = suffix.split_off(1);
+        fields.last_mut().unwrap().push_str(&suffix[0]);
+        fields.extend(rest);
+    }
+    Ok(fields)
+}
+
+/// Whether a word contains a top-level `$@` that [`expand_word_fields`]
+/// splits into separate fields
+pub fn contains_all_params(word: &str) -> bool {
+    find_all_params(word).is_some()
+}
+
+/// Find the byte range of the first unescaped `$@` or `${@}` that is not
+/// nested inside another `${...}` or `$(...)`
+fn find_all_params(word: &str) -> Option<(usize, usize)> {
+    let bytes = word.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'$' if depth == 0 && bytes.get(i + 1) == Some(&b'@') => return Some((i, i + 2)),
+            b'$' if depth == 0 && word[i..].starts_with("${@}") => return Some((i, i + 4)),
+            b'$' if matches!(bytes.get(i + 1), Some(b'{') | Some(b'(')) => {
+                depth += 1;
+                i += 1;
+            }
+            b'{' | b'(' if depth > 0 => depth += 1,
+            b'}' | b')' if depth > 0 => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
 /// Apply substring expansion ${var:offset} or ${var:offset:length}
 fn apply_substring_expansion(value: &str, spec: &str) -> Result<String, String> {
     let parts: Vec<&str> = spec.splitn(2, ':').collect();
@@ -491,7 +590,7 @@ fn apply_expansion_operator(
     arg: &str,
     env: &ShellEnv,
 ) -> Result<String, String> {
-    let value = env.get_var(name).cloned();
+    let value = lookup_parameter(name, env);
     let is_unset = value.is_none();
     let is_null = value.as_ref().map(|v| v.is_empty()).unwrap_or(true);
 
@@ -652,23 +751,41 @@ fn apply_expansion_operator(
     }
 }
 
-/// Simple glob matching (supports * and ? wildcards)
-fn glob_match(s: &str, pattern: &str) -> bool {
-    let s = s.as_bytes();
-    let p = pattern.as_bytes();
+/// Glob matching for `*`, `?` and bracket classes: `[abc]`, ranges like
+/// `[a-z]`, negation with `[!abc]` or `[^abc]`, and POSIX classes like
+/// `[[:digit:]]`. A backslash makes the next pattern character literal.
+pub fn glob_match(s: &str, pattern: &str) -> bool {
+    let s: Vec<char> = s.chars().collect();
+    let p: Vec<char> = pattern.chars().collect();
     let mut si = 0;
     let mut pi = 0;
     let mut star_pi: Option<usize> = None;
     let mut star_si = 0;
 
     while si < s.len() {
-        if pi < p.len() && (p[pi] == b'?' || p[pi] == s[si]) {
-            si += 1;
-            pi += 1;
-        } else if pi < p.len() && p[pi] == b'*' {
+        if pi < p.len() && p[pi] == '*' {
             star_pi = Some(pi);
             star_si = si;
             pi += 1;
+            continue;
+        }
+
+        // Pattern characters consumed by matching s[si], if it matches
+        let step = match p.get(pi) {
+            Some('?') => Some(1),
+            Some('[') => match match_bracket(&p[pi..], s[si]) {
+                Some((matched, len)) => matched.then_some(len),
+                // An unterminated `[` is literal
+                None => (s[si] == '[').then_some(1),
+            },
+            Some('\\') if pi + 1 < p.len() => (p[pi + 1] == s[si]).then_some(2),
+            Some(&c) => (c == s[si]).then_some(1),
+            None => None,
+        };
+
+        if let Some(len) = step {
+            si += 1;
+            pi += len;
         } else if let Some(sp) = star_pi {
             pi = sp + 1;
             star_si += 1;
@@ -677,15 +794,70 @@ fn glob_match(s: &str, pattern: &str) -> bool {
             return false;
         }
     }
-    while pi < p.len() && p[pi] == b'*' {
+    while pi < p.len() && p[pi] == '*' {
         pi += 1;
     }
     pi == p.len()
 }
 
+/// Match `c` against the bracket expression starting at `p[0]` (`[`).
+/// Returns whether it matched and the expression's length in pattern
+/// characters, or None if the expression is unterminated.
+fn match_bracket(p: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negate = matches!(p.get(i), Some('!' | '^'));
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let start = i;
+    while i < p.len() {
+        // `]` right after the opening bracket is a literal member
+        if p[i] == ']' && i > start {
+            return Some((matched != negate, i + 1));
+        }
+
+        if p[i] == '[' && p.get(i + 1) == Some(&':') {
+            let rest: String = p[i + 2..].iter().collect();
+            if let Some(end) = rest.find(":]") {
+                let name = &rest[..end];
+                matched |= match name {
+                    "alpha" => c.is_alphabetic(),
+                    "digit" => c.is_ascii_digit(),
+                    "alnum" => c.is_alphanumeric(),
+                    "upper" => c.is_uppercase(),
+                    "lower" => c.is_lowercase(),
+                    "space" => c.is_whitespace(),
+                    "blank" => c == ' ' || c == '\t',
+                    "punct" => c.is_ascii_punctuation(),
+                    "xdigit" => c.is_ascii_hexdigit(),
+                    _ => false,
+                };
+                i += 2 + name.chars().count() + 2;
+                continue;
+            }
+        }
+
+        if p.get(i + 1) == Some(&'-') && p.get(i + 2).is_some_and(|&hi| hi != ']') {
+            matched |= p[i] <= c && c <= p[i + 2];
+            i += 3;
+        } else {
+            matched |= p[i] == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Whether a `${var#pattern}`-style pattern needs glob matching
+fn is_glob_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
 /// Remove prefix matching pattern
 fn remove_prefix(value: &str, pattern: &str, longest: bool) -> String {
-    if pattern.contains('*') || pattern.contains('?') {
+    if is_glob_pattern(pattern) {
         // Glob pattern — try each possible prefix length
         // Collect valid char boundary positions
         let positions: Vec<usize> = value
@@ -719,7 +891,7 @@ fn remove_prefix(value: &str, pattern: &str, longest: bool) -> String {
 
 /// Remove suffix matching pattern
 fn remove_suffix(value: &str, pattern: &str, longest: bool) -> String {
-    if pattern.contains('*') || pattern.contains('?') {
+    if is_glob_pattern(pattern) {
         // Glob pattern — try each possible suffix length
         let positions: Vec<usize> = value
             .char_indices()
@@ -918,24 +1090,30 @@ fn contains_glob_chars(s: &str) -> bool {
 /// Expand pathname/glob patterns like *.txt, /data/*, test?.sh
 ///
 /// Returns a list of matching paths. If no matches are found:
+/// - With failglob: returns a "no match" error
 /// - With nullglob: returns empty vec
-/// - Without nullglob: returns the original pattern as-is
+/// - Otherwise: returns the original pattern as-is
 ///
 /// Respects shell options:
 /// - noglob: disables globbing entirely
+/// - failglob: no matches = error
 /// - nullglob: no matches = empty result
 /// - dotglob: include dotfiles in matches
 /// - nocaseglob: case-insensitive matching
 /// - extglob: extended glob patterns
-pub fn expand_glob(pattern: &str, cwd: &str, opts: &super::env::ShellOptions) -> Vec<String> {
+pub fn expand_glob(
+    pattern: &str,
+    cwd: &str,
+    opts: &super::env::ShellOptions,
+) -> Result<Vec<String>, String> {
     // If noglob is set, don't expand
     if opts.noglob {
-        return vec![pattern.to_string()];
+        return Ok(vec![pattern.to_string()]);
     }
 
     // If no glob characters, return as-is
     if !contains_glob_chars(pattern) {
-        return vec![pattern.to_string()];
+        return Ok(vec![pattern.to_string()]);
     }
 
     // Split pattern into directory and file parts
@@ -958,13 +1136,13 @@ pub fn expand_glob(pattern: &str, cwd: &str, opts: &super::env::ShellOptions) ->
 
     // If the file pattern has no glob chars, return original
     if !contains_glob_chars(&file_pattern) {
-        return vec![pattern.to_string()];
+        return Ok(vec![pattern.to_string()]);
     }
 
     // Convert glob pattern to regex using brush_parser
     let regex_str = match brush_parser::pattern::pattern_to_regex_str(&file_pattern, opts.extglob) {
         Ok(r) => r,
-        Err(_) => return vec![pattern.to_string()],
+        Err(_) => return Ok(vec![pattern.to_string()]),
     };
 
     // Build regex with options
@@ -976,7 +1154,7 @@ pub fn expand_glob(pattern: &str, cwd: &str, opts: &super::env::ShellOptions) ->
 
     let regex = match regex::Regex::new(&regex_pattern) {
         Ok(r) => r,
-        Err(_) => return vec![pattern.to_string()],
+        Err(_) => return Ok(vec![pattern.to_string()]),
     };
 
     // Read directory and match entries
@@ -1005,11 +1183,7 @@ pub fn expand_glob(pattern: &str, cwd: &str, opts: &super::env::ShellOptions) ->
             }
         }
         Err(_) => {
-            // Directory doesn't exist or can't be read
-            if opts.nullglob {
-                return Vec::new();
-            }
-            return vec![pattern.to_string()];
+            // Directory doesn't exist or can't be read: no matches
         }
     }
 
@@ -1018,13 +1192,16 @@ pub fn expand_glob(pattern: &str, cwd: &str, opts: &super::env::ShellOptions) ->
 
     // Handle no matches
     if matches.is_empty() {
+        if opts.failglob {
+            return Err(format!("no match: {}", pattern));
+        }
         if opts.nullglob {
-            return Vec::new();
+            return Ok(Vec::new());
         }
-        return vec![pattern.to_string()];
+        return Ok(vec![pattern.to_string()]);
     }
 
-    matches
+    Ok(matches)
 }
 
 #[cfg(test)]
@@ -1173,6 +1350,52 @@ mod tests {
         assert_eq!(expand_string("$@", &env, false).unwrap(), "a b c");
     }
 
+    #[test]
+    fn test_star_joins_with_first_ifs_char() {
+        let mut env = ShellEnv::new();
+        env.positional_params = vec!["a".to_string(), "b c".to_string()];
+        let _ = env.set_var("IFS", ":-");
+        assert_eq!(expand_string("$*", &env, false).unwrap(), "a:b c");
+        assert_eq!(expand_string("${*}", &env, false).unwrap(), "a:b c");
+        let _ = env.set_var("IFS", "");
+        assert_eq!(expand_string("$*", &env, false).unwrap(), "ab c");
+    }
+
+    #[test]
+    fn test_braced_positional_and_special_params() {
+        let mut env = ShellEnv::new();
+        env.last_exit_code = 3;
+        env.positional_params = (1..=11).map(|i| format!("p{}", i)).collect();
+        assert_eq!(expand_string("${10}", &env, false).unwrap(), "p10");
+        assert_eq!(expand_string("${11}x", &env, false).unwrap(), "p11x");
+        assert_eq!(expand_string("${#}", &env, false).unwrap(), "11");
+        assert_eq!(expand_string("${?}", &env, false).unwrap(), "3");
+        assert_eq!(expand_string("${12:-none}", &env, false).unwrap(), "none");
+        assert_eq!(expand_string("${#10}", &env, false).unwrap(), "3");
+    }
+
+    #[test]
+    fn test_word_fields_split_at_params() {
+        let mut env = ShellEnv::new();
+        env.positional_params = vec!["a b".to_string(), "c".to_string()];
+        assert_eq!(expand_word_fields("$@", &env).unwrap(), vec!["a b", "c"]);
+        assert_eq!(
+            expand_word_fields("[${@}]", &env).unwrap(),
+            vec!["[a b", "c]"]
+        );
+        assert_eq!(
+            expand_word_fields("$1-$@-x", &env).unwrap(),
+            vec!["a b-a b", "c-x"]
+        );
+        assert_eq!(expand_word_fields("$*", &env).unwrap(), vec!["a b c"]);
+        // An escaped $@ is left to expand_string
+        assert_eq!(expand_word_fields("\\$@", &env).unwrap(), vec!["$@"]);
+
+        env.positional_params.clear();
+        assert!(expand_word_fields("$@", &env).unwrap().is_empty());
+        assert_eq!(expand_word_fields("x$@", &env).unwrap(), vec!["x"]);
+    }
+
     #[test]
     fn test_session_id() {
         let env = ShellEnv::new();
@@ -1368,6 +1591,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_glob_match_bracket_classes() {
+        assert!(glob_match("file7", "file[0-9]"));
+        assert!(!glob_match("filex", "file[0-9]"));
+        assert!(glob_match("b", "[!a]"));
+        assert!(!glob_match("a", "[!a]"));
+        assert!(glob_match("c", "[^ab]"));
+        assert!(glob_match("x", "[abcx-z]"));
+        assert!(glob_match("]", "[]a]"));
+        assert!(glob_match("a-1", "[[:alpha:]]-[[:digit:]]"));
+        assert!(glob_match("log.txt", "*.[tl][xo][tg]"));
+        // An unterminated bracket is a literal character
+        assert!(glob_match("[x", "[x"));
+    }
+
+    #[test]
+    fn test_prefix_removal_with_bracket_class() {
+        let mut env = ShellEnv::new();
+        let _ = env.set_var("VERSION", "v12.3");
+
+        assert_eq!(
+            expand_string("${VERSION#[a-z]}", &env, false).unwrap(),
+            "12.3"
+        );
+        assert_eq!(
+            expand_string("${VERSION%.[0-9]}", &env, false).unwrap(),
+            "v12"
+        );
+    }
+
     #[test]
     fn test_replacement() {
         let mut env = ShellEnv::new();
@@ -12,5 +12,6 @@ mod new_executor;
 pub mod parser;
 pub mod pipeline;
 
-pub use env::ShellEnv;
-pub use pipeline::run_pipeline;
+pub use env::{ShellEnv, ShellResult};
+pub use new_executor::{poll_background_jobs, run_trap};
+pub use pipeline::{run_pipeline, run_pipeline_streaming};
@@ -8,35 +8,159 @@
 //! - Async cooperative command execution
 
 use super::commands::ShellCommands;
-use super::env::{ShellEnv, ShellResult};
+use super::env::{Job, ShellEnv, ShellResult};
 use super::expand;
 use super::parser::ParsedCommand;
 use super::parser::ParsedRedirect;
 use futures::future::{join, join_all};
 use futures_lite::io::AsyncWriteExt;
+use std::rc::Rc;
 
 const PIPE_CAPACITY: usize = 65536;
 
-/// Main entry point - parse and execute a shell command
-pub async fn run_shell(cmd_line: &str, env: &mut ShellEnv) -> ShellResult {
+/// A background job's pending execution.
+type JobFuture = std::pin::Pin<Box<dyn std::future::Future<Output = ShellResult>>>;
+
+thread_local! {
+    /// Futures of running background jobs, keyed by pid. They live here
+    /// rather than in `ShellEnv`, which is cloned and moved into commands.
+    static JOB_FUTURES: std::cell::RefCell<std::collections::HashMap<u64, JobFuture>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+}
+
+/// Main entry point - parse and execute a shell command, writing stdout and
+/// stderr to the given pipes as they are produced: chunk by chunk for a
+/// top-level pipeline, otherwise as each top-level command finishes.
+/// Returns the exit code; the pipes close when the writers are dropped.
+pub async fn run_shell_streaming(
+    cmd_line: &str,
+    env: &mut ShellEnv,
+    mut stdout: piper::Writer,
+    mut stderr: piper::Writer,
+) -> i32 {
     let cmd_line = cmd_line.trim();
 
     // Handle empty/comment-only
     if cmd_line.is_empty() || cmd_line.starts_with('#') {
-        return ShellResult::success("");
+        return 0;
     }
 
     // Parse with brush-parser
-    match super::parser::parse_command(cmd_line) {
-        Ok(parsed_cmds) if !parsed_cmds.is_empty() => {
-            execute_sequence(&parsed_cmds, env, None).await
+    let parsed_cmds = match super::parser::parse_command(cmd_line) {
+        Ok(parsed_cmds) => parsed_cmds,
+        Err(e) => {
+            let _ = stderr
+                .write_all(format!("parse error: {}", e).as_bytes())
+                .await;
+            return 2;
         }
-        Ok(_) => ShellResult::success(""),
-        Err(e) => ShellResult {
-            stdout: String::new(),
-            stderr: format!("parse error: {}", e),
-            code: 2,
-        },
+    };
+
+    let mut code = 0;
+    for cmd in &parsed_cmds {
+        let result = execute_command_streaming(cmd, env, &mut stdout).await;
+        write_result(&result, &mut stdout, &mut stderr).await;
+        code = result.code;
+        env.last_exit_code = result.code;
+
+        // Background jobs get a turn between top-level commands
+        let jobs = poll_background_jobs(env).await;
+        write_result(&jobs, &mut stdout, &mut stderr).await;
+
+        // A pending return, exit, break or continue skips the rest of the list
+        if env.returning || env.exiting || env.break_level > 0 || env.continue_level > 0 {
+            break;
+        }
+    }
+
+    // Background jobs and the EXIT trap report once the whole line has run
+    let mut tail = ShellResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        code,
+    };
+    finish_background_jobs(env, &mut tail).await;
+    run_exit_trap(env, &mut tail).await;
+    write_result(&tail, &mut stdout, &mut stderr).await;
+    tail.code
+}
+
+/// Execute a top-level command for [`run_shell_streaming`]. A pipeline's
+/// stdout is written to `stdout` directly rather than returned.
+async fn execute_command_streaming(
+    cmd: &ParsedCommand,
+    env: &mut ShellEnv,
+    stdout: &mut piper::Writer,
+) -> ShellResult {
+    if env.is_cancelled() {
+        return cancelled_result();
+    }
+
+    match cmd {
+        ParsedCommand::Pipeline { commands, negate } => {
+            let result = execute_pipeline(commands, env, None, Some(stdout)).await;
+            if *negate {
+                ShellResult {
+                    code: if result.code == 0 { 1 } else { 0 },
+                    ..result
+                }
+            } else {
+                check_errexit(env, result.code);
+                result
+            }
+        }
+        _ => execute_command_to(cmd, env, None, stdout).await,
+    }
+}
+
+/// Execute a single command, writing its stdout to `stdout`. A registry
+/// command's output is forwarded as it is produced; anything else is
+/// written once the command finishes.
+async fn execute_command_to(
+    cmd: &ParsedCommand,
+    env: &mut ShellEnv,
+    stdin: Option<StdinData>,
+    stdout: &mut piper::Writer,
+) -> ShellResult {
+    let mut result = match cmd {
+        ParsedCommand::Simple {
+            name,
+            args,
+            redirects,
+            env_vars,
+        } if !env.is_cancelled() => {
+            let result = execute_simple(
+                name,
+                args,
+                env_vars,
+                redirects,
+                env,
+                stdin,
+                Some(&mut *stdout),
+            )
+            .await;
+            check_errexit(env, result.code);
+            result
+        }
+        _ => Box::pin(execute_command(cmd, env, stdin)).await,
+    };
+    let _ = stdout.write_all(result.stdout.as_bytes()).await;
+    result.stdout.clear();
+    result
+}
+
+/// Write a finished command's output to the streaming pipes. Write errors
+/// mean the reader has gone away, so the output is dropped.
+async fn write_result(
+    result: &ShellResult,
+    stdout: &mut piper::Writer,
+    stderr: &mut piper::Writer,
+) {
+    if !result.stdout.is_empty() {
+        let _ = stdout.write_all(result.stdout.as_bytes()).await;
+    }
+    if !result.stderr.is_empty() {
+        let _ = stderr.write_all(result.stderr.as_bytes()).await;
     }
 }
 
@@ -44,21 +168,28 @@ pub async fn run_shell(cmd_line: &str, env: &mut ShellEnv) -> ShellResult {
 pub async fn execute_sequence(
     commands: &[ParsedCommand],
     env: &mut ShellEnv,
-    stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
     let mut combined_stdout = String::new();
     let mut combined_stderr = String::new();
     let mut last_code = 0i32;
+    let mut input = SharedStdin::new(stdin);
 
     for cmd in commands {
-        let result = execute_command(cmd, env, stdin.clone()).await;
+        let result = execute_command(cmd, env, input.next(env)).await;
+        input.advance(env);
         combined_stdout.push_str(&result.stdout);
         combined_stderr.push_str(&result.stderr);
         last_code = result.code;
         env.last_exit_code = result.code;
 
-        // For And/Or chains, the branching is handled inside execute_command
+        // For And/Or chains, the branching is handled inside execute_command.
+        // A pending return, exit, break or continue skips the rest of the list.
+        if env.returning || env.exiting || env.break_level > 0 || env.continue_level > 0 {
+            break;
+        }
     }
+    input.finish(env);
 
     ShellResult {
         stdout: combined_stdout,
@@ -71,31 +202,51 @@ pub async fn execute_sequence(
 pub async fn execute_command(
     cmd: &ParsedCommand,
     env: &mut ShellEnv,
-    stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
+    if env.is_cancelled() {
+        return cancelled_result();
+    }
+
     match cmd {
         ParsedCommand::Simple {
             name,
             args,
             redirects,
             env_vars,
-        } => execute_simple(name, args, env_vars, redirects, env, stdin).await,
+        } => {
+            let result = execute_simple(name, args, env_vars, redirects, env, stdin, None).await;
+            check_errexit(env, result.code);
+            result
+        }
 
         ParsedCommand::Pipeline { commands, negate } => {
-            let result = execute_pipeline(commands, env, stdin).await;
+            // `set -e` ignores a `!` pipeline, including a lone command in it
+            if *negate {
+                env.errexit_suppressed += 1;
+            }
+            let result = execute_pipeline(commands, env, stdin, None).await;
+            if *negate {
+                env.errexit_suppressed -= 1;
+            }
+            // Only a lone command's `read` consumes from the enclosing list
+            if commands.len() > 1 {
+                env.stdin_consumed = 0;
+            }
             if *negate {
                 ShellResult {
                     code: if result.code == 0 { 1 } else { 0 },
                     ..result
                 }
             } else {
+                check_errexit(env, result.code);
                 result
             }
         }
 
         ParsedCommand::And(left, right) => {
-            let left_result = Box::pin(execute_command(left, env, stdin.clone())).await;
-            if left_result.code == 0 {
+            let left_result = Box::pin(execute_condition(left, env, stdin.clone())).await;
+            if left_result.code == 0 && !env.returning && !env.exiting {
                 let right_result = Box::pin(execute_command(right, env, None)).await;
                 ShellResult {
                     stdout: format!("{}{}", left_result.stdout, right_result.stdout),
@@ -108,8 +259,8 @@ pub async fn execute_command(
         }
 
         ParsedCommand::Or(left, right) => {
-            let left_result = Box::pin(execute_command(left, env, stdin.clone())).await;
-            if left_result.code != 0 {
+            let left_result = Box::pin(execute_condition(left, env, stdin.clone())).await;
+            if left_result.code != 0 && !env.returning && !env.exiting {
                 let right_result = Box::pin(execute_command(right, env, None)).await;
                 ShellResult {
                     stdout: format!("{}{}", left_result.stdout, right_result.stdout),
@@ -127,6 +278,13 @@ pub async fn execute_command(
             execute_while(condition, body, env, stdin).await
         }
 
+        ParsedCommand::ArithFor {
+            init,
+            condition,
+            update,
+            body,
+        } => execute_arith_for(init, condition, update, body, env).await,
+
         ParsedCommand::If {
             conditionals,
             else_branch,
@@ -135,8 +293,21 @@ pub async fn execute_command(
         ParsedCommand::Case { word, cases } => execute_case(word, cases, env, stdin).await,
 
         ParsedCommand::Subshell(commands) => {
-            // Subshell executes in a copy of the environment (simplified for now)
-            Box::pin(execute_sequence(commands, env, stdin)).await
+            // Subshell executes in a copy of the environment (simplified for now).
+            // An EXIT trap set inside fires when it ends; the parent's is kept.
+            let outer_exit_trap = env.traps.remove("EXIT");
+            let mut result = Box::pin(execute_sequence(commands, env, stdin)).await;
+            if let Some(trap) = run_trap("EXIT", env).await {
+                result.stdout.push_str(&trap.stdout);
+                result.stderr.push_str(&trap.stderr);
+            }
+            if let Some(trap) = outer_exit_trap {
+                env.traps.insert("EXIT".to_string(), trap);
+            }
+            // An `exit` inside only ends the subshell
+            env.exiting = false;
+            check_errexit(env, result.code);
+            result
         }
 
         ParsedCommand::Brace(commands) => {
@@ -145,42 +316,98 @@ pub async fn execute_command(
         }
 
         ParsedCommand::FunctionDef { name, body } => {
-            define_function(name, body, env);
+            env.functions.insert(name.clone(), (**body).clone());
             ShellResult::success("")
         }
 
         ParsedCommand::Background(cmd) => {
-            // For single-threaded WASM, background is same as foreground
-            Box::pin(execute_command(cmd, env, stdin)).await
-        }
-
-        ParsedCommand::Timed(cmd) => {
-            use crate::bindings::wasi::clocks::monotonic_clock;
-            let start = monotonic_clock::now();
-            let mut result = Box::pin(execute_command(cmd, env, stdin)).await;
-            let elapsed = monotonic_clock::now() - start;
-            let elapsed_secs = elapsed as f64 / 1_000_000_000.0;
-            let mins = (elapsed_secs / 60.0) as u64;
-            let secs = elapsed_secs % 60.0;
-            let timing = format!("\nreal\t{}m{:.3}s\n", mins, secs);
-            result.stderr.push_str(&timing);
+            // The job runs in its own subshell and makes progress when polled:
+            // between top-level commands, before an interactive prompt, and
+            // by `jobs`, `wait` or the end of the run
+            let mut job_env = env.subshell();
+            let job_cmd = (**cmd).clone();
+            let future: JobFuture =
+                Box::pin(async move { execute_command(&job_cmd, &mut job_env, None).await });
+            let job = env.add_job(to_shell_string(cmd));
+            JOB_FUTURES.with(|jobs| jobs.borrow_mut().insert(job.pid, future));
+            ShellResult {
+                stdout: String::new(),
+                stderr: format!("[{}] {}\n", job.id, job.pid),
+                code: 0,
+            }
+        }
+
+        ParsedCommand::Timed { command, posix } => {
+            let start = std::time::Instant::now();
+            let mut result = Box::pin(execute_command(command, env, stdin)).await;
+            result
+                .stderr
+                .push_str(&format_time_report(start.elapsed(), *posix));
             result
         }
     }
 }
 
-/// Execute a pipeline, threading stdout → stdin between commands
+/// Execute a command whose status is tested (the left side of `&&`/`||`,
+/// an `if` or `while` condition), where `set -e` does not apply
+async fn execute_condition(
+    cmd: &ParsedCommand,
+    env: &mut ShellEnv,
+    stdin: Option<StdinData>,
+) -> ShellResult {
+    env.errexit_suppressed += 1;
+    let result = Box::pin(execute_command(cmd, env, stdin)).await;
+    env.errexit_suppressed -= 1;
+    result
+}
+
+/// Under `set -e`, a failing command outside a condition exits the shell
+fn check_errexit(env: &mut ShellEnv, code: i32) {
+    if code != 0 && env.options.errexit && env.errexit_suppressed == 0 {
+        env.exiting = true;
+    }
+}
+
+/// Result of a command that was not started because the run was cancelled.
+/// Exits 130, as for an interrupt.
+fn cancelled_result() -> ShellResult {
+    ShellResult::error("cancelled\n", 130)
+}
+
+/// Render the `time` report. The sandbox has no per-process CPU accounting,
+/// so user and sys are always reported as zero.
+fn format_time_report(elapsed: std::time::Duration, posix: bool) -> String {
+    let secs = elapsed.as_secs_f64();
+    if posix {
+        format!("real {:.2}\nuser 0.00\nsys 0.00\n", secs)
+    } else {
+        let mins = (secs / 60.0) as u64;
+        format!(
+            "\nreal\t{}m{:.3}s\nuser\t0m0.000s\nsys\t0m0.000s\n",
+            mins,
+            secs % 60.0
+        )
+    }
+}
+
+/// Execute a pipeline, threading stdout → stdin between commands.
+/// With `stdout` given, the final stdout is written there as it is produced
+/// instead of being returned.
 async fn execute_pipeline(
     commands: &[ParsedCommand],
     env: &mut ShellEnv,
-    initial_stdin: Option<Vec<u8>>,
+    initial_stdin: Option<StdinData>,
+    stdout: Option<&mut piper::Writer>,
 ) -> ShellResult {
     if commands.is_empty() {
         return ShellResult::success("");
     }
 
     if commands.len() == 1 {
-        return Box::pin(execute_command(&commands[0], env, initial_stdin)).await;
+        return match stdout {
+            Some(writer) => execute_command_to(&commands[0], env, initial_stdin, writer).await,
+            None => Box::pin(execute_command(&commands[0], env, initial_stdin)).await,
+        };
     }
 
     // Fast path: if every stage is a plain shell command, run the pipeline
@@ -188,7 +415,7 @@ async fn execute_pipeline(
     // This avoids deadlocks like `yes | head -n 3` in the legacy sequential model.
     match try_prepare_streaming_pipeline(commands, env).await {
         Ok(Some(stages)) => {
-            return execute_prepared_streaming_pipeline(stages, initial_stdin).await;
+            return execute_prepared_streaming_pipeline(stages, initial_stdin, stdout).await;
         }
         Ok(None) => {
             // Fall back to legacy behavior for complex stages.
@@ -201,6 +428,8 @@ async fn execute_pipeline(
     let mut final_result = ShellResult::success("");
     let mut all_stderr = String::new();
 
+    // `set -e` looks at the status of the whole pipeline, not each stage
+    env.errexit_suppressed += 1;
     for cmd in commands {
         let result = Box::pin(execute_command(cmd, env, current_stdin)).await;
 
@@ -208,7 +437,7 @@ async fn execute_pipeline(
         current_stdin = if result.stdout.is_empty() {
             None
         } else {
-            Some(result.stdout.into_bytes())
+            Some(result.stdout.into_bytes().into())
         };
 
         // Accumulate stderr
@@ -221,11 +450,18 @@ async fn execute_pipeline(
             code: result.code,
         };
     }
+    env.errexit_suppressed -= 1;
+    // Stages run as if in subshells, so an `exit` in one only ends that stage
+    env.exiting = false;
 
     // Final stdout is whatever the last command produced
-    let final_stdout = current_stdin
+    let mut final_stdout = current_stdin
         .map(|b| String::from_utf8_lossy(&b).to_string())
         .unwrap_or_default();
+    if let Some(writer) = stdout {
+        let _ = writer.write_all(final_stdout.as_bytes()).await;
+        final_stdout.clear();
+    }
 
     ShellResult {
         stdout: final_stdout,
@@ -238,6 +474,8 @@ struct PreparedPipelineStage {
     cmd_fn: super::commands::CommandFn,
     args: Vec<String>,
     env: ShellEnv,
+    /// `set -x` trace line, empty when tracing is off
+    trace: String,
 }
 
 /// Attempt to prepare a pipeline for streaming execution.
@@ -281,18 +519,24 @@ async fn try_prepare_streaming_pipeline(
         for arg in args {
             let brace_expanded = expand::expand_braces(arg);
             for be in brace_expanded {
-                let expanded = match expand::expand_string(&be, &stage_env, false) {
-                    Ok(s) => s,
+                let fields = match expand::expand_word_fields(&be, &stage_env) {
+                    Ok(fields) => fields,
                     Err(e) => return Err(ShellResult::error(e, 1)),
                 };
-                let final_exp =
-                    super::pipeline::execute_command_substitutions(&expanded, &mut stage_env).await;
-                let glob_results = expand::expand_glob(
-                    &final_exp,
-                    &stage_env.cwd.to_string_lossy(),
-                    &stage_env.options,
-                );
-                expanded_args.extend(glob_results);
+                for expanded in fields {
+                    let final_exp =
+                        super::pipeline::execute_command_substitutions(&expanded, &mut stage_env)
+                            .await;
+                    let glob_results = match expand::expand_glob(
+                        &final_exp,
+                        &stage_env.cwd.to_string_lossy(),
+                        &stage_env.options,
+                    ) {
+                        Ok(paths) => paths,
+                        Err(e) => return Err(ShellResult::error(e, 1)),
+                    };
+                    expanded_args.extend(glob_results);
+                }
             }
         }
 
@@ -301,11 +545,25 @@ async fn try_prepare_streaming_pipeline(
         let Some(cmd_fn) = ShellCommands::get_command(&expanded_name) else {
             return Ok(None);
         };
+        if runs_through_executor(&expanded_name, &expanded_args) {
+            return Ok(None);
+        }
+
+        let trace = if stage_env.options.xtrace {
+            let words: Vec<String> = std::iter::once(&expanded_name)
+                .chain(&expanded_args)
+                .map(|word| xtrace_quote(word))
+                .collect();
+            format!("+ {}\n", words.join(" "))
+        } else {
+            String::new()
+        };
 
         stages.push(PreparedPipelineStage {
             cmd_fn,
             args: expanded_args,
             env: stage_env,
+            trace,
         });
     }
 
@@ -313,15 +571,20 @@ async fn try_prepare_streaming_pipeline(
 }
 
 /// Execute a prepared pipeline concurrently with pipe-connected stages.
+/// The last stage's stdout is copied to `stdout` if given, else collected.
 async fn execute_prepared_streaming_pipeline(
     stages: Vec<PreparedPipelineStage>,
-    initial_stdin: Option<Vec<u8>>,
+    initial_stdin: Option<StdinData>,
+    stdout: Option<&mut piper::Writer>,
 ) -> ShellResult {
     let stage_count = stages.len();
     if stage_count == 0 {
         return ShellResult::success("");
     }
 
+    // Trace lines come before anything the stages write to stderr
+    let mut stderr: String = stages.iter().map(|stage| stage.trace.as_str()).collect();
+
     // stdin for stage 0
     let (stage0_stdin_reader, mut stage0_stdin_writer) = piper::pipe(PIPE_CAPACITY);
     if let Some(data) = initial_stdin {
@@ -376,7 +639,9 @@ async fn execute_prepared_streaming_pipeline(
         };
 
         let stderr_writer = stderr_writers[i].take().expect("missing stderr writer");
-        let PreparedPipelineStage { cmd_fn, args, env } = stage;
+        let PreparedPipelineStage {
+            cmd_fn, args, env, ..
+        } = stage;
 
         stage_futures.push(async move {
             cmd_fn(args, &env, stdin_reader, stdout_writer, stderr_writer).await
@@ -385,13 +650,22 @@ async fn execute_prepared_streaming_pipeline(
 
     let stderr_futures: Vec<_> = stderr_readers.into_iter().map(drain_reader).collect();
 
+    let stdout_future = async move {
+        match stdout {
+            Some(writer) => {
+                forward_reader(final_stdout_reader, writer).await;
+                Vec::new()
+            }
+            None => drain_reader(final_stdout_reader).await,
+        }
+    };
+
     let (codes, (stderr_chunks, stdout_bytes)) = join(
         join_all(stage_futures),
-        join(join_all(stderr_futures), drain_reader(final_stdout_reader)),
+        join(join_all(stderr_futures), stdout_future),
     )
     .await;
 
-    let mut stderr = String::new();
     for chunk in stderr_chunks {
         stderr.push_str(&String::from_utf8_lossy(&chunk));
     }
@@ -406,14 +680,16 @@ async fn execute_prepared_streaming_pipeline(
     }
 }
 
-/// Execute a simple command with stdin support
+/// Execute a simple command with stdin support. With `stdout` given, a
+/// registry command's stdout is written there as it is produced.
 async fn execute_simple(
     name: &str,
     args: &[String],
     env_vars: &[(String, String)],
     redirects: &[ParsedRedirect],
     env: &mut ShellEnv,
-    stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
+    stdout: Option<&mut piper::Writer>,
 ) -> ShellResult {
     // Expand command name
     let expanded_name = match expand::expand_string(name, env, false) {
@@ -431,38 +707,104 @@ async fn execute_simple(
         let brace_expanded = expand::expand_braces(arg);
 
         for be in brace_expanded {
-            let expanded = match expand::expand_string(&be, env, false) {
-                Ok(s) => s,
+            // "$@" expands to one field per positional parameter
+            let fields = match expand::expand_word_fields(&be, env) {
+                Ok(fields) => fields,
                 Err(e) => return ShellResult::error(e, 1),
             };
-            let final_exp = super::pipeline::execute_command_substitutions(&expanded, env).await;
-
-            // Finally, do pathname/glob expansion
-            let glob_results =
-                expand::expand_glob(&final_exp, &env.cwd.to_string_lossy(), &env.options);
-            expanded_args.extend(glob_results);
+            for expanded in fields {
+                let final_exp =
+                    super::pipeline::execute_command_substitutions(&expanded, env).await;
+
+                // Finally, do pathname/glob expansion
+                let glob_results =
+                    match expand::expand_glob(&final_exp, &env.cwd.to_string_lossy(), &env.options)
+                    {
+                        Ok(paths) => paths,
+                        Err(e) => return ShellResult::error(e, 1),
+                    };
+                expanded_args.extend(glob_results);
+            }
         }
     }
 
     // Set temporary environment variables (or permanent if no command)
+    let mut trace = Vec::new();
     for (key, value) in env_vars {
         // Check if variable is readonly
         if env.readonly.contains(key) {
             return ShellResult::error(format!("{}: readonly variable", key), 1);
         }
-        let expanded = expand::expand_string(value, env, false).unwrap_or_else(|_| value.clone());
+        let expanded = match expand::expand_string(value, env, false) {
+            Ok(s) => s,
+            Err(e) => return ShellResult::error(e, 1),
+        };
         // Process command substitution markers (e.g. COUNT=$(wc -l file))
         let final_val = super::pipeline::execute_command_substitutions(&expanded, env).await;
+        if env.options.xtrace {
+            trace.push(format!("{}={}", key, xtrace_quote(&final_val)));
+        }
         let _ = env.set_var(key, &final_val);
     }
 
+    // set -x: print the expanded command before running it
+    if env.options.xtrace {
+        trace.extend(
+            std::iter::once(&expanded_name)
+                .chain(&expanded_args)
+                .filter(|word| !word.is_empty())
+                .map(|word| xtrace_quote(word)),
+        );
+    }
+    let trace = if trace.is_empty() {
+        String::new()
+    } else {
+        format!("+ {}\n", trace.join(" "))
+    };
+
+    let mut result = Box::pin(run_expanded_simple(
+        &expanded_name,
+        expanded_args,
+        redirects,
+        env,
+        stdin,
+        stdout,
+    ))
+    .await;
+    result.stderr.insert_str(0, &trace);
+    result
+}
+
+/// Render a word for a `set -x` trace line, quoting it the way it would
+/// have to be typed
+fn xtrace_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./=:,+%@^".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// Run a simple command once its name and arguments are expanded
+async fn run_expanded_simple(
+    expanded_name: &str,
+    expanded_args: Vec<String>,
+    redirects: &[ParsedRedirect],
+    env: &mut ShellEnv,
+    stdin: Option<StdinData>,
+    stdout: Option<&mut piper::Writer>,
+) -> ShellResult {
     // If no command name, this is just a variable assignment
     if expanded_name.is_empty() {
         return ShellResult::success("");
     }
 
     // Handle builtins that need special treatment
-    match expanded_name.as_str() {
+    match expanded_name {
         // No-op commands
         ":" => return ShellResult::success(""),
         "true" => return ShellResult::success(""),
@@ -543,10 +885,45 @@ async fn execute_simple(
             if !env.in_function {
                 return ShellResult::error("return: can only be used in a function", 1);
             }
-            let code = expanded_args
-                .first()
-                .and_then(|a| a.parse().ok())
-                .unwrap_or(0);
+            // Without an argument, return the status of the last command
+            let code = match expanded_args.first() {
+                Some(arg) => match arg.parse::<i32>() {
+                    Ok(n) => n & 0xff,
+                    Err(_) => {
+                        env.returning = true;
+                        return ShellResult::error(
+                            format!("return: {}: numeric argument required", arg),
+                            2,
+                        );
+                    }
+                },
+                None => env.last_exit_code,
+            };
+            env.returning = true;
+            return ShellResult {
+                code,
+                stdout: String::new(),
+                stderr: String::new(),
+            };
+        }
+
+        // Exit (from the shell)
+        "exit" => {
+            // Without an argument, exit with the status of the last command
+            let code = match expanded_args.first() {
+                Some(arg) => match arg.parse::<i32>() {
+                    Ok(n) => n & 0xff,
+                    Err(_) => {
+                        env.exiting = true;
+                        return ShellResult::error(
+                            format!("exit: {}: numeric argument required", arg),
+                            2,
+                        );
+                    }
+                },
+                None => env.last_exit_code,
+            };
+            env.exiting = true;
             return ShellResult {
                 code,
                 stdout: String::new(),
@@ -830,16 +1207,82 @@ async fn execute_simple(
             return handle_declare_builtin(&expanded_args, env);
         }
 
-        // type — describe a command
+        // type — describe how a command resolves; which — its path only
         "type" => {
             return handle_type_builtin(&expanded_args, env);
         }
+        "which" => {
+            return handle_which_builtin(&expanded_args);
+        }
+
+        // Job control
+        "jobs" => return handle_jobs_builtin(&expanded_args, env).await,
+        "wait" => return handle_wait_builtin(&expanded_args, env).await,
+
+        "trap" => return handle_trap_builtin(&expanded_args, env),
+
+        // retry - re-run a command line until it succeeds
+        "retry" => return handle_retry_builtin(&expanded_args, env).await,
+
+        // dotenv run / find -exec - the commands may be functions or lazy modules
+        "dotenv" if runs_through_executor(expanded_name, &expanded_args) => {
+            return handle_dotenv_run_builtin(&expanded_args[1..], env, stdin).await;
+        }
+        "find" if runs_through_executor(expanded_name, &expanded_args) => {
+            let result = handle_find_exec_builtin(&expanded_args, env).await;
+            let (stdout, stderr) = handle_output_redirects(
+                result.stdout.into_bytes(),
+                result.stderr.into_bytes(),
+                redirects,
+                &env.cwd.to_string_lossy(),
+            );
+            return ShellResult {
+                stdout,
+                stderr,
+                code: result.code,
+            };
+        }
+
+        // (( expr )) - arithmetic command; succeeds when the result is nonzero
+        "((" => {
+            let expr = expanded_args.first().map(String::as_str).unwrap_or("");
+            return match super::arithmetic::evaluate(expr, env) {
+                Ok(value) => ShellResult {
+                    stdout: String::new(),
+                    stderr: String::new(),
+                    code: i32::from(value == 0),
+                },
+                Err(e) => ShellResult::error(format!("((: {}: {}", expr.trim(), e), 1),
+            };
+        }
+
+        "read" => {
+            // Input redirected to this command isn't the list's shared stdin
+            let redirected = redirects.iter().any(|redirect| {
+                matches!(
+                    redirect,
+                    ParsedRedirect::Read { .. }
+                        | ParsedRedirect::Heredoc { .. }
+                        | ParsedRedirect::HereString { .. }
+                )
+            });
+            let input = match get_stdin_data(stdin, redirects, env).await {
+                Ok(data) => data,
+                Err(err_result) => return err_result,
+            };
+            let result =
+                handle_read_builtin(&expanded_args, input.as_deref().unwrap_or_default(), env);
+            if redirected {
+                env.stdin_consumed = 0;
+            }
+            return result;
+        }
 
         _ => {}
     }
 
     // Check if this is a function call
-    if let Some(body) = env.functions.get(&expanded_name).cloned() {
+    if let Some(body) = env.functions.get(expanded_name).cloned() {
         return call_function(&body, &expanded_args, env, stdin).await;
     }
 
@@ -938,7 +1381,7 @@ async fn execute_simple(
                 }
 
                 // Get stdin data and write it to the process
-                let stdin_data = match get_stdin_data(stdin, redirects, env) {
+                let stdin_data = match get_stdin_data(stdin, redirects, env).await {
                     Ok(data) => data,
                     Err(err_result) => return err_result,
                 };
@@ -1019,7 +1462,7 @@ async fn execute_simple(
     let (stderr_reader, stderr_writer) = piper::pipe(PIPE_CAPACITY);
 
     // Handle stdin - from parameter or from redirect
-    let stdin_data = match get_stdin_data(stdin, redirects, env) {
+    let stdin_data = match get_stdin_data(stdin, redirects, env).await {
         Ok(data) => data,
         Err(err_result) => return err_result,
     };
@@ -1039,7 +1482,24 @@ async fn execute_simple(
         stdout_writer,
         stderr_writer,
     );
-    let stdout_drain = drain_reader(stdout_reader);
+    // Output redirected to a file is written once the command finishes
+    let redirects_stdout = redirects.iter().any(|redirect| {
+        matches!(
+            redirect,
+            ParsedRedirect::Write { .. }
+                | ParsedRedirect::Append { .. }
+                | ParsedRedirect::DupWrite { .. }
+        )
+    });
+    let stdout_drain = async move {
+        match stdout.filter(|_| !redirects_stdout) {
+            Some(writer) => {
+                forward_reader(stdout_reader, writer).await;
+                Vec::new()
+            }
+            None => drain_reader(stdout_reader).await,
+        }
+    };
     let stderr_drain = drain_reader(stderr_reader);
 
     let (code, (stdout_bytes, stderr_bytes)) =
@@ -1061,11 +1521,11 @@ async fn execute_simple(
 }
 
 /// Get stdin data from parameter or redirect
-fn get_stdin_data(
-    stdin: Option<Vec<u8>>,
+async fn get_stdin_data(
+    stdin: Option<StdinData>,
     redirects: &[ParsedRedirect],
-    env: &ShellEnv,
-) -> Result<Option<Vec<u8>>, ShellResult> {
+    env: &mut ShellEnv,
+) -> Result<Option<StdinData>, ShellResult> {
     // Check for stdin redirect (< file) and heredoc/herestring
     for redirect in redirects {
         match redirect {
@@ -1076,18 +1536,29 @@ fn get_stdin_data(
                     format!("{}/{}", env.cwd.to_string_lossy(), target)
                 };
                 match std::fs::read(&full_path) {
-                    Ok(content) => return Ok(Some(content)),
+                    Ok(content) => return Ok(Some(content.into())),
                     Err(e) => return Err(ShellResult::error(format!("{}: {}", target, e), 1)),
                 }
             }
-            ParsedRedirect::Heredoc { content, .. } => {
-                return Ok(Some(content.as_bytes().to_vec()));
+            ParsedRedirect::Heredoc {
+                content, expand, ..
+            } => {
+                if !*expand {
+                    return Ok(Some(content.as_bytes().to_vec().into()));
+                }
+                let expanded = expand::expand_string(&heredoc_to_expandable(content), env, true)
+                    .map_err(|e| ShellResult::error(e, 1))?;
+                let expanded = super::pipeline::execute_command_substitutions(&expanded, env).await;
+                return Ok(Some(expanded.into_bytes().into()));
             }
             ParsedRedirect::HereString { content, .. } => {
+                let expanded = expand::expand_string(content, env, false)
+                    .map_err(|e| ShellResult::error(e, 1))?;
+                let expanded = super::pipeline::execute_command_substitutions(&expanded, env).await;
                 // Here-strings append a newline
-                let mut data = content.as_bytes().to_vec();
+                let mut data = expanded.into_bytes();
                 data.push(b'\n');
-                return Ok(Some(data));
+                return Ok(Some(data.into()));
             }
             _ => {}
         }
@@ -1097,6 +1568,47 @@ fn get_stdin_data(
     Ok(stdin)
 }
 
+/// Prepare an unquoted here-document body for [`expand::expand_string`] in
+/// double-quote mode: backticks become `$(...)`, backslash-newline joins
+/// lines, and `\"` is kept as written since `"` is not special in a heredoc.
+fn heredoc_to_expandable(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('"') => out.push_str("\\\\\""),
+                Some(next) => {
+                    out.push('\\');
+                    out.push(next);
+                }
+                None => out.push('\\'),
+            },
+            '`' => {
+                out.push_str("$(");
+                while let Some(inner) = chars.next() {
+                    match inner {
+                        '`' => break,
+                        '\\' => match chars.next() {
+                            Some(escaped @ ('`' | '\\' | '$')) => out.push(escaped),
+                            Some(other) => {
+                                out.push('\\');
+                                out.push(other);
+                            }
+                            None => out.push('\\'),
+                        },
+                        _ => out.push(inner),
+                    }
+                }
+                out.push(')');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 /// Handle output redirects (>, >>, 2>, etc.)
 fn handle_output_redirects(
     stdout_bytes: Vec<u8>,
@@ -1406,77 +1918,763 @@ fn eval_simple_arithmetic(expr: &str) -> String {
         .unwrap_or_else(|_| "0".to_string())
 }
 
-/// List of shell builtins for the `type` command
-const SHELL_BUILTINS: &[&str] = &[
-    ":", "true", "false", "export", "unset", "set", "shopt", "readonly", "local", "return",
-    "break", "continue", "cd", "pushd", "popd", "dirs", "pwd", "eval", "alias", "unalias",
-    "getopts", "source", ".", "shift", "declare", "typeset", "type", "echo", "printf", "read",
-    "test", "[", "exit", "trap", "wait", "jobs", "bg", "fg", "kill", "umask", "hash", "command",
-    "builtin", "exec", "let",
-];
-
-/// Handle type builtin
-fn handle_type_builtin(args: &[String], env: &ShellEnv) -> ShellResult {
-    if args.is_empty() {
-        return ShellResult::error("type: usage: type name [name ...]", 1);
-    }
-
-    let mut output = String::new();
-    let mut all_found = true;
-
-    for name in args {
-        if let Some(value) = env.aliases.get(name) {
-            output.push_str(&format!("{} is aliased to `{}'\n", name, value));
-        } else if env.functions.contains_key(name) {
-            output.push_str(&format!("{} is a function\n", name));
-        } else if SHELL_BUILTINS.contains(&name.as_str()) {
-            output.push_str(&format!("{} is a shell builtin\n", name));
-        } else if ShellCommands::get_command(name).is_some() {
-            output.push_str(&format!("{} is {}\n", name, name));
-        } else {
-            output.push_str(&format!("-bash: type: {}: not found\n", name));
-            all_found = false;
+/// Give each running background job one poll, recording the status of
+/// jobs that finish. Returns their output for the caller to emit.
+async fn poll_jobs(env: &mut ShellEnv) -> ShellResult {
+    let mut output = ShellResult::success("");
+    for job in env.jobs.iter_mut().filter(|job| job.status.is_none()) {
+        let Some(mut future) = JOB_FUTURES.with(|jobs| jobs.borrow_mut().remove(&job.pid)) else {
+            continue;
+        };
+        match futures_lite::future::poll_once(&mut future).await {
+            Some(result) => {
+                job.status = Some(result.code);
+                output.stdout.push_str(&result.stdout);
+                output.stderr.push_str(&result.stderr);
+            }
+            None => {
+                JOB_FUTURES.with(|jobs| jobs.borrow_mut().insert(job.pid, future));
+            }
         }
     }
+    output
+}
 
-    if all_found {
-        ShellResult::success(output)
-    } else {
-        ShellResult {
-            stdout: String::new(),
-            stderr: output,
-            code: 1,
+/// Give background jobs a turn, as the shell does between top-level commands
+/// and before each interactive prompt. Returns their output; an interactive
+/// shell also reports the jobs that finished, like bash, and forgets them.
+pub async fn poll_background_jobs(env: &mut ShellEnv) -> ShellResult {
+    let mut result = poll_jobs(env).await;
+    if env.is_interactive {
+        let count = env.jobs.len();
+        for (index, job) in env.jobs.iter().enumerate() {
+            if job.status.is_some() {
+                result.stderr.push_str(&job_line(job, index, count, false));
+            }
         }
+        env.jobs.retain(|job| job.status.is_none());
     }
+    result
 }
 
-/// Handle shopt builtin for bash-style shell options
-fn handle_shopt_builtin(args: &[String], env: &mut ShellEnv) -> ShellResult {
-    // Parse flags: -s (set), -u (unset), -p (print), -q (query)
-    let mut set_mode = false;
-    let mut unset_mode = false;
-    let mut print_mode = false;
-    let mut query_mode = false;
-    let mut options_to_process: Vec<&str> = Vec::new();
+/// A job as listed by `jobs`: `[n]+ Running  command &`
+fn job_line(job: &Job, index: usize, count: usize, long: bool) -> String {
+    let marker = if index + 1 == count {
+        '+'
+    } else if index + 2 == count {
+        '-'
+    } else {
+        ' '
+    };
+    let state = match job.status {
+        None => "Running".to_string(),
+        Some(0) => "Done".to_string(),
+        Some(code) => format!("Exit {}", code),
+    };
+    let pid = if long {
+        format!("{} ", job.pid)
+    } else {
+        " ".to_string()
+    };
+    let command = if job.status.is_none() {
+        format!("{} &", job.command)
+    } else {
+        job.command.clone()
+    };
+    format!("[{}]{} {}{:<24}{}\n", job.id, marker, pid, state, command)
+}
 
-    for arg in args {
-        if arg == "-s" {
-            set_mode = true;
-        } else if arg == "-u" {
-            unset_mode = true;
-        } else if arg == "-p" {
-            print_mode = true;
-        } else if arg == "-q" {
-            query_mode = true;
-        } else if !arg.starts_with('-') {
-            options_to_process.push(arg);
-        }
+/// Run the given jobs to completion and forget them. The result holds their
+/// output and the exit code of the last one.
+async fn wait_for_jobs(env: &mut ShellEnv, pids: &[u64]) -> ShellResult {
+    let mut output = ShellResult::success("");
+    for pid in pids {
+        let Some(index) = env.jobs.iter().position(|job| job.pid == *pid) else {
+            continue;
+        };
+        let job = env.jobs.remove(index);
+        output.code = match job.status {
+            Some(code) => code,
+            None => match JOB_FUTURES.with(|jobs| jobs.borrow_mut().remove(pid)) {
+                Some(future) => {
+                    let result = future.await;
+                    output.stdout.push_str(&result.stdout);
+                    output.stderr.push_str(&result.stderr);
+                    result.code
+                }
+                // Started by a shell that has since gone away
+                None => 127,
+            },
+        };
     }
+    output
+}
 
-    // If no options specified, list all options
-    if options_to_process.is_empty() && !set_mode && !unset_mode {
-        let output = format_shopt_options(&env.options, print_mode);
-        return ShellResult::success(output);
+/// A non-interactive top-level run finishes its background jobs before
+/// returning, so their work isn't lost with the environment.
+async fn finish_background_jobs(env: &mut ShellEnv, result: &mut ShellResult) {
+    if env.subshell_depth > 0 || env.is_interactive || env.jobs.is_empty() {
+        return;
+    }
+    let pids: Vec<u64> = env.jobs.iter().map(|job| job.pid).collect();
+    let jobs_output = wait_for_jobs(env, &pids).await;
+    result.stdout.push_str(&jobs_output.stdout);
+    result.stderr.push_str(&jobs_output.stderr);
+}
+
+/// Handle retry builtin: `retry [-n RETRIES] [-d DELAY] [--backoff] -- COMMAND [ARGS]...`
+///
+/// Each attempt runs the command through the shell in a subshell, so
+/// functions, builtins and lazy modules work as they do at the prompt.
+async fn handle_retry_builtin(args: &[String], env: &ShellEnv) -> ShellResult {
+    let mut retries: usize = 3;
+    let mut delay: f64 = 1.0;
+    let mut backoff = false;
+    let mut i = 0;
+
+    while i < args.len() {
+        match args[i].as_str() {
+            "--" => {
+                i += 1;
+                break;
+            }
+            "-n" | "-d" if i + 1 < args.len() => {
+                let value = &args[i + 1];
+                let parsed = if args[i] == "-n" {
+                    value.parse().map(|n| retries = n).is_ok()
+                } else {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|d| *d >= 0.0)
+                        .map(|d| delay = d)
+                        .is_some()
+                };
+                if !parsed {
+                    return ShellResult::error(
+                        format!("retry: invalid value for {}: {}", args[i], value),
+                        1,
+                    );
+                }
+                i += 2;
+            }
+            "--backoff" => {
+                backoff = true;
+                i += 1;
+            }
+            _ => break,
+        }
+    }
+
+    if i >= args.len() {
+        return ShellResult::error("retry: missing command", 1);
+    }
+    let cmd_line = args[i..]
+        .iter()
+        .map(|word| xtrace_quote(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let (code, log) = super::commands::run_with_retries(retries, delay, backoff, || {
+        run_subshell_command(&cmd_line, env)
+    })
+    .await;
+    let mut result = ShellResult::success("");
+    for (out, err) in log {
+        result.stdout.push_str(&String::from_utf8_lossy(&out));
+        result.stderr.push_str(&String::from_utf8_lossy(&err));
+    }
+    result.code = code;
+    result
+}
+
+/// Whether a registry command line is run by an executor builtin instead,
+/// because it runs other commands (`dotenv run`, `find -exec`)
+fn runs_through_executor(name: &str, args: &[String]) -> bool {
+    match name {
+        "dotenv" => args.first().is_some_and(|arg| arg == "run"),
+        "find" => super::commands::find_runs_commands(args),
+        _ => false,
+    }
+}
+
+/// Handle `dotenv run [-f FILE] -- CMD [ARGS]...`: run the command in a
+/// subshell with the file's variables exported
+async fn handle_dotenv_run_builtin(
+    args: &[String],
+    env: &ShellEnv,
+    stdin: Option<StdinData>,
+) -> ShellResult {
+    let run = match super::commands::parse_dotenv_run(args, &env.cwd.to_string_lossy()) {
+        Ok(run) => run,
+        Err(e) => return ShellResult::error(e, 1),
+    };
+    let mut child_env = env.subshell();
+    for (key, value) in &run.vars {
+        if let Err(e) = child_env.export_var(key, Some(value)) {
+            return ShellResult::error(format!("dotenv: {}\n", e), 1);
+        }
+    }
+    let cmd_line = run
+        .command
+        .iter()
+        .map(|word| xtrace_quote(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match super::parser::parse_command(&cmd_line) {
+        Ok(parsed) => Box::pin(execute_sequence(&parsed, &mut child_env, stdin)).await,
+        Err(e) => ShellResult::error(format!("dotenv: {}\n", e), 1),
+    }
+}
+
+/// Handle `find ... -exec`: walk with the registry's `find`, then run each
+/// `-exec` command in a subshell
+async fn handle_find_exec_builtin(args: &[String], env: &ShellEnv) -> ShellResult {
+    let (_, remaining) = super::commands::parse_common(args);
+    let run = super::commands::find_run(&remaining, &env.cwd.to_string_lossy());
+    let mut result = ShellResult {
+        stdout: run.stdout,
+        stderr: run.stderr,
+        code: run.code,
+    };
+    for exec in run.execs {
+        let cmd_line = exec
+            .argv
+            .iter()
+            .map(|word| xtrace_quote(word))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let (code, out, err) = run_subshell_command(&cmd_line, env).await;
+        result.stdout.push_str(&String::from_utf8_lossy(&out));
+        result.stderr.push_str(&String::from_utf8_lossy(&err));
+        // A failing command only makes a per-path -exec false
+        if exec.batch && code != 0 {
+            result.code = 1;
+        }
+    }
+    result
+}
+
+/// Run `cmd_line` in a subshell of `env`, collecting its output
+async fn run_subshell_command(cmd_line: &str, env: &ShellEnv) -> (i32, Vec<u8>, Vec<u8>) {
+    let mut subshell = env.subshell();
+    let (stdout_reader, stdout_writer) = piper::pipe(PIPE_CAPACITY);
+    let (stderr_reader, stderr_writer) = piper::pipe(PIPE_CAPACITY);
+    let (code, (stdout, stderr)) = join(
+        Box::pin(super::pipeline::run_pipeline_streaming(
+            cmd_line,
+            &mut subshell,
+            stdout_writer,
+            stderr_writer,
+        )),
+        join(drain_reader(stdout_reader), drain_reader(stderr_reader)),
+    )
+    .await;
+    (code, stdout, stderr)
+}
+
+/// Handle jobs builtin: `jobs [-l|-p]`
+async fn handle_jobs_builtin(args: &[String], env: &mut ShellEnv) -> ShellResult {
+    let mut long = false;
+    let mut pids_only = false;
+    for arg in args {
+        match arg.as_str() {
+            "-l" => long = true,
+            "-p" => pids_only = true,
+            _ => return ShellResult::error(format!("jobs: {}: invalid option", arg), 2),
+        }
+    }
+
+    let mut result = poll_jobs(env).await;
+    let count = env.jobs.len();
+    for (index, job) in env.jobs.iter().enumerate() {
+        if pids_only {
+            result.stdout.push_str(&format!("{}\n", job.pid));
+        } else {
+            result.stdout.push_str(&job_line(job, index, count, long));
+        }
+    }
+    // Finished jobs are forgotten once reported
+    env.jobs.retain(|job| job.status.is_none());
+    result
+}
+
+/// Handle wait builtin: `wait [%job|pid]...`
+async fn handle_wait_builtin(args: &[String], env: &mut ShellEnv) -> ShellResult {
+    if args.is_empty() {
+        let pids: Vec<u64> = env.jobs.iter().map(|job| job.pid).collect();
+        let mut result = wait_for_jobs(env, &pids).await;
+        result.code = 0;
+        return result;
+    }
+
+    let mut result = ShellResult::success("");
+    for spec in args {
+        let Some(pid) = env.find_job(spec).map(|job| job.pid) else {
+            result.stderr.push_str(&if spec.starts_with('%') {
+                format!("wait: {}: no such job\n", spec)
+            } else {
+                format!("wait: pid {} is not a child of this shell\n", spec)
+            });
+            result.code = 127;
+            continue;
+        };
+        let waited = wait_for_jobs(env, &[pid]).await;
+        result.stdout.push_str(&waited.stdout);
+        result.stderr.push_str(&waited.stderr);
+        result.code = waited.code;
+    }
+    result
+}
+
+/// Handle read builtin: `read [-r] [-p PROMPT] [-n N] [NAME...]`
+///
+/// Reads one line of `input` (or N characters with `-n`), splits it on
+/// IFS into the NAMEs, the last taking the remainder, or stores it whole
+/// in REPLY. The bytes used are recorded in `env.stdin_consumed`.
+fn handle_read_builtin(args: &[String], input: &[u8], env: &mut ShellEnv) -> ShellResult {
+    let mut raw = false;
+    let mut prompt = None;
+    let mut nchars = None;
+    let mut names = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let arg = &args[i];
+        i += 1;
+        if !names.is_empty() || !arg.starts_with('-') || arg == "-" {
+            names.push(arg.clone());
+            continue;
+        }
+        if arg == "--" {
+            names.extend(args[i..].iter().cloned());
+            break;
+        }
+        for (pos, flag) in arg.char_indices().skip(1) {
+            match flag {
+                'r' => raw = true,
+                'p' | 'n' => {
+                    // The value is the rest of this argument or the next one
+                    let value = if pos + 1 < arg.len() {
+                        arg[pos + 1..].to_string()
+                    } else if i < args.len() {
+                        i += 1;
+                        args[i - 1].clone()
+                    } else {
+                        return ShellResult::error(
+                            format!("read: -{}: option requires an argument", flag),
+                            2,
+                        );
+                    };
+                    if flag == 'p' {
+                        prompt = Some(value);
+                    } else {
+                        match value.parse::<usize>() {
+                            Ok(n) => nchars = Some(n),
+                            Err(_) => {
+                                return ShellResult::error(
+                                    format!("read: {}: invalid number", value),
+                                    1,
+                                )
+                            }
+                        }
+                    }
+                    break;
+                }
+                _ => return ShellResult::error(format!("read: -{}: invalid option", flag), 2),
+            }
+        }
+    }
+
+    let (line, consumed, complete) = read_input_line(input, raw, nchars);
+    env.stdin_consumed = consumed;
+
+    let values = if names.is_empty() {
+        names.push("REPLY".to_string());
+        vec![line.iter().map(|(c, _)| *c).collect()]
+    } else {
+        let ifs = env
+            .get_var("IFS")
+            .cloned()
+            .unwrap_or_else(|| " \t\n".to_string());
+        split_read_fields(&line, &ifs, names.len())
+    };
+
+    let mut result = ShellResult::success("");
+    if let Some(prompt) = prompt {
+        result.stderr.push_str(&prompt);
+    }
+    for (name, value) in names.iter().zip(values) {
+        if let Err(e) = env.set_var(name, &value) {
+            result.stderr.push_str(&format!("read: {}\n", e));
+            result.code = 1;
+            return result;
+        }
+    }
+    // Running out of input before a full line (or N chars) is a failure
+    if !complete {
+        result.code = 1;
+    }
+    result
+}
+
+/// Take one line from the front of `input`, or at most `nchars` characters
+/// of it. Unless `raw`, a backslash quotes the next character and a
+/// backslash-newline continues the line. Returns the characters, each
+/// marked if it was quoted, the bytes consumed and whether a newline (or
+/// all `nchars`) was read before the input ran out.
+fn read_input_line(
+    input: &[u8],
+    raw: bool,
+    nchars: Option<usize>,
+) -> (Vec<(char, bool)>, usize, bool) {
+    let mut line = Vec::new();
+    // Decode one physical line at a time so `read` costs the length of the
+    // line, not of all remaining input. A backslash-newline continuation
+    // moves on to the next one.
+    let mut start = 0;
+    while start < input.len() {
+        let end = input[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(input.len(), |i| start + i + 1);
+        let text = String::from_utf8_lossy(&input[start..end]);
+        let mut chars = text.char_indices().map(|(pos, c)| (start + pos, c));
+        while let Some((pos, c)) = chars.next() {
+            if nchars.is_some_and(|n| line.len() >= n) {
+                return (line, pos, true);
+            }
+            match c {
+                '\n' => return (line, pos + 1, true),
+                '\\' if !raw => match chars.next() {
+                    Some((_, '\n')) => {}
+                    Some((_, escaped)) => line.push((escaped, true)),
+                    None => break,
+                },
+                _ => line.push((c, false)),
+            }
+        }
+        start = end;
+    }
+    let complete = nchars.is_some_and(|n| line.len() >= n);
+    (line, input.len(), complete)
+}
+
+/// Split a line read by `read` into `count` fields on the IFS characters.
+/// IFS whitespace is trimmed and runs of it separate once; every other
+/// IFS character separates exactly one field. Quoted characters never
+/// separate, and the last field keeps the rest of the line.
+fn split_read_fields(line: &[(char, bool)], ifs: &str, count: usize) -> Vec<String> {
+    let is_ifs = |&(c, quoted): &(char, bool)| !quoted && ifs.contains(c);
+    let is_ifs_space = |item: &(char, bool)| is_ifs(item) && item.0.is_whitespace();
+
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < line.len() && is_ifs_space(&line[pos]) {
+        pos += 1;
+    }
+    while fields.len() + 1 < count && pos < line.len() {
+        let start = pos;
+        while pos < line.len() && !is_ifs(&line[pos]) {
+            pos += 1;
+        }
+        fields.push(line[start..pos].iter().map(|(c, _)| *c).collect());
+        // One separator: IFS whitespace around at most one other IFS char
+        while pos < line.len() && is_ifs_space(&line[pos]) {
+            pos += 1;
+        }
+        if pos < line.len() && is_ifs(&line[pos]) && !is_ifs_space(&line[pos]) {
+            pos += 1;
+            while pos < line.len() && is_ifs_space(&line[pos]) {
+                pos += 1;
+            }
+        }
+    }
+
+    let mut end = line.len();
+    while end > pos && is_ifs_space(&line[end - 1]) {
+        end -= 1;
+    }
+    fields.push(line[pos..end].iter().map(|(c, _)| *c).collect());
+    fields.resize(count, String::new());
+    fields
+}
+
+/// Conditions `trap` accepts, with their signal numbers
+const TRAP_SIGNALS: &[(u8, &str)] = &[
+    (0, "EXIT"),
+    (1, "HUP"),
+    (2, "INT"),
+    (3, "QUIT"),
+    (15, "TERM"),
+];
+
+/// Normalise a trap condition (`INT`, `SIGINT`, `int`, `2`) to its name
+fn trap_signal_name(spec: &str) -> Option<&'static str> {
+    let upper = spec.to_ascii_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    TRAP_SIGNALS
+        .iter()
+        .find(|(num, sig)| *sig == name || num.to_string() == name)
+        .map(|(_, sig)| *sig)
+}
+
+/// Handle trap builtin: `trap [-lp] [[ACTION] SIGNAL...]`
+fn handle_trap_builtin(args: &[String], env: &mut ShellEnv) -> ShellResult {
+    if args.first().is_some_and(|arg| arg == "-l") {
+        let list: String = TRAP_SIGNALS
+            .iter()
+            .filter(|(num, _)| *num > 0)
+            .map(|(num, name)| format!("{:2}) SIG{}\n", num, name))
+            .collect();
+        return ShellResult::success(list);
+    }
+
+    let args = match args.first().map(String::as_str) {
+        Some("--") => &args[1..],
+        _ => args,
+    };
+
+    // `trap` and `trap -p [SIGNAL...]` print the registered handlers
+    if args.is_empty() || args[0] == "-p" {
+        let mut result = ShellResult::success("");
+        let wanted: Vec<&str> = if args.len() > 1 {
+            let mut names = Vec::new();
+            for spec in &args[1..] {
+                match trap_signal_name(spec) {
+                    Some(name) => names.push(name),
+                    None => {
+                        result
+                            .stderr
+                            .push_str(&format!("trap: {}: invalid signal specification\n", spec));
+                        result.code = 1;
+                    }
+                }
+            }
+            names
+        } else {
+            TRAP_SIGNALS.iter().map(|(_, name)| *name).collect()
+        };
+        for name in wanted {
+            if let Some(action) = env.get_trap(name) {
+                result.stdout.push_str(&format!(
+                    "trap -- '{}' {}\n",
+                    action.replace('\'', "'\\''"),
+                    name
+                ));
+            }
+        }
+        return result;
+    }
+
+    // A lone SIGNAL or an ACTION of `-` resets; an empty ACTION ignores
+    let (action, signals) = if args.len() == 1 {
+        (None, args)
+    } else if args[0] == "-" {
+        (None, &args[1..])
+    } else {
+        (Some(args[0].as_str()), &args[1..])
+    };
+
+    let mut result = ShellResult::success("");
+    for spec in signals {
+        match trap_signal_name(spec) {
+            Some(name) => env.set_trap(name, action),
+            None => {
+                result
+                    .stderr
+                    .push_str(&format!("trap: {}: invalid signal specification\n", spec));
+                result.code = 1;
+            }
+        }
+    }
+    result
+}
+
+/// Run the handler `trap` registered for `signal`, if any. An EXIT handler
+/// runs at most once. `$?` is left as it was before the handler ran.
+pub async fn run_trap(signal: &str, env: &mut ShellEnv) -> Option<ShellResult> {
+    let action = if signal == "EXIT" {
+        env.traps.remove(signal)?
+    } else {
+        env.get_trap(signal)?.clone()
+    };
+    // An empty action means the signal is ignored
+    if action.is_empty() {
+        return None;
+    }
+
+    let saved_code = env.last_exit_code;
+    // The trap runs even when it fires because the shell is exiting
+    let exiting = std::mem::take(&mut env.exiting);
+    let result = match super::parser::parse_command(&action) {
+        Ok(parsed) => Box::pin(execute_sequence(&parsed, env, None)).await,
+        Err(e) => ShellResult::error(format!("trap: {}", e), 2),
+    };
+    env.exiting |= exiting;
+    env.last_exit_code = saved_code;
+    Some(result)
+}
+
+/// A non-interactive run fires its EXIT trap once everything else is done.
+/// The trap's output is added, but the run keeps its own exit status.
+async fn run_exit_trap(env: &mut ShellEnv, result: &mut ShellResult) {
+    if env.is_interactive {
+        return;
+    }
+    if let Some(trap) = run_trap("EXIT", env).await {
+        result.stdout.push_str(&trap.stdout);
+        result.stderr.push_str(&trap.stderr);
+    }
+}
+
+/// List of shell builtins for the `type` command
+const SHELL_BUILTINS: &[&str] = &[
+    ":", "true", "false", "export", "unset", "set", "shopt", "readonly", "local", "return",
+    "break", "continue", "cd", "pushd", "popd", "dirs", "pwd", "eval", "alias", "unalias",
+    "getopts", "source", ".", "shift", "declare", "typeset", "type", "which", "echo", "printf",
+    "read", "test", "[", "exit", "trap", "wait", "jobs", "bg", "fg", "kill", "umask", "hash",
+    "command", "builtin", "exec", "let", "time", "retry",
+];
+
+/// Directory that `type` and `which` report registry commands under,
+/// matching the default `PATH`
+const COMMAND_DIR: &str = "/usr/bin";
+
+/// Check if a name is a shell keyword
+fn is_shell_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "if" | "then"
+            | "else"
+            | "elif"
+            | "fi"
+            | "for"
+            | "while"
+            | "until"
+            | "do"
+            | "done"
+            | "case"
+            | "esac"
+            | "in"
+            | "function"
+            | "{"
+            | "}"
+            | "!"
+            | "[["
+            | "]]"
+    )
+}
+
+/// Path reported for a command in the `ShellCommands` registry or one served
+/// by a lazy module (tsx, sqlite3, ...)
+fn command_path(name: &str) -> Option<String> {
+    let found = ShellCommands::get_command(name).is_some() || is_lazy_command(name);
+    found.then(|| format!("{}/{}", COMMAND_DIR, name))
+}
+
+/// Whether a lazy module provides `name`. Native builds (unit tests) have
+/// no module loader, so this is always false there.
+fn is_lazy_command(name: &str) -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        crate::bindings::mcp::module_loader::loader::get_lazy_module(name).is_some()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = name;
+        false
+    }
+}
+
+/// Handle type builtin
+///
+/// Resolution order follows bash: alias, keyword, function, builtin, then
+/// the command registry and lazy modules.
+fn handle_type_builtin(args: &[String], env: &ShellEnv) -> ShellResult {
+    if args.is_empty() {
+        return ShellResult::error("type: usage: type name [name ...]", 1);
+    }
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+
+    for name in args {
+        if let Some(value) = env.aliases.get(name) {
+            stdout.push_str(&format!("{} is an alias for {}\n", name, value));
+        } else if is_shell_keyword(name) {
+            stdout.push_str(&format!("{} is a shell keyword\n", name));
+        } else if env.functions.contains_key(name) {
+            stdout.push_str(&format!("{} is a function\n", name));
+        } else if SHELL_BUILTINS.contains(&name.as_str()) {
+            stdout.push_str(&format!("{} is a shell builtin\n", name));
+        } else if let Some(path) = command_path(name) {
+            stdout.push_str(&format!("{} is {}\n", name, path));
+        } else {
+            stderr.push_str(&format!("{}: not found\n", name));
+        }
+    }
+
+    ShellResult {
+        code: i32::from(!stderr.is_empty()),
+        stdout,
+        stderr,
+    }
+}
+
+/// Handle which builtin - like `type`, but only reports command paths
+fn handle_which_builtin(args: &[String]) -> ShellResult {
+    if args.is_empty() {
+        return ShellResult::error("which: usage: which name [name ...]", 1);
+    }
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+
+    for name in args {
+        match command_path(name) {
+            Some(path) => {
+                stdout.push_str(&path);
+                stdout.push('\n');
+            }
+            None => stderr.push_str(&format!("{}: not found\n", name)),
+        }
+    }
+
+    ShellResult {
+        code: i32::from(!stderr.is_empty()),
+        stdout,
+        stderr,
+    }
+}
+
+/// Handle shopt builtin for bash-style shell options
+fn handle_shopt_builtin(args: &[String], env: &mut ShellEnv) -> ShellResult {
+    // Parse flags: -s (set), -u (unset), -p (print), -q (query)
+    let mut set_mode = false;
+    let mut unset_mode = false;
+    let mut print_mode = false;
+    let mut query_mode = false;
+    let mut options_to_process: Vec<&str> = Vec::new();
+
+    for arg in args {
+        if arg == "-s" {
+            set_mode = true;
+        } else if arg == "-u" {
+            unset_mode = true;
+        } else if arg == "-p" {
+            print_mode = true;
+        } else if arg == "-q" {
+            query_mode = true;
+        } else if !arg.starts_with('-') {
+            options_to_process.push(arg);
+        }
+    }
+
+    // If no options specified, list all options
+    if options_to_process.is_empty() && !set_mode && !unset_mode {
+        let output = format_shopt_options(&env.options, print_mode);
+        return ShellResult::success(output);
     }
 
     // Process each option
@@ -1518,6 +2716,7 @@ fn format_shopt_options(opts: &super::env::ShellOptions, print_format: bool) ->
     let options = [
         ("extglob", opts.extglob),
         ("nullglob", opts.nullglob),
+        ("failglob", opts.failglob),
         ("dotglob", opts.dotglob),
         ("nocasematch", opts.nocasematch),
         ("nocaseglob", opts.nocaseglob),
@@ -1555,6 +2754,7 @@ fn is_shopt_set(opts: &super::env::ShellOptions, name: &str) -> bool {
     match name {
         "extglob" => opts.extglob,
         "nullglob" => opts.nullglob,
+        "failglob" => opts.failglob,
         "dotglob" => opts.dotglob,
         "nocasematch" => opts.nocasematch,
         "nocaseglob" => opts.nocaseglob,
@@ -1564,19 +2764,12 @@ fn is_shopt_set(opts: &super::env::ShellOptions, name: &str) -> bool {
     }
 }
 
-/// Define a function
-fn define_function(name: &str, body: &ParsedCommand, env: &mut ShellEnv) {
-    // Convert body to shell string for storage
-    let body_str = to_shell_string(body);
-    env.functions.insert(name.to_string(), body_str);
-}
-
 /// Call a function with arguments and stdin
 async fn call_function(
-    body: &str,
+    body: &ParsedCommand,
     args: &[String],
     env: &mut ShellEnv,
-    stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
     // Save state
     let old_params = std::mem::replace(&mut env.positional_params, args.to_vec());
@@ -1584,14 +2777,10 @@ async fn call_function(
     let old_local_vars = std::mem::take(&mut env.local_vars);
     env.in_function = true;
 
-    // Parse and execute function body with stdin
-    let result = match super::parser::parse_command(body) {
-        Ok(parsed) if !parsed.is_empty() => Box::pin(execute_sequence(&parsed, env, stdin)).await,
-        Ok(_) => ShellResult::success(""),
-        Err(e) => ShellResult::error(format!("function parse error: {}", e), 1),
-    };
+    let result = Box::pin(execute_command(body, env, stdin)).await;
 
     // Restore state
+    env.returning = false;
     env.positional_params = old_params;
     env.in_function = old_in_function;
     env.local_vars = old_local_vars;
@@ -1605,7 +2794,7 @@ async fn execute_for(
     words: &[String],
     body: &[ParsedCommand],
     env: &mut ShellEnv,
-    _stdin: Option<Vec<u8>>,
+    _stdin: Option<StdinData>,
 ) -> ShellResult {
     let mut combined_stdout = String::new();
     let mut combined_stderr = String::new();
@@ -1614,6 +2803,15 @@ async fn execute_for(
     // Expand words (variable expansion + command substitution + glob)
     let mut expanded_words = Vec::new();
     for word in words {
+        // "$@" keeps each positional parameter as its own word
+        if expand::contains_all_params(word) {
+            let fields = expand::expand_word_fields(word, env).unwrap_or_default();
+            for field in fields {
+                let field = super::pipeline::execute_command_substitutions(&field, env).await;
+                expanded_words.push(field);
+            }
+            continue;
+        }
         let expanded = expand::expand_string(word, env, false).unwrap_or_else(|_| word.clone());
         // Resolve any command substitution markers (e.g. $(seq 1 3))
         let expanded = super::pipeline::execute_command_substitutions(&expanded, env).await;
@@ -1635,6 +2833,16 @@ async fn execute_for(
             combined_stderr.push_str(&result.stderr);
             last_code = result.code;
 
+            // A return or exit leaves the loop along with the function
+            if env.returning || env.exiting {
+                env.loop_depth -= 1;
+                return ShellResult {
+                    stdout: combined_stdout,
+                    stderr: combined_stderr,
+                    code: last_code,
+                };
+            }
+
             // Check for break
             if env.break_level > 0 {
                 env.break_level -= 1;
@@ -1681,16 +2889,198 @@ async fn execute_for(
     }
 }
 
+/// Evaluate an arithmetic expression in the shell environment, keeping any
+/// assignments it makes (`i=0`, `i++`, `n += 2`)
+fn eval_arithmetic(expr: &str, env: &mut ShellEnv) -> Result<i64, String> {
+    let expanded = expand::expand_string(expr, env, false)?;
+    super::arithmetic::evaluate(&expanded, env).map_err(|e| format!("{}: {}", expr.trim(), e))
+}
+
+/// Execute an arithmetic for loop: `for ((init; condition; update))`.
+/// An empty condition is always true.
+async fn execute_arith_for(
+    init: &Option<String>,
+    condition: &Option<String>,
+    update: &Option<String>,
+    body: &[ParsedCommand],
+    env: &mut ShellEnv,
+) -> ShellResult {
+    let mut combined_stdout = String::new();
+    let mut combined_stderr = String::new();
+    let mut last_code = 0;
+
+    if let Some(init) = init {
+        if let Err(e) = eval_arithmetic(init, env) {
+            return ShellResult::error(format!("for: {}", e), 1);
+        }
+    }
+
+    // Enter loop scope
+    env.loop_depth += 1;
+
+    let mut first = true;
+    'outer: loop {
+        // The update runs before every iteration but the first, so that
+        // `continue` still advances the loop
+        if !first {
+            if let Some(update) = update {
+                if let Err(e) = eval_arithmetic(update, env) {
+                    combined_stderr.push_str(&format!("for: {}\n", e));
+                    last_code = 1;
+                    break;
+                }
+            }
+        }
+        first = false;
+
+        if let Some(condition) = condition.as_deref().filter(|c| !c.trim().is_empty()) {
+            match eval_arithmetic(condition, env) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    combined_stderr.push_str(&format!("for: {}\n", e));
+                    last_code = 1;
+                    break;
+                }
+            }
+        }
+
+        for cmd in body {
+            let result = Box::pin(execute_command(cmd, env, None)).await;
+            combined_stdout.push_str(&result.stdout);
+            combined_stderr.push_str(&result.stderr);
+            last_code = result.code;
+
+            // A return or exit leaves the loop along with the function
+            if env.returning || env.exiting {
+                break 'outer;
+            }
+
+            // Check for break
+            if env.break_level > 0 {
+                env.break_level -= 1;
+                if env.break_level == 0 {
+                    // Break this loop
+                    break 'outer;
+                }
+                // Propagate break to outer loop
+                env.loop_depth -= 1;
+                return ShellResult {
+                    stdout: combined_stdout,
+                    stderr: combined_stderr,
+                    code: last_code,
+                };
+            }
+
+            // Check for continue
+            if env.continue_level > 0 {
+                env.continue_level -= 1;
+                if env.continue_level == 0 {
+                    // Continue this loop
+                    continue 'outer;
+                }
+                // Propagate continue to outer loop
+                env.loop_depth -= 1;
+                return ShellResult {
+                    stdout: combined_stdout,
+                    stderr: combined_stderr,
+                    code: last_code,
+                };
+            }
+        }
+    }
+
+    // Exit loop scope
+    env.loop_depth -= 1;
+
+    ShellResult {
+        stdout: combined_stdout,
+        stderr: combined_stderr,
+        code: last_code,
+    }
+}
+
+/// Buffered stdin for a command: a shared buffer and the offset where this
+/// command's input starts. Handing what is left of a list's stdin to each
+/// of its commands is then a reference-count bump rather than a copy.
+#[derive(Clone)]
+pub struct StdinData {
+    buf: Rc<[u8]>,
+    start: usize,
+}
+
+impl StdinData {
+    /// The input after its first `n` bytes
+    fn skip(&self, n: usize) -> Self {
+        Self {
+            buf: Rc::clone(&self.buf),
+            start: (self.start + n).min(self.buf.len()),
+        }
+    }
+}
+
+impl From<Vec<u8>> for StdinData {
+    fn from(data: Vec<u8>) -> Self {
+        Self {
+            buf: data.into(),
+            start: 0,
+        }
+    }
+}
+
+impl std::ops::Deref for StdinData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+}
+
+/// Stdin shared by the commands of a list, loop or conditional. Only
+/// `read` consumes from it, recording how much in `env.stdin_consumed`;
+/// each following command sees what is left.
+struct SharedStdin {
+    data: Option<StdinData>,
+    offset: usize,
+}
+
+impl SharedStdin {
+    fn new(data: Option<StdinData>) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// Stdin for the next command
+    fn next(&self, env: &mut ShellEnv) -> Option<StdinData> {
+        env.stdin_consumed = 0;
+        self.data.as_ref().map(|data| data.skip(self.offset))
+    }
+
+    /// Skip past whatever the command just run consumed
+    fn advance(&mut self, env: &mut ShellEnv) {
+        if let Some(data) = &self.data {
+            self.offset = (self.offset + env.stdin_consumed).min(data.len());
+        }
+        env.stdin_consumed = 0;
+    }
+
+    /// Report everything consumed here to the enclosing list
+    fn finish(self, env: &mut ShellEnv) {
+        env.stdin_consumed = self.offset;
+    }
+}
+
 /// Execute a while loop
 async fn execute_while(
     condition: &[ParsedCommand],
     body: &[ParsedCommand],
     env: &mut ShellEnv,
-    _stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
     let mut combined_stdout = String::new();
     let mut combined_stderr = String::new();
     let mut last_code = 0;
+    // `while read line` consumes the loop's stdin a line at a time
+    let mut input = SharedStdin::new(stdin);
 
     // Enter loop scope
     env.loop_depth += 1;
@@ -1699,20 +3089,33 @@ async fn execute_while(
         // Evaluate condition
         let mut cond_result = ShellResult::success("");
         for cmd in condition {
-            cond_result = Box::pin(execute_command(cmd, env, None)).await;
+            cond_result = Box::pin(execute_condition(cmd, env, input.next(env))).await;
+            input.advance(env);
         }
 
-        if cond_result.code != 0 {
+        if cond_result.code != 0 || env.exiting {
             break;
         }
 
         // Execute body
         for cmd in body {
-            let result = Box::pin(execute_command(cmd, env, None)).await;
+            let result = Box::pin(execute_command(cmd, env, input.next(env))).await;
+            input.advance(env);
             combined_stdout.push_str(&result.stdout);
             combined_stderr.push_str(&result.stderr);
             last_code = result.code;
 
+            // A return or exit leaves the loop along with the function
+            if env.returning || env.exiting {
+                input.finish(env);
+                env.loop_depth -= 1;
+                return ShellResult {
+                    stdout: combined_stdout,
+                    stderr: combined_stderr,
+                    code: last_code,
+                };
+            }
+
             // Check for break
             if env.break_level > 0 {
                 env.break_level -= 1;
@@ -1721,6 +3124,7 @@ async fn execute_while(
                     break 'outer;
                 } else {
                     // Propagate break to outer loop
+                    input.finish(env);
                     env.loop_depth -= 1;
                     return ShellResult {
                         stdout: combined_stdout,
@@ -1738,6 +3142,7 @@ async fn execute_while(
                     continue 'outer;
                 } else {
                     // Propagate continue to outer loop
+                    input.finish(env);
                     env.loop_depth -= 1;
                     return ShellResult {
                         stdout: combined_stdout,
@@ -1750,6 +3155,7 @@ async fn execute_while(
     }
 
     // Exit loop scope
+    input.finish(env);
     env.loop_depth -= 1;
 
     ShellResult {
@@ -1764,35 +3170,36 @@ async fn execute_if(
     conditionals: &[(Vec<ParsedCommand>, Vec<ParsedCommand>)],
     else_branch: &Option<Vec<ParsedCommand>>,
     env: &mut ShellEnv,
-    _stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
+    let mut input = SharedStdin::new(stdin);
+    let mut result = ShellResult::success("");
+    let mut branch = else_branch.as_ref();
     for (condition, body) in conditionals {
         // Evaluate condition
         let mut cond_result = ShellResult::success("");
         for cmd in condition {
-            cond_result = Box::pin(execute_command(cmd, env, None)).await;
+            cond_result = Box::pin(execute_condition(cmd, env, input.next(env))).await;
+            input.advance(env);
+        }
+        if env.exiting {
+            input.finish(env);
+            return cond_result;
         }
 
         if cond_result.code == 0 {
-            // Condition true - execute body
-            let mut result = ShellResult::success("");
-            for cmd in body {
-                result = Box::pin(execute_command(cmd, env, None)).await;
-            }
-            return result;
+            branch = Some(body);
+            break;
         }
     }
 
-    // No condition matched - try else branch
-    if let Some(else_body) = else_branch {
-        let mut result = ShellResult::success("");
-        for cmd in else_body {
-            result = Box::pin(execute_command(cmd, env, None)).await;
-        }
-        return result;
+    // Run the matching body, or the else branch if no condition held
+    if let Some(body) = branch {
+        result = Box::pin(execute_sequence(body, env, input.next(env))).await;
+        input.advance(env);
     }
-
-    ShellResult::success("")
+    input.finish(env);
+    result
 }
 
 /// Execute a case statement
@@ -1800,7 +3207,7 @@ async fn execute_case(
     word: &str,
     cases: &[(Vec<String>, Vec<ParsedCommand>)],
     env: &mut ShellEnv,
-    _stdin: Option<Vec<u8>>,
+    stdin: Option<StdinData>,
 ) -> ShellResult {
     // Expand the word
     let expanded_word =
@@ -1808,13 +3215,9 @@ async fn execute_case(
 
     for (patterns, body) in cases {
         for pattern in patterns {
-            // Simple pattern matching (supports * and ?)
-            if matches_pattern(&expanded_word, pattern) {
-                let mut result = ShellResult::success("");
-                for cmd in body {
-                    result = Box::pin(execute_command(cmd, env, None)).await;
-                }
-                return result;
+            // Glob pattern matching (*, ? and [...] classes)
+            if expand::glob_match(&expanded_word, pattern) {
+                return Box::pin(execute_sequence(body, env, stdin)).await;
             }
         }
     }
@@ -1822,44 +3225,6 @@ async fn execute_case(
     ShellResult::success("")
 }
 
-/// Simple glob pattern matching
-fn matches_pattern(text: &str, pattern: &str) -> bool {
-    if pattern == "*" {
-        return true;
-    }
-    if pattern == text {
-        return true;
-    }
-
-    // Simple wildcard matching
-    let parts: Vec<&str> = pattern.split('*').collect();
-    if parts.len() == 1 {
-        return text == pattern;
-    }
-
-    let mut pos = 0;
-    for (i, part) in parts.iter().enumerate() {
-        if part.is_empty() {
-            continue;
-        }
-        if let Some(found) = text[pos..].find(part) {
-            if i == 0 && found != 0 {
-                return false; // First part must be at start
-            }
-            pos += found + part.len();
-        } else {
-            return false;
-        }
-    }
-
-    // If pattern doesn't end with *, text must end at pos
-    if !pattern.ends_with('*') && pos != text.len() {
-        return false;
-    }
-
-    true
-}
-
 /// Convert a ParsedCommand back to shell string (for function storage)
 fn to_shell_string(cmd: &ParsedCommand) -> String {
     match cmd {
@@ -1912,6 +3277,21 @@ fn to_shell_string(cmd: &ParsedCommand) -> String {
                 body_strs.join("; ")
             )
         }
+        ParsedCommand::ArithFor {
+            init,
+            condition,
+            update,
+            body,
+        } => {
+            let body_strs: Vec<String> = body.iter().map(to_shell_string).collect();
+            format!(
+                "for (({}; {}; {})); do {}; done",
+                init.as_deref().unwrap_or(""),
+                condition.as_deref().unwrap_or(""),
+                update.as_deref().unwrap_or(""),
+                body_strs.join("; ")
+            )
+        }
         ParsedCommand::If {
             conditionals,
             else_branch,
@@ -1968,8 +3348,9 @@ fn to_shell_string(cmd: &ParsedCommand) -> String {
         ParsedCommand::Background(cmd) => {
             format!("{} &", to_shell_string(cmd))
         }
-        ParsedCommand::Timed(cmd) => {
-            format!("time {}", to_shell_string(cmd))
+        ParsedCommand::Timed { command, posix } => {
+            let flag = if *posix { "-p " } else { "" };
+            format!("time {}{}", flag, to_shell_string(command))
         }
     }
 }
@@ -1982,6 +3363,24 @@ async fn drain_reader(mut reader: piper::Reader) -> Vec<u8> {
     buffer
 }
 
+/// Copy a pipe to `writer` as data arrives. If the writer's reader has gone
+/// away, the pipe is dropped so the stage writing it fails and finishes, as
+/// with SIGPIPE, instead of producing output nobody reads.
+async fn forward_reader(mut reader: piper::Reader, writer: &mut piper::Writer) {
+    use futures_lite::io::AsyncReadExt;
+    let mut chunk = [0u8; 4096];
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if writer.write_all(&chunk[..n]).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 // ==== Directory Builtins ====
 
 /// Handle cd built-in command
@@ -2094,12 +3493,13 @@ fn format_dir_stack(env: &ShellEnv) -> String {
 
 #[cfg(test)]
 mod tests {
+    use super::super::pipeline::run_pipeline;
     use super::*;
 
     #[test]
     fn test_run_shell_echo() {
         let mut env = ShellEnv::new();
-        let result = futures_lite::future::block_on(run_shell("echo hello", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline("echo hello", &mut env));
         assert_eq!(result.code, 0);
         assert_eq!(result.stdout.trim(), "hello");
     }
@@ -2107,29 +3507,92 @@ mod tests {
     #[test]
     fn test_run_shell_pipeline() {
         let mut env = ShellEnv::new();
-        let result = futures_lite::future::block_on(run_shell("echo hello | cat", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline("echo hello | cat", &mut env));
         assert_eq!(result.code, 0);
         assert_eq!(result.stdout.trim(), "hello");
     }
 
+    #[test]
+    fn test_cancelled_run_stops_before_next_command() {
+        let mut env = ShellEnv::new();
+        let flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        env.cancel_flag = Some(flag.clone());
+
+        let result = futures_lite::future::block_on(run_pipeline("echo a | cat", &mut env));
+        assert_eq!(result.stdout.trim(), "a");
+
+        flag.store(true, std::sync::atomic::Ordering::SeqCst);
+        let result = futures_lite::future::block_on(run_pipeline("echo a; echo b", &mut env));
+        assert_eq!(result.code, 130);
+        assert_eq!(result.stdout, "");
+    }
+
+    #[test]
+    fn test_run_shell_streaming_writes_to_pipes() {
+        let mut env = ShellEnv::new();
+        let (stdout_reader, stdout_writer) = piper::pipe(PIPE_CAPACITY);
+        let (stderr_reader, stderr_writer) = piper::pipe(PIPE_CAPACITY);
+        let (code, (stdout, stderr)) = futures_lite::future::block_on(join(
+            run_shell_streaming(
+                "echo one | cat; echo two; no_such_command_xyz",
+                &mut env,
+                stdout_writer,
+                stderr_writer,
+            ),
+            join(drain_reader(stdout_reader), drain_reader(stderr_reader)),
+        ));
+        assert_eq!(code, 127);
+        assert_eq!(String::from_utf8(stdout).unwrap(), "one\ntwo\n");
+        assert!(String::from_utf8(stderr)
+            .unwrap()
+            .contains("command not found"));
+    }
+
     #[test]
     fn test_run_shell_true_false() {
         let mut env = ShellEnv::new();
 
-        let result = futures_lite::future::block_on(run_shell("true", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline("true", &mut env));
         assert_eq!(result.code, 0);
 
-        let result = futures_lite::future::block_on(run_shell("false", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline("false", &mut env));
         assert_eq!(result.code, 1);
 
-        let result = futures_lite::future::block_on(run_shell(":", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline(":", &mut env));
+        assert_eq!(result.code, 0);
+    }
+
+    #[test]
+    fn test_time_reports_real_and_preserves_exit_code() {
+        let mut env = ShellEnv::new();
+        let result = futures_lite::future::block_on(run_pipeline("time sleep 0", &mut env));
+        assert_eq!(result.code, 0);
+        assert!(
+            result.stderr.contains("\nreal\t0m0."),
+            "stderr: {}",
+            result.stderr
+        );
+
+        let result = futures_lite::future::block_on(run_pipeline("time -p echo hi", &mut env));
         assert_eq!(result.code, 0);
+        assert_eq!(result.stdout.trim(), "hi");
+        assert!(
+            result.stderr.contains("real 0."),
+            "stderr: {}",
+            result.stderr
+        );
+        assert!(result.stderr.contains("user 0.00\nsys 0.00\n"));
+
+        let result = futures_lite::future::block_on(run_pipeline("time false", &mut env));
+        assert_eq!(result.code, 1);
+        assert!(result.stderr.contains("real"), "stderr: {}", result.stderr);
     }
 
     #[test]
     fn test_brace_in_pipeline() {
         let mut env = ShellEnv::new();
-        let result = futures_lite::future::block_on(run_shell("echo hello | { cat; }", &mut env));
+        let result =
+            futures_lite::future::block_on(run_pipeline("echo hello | { cat; }", &mut env));
         assert_eq!(result.code, 0);
         assert_eq!(result.stdout.trim(), "hello");
     }
@@ -2138,9 +3601,107 @@ mod tests {
     fn test_function_with_stdin() {
         let mut env = ShellEnv::new();
         // Define function with POSIX syntax (semicolon before })
-        futures_lite::future::block_on(run_shell("upper() { tr 'a-z' 'A-Z'; }", &mut env));
-        let result = futures_lite::future::block_on(run_shell("echo hello | upper", &mut env));
+        futures_lite::future::block_on(run_pipeline("upper() { tr 'a-z' 'A-Z'; }", &mut env));
+        let result = futures_lite::future::block_on(run_pipeline("echo hello | upper", &mut env));
         assert_eq!(result.code, 0);
         assert_eq!(result.stdout.trim(), "HELLO");
     }
+
+    #[test]
+    fn test_heredoc_to_expandable() {
+        assert_eq!(heredoc_to_expandable("a `echo b` c"), "a $(echo b) c");
+        assert_eq!(
+            heredoc_to_expandable("say \\\"hi\\\""),
+            "say \\\\\"hi\\\\\""
+        );
+        assert_eq!(heredoc_to_expandable("one \\\ntwo"), "one two");
+        assert_eq!(heredoc_to_expandable("\\$HOME"), "\\$HOME");
+    }
+
+    #[test]
+    fn test_read_line_and_field_splitting() {
+        let split = |input: &str, ifs: &str, count: usize| {
+            let (line, _, _) = read_input_line(input.as_bytes(), false, None);
+            split_read_fields(&line, ifs, count)
+        };
+        assert_eq!(
+            split("  one two  three four  \n", " \t\n", 3),
+            vec!["one", "two", "three four"]
+        );
+        assert_eq!(split("a", " \t\n", 3), vec!["a", "", ""]);
+        assert_eq!(split("a::b", ":", 3), vec!["a", "", "b"]);
+        assert_eq!(split("a : b:c", " :", 2), vec!["a", "b:c"]);
+        assert_eq!(split("a\\ b c", " ", 2), vec!["a b", "c"]);
+
+        // Backslash-newline continues the line unless -r
+        let (line, used, complete) = read_input_line(b"ab\\\ncd\nrest", false, None);
+        assert_eq!(line.len(), 4);
+        assert_eq!((used, complete), (7, true));
+        let (line, used, _) = read_input_line(b"ab\\\ncd\nrest", true, None);
+        assert_eq!((line.len(), used), (3, 4));
+
+        assert_eq!(read_input_line(b"hello", false, Some(3)).1, 3);
+        assert!(!read_input_line(b"hi", false, Some(3)).2);
+        assert!(!read_input_line(b"", false, None).2);
+    }
+
+    #[test]
+    fn test_shared_stdin_hands_out_the_rest_without_copying() {
+        let mut env = ShellEnv::new();
+        let mut input = SharedStdin::new(Some(b"one\ntwo\n".to_vec().into()));
+
+        let first = input.next(&mut env).unwrap();
+        assert_eq!(&first[..], b"one\ntwo\n");
+        env.stdin_consumed = 4; // as `read` would
+        input.advance(&mut env);
+
+        let second = input.next(&mut env).unwrap();
+        assert_eq!(&second[..], b"two\n");
+        assert!(Rc::ptr_eq(&first.buf, &second.buf));
+    }
+
+    #[test]
+    fn test_while_read_consumes_large_input() {
+        let mut env = ShellEnv::new();
+        let result = futures_lite::future::block_on(run_pipeline(
+            "seq 20000 | { n=0; while read l; do n=$((n + l)); done; echo $n; }",
+            &mut env,
+        ));
+        assert_eq!(result.code, 0, "{}", result.stderr);
+        assert_eq!(result.stdout, "200010000\n");
+    }
+
+    #[test]
+    fn test_trap_builtin_sets_lists_and_resets() {
+        let mut env = ShellEnv::new();
+        env.is_interactive = true; // keep the EXIT trap from firing
+        let result = futures_lite::future::block_on(run_pipeline(
+            "trap 'echo it'\\''s done' EXIT; trap 'echo int' SIGINT 15; trap -p",
+            &mut env,
+        ));
+        assert_eq!(result.code, 0, "{}", result.stderr);
+        assert_eq!(
+            result.stdout,
+            "trap -- 'echo it'\\''s done' EXIT\ntrap -- 'echo int' INT\ntrap -- 'echo int' TERM\n"
+        );
+
+        let result =
+            futures_lite::future::block_on(run_pipeline("trap - EXIT; trap TERM; trap", &mut env));
+        assert_eq!(result.stdout, "trap -- 'echo int' INT\n");
+
+        let result = futures_lite::future::block_on(run_pipeline("trap 'echo x' BOGUS", &mut env));
+        assert_eq!(result.code, 1);
+        assert!(result
+            .stderr
+            .contains("BOGUS: invalid signal specification"));
+
+        let trap = futures_lite::future::block_on(run_trap("INT", &mut env)).unwrap();
+        assert_eq!(trap.stdout, "int\n");
+
+        let result = futures_lite::future::block_on(run_pipeline("trap -l", &mut env));
+        assert_eq!(
+            result.stdout,
+            " 1) SIGHUP\n 2) SIGINT\n 3) SIGQUIT\n15) SIGTERM\n"
+        );
+    }
 }
@@ -37,6 +37,13 @@ pub enum ParsedCommand {
         condition: Vec<ParsedCommand>,
         body: Vec<ParsedCommand>,
     },
+    /// Arithmetic for loop: for ((init; condition; update))
+    ArithFor {
+        init: Option<String>,
+        condition: Option<String>,
+        update: Option<String>,
+        body: Vec<ParsedCommand>,
+    },
     /// If/then/else
     If {
         conditionals: Vec<(Vec<ParsedCommand>, Vec<ParsedCommand>)>,
@@ -58,8 +65,12 @@ pub enum ParsedCommand {
     },
     /// Background job (cmd &)
     Background(Box<ParsedCommand>),
-    /// Timed command (time cmd)
-    Timed(Box<ParsedCommand>),
+    /// Timed command (time [-p] cmd)
+    Timed {
+        command: Box<ParsedCommand>,
+        /// `time -p`: POSIX `real`/`user`/`sys` output
+        posix: bool,
+    },
 }
 
 /// A parsed I/O redirection.
@@ -72,9 +83,13 @@ pub enum ParsedRedirect {
     Write { fd: Option<u32>, target: String },
     /// stdout to file (append)
     Append { fd: Option<u32>, target: String },
-    /// Here-document
-    Heredoc { fd: Option<u32>, content: String },
-    /// Here-string
+    /// Here-document; `expand` is false when the delimiter was quoted
+    Heredoc {
+        fd: Option<u32>,
+        content: String,
+        expand: bool,
+    },
+    /// Here-string (content still to be expanded)
     HereString { fd: Option<u32>, content: String },
     /// Duplicate fd (e.g., 2>&1)
     DupWrite { fd: Option<u32>, target: String },
@@ -202,6 +217,8 @@ fn format_parameter_expansion(expr: &brush_parser::word::ParameterExpr) -> Strin
                 // For complex ones, use ${VAR} format
                 match parameter {
                     Parameter::Named(name) => format!("${}", name),
+                    // $10 would read as $1 followed by "0"
+                    Parameter::Positional(n) if *n > 9 => format!("${{{}}}", n),
                     Parameter::Positional(n) => format!("${}", n),
                     Parameter::Special(sp) => {
                         // Special params ($?, $$, $#, etc.) must use $X format
@@ -536,6 +553,10 @@ fn convert_and_or_list(list: ast::AndOrList) -> Option<ParsedCommand> {
 /// Convert a Pipeline to ParsedCommand.
 fn convert_pipeline(pipeline: ast::Pipeline) -> Option<ParsedCommand> {
     let timed = pipeline.timed.is_some();
+    let posix = matches!(
+        pipeline.timed,
+        Some(ast::PipelineTimed::TimedWithPosixOutput { .. })
+    );
     let commands: Vec<ParsedCommand> = pipeline
         .seq
         .into_iter()
@@ -556,7 +577,10 @@ fn convert_pipeline(pipeline: ast::Pipeline) -> Option<ParsedCommand> {
     };
 
     if timed {
-        Some(ParsedCommand::Timed(Box::new(result)))
+        Some(ParsedCommand::Timed {
+            command: Box::new(result),
+            posix,
+        })
     } else {
         Some(result)
     }
@@ -733,7 +757,8 @@ fn convert_compound_command(cmd: ast::CompoundCommand) -> Option<ParsedCommand>
             let words = for_clause
                 .values
                 .map(|ws| ws.into_iter().map(|w| word_to_string(&w)).collect())
-                .unwrap_or_default();
+                // `for x; do` iterates over the positional parameters
+                .unwrap_or_else(|| vec!["$@".to_string()]);
             let body = convert_do_group(&for_clause.body);
             Some(ParsedCommand::For {
                 var: for_clause.variable_name,
@@ -812,13 +837,10 @@ fn convert_compound_command(cmd: ast::CompoundCommand) -> Option<ParsedCommand>
         }),
         ast::CompoundCommand::ArithmeticForClause(arith_for) => {
             let body = convert_do_group(&arith_for.body);
-            Some(ParsedCommand::While {
-                condition: vec![ParsedCommand::Simple {
-                    name: "true".to_string(),
-                    args: vec![],
-                    redirects: vec![],
-                    env_vars: vec![],
-                }],
+            Some(ParsedCommand::ArithFor {
+                init: arith_for.initializer.map(|expr| expr.value),
+                condition: arith_for.condition.map(|expr| expr.value),
+                update: arith_for.updater.map(|expr| expr.value),
                 body,
             })
         }
@@ -875,16 +897,25 @@ fn convert_io_redirect(redir: ast::IoRedirect) -> Option<ParsedRedirect> {
         }
         ast::IoRedirect::HereDocument(fd, doc) => {
             let fd_num = fd.map(|f| f as u32);
+            let mut content = doc.doc.value.clone();
+            if doc.remove_tabs {
+                // <<- strips leading tabs from every body line
+                content = content
+                    .split_inclusive('\n')
+                    .map(|line| line.trim_start_matches('\t'))
+                    .collect();
+            }
             Some(ParsedRedirect::Heredoc {
                 fd: fd_num,
-                content: format!("{}", doc.doc),
+                content,
+                expand: doc.requires_expansion,
             })
         }
         ast::IoRedirect::HereString(fd, word) => {
             let fd_num = fd.map(|f| f as u32);
             Some(ParsedRedirect::HereString {
                 fd: fd_num,
-                content: format!("{}", word),
+                content: word_to_string(word),
             })
         }
         ast::IoRedirect::OutputAndError(word, _append) => {
@@ -951,6 +982,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_arithmetic_for_loop() {
+        let cmds = parse_command("for ((i = 0; i < 3; i++)); do echo $i; done").unwrap();
+        match &cmds[0] {
+            ParsedCommand::ArithFor {
+                init,
+                condition,
+                update,
+                body,
+            } => {
+                assert_eq!(init.as_deref().map(str::trim), Some("i = 0"));
+                assert_eq!(condition.as_deref().map(str::trim), Some("i < 3"));
+                assert_eq!(update.as_deref().map(str::trim), Some("i++"));
+                assert_eq!(body.len(), 1);
+            }
+            _ => panic!("Expected arithmetic for loop"),
+        }
+    }
+
     #[test]
     fn test_parse_if_statement() {
         let cmds = parse_command("if true; then echo yes; else echo no; fi").unwrap();
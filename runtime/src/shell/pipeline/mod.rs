@@ -4,6 +4,7 @@
 //! - Pipelines with | operator
 //! - Logical chaining with &&, ||, ;
 //! - I/O redirection: >, >>, <, 2>, 2>&1
+//! - Here-documents (<<EOF, <<-EOF, <<'EOF') and here-strings (<<<)
 //! - Variable expansion: $VAR, ${VAR}, ${VAR:-default}
 //! - Command substitution: $(cmd), `cmd`
 //! - Arithmetic expansion: $((expr))
@@ -12,6 +13,11 @@
 //! - Glob expansion: *, ?
 
 use super::env::{ShellEnv, ShellResult};
+use futures_lite::future::zip;
+use futures_lite::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Capacity of the pipes collecting [`run_pipeline`] output
+const PIPE_CAPACITY: usize = 65536;
 
 /// Maximum pipeline depth (for nested subshells/substitutions)
 const MAX_SUBSHELL_DEPTH: usize = 16;
@@ -98,21 +104,53 @@ pub fn normalize_path(path: &str) -> String {
 /// - Command substitution: $(cmd), `cmd`
 /// - Chaining operators: &&, ||, ;
 /// - Pipelines: cmd1 | cmd2 | cmd3
+///
+/// Output is collected in memory; use [`run_pipeline_streaming`] for
+/// output too large to buffer.
 pub async fn run_pipeline(cmd_line: &str, env: &mut ShellEnv) -> ShellResult {
-    let cmd_line = cmd_line.trim();
-
-    if cmd_line.is_empty() {
-        return ShellResult::success("");
+    let (stdout_reader, stdout_writer) = piper::pipe(PIPE_CAPACITY);
+    let (stderr_reader, stderr_writer) = piper::pipe(PIPE_CAPACITY);
+
+    let (code, (stdout, stderr)) = zip(
+        run_pipeline_streaming(cmd_line, env, stdout_writer, stderr_writer),
+        zip(read_to_string(stdout_reader), read_to_string(stderr_reader)),
+    )
+    .await;
+
+    ShellResult {
+        stdout,
+        stderr,
+        code,
     }
+}
 
+/// Run a shell pipeline like [`run_pipeline`], writing stdout and stderr to
+/// the given pipes as they are produced, and return the exit code.
+///
+/// The writers are dropped when the command line finishes, closing the
+/// pipes; callers must drain the readers concurrently.
+pub async fn run_pipeline_streaming(
+    cmd_line: &str,
+    env: &mut ShellEnv,
+    stdout: piper::Writer,
+    mut stderr: piper::Writer,
+) -> i32 {
     // Check subshell depth limit
     if env.subshell_depth > MAX_SUBSHELL_DEPTH {
-        return ShellResult::error("maximum subshell depth exceeded", 1);
+        let _ = stderr.write_all(b"maximum subshell depth exceeded").await;
+        return 1;
     }
 
     // Use the new executor which handles brush-parser exclusively
     // with proper stdin/stdout threading through pipelines
-    super::new_executor::run_shell(cmd_line, env).await
+    super::new_executor::run_shell_streaming(cmd_line, env, stdout, stderr).await
+}
+
+/// Read a pipe to the end as (lossy) UTF-8
+async fn read_to_string(mut reader: piper::Reader) -> String {
+    let mut buffer = Vec::new();
+    let _ = reader.read_to_end(&mut buffer).await;
+    String::from_utf8_lossy(&buffer).into_owned()
 }
 
 #[cfg(test)]
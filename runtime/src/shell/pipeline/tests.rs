@@ -406,6 +406,74 @@ fn test_set_xtrace() {
     assert!(env.options.xtrace);
 }
 
+#[test]
+fn test_set_errexit_stops_after_failing_command() {
+    let mut env = ShellEnv::new();
+    let result =
+        futures_lite::future::block_on(run_pipeline("set -e; echo one; false; echo two", &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "one\n");
+
+    // Tested commands don't trigger it; `set +e` turns it off
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "set -e; if false; then echo no; fi; false || echo handled; ! true; set +e; false; echo end",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "handled\nend\n");
+
+    // A `!` pipeline is exempt too, even when it holds a single failing command
+    let mut env = ShellEnv::new();
+    let result =
+        futures_lite::future::block_on(run_pipeline("set -e; ! false; echo after", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "after\n");
+}
+
+#[test]
+fn test_set_nounset_errors_on_unset_variable() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "set -u; echo \"a$UNSET\"; echo after",
+        &mut env,
+    ));
+    assert!(result.stderr.contains("UNSET: unbound variable"));
+    assert_eq!(result.stdout, "after\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("set +u; echo \"a$UNSET\"", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "a\n");
+}
+
+#[test]
+fn test_set_xtrace_prints_expanded_commands() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "set -x; X=1; echo $X 'a b' | cat; set +x; echo quiet",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "1 a b\nquiet\n");
+    assert_eq!(result.stderr, "+ X=1\n+ echo 1 'a b'\n+ cat\n+ set +x\n");
+}
+
+#[test]
+fn test_exit_skips_rest_of_script() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo one; f() { exit 3; }; f; echo two",
+        &mut env,
+    ));
+    assert_eq!(result.code, 3);
+    assert_eq!(result.stdout, "one\n");
+
+    // An exit inside a subshell only ends the subshell
+    let mut env = ShellEnv::new();
+    let result =
+        futures_lite::future::block_on(run_pipeline("(exit 4); echo \"after $?\"", &mut env));
+    assert_eq!(result.stdout, "after 4\n");
+}
+
 #[test]
 fn test_set_pipefail() {
     let mut env = ShellEnv::new();
@@ -604,6 +672,49 @@ fn test_unalias() {
     assert!(!env.aliases.contains_key("ll"));
 }
 
+#[test]
+fn test_type_builtin() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline("type cd", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "cd is a shell builtin\n");
+}
+
+#[test]
+fn test_type_alias_and_command() {
+    let mut env = ShellEnv::new();
+    env.aliases.insert("ll".to_string(), "ls -la".to_string());
+    let result = futures_lite::future::block_on(run_pipeline("type ll ls", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(
+        result.stdout,
+        "ll is an alias for ls -la\nls is /usr/bin/ls\n"
+    );
+}
+
+#[test]
+fn test_type_unknown() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline("type cd nosuchcmd", &mut env));
+    assert_eq!(result.code, 1);
+    // Names that resolve are still reported
+    assert_eq!(result.stdout, "cd is a shell builtin\n");
+    assert_eq!(result.stderr, "nosuchcmd: not found\n");
+}
+
+#[test]
+fn test_which_reports_paths_only() {
+    let mut env = ShellEnv::new();
+    env.aliases.insert("ll".to_string(), "ls -la".to_string());
+    let result = futures_lite::future::block_on(run_pipeline("which ls", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "/usr/bin/ls\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("which ll", &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stderr, "ll: not found\n");
+}
+
 #[test]
 fn test_getopts_basic() {
     let mut env = ShellEnv::new();
@@ -670,6 +781,22 @@ fn test_printf_command() {
     assert_eq!(result.stdout, "Hello world!");
 }
 
+#[test]
+fn test_printf_reuses_format_for_extra_args() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf '%-4s%3d\\n' a 1 bb 22 | cat",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "a     1\nbb   22\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("printf '%d' nope", &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "0");
+    assert!(result.stderr.contains("expected a numeric value"));
+}
+
 #[test]
 fn test_base64_encode() {
     let mut env = ShellEnv::new();
@@ -767,6 +894,94 @@ fn test_return_from_function() {
     assert_eq!(result.code, 42);
 }
 
+#[test]
+fn test_function_positional_params_return_and_pipelines() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("shell_functions");
+    env.cwd = std::path::PathBuf::from(&root);
+
+    let script = r#"greet() { echo "hi $1   ($# args: $@)"; }
+function check {
+    if [ -z "$1" ]; then
+        echo "usage: check NAME"
+        return 3
+    fi
+    echo "checking $1" > checked.txt
+    return
+    echo unreachable
+}
+set -- outer
+greet world and more
+check; echo "status $?"
+check file; echo "status $?"
+greet piped | tr a-z A-Z
+echo "after $1""#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "hi world   (3 args: world and more)\nusage: check NAME\nstatus 3\nstatus 0\nHI PIPED   (1 ARGS: PIPED)\nafter outer\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(format!("{root}/checked.txt")).unwrap(),
+        "checking file\n"
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_source_defines_in_current_env() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("shell_source");
+    env.cwd = std::path::PathBuf::from(&root);
+    std::fs::write(
+        format!("{root}/lib.sh"),
+        "GREETING=\"hello $1\"\ngreet() { echo \"$GREETING, $1\"; }\nalias ll='ls -la'\n",
+    )
+    .unwrap();
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("set -- outer; source lib.sh world", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(env.get_var("GREETING").unwrap(), "hello world");
+    assert_eq!(env.aliases.get("ll").unwrap(), "ls -la");
+    // Positional parameters are restored once the script returns
+    assert_eq!(env.positional_params, vec!["outer".to_string()]);
+
+    let result = futures_lite::future::block_on(run_pipeline("greet you", &mut env));
+    assert_eq!(result.stdout, "hello world, you\n");
+
+    // Without extra args `.` sees the caller's positional parameters
+    let result = futures_lite::future::block_on(run_pipeline(". ./lib.sh; greet again", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "hello outer, again\n");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_source_errors() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("shell_source_errors");
+    env.cwd = std::path::PathBuf::from(&root);
+    std::fs::write(format!("{root}/fail.sh"), "echo one\nfalse\necho two\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline("source missing.sh", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("missing.sh"));
+
+    // A failing line stops the script under `set -e`
+    let result = futures_lite::future::block_on(run_pipeline(
+        "set -e; source fail.sh; echo after",
+        &mut env,
+    ));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "one\n");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
 #[test]
 fn test_return_outside_function() {
     let mut env = ShellEnv::new();
@@ -856,6 +1071,23 @@ fn test_sort_uniq_pipeline() {
     let _ = std::fs::remove_file("/tmp/sortuniq.txt");
 }
 
+#[test]
+fn test_sort_numeric_key_pipeline() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'b 10\\na 9\\nc 100\\n' | sort -rn -k 2",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "c 100\nb 10\na 9\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'b 10\\na 9\\nc 100\\n' | sort -k2,2",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "b 10\nc 100\na 9\n");
+}
+
 #[test]
 fn test_cut_sort_pipeline() {
     let mut env = ShellEnv::new();
@@ -1297,7 +1529,9 @@ fn test_coreutils_help_completeness() {
         "date",
         "printf",
         "base64",
+        "base32",
         "md5sum",
+        "sha1sum",
         "sha256sum",
         "xxd",
     ];
@@ -1446,129 +1680,968 @@ fn test_find_with_name_and_type_filters() {
 }
 
 #[test]
-fn test_diff_equal_and_unified_difference_output() {
+fn test_find_relative_paths_depth_and_exec() {
     let mut env = ShellEnv::new();
-    let root = make_test_dir("coreutils_diff");
-    let left = format!("{root}/left.txt");
-    let right = format!("{root}/right.txt");
+    let root = make_test_dir("coreutils_find_exec");
+    std::fs::create_dir_all(format!("{root}/src/nested")).unwrap();
+    std::fs::write(format!("{root}/top.rs"), "top").unwrap();
+    std::fs::write(format!("{root}/src/lib.rs"), "lib").unwrap();
+    std::fs::write(format!("{root}/src/nested/deep.rs"), "deep").unwrap();
+    std::fs::write(format!("{root}/src/notes.md"), "notes").unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
 
-    std::fs::write(&left, "alpha\nbeta\n").unwrap();
-    std::fs::write(&right, "alpha\nbeta\n").unwrap();
+    let result = futures_lite::future::block_on(run_pipeline("find . -name '*.rs'", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "./src/lib.rs\n./src/nested/deep.rs\n./top.rs\n"
+    );
 
     let result =
-        futures_lite::future::block_on(run_pipeline(&format!("diff {left} {right}"), &mut env));
-    assert_eq!(result.code, 0);
-    assert!(result.stdout.trim().is_empty());
+        futures_lite::future::block_on(run_pipeline("find src -maxdepth 1 -name '*.rs'", &mut env));
+    assert_eq!(result.stdout, "src/lib.rs\n");
 
-    std::fs::write(&right, "alpha\nBETA\n").unwrap();
-    let result =
-        futures_lite::future::block_on(run_pipeline(&format!("diff -u {left} {right}"), &mut env));
+    let result = futures_lite::future::block_on(run_pipeline("find . -type d", &mut env));
+    assert_eq!(result.stdout, ".\n./src\n./src/nested\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "find src -path 'nested/*' -exec cat {} \\;",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "deep");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "find . -type f -name '*.rs' -exec echo found {} +",
+        &mut env,
+    ));
+    assert_eq!(
+        result.stdout,
+        "found ./src/lib.rs ./src/nested/deep.rs ./top.rs\n"
+    );
+
+    let result = futures_lite::future::block_on(run_pipeline("find . -exec echo {}", &mut env));
     assert_eq!(result.code, 1);
-    assert!(result.stdout.contains("--- "));
-    assert!(result.stdout.contains("+++ "));
-    assert!(result.stdout.contains("-beta"));
-    assert!(result.stdout.contains("+BETA"));
+    assert!(
+        result.stderr.contains("missing argument"),
+        "{}",
+        result.stderr
+    );
 
     let _ = std::fs::remove_dir_all(&root);
 }
 
 #[test]
-fn test_file_realpath_du_and_readlink_flags() {
+fn test_find_exec_runs_functions_through_the_shell() {
     let mut env = ShellEnv::new();
-    let root = make_test_dir("coreutils_paths");
+    let root = make_test_dir("coreutils_find_exec_shell");
+    std::fs::write(format!("{root}/a b.txt"), "a").unwrap();
+    std::fs::write(format!("{root}/c.txt"), "c").unwrap();
     env.cwd = std::path::PathBuf::from(&root);
 
-    let file_path = format!("{root}/sample.txt");
-    std::fs::write(&file_path, "hello\nworld\n").unwrap();
-
-    let result = futures_lite::future::block_on(run_pipeline("file sample.txt", &mut env));
-    assert_eq!(result.code, 0);
-    assert!(result.stdout.contains("sample.txt:"));
-    assert!(result.stdout.contains("ASCII text"));
-
-    let result = futures_lite::future::block_on(run_pipeline("realpath sample.txt", &mut env));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), file_path);
+    let script = r#"show() { echo "[$1]"; }
+find . -name '*.txt' -exec show {} \;
+find . -name '*.txt' -exec show {} + | cat
+find . -name 'c*' -exec show {} \; > out.log
+cat out.log
+find . -name '*.txt' -exec false {} +
+echo "batch $?""#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(
+        result.stdout, "[./a b.txt]\n[./c.txt]\n[./a b.txt]\n[./c.txt]\nbatch 1\n",
+        "{}",
+        result.stderr
+    );
+    let _ = std::fs::remove_dir_all(&root);
+}
 
-    let result = futures_lite::future::block_on(run_pipeline("realpath missing.txt", &mut env));
-    assert_eq!(result.code, 1);
-    assert!(result.stderr.contains("No such file or directory"));
+#[test]
+fn test_stat_reports_size_type_and_mtime() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_stat");
+    std::fs::write(format!("{root}/a.txt"), "hello").unwrap();
+    std::fs::write(format!("{root}/empty"), "").unwrap();
+    std::fs::create_dir(format!("{root}/dir")).unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
+    let mtime = std::fs::metadata(format!("{root}/a.txt"))
+        .unwrap()
+        .modified()
+        .unwrap()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
 
-    let result = futures_lite::future::block_on(run_pipeline("du -s .", &mut env));
-    assert_eq!(result.code, 0);
-    assert!(result.stdout.contains(&root));
+    let result = futures_lite::future::block_on(run_pipeline(
+        "stat -c '%n: %s bytes, %F, %Y (100%%)' a.txt empty dir",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    let lines: Vec<&str> = result.stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        format!("a.txt: 5 bytes, regular file, {mtime} (100%)")
+    );
+    assert!(lines[1].starts_with("empty: 0 bytes, regular empty file, "));
+    assert!(lines[2].starts_with("dir: "));
+    assert!(lines[2].contains(", directory, "));
 
-    let result = futures_lite::future::block_on(run_pipeline("readlink -f ./sample.txt", &mut env));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), file_path);
+    let result = futures_lite::future::block_on(run_pipeline("stat -t a.txt", &mut env));
+    assert_eq!(result.stdout, format!("a.txt 5 {mtime} f\n"));
 
-    let result = futures_lite::future::block_on(run_pipeline("readlink sample.txt", &mut env));
+    let result = futures_lite::future::block_on(run_pipeline("stat a.txt", &mut env));
     assert_eq!(result.code, 0);
-    assert!(result.stdout.trim().is_empty());
+    let lines: Vec<&str> = result.stdout.lines().collect();
+    assert_eq!(lines[0], "  File: a.txt");
+    assert!(lines[1].starts_with("  Size: 5 "), "{}", lines[1]);
+    assert!(lines[1].ends_with("Type: regular file"), "{}", lines[1]);
+    // Modify: YYYY-MM-DD HH:MM:SS.NNNNNNNNN +0000
+    let stamp = lines[2].strip_prefix("Modify: ").unwrap();
+    assert_eq!(stamp.len(), 35, "{}", stamp);
+    assert!(stamp.ends_with(" +0000"));
+
+    // A missing file is reported but the others are still shown
+    let result =
+        futures_lite::future::block_on(run_pipeline("stat --format=%s missing a.txt", &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "5\n");
+    assert!(
+        result.stderr.contains("cannot stat 'missing'"),
+        "{}",
+        result.stderr
+    );
 
     let _ = std::fs::remove_dir_all(&root);
 }
 
 #[test]
-fn test_head_tail_sed_cut_tr_and_tee_behaviors() {
+fn test_touch_sets_times() {
     let mut env = ShellEnv::new();
-    let root = make_test_dir("coreutils_text");
-    let lines_file = format!("{root}/lines.txt");
-    let tee_file = format!("{root}/tee.txt");
-    std::fs::write(&lines_file, "l1\nl2\nl3\nl4\n").unwrap();
+    let root = make_test_dir("coreutils_touch_times");
+    std::fs::create_dir(format!("{root}/dir")).unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
+    let mtime = |env: &mut ShellEnv, file: &str| {
+        futures_lite::future::block_on(run_pipeline(&format!("stat -c %Y {file}"), env)).stdout
+    };
 
     let result = futures_lite::future::block_on(run_pipeline(
-        "echo -e 'l1\\nl2\\nl3\\nl4' | head -n 2",
+        "touch a.txt; touch -t 202401021530.45 a.txt dir",
         &mut env,
     ));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.lines().collect::<Vec<_>>(), vec!["l1", "l2"]);
-
-    let result =
-        futures_lite::future::block_on(run_pipeline(&format!("tail -n 2 {lines_file}"), &mut env));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.lines().collect::<Vec<_>>(), vec!["l3", "l4"]);
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(mtime(&mut env, "a.txt"), "1704209445\n");
+    assert_eq!(mtime(&mut env, "dir"), "1704209445\n");
 
     let result = futures_lite::future::block_on(run_pipeline(
-        "echo 'foo foo' | sed 's/foo/bar/g'",
+        "touch -d 2020-05-06T07:08:09Z a.txt b.txt",
         &mut env,
     ));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), "bar bar");
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(mtime(&mut env, "a.txt"), "1588748889\n");
+    assert_eq!(mtime(&mut env, "b.txt"), "1588748889\n");
 
+    // -a leaves the modification time alone
     let result =
-        futures_lite::future::block_on(run_pipeline("echo 'a:b:c' | cut -d: -f2,3", &mut env));
+        futures_lite::future::block_on(run_pipeline("touch -a -t 200001010000 a.txt", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(mtime(&mut env, "a.txt"), "1588748889\n");
+
+    // -c doesn't create missing files
+    let result = futures_lite::future::block_on(run_pipeline("touch -c missing", &mut env));
     assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), "b:c");
+    assert!(!std::path::Path::new(&format!("{root}/missing")).exists());
 
-    let result = futures_lite::future::block_on(run_pipeline("echo 'a:b' | cut -d:", &mut env));
+    let result = futures_lite::future::block_on(run_pipeline("touch -t 2024 a.txt", &mut env));
     assert_eq!(result.code, 1);
-    assert!(result.stderr.contains("must specify a list of fields"));
+    assert!(result.stderr.contains("invalid date format"));
 
-    let result = futures_lite::future::block_on(run_pipeline("echo banana | tr -d an", &mut env));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), "b");
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_ln_symbolic_and_hard_links() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_ln");
+    std::fs::create_dir(format!("{root}/dir")).unwrap();
+    std::fs::write(format!("{root}/a.txt"), "alpha\n").unwrap();
+    std::fs::write(format!("{root}/b.txt"), "beta\n").unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
 
+    // The symlink target is stored as given, not resolved
     let result = futures_lite::future::block_on(run_pipeline(
-        &format!("echo first | tee {tee_file}"),
+        "ln -s a.txt link.txt; readlink link.txt; cat link.txt",
         &mut env,
     ));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), "first");
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "a.txt\nalpha\n");
 
+    // An existing link is only replaced with -f
+    let result = futures_lite::future::block_on(run_pipeline("ln -s b.txt link.txt", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result
+        .stderr
+        .contains("failed to create symbolic link 'link.txt'"));
     let result = futures_lite::future::block_on(run_pipeline(
-        &format!("echo second | tee -a {tee_file}"),
+        "ln -sf b.txt link.txt; readlink link.txt",
         &mut env,
     ));
-    assert_eq!(result.code, 0);
-    assert_eq!(result.stdout.trim(), "second");
-    assert_eq!(
-        std::fs::read_to_string(&tee_file).unwrap(),
-        "first\nsecond\n"
-    );
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "b.txt\n");
 
-    let _ = std::fs::remove_dir_all(&root);
-}
+    // Several targets go into the trailing directory
+    let result = futures_lite::future::block_on(run_pipeline(
+        "ln -s ../a.txt ../b.txt dir/; readlink dir/a.txt; cat dir/b.txt",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "../a.txt\nbeta\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("ln -s a.txt b.txt c.txt", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("target 'c.txt' is not a directory"));
+
+    // A hard link shares content with its target
+    let result = futures_lite::future::block_on(run_pipeline(
+        "ln a.txt hard.txt; echo more >> a.txt; cat hard.txt",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "alpha\nmore\n");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_jq_jsonc() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("json_jq_jsonc");
+    std::fs::write(
+        format!("{root}/tsconfig.json"),
+        "{\n  // compiler settings\n  \"compilerOptions\": {\n    \"strict\": true, /* keep */\n    \"target\": \"es2022\",\n  },\n}\n",
+    )
+    .unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "jq -r .compilerOptions.target tsconfig.json",
+        &mut env,
+    ));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("parse error"));
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "jq -r --jsonc .compilerOptions.target tsconfig.json",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "es2022\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "cat tsconfig.json | yaml from-json --jsonc",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "compilerOptions:\n  strict: true\n  target: es2022\n"
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_jq_filters_and_compact_output() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("json_jq_filters");
+    std::fs::write(
+        format!("{root}/data.json"),
+        r#"{"items": [{"name": "a", "active": true}, {"name": "b", "active": false}]}"#,
+    )
+    .unwrap();
+    env.cwd = std::path::PathBuf::from(&root);
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "jq -c '.items | map(.name)' data.json",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "[\"a\",\"b\"]\n");
+
+    // Each compact line is a separate input to the next jq
+    let result = futures_lite::future::block_on(run_pipeline(
+        "jq -c '.items[]' data.json | jq -r 'select(.active) | .name'",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "a\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("jq '.items[' data.json", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("syntax error"));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_jq_color_output() {
+    let mut env = ShellEnv::new();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo '{\"a\": [1, true, null, \"s\"]}' | jq -C -c .",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "{\x1b[34;1m\"a\"\x1b[0m:[\x1b[0;36m1\x1b[0m,\x1b[0;33mtrue\x1b[0m,\x1b[1;30mnull\x1b[0m,\x1b[0;32m\"s\"\x1b[0m]}\n"
+    );
+
+    // Auto mode colors only when stdout is a terminal, which it never is
+    // here, even in an interactive session
+    env.is_interactive = true;
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo '{\"a\": 1}' | jq -c .; echo 1 | jq --color=auto .",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "{\"a\":1}\n1\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo 1 | jq -C -M .; echo 1 | jq --color=never .; echo 1 | jq --color .",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "1\n1\n\x1b[0;36m1\x1b[0m\n");
+}
+
+#[test]
+fn test_find_and_grep_respect_gitignore() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_gitignore");
+    for dir in [".git", "node_modules/pkg", "src/gen"] {
+        std::fs::create_dir_all(format!("{root}/{dir}")).unwrap();
+    }
+    std::fs::write(
+        format!("{root}/.gitignore"),
+        "node_modules/\n*.log\n!keep.log\n",
+    )
+    .unwrap();
+    std::fs::write(format!("{root}/src/gen/.gitignore"), "*.rs\n").unwrap();
+    for file in [
+        ".git/HEAD",
+        "node_modules/pkg/index.js",
+        "src/main.rs",
+        "src/debug.log",
+        "src/keep.log",
+        "src/gen/out.rs",
+        "src/gen/notes.txt",
+    ] {
+        std::fs::write(format!("{root}/{file}"), "needle\n").unwrap();
+    }
+    env.cwd = std::path::PathBuf::from(&root);
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("find . --gitignore -type f", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "./.gitignore\n./src/gen/.gitignore\n./src/gen/notes.txt\n./src/keep.log\n./src/main.rs\n"
+    );
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("grep -rl --gitignore needle", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "src/gen/notes.txt\nsrc/keep.log\nsrc/main.rs\n"
+    );
+
+    // Without the flag nothing is skipped
+    let result = futures_lite::future::block_on(run_pipeline("grep -rl needle", &mut env));
+    assert!(result.stdout.contains("node_modules/pkg/index.js"));
+    assert!(result.stdout.contains(".git/HEAD"));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_diff_equal_and_unified_difference_output() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_diff");
+    let left = format!("{root}/left.txt");
+    let right = format!("{root}/right.txt");
+
+    std::fs::write(&left, "alpha\nbeta\n").unwrap();
+    std::fs::write(&right, "alpha\nbeta\n").unwrap();
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("diff {left} {right}"), &mut env));
+    assert_eq!(result.code, 0);
+    assert!(result.stdout.trim().is_empty());
+
+    std::fs::write(&right, "alpha\nBETA\n").unwrap();
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("diff -u {left} {right}"), &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stdout.contains("--- "));
+    assert!(result.stdout.contains("+++ "));
+    assert!(result.stdout.contains("-beta"));
+    assert!(result.stdout.contains("+BETA"));
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_file_realpath_du_and_readlink_flags() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_paths");
+    env.cwd = std::path::PathBuf::from(&root);
+
+    let file_path = format!("{root}/sample.txt");
+    std::fs::write(&file_path, "hello\nworld\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline("file sample.txt", &mut env));
+    assert_eq!(result.code, 0);
+    assert!(result.stdout.contains("sample.txt:"));
+    assert!(result.stdout.contains("ASCII text"));
+
+    let result = futures_lite::future::block_on(run_pipeline("realpath sample.txt", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), file_path);
+
+    let result = futures_lite::future::block_on(run_pipeline("realpath missing.txt", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("No such file or directory"));
+
+    let result = futures_lite::future::block_on(run_pipeline("du -s .", &mut env));
+    assert_eq!(result.code, 0);
+    assert!(result.stdout.contains(&root));
+
+    let result = futures_lite::future::block_on(run_pipeline("readlink -f ./sample.txt", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), file_path);
+
+    let result = futures_lite::future::block_on(run_pipeline("readlink sample.txt", &mut env));
+    assert_eq!(result.code, 0);
+    assert!(result.stdout.trim().is_empty());
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_head_tail_sed_cut_tr_and_tee_behaviors() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_text");
+    let lines_file = format!("{root}/lines.txt");
+    let tee_file = format!("{root}/tee.txt");
+    std::fs::write(&lines_file, "l1\nl2\nl3\nl4\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo -e 'l1\\nl2\\nl3\\nl4' | head -n 2",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.lines().collect::<Vec<_>>(), vec!["l1", "l2"]);
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("tail -n 2 {lines_file}"), &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.lines().collect::<Vec<_>>(), vec!["l3", "l4"]);
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo 'foo foo' | sed 's/foo/bar/g'",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), "bar bar");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("echo 'a:b:c' | cut -d: -f2,3", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), "b:c");
+
+    let result = futures_lite::future::block_on(run_pipeline("echo 'a:b' | cut -d:", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("must specify a list of fields"));
+
+    let result = futures_lite::future::block_on(run_pipeline("echo banana | tr -d an", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), "b");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("echo first | tee {tee_file}"),
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), "first");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("echo second | tee -a {tee_file}"),
+        &mut env,
+    ));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout.trim(), "second");
+    assert_eq!(
+        std::fs::read_to_string(&tee_file).unwrap(),
+        "first\nsecond\n"
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_tee_continues_past_unopenable_file() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_tee");
+    let good = format!("{root}/good.txt");
+    let bad = format!("{root}/no/such/dir.txt");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("printf 'a\\nb\\n' | tee {bad} {good} | wc -l"),
+        &mut env,
+    ));
+    assert_eq!(result.stdout.trim(), "2");
+    assert!(
+        result.stderr.contains("no/such/dir.txt"),
+        "{}",
+        result.stderr
+    );
+    assert_eq!(std::fs::read_to_string(&good).unwrap(), "a\nb\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("echo x | tee {bad}"), &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "x\n");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_grep_recursive_ignore_case_line_numbers() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_grep");
+    std::fs::create_dir_all(format!("{root}/sub")).unwrap();
+    std::fs::write(format!("{root}/a.txt"), "Hello\nworld\n").unwrap();
+    std::fs::write(format!("{root}/sub/b.txt"), "nothing\nsay HELLO\n").unwrap();
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("grep -rin hello {root}"), &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        format!("{root}/a.txt:1:Hello\n{root}/sub/b.txt:2:say HELLO\n")
+    );
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("grep -c -v -E 'l+o' {root}/a.txt"),
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "1\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("grep hello {root}"), &mut env));
+    assert_eq!(result.code, 2);
+    assert!(
+        result.stderr.contains("Is a directory"),
+        "{}",
+        result.stderr
+    );
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'x1\\ny\\nx2\\n' | grep -n x",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "1:x1\n3:x2\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("echo abc | grep zzz", &mut env));
+    assert_eq!(result.code, 1);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_sed_expressions_and_ranges() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_sed");
+    let file = format!("{root}/in.txt");
+    std::fs::write(&file, "foo foo\nbar\nbaz\nfoo\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("sed -e 's/foo/x/' -e '2,3d' {file}"),
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "x foo\nx\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline(&format!("sed -n '/^ba/p' {file}"), &mut env));
+    assert_eq!(result.stdout, "bar\nbaz\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("echo a | sed 'y/a/b/'", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(
+        result.stderr.contains("unknown command"),
+        "{}",
+        result.stderr
+    );
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_awk_fields_patterns_and_end() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_awk");
+    let file = format!("{root}/data.csv");
+    std::fs::write(&file, "name,qty\napple,3\npear,5\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("awk -F, -v mult=2 'NR>1 {{ total += $2 * mult; print $1 }} END {{ print total }}' {file}"),
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "apple\npear\n16\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'a 1\\nb 2\\n' | awk 'BEGIN { OFS = \"-\" } { print $1, $2 * 2 } END { print NR }'",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "a-2\nb-4\n2\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("echo x | awk '{ print $1'", &mut env));
+    assert_eq!(result.code, 2);
+    assert!(result.stderr.starts_with("awk:"), "{}", result.stderr);
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_xargs_batching_replacement_and_failures() {
+    let mut env = ShellEnv::new();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'a b c d e' | xargs -n 2 echo",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "a b\nc d\ne\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'x y\\nz\\n' | xargs -I {} echo '<{}>'",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "<x y>\n<z>\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'one two\\0three' | xargs -0 -n 1 echo",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "one two\nthree\n");
+
+    // A failing invocation doesn't stop the rest but sets 123
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf '1\\n2\\n' | xargs -I N test N = 1",
+        &mut env,
+    ));
+    assert_eq!(result.code, 123);
+}
+
+#[test]
+fn test_heredoc_and_herestring_feed_stdin() {
+    let mut env = ShellEnv::new();
+    let _ = env.set_var("NAME", "world");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "cat <<EOF\nhello $NAME\nsum $((1 + 2)) from $(echo sub) and `echo tick`\ncost \\$5\nEOF",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "hello world\nsum 3 from sub and tick\ncost $5\n"
+    );
+
+    // A quoted delimiter keeps the body literal
+    let result = futures_lite::future::block_on(run_pipeline(
+        "cat <<'EOF'\nhello $NAME $(echo no)\nEOF",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "hello $NAME $(echo no)\n");
+
+    // <<- strips leading tabs, including before the delimiter
+    let result =
+        futures_lite::future::block_on(run_pipeline("cat <<-END\n\t\tindented\n\tEND", &mut env));
+    assert_eq!(result.stdout, "indented\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "grep -c an <<EOF\nbanana\napple\nmango\nEOF",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "2\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("tr a-z A-Z <<< \"hi $NAME\"", &mut env));
+    assert_eq!(result.stdout, "HI WORLD\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("cat <<< 'no $NAME'", &mut env));
+    assert_eq!(result.stdout, "no $NAME\n");
+}
+
+#[test]
+fn test_background_jobs_and_wait() {
+    let mut env = ShellEnv::new();
+
+    // The job gets a turn after the command that started it
+    let result = futures_lite::future::block_on(run_pipeline(
+        "sleep 0 && echo bg &\necho fg; wait; echo \"waited $?\"",
+        &mut env,
+    ));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "bg\nfg\nwaited 0\n");
+    assert!(result.stderr.starts_with("[1] "), "{}", result.stderr);
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "false &\nwait $!; echo \"status $?\"; jobs",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "status 1\n");
+
+    let result =
+        futures_lite::future::block_on(run_pipeline("true &\necho $!; jobs -p; wait %1", &mut env));
+    let pids: Vec<&str> = result.stdout.lines().collect();
+    assert_eq!(pids.len(), 2, "{}", result.stdout);
+    assert_eq!(pids[0], pids[1]);
+
+    let result = futures_lite::future::block_on(run_pipeline("wait %9", &mut env));
+    assert_eq!(result.code, 127);
+    assert!(result.stderr.contains("no such job"), "{}", result.stderr);
+
+    // A job started by the last command still runs before the run returns
+    let result = futures_lite::future::block_on(run_pipeline("echo late &", &mut env));
+    assert_eq!(result.stdout, "late\n");
+}
+
+#[test]
+fn test_interactive_background_job_runs_before_next_prompt() {
+    let mut env = ShellEnv::new();
+    env.is_interactive = true;
+
+    // Nothing waits for the job, yet it runs and is reported as done
+    let result = futures_lite::future::block_on(run_pipeline("sleep 0 && echo done &", &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(result.stdout, "done\n");
+    assert!(result.stderr.contains("[1]+ Done"), "{}", result.stderr);
+    assert!(env.jobs.is_empty());
+
+    // Polling with no jobs is a no-op
+    let result = futures_lite::future::block_on(crate::shell::poll_background_jobs(&mut env));
+    assert_eq!(result.stdout, "");
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn test_special_and_positional_parameters() {
+    let mut env = ShellEnv::new();
+    let script = r#"count() { echo "$# args"; }
+set -- "a b" c "" 4 5 6 7 8 9 ten
+count "$@"
+count "$*"
+count $@x
+for w in "$@"; do printf '[%s]' "$w"; done; echo
+echo "${10} of $#"
+false
+echo "failed $?"
+echo "then $?"
+IFS=:
+echo "$*""#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "10 args\n1 args\n10 args\n[a b][c][][4][5][6][7][8][9][ten]\nten of 10\nfailed 1\nthen 0\na b:c::4:5:6:7:8:9:ten\n"
+    );
+}
+
+#[test]
+fn test_retry_exhausted_returns_last_code() {
+    let mut env = ShellEnv::new();
+    let result = futures_lite::future::block_on(run_pipeline("retry -n 2 -d 0 -- false", &mut env));
+    assert_eq!(result.code, 1);
+    assert!(
+        result
+            .stderr
+            .contains("retry: attempt 3/3 failed with exit code 1, giving up"),
+        "stderr: {}",
+        result.stderr
+    );
+
+    let result = futures_lite::future::block_on(run_pipeline("retry -d 0 -- echo ok", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "ok\n");
+    assert!(result.stderr.is_empty(), "stderr: {}", result.stderr);
+}
+
+#[test]
+fn test_retry_runs_functions_and_builtins() {
+    let mut env = ShellEnv::new();
+    let script = r#"greet() { echo "hi $1"; }
+retry -d 0 -- greet 'a b'
+retry -n 1 -d 0 -- cd /no/such/dir
+echo "cd $?""#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.stdout, "hi a b\ncd 1\n");
+    assert!(
+        result
+            .stderr
+            .contains("retry: attempt 2/2 failed with exit code 1, giving up"),
+        "stderr: {}",
+        result.stderr
+    );
+}
+
+#[test]
+fn test_exit_trap_runs_after_script_and_subshell() {
+    let mut env = ShellEnv::new();
+    let script = r#"trap 'echo "cleanup after $?"' EXIT
+echo working
+(trap 'echo subshell done' EXIT; echo in subshell)
+echo back
+false"#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.code, 1);
+    assert_eq!(
+        result.stdout,
+        "working\nin subshell\nsubshell done\nback\ncleanup after 1\n"
+    );
+    // The trap fired once and is gone
+    assert!(env.get_trap("EXIT").is_none());
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "trap 'echo never' EXIT; trap - EXIT; echo done",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "done\n");
+}
+
+#[test]
+fn test_read_builtin_splits_lines_into_variables() {
+    let mut env = ShellEnv::new();
+    let script = r#"printf 'alpha beta gamma delta\nsecond \\line\n' | { read a b c; echo "a=$a b=$b c=$c"; read -r line; echo "next: $line"; read last || echo "eof $?"; }"#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "a=alpha b=beta c=gamma delta\nnext: second \\line\neof 1\n"
+    );
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "printf 'x 1\\ny 2\\n' | while read name num; do echo \"$num:$name\"; done",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "1:x\n2:y\n");
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "echo hello | { read -n 3 -p 'word? ' w; echo \"$w\"; }",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "hel\n");
+    assert_eq!(result.stderr, "word? ");
+}
+
+#[test]
+fn test_arithmetic_for_loops_with_break_and_continue() {
+    let mut env = ShellEnv::new();
+    let script = r#"total=0
+for ((i = 1; i <= 5; i++)); do
+    total=$((total + i))
+    echo "i=$i total=$total"
+done
+echo "after: i=$i"
+for ((n = 0; ; n += 2)); do
+    if ((n == 4)); then continue; fi
+    if ((n > 8)); then break; fi
+    printf '%s ' "$n"
+done
+echo
+for ((x = 0; x < 3; x++)); do
+    for ((y = 0; y < 3; y++)); do
+        if ((y == 1)); then continue 2; fi
+        if ((x == 2)); then break 2; fi
+        echo "$x,$y"
+    done
+done"#;
+    let result = futures_lite::future::block_on(run_pipeline(script, &mut env));
+    assert_eq!(result.code, 0, "{}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "i=1 total=1\ni=2 total=3\ni=3 total=6\ni=4 total=10\ni=5 total=15\nafter: i=6\n0 2 6 8 \n0,0\n1,0\n"
+    );
+}
+
+#[test]
+fn test_run_pipeline_streaming_passes_output_larger_than_limit() {
+    use futures_lite::io::AsyncReadExt;
+
+    let dir = make_test_dir("stream_large");
+    let size = MAX_OUTPUT_SIZE + 1024 * 1024;
+    let line = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcde\n";
+    std::fs::write(
+        format!("{}/big.txt", dir),
+        line.repeat(size / line.len() + 1),
+    )
+    .unwrap();
+    let expected = std::fs::metadata(format!("{}/big.txt", dir)).unwrap().len() as usize;
+
+    let mut env = ShellEnv::new();
+    env.cwd = std::path::PathBuf::from(&dir);
+    let (mut stdout_reader, stdout_writer) = piper::pipe(65536);
+    let (mut stderr_reader, stderr_writer) = piper::pipe(65536);
+
+    // Count bytes as they arrive instead of buffering them
+    let count = async {
+        let mut total = 0;
+        let mut chunk = [0u8; 8192];
+        while let Ok(n) = stdout_reader.read(&mut chunk).await {
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+        total
+    };
+    let mut stderr = String::new();
+    let (code, (total, _)) = futures_lite::future::block_on(futures_lite::future::zip(
+        run_pipeline_streaming("cat big.txt | cat", &mut env, stdout_writer, stderr_writer),
+        futures_lite::future::zip(count, stderr_reader.read_to_string(&mut stderr)),
+    ));
+    assert_eq!(code, 0, "{}", stderr);
+    assert!(total > MAX_OUTPUT_SIZE);
+    assert_eq!(total, expected);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_run_pipeline_streaming_lone_command_streams() {
+    use futures_lite::io::AsyncReadExt;
+
+    // `yes` never finishes by itself, so its first lines only arrive if they
+    // are forwarded as they are written, and it stops once nobody reads
+    let mut env = ShellEnv::new();
+    let (mut stdout_reader, stdout_writer) = piper::pipe(65536);
+    let (mut stderr_reader, stderr_writer) = piper::pipe(65536);
+
+    let first = async move {
+        let mut chunk = [0u8; 64];
+        let n = stdout_reader.read(&mut chunk).await.unwrap();
+        String::from_utf8_lossy(&chunk[..n]).to_string()
+    };
+    let mut stderr = String::new();
+    let (_, (first, _)) = futures_lite::future::block_on(futures_lite::future::zip(
+        run_pipeline_streaming("yes", &mut env, stdout_writer, stderr_writer),
+        futures_lite::future::zip(first, stderr_reader.read_to_string(&mut stderr)),
+    ));
+    assert!(first.starts_with("y\n"), "{:?} {}", first, stderr);
+    assert!(first.lines().all(|line| line == "y"));
+}
 
 #[test]
 fn test_wc_multiple_files_includes_total() {
@@ -1596,6 +2669,46 @@ fn test_wc_multiple_files_includes_total() {
     let _ = std::fs::remove_dir_all(&root);
 }
 
+#[test]
+fn test_wc_default_columns_and_missing_file() {
+    let mut env = ShellEnv::new();
+    let root = make_test_dir("coreutils_wc_default");
+    let file1 = format!("{root}/f1.txt");
+    let file2 = format!("{root}/f2.txt");
+
+    std::fs::write(&file1, "one two\n").unwrap();
+    std::fs::write(&file2, "three\nfour five\n").unwrap();
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        &format!("wc {file1} {file2} {root}/missing.txt"),
+        &mut env,
+    ));
+    assert_eq!(result.code, 1);
+    assert!(result.stderr.contains("missing.txt"));
+
+    let lines: Vec<&str> = result.stdout.lines().collect();
+    assert_eq!(lines[0], format!("      1       2       8 {file1}"));
+    assert_eq!(lines[1], format!("      2       3      16 {file2}"));
+    assert_eq!(lines[2], "      3       5      24 total");
+
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_wc_reads_stdin() {
+    let mut env = ShellEnv::new();
+
+    let result = futures_lite::future::block_on(run_pipeline("printf 'a b\\nc' | wc", &mut env));
+    assert_eq!(result.code, 0);
+    assert_eq!(result.stdout, "      1       3       5\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("echo héllo | wc -m", &mut env));
+    assert_eq!(result.stdout, "6\n");
+
+    let result = futures_lite::future::block_on(run_pipeline("echo héllo | wc -c", &mut env));
+    assert_eq!(result.stdout, "7\n");
+}
+
 #[test]
 fn test_basename_dirname_env_and_printenv() {
     let mut env = ShellEnv::new();
@@ -1709,6 +2822,57 @@ fn test_glob_nullglob() {
     let _ = std::fs::remove_dir_all("/tmp/globtest4");
 }
 
+#[test]
+fn test_glob_bracket_classes() {
+    let mut env = ShellEnv::new();
+    let dir = make_test_dir("glob_brackets");
+    for name in ["a1", "a2", "b1", "bx"] {
+        std::fs::write(format!("{}/{}", dir, name), "").unwrap();
+    }
+    env.cwd = std::path::PathBuf::from(&dir);
+
+    let result = futures_lite::future::block_on(run_pipeline("echo ?[0-9]", &mut env));
+    assert_eq!(result.stdout, format!("{0}/a1 {0}/a2 {0}/b1\n", dir));
+
+    let result = futures_lite::future::block_on(run_pipeline("echo [!a]*", &mut env));
+    assert_eq!(result.stdout, format!("{0}/b1 {0}/bx\n", dir));
+
+    let result = futures_lite::future::block_on(run_pipeline(
+        "for f in a1 bx; do case $f in [!a][a-z]) echo \"$f letter\";; ?[0-9]) echo \"$f digit\";; esac; done",
+        &mut env,
+    ));
+    assert_eq!(result.stdout, "a1 digit\nbx letter\n");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_glob_no_match_modes() {
+    let mut env = ShellEnv::new();
+    let dir = make_test_dir("glob_nomatch");
+    env.cwd = std::path::PathBuf::from(&dir);
+
+    // Default: the pattern stays literal
+    let result = futures_lite::future::block_on(run_pipeline("echo *.tmp", &mut env));
+    assert_eq!(result.stdout, "*.tmp\n");
+
+    // nullglob: the pattern expands to nothing
+    let result =
+        futures_lite::future::block_on(run_pipeline("shopt -s nullglob; echo x *.tmp", &mut env));
+    assert_eq!(result.stdout, "x\n");
+
+    // failglob: the command fails without running, and wins over nullglob
+    let result = futures_lite::future::block_on(run_pipeline(
+        "shopt -s failglob; rm *.tmp && echo removed",
+        &mut env,
+    ));
+    assert_eq!(result.code, 1);
+    assert_eq!(result.stdout, "");
+    assert!(result.stderr.contains("no match: *.tmp"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_glob_dotglob() {
     let mut env = ShellEnv::new();